@@ -0,0 +1,394 @@
+use futures::future::{AbortHandle, Abortable};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Tauri store file and key the frontend saves AI settings under
+const SETTINGS_STORE: &str = "supawatch_store.json";
+const SETTINGS_KEY: &str = "ai_settings";
+
+/// Model and OpenAI-compatible endpoint (OpenAI, Azure, Ollama, LM Studio, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiSettings {
+    pub model: String,
+    /// API root including the version, e.g. `http://localhost:11434/v1`
+    pub base_url: String,
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        Self {
+            model: DEFAULT_MODEL.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl AiSettings {
+    /// Read settings from the Tauri store, falling back to defaults for missing values
+    pub fn load(app_handle: &tauri::AppHandle) -> Self {
+        use tauri_plugin_store::StoreExt;
+
+        let stored = app_handle
+            .store(SETTINGS_STORE)
+            .ok()
+            .and_then(|store| store.get(SETTINGS_KEY))
+            .and_then(|value| serde_json::from_value::<AiSettings>(value).ok())
+            .unwrap_or_default();
+
+        // Blank fields in the settings form mean "use the default"
+        let defaults = Self::default();
+        Self {
+            model: Some(stored.model.trim().to_string()).filter(|m| !m.is_empty()).unwrap_or(defaults.model),
+            base_url: Some(stored.base_url.trim().to_string()).filter(|u| !u.is_empty()).unwrap_or(defaults.base_url),
+        }
+    }
+
+    /// Local endpoints usually don't need a key
+    pub fn is_default_endpoint(&self) -> bool {
+        self.base_url.trim_end_matches('/') == DEFAULT_BASE_URL
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
+    }
+}
+
+fn with_auth(request: reqwest::RequestBuilder, api_key: Option<&str>) -> reqwest::RequestBuilder {
+    match api_key {
+        Some(key) if !key.is_empty() => request.header("Authorization", format!("Bearer {}", key)),
+        _ => request,
+    }
+}
+
+/// Prefix of the error returned when an in-flight AI request is cancelled
+pub const CANCELLED_PREFIX: &str = "CANCELLED:";
+
+/// In-flight AI requests per project, so the UI can cancel them
+#[derive(Default)]
+pub struct AiRequests {
+    /// Project id -> (request id, abort handle)
+    handles: RwLock<HashMap<Uuid, (Uuid, AbortHandle)>>,
+}
+
+impl AiRequests {
+    /// Run `request` until it completes or `cancel` is called for the same project.
+    /// A cancelled request future is dropped, which aborts the underlying HTTP call.
+    /// Starting a new request for a project cancels the previous one.
+    pub async fn run<T, F>(&self, project_id: Uuid, request: F) -> Result<T, String>
+    where
+        F: Future<Output = Result<T, String>>,
+    {
+        let request_id = Uuid::new_v4();
+        let (handle, registration) = AbortHandle::new_pair();
+        if let Some((_, previous)) = self.handles.write().await.insert(project_id, (request_id, handle)) {
+            previous.abort();
+        }
+
+        let result = Abortable::new(request, registration).await;
+
+        // Only remove our own handle; a newer request may have replaced it
+        let mut handles = self.handles.write().await;
+        if handles.get(&project_id).is_some_and(|(id, _)| *id == request_id) {
+            handles.remove(&project_id);
+        }
+
+        result.unwrap_or_else(|_| Err(format!("{}AI conversion cancelled", CANCELLED_PREFIX)))
+    }
+
+    /// Abort the project's in-flight request. Returns false if there was none.
+    pub async fn cancel(&self, project_id: Uuid) -> bool {
+        match self.handles.write().await.remove(&project_id) {
+            Some((_, handle)) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Stream a chat completion, calling `on_delta` with each piece of content as it
+/// arrives. Returns the full response content.
+pub async fn stream_chat_completion(
+    client: &reqwest::Client,
+    settings: &AiSettings,
+    api_key: Option<&str>,
+    messages: serde_json::Value,
+    mut on_delta: impl FnMut(&str),
+) -> Result<String, String> {
+    let mut response = with_auth(client.post(settings.url("chat/completions")), api_key)
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({
+            "model": settings.model,
+            "messages": messages,
+            "stream": true,
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to call OpenAI API: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("OpenAI API error ({}): {}", status, error_text));
+    }
+
+    // Chunks can split multi-byte characters, so only decode complete UTF-8 prefixes
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buffer = String::new();
+    let mut content = String::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read OpenAI response: {}", e))?
+    {
+        pending.extend_from_slice(&chunk);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(e) => e.valid_up_to(),
+        };
+        buffer.push_str(std::str::from_utf8(&pending[..valid]).unwrap_or_default());
+        pending.drain(..valid);
+        for delta in take_sse_deltas(&mut buffer) {
+            on_delta(&delta);
+            content.push_str(&delta);
+        }
+    }
+
+    Ok(content)
+}
+
+/// Check the key and endpoint with a models-list call. Ok(false) means the key was rejected.
+pub async fn validate_key(
+    client: &reqwest::Client,
+    settings: &AiSettings,
+    api_key: Option<&str>,
+) -> Result<bool, String> {
+    let response = with_auth(client.get(settings.url("models")), api_key)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {}: {}", settings.base_url, e))?;
+
+    match response.status().as_u16() {
+        200..=299 => Ok(true),
+        401 | 403 => Ok(false),
+        status => {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(format!("OpenAI API error ({}): {}", status, error_text))
+        }
+    }
+}
+
+/// A statement in AI output that shouldn't be applied without review
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlaggedStatement {
+    pub sql: String,
+    /// 1-based line in the converted SQL
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Converted SQL and the statements in it that were flagged
+#[derive(Debug, Clone, Serialize)]
+pub struct AiConvertResult {
+    pub sql: String,
+    pub flagged: Vec<FlaggedStatement>,
+}
+
+/// Check AI output before it is offered to the user: it must parse, and every statement
+/// must be schema definition (CREATE/ALTER/COMMENT/GRANT/REVOKE) or a read-only query.
+/// Data changes, DROPs and anything else are returned as flagged statements.
+pub fn review_generated_sql(sql: &str) -> Result<Vec<FlaggedStatement>, String> {
+    crate::parsing::parse_schema_sql(&[("ai.sql".to_string(), sql.to_string())])
+        .map_err(|e| format!("AI returned SQL that doesn't parse: {}", e))?;
+
+    let allowed_re = Regex::new(r"(?is)^(?:CREATE|ALTER|COMMENT\s+ON|GRANT|REVOKE|SELECT|WITH|EXPLAIN)\b").unwrap();
+    let dml_re = Regex::new(r"(?is)^(?:INSERT|UPDATE|DELETE|TRUNCATE|MERGE|COPY)\b").unwrap();
+    // Data-modifying CTEs (`WITH x AS (DELETE ...)`) hide DML behind a query
+    let nested_dml_re = Regex::new(r"(?is)\(\s*(?:INSERT|UPDATE|DELETE)\b").unwrap();
+    let destructive = crate::sync::destructive_statements(sql);
+
+    let flagged = crate::parsing::split_statements(sql)
+        .into_iter()
+        .filter_map(|stmt| {
+            let reason = if dml_re.is_match(&stmt.sql) || nested_dml_re.is_match(&stmt.sql) {
+                "Modifies data"
+            } else if stmt.sql.to_uppercase().starts_with("DROP") {
+                "Drops database objects"
+            } else if destructive.contains(&stmt.sql) {
+                "Can lose data"
+            } else if !allowed_re.is_match(&stmt.sql) {
+                "Not a schema definition or read-only statement"
+            } else {
+                return None;
+            };
+            Some(FlaggedStatement {
+                sql: stmt.sql,
+                line: stmt.line,
+                reason: reason.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(flagged)
+}
+
+/// Remove complete `data: {...}` lines from `buffer` and return their content deltas.
+/// A trailing partial line is left in the buffer for the next chunk.
+pub fn take_sse_deltas(buffer: &mut String) -> Vec<String> {
+    let Some(last_newline) = buffer.rfind('\n') else {
+        return Vec::new();
+    };
+    let complete: String = buffer.drain(..=last_newline).collect();
+
+    complete
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("data:"))
+        .map(str::trim)
+        .filter(|data| *data != "[DONE]")
+        .filter_map(|data| serde_json::from_str::<serde_json::Value>(data).ok())
+        .filter_map(|event| event["choices"][0]["delta"]["content"].as_str().map(String::from))
+        .filter(|delta| !delta.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Serve one request with `body` as an SSE stream; the raw request is sent back on the channel
+    fn one_shot_sse_server(body: &'static str) -> (String, std::sync::mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read headers and the JSON body (which ends with the closing brace)
+                while let Ok(n) = stream.read(&mut buf) {
+                    request.extend_from_slice(&buf[..n]);
+                    if n == 0 || request.ends_with(b"}") {
+                        break;
+                    }
+                }
+                tx.send(String::from_utf8_lossy(&request).to_string()).ok();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}/v1", addr), rx)
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_uses_configured_endpoint_and_model() {
+        let (base_url, requests) = one_shot_sse_server(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"SELECT 1\"}}]}\n\ndata: [DONE]\n\n",
+        );
+        let settings = AiSettings {
+            model: "llama3.1:8b".to_string(),
+            base_url: format!("{}/", base_url),
+        };
+
+        let mut deltas = Vec::new();
+        let content = stream_chat_completion(
+            &reqwest::Client::new(),
+            &settings,
+            None,
+            serde_json::json!([{"role": "user", "content": "one"}]),
+            |d| deltas.push(d.to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(content, "SELECT 1");
+        assert_eq!(deltas, vec!["SELECT 1"]);
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /v1/chat/completions HTTP/1.1"), "{}", request);
+        assert!(request.contains(r#""model":"llama3.1:8b""#));
+        assert!(!request.to_lowercase().contains("authorization:"), "no key, no auth header");
+    }
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_drops_request_and_returns_cancelled() {
+        let requests = Arc::new(AiRequests::default());
+        let project_id = Uuid::new_v4();
+        let dropped = Arc::new(AtomicBool::new(false));
+
+        let flag = DropFlag(dropped.clone());
+        let request = async move {
+            let _flag = flag;
+            futures::future::pending::<Result<String, String>>().await
+        };
+
+        let canceller = {
+            let requests = requests.clone();
+            async move {
+                // Let the request register and start waiting first
+                tokio::task::yield_now().await;
+                assert!(requests.cancel(project_id).await);
+            }
+        };
+
+        let (result, _) = tokio::join!(requests.run(project_id, request), canceller);
+        let err = result.unwrap_err();
+        assert!(err.starts_with(CANCELLED_PREFIX), "unexpected error: {}", err);
+        assert!(dropped.load(Ordering::SeqCst), "request future should be dropped");
+        assert!(!requests.cancel(project_id).await, "handle should be cleared");
+    }
+
+    #[test]
+    fn test_review_generated_sql_flags_dml() {
+        let create = r#"CREATE TABLE "public"."notes" ("id" bigint PRIMARY KEY, "body" text);"#;
+        assert!(review_generated_sql(create).unwrap().is_empty());
+
+        let sql = format!("{}\nDELETE FROM users WHERE id = 1;", create);
+        let flagged = review_generated_sql(&sql).unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].sql, "DELETE FROM users WHERE id = 1");
+        assert_eq!(flagged[0].line, 2);
+        assert_eq!(flagged[0].reason, "Modifies data");
+
+        let flagged = review_generated_sql("DROP SCHEMA public CASCADE;").unwrap();
+        assert_eq!(flagged[0].reason, "Drops database objects");
+
+        assert!(review_generated_sql("CREATE TABLE (").is_err());
+    }
+
+    #[test]
+    fn test_take_sse_deltas_keeps_partial_lines() {
+        let mut buffer = String::from(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"SELECT\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\" 1\"}}]}\n\ndata: {\"choi",
+        );
+        assert_eq!(take_sse_deltas(&mut buffer), vec!["SELECT", " 1"]);
+        assert_eq!(buffer, "data: {\"choi");
+
+        buffer.push_str("ces\":[{\"delta\":{}}]}\n\ndata: [DONE]\n\n");
+        assert!(take_sse_deltas(&mut buffer).is_empty());
+        assert!(buffer.is_empty());
+    }
+}
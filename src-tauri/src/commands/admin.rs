@@ -60,6 +60,172 @@ pub const DEFAULT_SIDEBAR_SPEC: &str = r#"{
   ]
 }"#;
 
+/// A problem found by `validate_sidebar_spec`. `path` points into the spec,
+/// e.g. `groups[1].items[0].id`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SpecIssue {
+    /// `invalid_structure`, `duplicate_id`, `dangling_reference` or `invalid_icon`
+    pub code: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl SpecIssue {
+    fn new(code: &str, path: &str, message: String) -> Self {
+        Self {
+            code: code.to_string(),
+            path: path.to_string(),
+            message,
+        }
+    }
+}
+
+/// Check a sidebar spec for problems the sidebar can't render: missing ids,
+/// duplicate group/item ids, actions pointing at items that don't exist and
+/// icon names that aren't lucide-style kebab-case.
+pub fn validate_spec(spec: &serde_json::Value) -> Vec<SpecIssue> {
+    let mut issues = Vec::new();
+    let Some(groups) = spec.get("groups").and_then(|g| g.as_array()) else {
+        issues.push(SpecIssue::new("invalid_structure", "groups", "Spec must have a groups array".to_string()));
+        return issues;
+    };
+
+    let mut group_ids = std::collections::HashSet::new();
+    for (i, group) in groups.iter().enumerate() {
+        let path = format!("groups[{}]", i);
+        let Some(id) = check_id_and_name(group, &path, &mut issues) else {
+            continue;
+        };
+        if !group_ids.insert(id) {
+            issues.push(SpecIssue::new(
+                "duplicate_id",
+                &format!("{}.id", path),
+                format!("Duplicate group id '{}'", id),
+            ));
+        }
+        check_icon(group, &path, &mut issues);
+
+        match group.get("items") {
+            None => {}
+            Some(serde_json::Value::Array(items)) => check_items(items, &format!("{}.items", path), &mut issues),
+            Some(_) => issues.push(SpecIssue::new(
+                "invalid_structure",
+                &format!("{}.items", path),
+                "items must be an array".to_string(),
+            )),
+        }
+        if let Some(template) = group.get("itemTemplate") {
+            check_item(template, &format!("{}.itemTemplate", path), &mut issues);
+        }
+    }
+    issues
+}
+
+/// Items must have unique ids among their siblings
+fn check_items(items: &[serde_json::Value], path: &str, issues: &mut Vec<SpecIssue>) {
+    let mut ids = std::collections::HashSet::new();
+    for (i, item) in items.iter().enumerate() {
+        let item_path = format!("{}[{}]", path, i);
+        if let Some(id) = check_item(item, &item_path, issues) {
+            if !ids.insert(id) {
+                issues.push(SpecIssue::new(
+                    "duplicate_id",
+                    &format!("{}.id", item_path),
+                    format!("Duplicate item id '{}'", id),
+                ));
+            }
+        }
+    }
+}
+
+fn check_item<'a>(item: &'a serde_json::Value, path: &str, issues: &mut Vec<SpecIssue>) -> Option<&'a str> {
+    let id = check_id_and_name(item, path, issues)?;
+    check_icon(item, path, issues);
+
+    let children = item.get("children").and_then(|c| c.as_array());
+    if let Some(children) = children {
+        check_items(children, &format!("{}.children", path), issues);
+    }
+
+    // Actions navigate to a child of the item that declares them
+    let mut references = Vec::new();
+    if let Some(target) = item.pointer("/primaryAction/itemId").and_then(|v| v.as_str()) {
+        references.push(("primaryAction.itemId".to_string(), target));
+    }
+    for (q, query) in item.get("queries").and_then(|q| q.as_array()).into_iter().flatten().enumerate() {
+        for (a, action) in query.get("rowActions").and_then(|r| r.as_array()).into_iter().flatten().enumerate() {
+            if let Some(target) = action.get("itemId").and_then(|v| v.as_str()) {
+                references.push((format!("queries[{}].rowActions[{}].itemId", q, a), target));
+            }
+        }
+    }
+    for (field, target) in references {
+        let exists = children
+            .into_iter()
+            .flatten()
+            .any(|child| child.get("id").and_then(|id| id.as_str()) == Some(target));
+        if !exists {
+            issues.push(SpecIssue::new(
+                "dangling_reference",
+                &format!("{}.{}", path, field),
+                format!("Item '{}' has no child item '{}'", id, target),
+            ));
+        }
+    }
+    Some(id)
+}
+
+fn check_id_and_name<'a>(value: &'a serde_json::Value, path: &str, issues: &mut Vec<SpecIssue>) -> Option<&'a str> {
+    if !value.is_object() {
+        issues.push(SpecIssue::new("invalid_structure", path, "Expected an object".to_string()));
+        return None;
+    }
+    if !value.get("name").is_some_and(|n| n.is_string()) {
+        issues.push(SpecIssue::new(
+            "invalid_structure",
+            &format!("{}.name", path),
+            "name must be a string".to_string(),
+        ));
+    }
+    match value.get("id").and_then(|id| id.as_str()) {
+        Some(id) if !id.trim().is_empty() => Some(id),
+        _ => {
+            issues.push(SpecIssue::new(
+                "invalid_structure",
+                &format!("{}.id", path),
+                "id must be a non-empty string".to_string(),
+            ));
+            None
+        }
+    }
+}
+
+/// Icons are lucide names as used by `DynamicIcon`, e.g. `file-text`
+fn check_icon(value: &serde_json::Value, path: &str, issues: &mut Vec<SpecIssue>) {
+    let Some(icon) = value.get("icon") else {
+        return;
+    };
+    let valid = icon.as_str().is_some_and(|name| {
+        !name.is_empty()
+            && name.split('-').all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            })
+    });
+    if !valid {
+        issues.push(SpecIssue::new(
+            "invalid_icon",
+            &format!("{}.icon", path),
+            format!("Invalid icon {}: use a lucide icon name like 'file-text'", icon),
+        ));
+    }
+}
+
+/// Validate a sidebar spec without writing it. An empty list means the spec is valid.
+#[tauri::command]
+pub async fn validate_sidebar_spec(spec: serde_json::Value) -> Result<Vec<SpecIssue>, String> {
+    Ok(validate_spec(&spec))
+}
+
 /// Check if admin.json exists for a project.
 #[tauri::command]
 pub async fn has_admin_config(
@@ -135,6 +301,15 @@ pub async fn write_sidebar_spec(
         .await
         .map_err(|e| e.to_string())?;
 
+    // Never persist a spec the sidebar can't render
+    let issues = validate_spec(&spec);
+    if !issues.is_empty() {
+        return Err(format!(
+            "INVALID_SPEC:{}",
+            serde_json::to_string(&issues).map_err(|e| e.to_string())?
+        ));
+    }
+
     let local_path = Path::new(&project.local_path);
     let config_path = sync::get_admin_config_write_path(local_path);
 
@@ -224,3 +399,338 @@ pub async fn add_sidebar_group(
     // Write the updated spec
     write_sidebar_spec(app_handle, project_id, spec).await
 }
+
+fn spec_groups_mut(spec: &mut serde_json::Value) -> Result<&mut Vec<serde_json::Value>, String> {
+    spec.get_mut("groups")
+        .and_then(|g| g.as_array_mut())
+        .ok_or_else(|| "Invalid spec: missing groups array".to_string())
+}
+
+fn value_id(value: &serde_json::Value) -> Option<&str> {
+    value.get("id").and_then(|id| id.as_str())
+}
+
+/// Remove a manual item. `group_id` is only needed when several groups have an item with that id.
+pub fn remove_item_from_spec(
+    spec: &mut serde_json::Value,
+    item_id: &str,
+    group_id: Option<&str>,
+) -> Result<(), String> {
+    let groups = spec_groups_mut(spec)?;
+    let mut matches: Vec<(usize, usize)> = Vec::new();
+    for (g, group) in groups.iter().enumerate() {
+        if group_id.is_some_and(|id| value_id(group) != Some(id)) {
+            continue;
+        }
+        if let Some(items) = group.get("items").and_then(|i| i.as_array()) {
+            if let Some(i) = items.iter().position(|item| value_id(item) == Some(item_id)) {
+                matches.push((g, i));
+            }
+        }
+    }
+
+    match matches.as_slice() {
+        [] => Err(format!("Item '{}' not found", item_id)),
+        [(g, i)] => {
+            if let Some(items) = groups[*g].get_mut("items").and_then(|i| i.as_array_mut()) {
+                items.remove(*i);
+            }
+            Ok(())
+        }
+        _ => Err(format!(
+            "Item '{}' exists in several groups; specify the group",
+            item_id
+        )),
+    }
+}
+
+/// Remove a group. Its manual items are appended to `move_items_to` when given,
+/// otherwise they are removed along with the group.
+pub fn remove_group_from_spec(
+    spec: &mut serde_json::Value,
+    group_id: &str,
+    move_items_to: Option<&str>,
+) -> Result<(), String> {
+    if move_items_to == Some(group_id) {
+        return Err("Cannot move items into the group being removed".to_string());
+    }
+
+    let groups = spec_groups_mut(spec)?;
+    let index = groups
+        .iter()
+        .position(|g| value_id(g) == Some(group_id))
+        .ok_or_else(|| format!("Group '{}' not found", group_id))?;
+
+    let orphans = match groups[index].get("items") {
+        Some(serde_json::Value::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    };
+
+    if let Some(target_id) = move_items_to {
+        let target = groups
+            .iter_mut()
+            .find(|g| value_id(g) == Some(target_id))
+            .ok_or_else(|| format!("Group '{}' not found", target_id))?;
+        let items = target
+            .as_object_mut()
+            .ok_or_else(|| "Invalid group structure".to_string())?
+            .entry("items")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+            .as_array_mut()
+            .ok_or_else(|| "Invalid items structure".to_string())?;
+
+        if let Some(clash) = orphans
+            .iter()
+            .filter_map(value_id)
+            .find(|id| items.iter().any(|item| value_id(item) == Some(*id)))
+        {
+            return Err(format!("Group '{}' already has an item '{}'", target_id, clash));
+        }
+        items.extend(orphans);
+    }
+
+    groups.retain(|g| value_id(g) != Some(group_id));
+    Ok(())
+}
+
+/// Reorder the groups, or the items of `group_id`. Listed ids come first in the
+/// given order; anything not listed keeps its relative order after them.
+pub fn reorder_spec(
+    spec: &mut serde_json::Value,
+    ordered_ids: &[String],
+    group_id: Option<&str>,
+) -> Result<(), String> {
+    let groups = spec_groups_mut(spec)?;
+    let entries = match group_id {
+        None => groups,
+        Some(group_id) => groups
+            .iter_mut()
+            .find(|g| value_id(g) == Some(group_id))
+            .ok_or_else(|| format!("Group '{}' not found", group_id))?
+            .get_mut("items")
+            .and_then(|i| i.as_array_mut())
+            .ok_or_else(|| format!("Group '{}' has no items to reorder", group_id))?,
+    };
+
+    if let Some(unknown) = ordered_ids
+        .iter()
+        .find(|id| !entries.iter().any(|e| value_id(e) == Some(id.as_str())))
+    {
+        return Err(format!("Unknown id '{}'", unknown));
+    }
+
+    // Stable sort: position in ordered_ids, unlisted entries last
+    entries.sort_by_key(|entry| {
+        value_id(entry)
+            .and_then(|id| ordered_ids.iter().position(|o| o == id))
+            .unwrap_or(usize::MAX)
+    });
+    Ok(())
+}
+
+/// Remove an item from the sidebar spec.
+#[tauri::command]
+pub async fn remove_sidebar_item(
+    app_handle: AppHandle,
+    project_id: String,
+    item_id: String,
+    group_id: Option<String>,
+) -> Result<(), String> {
+    let mut spec = get_sidebar_spec(app_handle.clone(), project_id.clone()).await?;
+    remove_item_from_spec(&mut spec, &item_id, group_id.as_deref())?;
+    write_sidebar_spec(app_handle, project_id, spec).await
+}
+
+/// Remove a group from the sidebar spec, moving its items to `move_items_to` or dropping them.
+#[tauri::command]
+pub async fn remove_sidebar_group(
+    app_handle: AppHandle,
+    project_id: String,
+    group_id: String,
+    move_items_to: Option<String>,
+) -> Result<(), String> {
+    let mut spec = get_sidebar_spec(app_handle.clone(), project_id.clone()).await?;
+    remove_group_from_spec(&mut spec, &group_id, move_items_to.as_deref())?;
+    write_sidebar_spec(app_handle, project_id, spec).await
+}
+
+/// Reorder the sidebar groups, or the items of one group when `group_id` is given.
+#[tauri::command]
+pub async fn reorder_sidebar(
+    app_handle: AppHandle,
+    project_id: String,
+    ordered_ids: Vec<String>,
+    group_id: Option<String>,
+) -> Result<(), String> {
+    let mut spec = get_sidebar_spec(app_handle.clone(), project_id.clone()).await?;
+    reorder_spec(&mut spec, &ordered_ids, group_id.as_deref())?;
+    write_sidebar_spec(app_handle, project_id, spec).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_and_valid_specs_pass() {
+        let default_spec: serde_json::Value = serde_json::from_str(DEFAULT_SIDEBAR_SPEC).unwrap();
+        assert_eq!(validate_spec(&default_spec), vec![]);
+
+        let spec = serde_json::json!({
+            "groups": [{
+                "id": "admin",
+                "name": "Admin",
+                "icon": "settings",
+                "items": [{
+                    "id": "users",
+                    "name": "Users",
+                    "icon": "contact",
+                    "primaryAction": { "label": "New", "itemId": "create" },
+                    "queries": [{
+                        "source": { "type": "sql", "value": "select * from users" },
+                        "rowActions": [{ "label": "Edit", "itemId": "edit" }]
+                    }],
+                    "children": [
+                        { "id": "create", "name": "Create user", "visible": false },
+                        { "id": "edit", "name": "Edit user", "visible": false }
+                    ]
+                }]
+            }]
+        });
+        assert_eq!(validate_spec(&spec), vec![]);
+    }
+
+    #[test]
+    fn test_duplicate_ids_are_reported() {
+        let spec = serde_json::json!({
+            "groups": [
+                {
+                    "id": "admin",
+                    "name": "Admin",
+                    "items": [
+                        { "id": "users", "name": "Users" },
+                        { "id": "users", "name": "Users again" }
+                    ]
+                },
+                { "id": "admin", "name": "Admin 2" }
+            ]
+        });
+
+        let issues = validate_spec(&spec);
+        assert_eq!(
+            issues,
+            vec![
+                SpecIssue::new("duplicate_id", "groups[0].items[1].id", "Duplicate item id 'users'".to_string()),
+                SpecIssue::new("duplicate_id", "groups[1].id", "Duplicate group id 'admin'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dangling_references_and_icons_are_reported() {
+        let spec = serde_json::json!({
+            "groups": [{
+                "id": "admin",
+                "name": "Admin",
+                "icon": "Settings Icon",
+                "items": [{
+                    "id": "users",
+                    "name": "Users",
+                    "queries": [{ "rowActions": [{ "label": "Edit", "itemId": "edit" }] }]
+                }]
+            }]
+        });
+
+        let codes: Vec<(String, String)> = validate_spec(&spec).into_iter().map(|i| (i.code, i.path)).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("invalid_icon".to_string(), "groups[0].icon".to_string()),
+                (
+                    "dangling_reference".to_string(),
+                    "groups[0].items[0].queries[0].rowActions[0].itemId".to_string()
+                ),
+            ]
+        );
+
+        assert_eq!(validate_spec(&serde_json::json!({}))[0].code, "invalid_structure");
+    }
+
+    fn two_group_spec() -> serde_json::Value {
+        serde_json::json!({
+            "groups": [
+                {
+                    "id": "reports",
+                    "name": "Reports",
+                    "items": [
+                        { "id": "signups", "name": "Signups" },
+                        { "id": "revenue", "name": "Revenue" }
+                    ]
+                },
+                {
+                    "id": "admin",
+                    "name": "Admin",
+                    "items": [{ "id": "users", "name": "Users" }]
+                },
+                { "id": "scripts", "name": "Scripts" }
+            ]
+        })
+    }
+
+    fn ids(values: &serde_json::Value) -> Vec<&str> {
+        values.as_array().unwrap().iter().filter_map(value_id).collect()
+    }
+
+    #[test]
+    fn test_remove_group_rehomes_its_items() {
+        let mut spec = two_group_spec();
+        remove_group_from_spec(&mut spec, "reports", Some("admin")).unwrap();
+        assert_eq!(ids(&spec["groups"]), vec!["admin", "scripts"]);
+        assert_eq!(ids(&spec["groups"][0]["items"]), vec!["users", "signups", "revenue"]);
+        assert_eq!(validate_spec(&spec), vec![]);
+
+        // Without a target the items go with the group; groups without items get an items array
+        let mut spec = two_group_spec();
+        remove_group_from_spec(&mut spec, "admin", Some("scripts")).unwrap();
+        assert_eq!(ids(&spec["groups"][1]["items"]), vec!["users"]);
+        remove_group_from_spec(&mut spec, "reports", None).unwrap();
+        assert_eq!(ids(&spec["groups"]), vec!["scripts"]);
+
+        let mut spec = two_group_spec();
+        assert!(remove_group_from_spec(&mut spec, "missing", None).is_err());
+        assert!(remove_group_from_spec(&mut spec, "admin", Some("admin")).is_err());
+        assert!(remove_group_from_spec(&mut spec, "admin", Some("missing")).is_err());
+        assert_eq!(spec, two_group_spec(), "failed removals leave the spec untouched");
+    }
+
+    #[test]
+    fn test_remove_item() {
+        let mut spec = two_group_spec();
+        remove_item_from_spec(&mut spec, "signups", None).unwrap();
+        assert_eq!(ids(&spec["groups"][0]["items"]), vec!["revenue"]);
+        assert!(remove_item_from_spec(&mut spec, "signups", None).is_err());
+
+        // The same id in two groups needs the group to disambiguate
+        remove_group_from_spec(&mut spec, "scripts", None).unwrap();
+        spec["groups"][1]["items"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({ "id": "revenue", "name": "Revenue" }));
+        assert!(remove_item_from_spec(&mut spec, "revenue", None).is_err());
+        remove_item_from_spec(&mut spec, "revenue", Some("admin")).unwrap();
+        assert_eq!(ids(&spec["groups"][1]["items"]), vec!["users"]);
+    }
+
+    #[test]
+    fn test_reorder_groups_and_items() {
+        let mut spec = two_group_spec();
+        reorder_spec(&mut spec, &["scripts".to_string(), "reports".to_string()], None).unwrap();
+        assert_eq!(ids(&spec["groups"]), vec!["scripts", "reports", "admin"]);
+
+        reorder_spec(&mut spec, &["revenue".to_string(), "signups".to_string()], Some("reports")).unwrap();
+        assert_eq!(ids(&spec["groups"][1]["items"]), vec!["revenue", "signups"]);
+
+        assert!(reorder_spec(&mut spec, &["nope".to_string()], None).is_err());
+        assert!(reorder_spec(&mut spec, &["users".to_string()], Some("scripts")).is_err());
+    }
+}
@@ -26,19 +26,163 @@ pub async fn pick_project_folder(app_handle: AppHandle) -> Result<Option<String>
         .map(|path| path.map(|p| p.to_string()))
 }
 
+/// A file manager invocation. Built separately from spawning so the
+/// per-OS command lines can be tested.
+#[derive(Debug, PartialEq)]
+struct RevealCommand {
+    program: &'static str,
+    args: Vec<String>,
+    /// Fall through to the next candidate unless this exits successfully
+    check_status: bool,
+}
+
+/// Commands that reveal `path` in the system file manager, in order of preference
+#[cfg(target_os = "macos")]
+fn reveal_commands(path: &std::path::Path) -> Vec<RevealCommand> {
+    vec![RevealCommand {
+        program: "open",
+        args: vec!["-R".to_string(), path.to_string_lossy().to_string()],
+        check_status: false,
+    }]
+}
+
+#[cfg(target_os = "windows")]
+fn reveal_commands(path: &std::path::Path) -> Vec<RevealCommand> {
+    // Passed as a separate argument so paths with spaces get quoted
+    vec![RevealCommand {
+        program: "explorer",
+        args: vec!["/select,".to_string(), path.to_string_lossy().to_string()],
+        check_status: false,
+    }]
+}
+
+#[cfg(target_os = "linux")]
+fn reveal_commands(path: &std::path::Path) -> Vec<RevealCommand> {
+    // FileManager1 selects the item, but not every desktop provides it;
+    // otherwise open the containing folder
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(path);
+    vec![
+        RevealCommand {
+            program: "dbus-send",
+            args: vec![
+                "--session".to_string(),
+                "--print-reply".to_string(),
+                "--dest=org.freedesktop.FileManager1".to_string(),
+                "--type=method_call".to_string(),
+                "/org/freedesktop/FileManager1".to_string(),
+                "org.freedesktop.FileManager1.ShowItems".to_string(),
+                format!("array:string:{}", file_uri(path)),
+                "string:".to_string(),
+            ],
+            check_status: true,
+        },
+        RevealCommand {
+            program: "xdg-open",
+            args: vec![parent.to_string_lossy().to_string()],
+            check_status: false,
+        },
+    ]
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn reveal_commands(_path: &std::path::Path) -> Vec<RevealCommand> {
+    Vec::new()
+}
+
+/// `file://` URI with everything but unreserved characters and `/` percent-encoded
+#[cfg(target_os = "linux")]
+fn file_uri(path: &std::path::Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
 #[tauri::command]
 pub async fn reveal_in_finder(path: String) -> Result<(), String> {
+    let commands = reveal_commands(std::path::Path::new(&path));
+    if commands.is_empty() {
+        return Err("Not supported on this OS".to_string());
+    }
+
+    let mut last_error = String::new();
+    for command in commands {
+        let mut process = std::process::Command::new(command.program);
+        process.args(&command.args);
+
+        if command.check_status {
+            match process.output() {
+                Ok(output) if output.status.success() => return Ok(()),
+                Ok(output) => last_error = String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                Err(e) => last_error = e.to_string(),
+            }
+        } else {
+            match process.spawn() {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+    }
+    Err(format!("Failed to reveal in file manager: {}", last_error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
     #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg("-R")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to reveal in finder: {}", e))?;
-        Ok(())
+    #[test]
+    fn test_reveal_uses_open_r_on_macos() {
+        let commands = reveal_commands(Path::new("/Users/me/My Project/supabase"));
+        assert_eq!(
+            commands,
+            vec![RevealCommand {
+                program: "open",
+                args: vec!["-R".to_string(), "/Users/me/My Project/supabase".to_string()],
+                check_status: false,
+            }]
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_reveal_uses_explorer_select_on_windows() {
+        let commands = reveal_commands(Path::new(r"C:\Users\me\My Project\supabase"));
+        assert_eq!(
+            commands,
+            vec![RevealCommand {
+                program: "explorer",
+                args: vec!["/select,".to_string(), r"C:\Users\me\My Project\supabase".to_string()],
+                check_status: false,
+            }]
+        );
     }
-    #[cfg(not(target_os = "macos"))]
-    {
-        Err("Not supported on this OS".to_string())
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_reveal_tries_file_manager_then_xdg_open_on_linux() {
+        let commands = reveal_commands(Path::new("/home/me/My Project/schema #1.sql"));
+        assert_eq!(commands.len(), 2);
+
+        assert_eq!(commands[0].program, "dbus-send");
+        assert!(commands[0].check_status);
+        assert!(commands[0]
+            .args
+            .contains(&"array:string:file:///home/me/My%20Project/schema%20%231.sql".to_string()));
+
+        assert_eq!(
+            commands[1],
+            RevealCommand {
+                program: "xdg-open",
+                args: vec!["/home/me/My Project".to_string()],
+                check_status: false,
+            }
+        );
     }
 }
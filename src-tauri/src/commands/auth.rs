@@ -1,7 +1,8 @@
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
 
-use crate::models::{LogEntry, LogSource};
+use crate::models::{AccountInfo, LogEntry, LogSource};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -34,14 +35,94 @@ pub async fn clear_access_token(app_handle: AppHandle) -> Result<(), String> {
 #[tauri::command]
 pub async fn validate_access_token(app_handle: AppHandle) -> Result<bool, String> {
     let state = app_handle.state::<Arc<AppState>>();
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let token = state.get_access_token().await.ok_or("Access token not configured")?;
+    let api = crate::supabase_api::SupabaseApi::new(token, state.http_client.clone());
 
+    // Only an auth rejection means the token is invalid; network and server
+    // errors are reported as errors so the UI doesn't ask to sign in again
     match api.list_projects().await {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
+        Ok(_) => {
+            *state.token_expired.write().await = false;
+            Ok(true)
+        }
+        Err(e) if e.is_auth_expired() => {
+            state.mark_token_expired().await;
+            Ok(false)
+        }
+        Err(e) => Err(e.to_string()),
     }
 }
 
+// Account commands
+#[tauri::command]
+pub async fn add_account(app_handle: AppHandle, name: String, token: String) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Account name is required".to_string());
+    }
+
+    let state = app_handle.state::<Arc<AppState>>();
+    state.add_account(name.clone(), token).await.map_err(|e| e.to_string())?;
+
+    let log = LogEntry::success(None, LogSource::System, format!("Account '{}' saved", name));
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_accounts(app_handle: AppHandle) -> Result<Vec<AccountInfo>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    Ok(state.list_accounts().await)
+}
+
+/// Switch the default account; `None` goes back to the single access token
+#[tauri::command]
+pub async fn set_active_account(app_handle: AppHandle, name: Option<String>) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    state.set_active_account(name.clone()).await.map_err(|e| e.to_string())?;
+
+    let message = match name {
+        Some(name) => format!("Switched to account '{}'", name),
+        None => "Switched to the default access token".to_string(),
+    };
+    let log = LogEntry::info(None, LogSource::System, message);
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_account(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    state.remove_account(&name).await.map_err(|e| e.to_string())
+}
+
+/// Bind a project to a named account, or unbind it to follow the active account
+#[tauri::command]
+pub async fn set_project_account(
+    app_handle: AppHandle,
+    project_id: String,
+    account: Option<String>,
+) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    if let Some(name) = &account {
+        if !state.list_accounts().await.iter().any(|a| &a.name == name) {
+            return Err(format!("Account not found: {}", name));
+        }
+    }
+
+    let mut project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    project.account = account;
+    project.updated_at = chrono::Utc::now();
+    state.update_project(project).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 // OpenAI API key commands
 #[tauri::command]
 pub async fn set_openai_key(
@@ -69,3 +150,15 @@ pub async fn clear_openai_key(app_handle: AppHandle) -> Result<(), String> {
     let state = app_handle.state::<Arc<AppState>>();
     state.clear_openai_key().await.map_err(|e| e.to_string())
 }
+
+/// Check the stored key (or `key`, before saving it) against the configured endpoint
+#[tauri::command]
+pub async fn validate_openai_key(app_handle: AppHandle, key: Option<String>) -> Result<bool, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let settings = crate::ai::AiSettings::load(&app_handle);
+    let key = match key {
+        Some(key) => Some(key),
+        None => state.get_openai_key().await,
+    };
+    crate::ai::validate_key(&state.http_client, &settings, key.as_deref()).await
+}
@@ -2,8 +2,10 @@ use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 use uuid::Uuid;
 
+use crate::log_stream::{LogFilters, RemoteLogSource};
 use crate::models::LogEntry;
 use crate::state::AppState;
+use crate::supabase_api::{LogQueryFilter, LogSeverity};
 
 #[tauri::command]
 pub async fn get_logs(
@@ -37,6 +39,23 @@ pub async fn clear_logs(
     Ok(())
 }
 
+/// Export the app's log history as JSON lines; returns the number of entries written
+#[tauri::command]
+pub async fn export_logs(
+    app_handle: AppHandle,
+    project_id: Option<String>,
+    out_path: String,
+) -> Result<usize, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+
+    let uuid = match project_id {
+        Some(id) => Some(Uuid::parse_str(&id).map_err(|e| e.to_string())?),
+        None => None,
+    };
+
+    state.export_logs(uuid, std::path::Path::new(&out_path)).await
+}
+
 #[tauri::command]
 pub async fn query_supabase_logs(
     app_handle: AppHandle,
@@ -53,7 +72,7 @@ pub async fn query_supabase_logs(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
     api.query_logs(
         &project_ref,
@@ -71,6 +90,8 @@ pub async fn get_edge_function_logs(
     project_id: String,
     function_name: Option<String>,
     minutes: Option<u32>,
+    severity: Option<LogSeverity>,
+    search: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
@@ -80,9 +101,10 @@ pub async fn get_edge_function_logs(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
-    api.get_edge_function_logs(&project_ref, function_name.as_deref(), minutes.unwrap_or(60))
+    let filter = LogQueryFilter { severity, search };
+    api.get_edge_function_logs(&project_ref, function_name.as_deref(), minutes.unwrap_or(60), &filter)
         .await
         .map_err(|e| e.to_string())
 }
@@ -92,6 +114,8 @@ pub async fn get_postgres_logs(
     app_handle: AppHandle,
     project_id: String,
     minutes: Option<u32>,
+    severity: Option<LogSeverity>,
+    search: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
@@ -101,9 +125,10 @@ pub async fn get_postgres_logs(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
-    api.get_postgres_logs(&project_ref, minutes.unwrap_or(60))
+    let filter = LogQueryFilter { severity, search };
+    api.get_postgres_logs(&project_ref, minutes.unwrap_or(60), &filter)
         .await
         .map_err(|e| e.to_string())
 }
@@ -113,6 +138,8 @@ pub async fn get_auth_logs(
     app_handle: AppHandle,
     project_id: String,
     minutes: Option<u32>,
+    severity: Option<LogSeverity>,
+    search: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
@@ -122,9 +149,49 @@ pub async fn get_auth_logs(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
-    api.get_auth_logs(&project_ref, minutes.unwrap_or(60))
+    let filter = LogQueryFilter { severity, search };
+    api.get_auth_logs(&project_ref, minutes.unwrap_or(60), &filter)
         .await
         .map_err(|e| e.to_string())
 }
+
+/// Start tailing a Supabase log source; new entries arrive as `log-stream` events
+#[tauri::command]
+pub async fn stream_logs(
+    app_handle: AppHandle,
+    project_id: String,
+    source: RemoteLogSource,
+    filters: Option<LogFilters>,
+) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    state
+        .log_streams
+        .start(app_handle.clone(), uuid, project_ref, api, source, filters.unwrap_or_default())
+        .await;
+    Ok(())
+}
+
+/// Stop tailing one source, or all of the project's streams when `source` is omitted
+#[tauri::command]
+pub async fn stop_log_stream(
+    app_handle: AppHandle,
+    project_id: String,
+    source: Option<RemoteLogSource>,
+) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    state.log_streams.stop(uuid, source).await;
+    Ok(())
+}
@@ -2,9 +2,10 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use uuid::Uuid;
 
-use crate::models::{LogEntry, LogSource, Project, RemoteProject, ProjectKeys};
+use crate::file_sink::{FileOperation, FileSink};
+use crate::models::{LogEntry, LogSource, Project, ProjectStatus, PullStatus, RemoteProject, ProjectKeys};
 use crate::state::AppState;
-use crate::supabase_api::Organization;
+use crate::supabase_api::{ManagementApi, Organization};
 use crate::sync;
 
 
@@ -29,6 +30,238 @@ pub async fn list_remote_projects(
         .collect())
 }
 
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// Regions from the Management API, or the built-in list if it can't be fetched
+async fn available_regions(api: &dyn ManagementApi, organization_id: &str) -> Vec<String> {
+    match api.list_available_regions(organization_id).await {
+        Ok(regions) if !regions.is_empty() => regions,
+        _ => crate::supabase_api::DEFAULT_REGIONS.iter().map(|r| r.to_string()).collect(),
+    }
+}
+
+/// The region to create a project in: `DEFAULT_REGION` unless one is requested,
+/// which has to be available to the organization
+async fn resolve_region(api: &dyn ManagementApi, organization_id: &str, region: Option<String>) -> Result<String, String> {
+    let Some(region) = region else {
+        return Ok(DEFAULT_REGION.to_string());
+    };
+    if !available_regions(api, organization_id).await.contains(&region) {
+        return Err(format!("Region '{}' is not available for this organization", region));
+    }
+    Ok(region)
+}
+
+/// The stored password is only revealed to an account that can still access the
+/// project, so it doesn't outlive losing access to it (or signing out)
+async fn check_db_password_access(api: &dyn ManagementApi, project_ref: &str) -> Result<(), String> {
+    let projects = api.list_projects().await.map_err(|e| e.to_string())?;
+    if !projects.iter().any(|p| p.id == project_ref) {
+        return Err("The signed-in account can't access this project, so its database password isn't shown.".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_regions(app_handle: AppHandle, organization_id: String) -> Result<Vec<String>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    Ok(available_regions(&api, &organization_id).await)
+}
+
+/// Database password generated when the remote project was created from Harbor.
+/// Only available for projects created here, to an account that can access the
+/// project (see `check_db_password_access`); each reveal is logged.
+#[tauri::command]
+pub async fn get_project_db_password(app_handle: AppHandle, project_id: String) -> Result<String, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+    state.surface_auth_error(check_db_password_access(&api, &project_ref).await).await?;
+
+    let password = state
+        .get_db_password(&project_ref)
+        .ok_or("No stored database password for this project. Reset it in the Supabase dashboard.")?;
+
+    let log = LogEntry::info(Some(uuid), LogSource::System, "Database password revealed".to_string());
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(password)
+}
+
+/// Warnings to confirm before creating (`link_mode == false`) or linking a project in `local_path`
+#[tauri::command]
+pub async fn check_project_folder(
+    local_path: String,
+    link_mode: bool,
+) -> Result<Vec<sync::PreflightWarning>, String> {
+    Ok(sync::preflight_project_folder(std::path::Path::new(&local_path), link_mode))
+}
+
+/// Pull the schema and edge functions of a linked project, writing through `sink`.
+/// Parts that already succeeded in `previous` are skipped, so this also resumes a
+/// partial pull. A dry run only logs failures.
+#[allow(clippy::too_many_arguments)]
+async fn auto_pull_linked(
+    api: &dyn crate::supabase_api::ManagementApi,
+    project_ref: &str,
+    project_id: Option<Uuid>,
+    local_path: &std::path::Path,
+    generate_typescript: bool,
+    typescript_output_path: Option<&str>,
+    state: &AppState,
+    app_handle: &AppHandle,
+    previous: Option<&PullStatus>,
+    sink: &FileSink,
+) -> PullStatus {
+    let schema = async {
+        // Use the shared fetch_remote_schema_sql (same as pull flow)
+        let (_, remote_schema) = match sync::fetch_remote_schema_sql(api, project_ref, None, None).await {
+            Ok(result) => result,
+            Err(e) => {
+                let log = LogEntry::error(
+                    project_id,
+                    LogSource::System,
+                    format!("Failed to auto-pull schema: {}", e),
+                );
+                state.add_log(log.clone()).await;
+                app_handle.emit("log", &log).ok();
+                return Err(e);
+            }
+        };
+
+        let split_files = crate::generator::split_sql(&remote_schema);
+
+        let message = if sync::detect_project_layout(local_path) == sync::ProjectLayout::Migrations {
+            // Keep a migrations-only project migrations-only: what the remote has beyond
+            // the existing migrations becomes one new migration
+            let migrations_dir = sync::migrations_dir(local_path);
+            match sync::migration_to_remote(&migrations_dir, &remote_schema).await? {
+                None => "Existing migrations already match the remote schema".to_string(),
+                Some(migration) => {
+                    let filename = format!("{}_remote_schema.sql", chrono::Utc::now().format("%Y%m%d%H%M%S"));
+                    sink.write(&migrations_dir.join(&filename), &migration).await?;
+                    format!("Remote changes pulled to supabase/migrations/{}", filename)
+                }
+            }
+        } else {
+            // Write split schema files (same as pull flow)
+            let schemas_dir = local_path.join("supabase").join("schemas");
+            sink.create_dir_all(&schemas_dir).await?;
+
+            let mut written_files: Vec<String> = Vec::new();
+            for (filename, content) in &split_files {
+                sink.write(&schemas_dir.join(filename), content).await?;
+                written_files.push(filename.clone());
+            }
+            format!("Schema pulled to supabase/schemas/ ({})", written_files.join(", "))
+        };
+
+        if !sink.is_dry_run() {
+            let log = LogEntry::success(project_id, LogSource::System, message);
+            state.add_log(log.clone()).await;
+            app_handle.emit("log", &log).ok();
+        }
+
+        // Generate TypeScript types from the split files we already have
+        if generate_typescript {
+            let output_path = sync::get_typescript_output_path(local_path, typescript_output_path);
+            if let Err(e) = sync::generate_typescript_types_from_sql(&split_files, &output_path, sink).await {
+                let log = LogEntry::error(
+                    project_id,
+                    LogSource::System,
+                    format!("Failed to generate TypeScript types: {}", e),
+                );
+                state.add_log(log.clone()).await;
+                app_handle.emit("log", &log).ok();
+            } else if !sink.is_dry_run() {
+                let relative_output = output_path
+                    .strip_prefix(local_path)
+                    .unwrap_or(&output_path)
+                    .to_string_lossy();
+                let log = LogEntry::success(
+                    project_id,
+                    LogSource::System,
+                    format!("TypeScript types generated: {}", relative_output),
+                );
+                state.add_log(log.clone()).await;
+                app_handle.emit("log", &log).ok();
+            }
+        }
+        Ok::<(), String>(())
+    };
+
+    // Auto-pull Edge Functions using shared sync module
+    let events = sync::EventSink::new(app_handle);
+    let functions = sync::pull_edge_functions(api, project_ref, project_id, local_path, state, &events, sink);
+
+    let status = sync::run_pull_steps(previous, schema, functions).await;
+    if !status.is_complete() {
+        let log = LogEntry::warning(
+            project_id,
+            LogSource::System,
+            format!("Auto-pull incomplete ({}). Resume the pull to retry.", status.errors.join("; ")),
+        );
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+    }
+    status
+}
+
+/// Retry the parts of a linked project's auto-pull that failed
+#[tauri::command]
+pub async fn resume_pull(app_handle: AppHandle, project_id: String) -> Result<PullStatus, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let mut project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .clone()
+        .ok_or("Project not linked to Supabase")?;
+
+    let previous = project.last_pull_status.clone().unwrap_or_default();
+    if previous.is_complete() {
+        return Ok(previous);
+    }
+    let _lock = state.try_lock_project(uuid).await?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+    let status = auto_pull_linked(
+        &api,
+        &project_ref,
+        Some(uuid),
+        std::path::Path::new(&project.local_path),
+        project.generate_typescript,
+        project.typescript_output_path.as_deref(),
+        state.inner(),
+        &app_handle,
+        Some(&previous),
+        &FileSink::default(),
+    )
+    .await;
+
+    if status.is_complete() {
+        let log = LogEntry::success(Some(uuid), LogSource::System, "Pull completed".to_string());
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+    }
+
+    if status.schema_pulled && !previous.schema_pulled {
+        project.last_pull_at = status.attempted_at;
+    }
+    project.last_pull_status = Some(status.clone());
+    state.update_project(project).await.map_err(|e| e.to_string())?;
+    Ok(status)
+}
+
 #[tauri::command]
 pub async fn list_organizations(app_handle: AppHandle) -> Result<Vec<Organization>, String> {
     let state = app_handle.state::<Arc<AppState>>();
@@ -38,6 +271,18 @@ pub async fn list_organizations(app_handle: AppHandle) -> Result<Vec<Organizatio
         .map_err(|e| format!("Failed to list organizations: {}", e))
 }
 
+/// A project returned by `create_project`, with the file operations it performed
+#[derive(serde::Serialize)]
+pub struct CreatedProject {
+    #[serde(flatten)]
+    pub project: Project,
+    pub operations: Vec<FileOperation>,
+}
+
+/// Create a project, or link one to an existing Supabase project and pull it.
+/// With `dry_run`, no remote project is created and nothing is written or saved:
+/// the returned project is what would be added, with the file operations that
+/// creating it would perform.
 #[tauri::command]
 pub async fn create_project(
     app_handle: AppHandle,
@@ -48,18 +293,39 @@ pub async fn create_project(
     organization_id: Option<String>,
     generate_typescript: Option<bool>,
     typescript_output_path: Option<String>,
-) -> Result<Project, String> {
+    region: Option<String>,
+    confirmed: Option<bool>,
+    dry_run: Option<bool>,
+) -> Result<CreatedProject, String> {
     let state = app_handle.state::<Arc<AppState>>().inner().clone();
+    let confirmed = confirmed.unwrap_or(false);
+    let sink = FileSink::new(dry_run.unwrap_or(false));
+
+    let warnings = sync::preflight_project_folder(
+        std::path::Path::new(&local_path),
+        supabase_project_ref.is_some(),
+    );
+    if !warnings.is_empty() && !confirmed {
+        return Err(format!(
+            "PREFLIGHT_WARNING:{}",
+            serde_json::to_string(&warnings).map_err(|e| e.to_string())?
+        ));
+    }
+
+    // Set when the linked project was auto-pulled
+    let mut pull_status = None;
 
     let (project_id, project_ref) = if let Some(refer) = supabase_project_ref {
         // Sync/Link Mode
-        let log = LogEntry::info(
-            None, 
-            LogSource::System, 
-            format!("Linking to existing project: {}", refer)
-        );
-        state.add_log(log.clone()).await;
-        app_handle.emit("log", &log).ok();
+        if !sink.is_dry_run() {
+            let log = LogEntry::info(
+                None,
+                LogSource::System,
+                format!("Linking to existing project: {}", refer)
+            );
+            state.add_log(log.clone()).await;
+            app_handle.emit("log", &log).ok();
+        }
 
         // Resolve Project ID (if missing)
         let pid = if let Some(id) = supabase_project_id {
@@ -82,102 +348,45 @@ pub async fn create_project(
         };
 
         // Check if supabase folder already exists — if so, skip syncing entirely
+        // unless the user confirmed overwriting the local schema
         let supabase_dir = std::path::Path::new(&local_path).join("supabase");
-        if supabase_dir.exists() {
-            let log = LogEntry::info(
-                None,
-                LogSource::System,
-                "Existing supabase folder detected — skipping auto-sync. You can pull manually.".to_string(),
-            );
-            state.add_log(log.clone()).await;
-            app_handle.emit("log", &log).ok();
-        } else if state.has_access_token().await {
-            if let Ok(api) = state.get_api_client().await {
+        if supabase_dir.exists() && !confirmed {
+            if !sink.is_dry_run() {
                 let log = LogEntry::info(
                     None,
                     LogSource::System,
-                    format!("Auto-pulling schema for linked project: {}", refer),
+                    "Existing supabase folder detected — skipping auto-sync. You can pull manually.".to_string(),
                 );
                 state.add_log(log.clone()).await;
                 app_handle.emit("log", &log).ok();
-
-                // Use the shared fetch_remote_schema_sql (same as pull flow)
-                match super::sync::fetch_remote_schema_sql(&api, &refer).await {
-                    Ok((_sql, remote_schema)) => {
-                        // Write split schema files (same as pull flow)
-                        let schemas_dir = supabase_dir.join("schemas");
-                        tokio::fs::create_dir_all(&schemas_dir).await.map_err(|e| e.to_string())?;
-
-                        let split_files = crate::generator::split_sql(&remote_schema);
-                        let mut written_files: Vec<String> = Vec::new();
-                        for (filename, content) in &split_files {
-                            let file_path = schemas_dir.join(filename);
-                            tokio::fs::write(&file_path, content)
-                                .await
-                                .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
-                            written_files.push(filename.clone());
-                        }
-
-                        let file_list = written_files.join(", ");
-                        let log = LogEntry::success(
-                            None,
-                            LogSource::System,
-                            format!("Schema pulled to supabase/schemas/ ({})", file_list),
-                        );
-                        state.add_log(log.clone()).await;
-                        app_handle.emit("log", &log).ok();
-
-                        // Generate TypeScript types from the split files we already have
-                        if generate_typescript.unwrap_or(true) {
-                            let project_path = std::path::Path::new(&local_path);
-                            let output_path = sync::get_typescript_output_path(
-                                project_path,
-                                typescript_output_path.as_deref(),
-                            );
-                            if let Err(e) = sync::generate_typescript_types_from_sql(&split_files, &output_path).await {
-                                let log = LogEntry::error(
-                                    None,
-                                    LogSource::System,
-                                    format!("Failed to generate TypeScript types: {}", e),
-                                );
-                                state.add_log(log.clone()).await;
-                                app_handle.emit("log", &log).ok();
-                            } else {
-                                let relative_output = output_path
-                                    .strip_prefix(project_path)
-                                    .unwrap_or(&output_path)
-                                    .to_string_lossy();
-                                let log = LogEntry::success(
-                                    None,
-                                    LogSource::System,
-                                    format!("TypeScript types generated: {}", relative_output),
-                                );
-                                state.add_log(log.clone()).await;
-                                app_handle.emit("log", &log).ok();
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let log = LogEntry::error(
-                            None,
-                            LogSource::System,
-                            format!("Failed to auto-pull schema: {}", e),
-                        );
-                        state.add_log(log.clone()).await;
-                        app_handle.emit("log", &log).ok();
-                    }
+            }
+        } else if state.has_access_token().await {
+            if let Ok(api) = state.get_api_client().await {
+                if !sink.is_dry_run() {
+                    let log = LogEntry::info(
+                        None,
+                        LogSource::System,
+                        format!("Auto-pulling schema for linked project: {}", refer),
+                    );
+                    state.add_log(log.clone()).await;
+                    app_handle.emit("log", &log).ok();
                 }
 
-                // Auto-pull Edge Functions using shared sync module
-                let _ = sync::pull_edge_functions(
-                    &api,
-                    &refer,
-                    None,
-                    std::path::Path::new(&local_path),
-                    state.as_ref(),
-                    &app_handle,
-                )
-                .await;
+                pull_status = Some(
+                    auto_pull_linked(
+                        &api,
+                        &refer,
+                        None,
+                        std::path::Path::new(&local_path),
+                        generate_typescript.unwrap_or(true),
+                        typescript_output_path.as_deref(),
+                        state.as_ref(),
+                        &app_handle,
+                        None,
+                        &sink,
+                    )
+                    .await,
+                );
             }
         }
         
@@ -193,30 +402,26 @@ pub async fn create_project(
             let schema_path = schemas_dir.join("schema.sql");
 
             // Create directories
-            tokio::fs::create_dir_all(&schemas_dir)
-                .await
-                .map_err(|e| format!("Failed to create schemas directory: {}", e))?;
-            tokio::fs::create_dir_all(&functions_dir)
-                .await
-                .map_err(|e| format!("Failed to create functions directory: {}", e))?;
+            sink.create_dir_all(&schemas_dir).await?;
+            sink.create_dir_all(&functions_dir).await?;
 
             // Create placeholder schema.sql
             let placeholder = "-- Supabase schema\n\n-- Add your table definitions and other schema elements here.\n";
-            tokio::fs::write(&schema_path, placeholder)
-                .await
-                .map_err(|e| format!("Failed to create schema.sql: {}", e))?;
+            sink.write(&schema_path, placeholder).await?;
 
-            let log = LogEntry::success(
-                None,
-                LogSource::System,
-                "Created local supabase directory structure".to_string(),
-            );
-            state.add_log(log.clone()).await;
-            app_handle.emit("log", &log).ok();
+            if !sink.is_dry_run() {
+                let log = LogEntry::success(
+                    None,
+                    LogSource::System,
+                    "Created local supabase directory structure".to_string(),
+                );
+                state.add_log(log.clone()).await;
+                app_handle.emit("log", &log).ok();
+            }
         }
 
-       // Try to create remote project if authenticated
-        if state.has_access_token().await {
+       // Try to create remote project if authenticated (never in a dry run)
+        if state.has_access_token().await && !sink.is_dry_run() {
             let api = state.get_api_client().await.map_err(|e| e.to_string())?;
             
             // Get organizations
@@ -228,9 +433,10 @@ pub async fn create_project(
                 orgs.first().ok_or("No organizations found. Please create one in Supabase dashboard.".to_string())?
             };
 
-            // Generate a secure password (using UUID v4 for now as it's random enough)
-            let db_pass = Uuid::new_v4().to_string();
-            let region = "us-east-1"; // Default region
+            let region = resolve_region(&api, &org.id, region).await?;
+
+            // 64 random hex characters; kept in the keyring so it can be retrieved later
+            let db_pass = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
 
             let log = LogEntry::info(
                 None,
@@ -240,8 +446,18 @@ pub async fn create_project(
             state.add_log(log.clone()).await;
             app_handle.emit("log", &log).ok();
 
-            match api.create_project(&name, &org.id, &db_pass, region).await {
+            match api.create_project(&name, &org.id, &db_pass, &region).await {
                 Ok(remote_project) => {
+                    if let Err(e) = state.set_db_password(&remote_project.id, &db_pass) {
+                        let log = LogEntry::warning(
+                            None,
+                            LogSource::System,
+                            format!("Could not store the database password in the keychain: {}", e),
+                        );
+                        state.add_log(log.clone()).await;
+                        app_handle.emit("log", &log).ok();
+                    }
+
                     let log = LogEntry::success(
                         None,
                         LogSource::System,
@@ -276,6 +492,10 @@ pub async fn create_project(
     } else {
         Project::new(name, local_path)
     };
+    // Keep using the account the project was created or linked with after switching accounts
+    if project.supabase_project_ref.is_some() {
+        project.account = state.active_account().await;
+    }
 
     // Apply TypeScript settings if provided
     if let Some(enabled) = generate_typescript {
@@ -284,6 +504,15 @@ pub async fn create_project(
     if let Some(path) = typescript_output_path {
         project.typescript_output_path = Some(path);
     }
+    project.last_pull_at = pull_status
+        .as_ref()
+        .filter(|status| status.schema_pulled)
+        .and_then(|status| status.attempted_at);
+    project.last_pull_status = pull_status;
+
+    if sink.is_dry_run() {
+        return Ok(CreatedProject { project, operations: sink.operations() });
+    }
 
     let result = state
         .add_project(project)
@@ -389,7 +618,7 @@ pub async fn create_project(
         });
     }
 
-    Ok(result)
+    Ok(CreatedProject { project: result, operations: sink.operations() })
 }
 
 #[tauri::command]
@@ -456,6 +685,10 @@ pub async fn link_supabase_project(
 
     project.supabase_project_ref = Some(supabase_project_ref.clone());
     project.supabase_project_id = Some(remote.id);
+    // Keep using the account the project was linked with after switching accounts
+    project.account = state.active_account().await;
+    // A different remote may hold data the local schema doesn't know about yet
+    project.safe_mode = true;
     project.updated_at = chrono::Utc::now();
 
     let result = state
@@ -474,6 +707,57 @@ pub async fn link_supabase_project(
     Ok(result)
 }
 
+/// Linked/watching state, remote drift, last pull/push and pending function changes
+#[tauri::command]
+pub async fn project_status(app_handle: AppHandle, project_id: String) -> Result<ProjectStatus, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+
+    // Only the fingerprint query runs remotely; without a baseline drift is unknown
+    let drifted = match (&project.supabase_project_ref, state.load_schema_baseline(uuid).await) {
+        (Some(project_ref), Some(baseline)) => match state.get_project_api_client(uuid).await {
+            Ok(api) => crate::introspection::Introspector::new(&api, project_ref.clone())
+                .with_watched_schemas(project.watched_schemas.clone())
+                .fingerprint()
+                .await
+                .ok()
+                .map(|fingerprint| fingerprint != baseline.fingerprint),
+            Err(_) => None,
+        },
+        _ => None,
+    };
+
+    state.project_status(uuid, drifted).await.map_err(|e| e.to_string())
+}
+
+/// Detach a project from its Supabase project. Push and pull then fail with
+/// "Project not linked to Supabase" until it is linked again.
+#[tauri::command]
+pub async fn unlink_supabase_project(app_handle: AppHandle, project_id: String) -> Result<Project, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let previous_ref = state
+        .get_project(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+
+    let result = state.unlink_project(uuid).await.map_err(|e| e.to_string())?;
+
+    let log = LogEntry::info(
+        Some(uuid),
+        LogSource::System,
+        format!("Unlinked from Supabase project: {}", previous_ref),
+    );
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_project_keys(app_handle: AppHandle, project_id: String) -> Result<ProjectKeys, String> {
     let state = app_handle.state::<Arc<AppState>>();
@@ -485,7 +769,7 @@ pub async fn get_project_keys(app_handle: AppHandle, project_id: String) -> Resu
     let project_ref = project.supabase_project_ref
         .ok_or("Project is not linked to a Supabase project".to_string())?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
     
     // Use cached keys if possible? No, for now fetch fresh to ensure validity
     // or maybe we should cache them in project struct?
@@ -509,3 +793,31 @@ pub async fn get_project_keys(app_handle: AppHandle, project_id: String) -> Resu
         service_role_key,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supabase_api::mock::MockApi;
+
+    #[tokio::test]
+    async fn test_resolve_region() {
+        let api = MockApi::new().with_regions(&["eu-central-1", "us-east-1"]);
+        assert_eq!(resolve_region(&api, "org", Some("eu-central-1".to_string())).await.unwrap(), "eu-central-1");
+        assert_eq!(resolve_region(&api, "org", None).await.unwrap(), DEFAULT_REGION);
+        let err = resolve_region(&api, "org", Some("ap-south-1".to_string())).await.unwrap_err();
+        assert_eq!(err, "Region 'ap-south-1' is not available for this organization");
+
+        // Without a region list from the API the built-in one applies
+        let api = MockApi::new();
+        assert_eq!(resolve_region(&api, "org", Some("ap-south-1".to_string())).await.unwrap(), "ap-south-1");
+        assert!(resolve_region(&api, "org", Some("mars-1".to_string())).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_db_password_needs_access_to_the_project() {
+        let api = MockApi::new().with_project("abcdefghijklmnopqrst");
+        assert!(check_db_password_access(&api, "abcdefghijklmnopqrst").await.is_ok());
+        assert!(check_db_password_access(&api, "tsrqponmlkjihgfedcba").await.is_err());
+        assert!(check_db_password_access(&MockApi::new(), "abcdefghijklmnopqrst").await.is_err());
+    }
+}
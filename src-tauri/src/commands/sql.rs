@@ -1,17 +1,24 @@
+use serde::Serialize;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use uuid::Uuid;
-use sqlparser::dialect::PostgreSqlDialect;
-use sqlparser::parser::Parser;
+use crate::models::SavedQuery;
 use crate::state::AppState;
 
+/// Payload of `ai-convert-chunk` events emitted while a conversion streams in
+#[derive(Clone, Serialize)]
+struct AiConvertChunk {
+    project_id: Uuid,
+    delta: String,
+}
+
 /// Validate SQL syntax using sqlparser
 #[tauri::command]
 pub fn validate_sql(sql: String) -> Result<(), String> {
-    let dialect = PostgreSqlDialect {};
-    Parser::parse_sql(&dialect, &sql)
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+    match crate::sync::validate_sql(&sql) {
+        Some(error) => Err(error.message),
+        None => Ok(()),
+    }
 }
 
 /// Build a concise schema description for AI context
@@ -63,24 +70,27 @@ fn build_schema_context(schema: &crate::schema::DbSchema) -> String {
     context
 }
 
-/// Convert natural language or invalid SQL to valid SQL using OpenAI
+/// Convert natural language or invalid SQL to valid SQL using OpenAI.
+/// The response is streamed as `ai-convert-chunk` events and can be aborted with `cancel_ai_convert`.
+/// Statements that aren't schema definition or read-only queries are returned as `flagged`.
 #[tauri::command]
 pub async fn convert_with_ai(
     app_handle: AppHandle,
     project_id: String,
     input: String,
     error_message: Option<String>,
-) -> Result<String, String> {
+) -> Result<crate::ai::AiConvertResult, String> {
     let state = app_handle.state::<Arc<AppState>>();
-    
-    let api_key = state
-        .get_openai_key()
-        .await
-        .ok_or("OpenAI API key not configured. Please add it in Settings.")?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let settings = crate::ai::AiSettings::load(&app_handle);
+    let api_key = state.get_openai_key().await;
+    if api_key.is_none() && settings.is_default_endpoint() {
+        return Err("OpenAI API key not configured. Please add it in Settings.".to_string());
+    }
 
     // Get schema context - check cache first, then introspect if needed
     let schema_context = {
-        let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
         let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
         
         if project.supabase_project_ref.is_none() {
@@ -92,7 +102,7 @@ pub async fn convert_with_ai(
             } else {
                 // Cache miss - fetch and cache
                 let project_ref = project.supabase_project_ref.as_ref().unwrap();
-                let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+                let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
                 let introspector = crate::introspection::Introspector::new(&api, project_ref.clone());
                 
                 match introspector.introspect().await {
@@ -141,43 +151,41 @@ Return only valid PostgreSQL SQL."#,
     println!("\nUser Content:\n{}", user_content);
     println!("--- AI Request Debug End ---");
 
-    let response = state.http_client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
-        .json(&serde_json::json!({
-            "model": "gpt-4o-mini",
-            "messages": [
-                {
-                    "role": "system",
-                    "content": system_prompt
-                },
-                {
-                    "role": "user",
-                    "content": user_content
-                }
-            ],
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to call OpenAI API: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error ({}): {}", status, error_text));
-    }
-
-    let json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": system_prompt
+        },
+        {
+            "role": "user",
+            "content": user_content
+        }
+    ]);
 
-    let sql = json["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or("Invalid response from OpenAI")?
-        .trim()
-        .to_string();
+    let emitter = app_handle.clone();
+    let on_delta = move |delta: &str| {
+        emitter
+            .emit("ai-convert-chunk", AiConvertChunk { project_id: uuid, delta: delta.to_string() })
+            .ok();
+    };
+    let content = state
+        .ai_requests
+        .run(
+            uuid,
+            crate::ai::stream_chat_completion(
+                &state.http_client,
+                &settings,
+                api_key.as_deref(),
+                messages,
+                on_delta,
+            ),
+        )
+        .await?;
+
+    if content.trim().is_empty() {
+        return Err("Invalid response from OpenAI".to_string());
+    }
+    let sql = content.trim().to_string();
 
     // Clean up any markdown code blocks if present
     let sql = sql
@@ -187,5 +195,59 @@ Return only valid PostgreSQL SQL."#,
         .trim()
         .to_string();
 
-    Ok(sql)
+    let flagged = crate::ai::review_generated_sql(&sql)?;
+    Ok(crate::ai::AiConvertResult { sql, flagged })
+}
+
+/// Abort the project's in-flight AI conversion; `convert_with_ai` then fails with `CANCELLED:`
+#[tauri::command]
+pub async fn cancel_ai_convert(app_handle: AppHandle, project_id: String) -> Result<bool, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    Ok(state.ai_requests.cancel(uuid).await)
+}
+
+#[tauri::command]
+pub async fn save_query(
+    app_handle: AppHandle,
+    project_id: String,
+    name: String,
+    sql: String,
+) -> Result<SavedQuery, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    state.save_query(uuid, name, sql).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_saved_queries(app_handle: AppHandle, project_id: String) -> Result<Vec<SavedQuery>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    Ok(state.list_saved_queries(uuid).await)
+}
+
+#[tauri::command]
+pub async fn delete_saved_query(app_handle: AppHandle, project_id: String, query_id: String) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let query_uuid = Uuid::parse_str(&query_id).map_err(|e| e.to_string())?;
+    state.delete_saved_query(uuid, query_uuid).await.map_err(|e| e.to_string())
+}
+
+/// Run a saved query the same way `run_query` runs editor SQL
+#[tauri::command]
+pub async fn run_saved_query(
+    app_handle: AppHandle,
+    project_id: String,
+    query_id: String,
+    read_only: Option<bool>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+) -> Result<crate::query::QueryPage, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let query_uuid = Uuid::parse_str(&query_id).map_err(|e| e.to_string())?;
+    let saved = state.get_saved_query(uuid, query_uuid).await.map_err(|e| e.to_string())?;
+
+    super::sync::run_query(app_handle.clone(), project_id, saved.sql, read_only, limit, offset).await
 }
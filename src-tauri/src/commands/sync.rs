@@ -3,9 +3,12 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use uuid::Uuid;
 
-use crate::models::{LogEntry, LogSource, Project};
+use crate::file_sink::{FileOperation, FileSink};
+use crate::introspection::progress::{ProgressSink, ProgressTracker, ProgressUpdate};
+use crate::models::{LogEntry, LogPhase, LogSource, Project};
 use crate::state::AppState;
-use crate::sync;
+use crate::supabase_api::ManagementApi;
+use crate::sync::{self, EventSink};
 use crate::tray::update_icon;
 
 
@@ -16,20 +19,32 @@ pub struct PullDiffResponse {
     pub schema_files: Vec<String>,
 }
 
-pub(crate) async fn fetch_remote_schema_sql(
-    api: &crate::supabase_api::SupabaseApi,
-    project_ref: &str,
-) -> Result<(String, crate::schema::DbSchema), String> {
-    // 1. Introspect Remote
-    let introspector = crate::introspection::Introspector::new(api, project_ref.to_string());
-    let remote_schema = introspector.introspect().await.map_err(|e| e.to_string())?;
+/// Payload of the `pull-progress` and `push-progress` events
+#[derive(Clone, serde::Serialize)]
+pub struct ProgressEvent {
+    pub project_id: Uuid,
+    #[serde(flatten)]
+    pub update: ProgressUpdate,
+}
 
-    // 2. Generate SQL (Full Dump)
-    let empty_schema = crate::schema::DbSchema::new();
-    let diff = crate::diff::compute_diff(&empty_schema, &remote_schema);
-    let sql = crate::generator::generate_sql(&diff, &remote_schema);
+/// Build a progress sink that emits `event` to the frontend for the given project
+fn progress_emitter(events: &EventSink, event: &'static str, project_id: Uuid) -> ProgressSink {
+    let events = events.clone();
+    Arc::new(move |update: ProgressUpdate| {
+        events
+            .emit(event, ProgressEvent { project_id, update })
+            .ok();
+    })
+}
 
-    Ok((sql, remote_schema))
+/// Parse `project_id` and build the API client a push or pull of it runs against
+async fn project_api_client(
+    state: &AppState,
+    project_id: &str,
+) -> Result<(Uuid, crate::supabase_api::SupabaseApi), String> {
+    let uuid = Uuid::parse_str(project_id).map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+    Ok((uuid, api))
 }
 
 #[tauri::command]
@@ -46,10 +61,12 @@ pub async fn get_pull_diff(
         .clone()
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
     // 1. Get Schema SQL and remote schema
-    let (migration_sql, remote_schema) = fetch_remote_schema_sql(&api, &project_ref).await?;
+    let (migration_sql, remote_schema) = state
+        .surface_auth_error(sync::fetch_remote_schema_sql(&api, &project_ref, project.watched_schemas.clone(), None).await)
+        .await?;
 
     // 2. Compute the split file names that will be created on pull
     let split_files = crate::generator::split_sql(&remote_schema);
@@ -74,23 +91,47 @@ pub async fn get_pull_diff(
 }
 
 
+/// Pull the remote schema, TypeScript types and edge functions into the project.
+/// With `dry_run`, nothing is written and the project isn't marked as pulled: the
+/// file operations the pull would perform are returned instead. Otherwise the
+/// operations it performed are returned.
 #[tauri::command]
 pub async fn pull_project(
     app_handle: AppHandle,
     project_id: String,
-) -> Result<String, String> {
+    split: Option<bool>,
+    sync_functions: Option<bool>,
+    merge: Option<bool>,
+    dry_run: Option<bool>,
+) -> Result<Vec<FileOperation>, String> {
     update_icon(&app_handle, true);
-    let result = pull_project_internal(&app_handle, project_id).await;
+    let state = app_handle.state::<Arc<AppState>>();
+    let sink = FileSink::new(dry_run.unwrap_or(false));
+    let result = match project_api_client(&state, &project_id).await {
+        Ok((uuid, api)) => {
+            let events = EventSink::new(&app_handle);
+            pull_project_internal(&api, &state, &events, uuid, split, sync_functions, merge, &sink).await
+        }
+        Err(e) => Err(e),
+    };
+    let result = state.surface_auth_error(result).await;
     update_icon(&app_handle, false);
-    result
+    result.map(|_| sink.operations())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn pull_project_internal(
-    app_handle: &AppHandle,
-    project_id: String,
+    api: &dyn ManagementApi,
+    state: &AppState,
+    events: &EventSink,
+    uuid: Uuid,
+    split: Option<bool>,
+    sync_functions: Option<bool>,
+    merge: Option<bool>,
+    sink: &FileSink,
 ) -> Result<String, String> {
-    let state = app_handle.state::<Arc<AppState>>();
-    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    // Held until the pull finishes so a push can't interleave with it
+    let _lock = state.try_lock_project(uuid).await?;
 
     let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
     let project_ref = project
@@ -98,88 +139,119 @@ async fn pull_project_internal(
         .clone()
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
-
     let log = LogEntry::info(Some(uuid), LogSource::System, "Pulling remote schema...".to_string());
     state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    events.emit("log", &log).ok();
+
+    // Merging keeps unpushed local edits, using the previous pull as the common ancestor
+    let local_source = sync::find_schema_source(Path::new(&project.local_path));
+    let baseline = match (merge.unwrap_or(false), &local_source) {
+        (true, Some(_)) => Some(
+            state
+                .load_schema_baseline(uuid)
+                .await
+                .ok_or("No previous pull to merge with. Pull without merging first.")?,
+        ),
+        _ => None,
+    };
+    let merge_base = baseline.as_ref().zip(local_source.as_ref()).map(|(b, source)| (&b.schema, source));
+
+    // 1. Introspect the remote and write schema files (numbered split files by default, or a single schema.sql)
+    let schemas_dir = std::path::Path::new(&project.local_path).join("supabase").join("schemas");
+    let progress = progress_emitter(events, "pull-progress", uuid);
+    let pulled = match sync::pull_schema(
+        api,
+        &project_ref,
+        project.watched_schemas.clone(),
+        &schemas_dir,
+        split.unwrap_or(true),
+        Some(progress),
+        merge_base,
+        sink,
+    )
+    .await
+    {
+        Ok(pulled) => pulled,
+        Err(err) if err.starts_with("MERGE_CONFLICT:") => {
+            let log = LogEntry::warning(
+                Some(uuid),
+                LogSource::System,
+                "Local and remote changed the same objects. Schema files were left untouched.".to_string(),
+            );
+            state.add_log(log.clone()).await;
+            events.emit("log", &log).ok();
+            return Err(err);
+        }
+        Err(err) => return Err(err),
+    };
 
-    // 1. Fetch Remote Schema (Introspect + Generate SQL)
-    let (sql, remote_schema) = fetch_remote_schema_sql(&api, &project_ref).await?;
+    if !sink.is_dry_run() {
+        // Cache the schema for AI SQL conversion
+        state.set_cached_schema(uuid, pulled.remote.schema.clone()).await;
 
-    // Cache the schema for AI SQL conversion
-    state.set_cached_schema(uuid, remote_schema.clone()).await;
+        // Remember what the remote looked like so push can detect out-of-band changes
+        state.save_schema_baseline(uuid, &pulled.remote).await;
 
-    // 3. Write split files
-    let supabase_dir = std::path::Path::new(&project.local_path).join("supabase");
-    if !supabase_dir.exists() {
-        tokio::fs::create_dir_all(&supabase_dir)
-            .await
-            .map_err(|e| e.to_string())?;
-    }
-    
-    let schemas_dir = supabase_dir.join("schemas");
-    // Ensure schemas dir exists
-    if !schemas_dir.exists() {
-         tokio::fs::create_dir_all(&schemas_dir)
-            .await
-            .map_err(|e| e.to_string())?;
+        let file_list = pulled.written_files.join(", ");
+        let log = LogEntry::success(
+            Some(uuid),
+            LogSource::System,
+            format!("Schema pulled to supabase/schemas/ ({})", file_list),
+        );
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
     }
 
-    // Generate split files from the remote schema
-    let split_files = crate::generator::split_sql(&remote_schema);
-
-    // Clear any existing files in the schemas directory
-    let mut existing_entries = tokio::fs::read_dir(&schemas_dir)
-        .await
-        .map_err(|e| e.to_string())?;
-    while let Some(entry) = existing_entries.next_entry().await.map_err(|e| e.to_string())? {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) == Some("sql") {
-            tokio::fs::remove_file(&path)
-                .await
-                .map_err(|e| format!("Failed to remove old schema file: {}", e))?;
-        }
+    // 2. Generate TypeScript types from the pulled schema. A dry run wrote no schema
+    // files, so the types come from the pulled SQL itself.
+    if sink.is_dry_run() {
+        let files = vec![("schema.sql".to_string(), pulled.sql.clone())];
+        write_project_typescript(&project, &files, state, events, sink).await;
+    } else {
+        let pull_schema_source = sync::SchemaSource::Directory(schemas_dir);
+        generate_typescript_for_project(&project, &pull_schema_source, state, events, sink).await;
     }
 
-    // Write each split file
-    let mut written_files: Vec<String> = Vec::new();
-    for (filename, content) in &split_files {
-        let file_path = schemas_dir.join(filename);
-        tokio::fs::write(&file_path, content)
-            .await
-            .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
-        written_files.push(filename.clone());
+    // The schema is pulled whatever happens to the edge functions
+    if !sink.is_dry_run() {
+        sync::record_sync_time(state, uuid, false).await;
     }
 
-    let file_list = written_files.join(", ");
-    let log = LogEntry::success(
-        Some(uuid),
-        LogSource::System,
-        format!("Schema pulled to supabase/schemas/ ({})", file_list),
-    );
-    state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
-
-    // 4. Generate TypeScript types from the pulled schema
-    let pull_schema_source = sync::SchemaSource::Directory(schemas_dir);
-    generate_typescript_for_project(&project, &pull_schema_source, state.inner(), app_handle).await;
-
-    // 5. Pull Edge Functions
-    sync::pull_edge_functions(&api, &project_ref, Some(uuid), std::path::Path::new(&project.local_path), state.inner(), app_handle).await?;
+    // 3. Pull Edge Functions, unless they're managed elsewhere
+    let functions = sync::sync_functions_if_enabled(project.sync_functions_enabled(sync_functions), || {
+        sync::pull_edge_functions(
+            api,
+            &project_ref,
+            Some(uuid),
+            std::path::Path::new(&project.local_path),
+            state,
+            events,
+            sink,
+        )
+    })
+    .await;
+    if let Err(e) = functions {
+        let log = LogEntry::warning(
+            Some(uuid),
+            LogSource::System,
+            format!("Schema pulled, but edge functions were not: {}", e),
+        );
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
 
-    Ok(sql)
+    Ok(pulled.sql)
 }
 
 // Helper to push edge functions (deploy changed functions)
 // Helper to push edge functions (deploy changed functions)
 async fn push_edge_functions(
-    api: &crate::supabase_api::SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
     project_id: Uuid,
     project_local_path: &std::path::Path,
-    state: &Arc<AppState>,
-    app_handle: &AppHandle,
+    state: &AppState,
+    events: &EventSink,
 ) -> Result<Vec<EdgeFunctionDeploymentResult>, String> {
     use futures::stream::{self, StreamExt};
 
@@ -190,7 +262,7 @@ async fn push_edge_functions(
     );
     println!("[INFO] Checking edge functions for changes...");
     state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    events.emit("log", &log).ok();
 
     // Use shared logic to find changed functions
     let changed_functions = sync::compute_edge_functions_diff(project_local_path)
@@ -205,7 +277,7 @@ async fn push_edge_functions(
         );
         println!("[INFO] No edge function changes detected.");
         state.add_log(log).await;
-        // app_handle.emit("log", &log).ok(); // Optional: don't spam if nothing happened
+        // events.emit("log", &log).ok(); // Optional: don't spam if nothing happened
         return Ok(Vec::new());
     }
 
@@ -217,17 +289,28 @@ async fn push_edge_functions(
 
     let results = stream::iter(changed_functions)
         .map(|func| {
-            let api = api.clone();
-            let project_ref = project_ref.clone();
-            let state = state.clone();
-            let app_handle = app_handle.clone();
-            let project_local_path = project_local_path;
-
             async move {
                 let function_slug = func.slug;
                 let function_path = project_local_path.join(&func.path);
 
-                let files = match sync::collect_function_files(&function_path).await {
+                if let Err(e) = sync::validate_function_dir_slug(&function_slug, &function_path) {
+                    let log = LogEntry::error(Some(project_id), LogSource::EdgeFunction, e.clone());
+                    state.add_log(log.clone()).await;
+                    events.emit("log", &log).ok();
+                    return (
+                        EdgeFunctionDeploymentResult {
+                            name: function_slug,
+                            status: "error".to_string(),
+                            version: None,
+                            error: Some(e),
+                        },
+                        None,
+                        None,
+                    );
+                }
+
+                // Only paths and sizes are read up front; contents are hashed and uploaded in chunks
+                let files = match sync::function_deploy_files(&function_path).await {
                     Ok(f) => f,
                     Err(e) => {
                         let log = LogEntry::warning(
@@ -262,9 +345,25 @@ async fn push_edge_functions(
                     );
                 }
 
-                let entrypoint = sync::determine_entrypoint(&files);
-                let local_hash = sync::compute_files_hash(&files);
+                let paths: Vec<&str> = files.iter().map(|f| f.path()).collect();
+                let entrypoint = sync::entrypoint_from_paths(&paths);
+                let local_hash = match sync::hash_deploy_files(&files).await {
+                    Ok(hash) => hash,
+                    Err(e) => {
+                        return (
+                            EdgeFunctionDeploymentResult {
+                                name: function_slug.clone(),
+                                status: "error".to_string(),
+                                version: None,
+                                error: Some(format!("Failed to read files: {}", e)),
+                            },
+                            None,
+                            None,
+                        );
+                    }
+                };
                 let hash_file = function_path.join(".harbor_hash");
+                let deploy_files = files;
 
                 // Phase 1: Upload (with bundle_only if multiple)
                 // Retry up to 3 times for transient failures
@@ -281,7 +380,7 @@ async fn push_edge_functions(
                             &function_slug,
                             &function_slug,
                             &entrypoint,
-                            files.clone(),
+                            deploy_files.clone(),
                             use_bundle_only,
                         )
                         .await
@@ -300,7 +399,7 @@ async fn push_edge_functions(
                             );
                             println!("[INFO] {}", log_msg);
                             state.add_log(log.clone()).await;
-                            app_handle.emit("log", &log).ok();
+                            events.emit("log", &log).ok();
 
                             let deploy_result = EdgeFunctionDeploymentResult {
                                 name: result.name.clone(),
@@ -330,7 +429,7 @@ async fn push_edge_functions(
                                 );
                                 println!("[WARN] Deploy '{}' attempt {} failed (retrying): {}", function_slug, attempt + 1, last_err);
                                 state.add_log(log.clone()).await;
-                                app_handle.emit("log", &log).ok();
+                                events.emit("log", &log).ok();
                             }
                         }
                     }
@@ -344,7 +443,7 @@ async fn push_edge_functions(
                 );
                 println!("[ERROR] Failed to deploy '{}': {}", function_slug, last_err);
                 state.add_log(log.clone()).await;
-                app_handle.emit("log", &log).ok();
+                events.emit("log", &log).ok();
 
                 (
                     EdgeFunctionDeploymentResult {
@@ -389,7 +488,7 @@ async fn push_edge_functions(
         );
         println!("[INFO] Activating {} edge functions atomically...", bundled_responses.len());
         state.add_log(log.clone()).await;
-        app_handle.emit("log", &log).ok();
+        events.emit("log", &log).ok();
 
         match api.bulk_update_functions(project_ref, &bundled_responses).await {
             Ok(_) => {
@@ -413,7 +512,7 @@ async fn push_edge_functions(
                     );
                     println!("[WARN] Bulk activation attempt {} failed (retrying): {}", attempt, first_err);
                     state.add_log(log.clone()).await;
-                    app_handle.emit("log", &log).ok();
+                    events.emit("log", &log).ok();
 
                     match api.bulk_update_functions(project_ref, &bundled_responses).await {
                         Ok(_) => {
@@ -439,7 +538,7 @@ async fn push_edge_functions(
                     );
                     println!("[ERROR] {}", err_msg);
                     state.add_log(log.clone()).await;
-                    app_handle.emit("log", &log).ok();
+                    events.emit("log", &log).ok();
 
                     // Mark all previously "success" as error since activation failed
                     for res in &mut final_results {
@@ -468,7 +567,7 @@ async fn push_edge_functions(
         );
         println!("[INFO] Successfully deployed {} edge function(s)", deployed_count);
         state.add_log(log.clone()).await;
-        app_handle.emit("log", &log).ok();
+        events.emit("log", &log).ok();
     }
 
     Ok(final_results)
@@ -493,44 +592,143 @@ pub async fn push_project(
     app_handle: AppHandle,
     project_id: String,
     force: Option<bool>,
+    sync_functions: Option<bool>,
+    accept_renames: Option<Vec<sync::ColumnRenameCandidate>>,
+    transaction_mode: Option<crate::generator::TransactionMode>,
+    ignore_divergence: Option<bool>,
 ) -> Result<PushResponse, String> {
     update_icon(&app_handle, true);
-    let result = push_project_internal(&app_handle, project_id, force).await;
+    let state = app_handle.state::<Arc<AppState>>();
+    let result = match project_api_client(&state, &project_id).await {
+        Ok((uuid, api)) => {
+            push_project_internal(
+                &api,
+                &state,
+                &EventSink::new(&app_handle),
+                uuid,
+                force,
+                sync_functions,
+                accept_renames,
+                transaction_mode,
+                ignore_divergence,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    let result = state.surface_auth_error(result).await;
     update_icon(&app_handle, false);
     result
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn push_project_internal(
-    app_handle: &AppHandle,
-    project_id: String,
+    api: &dyn ManagementApi,
+    state: &AppState,
+    events: &EventSink,
+    uuid: Uuid,
     force: Option<bool>,
+    sync_functions: Option<bool>,
+    accept_renames: Option<Vec<sync::ColumnRenameCandidate>>,
+    transaction_mode: Option<crate::generator::TransactionMode>,
+    ignore_divergence: Option<bool>,
 ) -> Result<PushResponse, String> {
-    let state = app_handle.state::<Arc<AppState>>();
-    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let _lock = state.try_lock_project(uuid).await?;
 
     let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let sync_functions = project.sync_functions_enabled(sync_functions);
     let project_ref = project
         .supabase_project_ref
         .clone()
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
-
     let log = LogEntry::info(Some(uuid), LogSource::System, "Pushing schema changes...".to_string());
     println!("[INFO] Pushing schema changes for project {}", uuid);
     state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    events.emit("log", &log).ok();
 
     // Find schema source using shared sync module
     let schema_source = sync::find_schema_source(Path::new(&project.local_path))
         .ok_or("Schema not found (checked supabase/schemas/ directory and supabase/schemas/schema.sql and supabase/schema.sql)")?;
 
+    // Stages: diff, generate, execute
+    let progress = ProgressTracker::new(3, Some(progress_emitter(events, "push-progress", uuid)));
+
     // Compute diff using shared sync module (introspect remote, parse local, compute diff)
-    let diff_result = sync::compute_schema_diff(&api, &project_ref, &schema_source).await?;
-    let diff = diff_result.diff;
+    let cached_remote = state.load_remote_schema_cache(uuid).await;
+    let mut diff_options = project.diff_options();
+    diff_options.accepted_renames = accept_renames.unwrap_or_default();
+    let diff_result = sync::compute_schema_diff(api, &project_ref, &schema_source, &diff_options, &project.generate_options(), cached_remote).await?;
+    state.save_remote_schema_cache(uuid, &diff_result.remote).await;
+    let diff = &diff_result.diff;
+    progress.complete("diff");
 
     let summary = diff.summarize();
-    
+    let phase_logs = sync::push_phase_logs(uuid, &diff_result);
+
+    // Introspect and diff entries; generate/execute follow once the push is confirmed
+    for log in phase_logs.before_checks {
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
+
+    // Statements the parser skipped never reach the remote; say so instead of dropping them quietly
+    for statement in &diff_result.unparsed {
+        let log = LogEntry::warning(
+            Some(uuid),
+            LogSource::System,
+            format!("Skipped statement in {}: {}", statement.file, statement.reason),
+        )
+        .with_details(statement.text.clone())
+        .with_phase(LogPhase::Diff);
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
+
+    // Surface references that will fail at apply time before anything runs
+    for warning in &diff_result.reference_warnings {
+        let log = LogEntry::warning(Some(uuid), LogSource::System, warning.clone()).with_phase(LogPhase::Diff);
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
+
+    // Refuse to overwrite remote changes made since the last pull/push. Accepting that is
+    // separate from `force`, which only confirms destructive changes.
+    if !ignore_divergence.unwrap_or(false) && !diff.is_empty() {
+        let baseline = state.load_schema_baseline(uuid).await;
+        if let Err(err) = sync::check_divergence(baseline.as_ref(), &diff_result.remote, &project.diff_options()) {
+            let log = LogEntry::warning(
+                Some(uuid),
+                LogSource::System,
+                "Remote schema changed since last pull. Confirmation required.".to_string(),
+            );
+            state.add_log(log.clone()).await;
+            events.emit("log", &log).ok();
+
+            return Err(err);
+        }
+    }
+
+    if let Err(err) = sync::check_rebuild_required(diff) {
+        let log = LogEntry::error(Some(uuid), LogSource::System, err.clone()).with_phase(LogPhase::Diff);
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+
+        return Err(err);
+    }
+
+    if let Err(err) = sync::check_safe_mode(project.safe_mode, diff) {
+        let log = LogEntry::warning(
+            Some(uuid),
+            LogSource::System,
+            "Destructive changes blocked by safe mode.".to_string(),
+        );
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+
+        return Err(err);
+    }
+
     // Check for destructive changes
     if !force.unwrap_or(false) && diff.is_destructive() {
         let log = LogEntry::warning(
@@ -539,77 +737,96 @@ async fn push_project_internal(
             "Destructive changes detected. Confirmation required.".to_string(),
         );
         state.add_log(log.clone()).await;
-        app_handle.emit("log", &log).ok();
-        
-        return Err(format!("CONFIRMATION_NEEDED:{}", summary));
+        events.emit("log", &log).ok();
+
+        // Objects that still reference what's being dropped fail the drop or go with it (CASCADE)
+        let dependents = crate::diff::dependents::dependents_of_drops(diff, &diff_result.remote.schema);
+        if dependents.is_empty() {
+            return Err(format!("CONFIRMATION_NEEDED:{}", summary));
+        }
+        return Err(format!("CONFIRMATION_NEEDED:{}\n\nDependent objects:\n{}", summary, dependents.join("\n")));
     }
 
     println!("[INFO] Diff Summary:\n{}", summary);
-    let log = LogEntry::info(
-        Some(uuid),
-        LogSource::System,
-        format!("Diff Summary:\n{}", summary),
-    );
-    state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
 
     // Use migration SQL from diff result
     let migration_sql = &diff_result.migration_sql;
+    progress.complete("generate");
 
     if migration_sql.trim().is_empty() {
+        // Local and remote agree, so the current remote is the new baseline
+        state.save_schema_baseline(uuid, &diff_result.remote).await;
+
          let log = LogEntry::success(
             Some(uuid),
             LogSource::System,
             "No schema changes detected.".to_string(),
-        );
+        )
+        .with_phase(LogPhase::Generate);
         state.add_log(log.clone()).await;
-        app_handle.emit("log", &log).ok();
+        events.emit("log", &log).ok();
         
+        progress.complete("execute");
+
         // Still deploy edge functions even if no schema changes
-        let edge_function_results = push_edge_functions(&api, &project_ref, uuid, std::path::Path::new(&project.local_path), state.inner(), app_handle).await?;
-        
+        let edge_function_results = sync::sync_functions_if_enabled(sync_functions, || {
+            push_edge_functions(api, &project_ref, uuid, std::path::Path::new(&project.local_path), state, events)
+        })
+        .await?
+        .unwrap_or_default();
+        sync::record_sync_time(state, uuid, true).await;
+
         return Ok(PushResponse {
             migration_sql: "No changes".to_string(),
             edge_function_results,
         });
     }
 
-    let log = LogEntry::info(
-        Some(uuid),
-        LogSource::System,
-        format!("Applying changes:\n{}", migration_sql),
-    );
     println!("[INFO] Applying changes:\n{}", migration_sql);
-    state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    for log in phase_logs.after_checks {
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
 
     // 5. Execute
-    let result = api.run_query(&project_ref, &migration_sql, false).await.map_err(|e| e.to_string())?;
-
-    if let Some(err) = result.error {
-        let log = LogEntry::error(Some(uuid), LogSource::System, format!("Migration failed: {}", err));
+    let transaction_mode = transaction_mode.unwrap_or_default();
+    if let Err(err) =
+        sync::execute_migration_with_mode(api, &project_ref, &diff_result.migration, transaction_mode).await
+    {
+        let log = LogEntry::error(Some(uuid), LogSource::System, format!("Migration failed: {}", err))
+            .with_phase(LogPhase::Execute);
         println!("[ERROR] Migration failed: {}", err);
         state.add_log(log.clone()).await;
-        app_handle.emit("log", &log).ok();
+        events.emit("log", &log).ok();
         return Err(err);
     }
+    progress.complete("execute");
 
     let log = LogEntry::success(
         Some(uuid),
         LogSource::System,
         "Schema changes pushed successfully.".to_string(),
-    );
+    )
+    .with_phase(LogPhase::Execute);
     state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    events.emit("log", &log).ok();
 
     // Clear schema cache since remote schema changed
     state.clear_cached_schema(uuid).await;
 
+    // The remote now matches local; record it as the baseline for the next push
+    sync::save_pushed_baseline(state, api, &project, &project_ref, &diff_result.local_schema).await;
+
     // 6. Generate TypeScript types after successful push
-    generate_typescript_for_project(&project, &schema_source, state.inner(), app_handle).await;
+    generate_typescript_for_project(&project, &schema_source, state, events, &FileSink::default()).await;
 
     // 7. Deploy edge functions if any have changed
-    let edge_function_results = push_edge_functions(&api, &project_ref, uuid, std::path::Path::new(&project.local_path), state.inner(), app_handle).await?;
+    let edge_function_results = sync::sync_functions_if_enabled(sync_functions, || {
+        push_edge_functions(api, &project_ref, uuid, std::path::Path::new(&project.local_path), state, events)
+    })
+    .await?
+    .unwrap_or_default();
+    sync::record_sync_time(state, uuid, true).await;
 
     Ok(PushResponse {
         migration_sql: migration_sql.to_string(),
@@ -617,13 +834,80 @@ async fn push_project_internal(
     })
 }
 
+/// Push every linked project. Each project is pushed as with `push_project`;
+/// one failing (or needing confirmation) doesn't stop the rest.
+#[tauri::command]
+pub async fn push_all_projects(
+    app_handle: AppHandle,
+    force: Option<bool>,
+) -> Result<Vec<sync::BulkOperationResult>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let projects = state.get_projects().await;
+
+    update_icon(&app_handle, true);
+    let results = sync::run_for_linked_projects(projects, sync::BULK_CONCURRENCY, |project| {
+        let app_handle = app_handle.clone();
+        async move {
+            let state = app_handle.state::<Arc<AppState>>();
+            let api = state.get_project_api_client(project.id).await.map_err(|e| e.to_string())?;
+            let events = EventSink::new(&app_handle);
+            push_project_internal(&api, &state, &events, project.id, force, None, None, None, None)
+                .await
+                .map(|_| ())
+        }
+    })
+    .await;
+    update_icon(&app_handle, false);
+
+    let log = sync::bulk_summary_log("Push all", &results);
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(results)
+}
+
+/// Pull every linked project, overwriting local schema files as `pull_project` does
+#[tauri::command]
+pub async fn pull_all_projects(app_handle: AppHandle) -> Result<Vec<sync::BulkOperationResult>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let projects = state.get_projects().await;
+
+    update_icon(&app_handle, true);
+    let results = sync::run_for_linked_projects(projects, sync::BULK_CONCURRENCY, |project| {
+        let app_handle = app_handle.clone();
+        async move {
+            let state = app_handle.state::<Arc<AppState>>();
+            let api = state.get_project_api_client(project.id).await.map_err(|e| e.to_string())?;
+            let events = EventSink::new(&app_handle);
+            let sink = FileSink::default();
+            pull_project_internal(&api, &state, &events, project.id, None, None, None, &sink)
+                .await
+                .map(|_| ())
+        }
+    })
+    .await;
+    update_icon(&app_handle, false);
+
+    let log = sync::bulk_summary_log("Pull all", &results);
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(results)
+}
+
+/// Run an ad-hoc query. When `limit` is given and the query is a single SELECT,
+/// it is wrapped to return one page and the total row count; other statements
+/// run untouched. Results larger than `MAX_RESULT_BYTES` are truncated.
+/// With `read_only`, anything but SELECT/EXPLAIN/SHOW is rejected before sending.
 #[tauri::command]
 pub async fn run_query(
     app_handle: AppHandle,
     project_id: String,
     query: String,
     read_only: Option<bool>,
-) -> Result<serde_json::Value, String> {
+    limit: Option<u64>,
+    offset: Option<u64>,
+) -> Result<crate::query::QueryPage, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
 
@@ -632,7 +916,7 @@ pub async fn run_query(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
     let log = LogEntry::info(
         Some(uuid),
@@ -642,8 +926,21 @@ pub async fn run_query(
     state.add_log(log.clone()).await;
     app_handle.emit("log", &log).ok();
 
+    let read_only = read_only.unwrap_or(false);
+    if read_only {
+        if let Err(e) = crate::query::check_read_only(&query) {
+            let log = LogEntry::error(Some(uuid), LogSource::Schema, format!("Query rejected: {}", e));
+            state.add_log(log.clone()).await;
+            app_handle.emit("log", &log).ok();
+            return Err(e);
+        }
+    }
+
+    let paginated = limit.and_then(|limit| crate::query::paginate_select(&query, limit, offset.unwrap_or(0)));
+    let sql = paginated.as_ref().map(|p| p.page_sql.as_str()).unwrap_or(&query);
+
     let result = api
-        .run_query(&project_ref, &query, read_only.unwrap_or(false))
+        .run_query(&project_ref, sql, read_only)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -665,25 +962,49 @@ pub async fn run_query(
         return Err(error);
     }
 
-    let log = LogEntry::success(
-        Some(uuid),
-        LogSource::Schema,
-        "Query executed successfully".to_string(),
+    // The count is best-effort metadata; a failure shouldn't fail the query
+    let total_count = match &paginated {
+        Some(p) => match api.run_query(&project_ref, &p.count_sql, read_only).await {
+            Ok(count) => count.result.as_ref().and_then(crate::query::parse_total_count),
+            Err(_) => None,
+        },
+        None => None,
+    };
+
+    let (rows, truncated) = crate::query::cap_result_size(
+        result.result.unwrap_or(serde_json::Value::Null),
+        crate::query::MAX_RESULT_BYTES,
     );
+
+    let log = if truncated {
+        LogEntry::warning(
+            Some(uuid),
+            LogSource::Schema,
+            "Query result was too large and has been truncated".to_string(),
+        )
+    } else {
+        LogEntry::success(
+            Some(uuid),
+            LogSource::Schema,
+            "Query executed successfully".to_string(),
+        )
+    };
     state.add_log(log.clone()).await;
     app_handle.emit("log", &log).ok();
 
-    Ok(result.result.unwrap_or(serde_json::Value::Null))
+    Ok(crate::query::QueryPage { rows, total_count, truncated })
 }
 
+/// Plan of a single statement as `EXPLAIN (FORMAT JSON)` output. With `analyze` the
+/// statement runs to collect timings, which is refused for writes unless `force` is set.
 #[tauri::command]
-pub async fn deploy_edge_function(
+pub async fn explain_query(
     app_handle: AppHandle,
     project_id: String,
-    function_slug: String,
-    function_name: String,
-    function_path: String,
-) -> Result<String, String> {
+    query: String,
+    analyze: Option<bool>,
+    force: Option<bool>,
+) -> Result<serde_json::Value, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
 
@@ -692,22 +1013,196 @@ pub async fn deploy_edge_function(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let analyze = analyze.unwrap_or(false);
+    let force = force.unwrap_or(false);
+    let sql = crate::query::explain_sql(&query, analyze, force)?;
 
-    let log = LogEntry::info(
-        Some(uuid),
-        LogSource::EdgeFunction,
-        format!("Deploying edge function: {}", function_name),
-    );
-    state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+    // A forced ANALYZE of a write has to run read-write
+    let read_only = !(analyze && force);
+    let result = api
+        .run_query(&project_ref, &sql, read_only)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(error) = result.error {
+        let log = LogEntry::error(Some(uuid), LogSource::Schema, format!("EXPLAIN failed: {}", error));
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+        return Err(error);
+    }
 
-    // Get function directory from path
-    let full_path = Path::new(&project.local_path).join(&function_path);
-    let function_dir = full_path.parent().unwrap_or(&full_path);
+    crate::query::parse_explain_plan(&result.result.unwrap_or(serde_json::Value::Null))
+}
+
+/// Run a query and write all of its rows to `out_path` as CSV or JSON.
+/// Unlike `run_query`, the result isn't paginated or truncated.
+#[tauri::command]
+pub async fn export_query_result(
+    app_handle: AppHandle,
+    project_id: String,
+    query: String,
+    format: crate::query::ExportFormat,
+    out_path: String,
+) -> Result<usize, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let result = api
+        .run_query(&project_ref, &query, true)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(error) = result.error {
+        return Err(error);
+    }
+
+    let rows = result.result.unwrap_or(serde_json::Value::Null);
+    let row_count = rows.as_array().map(|r| r.len()).unwrap_or(0);
+    let contents = crate::query::export_rows(&rows, &result.columns, format)?;
+    tokio::fs::write(&out_path, contents)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", out_path, e))?;
+
+    let log = LogEntry::success(
+        Some(uuid),
+        LogSource::Schema,
+        format!("Exported {} rows to {}", row_count, out_path),
+    );
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(row_count)
+}
+
+/// Apply a single statement (or a few) as a migration step, e.g. to retry the
+/// statement a push failed on. Unlike `run_query`, errors are returned as data
+/// with the failing line, and destructive SQL requires `force`.
+#[tauri::command]
+pub async fn apply_sql(
+    app_handle: AppHandle,
+    project_id: String,
+    sql: String,
+    read_only: Option<bool>,
+    force: Option<bool>,
+) -> Result<sync::ApplySqlResult, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+
+    let read_only = read_only.unwrap_or(false);
+    let destructive = sync::destructive_statements(&sql);
+    if !force.unwrap_or(false) && !destructive.is_empty() {
+        let log = LogEntry::warning(
+            Some(uuid),
+            LogSource::System,
+            "Destructive SQL detected. Confirmation required.".to_string(),
+        );
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+
+        return Err(format!("CONFIRMATION_NEEDED:{}", destructive.join(";\n")));
+    }
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let log = LogEntry::info(Some(uuid), LogSource::System, format!("Applying SQL:\n{}", sql));
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    let query_result = match api.run_query(&project_ref, &sql, read_only).await {
+        Ok(response) => match response.error {
+            Some(error) => Err(error),
+            None => Ok(response.result.unwrap_or(serde_json::Value::Null)),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    let result = sync::ApplySqlResult::from_query_result(query_result);
+
+    let log = match &result.error {
+        Some(error) => LogEntry::error(
+            Some(uuid),
+            LogSource::System,
+            format!("Migration statement failed: {}", error.message),
+        ),
+        None => LogEntry::success(Some(uuid), LogSource::System, "SQL applied successfully.".to_string()),
+    };
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    if result.success && !read_only {
+        // The statement may have changed the remote schema
+        state.clear_cached_schema(uuid).await;
+    }
+
+    Ok(result)
+}
+
+/// Local hash, deployed version and dirty flag of every edge function,
+/// local or remote. Unlinked projects only report local functions.
+#[tauri::command]
+pub async fn function_status(
+    app_handle: AppHandle,
+    project_id: String,
+) -> Result<Vec<sync::FunctionStatus>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+
+    let remote = match &project.supabase_project_ref {
+        Some(project_ref) => {
+            let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+            api.list_functions(project_ref).await.map_err(|e| e.to_string())?
+        }
+        None => Vec::new(),
+    };
+
+    sync::function_statuses(Path::new(&project.local_path), &remote).await
+}
+
+#[tauri::command]
+pub async fn deploy_edge_function(
+    app_handle: AppHandle,
+    project_id: String,
+    function_slug: String,
+    function_name: String,
+    function_path: String,
+) -> Result<String, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let log = LogEntry::info(
+        Some(uuid),
+        LogSource::EdgeFunction,
+        format!("Deploying edge function: {}", function_name),
+    );
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    // Get function directory from path
+    let full_path = Path::new(&project.local_path).join(&function_path);
+    let function_dir = full_path.parent().unwrap_or(&full_path);
+
+    sync::validate_function_dir_slug(&function_slug, function_dir)?;
 
     // Collect all files from the function directory using shared sync module
-    let files = sync::collect_function_files(function_dir)
+    let files = sync::function_deploy_files(function_dir)
         .await
         .map_err(|e| format!("Failed to read function files: {}", e))?;
 
@@ -716,7 +1211,8 @@ pub async fn deploy_edge_function(
     }
 
     // Determine entrypoint using shared sync module
-    let entrypoint = sync::determine_entrypoint(&files);
+    let paths: Vec<&str> = files.iter().map(|f| f.path()).collect();
+    let entrypoint = sync::entrypoint_from_paths(&paths);
 
     let result = api
         .deploy_function(
@@ -771,7 +1267,7 @@ pub async fn get_remote_schema(
         .supabase_project_ref
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
     let log = LogEntry::info(
         Some(uuid),
@@ -781,10 +1277,9 @@ pub async fn get_remote_schema(
     state.add_log(log.clone()).await;
     app_handle.emit("log", &log).ok();
 
-    let schema = api
-        .get_schema(&project_ref)
-        .await
-        .map_err(|e| e.to_string())?;
+    let schema = state
+        .surface_auth_error(api.get_schema(&project_ref).await.map_err(|e| e.to_string()))
+        .await?;
 
     let log = LogEntry::success(
         Some(uuid),
@@ -797,13 +1292,110 @@ pub async fn get_remote_schema(
     Ok(schema)
 }
 
+/// Introspect the linked project and return the full `DbSchema` as JSON,
+/// for external scripts or snapshot tests of the schema.
+#[tauri::command]
+pub async fn export_schema_json(
+    app_handle: AppHandle,
+    project_id: String,
+) -> Result<serde_json::Value, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .clone()
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let cached_remote = state.load_remote_schema_cache(uuid).await;
+    let remote = state
+        .surface_auth_error(sync::introspect_remote(&api, &project_ref, project.watched_schemas.clone(), cached_remote).await)
+        .await?;
+    state.save_remote_schema_cache(uuid, &remote).await;
+
+    serde_json::to_value(&remote.schema).map_err(|e| e.to_string())
+}
+
+/// Introspect the linked project and return object counts and names per kind,
+/// a compact alternative to `get_remote_schema` and `export_schema_json`.
+#[tauri::command]
+pub async fn introspect_remote(
+    app_handle: AppHandle,
+    project_id: String,
+) -> Result<crate::schema::SchemaSummary, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .clone()
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let cached_remote = state.load_remote_schema_cache(uuid).await;
+    let remote = state
+        .surface_auth_error(sync::introspect_remote(&api, &project_ref, project.watched_schemas.clone(), cached_remote).await)
+        .await?;
+    state.save_remote_schema_cache(uuid, &remote).await;
+
+    let summary = remote.schema.summary();
+    let total: usize = summary.counts.values().sum();
+    let log = LogEntry::info(
+        Some(uuid),
+        LogSource::Schema,
+        format!("Introspected {} remote objects", total),
+    );
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(summary)
+}
+
+/// Objects on the remote that reference `object_name`, to check what a drop would break.
+/// `object_kind` is one of `diff::dependents::DEPENDENT_KINDS`.
+#[tauri::command]
+pub async fn find_dependents(
+    app_handle: AppHandle,
+    project_id: String,
+    object_kind: String,
+    object_name: String,
+) -> Result<Vec<crate::diff::dependents::Dependent>, String> {
+    crate::diff::dependents::check_dependent_kind(&object_kind)?;
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .clone()
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let cached_remote = state.load_remote_schema_cache(uuid).await;
+    let remote = sync::introspect_remote(&api, &project_ref, project.watched_schemas.clone(), cached_remote).await?;
+    state.save_remote_schema_cache(uuid, &remote).await;
+
+    crate::diff::dependents::find_dependents(&remote.schema, &object_kind, &object_name)
+}
+
+/// Run the seed files in `supabase/seed` in transactional batches, reporting the
+/// failing statement and its line on error. With `idempotent`, plain INSERTs into tables with a primary
+/// key or unique index get `ON CONFLICT DO NOTHING` so seeds can be re-run
+/// without duplicating rows.
 #[tauri::command]
 pub async fn run_seeds(
     app_handle: AppHandle,
     project_id: String,
+    idempotent: Option<bool>,
 ) -> Result<String, String> {
     update_icon(&app_handle, true);
-    let result = run_seeds_internal(&app_handle, project_id).await;
+    let result = run_seeds_internal(&app_handle, project_id, idempotent.unwrap_or(false)).await;
     update_icon(&app_handle, false);
     result
 }
@@ -811,9 +1403,11 @@ pub async fn run_seeds(
 async fn run_seeds_internal(
     app_handle: &AppHandle,
     project_id: String,
+    idempotent: bool,
 ) -> Result<String, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let _lock = state.try_lock_project(uuid).await?;
 
     let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
     let project_ref = project
@@ -821,7 +1415,7 @@ async fn run_seeds_internal(
         .clone()
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
     let log = LogEntry::info(Some(uuid), LogSource::System, "Running seed files...".to_string());
     state.add_log(log.clone()).await;
@@ -870,17 +1464,45 @@ async fn run_seeds_internal(
             .cmp(b.file_name().unwrap_or_default())
     });
 
+    // Keys come from the local schema; without it no INSERT is rewritten
+    let mut local_schema = crate::schema::DbSchema::new();
+    if idempotent {
+        let parsed = match sync::find_schema_source(Path::new(&project.local_path)) {
+            Some(source) => match sync::read_schema_source(&source).await {
+                Ok(files) => crate::parsing::parse_schema_sql(&files),
+                Err(e) => Err(e),
+            },
+            None => Err("no local schema found".to_string()),
+        };
+        match parsed {
+            Ok(schema) => local_schema = schema,
+            Err(e) => {
+                let log = LogEntry::warning(
+                    Some(uuid),
+                    LogSource::System,
+                    format!("Seeds will run unchanged, table keys unavailable: {}", e),
+                );
+                state.add_log(log.clone()).await;
+                app_handle.emit("log", &log).ok();
+            }
+        }
+    }
+
     let total_files = seed_files.len();
     let mut executed_count = 0;
 
-    // Execute each seed file in order
+    // Statements of every seed file run in batches of `SEED_BATCH_SIZE`, each in its
+    // own transaction. Each statement remembers its file and position for error reporting.
+    let mut statements: Vec<crate::parsing::SqlStatement> = Vec::new();
+    let mut origins: Vec<(String, usize)> = Vec::new();
+
     for (index, seed_path) in seed_files.iter().enumerate() {
-        let filename = seed_path.file_name().unwrap_or_default().to_string_lossy();
+        let filename = seed_path.file_name().unwrap_or_default().to_string_lossy().to_string();
 
         let log = LogEntry::info(
             Some(uuid),
             LogSource::System,
-            format!("Running seed ({}/{}) {}...", index + 1, total_files, filename),
+            format!("Preparing seed ({}/{}) {}...", index + 1, total_files, filename),
         );
         state.add_log(log.clone()).await;
         app_handle.emit("log", &log).ok();
@@ -911,35 +1533,61 @@ async fn run_seeds_internal(
             continue;
         }
 
-        // Execute the SQL
-        // Prepend search_path to ensure functions/tables in public/extensions are found
-        let run_sql = format!("SET search_path = \"$user\", public, extensions;\n{}", sql);
-
-        match api.run_query(&project_ref, &run_sql, false).await {
-            Ok(result) => {
-                if let Some(err) = result.error {
-                    let log = LogEntry::error(
-                        Some(uuid),
-                        LogSource::System,
-                        format!("Seed {} failed: {}", filename, err),
-                    );
-                    state.add_log(log.clone()).await;
-                    app_handle.emit("log", &log).ok();
-                    return Err(format!("Seed {} failed: {}", filename, err));
-                }
-                executed_count += 1;
-            }
-            Err(e) => {
-                let log = LogEntry::error(
+        let mut file_statements = crate::parsing::split_statements(&sql);
+        if idempotent {
+            let (rewritten_statements, rewritten) = sync::make_seed_idempotent(file_statements, &local_schema);
+            file_statements = rewritten_statements;
+            if rewritten > 0 {
+                let log = LogEntry::info(
                     Some(uuid),
                     LogSource::System,
-                    format!("Seed {} failed: {}", filename, e),
+                    format!("Seed {}: {} INSERT statements made idempotent", filename, rewritten),
                 );
                 state.add_log(log.clone()).await;
                 app_handle.emit("log", &log).ok();
-                return Err(format!("Seed {} failed: {}", filename, e));
             }
         }
+        origins.extend((0..file_statements.len()).map(|i| (filename.clone(), i)));
+        statements.extend(file_statements);
+        executed_count += 1;
+    }
+
+    let batches: Vec<&[crate::parsing::SqlStatement]> = statements.chunks(sync::SEED_BATCH_SIZE).collect();
+    let total_batches = batches.len();
+    for (batch_index, batch) in batches.into_iter().enumerate() {
+        let log = LogEntry::info(
+            Some(uuid),
+            LogSource::System,
+            format!("Running seed batch {}/{} ({} statements)...", batch_index + 1, total_batches, batch.len()),
+        );
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+
+        if let Err((failing, err)) = sync::run_seed_batch(&api, &project_ref, batch).await {
+            let committed = if batch_index == 0 {
+                "no seed data was written".to_string()
+            } else {
+                format!("the {} batch(es) before it were committed", batch_index)
+            };
+            let message = match failing {
+                Some(k) => {
+                    let k = batch_index * sync::SEED_BATCH_SIZE + k;
+                    format!(
+                        "Seed {} failed at statement {} (line {}), {}: {}",
+                        origins[k].0,
+                        origins[k].1 + 1,
+                        statements[k].line,
+                        committed,
+                        err
+                    )
+                }
+                None => format!("Seed batch {}/{} failed, {}: {}", batch_index + 1, total_batches, committed, err),
+            };
+            let log = LogEntry::error(Some(uuid), LogSource::System, message.clone());
+            state.add_log(log.clone()).await;
+            app_handle.emit("log", &log).ok();
+            return Err(message);
+        }
     }
 
     let log = LogEntry::success(
@@ -959,29 +1607,14 @@ async fn generate_typescript_for_project(
     project: &Project,
     source: &sync::SchemaSource,
     state: &AppState,
-    app_handle: &AppHandle,
+    events: &EventSink,
+    sink: &FileSink,
 ) {
     // Check if TypeScript generation is enabled for this project
     if !project.generate_typescript {
         return;
     }
 
-    let project_path = Path::new(&project.local_path);
-
-    // Get TypeScript output path (use custom path if configured)
-    let ts_output_path = sync::get_typescript_output_path(
-        project_path,
-        project.typescript_output_path.as_deref(),
-    );
-
-    let log = LogEntry::info(
-        Some(project.id),
-        LogSource::Schema,
-        "Generating TypeScript types...".to_string(),
-    );
-    state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
-
     // Read schema SQL from source
     let schema_sql = match sync::read_schema_source(source).await {
         Ok(sql) => sql,
@@ -992,13 +1625,47 @@ async fn generate_typescript_for_project(
                 format!("Failed to read schema for TypeScript generation: {}", e),
             );
             state.add_log(log.clone()).await;
-            app_handle.emit("log", &log).ok();
+            events.emit("log", &log).ok();
             return;
         }
     };
 
-    // Generate TypeScript types
-    match sync::generate_typescript_types_from_sql(&schema_sql, &ts_output_path).await {
+    write_project_typescript(project, &schema_sql, state, events, sink).await;
+}
+
+/// Write the project's TypeScript types for `schema_sql` through `sink`.
+/// A dry run only logs failures.
+async fn write_project_typescript(
+    project: &Project,
+    schema_sql: &[(String, String)],
+    state: &AppState,
+    events: &EventSink,
+    sink: &FileSink,
+) {
+    if !project.generate_typescript {
+        return;
+    }
+
+    let project_path = Path::new(&project.local_path);
+
+    // Get TypeScript output path (use custom path if configured)
+    let ts_output_path = sync::get_typescript_output_path(
+        project_path,
+        project.typescript_output_path.as_deref(),
+    );
+
+    if !sink.is_dry_run() {
+        let log = LogEntry::info(
+            Some(project.id),
+            LogSource::Schema,
+            "Generating TypeScript types...".to_string(),
+        );
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
+
+    match sync::generate_typescript_types_from_sql(schema_sql, &ts_output_path, sink).await {
+        Ok(()) if sink.is_dry_run() => {}
         Ok(()) => {
             let relative_output = ts_output_path
                 .strip_prefix(project_path)
@@ -1010,7 +1677,7 @@ async fn generate_typescript_for_project(
                 format!("TypeScript types generated: {}", relative_output),
             );
             state.add_log(log.clone()).await;
-            app_handle.emit("log", &log).ok();
+            events.emit("log", &log).ok();
         }
         Err(e) => {
             let log = LogEntry::error(
@@ -1019,11 +1686,69 @@ async fn generate_typescript_for_project(
                 format!("TypeScript generation failed: {}", e),
             );
             state.add_log(log.clone()).await;
-            app_handle.emit("log", &log).ok();
+            events.emit("log", &log).ok();
         }
     }
 }
 
+/// Compare two linked projects (e.g. staging and prod): what `target` would
+/// need to change to match `source`. The source project's sync settings
+/// decide which schemas and tables are compared.
+#[tauri::command]
+pub async fn diff_projects(
+    app_handle: AppHandle,
+    source_project_id: String,
+    target_project_id: String,
+) -> Result<sync::DiffReport, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let source_uuid = Uuid::parse_str(&source_project_id).map_err(|e| e.to_string())?;
+    let target_uuid = Uuid::parse_str(&target_project_id).map_err(|e| e.to_string())?;
+
+    let source = state.get_project(source_uuid).await.map_err(|e| e.to_string())?;
+    let target = state.get_project(target_uuid).await.map_err(|e| e.to_string())?;
+    let source_ref = source
+        .supabase_project_ref
+        .clone()
+        .ok_or(format!("Project '{}' not linked to Supabase", source.name))?;
+    let target_ref = target
+        .supabase_project_ref
+        .clone()
+        .ok_or(format!("Project '{}' not linked to Supabase", target.name))?;
+
+    let source_api = state.get_project_api_client(source_uuid).await.map_err(|e| e.to_string())?;
+    let target_api = state.get_project_api_client(target_uuid).await.map_err(|e| e.to_string())?;
+    let options = source.diff_options();
+
+    let log = LogEntry::info(
+        Some(source_uuid),
+        LogSource::System,
+        format!("Comparing schema of '{}' with '{}'...", source.name, target.name),
+    );
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    let source_schema = sync::introspect_remote(&source_api, &source_ref, options.watched_schemas.clone(), None)
+        .await?
+        .schema;
+    // Two local projects can point at the same Supabase project; no need to introspect twice
+    let target_schema = if target_ref == source_ref {
+        source_schema.clone()
+    } else {
+        sync::introspect_remote(&target_api, &target_ref, options.watched_schemas.clone(), None)
+            .await?
+            .schema
+    };
+
+    Ok(sync::build_diff_report(&source_schema, &target_schema, &options))
+}
+
+/// Compare two schema files on disk without a database connection: what
+/// `base_path` would need to change to match `target_path`.
+#[tauri::command]
+pub async fn diff_schema_files(base_path: String, target_path: String) -> Result<sync::DiffReport, String> {
+    sync::diff_schema_files(Path::new(&base_path), Path::new(&target_path)).await
+}
+
 #[derive(serde::Serialize)]
 pub struct DiffResponse {
     pub summary: String,
@@ -1032,6 +1757,42 @@ pub struct DiffResponse {
     pub edge_functions: Vec<sync::EdgeFunctionDiff>,
 }
 
+/// Generated SQL for the changes to a single object, e.g. `("table", "public.users")`,
+/// so it can be reviewed before pushing everything.
+#[tauri::command]
+pub async fn preview_object_sql(
+    app_handle: AppHandle,
+    project_id: String,
+    object_kind: String,
+    object_name: String,
+) -> Result<String, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .clone()
+        .ok_or("Project not linked to Supabase")?;
+
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let schema_source = sync::find_schema_source(Path::new(&project.local_path))
+        .ok_or("Schema not found (checked supabase/schemas/ directory and supabase/schemas/schema.sql and supabase/schema.sql)")?;
+
+    let cached_remote = state.load_remote_schema_cache(uuid).await;
+    let mut diff_result = sync::compute_schema_diff(&api, &project_ref, &schema_source, &project.diff_options(), &project.generate_options(), cached_remote).await?;
+    state.save_remote_schema_cache(uuid, &diff_result.remote).await;
+
+    diff_result.diff.retain_object(&object_kind, &object_name)?;
+    let migration = crate::generator::generate_migration_with_options(
+        &diff_result.diff,
+        &diff_result.local_schema,
+        &project.generate_options(),
+    );
+    Ok(migration.to_sql())
+}
+
 #[tauri::command]
 pub async fn get_project_diff(
     app_handle: AppHandle,
@@ -1046,14 +1807,21 @@ pub async fn get_project_diff(
         .clone()
         .ok_or("Project not linked to Supabase")?;
 
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
 
     // Find schema source
     let schema_source = sync::find_schema_source(Path::new(&project.local_path))
         .ok_or("Schema not found (checked supabase/schemas/ directory and supabase/schemas/schema.sql and supabase/schema.sql)")?;
 
     // Compute diff
-    let diff_result = sync::compute_schema_diff(&api, &project_ref, &schema_source).await?;
+    let cached_remote = state.load_remote_schema_cache(uuid).await;
+    let diff_result = state
+        .surface_auth_error(
+            sync::compute_schema_diff(&api, &project_ref, &schema_source, &project.diff_options(), &project.generate_options(), cached_remote)
+                .await,
+        )
+        .await?;
+    state.save_remote_schema_cache(uuid, &diff_result.remote).await;
     let diff = diff_result.diff;
     let summary = diff.summarize();
     let is_destructive = diff.is_destructive();
@@ -1072,6 +1840,82 @@ pub async fn get_project_diff(
     })
 }
 
+/// List migrations in `supabase/migrations/`, newest first
+#[tauri::command]
+pub async fn list_migrations(
+    app_handle: AppHandle,
+    project_id: String,
+) -> Result<Vec<sync::MigrationFile>, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    sync::list_migration_files(&sync::migrations_dir(Path::new(&project.local_path))).await
+}
+
+#[tauri::command]
+pub async fn get_migration_content(
+    app_handle: AppHandle,
+    project_id: String,
+    filename: String,
+) -> Result<String, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    sync::read_migration_file(&sync::migrations_dir(Path::new(&project.local_path)), &filename).await
+}
+
+#[tauri::command]
+pub async fn apply_migration(
+    app_handle: AppHandle,
+    project_id: String,
+    filename: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let _lock = state.try_lock_project(uuid).await?;
+
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+    let project_ref = project
+        .supabase_project_ref
+        .ok_or("Project not linked to Supabase")?;
+    let api = state.get_project_api_client(uuid).await.map_err(|e| e.to_string())?;
+
+    let log = LogEntry::info(Some(uuid), LogSource::System, format!("Applying migration {}...", filename));
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    let dir = sync::migrations_dir(Path::new(&project.local_path));
+    let result = sync::apply_migration_file(&api, &project_ref, &dir, &filename, project.safe_mode, force.unwrap_or(false)).await;
+
+    let log = match &result {
+        Ok(plan) => LogEntry::success(
+            Some(uuid),
+            LogSource::System,
+            format!(
+                "Applied migration {} ({} statements).",
+                filename,
+                plan.statements().count()
+            ),
+        ),
+        Err(e) if e.starts_with("CONFIRMATION_NEEDED:") => LogEntry::warning(
+            Some(uuid),
+            LogSource::System,
+            format!("Migration {} is destructive. Confirmation required.", filename),
+        ),
+        Err(e) => LogEntry::error(Some(uuid), LogSource::System, format!("Migration {} failed: {}", filename, e)),
+    };
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    result?;
+    // The migration changed the remote schema
+    state.clear_cached_schema(uuid).await;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_seed_content(
     app_handle: AppHandle,
@@ -1128,12 +1972,37 @@ pub async fn get_seed_content(
     Ok(combined_sql)
 }
 
+#[derive(serde::Serialize)]
+pub struct SchemaAnalysis {
+    pub summary: crate::schema::SchemaSummary,
+    /// Statements Supawatch will skip when syncing this file
+    pub unrecognized: Vec<crate::parsing::UnparsedStatement>,
+}
+
+/// Parse a hand-written schema file without linking it to a project, reporting
+/// what was recognized and which statements would be silently skipped.
+#[tauri::command]
+pub async fn analyze_schema_file(path: String) -> Result<SchemaAnalysis, String> {
+    let sql = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read schema file: {}", e))?;
+    let filename = Path::new(&path).file_name().unwrap_or_default().to_string_lossy().to_string();
+    let (schema, unrecognized) = crate::parsing::parse_schema_sql_with_report(&[(filename, sql)])?;
+
+    Ok(SchemaAnalysis {
+        summary: schema.summary(),
+        unrecognized,
+    })
+}
+
 /// Split an existing schema.sql into categorized files.
 /// This can be used standalone to "prettify" an existing monolithic schema file.
+/// With `dry_run`, returns the files it would write without touching the project.
 #[tauri::command]
 pub async fn split_schema(
     app_handle: AppHandle,
     project_id: String,
+    dry_run: Option<bool>,
 ) -> Result<Vec<String>, String> {
     let state = app_handle.state::<Arc<AppState>>();
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
@@ -1156,27 +2025,25 @@ pub async fn split_schema(
     let split_files = crate::generator::split_sql(&schema);
 
     // Write split files to the schemas directory
+    let sink = FileSink::new(dry_run.unwrap_or(false));
     let schemas_dir = Path::new(&project.local_path).join("supabase").join("schemas");
     if !schemas_dir.exists() {
-        tokio::fs::create_dir_all(&schemas_dir)
-            .await
-            .map_err(|e| e.to_string())?;
+        sink.create_dir_all(&schemas_dir).await?;
     }
 
     let mut written_files: Vec<String> = Vec::new();
     for (filename, content) in &split_files {
-        let file_path = schemas_dir.join(filename);
-        tokio::fs::write(&file_path, content)
-            .await
-            .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+        sink.write(&schemas_dir.join(filename), content).await?;
         written_files.push(filename.clone());
     }
 
     // Remove the old monolithic schema.sql
     if schema_path.exists() {
-        tokio::fs::remove_file(&schema_path)
-            .await
-            .map_err(|e| format!("Failed to remove old schema file: {}", e))?;
+        sink.remove_file(&schema_path).await?;
+    }
+
+    if sink.is_dry_run() {
+        return Ok(written_files);
     }
 
     let log = LogEntry::info(
@@ -1189,3 +2056,131 @@ pub async fn split_schema(
 
     Ok(written_files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supabase_api::mock::MockApi;
+    use serde_json::json;
+
+    /// A remote whose `tables` each have an `id` and a `title` column
+    fn remote(fingerprint: &str, tables: &[&str]) -> MockApi {
+        let column = |table: &str, name: &str, data_type: &str, udt_name: &str, nullable: &str, position: i32| {
+            json!({
+                "schema": "public", "table_name": table, "column_name": name,
+                "data_type": data_type, "udt_name": udt_name, "is_nullable": nullable,
+                "column_default": null, "is_identity": "NO", "is_primary_key": false,
+                "is_unique": false, "ordinal_position": position
+            })
+        };
+        let columns: Vec<_> = tables
+            .iter()
+            .flat_map(|table| {
+                vec![
+                    column(table, "id", "bigint", "int8", "NO", 1),
+                    column(table, "title", "text", "text", "YES", 2),
+                ]
+            })
+            .collect();
+        let tables: Vec<_> = tables.iter().map(|table| json!({"schema": "public", "name": table})).collect();
+        MockApi::new()
+            .respond("as fingerprint FROM parts", json!([{"fingerprint": fingerprint}]))
+            .respond("row_to_json(table_comments)", json!([{"data": {"tables": tables, "columns": columns}}]))
+    }
+
+    #[tokio::test]
+    async fn test_pull_then_push_against_mock_api() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_mock_sync_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        let state = AppState::with_data_dir(dir.join("data"), Some("token".to_string()), None);
+        let mut project = Project::new("demo".to_string(), dir.join("project").to_string_lossy().to_string());
+        project.supabase_project_ref = Some("ref".to_string());
+        project.generate_typescript = false;
+        project.sync_functions = false;
+        let project = state.add_project(project).await.unwrap();
+        let events = EventSink::default();
+        let api = remote("abc", &["todos"]);
+
+        // Pull writes the introspected schema as schema.sql
+        let sql = pull_project_internal(&api, &state, &events, project.id, Some(false), None, None, &FileSink::default())
+            .await
+            .unwrap();
+        let schema_path = dir.join("project").join("supabase").join("schemas").join("schema.sql");
+        let written = std::fs::read_to_string(&schema_path).unwrap();
+        assert_eq!(written, sql);
+        assert!(written.contains("CREATE TABLE \"public\".\"todos\""), "{}", written);
+        assert!(api.writes().is_empty(), "pull must not modify the remote");
+
+        // Dropping a table is refused in safe mode, then needs confirmation
+        std::fs::write(&schema_path, "").unwrap();
+        let err = push_project_internal(&api, &state, &events, project.id, None, None, None, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("Safe mode blocks destructive changes"), "{}", err);
+        let mut unsafe_project = state.get_project(project.id).await.unwrap();
+        unsafe_project.safe_mode = false;
+        state.update_project(unsafe_project).await.unwrap();
+        let err = push_project_internal(&api, &state, &events, project.id, None, None, None, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("CONFIRMATION_NEEDED:"), "{}", err);
+        assert!(api.writes().is_empty(), "{:?}", api.writes());
+
+        // A remote that changed since the pull isn't overwritten without confirmation
+        std::fs::write(&schema_path, &written).unwrap();
+        let changed = remote("def", &["todos", "notes"]);
+        let err = push_project_internal(&changed, &state, &events, project.id, Some(true), None, None, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("REMOTE_DIVERGED:"), "{}", err);
+        assert!(changed.writes().is_empty(), "{:?}", changed.writes());
+
+        // Push sends only the local addition, in one transaction
+        std::fs::write(&schema_path, format!("{}\nCREATE TABLE \"public\".\"tags\" (\"name\" text);\n", written)).unwrap();
+        let pushed = push_project_internal(&api, &state, &events, project.id, None, None, None, None, None)
+            .await
+            .unwrap();
+        let writes = api.writes();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(writes.len(), 1, "{:?}", writes);
+        assert!(writes[0].starts_with("BEGIN;") && writes[0].ends_with("COMMIT;"), "{}", writes[0]);
+        assert!(pushed.migration_sql.contains("CREATE TABLE \"public\".\"tags\""), "{}", pushed.migration_sql);
+        assert!(writes[0].contains("CREATE TABLE \"public\".\"tags\""), "{}", writes[0]);
+        assert!(!writes[0].contains("todos"), "{}", writes[0]);
+    }
+
+    #[tokio::test]
+    async fn test_push_logs_phases_in_order() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_push_phases_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        let state = AppState::with_data_dir(dir.join("data"), Some("token".to_string()), None);
+        let mut project = Project::new("demo".to_string(), dir.join("project").to_string_lossy().to_string());
+        project.supabase_project_ref = Some("ref".to_string());
+        project.generate_typescript = false;
+        project.sync_functions = false;
+        let project = state.add_project(project).await.unwrap();
+        let events = EventSink::default();
+        let api = remote("abc", &["todos"]);
+
+        let sql = pull_project_internal(&api, &state, &events, project.id, Some(false), None, None, &FileSink::default())
+            .await
+            .unwrap();
+        let schema_path = dir.join("project").join("supabase").join("schemas").join("schema.sql");
+        std::fs::write(&schema_path, format!("{}\nCREATE TABLE \"public\".\"tags\" (\"name\" text);\n", sql)).unwrap();
+        state.clear_logs(Some(project.id)).await;
+        push_project_internal(&api, &state, &events, project.id, None, None, None, None, None)
+            .await
+            .unwrap();
+
+        // `get_logs` is newest first
+        let mut logs = state.get_logs(Some(project.id), usize::MAX).await;
+        logs.reverse();
+        std::fs::remove_dir_all(&dir).ok();
+        let phases: Vec<LogPhase> = logs.iter().filter_map(|l| l.phase).collect();
+        assert_eq!(
+            phases,
+            vec![LogPhase::Introspect, LogPhase::Diff, LogPhase::Generate, LogPhase::Execute, LogPhase::Execute]
+        );
+        assert_eq!(logs.iter().rfind(|l| l.phase.is_some()).unwrap().message, "Schema changes pushed successfully.");
+    }
+}
@@ -1,5 +1,11 @@
 
-use tauri::Manager;
+use std::sync::Arc;
+use tauri::{Emitter, Manager};
+use uuid::Uuid;
+
+use crate::models::{LogEntry, LogSource};
+use crate::state::AppState;
+
 #[tauri::command]
 pub async fn is_folder_empty(path: String) -> Result<bool, String> {
     let path = std::path::Path::new(&path);
@@ -100,3 +106,218 @@ pub async fn copy_template(
 
     Ok(())
 }
+
+/// Starting points for new edge functions
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeFunctionTemplate {
+    HelloWorld,
+    Webhook,
+    Cron,
+}
+
+impl EdgeFunctionTemplate {
+    fn source(self) -> &'static str {
+        match self {
+            Self::HelloWorld => HELLO_WORLD_FUNCTION,
+            Self::Webhook => WEBHOOK_FUNCTION,
+            Self::Cron => CRON_FUNCTION,
+        }
+    }
+}
+
+const HELLO_WORLD_FUNCTION: &str = r#"Deno.serve(async (req) => {
+  const { name } = await req.json();
+  const data = { message: `Hello ${name}!` };
+
+  return new Response(JSON.stringify(data), {
+    headers: { "Content-Type": "application/json" },
+  });
+});
+"#;
+
+const WEBHOOK_FUNCTION: &str = r#"// Set WEBHOOK_SECRET with `supabase secrets set WEBHOOK_SECRET=...`
+const secret = Deno.env.get("WEBHOOK_SECRET");
+
+Deno.serve(async (req) => {
+  if (req.method !== "POST") {
+    return new Response("Method not allowed", { status: 405 });
+  }
+  if (secret && req.headers.get("x-webhook-secret") !== secret) {
+    return new Response("Unauthorized", { status: 401 });
+  }
+
+  const payload = await req.json();
+  console.log("Received webhook", payload);
+
+  return new Response(JSON.stringify({ received: true }), {
+    headers: { "Content-Type": "application/json" },
+  });
+});
+"#;
+
+const CRON_FUNCTION: &str = r#"// Schedule with pg_cron + pg_net, e.g. every hour:
+// select cron.schedule('hourly-job', '0 * * * *', $$
+//   select net.http_post(url := 'https://<project-ref>.supabase.co/functions/v1/<slug>',
+//     headers := jsonb_build_object('Authorization', 'Bearer <service-role-key>'))
+// $$);
+import { createClient } from "jsr:@supabase/supabase-js@2";
+
+const supabase = createClient(
+  Deno.env.get("SUPABASE_URL")!,
+  Deno.env.get("SUPABASE_SERVICE_ROLE_KEY")!,
+);
+
+Deno.serve(async () => {
+  const startedAt = new Date().toISOString();
+  console.log("Scheduled job started", startedAt);
+
+  // Do the job's work with `supabase`, e.g. removing expired rows:
+  // const { error } = await supabase.from("sessions").delete().lt("expires_at", startedAt);
+  // if (error) return new Response(JSON.stringify({ error: error.message }), { status: 500 });
+
+  return new Response(JSON.stringify({ ok: true, startedAt }), {
+    headers: { "Content-Type": "application/json" },
+  });
+});
+"#;
+
+const FUNCTION_DENO_JSON: &str = r#"{
+  "imports": {
+    "@supabase/supabase-js": "jsr:@supabase/supabase-js@2"
+  }
+}
+"#;
+
+/// Create `<functions_dir>/<slug>/index.ts` from a template, plus `deno.json` when asked.
+/// Slugs follow the same rule as deploys, `sync::validate_function_slug`.
+pub async fn scaffold_edge_function(
+    functions_dir: &std::path::Path,
+    slug: &str,
+    template: EdgeFunctionTemplate,
+    with_deno_json: bool,
+) -> Result<std::path::PathBuf, String> {
+    crate::sync::validate_function_slug(slug)?;
+
+    let func_dir = functions_dir.join(slug);
+    if func_dir.exists() {
+        return Err(format!("Function '{}' already exists", slug));
+    }
+
+    tokio::fs::create_dir_all(&func_dir)
+        .await
+        .map_err(|e| format!("Failed to create function directory: {}", e))?;
+    tokio::fs::write(func_dir.join("index.ts"), template.source())
+        .await
+        .map_err(|e| format!("Failed to write index.ts: {}", e))?;
+    if with_deno_json {
+        tokio::fs::write(func_dir.join("deno.json"), FUNCTION_DENO_JSON)
+            .await
+            .map_err(|e| format!("Failed to write deno.json: {}", e))?;
+    }
+
+    Ok(func_dir)
+}
+
+#[tauri::command]
+pub async fn create_edge_function(
+    app_handle: tauri::AppHandle,
+    project_id: String,
+    slug: String,
+    template: EdgeFunctionTemplate,
+    deno_json: Option<bool>,
+) -> Result<String, String> {
+    let state = app_handle.state::<Arc<AppState>>();
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let project = state.get_project(uuid).await.map_err(|e| e.to_string())?;
+
+    let functions_dir = std::path::Path::new(&project.local_path).join("supabase").join("functions");
+    let func_dir = scaffold_edge_function(&functions_dir, &slug, template, deno_json.unwrap_or(false)).await?;
+
+    let log = LogEntry::success(
+        Some(uuid),
+        LogSource::EdgeFunction,
+        format!("Created edge function: {}", slug),
+    );
+    state.add_log(log.clone()).await;
+    app_handle.emit("log", &log).ok();
+
+    Ok(func_dir.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_functions_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("harbor_test_functions_{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_creates_index_ts_from_template() {
+        let functions_dir = temp_functions_dir();
+
+        for (slug, template) in [
+            ("hello-world", EdgeFunctionTemplate::HelloWorld),
+            ("stripe_webhook", EdgeFunctionTemplate::Webhook),
+            ("nightly-cleanup", EdgeFunctionTemplate::Cron),
+        ] {
+            let func_dir = scaffold_edge_function(&functions_dir, slug, template, true).await.unwrap();
+            let source = std::fs::read_to_string(func_dir.join("index.ts")).unwrap();
+            assert!(source.contains("Deno.serve("), "{} has no handler", slug);
+
+            let deno_json: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(func_dir.join("deno.json")).unwrap()).unwrap();
+            assert!(deno_json["imports"].is_object());
+
+            // The template is found by the existing entrypoint logic
+            let files = crate::sync::function_deploy_files(&func_dir).await.unwrap();
+            let paths: Vec<&str> = files.iter().map(|f| f.path()).collect();
+            assert_eq!(crate::sync::entrypoint_from_paths(&paths), "index.ts");
+        }
+
+        // An existing function is never overwritten
+        assert!(scaffold_edge_function(&functions_dir, "hello-world", EdgeFunctionTemplate::Cron, false)
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(&functions_dir).ok();
+    }
+
+    #[test]
+    fn test_template_imports_resolve_without_deno_json() {
+        // deno.json is optional, so every import must carry its own registry or be relative
+        let import_re = regex::Regex::new(r#"(?m)^import\s.*\sfrom\s+"([^"]+)""#).unwrap();
+        for template in [EdgeFunctionTemplate::HelloWorld, EdgeFunctionTemplate::Webhook, EdgeFunctionTemplate::Cron] {
+            for caps in import_re.captures_iter(template.source()) {
+                let specifier = &caps[1];
+                assert!(
+                    ["jsr:", "npm:", "https://", "./", "../"].iter().any(|p| specifier.starts_with(p)),
+                    "{:?} imports bare specifier '{}'",
+                    template,
+                    specifier
+                );
+            }
+        }
+        assert!(CRON_FUNCTION.contains(r#"from "jsr:@supabase/supabase-js@2""#));
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_rejects_invalid_slugs() {
+        let functions_dir = temp_functions_dir();
+
+        for slug in ["My Function", "1-function", "-leading", "../escape", "emoji-🚀", ""] {
+            let result = scaffold_edge_function(&functions_dir, slug, EdgeFunctionTemplate::HelloWorld, false).await;
+            assert!(result.is_err(), "'{}' should be rejected", slug);
+            assert_eq!(result.unwrap_err(), crate::sync::validate_function_slug(slug).unwrap_err());
+        }
+        assert!(!functions_dir.exists());
+    }
+
+    #[test]
+    fn test_template_names_deserialize_in_kebab_case() {
+        let template: EdgeFunctionTemplate = serde_json::from_str("\"hello-world\"").unwrap();
+        assert_eq!(template, EdgeFunctionTemplate::HelloWorld);
+        assert!(serde_json::from_str::<EdgeFunctionTemplate>("\"unknown\"").is_err());
+    }
+}
@@ -18,6 +18,18 @@ pub const DEFAULT_ROLES: &[&str] = &[
     // Note: pg_* and supabase* roles are already filtered in queries.rs
 ];
 
+/// Roles whose grants Supabase sets up on its own (default privileges, ownership).
+/// Remote grants to these roles are ignored when diffing grants unless the local
+/// definition grants to the role too. Projects can override the list.
+pub const IGNORED_GRANT_ROLES: &[&str] = &[
+    "anon",
+    "authenticated",
+    "service_role",
+    "public",
+    "postgres",
+    "supabase_admin",
+];
+
 /// Default extensions that exist in every Supabase project.
 /// These should be excluded from diff operations to prevent
 /// attempting to drop these system-managed extensions.
@@ -80,6 +92,36 @@ pub fn is_excluded_schema(name: &str) -> bool {
         || name == "public" // public is a special case - exists by default
 }
 
+/// Check if a schema and the objects in it are owned by Postgres or Supabase, i.e. an
+/// excluded schema other than `public`. Such objects are never dropped.
+pub fn is_supabase_managed_schema(name: &str) -> bool {
+    name != "public" && is_excluded_schema(name)
+}
+
+/// Check if a schema comment is one Postgres or Supabase sets: any comment on a
+/// Supabase-managed schema, or the stock comment on `public`
+pub fn is_builtin_schema_comment(schema: &str, comment: Option<&str>) -> bool {
+    is_supabase_managed_schema(schema) || (schema == "public" && comment == Some("standard public schema"))
+}
+
+/// Roles Supabase sets up `ALTER DEFAULT PRIVILEGES` for in every project
+pub const DEFAULT_ACL_ROLES: &[&str] = &["postgres", "supabase_admin"];
+
+/// Grantees of the default privileges Supabase configures
+pub const DEFAULT_ACL_GRANTEES: &[&str] =
+    &["postgres", "anon", "authenticated", "service_role", "supabase_admin"];
+
+/// Check if a default privilege is one Supabase configures itself, i.e. granted by
+/// `postgres` or `supabase_admin` to the API roles globally or in `public`, or in a
+/// system schema. The same grant in a user schema was added by the user.
+pub fn is_builtin_default_acl(role: &str, schema: Option<&str>, grantee: &str) -> bool {
+    match schema {
+        Some(s) if is_supabase_managed_schema(s) => true,
+        Some(s) if s != "public" => false,
+        _ => DEFAULT_ACL_ROLES.contains(&role) && DEFAULT_ACL_GRANTEES.contains(&grantee),
+    }
+}
+
 /// Generate a SQL-formatted list of excluded schemas for use in queries.
 /// Returns format: 'auth', 'storage', 'extensions', ...
 pub fn excluded_schemas_sql_list() -> String {
@@ -131,6 +173,22 @@ mod tests {
         assert!(!is_excluded_schema("app"));
     }
 
+    #[test]
+    fn test_is_builtin_default_acl() {
+        assert!(is_builtin_default_acl("postgres", Some("public"), "anon"));
+        assert!(is_builtin_default_acl("supabase_admin", None, "service_role"));
+        assert!(is_builtin_default_acl("my_role", Some("storage"), "my_role"));
+        assert!(!is_builtin_default_acl("postgres", Some("public"), "reporting"));
+        assert!(!is_builtin_default_acl("app_owner", Some("app"), "anon"));
+        assert!(is_builtin_schema_comment("public", Some("standard public schema")));
+        assert!(is_builtin_schema_comment("auth", Some("auth schema")));
+        assert!(!is_builtin_schema_comment("public", Some("Application tables")));
+        assert!(!is_builtin_schema_comment("app", Some("standard public schema")));
+        // Supabase only sets these up globally and in public
+        assert!(!is_builtin_default_acl("postgres", Some("app"), "anon"));
+        assert!(!is_builtin_default_acl("supabase_admin", Some("app"), "authenticated"));
+    }
+
     #[test]
     fn test_excluded_schemas_sql_list() {
         let sql_list = excluded_schemas_sql_list();
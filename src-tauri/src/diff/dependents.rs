@@ -0,0 +1,191 @@
+//! Objects that reference a table, view, function or type, so a drop can be checked
+//! for what it would break (or cascade to) before it runs.
+
+use super::{split_object_key, EnumChangeType, SchemaDiff};
+use crate::schema::{qualified_name, DbSchema};
+use regex::Regex;
+use serde::Serialize;
+
+/// An object that references the one being looked up
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Dependent {
+    /// Object kind, e.g. "view", "foreign_key" or "trigger"
+    pub kind: String,
+    /// Qualified name; constraints, triggers and columns are prefixed with their table
+    pub name: String,
+    /// How it references the object
+    pub reason: String,
+}
+
+/// The `OBJECT_KINDS` whose dependents `find_dependents` can look up
+pub const DEPENDENT_KINDS: &[&str] = &["table", "view", "function", "enum", "type", "domain"];
+
+/// Fail for a `kind` that isn't in `DEPENDENT_KINDS`
+pub fn check_dependent_kind(kind: &str) -> Result<(), String> {
+    if DEPENDENT_KINDS.contains(&kind) {
+        return Ok(());
+    }
+    Err(format!(
+        "Unknown object kind '{}', expected one of: {}",
+        kind,
+        DEPENDENT_KINDS.join(", ")
+    ))
+}
+
+/// Objects in `schema` referencing the object `kind`/`name`. `kind` is one of
+/// `DEPENDENT_KINDS`; `name` may be bare (resolved in any schema), `schema.name` or a
+/// `DbSchema` key. Functions and views are matched against their definitions by name,
+/// so a mention in a comment counts as a reference.
+pub fn find_dependents(schema: &DbSchema, kind: &str, name: &str) -> Result<Vec<Dependent>, String> {
+    check_dependent_kind(kind)?;
+    let (want_schema, want_name) = split_object_key(name);
+    let matches = |object_schema: &str, object_name: &str| {
+        object_name == want_name && want_schema.as_deref().map_or(true, |s| s == object_schema)
+    };
+    let mentioned = Regex::new(&format!(r#"(?i)(^|[^A-Za-z0-9_$]){}($|[^A-Za-z0-9_$])"#, regex::escape(&want_name))).unwrap();
+    let called = Regex::new(&format!(r#"(?i)(^|[^A-Za-z0-9_$]){}"?\s*\("#, regex::escape(&want_name))).unwrap();
+
+    let mut dependents = Vec::new();
+    let mut push = |kind: &str, name: String, reason: String| {
+        dependents.push(Dependent { kind: kind.to_string(), name, reason });
+    };
+
+    match kind {
+        "table" | "view" => {
+            for table in schema.tables.values() {
+                let table_key = qualified_name(&table.schema, &table.table_name);
+                for fk in &table.foreign_keys {
+                    if matches(&fk.foreign_schema, &fk.foreign_table) && !matches(&table.schema, &table.table_name) {
+                        push(
+                            "foreign_key",
+                            format!("{}.{}", table_key, fk.constraint_name),
+                            format!("references {}", qualified_name(&fk.foreign_schema, &fk.foreign_table)),
+                        );
+                    }
+                }
+            }
+            for view in schema.views.values() {
+                if matches(&view.schema, &view.name) {
+                    continue;
+                }
+                if super::validate::view_relations(view).iter().any(|(s, n)| matches(s, n)) {
+                    push("view", qualified_name(&view.schema, &view.name), format!("selects from {}", want_name));
+                }
+            }
+            for rule in schema.rules.values() {
+                let (rule_schema, relation) = split_object_key(&rule.relation);
+                if matches(rule_schema.as_deref().unwrap_or(&rule.schema), &relation) {
+                    push("rule", rule.key(), format!("is defined on {}", want_name));
+                }
+            }
+            for function in schema.functions.values() {
+                if mentioned.is_match(&function.definition) {
+                    push("function", qualified_name(&function.schema, &function.name), format!("mentions {}", want_name));
+                }
+            }
+        }
+        "function" => {
+            for table in schema.tables.values() {
+                let table_key = qualified_name(&table.schema, &table.table_name);
+                for trigger in &table.triggers {
+                    let (trigger_schema, function) = split_object_key(&trigger.function_name);
+                    if matches(trigger_schema.as_deref().unwrap_or("public"), &function) {
+                        push("trigger", format!("{}.{}", table_key, trigger.name), format!("executes {}", want_name));
+                    }
+                }
+                for column in table.columns.values() {
+                    if column.column_default.as_deref().is_some_and(|d| called.is_match(d)) {
+                        push("column", format!("{}.{}", table_key, column.column_name), format!("defaults to {}()", want_name));
+                    }
+                }
+            }
+            for view in schema.views.values() {
+                if called.is_match(&view.definition) {
+                    push("view", qualified_name(&view.schema, &view.name), format!("calls {}", want_name));
+                }
+            }
+            for function in schema.functions.values() {
+                if !matches(&function.schema, &function.name) && called.is_match(&function.definition) {
+                    push("function", qualified_name(&function.schema, &function.name), format!("calls {}", want_name));
+                }
+            }
+        }
+        "enum" | "type" | "domain" => {
+            let is_type = |type_name: &str| {
+                let (type_schema, base) = split_object_key(type_name.trim_end_matches("[]").trim_start_matches('_'));
+                matches(type_schema.as_deref().unwrap_or(want_schema.as_deref().unwrap_or("public")), &base)
+            };
+            for table in schema.tables.values() {
+                let table_key = qualified_name(&table.schema, &table.table_name);
+                for column in table.columns.values() {
+                    let uses = column.enum_name.as_deref().is_some_and(&is_type)
+                        || is_type(&column.udt_name)
+                        || is_type(&column.data_type);
+                    if uses {
+                        push("column", format!("{}.{}", table_key, column.column_name), format!("has type {}", want_name));
+                    }
+                }
+            }
+            for function in schema.functions.values() {
+                if function.args.iter().any(|a| is_type(&a.type_)) || is_type(&function.return_type) {
+                    push("function", qualified_name(&function.schema, &function.name), format!("takes or returns {}", want_name));
+                }
+            }
+            for composite in schema.composite_types.values() {
+                if composite.attributes.iter().any(|a| is_type(&a.data_type)) {
+                    push("type", qualified_name(&composite.schema, &composite.name), format!("has an attribute of type {}", want_name));
+                }
+            }
+            for domain in schema.domains.values() {
+                if is_type(&domain.base_type) {
+                    push("domain", qualified_name(&domain.schema, &domain.name), format!("is based on {}", want_name));
+                }
+            }
+        }
+        _ => unreachable!("checked against DEPENDENT_KINDS"),
+    }
+
+    dependents.sort_by(|a, b| (&a.kind, &a.name).cmp(&(&b.kind, &b.name)));
+    Ok(dependents)
+}
+
+/// Objects on `remote` that depend on something `diff` drops and aren't dropped
+/// themselves, one line each for the destructive-change confirmation
+pub fn dependents_of_drops(diff: &SchemaDiff, remote: &DbSchema) -> Vec<String> {
+    let dropped_enums = diff
+        .enum_changes
+        .iter()
+        .filter(|c| c.type_ == EnumChangeType::Drop)
+        .map(|c| &c.name);
+    let drops: Vec<(&str, &String)> = diff
+        .tables_to_drop
+        .iter()
+        .map(|k| ("table", k))
+        .chain(diff.views_to_drop.iter().map(|k| ("view", k)))
+        .chain(diff.functions_to_drop.iter().map(|k| ("function", k)))
+        .chain(dropped_enums.map(|k| ("enum", k)))
+        .chain(diff.composite_types_to_drop.iter().map(|k| ("type", k)))
+        .chain(diff.domains_to_drop.iter().map(|k| ("domain", k)))
+        .collect();
+
+    // Constraints, triggers, columns and rules go away with their table
+    let is_dropped = |dependent: &Dependent| {
+        drops.iter().any(|(_, key)| {
+            let key = key.split('(').next().unwrap_or(key);
+            dependent.name == key || dependent.name.starts_with(&format!("{}.", key))
+        })
+    };
+
+    let mut lines = Vec::new();
+    for (kind, key) in &drops {
+        for dependent in find_dependents(remote, kind, key).unwrap_or_default() {
+            if !is_dropped(&dependent) {
+                lines.push(format!(
+                    "! {} {} depends on dropped {} {} ({})",
+                    dependent.kind, dependent.name, kind, key, dependent.reason
+                ));
+            }
+        }
+    }
+    lines
+}
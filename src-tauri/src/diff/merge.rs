@@ -0,0 +1,123 @@
+//! Three-way merge of a local schema and a freshly pulled remote schema against the
+//! schema of the previous pull, so pulling keeps local edits that haven't been pushed.
+
+use super::compute_diff;
+use crate::schema::DbSchema;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// An object changed both locally and remotely since the previous pull
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergeConflict {
+    /// Object kind, e.g. "table" or "function"
+    pub kind: String,
+    pub name: String,
+}
+
+impl std::fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.kind, self.name)
+    }
+}
+
+/// Merged schema plus the objects both sides changed. Conflicting objects keep
+/// their local definition.
+#[derive(Debug)]
+pub struct MergeResult {
+    pub schema: DbSchema,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Merge object by object: a side that left an object as it was in `base` takes the
+/// other side's version (including a drop), and an object both sides changed differently
+/// is a conflict. Objects are compared with the diff engine, so formatting differences
+/// between parsed and introspected definitions don't count as changes.
+pub fn merge_schemas(base: &DbSchema, local: &DbSchema, remote: &DbSchema) -> MergeResult {
+    let mut merged = DbSchema::new();
+    let mut conflicts = Vec::new();
+
+    macro_rules! merge_kind {
+        ($field:ident, $kind:literal) => {
+            merged.$field = merge_objects(
+                $kind,
+                &base.$field,
+                &local.$field,
+                &remote.$field,
+                |schema, key, object| {
+                    schema.$field.insert(key, object);
+                },
+                &mut conflicts,
+            );
+        };
+    }
+    merge_kind!(tables, "table");
+    merge_kind!(enums, "enum");
+    merge_kind!(functions, "function");
+    merge_kind!(roles, "role");
+    merge_kind!(views, "view");
+    merge_kind!(sequences, "sequence");
+    merge_kind!(extensions, "extension");
+    merge_kind!(composite_types, "type");
+    merge_kind!(domains, "domain");
+    merge_kind!(rules, "rule");
+    merge_kind!(schema_comments, "schema comment");
+
+    merged.schema_grants = merge_entries(&base.schema_grants, &local.schema_grants, &remote.schema_grants);
+    merged.default_privileges =
+        merge_entries(&base.default_privileges, &local.default_privileges, &remote.default_privileges);
+    merged.default_acls = merge_entries(&base.default_acls, &local.default_acls, &remote.default_acls);
+
+    MergeResult { schema: merged, conflicts }
+}
+
+fn merge_objects<T: Clone>(
+    kind: &str,
+    base: &HashMap<String, T>,
+    local: &HashMap<String, T>,
+    remote: &HashMap<String, T>,
+    insert: impl Fn(&mut DbSchema, String, T),
+    conflicts: &mut Vec<MergeConflict>,
+) -> HashMap<String, T> {
+    let same = |key: &str, a: Option<&T>, b: Option<&T>| match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            let mut left = DbSchema::new();
+            let mut right = DbSchema::new();
+            insert(&mut left, key.to_string(), a.clone());
+            insert(&mut right, key.to_string(), b.clone());
+            compute_diff(&left, &right).is_empty()
+        }
+        _ => false,
+    };
+
+    let keys: BTreeSet<&String> = base.keys().chain(local.keys()).chain(remote.keys()).collect();
+    let mut merged = HashMap::new();
+    for key in keys {
+        let (b, l, r) = (base.get(key), local.get(key), remote.get(key));
+        let chosen = if same(key, l, b) {
+            r
+        } else if same(key, r, b) || same(key, l, r) {
+            l
+        } else {
+            conflicts.push(MergeConflict { kind: kind.to_string(), name: key.clone() });
+            l
+        };
+        if let Some(object) = chosen {
+            merged.insert(key.clone(), object.clone());
+        }
+    }
+    merged
+}
+
+/// Grant-like entries have no identity beyond their value: keep what both sides have,
+/// plus what either side added, minus what either side removed.
+fn merge_entries<T: Clone + PartialEq>(base: &[T], local: &[T], remote: &[T]) -> Vec<T> {
+    let mut merged: Vec<T> = Vec::new();
+    for entry in local.iter().chain(remote) {
+        let kept = (local.contains(entry) && remote.contains(entry)) || !base.contains(entry);
+        if kept && !merged.contains(entry) {
+            merged.push(entry.clone());
+        }
+    }
+    merged
+}
@@ -1,16 +1,26 @@
 use crate::defaults;
 use crate::schema::{
-    CompositeTypeInfo, DbSchema, DomainInfo, EnumInfo, ExtensionInfo, ForeignKeyInfo, FunctionGrant, FunctionInfo,
-    IndexInfo, ObjectGrant, PolicyInfo, RoleInfo, SequenceInfo, TableInfo, TriggerInfo, ViewInfo,
+    ColumnInfo, CompositeTypeInfo, DbSchema, DomainInfo, EnumInfo, ExtensionInfo, ForeignKeyInfo, FunctionGrant, FunctionInfo,
+    IndexInfo, ObjectGrant, PartitionInfo, PolicyInfo, RoleInfo, SequenceInfo, TableInfo, TriggerInfo, ViewInfo,
+    qualified_name,
 };
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+pub mod dependents;
+pub mod merge;
 pub mod objects;
 pub mod summary;
 pub mod tables;
 pub mod utils;
+pub mod validate;
 
-#[derive(Debug)]
+/// Object kinds accepted by `SchemaDiff::retain_object` and `SchemaDiff::clear_kind`
+pub const OBJECT_KINDS: &[&str] = &[
+    "table", "view", "function", "sequence", "enum", "type", "domain", "extension", "role", "rule", "schema",
+];
+
+#[derive(Debug, Clone)]
 pub struct SchemaDiff {
     pub tables_to_create: Vec<String>,
     pub tables_to_drop: Vec<String>,
@@ -41,36 +51,80 @@ pub struct SchemaDiff {
     pub schema_grants_to_drop: Vec<crate::schema::SchemaGrant>,
     pub default_privileges_to_create: Vec<crate::schema::DefaultPrivilege>,
     pub default_privileges_to_drop: Vec<crate::schema::DefaultPrivilege>,
+    pub default_acls_to_create: Vec<crate::schema::DefaultAcl>,
+    pub default_acls_to_drop: Vec<crate::schema::DefaultAcl>,
+    /// New and changed rules, applied with `CREATE OR REPLACE RULE`
+    pub rules_to_create: Vec<crate::schema::RuleInfo>,
+    pub rules_to_drop: Vec<crate::schema::RuleInfo>,
+    /// (schema, comment) for schemas whose comment the local files set; `None` clears it
+    pub schema_comments_to_set: Vec<(String, Option<String>)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TableDiff {
     pub columns_to_add: Vec<String>,
     pub columns_to_drop: Vec<String>,
     pub columns_to_modify: Vec<ColumnModification>,
+    /// (old_name, new_name) of columns renamed in place, from an accepted rename candidate
+    pub columns_to_rename: Vec<(String, String)>,
+    /// A single dropped column and a single added one that look like a rename.
+    /// Left as drop + add unless `DiffOptions::accept_renames` is set.
+    pub column_rename_candidate: Option<(String, String)>,
     pub rls_change: Option<bool>,
     pub comment_change: Option<Option<String>>,
+    pub storage_params_change: Option<(Vec<(String, String)>, Vec<(String, String)>)>, // (old, new)
+    pub tablespace_change: Option<Option<String>>,
+    pub unlogged_change: Option<bool>,
+    /// Partitions missing remotely; remote-only partitions are never dropped
+    pub partitions_to_create: Vec<PartitionInfo>,
+    /// (remote, local) partition key when the table became partitioned, stopped being
+    /// partitioned or was re-keyed. Postgres can't alter this in place, so it's reported
+    /// and blocks the push rather than being generated.
+    pub partition_strategy_change: Option<(Option<String>, Option<String>)>,
+    /// (remote, local) partitions whose bound changed, re-attached with the new bound
+    pub partition_bounds_to_change: Vec<(PartitionInfo, PartitionInfo)>,
+    pub object_comments_to_set: Vec<ObjectCommentChange>,
     pub policies_to_create: Vec<PolicyInfo>,
     pub policies_to_drop: Vec<PolicyInfo>,
     pub triggers_to_create: Vec<TriggerInfo>,
     pub triggers_to_drop: Vec<TriggerInfo>,
     pub indexes_to_create: Vec<IndexInfo>,
     pub indexes_to_drop: Vec<IndexInfo>,
+    /// (old_name, new_name) of indexes renamed without any change to their definition
+    pub indexes_to_rename: Vec<(String, String)>,
     pub check_constraints_to_create: Vec<crate::schema::CheckConstraintInfo>,
     pub check_constraints_to_drop: Vec<crate::schema::CheckConstraintInfo>,
+    pub exclusion_constraints_to_create: Vec<crate::schema::ExclusionConstraintInfo>,
+    pub exclusion_constraints_to_drop: Vec<crate::schema::ExclusionConstraintInfo>,
     pub foreign_keys_to_create: Vec<ForeignKeyInfo>,
     pub foreign_keys_to_drop: Vec<ForeignKeyInfo>,
     pub grants_to_create: Vec<crate::schema::ObjectGrant>,
     pub grants_to_drop: Vec<crate::schema::ObjectGrant>,
 }
 
-#[derive(Debug)]
+/// Table-owned objects that carry their own comment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentTarget {
+    Index,
+    Constraint,
+    Policy,
+}
+
+/// Set (or clear, when `comment` is `None`) the comment on an index, constraint or policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectCommentChange {
+    pub target: CommentTarget,
+    pub name: String,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
 pub struct ColumnModification {
     pub column_name: String,
     pub changes: ColumnChangeDetail,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ColumnChangeDetail {
     pub type_change: Option<(String, String)>,
     pub nullable_change: Option<(bool, bool)>,
@@ -81,28 +135,29 @@ pub struct ColumnChangeDetail {
     pub comment_change: Option<(Option<String>, Option<String>)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EnumChange {
+    /// The `DbSchema.enums` key, already qualified and quoted: `"public"."status"`
     pub name: String,
     pub type_: EnumChangeType,
     pub values_to_add: Option<Vec<String>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum EnumChangeType {
     Create,
     Drop,
     AddValue,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CompositeTypeDiff {
     pub attributes_to_add: Vec<crate::schema::CompositeTypeAttribute>,
     pub attributes_to_drop: Vec<crate::schema::CompositeTypeAttribute>,
     pub attributes_to_alter: Vec<(crate::schema::CompositeTypeAttribute, crate::schema::CompositeTypeAttribute)>, // (old, new)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DomainDiff {
     pub default_change: Option<(Option<String>, Option<String>)>, // (old, new)
     pub not_null_change: Option<(bool, bool)>, // (old, new)
@@ -110,7 +165,199 @@ pub struct DomainDiff {
     pub constraints_to_drop: Vec<crate::schema::DomainCheckConstraint>,
 }
 
+/// A dropped and an added column of `table` (a `table_changes` key) that look like a rename
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ColumnRenameCandidate {
+    pub table: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Options that narrow which objects `compute_diff_with_options` considers.
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Only diff objects living in these schemas (every schema when `None`)
+    pub watched_schemas: Option<Vec<String>>,
+    /// Glob patterns of tables to manage; when non-empty, all other tables are ignored.
+    /// Patterns containing a `.` match `schema.table`, otherwise just the table name.
+    pub table_allowlist: Vec<String>,
+    /// Glob patterns of tables to leave alone, applied after the allowlist
+    pub table_denylist: Vec<String>,
+    /// Additive push: never drop objects that only exist remotely
+    pub merge: bool,
+    /// Rename candidates to apply as `RENAME COLUMN` instead of drop + add; other
+    /// candidates stay a drop + add
+    pub accepted_renames: Vec<ColumnRenameCandidate>,
+    /// Roles whose remote grants are implicit (see `defaults::IGNORED_GRANT_ROLES`, used when `None`)
+    pub ignored_grant_roles: Option<Vec<String>>,
+}
+
+impl DiffOptions {
+    fn grant_roles(&self) -> Vec<String> {
+        match &self.ignored_grant_roles {
+            Some(roles) => roles.clone(),
+            None => default_ignored_grant_roles(),
+        }
+    }
+
+    fn is_unrestricted(&self) -> bool {
+        self.watched_schemas.is_none() && self.table_allowlist.is_empty() && self.table_denylist.is_empty()
+    }
+
+    fn includes_schema(&self, schema: &str) -> bool {
+        match &self.watched_schemas {
+            Some(schemas) => schemas.iter().any(|s| s == schema),
+            None => true,
+        }
+    }
+
+    pub fn includes_table(&self, schema: &str, table: &str) -> bool {
+        if !self.includes_schema(schema) {
+            return false;
+        }
+
+        let qualified = format!("{}.{}", schema, table);
+        let matches = |pattern: &String| {
+            if pattern.contains('.') {
+                utils::glob_match(pattern, &qualified)
+            } else {
+                utils::glob_match(pattern, table)
+            }
+        };
+
+        if !self.table_allowlist.is_empty() && !self.table_allowlist.iter().any(matches) {
+            return false;
+        }
+        !self.table_denylist.iter().any(matches)
+    }
+
+    /// Sequences owned by an excluded table (SERIAL etc.) are excluded along with it
+    fn includes_sequence(&self, seq: &SequenceInfo) -> bool {
+        if !self.includes_schema(&seq.schema) {
+            return false;
+        }
+        let Some(owned_by) = &seq.owned_by else {
+            return true;
+        };
+        let parts: Vec<String> = owned_by.split('.').map(|p| p.trim_matches('"').to_string()).collect();
+        match parts.len() {
+            2 => self.includes_table(&seq.schema, &parts[0]),
+            n if n >= 3 => self.includes_table(&parts[n - 3], &parts[n - 2]),
+            _ => true,
+        }
+    }
+}
+
 pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
+    diff_schemas(remote, local, &default_ignored_grant_roles())
+}
+
+pub fn default_ignored_grant_roles() -> Vec<String> {
+    defaults::IGNORED_GRANT_ROLES.iter().map(|r| r.to_string()).collect()
+}
+
+/// Compute a diff restricted by `options`. Objects outside the watched scope are
+/// removed from both sides first, so they are never created, altered or dropped.
+pub fn compute_diff_with_options(remote: &DbSchema, local: &DbSchema, options: &DiffOptions) -> SchemaDiff {
+    let grant_roles = options.grant_roles();
+    let mut diff = if options.is_unrestricted() {
+        diff_schemas(remote, local, &grant_roles)
+    } else {
+        diff_schemas(&scope_schema(remote, options), &scope_schema(local, options), &grant_roles)
+    };
+    for rename in &options.accepted_renames {
+        let columns = remote
+            .tables
+            .get(&rename.table)
+            .and_then(|t| t.columns.get(&rename.from))
+            .zip(local.tables.get(&rename.table).and_then(|t| t.columns.get(&rename.to)));
+        if let (Some(table_diff), Some((old_col, new_col))) = (diff.table_changes.get_mut(&rename.table), columns) {
+            table_diff.accept_column_rename(rename, old_col, new_col);
+        }
+    }
+    if options.merge {
+        diff.retain_additive(local);
+    }
+    diff
+}
+
+/// `"public"."f"(integer)`, `public.users` or `users` -> `(Some("public"), "users")`.
+/// Arguments of function signatures are ignored.
+fn split_object_key(key: &str) -> (Option<String>, String) {
+    let without_args = key.split('(').next().unwrap_or(key);
+    let parts: Vec<String> = without_args.split('.').map(|p| p.trim().trim_matches('"').to_string()).collect();
+    match parts.as_slice() {
+        [schema, name] => (Some(schema.clone()), name.clone()),
+        _ => (None, parts.join(".")),
+    }
+}
+
+/// Return a copy of `schema` containing only the objects `options` allows.
+fn scope_schema(schema: &DbSchema, options: &DiffOptions) -> DbSchema {
+    let mut scoped = schema.clone();
+    scoped.tables.retain(|_, t| options.includes_table(&t.schema, &t.table_name));
+    scoped.enums.retain(|_, e| options.includes_schema(&e.schema));
+    scoped.functions.retain(|_, f| options.includes_schema(&f.schema));
+    scoped.views.retain(|_, v| options.includes_schema(&v.schema));
+    scoped.sequences.retain(|_, s| options.includes_sequence(s));
+    scoped.composite_types.retain(|_, c| options.includes_schema(&c.schema));
+    scoped.domains.retain(|_, d| options.includes_schema(&d.schema));
+    scoped.rules.retain(|_, r| options.includes_schema(&r.schema));
+    scoped.schema_grants.retain(|g| options.includes_schema(&g.schema));
+    scoped.default_privileges.retain(|p| options.includes_schema(&p.schema));
+    scoped.schema_comments.retain(|s, _| options.includes_schema(s));
+    scoped
+        .default_acls
+        .retain(|a| a.schema.as_deref().map_or(true, |s| options.includes_schema(s)));
+    scoped
+}
+
+/// Remote objects in schemas Supabase manages (`storage`, `realtime`, `vault`, ...) that the
+/// local schema doesn't define are left out of the diff, so one leaking into introspection
+/// is never dropped. The same goes for triggers executing functions in those schemas,
+/// such as database webhooks calling `supabase_functions.http_request`.
+fn protect_managed_objects<'a>(remote: &'a DbSchema, local: &DbSchema) -> Cow<'a, DbSchema> {
+    let managed = |schema: &str| defaults::is_supabase_managed_schema(schema);
+    let managed_key = |key: &str| split_object_key(key).0.is_some_and(|s| managed(&s));
+    let managed_trigger = |trigger: &TriggerInfo| managed_key(&trigger.function_name);
+
+    let leaks = remote.tables.values().any(|t| managed(&t.schema) || t.triggers.iter().any(managed_trigger))
+        || remote.enums.values().any(|e| managed(&e.schema))
+        || remote.functions.values().any(|f| managed(&f.schema))
+        || remote.views.values().any(|v| managed(&v.schema))
+        || remote.sequences.values().any(|s| managed(&s.schema))
+        || remote.composite_types.values().any(|c| managed(&c.schema))
+        || remote.domains.values().any(|d| managed(&d.schema))
+        || remote.rules.values().any(|r| managed(&r.schema));
+    if !leaks {
+        return Cow::Borrowed(remote);
+    }
+
+    let mut protected = remote.clone();
+    protected.tables.retain(|k, t| !managed(&t.schema) || local.tables.contains_key(k));
+    protected.enums.retain(|k, e| !managed(&e.schema) || local.enums.contains_key(k));
+    protected.functions.retain(|k, f| !managed(&f.schema) || local.functions.contains_key(k));
+    protected.views.retain(|k, v| !managed(&v.schema) || local.views.contains_key(k));
+    protected.sequences.retain(|k, s| !managed(&s.schema) || local.sequences.contains_key(k));
+    protected.composite_types.retain(|k, c| !managed(&c.schema) || local.composite_types.contains_key(k));
+    protected.domains.retain(|k, d| !managed(&d.schema) || local.domains.contains_key(k));
+    protected.rules.retain(|k, r| !managed(&r.schema) || local.rules.contains_key(k));
+    for (key, table) in protected.tables.iter_mut() {
+        let local_triggers: Vec<&str> = local
+            .tables
+            .get(key)
+            .map(|t| t.triggers.iter().map(|tr| tr.name.as_str()).collect())
+            .unwrap_or_default();
+        table
+            .triggers
+            .retain(|tr| !managed_trigger(tr) || local_triggers.contains(&tr.name.as_str()));
+    }
+    Cow::Owned(protected)
+}
+
+/// `ignored_grant_roles`: roles whose remote grants only count where local grants to them too
+fn diff_schemas(remote: &DbSchema, local: &DbSchema, ignored_grant_roles: &[String]) -> SchemaDiff {
+    let remote: &DbSchema = &protect_managed_objects(remote, local);
     let mut diff = SchemaDiff {
         tables_to_create: vec![],
         tables_to_drop: vec![],
@@ -141,6 +388,11 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     // Tables
@@ -149,7 +401,7 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
             diff.tables_to_create.push(name.clone());
         } else {
             let remote_table = remote.tables.get(name).unwrap();
-            let table_diff = tables::compute_table_diff(remote_table, local_table);
+            let table_diff = tables::compute_table_diff_with_grant_roles(remote_table, local_table, ignored_grant_roles);
             if !table_diff.is_empty() {
                 diff.table_changes.insert(name.clone(), table_diff);
             }
@@ -290,13 +542,16 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
             let def_changed = local_def_normalized != remote_def_normalized;
             let return_changed = local_return_normalized != remote_return_normalized;
             let lang_changed = local_func.language.to_lowercase() != remote_func.language.to_lowercase();
+            let volatility_changed = normalize_volatility(&local_func.volatility) != normalize_volatility(&remote_func.volatility);
+            let strict_changed = local_func.is_strict != remote_func.is_strict;
             let security_definer_changed = local_func.security_definer != remote_func.security_definer;
             let config_params_changed = !config_params_match(&local_func.config_params, &remote_func.config_params);
             // Only compare grants if local schema explicitly defines grants
             // (skip if local has no grants, since users likely haven't added GRANT statements to their schema files)
-            let grants_changed = !local_func.grants.is_empty() && !grants_match(&local_func.grants, &remote_func.grants);
+            let grants_changed = !local_func.grants.is_empty()
+                && !grants_match(&local_func.grants, &remote_func.grants, ignored_grant_roles);
             
-            if def_changed || return_changed || lang_changed || security_definer_changed || config_params_changed || grants_changed {
+            if def_changed || return_changed || lang_changed || volatility_changed || strict_changed || security_definer_changed || config_params_changed || grants_changed {
                 eprintln!("=== FUNCTION DIFF DEBUG for {} ===", name);
                 if def_changed {
                     eprintln!("  Definition changed:");
@@ -309,6 +564,12 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
                 if lang_changed {
                     eprintln!("  Language changed: '{}' vs '{}'", local_func.language, remote_func.language);
                 }
+                if volatility_changed {
+                    eprintln!("  Volatility changed: {:?} vs {:?}", local_func.volatility, remote_func.volatility);
+                }
+                if strict_changed {
+                    eprintln!("  Strict changed: {} vs {}", local_func.is_strict, remote_func.is_strict);
+                }
                 if security_definer_changed {
                     eprintln!("  Security definer changed: {} vs {}", local_func.security_definer, remote_func.security_definer);
                 }
@@ -338,7 +599,7 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
             diff.views_to_create.push(local_view.clone());
         } else {
             let remote_view = remote.views.get(name).unwrap();
-            if objects::views_differ(local_view, remote_view) {
+            if objects::views_differ(local_view, remote_view, ignored_grant_roles) {
                 diff.views_to_update.push(local_view.clone());
             }
         }
@@ -359,7 +620,7 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
             diff.sequences_to_create.push(local_seq.clone());
         } else {
             let remote_seq = remote.sequences.get(name).unwrap();
-            if objects::sequences_differ(local_seq, remote_seq) {
+            if objects::sequences_differ(local_seq, remote_seq, ignored_grant_roles) {
                 diff.sequences_to_update.push(local_seq.clone());
             }
         }
@@ -531,14 +792,146 @@ pub fn compute_diff(remote: &DbSchema, local: &DbSchema) -> SchemaDiff {
         }
     }
 
+    // ALTER DEFAULT PRIVILEGES. Like schema-wide grants, remote entries are only
+    // revoked in schemas the local files set default privileges for.
+    let is_builtin = |a: &crate::schema::DefaultAcl| {
+        defaults::is_builtin_default_acl(&a.role, a.schema.as_deref(), &a.grantee)
+    };
+    for local_acl in local.default_acls.iter().filter(|a| !is_builtin(a)) {
+        if !remote.default_acls.contains(local_acl) {
+            diff.default_acls_to_create.push(local_acl.clone());
+        }
+    }
+    for remote_acl in remote.default_acls.iter().filter(|a| !is_builtin(a)) {
+        let schema_managed = local.default_acls.iter().any(|a| a.schema == remote_acl.schema);
+        if schema_managed && !local.default_acls.contains(remote_acl) {
+            diff.default_acls_to_drop.push(remote_acl.clone());
+        }
+    }
+
+    // Rules are compared on their definition text
+    for (key, local_rule) in &local.rules {
+        let changed = remote.rules.get(key).map_or(true, |remote_rule| {
+            utils::normalize_rule_definition(&local_rule.definition)
+                != utils::normalize_rule_definition(&remote_rule.definition)
+        });
+        if changed {
+            diff.rules_to_create.push(local_rule.clone());
+        }
+    }
+    for (key, remote_rule) in &remote.rules {
+        if !local.rules.contains_key(key) {
+            diff.rules_to_drop.push(remote_rule.clone());
+        }
+    }
+    // A view the migration drops and recreates (an updated materialized view, or one
+    // both dropped and created) loses its rules with it, so they're created again
+    let recreated_views: Vec<(&str, &str)> = diff
+        .views_to_update
+        .iter()
+        .filter(|v| v.is_materialized)
+        .chain(
+            diff.views_to_create
+                .iter()
+                .filter(|v| diff.views_to_drop.contains(&qualified_name(&v.schema, &v.name))),
+        )
+        .map(|v| (v.schema.as_str(), v.name.as_str()))
+        .collect();
+    let mut recreated_rules: Vec<crate::schema::RuleInfo> = local
+        .rules
+        .values()
+        .filter(|r| recreated_views.contains(&(r.schema.as_str(), r.relation.as_str())))
+        .filter(|r| !diff.rules_to_create.iter().any(|c| c.key() == r.key()))
+        .cloned()
+        .collect();
+    diff.rules_to_create.append(&mut recreated_rules);
+
+    // Schemas aren't modelled, so a remote comment is only changed when the local
+    // files comment on that schema
+    for (schema, comment) in &local.schema_comments {
+        if defaults::is_builtin_schema_comment(schema, comment.as_deref()) {
+            continue;
+        }
+        if remote.schema_comments.get(schema).cloned().flatten() != *comment {
+            diff.schema_comments_to_set.push((schema.clone(), comment.clone()));
+        }
+    }
+
+    // Local files may add to a managed table (e.g. policies on `storage.objects`),
+    // but never take away what Supabase put there
+    for (key, table_diff) in diff.table_changes.iter_mut() {
+        if split_object_key(key).0.is_some_and(|s| defaults::is_supabase_managed_schema(&s)) {
+            table_diff.retain_additive();
+        }
+    }
+    diff.table_changes.retain(|_, table_diff| !table_diff.is_empty());
+
+    diff.sort();
     diff
 }
 
 impl TableDiff {
+    /// Order everything built from `HashMap` iteration, adding columns in table order
+    pub(crate) fn sort(&mut self, local: &TableInfo) {
+        let position = |name: &String| {
+            local.columns.get(name).and_then(|c| c.ordinal_position).unwrap_or(i32::MAX)
+        };
+        self.columns_to_add.sort_by(|a, b| position(a).cmp(&position(b)).then_with(|| a.cmp(b)));
+        self.columns_to_drop.sort();
+        self.columns_to_modify.sort_by(|a, b| a.column_name.cmp(&b.column_name));
+        self.policies_to_create.sort_by(|a, b| a.name.cmp(&b.name));
+        self.policies_to_drop.sort_by(|a, b| a.name.cmp(&b.name));
+        self.triggers_to_create.sort_by(|a, b| a.name.cmp(&b.name));
+        self.triggers_to_drop.sort_by(|a, b| a.name.cmp(&b.name));
+        self.indexes_to_create.sort_by(|a, b| a.index_name.cmp(&b.index_name));
+        self.indexes_to_drop.sort_by(|a, b| a.index_name.cmp(&b.index_name));
+        self.foreign_keys_to_create.sort_by(|a, b| a.constraint_name.cmp(&b.constraint_name));
+        self.foreign_keys_to_drop.sort_by(|a, b| a.constraint_name.cmp(&b.constraint_name));
+    }
+
+    /// Replace the drop + add of the rename candidate with a rename, keeping the column's
+    /// data, if it's `rename`. Any other change between the two columns (collation,
+    /// comment, ...) is still applied to the renamed column.
+    fn accept_column_rename(&mut self, rename: &ColumnRenameCandidate, old_col: &ColumnInfo, new_col: &ColumnInfo) {
+        if self.column_rename_candidate.as_ref() != Some(&(rename.from.clone(), rename.to.clone())) {
+            return;
+        }
+        self.column_rename_candidate = None;
+        self.columns_to_drop.retain(|c| c != &rename.from);
+        self.columns_to_add.retain(|c| c != &rename.to);
+        self.columns_to_rename.push((rename.from.clone(), rename.to.clone()));
+        if let Some(changes) = tables::column_changes(old_col, new_col) {
+            self.columns_to_modify.push(ColumnModification { column_name: rename.to.clone(), changes });
+        }
+    }
+
+    /// Keep only drops that are followed by re-creating an object of the same name
+    fn retain_additive(&mut self) {
+        // A changed generated column is dropped and re-added under the same name
+        let columns_to_add = &self.columns_to_add;
+        self.columns_to_drop.retain(|c| columns_to_add.contains(c));
+        self.column_rename_candidate = None;
+        self.grants_to_drop.clear();
+
+        let policies: Vec<&str> = self.policies_to_create.iter().map(|p| p.name.as_str()).collect();
+        self.policies_to_drop.retain(|p| policies.contains(&p.name.as_str()));
+        let triggers: Vec<&str> = self.triggers_to_create.iter().map(|t| t.name.as_str()).collect();
+        self.triggers_to_drop.retain(|t| triggers.contains(&t.name.as_str()));
+        let indexes: Vec<&str> = self.indexes_to_create.iter().map(|i| i.index_name.as_str()).collect();
+        self.indexes_to_drop.retain(|i| indexes.contains(&i.index_name.as_str()));
+        let checks: Vec<&str> = self.check_constraints_to_create.iter().map(|c| c.name.as_str()).collect();
+        self.check_constraints_to_drop.retain(|c| checks.contains(&c.name.as_str()));
+        let exclusions: Vec<&str> = self.exclusion_constraints_to_create.iter().map(|c| c.name.as_str()).collect();
+        self.exclusion_constraints_to_drop.retain(|c| exclusions.contains(&c.name.as_str()));
+        let foreign_keys: Vec<&str> = self.foreign_keys_to_create.iter().map(|f| f.constraint_name.as_str()).collect();
+        self.foreign_keys_to_drop.retain(|f| foreign_keys.contains(&f.constraint_name.as_str()));
+    }
+
     pub fn is_empty(&self) -> bool {
         self.columns_to_add.is_empty()
             && self.columns_to_drop.is_empty()
             && self.columns_to_modify.is_empty()
+            && self.columns_to_rename.is_empty()
             && self.rls_change.is_none()
             && self.policies_to_create.is_empty()
             && self.policies_to_drop.is_empty()
@@ -546,13 +939,23 @@ impl TableDiff {
             && self.triggers_to_drop.is_empty()
             && self.indexes_to_create.is_empty()
             && self.indexes_to_drop.is_empty()
+            && self.indexes_to_rename.is_empty()
             && self.check_constraints_to_create.is_empty()
             && self.check_constraints_to_drop.is_empty()
+            && self.exclusion_constraints_to_create.is_empty()
+            && self.exclusion_constraints_to_drop.is_empty()
             && self.foreign_keys_to_create.is_empty()
             && self.foreign_keys_to_drop.is_empty()
             && self.grants_to_create.is_empty()
             && self.grants_to_drop.is_empty()
             && self.comment_change.is_none()
+            && self.storage_params_change.is_none()
+            && self.tablespace_change.is_none()
+            && self.unlogged_change.is_none()
+            && self.partitions_to_create.is_empty()
+            && self.partition_strategy_change.is_none()
+            && self.partition_bounds_to_change.is_empty()
+            && self.object_comments_to_set.is_empty()
     }
 
     pub fn is_destructive(&self) -> bool {
@@ -582,6 +985,223 @@ impl TableDiff {
 }
 
 impl SchemaDiff {
+    /// Put every list in a stable order, so the same schemas always produce
+    /// byte-identical SQL regardless of `HashMap` iteration order
+    fn sort(&mut self) {
+        let function_key = |f: &FunctionInfo| {
+            let args: Vec<&str> = f.args.iter().map(|a| a.type_.as_str()).collect();
+            (f.schema.clone(), f.name.clone(), args.join(","))
+        };
+
+        self.tables_to_create.sort();
+        self.tables_to_drop.sort();
+        self.enum_changes.sort_by(|a, b| a.name.cmp(&b.name));
+        self.functions_to_create.sort_by_key(function_key);
+        self.functions_to_drop.sort();
+        self.functions_to_update.sort_by_key(function_key);
+        self.views_to_create.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.views_to_create = order_views_by_dependency(std::mem::take(&mut self.views_to_create));
+        self.views_to_drop.sort();
+        self.views_to_update.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.sequences_to_create.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.sequences_to_drop.sort();
+        self.sequences_to_update.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.extensions_to_create.sort_by(|a, b| a.name.cmp(&b.name));
+        self.extensions_to_drop.sort();
+        self.extensions_to_update.sort_by(|a, b| a.name.cmp(&b.name));
+        self.composite_types_to_create.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.composite_types_to_drop.sort();
+        self.composite_types_to_update.sort_by(|a, b| (&a.0.schema, &a.0.name).cmp(&(&b.0.schema, &b.0.name)));
+        self.domains_to_create.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.domains_to_drop.sort();
+        self.domains_to_update.sort_by(|a, b| (&a.0.schema, &a.0.name).cmp(&(&b.0.schema, &b.0.name)));
+        self.roles_to_create.sort_by(|a, b| a.name.cmp(&b.name));
+        self.roles_to_drop.sort();
+        self.roles_to_update.sort_by(|a, b| a.name.cmp(&b.name));
+        self.rules_to_create.sort_by_key(|r| r.key());
+        self.rules_to_drop.sort_by_key(|r| r.key());
+        self.schema_comments_to_set.sort();
+    }
+
+    /// Remove drops of objects that only exist remotely, keeping drops that are
+    /// half of a drop-and-recreate (e.g. a function whose signature changed).
+    pub fn retain_additive(&mut self, local: &DbSchema) {
+        self.tables_to_drop.clear();
+        self.enum_changes.retain(|c| c.type_ != EnumChangeType::Drop);
+        self.functions_to_drop.retain(|name| local.functions.contains_key(name));
+        self.views_to_drop.retain(|name| local.views.contains_key(name));
+        self.sequences_to_drop.retain(|name| local.sequences.contains_key(name));
+        self.extensions_to_drop.retain(|name| local.extensions.contains_key(name));
+        self.composite_types_to_drop.retain(|name| local.composite_types.contains_key(name));
+        self.domains_to_drop.retain(|name| local.domains.contains_key(name));
+        self.roles_to_drop.clear();
+        self.schema_grants_to_drop.clear();
+        self.default_privileges_to_drop.clear();
+        self.default_acls_to_drop.clear();
+        self.rules_to_drop.clear();
+
+        for (_, composite_diff) in self.composite_types_to_update.iter_mut() {
+            composite_diff.attributes_to_drop.clear();
+        }
+        for (_, domain_diff) in self.domains_to_update.iter_mut() {
+            let recreated: Vec<_> = domain_diff.constraints_to_add.iter().map(|c| c.name.clone()).collect();
+            domain_diff
+                .constraints_to_drop
+                .retain(|c| c.name.is_some() && recreated.contains(&c.name));
+        }
+
+        for table_diff in self.table_changes.values_mut() {
+            table_diff.retain_additive();
+        }
+        self.table_changes.retain(|_, table_diff| !table_diff.is_empty());
+    }
+
+    /// Keep only the changes to one object, to preview its SQL on its own. `kind` is one
+    /// of `OBJECT_KINDS`; `name` may be `users`, `public.users` or `"public"."users"`, and
+    /// a bare name matches the object in any schema.
+    pub fn retain_object(&mut self, kind: &str, name: &str) -> Result<(), String> {
+        if !OBJECT_KINDS.contains(&kind) {
+            return Err(format!(
+                "Unknown object kind '{}', expected one of: {}",
+                kind,
+                OBJECT_KINDS.join(", ")
+            ));
+        }
+        for other in OBJECT_KINDS.iter().filter(|k| **k != kind) {
+            self.clear_kind(other);
+        }
+
+        let (want_schema, want_name) = split_object_key(name);
+        let matches = |schema: &str, object: &str| {
+            object == want_name && want_schema.as_deref().map_or(true, |s| s == schema)
+        };
+        let key_matches = |key: &String| {
+            let (schema, object) = split_object_key(key);
+            matches(schema.as_deref().unwrap_or("public"), &object)
+        };
+
+        match kind {
+            "table" => {
+                self.tables_to_create.retain(key_matches);
+                self.tables_to_drop.retain(key_matches);
+                self.table_changes.retain(|k, _| key_matches(k));
+            }
+            "enum" => self.enum_changes.retain(|c| key_matches(&c.name)),
+            "function" => {
+                self.functions_to_create.retain(|f| matches(&f.schema, &f.name));
+                self.functions_to_drop.retain(key_matches);
+                self.functions_to_update.retain(|f| matches(&f.schema, &f.name));
+            }
+            "view" => {
+                self.views_to_create.retain(|v| matches(&v.schema, &v.name));
+                self.views_to_drop.retain(key_matches);
+                self.views_to_update.retain(|v| matches(&v.schema, &v.name));
+            }
+            "sequence" => {
+                self.sequences_to_create.retain(|s| matches(&s.schema, &s.name));
+                self.sequences_to_drop.retain(key_matches);
+                self.sequences_to_update.retain(|s| matches(&s.schema, &s.name));
+            }
+            "extension" => {
+                self.extensions_to_create.retain(|e| e.name == want_name);
+                self.extensions_to_drop.retain(|k| *k == want_name);
+                self.extensions_to_update.retain(|e| e.name == want_name);
+            }
+            "type" => {
+                self.composite_types_to_create.retain(|c| matches(&c.schema, &c.name));
+                self.composite_types_to_drop.retain(key_matches);
+                self.composite_types_to_update.retain(|(c, _)| matches(&c.schema, &c.name));
+            }
+            "domain" => {
+                self.domains_to_create.retain(|d| matches(&d.schema, &d.name));
+                self.domains_to_drop.retain(key_matches);
+                self.domains_to_update.retain(|(d, _)| matches(&d.schema, &d.name));
+            }
+            "role" => {
+                self.roles_to_create.retain(|r| r.name == want_name);
+                self.roles_to_drop.retain(|k| *k == want_name);
+                self.roles_to_update.retain(|r| r.name == want_name);
+            }
+            "rule" => {
+                self.rules_to_create.retain(|r| matches(&r.schema, &r.name));
+                self.rules_to_drop.retain(|r| matches(&r.schema, &r.name));
+            }
+            "schema" => {
+                // Only a schema's comment is named; grants and default privileges aren't
+                self.schema_comments_to_set.retain(|(s, _)| *s == want_name);
+                self.schema_grants_to_create.clear();
+                self.schema_grants_to_drop.clear();
+                self.default_privileges_to_create.clear();
+                self.default_privileges_to_drop.clear();
+                self.default_acls_to_create.clear();
+                self.default_acls_to_drop.clear();
+            }
+            _ => unreachable!("checked against OBJECT_KINDS"),
+        }
+        Ok(())
+    }
+
+    /// Drop every change to objects of one kind from `OBJECT_KINDS`
+    pub fn clear_kind(&mut self, kind: &str) {
+        match kind {
+            "table" => {
+                self.tables_to_create.clear();
+                self.tables_to_drop.clear();
+                self.table_changes.clear();
+            }
+            "view" => {
+                self.views_to_create.clear();
+                self.views_to_drop.clear();
+                self.views_to_update.clear();
+            }
+            "function" => {
+                self.functions_to_create.clear();
+                self.functions_to_drop.clear();
+                self.functions_to_update.clear();
+            }
+            "sequence" => {
+                self.sequences_to_create.clear();
+                self.sequences_to_drop.clear();
+                self.sequences_to_update.clear();
+            }
+            "enum" => self.enum_changes.clear(),
+            "type" => {
+                self.composite_types_to_create.clear();
+                self.composite_types_to_drop.clear();
+                self.composite_types_to_update.clear();
+            }
+            "domain" => {
+                self.domains_to_create.clear();
+                self.domains_to_drop.clear();
+                self.domains_to_update.clear();
+            }
+            "extension" => {
+                self.extensions_to_create.clear();
+                self.extensions_to_drop.clear();
+                self.extensions_to_update.clear();
+            }
+            "role" => {
+                self.roles_to_create.clear();
+                self.roles_to_drop.clear();
+                self.roles_to_update.clear();
+            }
+            "rule" => {
+                self.rules_to_create.clear();
+                self.rules_to_drop.clear();
+            }
+            "schema" => {
+                self.schema_comments_to_set.clear();
+                self.schema_grants_to_create.clear();
+                self.schema_grants_to_drop.clear();
+                self.default_privileges_to_create.clear();
+                self.default_privileges_to_drop.clear();
+                self.default_acls_to_create.clear();
+                self.default_acls_to_drop.clear();
+            }
+            _ => {}
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.tables_to_create.is_empty()
             && self.tables_to_drop.is_empty()
@@ -612,6 +1232,11 @@ impl SchemaDiff {
             && self.schema_grants_to_drop.is_empty()
             && self.default_privileges_to_create.is_empty()
             && self.default_privileges_to_drop.is_empty()
+            && self.default_acls_to_create.is_empty()
+            && self.default_acls_to_drop.is_empty()
+            && self.rules_to_create.is_empty()
+            && self.rules_to_drop.is_empty()
+            && self.schema_comments_to_set.is_empty()
     }
 
     pub fn is_destructive(&self) -> bool {
@@ -631,10 +1256,40 @@ impl SchemaDiff {
             }
         }
 
+        // Dropping or retyping an attribute rewrites every value stored with the type
+        for (_, composite_diff) in &self.composite_types_to_update {
+            if !composite_diff.attributes_to_drop.is_empty() || !composite_diff.attributes_to_alter.is_empty() {
+                return true;
+            }
+        }
+
         false
     }
 }
 
+/// Put views after the views they read from, keeping name order otherwise.
+/// Views in a dependency cycle are left in name order at the end.
+fn order_views_by_dependency(mut pending: Vec<ViewInfo>) -> Vec<ViewInfo> {
+    let mut ordered: Vec<ViewInfo> = Vec::with_capacity(pending.len());
+    let relations: HashMap<(String, String), Vec<(String, String)>> = pending
+        .iter()
+        .map(|v| ((v.schema.clone(), v.name.clone()), validate::view_relations(v)))
+        .collect();
+
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|view| {
+            relations[&(view.schema.clone(), view.name.clone())]
+                .iter()
+                .all(|(schema, name)| !pending.iter().any(|other| &other.schema == schema && &other.name == name))
+        });
+        match ready {
+            Some(index) => ordered.push(pending.remove(index)),
+            None => ordered.append(&mut pending),
+        }
+    }
+    ordered
+}
+
 fn compute_composite_type_diff(remote: &CompositeTypeInfo, local: &CompositeTypeInfo) -> CompositeTypeDiff {
     let mut diff = CompositeTypeDiff {
         attributes_to_add: vec![],
@@ -667,15 +1322,24 @@ fn compute_domain_diff(remote: &DomainInfo, local: &DomainInfo) -> DomainDiff {
         constraints_to_drop: vec![],
     };
 
-    if local.default_value != remote.default_value {
+    if utils::normalize_default_option(&local.default_value) != utils::normalize_default_option(&remote.default_value) {
         diff.default_change = Some((remote.default_value.clone(), local.default_value.clone()));
     }
     if local.is_not_null != remote.is_not_null {
         diff.not_null_change = Some((remote.is_not_null, local.is_not_null));
     }
     for local_con in &local.check_constraints {
-        if !remote.check_constraints.iter().any(|r| r.name == local_con.name) {
-            diff.constraints_to_add.push(local_con.clone());
+        match remote.check_constraints.iter().find(|r| r.name == local_con.name) {
+            None => diff.constraints_to_add.push(local_con.clone()),
+            // Same name, different expression: drop and re-add
+            Some(remote_con)
+                if utils::normalize_check_expression(&remote_con.expression)
+                    != utils::normalize_check_expression(&local_con.expression) =>
+            {
+                diff.constraints_to_drop.push(remote_con.clone());
+                diff.constraints_to_add.push(local_con.clone());
+            }
+            Some(_) => {}
         }
     }
     for remote_con in &remote.check_constraints {
@@ -687,18 +1351,16 @@ fn compute_domain_diff(remote: &DomainInfo, local: &DomainInfo) -> DomainDiff {
 }
 
 /// Compare two lists of object grants (table, view, sequence) for equality, ignoring order
-pub(crate) fn object_grants_match(local: &[crate::schema::ObjectGrant], remote: &[crate::schema::ObjectGrant]) -> bool {
-    // Filter implicit system grants from remote
-    let remote_filtered: Vec<&crate::schema::ObjectGrant> = remote.iter().filter(|r| {
-        let name = r.grantee.as_str();
-        if name == "postgres" || name == "supabase_admin" {
-            return false;
-        }
-        if name == "anon" || name == "service_role" || name == "public" || name == "authenticated" {
-            return local.iter().any(|l| l.grantee == name);
-        }
-        true
-    }).collect();
+pub(crate) fn object_grants_match(
+    local: &[crate::schema::ObjectGrant],
+    remote: &[crate::schema::ObjectGrant],
+    ignored_roles: &[String],
+) -> bool {
+    // Filter implicit grants from remote; they only count for roles local grants to as well
+    let remote_filtered: Vec<&crate::schema::ObjectGrant> = remote
+        .iter()
+        .filter(|r| !ignored_roles.contains(&r.grantee) || local.iter().any(|l| l.grantee == r.grantee))
+        .collect();
 
     if local.is_empty() && remote_filtered.is_empty() {
         return true;
@@ -715,24 +1377,14 @@ pub(crate) fn object_grants_match(local: &[crate::schema::ObjectGrant], remote:
 }
 
 /// Compare two lists of function grants for equality, ignoring order
-fn grants_match(local: &[FunctionGrant], remote: &[FunctionGrant]) -> bool {
-    // Filter implicit system grants from remote before comparison
-    let remote_filtered: Vec<&FunctionGrant> = remote.iter().filter(|r| {
-        let name = r.grantee.as_str();
-        
-        // Always ignore postgres (owner) and system roles
-        if name == "postgres" || name == "supabase_admin" {
-            return false;
-        }
-
-        // For default roles, only include them if they are present in local definition
-        // This implies: if local doesn't mention them, we ignore whatever the remote has (implicit default)
-        if name == "anon" || name == "service_role" || name == "public" || name == "authenticated" {
-            return local.iter().any(|l| l.grantee == name);
-        }
-
-        true
-    }).collect();
+fn grants_match(local: &[FunctionGrant], remote: &[FunctionGrant], ignored_roles: &[String]) -> bool {
+    // Filter implicit grants from remote before comparison. Grants to ignored roles
+    // (owner, Supabase's API roles by default) only count if the local definition
+    // mentions the role; otherwise whatever the remote has is an implicit default.
+    let remote_filtered: Vec<&FunctionGrant> = remote
+        .iter()
+        .filter(|r| !ignored_roles.contains(&r.grantee) || local.iter().any(|l| l.grantee == r.grantee))
+        .collect();
 
     // If local has no grants, and we filtered everything from remote (or remote only had defaults we filtered out), match.
     if local.is_empty() && remote_filtered.is_empty() {
@@ -754,6 +1406,14 @@ fn grants_match(local: &[FunctionGrant], remote: &[FunctionGrant]) -> bool {
     true
 }
 
+/// Functions without an explicit volatility are VOLATILE
+fn normalize_volatility(volatility: &Option<String>) -> String {
+    volatility
+        .as_deref()
+        .unwrap_or("VOLATILE")
+        .to_uppercase()
+}
+
 /// Normalize a config param value by stripping surrounding quotes
 fn normalize_config_value(value: &str) -> String {
     value.trim_matches('"').trim_matches('\'').to_string()
@@ -1,7 +1,7 @@
 use crate::schema::{SequenceInfo, ViewInfo};
 use super::utils;
 
-pub fn views_differ(local: &ViewInfo, remote: &ViewInfo) -> bool {
+pub fn views_differ(local: &ViewInfo, remote: &ViewInfo, ignored_grant_roles: &[String]) -> bool {
     // Normalize view definitions before comparison to handle formatting differences
     // (CREATE VIEW prefix in local vs just SELECT in remote, quoted identifiers, whitespace)
     let local_def_normalized = utils::normalize_view_definition(&local.definition);
@@ -37,13 +37,25 @@ pub fn views_differ(local: &ViewInfo, remote: &ViewInfo) -> bool {
     
     local_def_normalized != remote_def_normalized
         || local.is_materialized != remote.is_materialized
-        || local.with_options != remote.with_options
+        || utils::normalize_view_options(&local.with_options) != utils::normalize_view_options(&remote.with_options)
         || local.check_option != remote.check_option
+        || column_aliases_differ(local, remote)
         // Also check grants
-        || (!local.grants.is_empty() && !super::object_grants_match(&local.grants, &remote.grants))
+        || (!local.grants.is_empty() && !super::object_grants_match(&local.grants, &remote.grants, ignored_grant_roles))
 }
 
-pub fn sequences_differ(local: &SequenceInfo, remote: &SequenceInfo) -> bool {
+/// Introspected views carry no aliases, Postgres folds them into the definition's
+/// select list, so local aliases are checked against the remote column names.
+fn column_aliases_differ(local: &ViewInfo, remote: &ViewInfo) -> bool {
+    if !remote.column_aliases.is_empty() {
+        return local.column_aliases != remote.column_aliases;
+    }
+    !local.column_aliases.is_empty()
+        && !remote.columns.is_empty()
+        && !local.column_aliases.iter().eq(remote.columns.iter().map(|c| &c.name))
+}
+
+pub fn sequences_differ(local: &SequenceInfo, remote: &SequenceInfo, ignored_grant_roles: &[String]) -> bool {
     local.data_type != remote.data_type
         || local.start_value != remote.start_value
         || local.min_value != remote.min_value
@@ -52,5 +64,5 @@ pub fn sequences_differ(local: &SequenceInfo, remote: &SequenceInfo) -> bool {
         || local.cycle != remote.cycle
         || local.cache_size != remote.cache_size
         // Also check grants
-        || (!local.grants.is_empty() && !super::object_grants_match(&local.grants, &remote.grants))
+        || (!local.grants.is_empty() && !super::object_grants_match(&local.grants, &remote.grants, ignored_grant_roles))
 }
@@ -23,8 +23,17 @@ impl SchemaDiff {
         for t in &self.composite_types_to_drop {
             parts.push(format!("- Type '{}'", t));
         }
-        for (comp, _) in &self.composite_types_to_update {
+        for (comp, type_diff) in &self.composite_types_to_update {
             parts.push(format!("~ Type '{}' (attributes changed)", comp.name));
+            for attr in &type_diff.attributes_to_add {
+                parts.push(format!("  + Attribute '{}' {}", attr.name, attr.data_type));
+            }
+            for attr in &type_diff.attributes_to_drop {
+                parts.push(format!("  - Attribute '{}'", attr.name));
+            }
+            for (old, new) in &type_diff.attributes_to_alter {
+                parts.push(format!("  ~ Attribute '{}' {} -> {}", new.name, old.data_type, new.data_type));
+            }
         }
 
         // Domains
@@ -34,8 +43,14 @@ impl SchemaDiff {
         for d in &self.domains_to_drop {
             parts.push(format!("- Domain '{}'", d));
         }
-        for (domain, _) in &self.domains_to_update {
+        for (domain, domain_diff) in &self.domains_to_update {
             parts.push(format!("~ Domain '{}' (properties changed)", domain.name));
+            for con in &domain_diff.constraints_to_drop {
+                parts.push(format!("  - Constraint '{}'", con.name.as_deref().unwrap_or("(unnamed)")));
+            }
+            for con in &domain_diff.constraints_to_add {
+                parts.push(format!("  + Constraint '{}' {}", con.name.as_deref().unwrap_or("(unnamed)"), con.expression));
+            }
         }
 
         // Enums
@@ -79,13 +94,24 @@ impl SchemaDiff {
         }
 
         // Table changes
-        for (table_name, diff) in &self.table_changes {
+        let mut table_changes: Vec<_> = self.table_changes.iter().collect();
+        table_changes.sort_by(|a, b| a.0.cmp(b.0));
+        for (table_name, diff) in table_changes {
             for col in &diff.columns_to_add {
                 parts.push(format!("+ Column '{}.{}'", table_name, col));
             }
             for col in &diff.columns_to_drop {
                 parts.push(format!("- Column '{}.{}'", table_name, col));
             }
+            for (old_name, new_name) in &diff.columns_to_rename {
+                parts.push(format!("~ Column '{}.{}' renamed to '{}'", table_name, old_name, new_name));
+            }
+            if let Some((old_name, new_name)) = &diff.column_rename_candidate {
+                parts.push(format!(
+                    "? Column '{}.{}' may have been renamed to '{}' (accept the rename to keep its data)",
+                    table_name, old_name, new_name
+                ));
+            }
             for mod_col in &diff.columns_to_modify {
                 let mut changes = vec![];
                 if let Some((from, to)) = &mod_col.changes.type_change {
@@ -119,6 +145,58 @@ impl SchemaDiff {
                 parts.push(format!("~ Table '{}' comment changed", table_name));
             }
 
+            if diff.storage_params_change.is_some() {
+                parts.push(format!("~ Table '{}' storage parameters changed", table_name));
+            }
+
+            if let Some(unlogged) = diff.unlogged_change {
+                parts.push(format!(
+                    "~ Table '{}' SET {}",
+                    table_name,
+                    if unlogged { "UNLOGGED" } else { "LOGGED" }
+                ));
+            }
+
+            for partition in &diff.partitions_to_create {
+                parts.push(format!(
+                    "+ Partition '{}' OF '{}' {}",
+                    partition.name, table_name, partition.bound
+                ));
+            }
+
+            if let Some((old, new)) = &diff.partition_strategy_change {
+                parts.push(format!(
+                    "! Table '{}' partitioning {} -> {} (requires rebuilding the table)",
+                    table_name,
+                    old.as_deref().unwrap_or("none"),
+                    new.as_deref().unwrap_or("none")
+                ));
+            }
+
+            for (old, new) in &diff.partition_bounds_to_change {
+                parts.push(format!(
+                    "~ Partition '{}' OF '{}' {} -> {}",
+                    new.name, table_name, old.bound, new.bound
+                ));
+            }
+
+            for change in &diff.object_comments_to_set {
+                let kind = match change.target {
+                    super::CommentTarget::Index => "Index",
+                    super::CommentTarget::Constraint => "Constraint",
+                    super::CommentTarget::Policy => "Policy",
+                };
+                parts.push(format!("~ {} '{}' ON '{}' comment changed", kind, change.name, table_name));
+            }
+
+            if let Some(tablespace) = &diff.tablespace_change {
+                parts.push(format!(
+                    "~ Table '{}' tablespace: {}",
+                    table_name,
+                    tablespace.as_deref().unwrap_or("pg_default")
+                ));
+            }
+
             for p in &diff.policies_to_create {
                 parts.push(format!("+ Policy '{}' ON '{}'", p.name, table_name));
             }
@@ -139,6 +217,9 @@ impl SchemaDiff {
             for i in &diff.indexes_to_drop {
                 parts.push(format!("- Index '{}' ON '{}'", i.index_name, table_name));
             }
+            for (old_name, new_name) in &diff.indexes_to_rename {
+                parts.push(format!("~ Index '{}' renamed to '{}' ON '{}'", old_name, new_name, table_name));
+            }
 
             for c in &diff.check_constraints_to_create {
                 parts.push(format!("+ Check '{}' ON '{}'", c.name, table_name));
@@ -147,6 +228,13 @@ impl SchemaDiff {
                 parts.push(format!("- Check '{}' ON '{}'", c.name, table_name));
             }
 
+            for c in &diff.exclusion_constraints_to_create {
+                parts.push(format!("+ Exclusion '{}' ON '{}'", c.name, table_name));
+            }
+            for c in &diff.exclusion_constraints_to_drop {
+                parts.push(format!("- Exclusion '{}' ON '{}'", c.name, table_name));
+            }
+
             for f in &diff.foreign_keys_to_create {
                 parts.push(format!("+ FK '{}' ON '{}'", f.constraint_name, table_name));
             }
@@ -183,6 +271,18 @@ impl SchemaDiff {
             parts.push(format!("~ {}View '{}'", mat, view.name));
         }
 
+        // Rules
+        for rule in &self.rules_to_create {
+            parts.push(format!("+ Rule '{}' ON '{}'", rule.name, rule.relation));
+        }
+        for rule in &self.rules_to_drop {
+            parts.push(format!("- Rule '{}' ON '{}'", rule.name, rule.relation));
+        }
+
+        for (schema, _) in &self.schema_comments_to_set {
+            parts.push(format!("~ Comment on schema '{}'", schema));
+        }
+
         // Functions
         for f in &self.functions_to_create {
             parts.push(format!("+ Function '{}'", f.name));
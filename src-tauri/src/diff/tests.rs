@@ -14,7 +14,7 @@ fn test_create_table() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     local.tables.insert("users".into(), table);
@@ -38,7 +38,7 @@ fn test_drop_table() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     remote.tables.insert("users".into(), table);
@@ -64,7 +64,7 @@ fn test_add_column() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     
@@ -86,6 +86,7 @@ fn test_add_column() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -110,7 +111,7 @@ fn test_drop_column() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     
@@ -130,6 +131,7 @@ fn test_drop_column() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let local_table = TableInfo {
@@ -141,7 +143,7 @@ fn test_drop_column() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -167,7 +169,7 @@ fn test_modify_column_type() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     
@@ -187,6 +189,7 @@ fn test_modify_column_type() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     
     let mut local_table = remote_table.clone();
@@ -206,6 +209,7 @@ fn test_modify_column_type() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -233,7 +237,7 @@ fn test_modify_column_nullable() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -253,6 +257,7 @@ fn test_modify_column_nullable() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let mut local_table = remote_table.clone();
@@ -272,6 +277,7 @@ fn test_modify_column_nullable() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -298,7 +304,7 @@ fn test_modify_generated_column_expression() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     
@@ -319,6 +325,7 @@ fn test_modify_generated_column_expression() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     
     let mut local_table = remote_table.clone();
@@ -339,6 +346,7 @@ fn test_modify_generated_column_expression() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("products".into(), remote_table);
@@ -366,7 +374,7 @@ fn test_add_check_constraint() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -375,6 +383,7 @@ fn test_add_check_constraint() {
         name: "age_positive".into(),
         expression: "age > 0".into(),
         columns: vec!["age".into()],
+        comment: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -400,13 +409,14 @@ fn test_drop_check_constraint() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     remote_table.check_constraints.push(CheckConstraintInfo {
         name: "age_positive".into(),
         expression: "age > 0".into(),
         columns: vec!["age".into()],
+        comment: None,
     });
 
     let local_table = remote_table.clone();
@@ -486,6 +496,11 @@ fn test_summarize() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let summary = diff.summarize();
@@ -534,24 +549,30 @@ fn test_enum_add_value() {
 fn test_index_method_comparison() {
     let local = IndexInfo {
         index_name: "idx_test".to_string(),
-        columns: vec!["col1".to_string()],
+        columns: vec!["col1".into()],
         is_unique: false,
         is_primary: false,
         owning_constraint: None,
         index_method: "gin".to_string(),
         where_clause: None,
         expressions: vec![],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     let remote = IndexInfo {
         index_name: "idx_test".to_string(),
-        columns: vec!["col1".to_string()],
+        columns: vec!["col1".into()],
         is_unique: false,
         is_primary: false,
         owning_constraint: None,
         index_method: "btree".to_string(),
         where_clause: None,
         expressions: vec![],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     assert!(tables::indexes_differ(&local, &remote));
@@ -620,7 +641,7 @@ fn test_destructive_change_detection() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     });
     // Local empty -> Drop table
@@ -645,6 +666,7 @@ fn test_destructive_change_detection() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let mut local_with_table = local.clone();
@@ -657,7 +679,7 @@ fn test_destructive_change_detection() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     }); // Table exists but no column -> Drop column
 
@@ -678,6 +700,7 @@ fn test_policy_comparison_normalized() {
         roles: vec!["public".to_string()],
         qual: Some("auth.uid() = user_id".to_string()),
         with_check: None,
+        comment: None,
     };
 
     // Remote might have extra parentheses or different spacing
@@ -687,6 +710,7 @@ fn test_policy_comparison_normalized() {
         roles: vec!["public".to_string()],
         qual: Some("(auth.uid() = user_id)".to_string()),
         with_check: None,
+        comment: None,
     };
 
     // These should NOT differ (the expressions are equivalent)
@@ -700,6 +724,7 @@ fn test_policy_comparison_normalized() {
         roles: vec!["public".to_string()],
         qual: Some("auth.uid() = user_id".to_string()),
         with_check: None,
+        comment: None,
     };
 
     assert!(tables::policies_differ(&local, &remote_different_cmd),
@@ -718,6 +743,7 @@ fn test_policy_comparison_with_subquery() {
         roles: vec!["authenticated".to_string()],
         qual: Some("character_id IN (SELECT id FROM \"public\".\"characters\" WHERE user_id = auth.uid())".to_string()),
         with_check: None,
+        comment: None,
     };
 
     let remote = PolicyInfo {
@@ -726,6 +752,7 @@ fn test_policy_comparison_with_subquery() {
         roles: vec!["authenticated".to_string()],
         qual: Some("(character_id IN ( SELECT characters.id\n   FROM characters\n  WHERE (characters.user_id = auth.uid())))".to_string()),
         with_check: None,
+        comment: None,
     };
 
     // These should NOT differ - they are semantically equivalent
@@ -928,7 +955,7 @@ fn test_type_change_is_destructive() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -950,6 +977,7 @@ fn test_type_change_is_destructive() {
             enum_name: None,
             is_array: false,
             comment: None,
+            ordinal_position: None,
         },
     );
 
@@ -973,6 +1001,7 @@ fn test_type_change_is_destructive() {
             enum_name: None,
             is_array: false,
             comment: None,
+            ordinal_position: None,
         },
     );
 
@@ -1021,7 +1050,7 @@ fn test_add_column_is_not_destructive() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -1044,6 +1073,7 @@ fn test_add_column_is_not_destructive() {
             enum_name: None,
             is_array: false,
             comment: None,
+            ordinal_position: None,
         },
     );
 
@@ -1174,7 +1204,7 @@ fn test_full_schema_diff_does_not_drop_system_objects() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     users_table.columns.insert(
@@ -1195,6 +1225,7 @@ fn test_full_schema_diff_does_not_drop_system_objects() {
             enum_name: None,
             is_array: false,
             comment: None,
+            ordinal_position: None,
         },
     );
     remote.tables.insert("\"public\".\"users\"".into(), users_table.clone());
@@ -1244,6 +1275,7 @@ fn test_view_create() {
             definition: "SELECT id, COUNT(*) FROM users GROUP BY id".to_string(),
             is_materialized: false,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1269,6 +1301,7 @@ fn test_view_drop() {
             definition: "SELECT 1".to_string(),
             is_materialized: false,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1294,6 +1327,7 @@ fn test_view_update() {
             definition: "SELECT id FROM users".to_string(),
             is_materialized: false,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1309,6 +1343,7 @@ fn test_view_update() {
             definition: "SELECT id, name FROM users".to_string(), // Changed
             is_materialized: false,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1335,6 +1370,7 @@ fn test_view_cte_diff() {
             definition: "CREATE OR REPLACE VIEW \"public\".\"player_stats\" AS WITH playable_characters AS (SELECT o.id FROM public.objects o JOIN public.object_types t ON t.id = o.type_id WHERE t.category = 'character' AND t.is_playable = true AND o.user_id IS NOT NULL) SELECT s.name AS stat_name, COUNT(DISTINCT os.object_id)::INTEGER AS player_count, COALESCE(ROUND(AVG(os.current)::NUMERIC, 2), 0::NUMERIC) AS avg_current FROM playable_characters pc JOIN public.object_stats os ON os.object_id = pc.id JOIN public.stats s ON s.id = os.stat_id GROUP BY s.name;".to_string(),
             is_materialized: false,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1365,6 +1401,7 @@ fn test_view_cte_diff() {
   GROUP BY s.name;".to_string(),
             is_materialized: false,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1393,6 +1430,7 @@ fn test_materialized_view_create() {
             definition: "SELECT * FROM users".to_string(),
             is_materialized: true,
             columns: vec![],
+            column_aliases: vec![],
             indexes: vec![],
             comment: None,
             with_options: vec![],
@@ -1545,6 +1583,48 @@ fn test_function_update() {
     assert_eq!(diff.functions_to_update[0].name, "my_func");
 }
 
+#[test]
+fn test_function_volatility_change() {
+    let func = FunctionInfo {
+        schema: "public".to_string(),
+        name: "my_func".to_string(),
+        args: vec![],
+        return_type: "integer".to_string(),
+        language: "sql".to_string(),
+        definition: "SELECT 1".to_string(),
+        volatility: Some("VOLATILE".to_string()),
+        is_strict: false,
+        security_definer: false,
+        config_params: vec![],
+        grants: vec![], extension: None,
+    };
+
+    let mut remote = DbSchema::new();
+    let mut local = DbSchema::new();
+    remote.functions.insert("\"public\".\"my_func\"()".to_string(), func.clone());
+
+    // No explicit volatility is the same as VOLATILE
+    local.functions.insert(
+        "\"public\".\"my_func\"()".to_string(),
+        FunctionInfo { volatility: None, ..func.clone() },
+    );
+    assert!(compute_diff(&remote, &local).functions_to_update.is_empty());
+
+    local.functions.insert(
+        "\"public\".\"my_func\"()".to_string(),
+        FunctionInfo { volatility: Some("STABLE".to_string()), ..func.clone() },
+    );
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.functions_to_update.len(), 1);
+    assert_eq!(diff.functions_to_update[0].volatility.as_deref(), Some("STABLE"));
+
+    local.functions.insert(
+        "\"public\".\"my_func\"()".to_string(),
+        FunctionInfo { is_strict: true, ..func },
+    );
+    assert_eq!(compute_diff(&remote, &local).functions_to_update.len(), 1);
+}
+
 #[test]
 fn test_domain_create() {
     let remote = DbSchema::new();
@@ -1592,6 +1672,32 @@ fn test_domain_drop() {
     assert_eq!(diff.domains_to_drop.len(), 1);
 }
 
+#[test]
+fn test_domain_check_change_generates_alter_domain() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let remote = parse(r#"CREATE DOMAIN "public"."score" AS integer CONSTRAINT "score_range" CHECK (VALUE >= 0);"#);
+    let local = parse(
+        r#"CREATE DOMAIN "public"."score" AS integer DEFAULT 0 NOT NULL CONSTRAINT "score_range" CHECK (VALUE >= 0 AND VALUE <= 100);"#,
+    );
+    assert!(local.domains["\"public\".\"score\""].is_not_null);
+
+    let diff = compute_diff(&remote, &local);
+    assert!(diff.domains_to_create.is_empty() && diff.domains_to_drop.is_empty());
+    assert_eq!(diff.domains_to_update.len(), 1);
+
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("ALTER DOMAIN \"public\".\"score\" SET DEFAULT 0;"), "{}", sql);
+    assert!(sql.contains("ALTER DOMAIN \"public\".\"score\" SET NOT NULL;"), "{}", sql);
+    let drop = sql.find("ALTER DOMAIN \"public\".\"score\" DROP CONSTRAINT \"score_range\";").expect(&sql);
+    let add = sql
+        .find("ALTER DOMAIN \"public\".\"score\" ADD CONSTRAINT \"score_range\" CHECK (VALUE >= 0 AND VALUE <= 100);")
+        .expect(&sql);
+    assert!(drop < add);
+
+    // An unchanged constraint is left alone
+    assert!(compute_diff(&local, &local).domains_to_update.is_empty());
+}
+
 #[test]
 fn test_composite_type_create() {
     let remote = DbSchema::new();
@@ -1638,6 +1744,46 @@ fn test_composite_type_drop() {
     assert_eq!(diff.composite_types_to_drop.len(), 1);
 }
 
+#[test]
+fn test_composite_type_attribute_added_with_alter_type() {
+    let address = |attributes: &[(&str, &str)]| crate::schema::CompositeTypeInfo {
+        schema: "public".to_string(),
+        name: "address".to_string(),
+        attributes: attributes
+            .iter()
+            .map(|(name, data_type)| crate::schema::CompositeTypeAttribute {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                collation: None,
+            })
+            .collect(),
+        comment: None,
+        extension: None,
+    };
+    let key = "\"public\".\"address\"".to_string();
+    let mut remote = DbSchema::new();
+    remote.composite_types.insert(key.clone(), address(&[("street", "text")]));
+    let mut local = DbSchema::new();
+    local.composite_types.insert(key.clone(), address(&[("street", "text"), ("zip", "varchar(10)")]));
+
+    let diff = compute_diff(&remote, &local);
+    assert!(diff.composite_types_to_create.is_empty() && diff.composite_types_to_drop.is_empty());
+    assert_eq!(diff.composite_types_to_update.len(), 1);
+    let (_, type_diff) = &diff.composite_types_to_update[0];
+    assert_eq!(type_diff.attributes_to_add.len(), 1);
+    assert!(!diff.is_destructive(), "adding an attribute keeps existing values");
+
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("ALTER TYPE \"public\".\"address\" ADD ATTRIBUTE \"zip\" varchar(10);"), "{}", sql);
+    assert!(!sql.contains("DROP TYPE"));
+
+    // Dropping an attribute needs confirmation
+    let diff = compute_diff(&local, &remote);
+    assert!(diff.is_destructive());
+    let sql = crate::generator::generate_sql(&diff, &remote);
+    assert!(sql.contains("ALTER TYPE \"public\".\"address\" DROP ATTRIBUTE \"zip\";"), "{}", sql);
+}
+
 #[test]
 fn test_extension_create() {
     let remote = DbSchema::new();
@@ -1741,7 +1887,7 @@ fn test_column_default_change() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -1761,6 +1907,7 @@ fn test_column_default_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let mut local_table = remote_table.clone();
@@ -1780,6 +1927,7 @@ fn test_column_default_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -1805,7 +1953,7 @@ fn test_identity_column_change() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -1825,6 +1973,7 @@ fn test_identity_column_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let mut local_table = remote_table.clone();
@@ -1844,6 +1993,7 @@ fn test_identity_column_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("items".into(), remote_table);
@@ -1869,7 +2019,7 @@ fn test_collation_change() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -1889,6 +2039,7 @@ fn test_collation_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let mut local_table = remote_table.clone();
@@ -1908,6 +2059,7 @@ fn test_collation_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("data".into(), remote_table);
@@ -1933,7 +2085,7 @@ fn test_column_comment_change() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -1953,6 +2105,7 @@ fn test_column_comment_change() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     let mut local_table = remote_table.clone();
@@ -1972,6 +2125,7 @@ fn test_column_comment_change() {
         enum_name: None,
         is_array: false,
         comment: Some("User email address".into()), // Added comment
+        ordinal_position: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -1997,7 +2151,7 @@ fn test_table_comment_change() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2026,7 +2180,7 @@ fn test_foreign_key_add() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2072,7 +2226,7 @@ fn test_foreign_key_drop() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2085,7 +2239,7 @@ fn test_foreign_key_drop() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2111,7 +2265,7 @@ fn test_trigger_create() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2154,7 +2308,7 @@ fn test_trigger_drop() {
         }],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2167,7 +2321,7 @@ fn test_trigger_drop() {
         triggers: vec![], // Trigger removed
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2193,7 +2347,7 @@ fn test_index_with_expression() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2207,6 +2361,9 @@ fn test_index_with_expression() {
         index_method: "btree".into(),
         where_clause: None,
         expressions: vec!["lower(email)".into()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     });
 
     remote.tables.insert("users".into(), remote_table);
@@ -2232,7 +2389,7 @@ fn test_policy_create() {
         triggers: vec![],
         rls_enabled: true,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2243,6 +2400,7 @@ fn test_policy_create() {
         roles: vec!["public".into()],
         qual: Some("user_id = auth.uid()".into()),
         with_check: None,
+        comment: None,
     });
 
     remote.tables.insert("posts".into(), remote_table);
@@ -2272,8 +2430,9 @@ fn test_policy_drop() {
             roles: vec!["public".into()],
             qual: Some("true".into()),
             with_check: None,
+            comment: None,
         }],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2286,7 +2445,7 @@ fn test_policy_drop() {
         triggers: vec![],
         rls_enabled: true,
         policies: vec![], // Policy removed
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2312,7 +2471,7 @@ fn test_rls_enable() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2341,7 +2500,7 @@ fn test_rls_disable() {
         triggers: vec![],
         rls_enabled: true,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2405,7 +2564,7 @@ fn test_table_diff_is_empty() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
 
@@ -2584,6 +2743,7 @@ fn test_view_diff_normalization_coalesce_cast() {
         definition: local_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -2596,13 +2756,14 @@ fn test_view_diff_normalization_coalesce_cast() {
         definition: remote_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
         check_option: None, grants: vec![], extension: None,
     };
 
-    assert!(!super::objects::views_differ(&local, &remote), "Views should be considered identical despite type casting");
+    assert!(!super::objects::views_differ(&local, &remote, &default_ignored_grant_roles()), "Views should be considered identical despite type casting");
 }
 
 #[test]
@@ -2617,6 +2778,7 @@ fn test_view_diff_normalization_interval() {
         definition: local_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -2629,13 +2791,14 @@ fn test_view_diff_normalization_interval() {
         definition: remote_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
         check_option: None, grants: vec![], extension: None,
     };
 
-    assert!(!super::objects::views_differ(&local, &remote), "Views should be considered identical despite interval syntax differences");
+    assert!(!super::objects::views_differ(&local, &remote, &default_ignored_grant_roles()), "Views should be considered identical despite interval syntax differences");
 }
 
 #[test]
@@ -2655,6 +2818,7 @@ fn test_view_diff_normalization_complex_parens() {
         definition: local_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -2667,13 +2831,14 @@ fn test_view_diff_normalization_complex_parens() {
         definition: remote_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
         check_option: None, grants: vec![], extension: None,
     };
 
-    assert!(!super::objects::views_differ(&local, &remote), "Views should be considered identical despite complex nested parens in JOIN/FILTER");
+    assert!(!super::objects::views_differ(&local, &remote, &default_ignored_grant_roles()), "Views should be considered identical despite complex nested parens in JOIN/FILTER");
 }
 
 #[test]
@@ -2695,6 +2860,7 @@ fn test_view_diff_normalization_join_on_group_by() {
         definition: local_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -2707,13 +2873,14 @@ fn test_view_diff_normalization_join_on_group_by() {
         definition: remote_def.to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
         check_option: None, grants: vec![], extension: None,
     };
 
-    assert!(!super::objects::views_differ(&local, &remote), "Views should be identical despite pg_get_viewdef's extra parens around JOIN/ON and missing space before GROUP BY");
+    assert!(!super::objects::views_differ(&local, &remote, &default_ignored_grant_roles()), "Views should be identical despite pg_get_viewdef's extra parens around JOIN/ON and missing space before GROUP BY");
 }
 #[test]
 fn test_function_param_rename_detection() {
@@ -2912,6 +3079,9 @@ fn test_expression_only_index_no_diff() {
         index_method: "btree".to_string(),
         where_clause: Some("principal_member_id IS NOT NULL".to_string()),
         expressions: vec!["coalesce(node_id, '00000000-0000-0000-0000-000000000000'::UUID)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     // Remote has lowercase type cast (PostgreSQL normalizes to lowercase)
@@ -2924,6 +3094,9 @@ fn test_expression_only_index_no_diff() {
         index_method: "btree".to_string(),
         where_clause: Some("(principal_member_id IS NOT NULL)".to_string()),
         expressions: vec!["COALESCE(node_id, '00000000-0000-0000-0000-000000000000'::uuid)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     assert!(
@@ -2944,6 +3117,9 @@ fn test_expression_index_type_cast_normalization() {
         index_method: "btree".to_string(),
         where_clause: None,
         expressions: vec!["coalesce(col, 'default'::TEXT)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     let remote_idx = IndexInfo {
@@ -2955,6 +3131,9 @@ fn test_expression_index_type_cast_normalization() {
         index_method: "btree".to_string(),
         where_clause: None,
         expressions: vec!["COALESCE(col, 'default'::text)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     assert!(
@@ -2982,6 +3161,9 @@ fn test_expression_only_index_realistic_pipeline() {
         index_method: "btree".to_string(),
         where_clause: Some("(principal_member_id IS NOT NULL)".to_string()),
         expressions: vec!["COALESCE(node_id, '00000000-0000-0000-0000-000000000000'::uuid)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     // Local side: sqlparser parses CREATE INDEX ... (coalesce(...))
@@ -2995,6 +3177,9 @@ fn test_expression_only_index_realistic_pipeline() {
         index_method: "btree".to_string(),
         where_clause: Some("principal_member_id IS NOT NULL".to_string()),
         expressions: vec!["COALESCE(node_id, '00000000-0000-0000-0000-000000000000'::UUID)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     eprintln!("=== REALISTIC PIPELINE TEST ===");
@@ -3049,13 +3234,16 @@ CREATE UNIQUE INDEX "role_bindings_member_unique_idx" ON "authz"."role_bindings"
     // Remote has same regular columns + expression extracted from pg_get_indexdef
     let remote_idx = IndexInfo {
         index_name: "role_bindings_member_unique_idx".to_string(),
-        columns: vec!["organization_id".to_string(), "role_id".to_string(), "scope".to_string(), "principal_member_id".to_string()],
+        columns: vec!["organization_id".into(), "role_id".into(), "scope".into(), "principal_member_id".into()],
         is_unique: true,
         is_primary: false,
         owning_constraint: None,
         index_method: "btree".to_string(),
         where_clause: Some("(principal_member_id IS NOT NULL)".to_string()),
         expressions: vec!["COALESCE(node_id, '00000000-0000-0000-0000-000000000000'::uuid)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     eprintln!("=== REMOTE (simulated) ===");
@@ -3086,7 +3274,7 @@ fn test_generated_column_uuid_cast_normalization() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     
@@ -3107,6 +3295,7 @@ fn test_generated_column_uuid_cast_normalization() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     
     // Local definition matches but without ::uuid cast on NULL
@@ -3127,6 +3316,7 @@ fn test_generated_column_uuid_cast_normalization() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("role_bindings".into(), remote_table);
@@ -3205,7 +3395,7 @@ fn test_generated_column_custom_type_cast() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     
@@ -3226,6 +3416,7 @@ fn test_generated_column_custom_type_cast() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     
     let mut local_table = remote_table.clone();
@@ -3247,6 +3438,7 @@ fn test_generated_column_custom_type_cast() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
     remote.tables.insert("items".into(), remote_table);
@@ -3333,7 +3525,7 @@ fn test_ignore_bigserial_diff() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     remote_table.columns.insert("id".into(), ColumnInfo {
@@ -3352,6 +3544,7 @@ fn test_ignore_bigserial_diff() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     remote.tables.insert("backfill_jobs".into(), remote_table);
 
@@ -3365,7 +3558,7 @@ fn test_ignore_bigserial_diff() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     local_table.columns.insert("id".into(), ColumnInfo {
@@ -3384,6 +3577,7 @@ fn test_ignore_bigserial_diff() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     local.tables.insert("backfill_jobs".into(), local_table);
 
@@ -3429,7 +3623,7 @@ fn test_ignore_implicit_sequence_drop() {
         triggers: vec![],
         rls_enabled: false,
         policies: vec![],
-        check_constraints: vec![], grants: vec![], extension: None,
+        check_constraints: vec![], exclusion_constraints: vec![], grants: vec![], extension: None, storage_params: vec![], tablespace: None, is_unlogged: false, partition_strategy: None, partitions: vec![],
         comment: None,
     };
     local_table.columns.insert("id".into(), ColumnInfo {
@@ -3448,6 +3642,7 @@ fn test_ignore_implicit_sequence_drop() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
     local.tables.insert("backfill_jobs".into(), local_table);
 
@@ -3493,10 +3688,17 @@ fn test_check_constraint_expression_diff_no_false_positive() {
             name: "conversations_type_check".into(),
             expression: "((type)::text = ANY ((ARRAY['solo'::character varying, 'multiplayer'::character varying])::text[]))".into(),
             columns: vec!["type".into()],
+            comment: None,
         }],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
     remote.tables.insert("conversations".into(), remote_table);
 
@@ -3513,10 +3715,17 @@ fn test_check_constraint_expression_diff_no_false_positive() {
             name: "conversations_type_check".into(),
             expression: "CHECK (type IN ('solo', 'multiplayer'))".into(),
             columns: vec!["type".into()],
+            comment: None,
         }],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
     local.tables.insert("conversations".into(), local_table);
 
@@ -3552,3 +3761,1024 @@ fn test_check_constraint_in_vs_any_array_four_values() {
         "Multi-value IN vs ANY(ARRAY[]) should match.\n  Local:  {}\n  Remote: {}",
         local_n, remote_n);
 }
+
+#[test]
+fn test_watched_schemas_excludes_other_schemas() {
+    let mut remote = DbSchema::new();
+    let mut local = DbSchema::new();
+
+    remote.tables.insert("\"app\".\"audit_log\"".into(), TableInfo {
+        schema: "app".into(),
+        table_name: "audit_log".into(),
+        ..Default::default()
+    });
+    local.tables.insert("\"public\".\"users\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "users".into(),
+        ..Default::default()
+    });
+
+    // Without a filter the app table is considered drift and dropped
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.tables_to_drop, vec!["\"app\".\"audit_log\""]);
+
+    let options = DiffOptions {
+        watched_schemas: Some(vec!["public".into()]),
+        ..Default::default()
+    };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert!(diff.tables_to_drop.is_empty(), "app schema should be ignored: {:?}", diff.tables_to_drop);
+    assert_eq!(diff.tables_to_create, vec!["\"public\".\"users\""]);
+}
+
+#[test]
+fn test_denylisted_table_not_dropped() {
+    let mut remote = DbSchema::new();
+    let local = DbSchema::new();
+
+    remote.tables.insert("\"public\".\"legacy_orders\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "legacy_orders".into(),
+        ..Default::default()
+    });
+
+    let options = DiffOptions {
+        table_denylist: vec!["legacy_*".into()],
+        ..Default::default()
+    };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert!(diff.tables_to_drop.is_empty());
+    assert!(!diff.is_destructive());
+}
+
+#[test]
+fn test_table_allowlist_ignores_unlisted_tables() {
+    let mut remote = DbSchema::new();
+    let mut local = DbSchema::new();
+
+    remote.tables.insert("\"public\".\"reports\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "reports".into(),
+        ..Default::default()
+    });
+    local.tables.insert("\"public\".\"app_users\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "app_users".into(),
+        ..Default::default()
+    });
+    local.tables.insert("\"public\".\"scratch\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "scratch".into(),
+        ..Default::default()
+    });
+
+    let options = DiffOptions {
+        table_allowlist: vec!["public.app_*".into()],
+        ..Default::default()
+    };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert_eq!(diff.tables_to_create, vec!["\"public\".\"app_users\""]);
+    assert!(diff.tables_to_drop.is_empty());
+}
+
+#[test]
+fn test_denylisted_table_serial_sequence_not_dropped() {
+    let mut remote = DbSchema::new();
+    let local = DbSchema::new();
+
+    remote.tables.insert("\"public\".\"legacy_orders\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "legacy_orders".into(),
+        ..Default::default()
+    });
+    remote.sequences.insert("\"public\".\"legacy_orders_id_seq\"".into(), SequenceInfo {
+        schema: "public".into(),
+        name: "legacy_orders_id_seq".into(),
+        data_type: "bigint".into(),
+        start_value: 1,
+        min_value: 1,
+        max_value: i64::MAX,
+        increment: 1,
+        cycle: false,
+        cache_size: 1,
+        owned_by: Some("legacy_orders.id".into()),
+        grants: vec![],
+        comment: None,
+        extension: None,
+    });
+
+    let options = DiffOptions {
+        table_denylist: vec!["legacy_orders".into()],
+        ..Default::default()
+    };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert!(diff.sequences_to_drop.is_empty());
+}
+
+#[test]
+fn test_glob_match() {
+    assert!(utils::glob_match("legacy_*", "legacy_orders"));
+    assert!(utils::glob_match("*", "anything"));
+    assert!(utils::glob_match("user?", "users"));
+    assert!(utils::glob_match("public.*_log", "public.audit_log"));
+    assert!(!utils::glob_match("legacy_*", "orders"));
+    assert!(!utils::glob_match("user?", "user"));
+}
+
+#[test]
+fn test_index_rename_detected() {
+    let index = |name: &str| IndexInfo {
+        index_name: name.into(),
+        columns: vec!["email".into()],
+        is_unique: false,
+        is_primary: false,
+        owning_constraint: None,
+        index_method: "btree".into(),
+        where_clause: Some("deleted_at IS NULL".into()),
+        expressions: vec![],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
+    };
+
+    let remote_table = TableInfo {
+        schema: "public".into(),
+        table_name: "users".into(),
+        indexes: vec![index("users_email_idx")],
+        ..Default::default()
+    };
+    let mut local_table = remote_table.clone();
+    local_table.indexes = vec![index("idx_users_active_email")];
+
+    let table_diff = tables::compute_table_diff(&remote_table, &local_table);
+    assert!(table_diff.indexes_to_create.is_empty());
+    assert!(table_diff.indexes_to_drop.is_empty());
+    assert_eq!(
+        table_diff.indexes_to_rename,
+        vec![("users_email_idx".to_string(), "idx_users_active_email".to_string())]
+    );
+
+    // A different predicate is a real change, not a rename
+    local_table.indexes[0].where_clause = None;
+    let table_diff = tables::compute_table_diff(&remote_table, &local_table);
+    assert!(table_diff.indexes_to_rename.is_empty());
+    assert_eq!(table_diff.indexes_to_create.len(), 1);
+    assert_eq!(table_diff.indexes_to_drop.len(), 1);
+}
+
+#[test]
+fn test_column_rename_detected() {
+    let column = |name: &str| ColumnInfo {
+        column_name: name.into(),
+        data_type: "text".into(),
+        is_nullable: true,
+        udt_name: "text".into(),
+        ..Default::default()
+    };
+
+    let mut remote = DbSchema::new();
+    let mut remote_table = TableInfo {
+        schema: "public".into(),
+        table_name: "users".into(),
+        ..Default::default()
+    };
+    remote_table.columns.insert("id".into(), ColumnInfo { data_type: "bigint".into(), ..column("id") });
+    remote_table.columns.insert("name".into(), column("name"));
+    let mut local_table = remote_table.clone();
+    local_table.columns.remove("name");
+    local_table.columns.insert("full_name".into(), column("full_name"));
+    remote.tables.insert("\"public\".\"users\"".into(), remote_table);
+    let mut local = DbSchema::new();
+    local.tables.insert("\"public\".\"users\"".into(), local_table);
+
+    // Offered, but still a drop + add until accepted
+    let diff = compute_diff(&remote, &local);
+    let changes = &diff.table_changes["\"public\".\"users\""];
+    assert_eq!(changes.column_rename_candidate, Some(("name".to_string(), "full_name".to_string())));
+    assert_eq!(changes.columns_to_drop, vec!["name"]);
+    assert!(diff.is_destructive());
+    assert!(diff.summarize().contains("may have been renamed to 'full_name'"));
+
+    let rename = ColumnRenameCandidate {
+        table: "\"public\".\"users\"".into(),
+        from: "name".into(),
+        to: "full_name".into(),
+    };
+    let options = DiffOptions { accepted_renames: vec![rename.clone()], ..Default::default() };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    let changes = &diff.table_changes["\"public\".\"users\""];
+    assert!(changes.columns_to_drop.is_empty());
+    assert!(changes.columns_to_add.is_empty());
+    assert!(!diff.is_destructive());
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("ALTER TABLE \"public\".\"users\" RENAME COLUMN \"name\" TO \"full_name\";"), "{}", sql);
+
+    // Accepting a different pair leaves the candidate a drop + add
+    let other = ColumnRenameCandidate { to: "nickname".into(), ..rename.clone() };
+    let options = DiffOptions { accepted_renames: vec![other], ..Default::default() };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert_eq!(diff.table_changes["\"public\".\"users\""].columns_to_drop, vec!["name"]);
+
+    // Other changes to the renamed column are kept
+    local.tables.get_mut("\"public\".\"users\"").unwrap().columns.get_mut("full_name").unwrap().comment =
+        Some("Display name".into());
+    let options = DiffOptions { accepted_renames: vec![rename], ..Default::default() };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    let changes = &diff.table_changes["\"public\".\"users\""];
+    assert_eq!(changes.columns_to_rename, vec![("name".to_string(), "full_name".to_string())]);
+    assert_eq!(changes.columns_to_modify.len(), 1);
+    assert_eq!(changes.columns_to_modify[0].column_name, "full_name");
+    assert_eq!(
+        changes.columns_to_modify[0].changes.comment_change,
+        Some((None, Some("Display name".to_string())))
+    );
+    local.tables.get_mut("\"public\".\"users\"").unwrap().columns.get_mut("full_name").unwrap().comment = None;
+
+    // Defaults are compared as Postgres prints them, so a cast doesn't hide the rename
+    remote.tables.get_mut("\"public\".\"users\"").unwrap().columns.get_mut("name").unwrap().column_default =
+        Some("'anonymous'::text".into());
+    local.tables.get_mut("\"public\".\"users\"").unwrap().columns.get_mut("full_name").unwrap().column_default =
+        Some("'anonymous'".into());
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(
+        diff.table_changes["\"public\".\"users\""].column_rename_candidate,
+        Some(("name".to_string(), "full_name".to_string()))
+    );
+
+    // A different type is not a rename
+    local.tables.get_mut("\"public\".\"users\"").unwrap().columns.get_mut("full_name").unwrap().data_type = "varchar(100)".into();
+    let diff = compute_diff(&remote, &local);
+    assert!(diff.table_changes["\"public\".\"users\""].column_rename_candidate.is_none());
+}
+
+#[test]
+fn test_expression_index_spacing_no_diff() {
+    let index = |expression: &str| IndexInfo {
+        index_name: "idx_users_lower_email".into(),
+        columns: vec![],
+        is_unique: true,
+        is_primary: false,
+        owning_constraint: None,
+        index_method: "btree".into(),
+        where_clause: None,
+        expressions: vec![expression.into()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
+    };
+
+    // Local as written in schema.sql vs Postgres's canonical pg_get_indexdef rendering
+    let local_idx = index("LOWER( email )");
+    let remote_idx = index("(lower((email)::text))");
+
+    assert!(!tables::indexes_differ(&local_idx, &remote_idx));
+
+    let remote_table = TableInfo {
+        schema: "public".into(),
+        table_name: "users".into(),
+        indexes: vec![remote_idx],
+        ..Default::default()
+    };
+    let local_table = TableInfo {
+        indexes: vec![local_idx],
+        ..remote_table.clone()
+    };
+    assert!(tables::compute_table_diff(&remote_table, &local_table).is_empty());
+}
+
+#[test]
+fn test_type_alias_pairs_produce_no_diff() {
+    let pairs = [
+        ("varchar(255)", "character varying(255)"),
+        ("int4", "integer"),
+        ("timestamptz", "timestamp with time zone"),
+        ("timestamptz(3)", "timestamp(3) with time zone"),
+        ("timestamp", "timestamp without time zone"),
+        ("decimal(10, 2)", "numeric(10,2)"),
+        ("numeric(12)", "numeric(12,0)"),
+        ("char", "character(1)"),
+        ("bool", "boolean"),
+        ("float", "double precision"),
+        ("float(10)", "real"),
+        ("varchar(50)[]", "character varying(50)[]"),
+    ];
+
+    for (local_type, remote_type) in pairs {
+        let table_with_type = |data_type: &str| TableInfo {
+            schema: "public".into(),
+            table_name: "items".into(),
+            columns: HashMap::from([(
+                "value".to_string(),
+                ColumnInfo {
+                    column_name: "value".into(),
+                    data_type: data_type.into(),
+                    is_nullable: true,
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+
+        let diff = crate::diff::tables::compute_table_diff(&table_with_type(remote_type), &table_with_type(local_type));
+        assert!(
+            diff.columns_to_modify.is_empty(),
+            "{} vs {} should not produce a type change",
+            local_type,
+            remote_type
+        );
+    }
+
+    // Precision and length changes are still detected
+    assert_ne!(utils::normalize_data_type("numeric(10,2)"), utils::normalize_data_type("numeric"));
+    assert_ne!(utils::normalize_data_type("varchar(100)"), utils::normalize_data_type("varchar(255)"));
+}
+
+#[test]
+fn test_merge_mode_never_drops_remote_only_objects() {
+    let users = |extra_column: bool| {
+        let mut table = TableInfo {
+            schema: "public".into(),
+            table_name: "users".into(),
+            ..Default::default()
+        };
+        table.columns.insert("id".into(), ColumnInfo {
+            column_name: "id".into(),
+            data_type: "bigint".into(),
+            udt_name: "int8".into(),
+            ..Default::default()
+        });
+        if extra_column {
+            table.columns.insert("legacy_flag".into(), ColumnInfo {
+                column_name: "legacy_flag".into(),
+                data_type: "boolean".into(),
+                udt_name: "bool".into(),
+                is_nullable: true,
+                ..Default::default()
+            });
+        }
+        table
+    };
+
+    let mut remote = DbSchema::new();
+    let mut local = DbSchema::new();
+    remote.tables.insert("\"public\".\"users\"".into(), users(true));
+    remote.tables.insert("\"public\".\"reports\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "reports".into(),
+        ..Default::default()
+    });
+    remote.tables.insert("\"analytics\".\"events\"".into(), TableInfo {
+        schema: "analytics".into(),
+        table_name: "events".into(),
+        ..Default::default()
+    });
+    local.tables.insert("\"public\".\"users\"".into(), users(false));
+    local.tables.insert("\"public\".\"posts\"".into(), TableInfo {
+        schema: "public".into(),
+        table_name: "posts".into(),
+        ..Default::default()
+    });
+
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.tables_to_drop.len(), 2);
+    assert!(diff.table_changes.contains_key("\"public\".\"users\""));
+
+    let options = DiffOptions {
+        merge: true,
+        ..Default::default()
+    };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert!(diff.tables_to_drop.is_empty());
+    assert!(diff.table_changes.is_empty(), "column drops should be filtered: {:?}", diff.table_changes);
+    assert_eq!(diff.tables_to_create, vec!["\"public\".\"posts\""]);
+    assert!(!diff.is_destructive());
+}
+
+#[test]
+fn test_merge_keeps_drop_of_changed_generated_column() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let remote = parse(
+        "CREATE TABLE public.items (id bigint PRIMARY KEY, price numeric, legacy text, total numeric GENERATED ALWAYS AS (price * 2) STORED);",
+    );
+    let local = parse("CREATE TABLE public.items (id bigint PRIMARY KEY, price numeric, total numeric GENERATED ALWAYS AS (price * 3) STORED);");
+
+    let options = DiffOptions { merge: true, ..Default::default() };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    let changes = &diff.table_changes["\"public\".\"items\""];
+    assert_eq!(changes.columns_to_drop, vec!["total"]);
+    assert_eq!(changes.columns_to_add, vec!["total"]);
+
+    let sql = crate::generator::generate_sql(&diff, &local);
+    let drop = sql.find("DROP COLUMN IF EXISTS \"total\"").expect(&sql);
+    let add = sql.find("ADD COLUMN \"total\"").expect(&sql);
+    assert!(drop < add, "{}", sql);
+    assert!(!sql.contains("\"legacy\""), "{}", sql);
+}
+
+#[test]
+fn test_trigger_referencing_missing_function_is_flagged() {
+    let local = crate::parsing::parse_schema_sql(&[(
+        "schema.sql".to_string(),
+        r#"
+CREATE TABLE "public"."posts" (
+    "id" bigint PRIMARY KEY,
+    "author_id" bigint,
+    CONSTRAINT "posts_author_fk" FOREIGN KEY ("author_id") REFERENCES "public"."authors"("id")
+);
+CREATE FUNCTION "public"."touch"() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN RETURN NEW; END; $$;
+CREATE TRIGGER "posts_touch" BEFORE UPDATE ON "public"."posts" FOR EACH ROW EXECUTE FUNCTION "public"."touch"();
+CREATE TRIGGER "posts_audit" AFTER INSERT ON "public"."posts" FOR EACH ROW EXECUTE FUNCTION "public"."audit"();
+CREATE VIEW "public"."recent_posts" AS SELECT * FROM "public"."posts";
+"#
+        .to_string(),
+    )])
+    .unwrap();
+
+    let diff = compute_diff(&DbSchema::new(), &local);
+    let warnings = validate::validate_references(&diff, &DbSchema::new(), &local);
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+    assert!(warnings.iter().any(|w| w.starts_with("Trigger 'posts_audit'") && w.contains("\"public\".\"audit\"")));
+    assert!(warnings.iter().any(|w| w.starts_with("Foreign key") && w.contains("\"public\".\"authors\"")));
+
+    // The function already existing remotely (and kept by a merge push) resolves the trigger
+    let mut remote = DbSchema::new();
+    let mut audit = local.functions.values().next().unwrap().clone();
+    audit.name = "audit".to_string();
+    remote.functions.insert("\"public\".\"audit\"()".to_string(), audit);
+    let options = DiffOptions { merge: true, ..Default::default() };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    let warnings = validate::validate_references(&diff, &remote, &local);
+    assert!(warnings.iter().all(|w| !w.contains("audit")), "{:?}", warnings);
+}
+
+#[test]
+fn test_custom_default_privilege_is_granted_and_builtin_ones_ignored() {
+    let local = crate::parsing::parse_schema_sql(&[(
+        "schema.sql".to_string(),
+        "ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT SELECT ON TABLES TO reporting;".to_string(),
+    )])
+    .unwrap();
+
+    let builtin = DefaultAcl {
+        role: "postgres".into(),
+        schema: Some("public".into()),
+        object_type: "tables".into(),
+        grantee: "anon".into(),
+        privilege: "SELECT".into(),
+    };
+    let stale = DefaultAcl { grantee: "old_reporting".into(), ..builtin.clone() };
+    let remote = DbSchema { default_acls: vec![builtin, stale.clone()], ..DbSchema::new() };
+
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.default_acls_to_create, local.default_acls);
+    // Supabase's own default for anon is left alone, the stale custom one is revoked
+    assert_eq!(diff.default_acls_to_drop, vec![stale]);
+
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains(
+        "ALTER DEFAULT PRIVILEGES FOR ROLE \"postgres\" IN SCHEMA \"public\" GRANT SELECT ON TABLES TO \"reporting\";"
+    ));
+    assert!(sql.contains(
+        "ALTER DEFAULT PRIVILEGES FOR ROLE \"postgres\" IN SCHEMA \"public\" REVOKE SELECT ON TABLES FROM \"old_reporting\";"
+    ));
+}
+
+#[test]
+fn test_merge_keeps_local_and_remote_additions() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let base = parse(r#"CREATE TABLE "public"."users" ("id" bigint NOT NULL);"#);
+    let local = parse(
+        r#"CREATE TABLE "public"."users" ("id" bigint NOT NULL);
+CREATE TABLE "public"."drafts" ("id" bigint NOT NULL);"#,
+    );
+    let remote = parse(
+        r#"CREATE TABLE "public"."users" ("id" bigint NOT NULL);
+CREATE TABLE "public"."comments" ("id" bigint NOT NULL);"#,
+    );
+
+    let merged = merge::merge_schemas(&base, &local, &remote);
+    assert!(merged.conflicts.is_empty(), "{:?}", merged.conflicts);
+    let mut tables: Vec<&String> = merged.schema.tables.keys().collect();
+    tables.sort();
+    assert_eq!(tables, vec!["\"public\".\"comments\"", "\"public\".\"drafts\"", "\"public\".\"users\""]);
+
+    // A table dropped remotely and left alone locally is dropped
+    let merged = merge::merge_schemas(&base, &local, &DbSchema::new());
+    assert!(!merged.schema.tables.contains_key("\"public\".\"users\""));
+}
+
+#[test]
+fn test_merge_reports_objects_changed_on_both_sides() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let base = parse(r#"CREATE TABLE "public"."users" ("id" bigint NOT NULL);"#);
+    let local = parse(r#"CREATE TABLE "public"."users" ("id" bigint NOT NULL, "name" text);"#);
+    let remote = parse(r#"CREATE TABLE "public"."users" ("id" bigint NOT NULL, "email" text);"#);
+
+    let merged = merge::merge_schemas(&base, &local, &remote);
+    assert_eq!(
+        merged.conflicts,
+        vec![merge::MergeConflict { kind: "table".to_string(), name: "\"public\".\"users\"".to_string() }]
+    );
+    // The local definition is kept for conflicting objects
+    assert!(merged.schema.tables["\"public\".\"users\""].columns.contains_key("name"));
+
+    // The same change on both sides is not a conflict
+    assert!(merge::merge_schemas(&base, &local, &local).conflicts.is_empty());
+}
+
+#[test]
+fn test_identical_rule_no_churn() {
+    let rule = |definition: &str| RuleInfo {
+        schema: "public".into(),
+        relation: "active_items".into(),
+        name: "protect_delete".into(),
+        definition: definition.into(),
+    };
+
+    // Local as written in the schema file, remote as pg_get_ruledef prints it
+    let mut local = DbSchema::new();
+    let local_rule = rule("ON DELETE TO public.active_items DO INSTEAD NOTHING");
+    local.rules.insert(local_rule.key(), local_rule);
+    let mut remote = DbSchema::new();
+    let remote_rule = rule("ON DELETE TO active_items DO INSTEAD NOTHING;");
+    remote.rules.insert(remote_rule.key(), remote_rule);
+
+    let diff = compute_diff(&remote, &local);
+    assert!(diff.rules_to_create.is_empty());
+    assert!(diff.rules_to_drop.is_empty());
+
+    let changed = rule("ON UPDATE TO public.active_items DO INSTEAD NOTHING");
+    local.rules.insert(changed.key(), changed);
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.rules_to_create.len(), 1);
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("CREATE OR REPLACE RULE \"protect_delete\" AS ON UPDATE TO public.active_items DO INSTEAD NOTHING;"), "{}", sql);
+}
+
+#[test]
+fn test_rule_normalization_only_strips_public_qualifiers() {
+    let normalize = utils::normalize_rule_definition;
+    assert_eq!(
+        normalize("ON DELETE TO public.items DO INSTEAD NOTHING"),
+        normalize("ON DELETE TO \"public\".\"items\" DO INSTEAD NOTHING;")
+    );
+    assert_eq!(normalize("ON DELETE TO items DO INSTEAD NOTHING"), normalize("ON DELETE TO public.items DO INSTEAD NOTHING"));
+    assert_ne!(normalize("ON DELETE TO mypublic.items DO INSTEAD NOTHING"), normalize("ON DELETE TO my.items DO INSTEAD NOTHING"));
+    assert_ne!(
+        normalize("ON INSERT TO log DO ALSO NOTIFY ch, 'public.x'"),
+        normalize("ON INSERT TO log DO ALSO NOTIFY ch, 'x'")
+    );
+}
+
+#[test]
+fn test_views_to_create_follow_their_dependencies() {
+    let view = |name: &str, definition: &str| ViewInfo {
+        schema: "public".into(),
+        name: name.into(),
+        definition: definition.into(),
+        is_materialized: false,
+        columns: vec![],
+        column_aliases: vec![],
+        indexes: vec![],
+        comment: None,
+        with_options: vec![],
+        check_option: None,
+        grants: vec![],
+        extension: None,
+    };
+
+    let mut local = DbSchema::new();
+    for v in [
+        view("a_recent", "SELECT * FROM public.z_active WHERE created_at > now() - interval '1 day'"),
+        view("b_plain", "SELECT 1 AS one"),
+        view("m_counts", "SELECT count(*) FROM a_recent"),
+        view("z_active", "SELECT * FROM items WHERE active"),
+    ] {
+        local.views.insert(qualified_name(&v.schema, &v.name), v);
+    }
+
+    let diff = compute_diff(&DbSchema::new(), &local);
+    let names: Vec<&str> = diff.views_to_create.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["b_plain", "z_active", "a_recent", "m_counts"]);
+}
+
+#[test]
+fn test_rules_reemitted_for_recreated_views() {
+    let view = |definition: &str| ViewInfo {
+        schema: "public".into(),
+        name: "item_stats".into(),
+        definition: definition.into(),
+        is_materialized: true,
+        columns: vec![],
+        column_aliases: vec![],
+        indexes: vec![],
+        comment: None,
+        with_options: vec![],
+        check_option: None,
+        grants: vec![],
+        extension: None,
+    };
+    let rule = RuleInfo {
+        schema: "public".into(),
+        relation: "item_stats".into(),
+        name: "no_delete".into(),
+        definition: "ON DELETE TO public.item_stats DO INSTEAD NOTHING".into(),
+    };
+
+    let mut remote = DbSchema::new();
+    remote.views.insert("\"public\".\"item_stats\"".into(), view("SELECT count(*) FROM items"));
+    remote.rules.insert(rule.key(), rule.clone());
+    let mut local = DbSchema::new();
+    local.views.insert("\"public\".\"item_stats\"".into(), view("SELECT count(*) AS n FROM items"));
+    local.rules.insert(rule.key(), rule.clone());
+
+    // The materialized view is dropped and recreated, so its unchanged rule comes back
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.views_to_update.len(), 1);
+    assert_eq!(diff.rules_to_create, vec![rule.clone()]);
+    let sql = crate::generator::generate_sql(&diff, &local);
+    let create_view = sql.find("CREATE MATERIALIZED VIEW").unwrap();
+    let create_rule = sql.find("CREATE OR REPLACE RULE \"no_delete\"").unwrap();
+    assert!(create_view < create_rule, "{}", sql);
+
+    // An unchanged view keeps its rule
+    let diff = compute_diff(&remote, &remote);
+    assert!(diff.rules_to_create.is_empty());
+}
+
+#[test]
+fn test_retain_object_previews_single_table() {
+    let remote = crate::parsing::parse_schema_sql(&[(
+        "remote.sql".to_string(),
+        "CREATE TABLE public.users (id bigint PRIMARY KEY); CREATE TABLE public.posts (id bigint PRIMARY KEY); CREATE VIEW public.v AS SELECT 1 AS one;".to_string(),
+    )])
+    .unwrap();
+    let local = crate::parsing::parse_schema_sql(&[(
+        "schema.sql".to_string(),
+        "CREATE TABLE public.users (id bigint PRIMARY KEY, email text); CREATE TABLE public.posts (id bigint PRIMARY KEY, body text); CREATE TABLE public.tags (id bigint PRIMARY KEY);".to_string(),
+    )])
+    .unwrap();
+
+    let mut diff = compute_diff(&remote, &local);
+    diff.retain_object("table", "public.users").unwrap();
+    let sql = crate::generator::generate_sql(&diff, &local);
+
+    let statements: Vec<&str> = sql.lines().filter(|l| !l.trim().is_empty()).collect();
+    assert!(!statements.is_empty());
+    for statement in &statements {
+        assert!(statement.starts_with("ALTER TABLE \"public\".\"users\""), "{}", sql);
+    }
+    assert!(sql.contains("ADD COLUMN \"email\""));
+}
+
+#[test]
+fn test_retain_object_rejects_unknown_kind() {
+    let mut diff = compute_diff(&DbSchema::new(), &DbSchema::new());
+    let err = diff.retain_object("tabel", "public.users").unwrap_err();
+    assert!(err.contains("Unknown object kind 'tabel'"), "{}", err);
+    assert!(err.contains("table, view, function"), "{}", err);
+}
+
+#[test]
+fn test_find_dependents_of_table() {
+    let schema = crate::parsing::parse_schema_sql(&[(
+        "schema.sql".to_string(),
+        r#"CREATE TABLE public.users (id bigint PRIMARY KEY, email text);
+CREATE TABLE public.posts (id bigint PRIMARY KEY, author_id bigint REFERENCES public.users (id));
+CREATE VIEW public.user_emails AS SELECT id, email FROM public.users;
+CREATE VIEW public.post_ids AS SELECT id FROM public.posts;"#
+            .to_string(),
+    )])
+    .unwrap();
+
+    let dependents = dependents::find_dependents(&schema, "table", "public.users").unwrap();
+    let names: Vec<(&str, &str)> = dependents.iter().map(|d| (d.kind.as_str(), d.name.as_str())).collect();
+    assert!(names.contains(&("view", "\"public\".\"user_emails\"")), "{:?}", dependents);
+    assert!(names.iter().any(|(kind, name)| *kind == "foreign_key" && name.starts_with("\"public\".\"posts\".")), "{:?}", dependents);
+    assert!(!names.iter().any(|(_, name)| name.contains("post_ids")), "{:?}", dependents);
+
+    // Dropping the table while the view stays is called out in the confirmation
+    let mut local = schema.clone();
+    local.tables.remove("\"public\".\"users\"");
+    local.tables.get_mut("\"public\".\"posts\"").unwrap().foreign_keys.clear();
+    local.views.remove("\"public\".\"post_ids\"");
+    let diff = compute_diff(&schema, &local);
+    let lines = dependents::dependents_of_drops(&diff, &schema);
+    assert!(lines.iter().any(|l| l.contains("view \"public\".\"user_emails\"")), "{:?}", lines);
+    assert!(!lines.iter().any(|l| l.contains("post_ids")), "{:?}", lines);
+
+    // Kinds without a dependents lookup are refused rather than reported as unreferenced
+    let err = dependents::find_dependents(&schema, "tabel", "public.users").unwrap_err();
+    assert!(err.starts_with("Unknown object kind 'tabel'"), "{}", err);
+    assert!(dependents::find_dependents(&schema, "sequence", "public.users_id_seq").is_err());
+    assert!(dependents::DEPENDENT_KINDS.iter().all(|k| OBJECT_KINDS.contains(k)));
+}
+
+#[test]
+fn test_ignored_grant_roles_suppress_custom_role_grants() {
+    let remote = crate::parsing::parse_schema_sql(&[(
+        "remote.sql".to_string(),
+        "CREATE FUNCTION public.touch() RETURNS void LANGUAGE sql AS $$ SELECT 1 $$;".to_string(),
+    )])
+    .unwrap();
+    let mut local = remote.clone();
+    let mut remote = remote;
+
+    let grant = |grantee: &str| FunctionGrant { grantee: grantee.to_string(), privilege: "EXECUTE".to_string() };
+    local.functions.values_mut().next().unwrap().grants = vec![grant("authenticated")];
+    // A convention role every function is granted to outside the schema files
+    remote.functions.values_mut().next().unwrap().grants = vec![grant("authenticated"), grant("reporting")];
+
+    let diff = compute_diff_with_options(&remote, &local, &DiffOptions::default());
+    assert_eq!(diff.functions_to_update.len(), 1, "reporting isn't ignored by default");
+
+    let mut roles = default_ignored_grant_roles();
+    roles.push("reporting".to_string());
+    let options = DiffOptions { ignored_grant_roles: Some(roles), ..Default::default() };
+    let diff = compute_diff_with_options(&remote, &local, &options);
+    assert!(diff.is_empty(), "{:?}", diff.functions_to_update);
+}
+
+#[test]
+fn test_schema_comment_change() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let remote = parse("COMMENT ON SCHEMA api IS 'Old description';");
+    assert_eq!(remote.schema_comments["api"].as_deref(), Some("Old description"));
+
+    let local = parse("COMMENT ON SCHEMA api IS 'Public API, don''t touch';");
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(
+        diff.schema_comments_to_set,
+        vec![("api".to_string(), Some("Public API, don't touch".to_string()))]
+    );
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("COMMENT ON SCHEMA \"api\" IS 'Public API, don''t touch';"), "{}", sql);
+
+    // IS NULL clears the remote comment; schemas the local files don't comment on are left alone
+    let cleared = parse("COMMENT ON SCHEMA api IS NULL;");
+    let diff = compute_diff(&remote, &cleared);
+    assert_eq!(diff.schema_comments_to_set, vec![("api".to_string(), None)]);
+    assert!(compute_diff(&remote, &DbSchema::new()).is_empty());
+
+    // Comments Postgres and Supabase set themselves are neither diffed nor written out
+    let builtin = parse("COMMENT ON SCHEMA public IS 'standard public schema';\nCOMMENT ON SCHEMA auth IS 'auth schema';");
+    assert!(compute_diff(&DbSchema::new(), &builtin).is_empty());
+    let files = crate::generator::split_sql(&builtin);
+    assert!(files.iter().all(|(_, sql)| !sql.contains("COMMENT ON SCHEMA")), "{:?}", files);
+}
+
+#[test]
+fn test_statement_order_flags_type_dropped_before_dependent_table() {
+    let remote = crate::parsing::parse_schema_sql(&[(
+        "schema.sql".to_string(),
+        r#"
+CREATE TYPE "public"."mood" AS ENUM ('happy', 'sad');
+CREATE TABLE "public"."people" ("id" bigint PRIMARY KEY, "current_mood" "public"."mood");
+"#
+        .to_string(),
+    )])
+    .unwrap();
+    let local = DbSchema::new();
+
+    // The generator drops the table first and defers the type drop to the end
+    let diff = compute_diff(&remote, &local);
+    let plan = crate::generator::generate_migration(&diff, &local);
+    let diagnostics = validate::validate_statement_order(&plan.transactional, &remote, &local);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    let statements = vec![
+        "DROP TYPE IF EXISTS \"public\".\"mood\" CASCADE;".to_string(),
+        "DROP TABLE IF EXISTS \"public\".\"people\" CASCADE;".to_string(),
+    ];
+    let diagnostics = validate::validate_statement_order(&statements, &remote, &local);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0].starts_with("Statement 1 drops type \"public\".\"mood\" before statement 2"), "{}", diagnostics[0]);
+
+    // Creating a table before the type its column uses is flagged too
+    let statements = vec![
+        "CREATE TABLE \"public\".\"people\" (\"id\" bigint PRIMARY KEY, \"current_mood\" \"public\".\"mood\");".to_string(),
+        "CREATE TYPE \"public\".\"mood\" AS ENUM ('happy', 'sad');".to_string(),
+    ];
+    let diagnostics = validate::validate_statement_order(&statements, &DbSchema::new(), &remote);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0].starts_with("Statement 1 uses type \"public\".\"mood\" before statement 2 creates it"), "{}", diagnostics[0]);
+}
+
+#[test]
+fn test_view_check_option_round_trip() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let table = "CREATE TABLE public.users (id bigint PRIMARY KEY, active boolean NOT NULL);";
+    let remote = parse(&format!("{}\nCREATE VIEW public.active_users AS SELECT id, active FROM public.users WHERE active;", table));
+    let local = parse(&format!(
+        "{}\nCREATE VIEW public.active_users AS SELECT id, active FROM public.users WHERE active WITH LOCAL CHECK OPTION;",
+        table
+    ));
+    let key = "\"public\".\"active_users\"";
+    assert_eq!(remote.views[key].check_option, None);
+    assert_eq!(local.views[key].check_option.as_deref(), Some("LOCAL"));
+
+    let diff = compute_diff(&remote, &local);
+    assert_eq!(diff.views_to_update.len(), 1);
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("WHERE active WITH LOCAL CHECK OPTION;"), "{}", sql);
+    assert!(compute_diff(&local, &local).is_empty());
+
+    // A bare WITH CHECK OPTION is CASCADED, as Postgres stores it
+    let cascaded = parse(&format!(
+        "{}\nCREATE OR REPLACE VIEW public.active_users AS SELECT id, active FROM public.users WHERE active WITH CHECK OPTION;",
+        table
+    ));
+    assert_eq!(cascaded.views[key].check_option.as_deref(), Some("CASCADED"));
+    assert_eq!(compute_diff(&local, &cascaded).views_to_update.len(), 1);
+}
+
+#[test]
+fn test_view_security_invoker_round_trip() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let view = |options: &str| {
+        parse(&format!(
+            "CREATE TABLE public.posts (id bigint PRIMARY KEY, title text);\nCREATE VIEW public.post_titles {}AS SELECT id, title FROM public.posts;",
+            options
+        ))
+    };
+    let key = "\"public\".\"post_titles\"";
+    let local = view("WITH (security_invoker = true) ");
+    assert_eq!(local.views[key].with_options, vec!["security_invoker=true".to_string()]);
+
+    // Introspection reads reloptions as written, e.g. `security_invoker=on`
+    let mut remote = local.clone();
+    remote.views.get_mut(key).unwrap().with_options = vec!["security_invoker=on".to_string()];
+    assert!(compute_diff(&remote, &local).is_empty());
+    assert!(compute_diff(&view(""), &view("WITH (security_barrier = false) ")).is_empty());
+
+    let diff = compute_diff(&view(""), &local);
+    assert_eq!(diff.views_to_update.len(), 1);
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("CREATE OR REPLACE VIEW \"public\".\"post_titles\" WITH (security_invoker=true) AS"), "{}", sql);
+
+    let disabled = view("WITH (security_invoker = false) ");
+    let diff = compute_diff(&remote, &disabled);
+    assert_eq!(diff.views_to_update.len(), 1);
+    let sql = crate::generator::generate_sql(&diff, &disabled);
+    assert!(sql.contains("WITH (security_invoker=false)"), "{}", sql);
+}
+
+#[test]
+fn test_view_column_aliases_round_trip() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let view = |aliases: &str| {
+        parse(&format!(
+            "CREATE TABLE public.posts (id bigint PRIMARY KEY, title text);\nCREATE VIEW public.post_titles {}AS SELECT id, title FROM public.posts;",
+            aliases
+        ))
+    };
+    let key = "\"public\".\"post_titles\"";
+    let local = view("(post_id, heading) ");
+    assert_eq!(local.views[key].column_aliases, vec!["post_id".to_string(), "heading".to_string()]);
+
+    let diff = compute_diff(&view(""), &local);
+    assert_eq!(diff.views_to_update.len(), 1);
+    let sql = crate::generator::generate_sql(&diff, &local);
+    assert!(sql.contains("CREATE OR REPLACE VIEW \"public\".\"post_titles\" (\"post_id\", \"heading\") AS"), "{}", sql);
+
+    // The generated view parses back to the same aliases, without churn
+    let reparsed = parse(&format!("CREATE TABLE public.posts (id bigint PRIMARY KEY, title text);\n{}", sql));
+    assert_eq!(reparsed.views[key].column_aliases, local.views[key].column_aliases);
+    assert!(compute_diff(&reparsed, &local).is_empty());
+}
+
+#[test]
+fn test_supabase_managed_objects_are_never_dropped() {
+    let table = |schema: &str, name: &str| TableInfo {
+        schema: schema.to_string(),
+        table_name: name.to_string(),
+        columns: HashMap::from([(
+            "id".to_string(),
+            ColumnInfo {
+                column_name: "id".to_string(),
+                data_type: "uuid".to_string(),
+                udt_name: "uuid".to_string(),
+                is_primary_key: true,
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let local_todos = table("public", "todos");
+    let mut remote_todos = local_todos.clone();
+    // A database webhook, created from the dashboard
+    remote_todos.triggers.push(TriggerInfo {
+        name: "todos_webhook".to_string(),
+        events: vec!["INSERT".to_string()],
+        timing: "AFTER".to_string(),
+        orientation: "ROW".to_string(),
+        function_name: "supabase_functions.http_request".to_string(),
+        when_clause: None,
+    });
+
+    let mut remote = DbSchema::new();
+    remote.tables.insert("\"public\".\"todos\"".to_string(), remote_todos);
+    remote.tables.insert("\"storage\".\"objects\"".to_string(), table("storage", "objects"));
+    remote.tables.insert("\"realtime\".\"messages\"".to_string(), table("realtime", "messages"));
+    remote.views.insert(
+        "\"vault\".\"decrypted_secrets\"".to_string(),
+        ViewInfo {
+            schema: "vault".to_string(),
+            name: "decrypted_secrets".to_string(),
+            definition: "SELECT id FROM vault.secrets".to_string(),
+            is_materialized: false,
+            columns: vec![],
+            column_aliases: vec![],
+            indexes: vec![],
+            comment: None,
+            with_options: vec![],
+            check_option: None,
+            grants: vec![],
+            extension: None,
+        },
+    );
+    remote.functions.insert(
+        "\"storage\".\"foldername\"(text)".to_string(),
+        FunctionInfo {
+            schema: "storage".to_string(),
+            name: "foldername".to_string(),
+            args: vec![FunctionArg { name: "name".to_string(), type_: "text".to_string(), mode: None, default_value: None }],
+            return_type: "text[]".to_string(),
+            language: "plpgsql".to_string(),
+            definition: "BEGIN RETURN string_to_array(name, '/'); END".to_string(),
+            volatility: None,
+            is_strict: false,
+            security_definer: false,
+            config_params: vec![],
+            grants: vec![],
+            extension: None,
+        },
+    );
+
+    let mut local = DbSchema::new();
+    local.tables.insert("\"public\".\"todos\"".to_string(), local_todos);
+
+    let diff = compute_diff(&remote, &local);
+    assert!(diff.tables_to_drop.is_empty(), "{:?}", diff.tables_to_drop);
+    assert!(diff.views_to_drop.is_empty(), "{:?}", diff.views_to_drop);
+    assert!(diff.functions_to_drop.is_empty(), "{:?}", diff.functions_to_drop);
+    assert!(diff.table_changes.is_empty(), "{:?}", diff.table_changes);
+    assert!(diff.is_empty());
+
+    // Scoping to watched schemas goes through the same path
+    let options = DiffOptions { watched_schemas: Some(vec!["public".to_string(), "storage".to_string()]), ..Default::default() };
+    assert!(compute_diff_with_options(&remote, &local, &options).tables_to_drop.is_empty());
+}
+
+#[test]
+fn test_partition_key_and_bound_changes() {
+    let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let events = |key: &str, bound: &str| {
+        parse(&format!(
+            "CREATE TABLE public.events (id bigint NOT NULL, created_at date NOT NULL, kind text) PARTITION BY {};\nCREATE TABLE public.events_2024 PARTITION OF public.events {};",
+            key, bound
+        ))
+    };
+    let table = "\"public\".\"events\"";
+    let remote = events("RANGE (created_at)", "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')");
+
+    // Spelling differences are not changes
+    let respelled = events("range (\"created_at\")", "for values from ( '2024-01-01' )  to ('2025-01-01')");
+    assert!(compute_diff(&remote, &respelled).is_empty());
+
+    let widened = events("RANGE (created_at)", "FOR VALUES FROM ('2024-01-01') TO ('2026-01-01')");
+    let diff = compute_diff(&remote, &widened);
+    assert_eq!(diff.table_changes[table].partition_bounds_to_change.len(), 1);
+    let sql = crate::generator::generate_sql(&diff, &widened);
+    assert!(sql.contains("ALTER TABLE \"public\".\"events\" DETACH PARTITION \"public\".\"events_2024\";"), "{}", sql);
+    assert!(
+        sql.contains("ALTER TABLE \"public\".\"events\" ATTACH PARTITION \"public\".\"events_2024\" FOR VALUES FROM ('2024-01-01') TO ('2026-01-01');"),
+        "{}",
+        sql
+    );
+
+    // Postgres prints timestamptz bounds in full, with the UTC offset
+    let timestamps = |bound: &str| {
+        parse(&format!(
+            "CREATE TABLE public.logs (id bigint NOT NULL, at timestamptz NOT NULL) PARTITION BY RANGE (at);\nCREATE TABLE public.logs_2024 PARTITION OF public.logs {};",
+            bound
+        ))
+    };
+    let local = timestamps("FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')");
+    let introspected = timestamps("FOR VALUES FROM ('2024-01-01 00:00:00+00') TO ('2025-01-01 00:00:00+00')");
+    assert!(compute_diff(&introspected, &local).is_empty());
+    let offset = timestamps("FOR VALUES FROM ('2023-12-31 19:00:00-05') TO ('2025-01-01T00:00:00Z')");
+    assert!(compute_diff(&introspected, &offset).is_empty());
+    let shifted = timestamps("FOR VALUES FROM ('2024-01-01 00:00:00+01') TO ('2025-01-01 00:00:00+00')");
+    assert_eq!(compute_diff(&introspected, &shifted).table_changes.len(), 1);
+
+    // A new partition key can't be applied in place; it's reported, not generated
+    let rekeyed = events("LIST (kind)", "FOR VALUES IN ('click')");
+    let diff = compute_diff(&remote, &rekeyed);
+    assert_eq!(
+        diff.table_changes[table].partition_strategy_change,
+        Some((Some("RANGE (created_at)".to_string()), Some("LIST (kind)".to_string())))
+    );
+    assert!(diff.summarize().contains("requires rebuilding the table"));
+    assert!(!crate::generator::generate_sql(&diff, &rekeyed).contains("PARTITION BY"));
+}
@@ -1,6 +1,17 @@
 use sqlparser::dialect::PostgreSqlDialect;
 use sqlparser::parser::Parser;
 use sqlparser::ast::{Expr, Statement, Value, SetExpr, TableFactor, SelectItem};
+use once_cell::sync::Lazy;
+
+/// A `public.` schema qualifier in normalized SQL, not the tail of a longer name
+static TIMESTAMP_LITERAL_RE: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(
+        r"^(\d{4}-\d{2}-\d{2})(?:[ T](\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?))?\s*(?:(Z)|([+-])(\d{2})(?::?(\d{2}))?)?$",
+    )
+    .unwrap()
+});
+
+static PUBLIC_QUALIFIER_RE: Lazy<regex::Regex> = Lazy::new(|| regex::Regex::new(r"(^|[^a-z0-9_$.])public\.").unwrap());
 
 
 fn clean_function_arg(arg: sqlparser::ast::FunctionArg) -> sqlparser::ast::FunctionArg {
@@ -522,18 +533,11 @@ pub fn normalize_option(opt: &Option<String>) -> Option<String> {
 pub fn normalize_function_definition(definition: &str) -> String {
     let mut s = definition.to_string();
     
-    // Normalize dollar quoting - replace common $<tag>$ patterns with $$
-    // These are the most common dollar-quote tags used in PostgreSQL
-    let dollar_quote_tags = [
-        "$function$", "$FUNCTION$", 
-        "$body$", "$BODY$",
-        "$code$", "$CODE$",
-        "$sql$", "$SQL$",
-        "$plpgsql$", "$PLPGSQL$",
-    ];
-    for tag in dollar_quote_tags {
-        s = s.replace(tag, "$$");
-    }
+    // Normalize dollar quoting - replace any $<tag>$ with $$, including tags of
+    // dollar-quoted literals nested inside the body. Tags can't start with a digit,
+    // so positional parameters like $1 are left alone.
+    let dollar_tag = regex::Regex::new(r"\$[A-Za-z_][A-Za-z0-9_]*\$").unwrap();
+    s = dollar_tag.replace_all(&s, "$$$$").to_string();
     
     // Remove double quotes around identifiers
     // This handles "public"."func_name" -> public.func_name
@@ -659,6 +663,124 @@ pub fn normalize_view_definition(definition: &str) -> String {
     cleanup_view_parens(&normalized)
 }
 
+/// Normalize view `WITH (...)` options such as `security_invoker` for comparison:
+/// `name=value` with boolean spellings folded to `true`/`false`, options left at their
+/// `false` default dropped, sorted. Parsed options read `security_invoker = true`,
+/// introspected ones `security_invoker=on` if that's how they were set.
+pub fn normalize_view_options(options: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = options
+        .iter()
+        .filter_map(|option| {
+            let (name, value) = option.split_once('=').unwrap_or((option, "true"));
+            let name = name.trim().trim_matches('"').to_lowercase();
+            let value = value.trim().trim_matches('\'').to_lowercase();
+            let value = match value.as_str() {
+                "on" | "yes" | "1" | "t" => "true".to_string(),
+                "off" | "no" | "0" | "f" => "false".to_string(),
+                _ => value,
+            };
+            (value != "false").then(|| format!("{}={}", name, value))
+        })
+        .collect();
+    normalized.sort();
+    normalized
+}
+
+/// Normalize a partition key (`RANGE (created_at)`) or bound (`FOR VALUES IN ('a')`) for
+/// comparison: whitespace collapsed, keywords uppercased and simple identifiers unquoted.
+/// Date and timestamp literals are compared as UTC instants, since Postgres prints
+/// a `timestamptz` bound written as `'2024-01-01'` as `'2024-01-01 00:00:00+00'`.
+/// Other literals are kept as written.
+pub fn normalize_partition_clause(clause: &str) -> String {
+    let quoted_ident = regex::Regex::new(r#""([a-z_][a-z0-9_]*)""#).unwrap();
+    let clause = quoted_ident.replace_all(clause.trim(), "$1");
+
+    let mut normalized = String::new();
+    let mut literal: Option<String> = None;
+    let mut pending_space = false;
+    for c in clause.chars() {
+        if let Some(text) = literal.as_mut() {
+            if c == '\'' {
+                normalized.push_str(&normalize_bound_literal(text));
+                normalized.push(c);
+                literal = None;
+            } else {
+                text.push(c);
+            }
+        } else if c.is_whitespace() {
+            pending_space = true;
+        } else {
+            // Spacing around punctuation is cosmetic
+            let punctuation = |c: char| matches!(c, '(' | ')' | ',');
+            if pending_space && !punctuation(c) && !normalized.ends_with(punctuation) {
+                normalized.push(' ');
+            }
+            pending_space = false;
+            if c == '\'' {
+                normalized.push(c);
+                literal = Some(String::new());
+            } else {
+                normalized.extend(c.to_uppercase());
+            }
+        }
+    }
+    if let Some(text) = literal {
+        normalized.push_str(&text);
+    }
+    normalized
+}
+
+/// `'2024-01-01'`, `'2024-01-01 00:00:00+00'` and `'2023-12-31 19:00:00-05'` are the
+/// same bound: rewrite date and timestamp text as a UTC `YYYY-MM-DD HH:MM:SS`.
+/// Timestamps without an offset are taken as UTC, the timezone Supabase databases use.
+fn normalize_bound_literal(text: &str) -> String {
+    let Some(caps) = TIMESTAMP_LITERAL_RE.captures(text) else {
+        return text.to_string();
+    };
+    let Ok(date) = chrono::NaiveDate::parse_from_str(&caps[1], "%Y-%m-%d") else {
+        return text.to_string();
+    };
+    let time = match caps.get(2) {
+        Some(time) => match chrono::NaiveTime::parse_from_str(time.as_str(), "%H:%M:%S%.f")
+            .or_else(|_| chrono::NaiveTime::parse_from_str(time.as_str(), "%H:%M"))
+        {
+            Ok(time) => time,
+            Err(_) => return text.to_string(),
+        },
+        None => chrono::NaiveTime::MIN,
+    };
+    let offset_seconds = match (caps.get(4), caps.get(5)) {
+        (Some(sign), Some(hours)) => {
+            let hours: i64 = hours.as_str().parse().unwrap_or(0);
+            let minutes: i64 = caps.get(6).and_then(|m| m.as_str().parse().ok()).unwrap_or(0);
+            let seconds = hours * 3600 + minutes * 60;
+            if sign.as_str() == "-" { -seconds } else { seconds }
+        }
+        _ => 0,
+    };
+    let utc = date.and_time(time) - chrono::Duration::seconds(offset_seconds);
+    utc.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+}
+
+/// Normalize a rule body (`ON ... DO ...`) for comparison. `pg_get_ruledef` leaves
+/// relations in `public` unqualified and appends a semicolon.
+pub fn normalize_rule_definition(definition: &str) -> String {
+    let normalized = normalize_sql(definition.trim().trim_end_matches(';'));
+    // Odd segments between single quotes are string literals and stay as written
+    normalized
+        .split('\'')
+        .enumerate()
+        .map(|(i, segment)| {
+            if i % 2 == 0 {
+                PUBLIC_QUALIFIER_RE.replace_all(segment, "$1").into_owned()
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("'")
+}
+
 /// Normalize CHECK constraint expressions for comparison.
 /// Strips the CHECK keyword, type casts, and normalizes expressions.
 /// This handles differences between local parsing (CHECK (status IN ('a', 'b'))) and
@@ -777,9 +899,15 @@ pub fn normalize_default_option(opt: &Option<String>) -> Option<String> {
 /// - timetz -> time with time zone
 /// - time -> time without time zone
 /// - public.custom_type -> custom_type (strip default schema prefix)
+///
+/// Type modifiers are kept in the form `format_type` reports them, so
+/// `varchar(255)` matches `character varying(255)`, `timestamptz(3)` matches
+/// `timestamp(3) with time zone` and `decimal(10, 2)` matches `numeric(10,2)`,
+/// while `numeric(10,2)` vs `numeric` is still a real change.
 pub fn normalize_data_type(data_type: &str) -> String {
     let lower = data_type.to_lowercase();
-    let trimmed = lower.trim();
+    let collapsed = lower.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.as_str();
     
     // Strip schema prefixes from types
     // e.g. "public.file_node_kind" -> "file_node_kind"
@@ -791,34 +919,72 @@ pub fn normalize_data_type(data_type: &str) -> String {
         .find(|prefix| trimmed.starts_with(*prefix))
         .map(|prefix| &trimmed[prefix.len()..])
         .unwrap_or(trimmed);
-    
-    // Check for exact matches first
-    match trimmed {
-        "decimal" => "numeric".to_string(),
-        "int" | "int4" | "serial" => "integer".to_string(),
-        "int8" | "bigserial" => "bigint".to_string(),
-        "int2" | "smallserial" => "smallint".to_string(),
-        "bool" => "boolean".to_string(),
-        "float8" | "float" => "double precision".to_string(), // In Postgres 'float' is double per default
-        "real" | "float4" => "real".to_string(),
-        "character varying" | "varchar" => "character varying".to_string(), // Normalize to canonical name
-        "char" => "character".to_string(),
-        // Timestamp type aliases - normalize to canonical
-        "timestamptz" => "timestamp with time zone".to_string(),
-        "timestamp with time zone" => "timestamp with time zone".to_string(),
-        "timestamp" => "timestamp without time zone".to_string(),
-        "timestamp without time zone" => "timestamp without time zone".to_string(),
-        "timetz" => "time with time zone".to_string(),
-        "time with time zone" => "time with time zone".to_string(),
-        "time" => "time without time zone".to_string(),
-        "time without time zone" => "time without time zone".to_string(),
-        
-        // Handle array types recursively
-        s if s.ends_with("[]") => {
-            let inner = &s[..s.len() - 2];
-            format!("{}[]", normalize_data_type(inner))
+
+    // Handle array types recursively
+    if let Some(inner) = trimmed.strip_suffix("[]") {
+        return format!("{}[]", normalize_data_type(inner));
+    }
+
+    // Split "base(modifiers) [with|without time zone]"; anything else (custom types
+    // like vector(1536) or geography(point,4326)) is compared as written
+    let type_re = regex::Regex::new(
+        r"^([a-z_][a-z0-9_ ]*?) ?(?:\(([0-9, ]+)\))? ?(with time zone|without time zone)?$",
+    )
+    .unwrap();
+    let Some(caps) = type_re.captures(trimmed) else {
+        return trimmed.to_string();
+    };
+    let base = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+    let modifiers: Vec<&str> = caps
+        .get(2)
+        .map(|m| m.as_str().split(',').map(|p| p.trim()).collect())
+        .unwrap_or_default();
+    let zone = caps.get(3).map(|m| m.as_str());
+
+    let with_modifiers = |name: &str, modifiers: &[&str]| {
+        if modifiers.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}({})", name, modifiers.join(","))
+        }
+    };
+
+    match (base, zone) {
+        // Timestamp/time aliases; the precision goes before the time zone clause
+        ("timestamptz", None) | ("timestamp", Some("with time zone")) => {
+            format!("{} with time zone", with_modifiers("timestamp", &modifiers))
+        }
+        ("timestamp", _) => format!("{} without time zone", with_modifiers("timestamp", &modifiers)),
+        ("timetz", None) | ("time", Some("with time zone")) => {
+            format!("{} with time zone", with_modifiers("time", &modifiers))
+        }
+        ("time", _) => format!("{} without time zone", with_modifiers("time", &modifiers)),
+        (_, Some(_)) => trimmed.to_string(),
+
+        // numeric(p) is numeric(p,0)
+        ("decimal" | "numeric", _) => match modifiers.as_slice() {
+            [precision] => format!("numeric({},0)", precision),
+            _ => with_modifiers("numeric", &modifiers),
+        },
+        // float(p) is real up to 24 bits of precision, double precision above
+        ("float", _) => match modifiers.first().and_then(|p| p.parse::<u32>().ok()) {
+            Some(p) if p <= 24 => "real".to_string(),
+            _ => "double precision".to_string(),
         },
-        _ => trimmed.to_string(),
+        ("int" | "int4" | "serial", _) => "integer".to_string(),
+        ("int8" | "bigserial", _) => "bigint".to_string(),
+        ("int2" | "smallserial", _) => "smallint".to_string(),
+        ("bool", _) => "boolean".to_string(),
+        ("float8", _) => "double precision".to_string(),
+        ("float4", _) => "real".to_string(),
+        ("varchar" | "character varying", _) => with_modifiers("character varying", &modifiers),
+        // char without a length is char(1)
+        ("char" | "character" | "bpchar", _) => {
+            with_modifiers("character", if modifiers.is_empty() { &["1"][..] } else { &modifiers[..] })
+        }
+        ("varbit" | "bit varying", _) => with_modifiers("bit varying", &modifiers),
+        ("bit", _) => with_modifiers("bit", if modifiers.is_empty() { &["1"][..] } else { &modifiers[..] }),
+        (name, _) => with_modifiers(name, &modifiers),
     }
 }
 
@@ -873,3 +1039,35 @@ pub fn normalize_function_return_type(return_type: &str) -> String {
     normalize_data_type(trimmed)
 }
 
+
+/// Match `text` against a simple glob pattern (`*` matches any run of characters, `?` a single one)
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<usize> = None;
+    let mut mark = 0;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some(pi);
+            mark = ti;
+            pi += 1;
+        } else if let Some(s) = star {
+            // Backtrack: let the last '*' swallow one more character
+            pi = s + 1;
+            mark += 1;
+            ti = mark;
+        } else {
+            return false;
+        }
+    }
+
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
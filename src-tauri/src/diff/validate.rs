@@ -0,0 +1,402 @@
+//! Pre-apply checks that objects referenced by a migration will exist once it runs,
+//! and that its statements create and drop them in a safe order.
+
+use super::dependents::{find_dependents, Dependent};
+use super::SchemaDiff;
+use crate::schema::{qualified_name, DbSchema, ForeignKeyInfo, TriggerInfo, ViewInfo};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sqlparser::ast::{TableFactor, Visit, Visitor};
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::parser::Parser;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// A possibly schema-qualified, possibly quoted object name
+const NAME: &str = r#"(?:"(?:[^"]|"")+"|\w+)(?:\s*\.\s*(?:"(?:[^"]|"")+"|\w+))?"#;
+
+static OBJECT_STATEMENT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r#"(?is)^(?P<action>CREATE|ALTER|DROP)\s+(?:OR\s+REPLACE\s+)?(?:UNLOGGED\s+)?(?:MATERIALIZED\s+)?(?P<kind>TABLE|VIEW|TYPE|DOMAIN|FUNCTION)\s+(?:IF\s+(?:NOT\s+)?EXISTS\s+)?(?:ONLY\s+)?(?P<name>{})"#,
+        NAME
+    ))
+    .unwrap()
+});
+
+static ON_TABLE_STATEMENT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r#"(?is)^CREATE\s+(?:OR\s+REPLACE\s+)?(?:UNIQUE\s+)?(?:INDEX|TRIGGER|POLICY)\b.*?\sON\s+(?:ONLY\s+)?(?P<name>{})"#,
+        NAME
+    ))
+    .unwrap()
+});
+
+/// Objects that exist after the migration: remote objects it doesn't drop plus
+/// the ones it creates. Only schemas seen on either side are checked, so references
+/// into unmanaged schemas like `auth` or `extensions` never warn.
+struct Available {
+    schemas: HashSet<String>,
+    /// `(schema, name)` of functions
+    functions: HashSet<(String, String)>,
+    /// Qualified names of tables and views
+    relations: HashSet<String>,
+}
+
+impl Available {
+    fn new(diff: &SchemaDiff, remote: &DbSchema, local: &DbSchema) -> Self {
+        let schemas = [remote, local]
+            .iter()
+            .flat_map(|s| {
+                s.tables
+                    .values()
+                    .map(|t| t.schema.clone())
+                    .chain(s.views.values().map(|v| v.schema.clone()))
+                    .chain(s.functions.values().map(|f| f.schema.clone()))
+            })
+            .collect();
+
+        let functions = remote
+            .functions
+            .iter()
+            .filter(|(signature, _)| !diff.functions_to_drop.contains(signature))
+            .map(|(_, f)| (f.schema.clone(), f.name.clone()))
+            .chain(diff.functions_to_create.iter().map(|f| (f.schema.clone(), f.name.clone())))
+            .collect();
+
+        let relations = remote
+            .tables
+            .keys()
+            .filter(|key| !diff.tables_to_drop.contains(key))
+            .chain(remote.views.keys().filter(|key| !diff.views_to_drop.contains(key)))
+            .cloned()
+            .chain(diff.tables_to_create.iter().cloned())
+            .chain(diff.views_to_create.iter().map(|v| qualified_name(&v.schema, &v.name)))
+            .collect();
+
+        Self { schemas, functions, relations }
+    }
+
+    fn has_function(&self, schema: &str, name: &str) -> bool {
+        !self.schemas.contains(schema) || self.functions.contains(&(schema.to_string(), name.to_string()))
+    }
+
+    fn has_relation(&self, schema: &str, name: &str) -> bool {
+        !self.schemas.contains(schema) || self.relations.contains(&qualified_name(schema, name))
+    }
+}
+
+/// Check that trigger functions, foreign key targets and the relations views select
+/// from resolve against the remote schema plus what the migration creates.
+/// Returns one warning per unresolved reference; an empty list means nothing was found.
+pub fn validate_references(diff: &SchemaDiff, remote: &DbSchema, local: &DbSchema) -> Vec<String> {
+    let available = Available::new(diff, remote, local);
+    let mut warnings = Vec::new();
+
+    let mut table_keys: Vec<&String> = diff.tables_to_create.iter().chain(diff.table_changes.keys()).collect();
+    table_keys.sort();
+    for table_key in table_keys {
+        let (triggers, foreign_keys): (Vec<&TriggerInfo>, Vec<&ForeignKeyInfo>) =
+            match diff.table_changes.get(table_key) {
+                Some(changes) => (
+                    changes.triggers_to_create.iter().collect(),
+                    changes.foreign_keys_to_create.iter().collect(),
+                ),
+                None => match local.tables.get(table_key) {
+                    Some(table) => (table.triggers.iter().collect(), table.foreign_keys.iter().collect()),
+                    None => continue,
+                },
+            };
+
+        for trigger in triggers {
+            let (schema, name) = split_qualified(&trigger.function_name);
+            if !available.has_function(&schema, &name) {
+                warnings.push(format!(
+                    "Trigger '{}' on {} calls function {}, which is not defined locally or on the remote",
+                    trigger.name,
+                    table_key,
+                    qualified_name(&schema, &name)
+                ));
+            }
+        }
+
+        for fk in foreign_keys {
+            if !available.has_relation(&fk.foreign_schema, &fk.foreign_table) {
+                warnings.push(format!(
+                    "Foreign key '{}' on {} references table {}, which is not defined locally or on the remote",
+                    fk.constraint_name,
+                    table_key,
+                    qualified_name(&fk.foreign_schema, &fk.foreign_table)
+                ));
+            }
+        }
+    }
+
+    for view in diff.views_to_create.iter().chain(&diff.views_to_update) {
+        for (schema, name) in view_relations(view) {
+            if !available.has_relation(&schema, &name) {
+                warnings.push(format!(
+                    "View {} selects from {}, which is not defined locally or on the remote",
+                    qualified_name(&view.schema, &view.name),
+                    qualified_name(&schema, &name)
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Create,
+    Alter,
+    Drop,
+}
+
+/// What one migration statement does to which object. Tables and views share the
+/// "relation" kind; enums, composite types and domains the "type" kind.
+#[derive(Debug)]
+struct Step {
+    action: Action,
+    kind: &'static str,
+    schema: String,
+    name: String,
+}
+
+impl Step {
+    fn is(&self, kind: &str, schema: &str, name: &str) -> bool {
+        self.kind == kind && self.schema == schema && self.name == name
+    }
+
+    fn label(&self) -> String {
+        let kind = if self.kind == "relation" { "table" } else { self.kind };
+        format!("{} {}", kind, qualified_name(&self.schema, &self.name))
+    }
+}
+
+/// Check the order of a migration's statements (as generated, before any transaction
+/// wrapping): an object must be created before a statement uses it, and dropped only
+/// after the last statement that alters or drops something depending on it. Dependencies
+/// come from `local` for created objects and from `remote` for dropped ones, so this
+/// catches drop-and-recreate sequences the generator got wrong. Returns one diagnostic
+/// per unsafe pair of statements; statements are numbered from 1.
+pub fn validate_statement_order(statements: &[String], remote: &DbSchema, local: &DbSchema) -> Vec<String> {
+    let steps: Vec<Option<Step>> = statements.iter().map(|s| classify_statement(s)).collect();
+    let mut diagnostics = Vec::new();
+    // An object is usually both dropped and recreated; look its dependents up once per side
+    let mut local_dependents: HashMap<(&str, String), Vec<Dependent>> = HashMap::new();
+    let mut remote_dependents: HashMap<(&str, String), Vec<Dependent>> = HashMap::new();
+
+    let exists_at = |index: usize, kind: &str, schema: &str, name: &str| {
+        let mut exists = exists_remotely(remote, kind, schema, name);
+        for step in steps[..index].iter().flatten().filter(|s| s.is(kind, schema, name)) {
+            match step.action {
+                Action::Create => exists = true,
+                Action::Drop => exists = false,
+                Action::Alter => {}
+            }
+        }
+        exists
+    };
+
+    for (j, step) in steps.iter().enumerate() {
+        let Some(step) = step else { continue };
+        let Some(lookup_kind) = dependents_kind(step.kind) else { continue };
+        let key = qualified_name(&step.schema, &step.name);
+
+        match step.action {
+            // Create before use: nothing earlier may touch an object depending on this one
+            // unless the object already existed at that point
+            Action::Create => {
+                let dependents = local_dependents
+                    .entry((lookup_kind, key.clone()))
+                    .or_insert_with(|| find_dependents(local, lookup_kind, &key).unwrap_or_default());
+                for dependent in dependents.iter() {
+                    // Function bodies only resolve tables and functions when they run
+                    if dependent.kind == "function" && lookup_kind != "type" {
+                        continue;
+                    }
+                    let (kind, schema, name) = dependent_owner(dependent);
+                    let earlier = steps[..j].iter().enumerate().find(|(_, s)| {
+                        s.as_ref().is_some_and(|s| s.action != Action::Drop && s.is(kind, &schema, &name))
+                    });
+                    if let Some((i, _)) = earlier {
+                        if !exists_at(i, step.kind, &step.schema, &step.name) {
+                            diagnostics.push(format!(
+                                "Statement {} uses {} before statement {} creates it ({} {} {})",
+                                i + 1,
+                                step.label(),
+                                j + 1,
+                                dependent.kind,
+                                dependent.name,
+                                dependent.reason
+                            ));
+                        }
+                    }
+                }
+            }
+            // Drop after last use: nothing later may alter or drop a remote object
+            // depending on this one
+            Action::Drop => {
+                let dependents = remote_dependents
+                    .entry((lookup_kind, key.clone()))
+                    .or_insert_with(|| find_dependents(remote, lookup_kind, &key).unwrap_or_default());
+                for dependent in dependents.iter() {
+                    let (kind, schema, name) = dependent_owner(dependent);
+                    if step.is(kind, &schema, &name) {
+                        continue;
+                    }
+                    let later = steps.iter().enumerate().skip(j + 1).find(|(_, s)| {
+                        s.as_ref().is_some_and(|s| s.action != Action::Create && s.is(kind, &schema, &name))
+                    });
+                    if let Some((k, _)) = later {
+                        diagnostics.push(format!(
+                            "Statement {} drops {} before statement {} changes {} {}, which {}",
+                            j + 1,
+                            step.label(),
+                            k + 1,
+                            dependent.kind,
+                            dependent.name,
+                            dependent.reason
+                        ));
+                    }
+                }
+            }
+            Action::Alter => {}
+        }
+    }
+
+    diagnostics
+}
+
+/// Object created, altered or dropped by a generated statement. Index, trigger and
+/// policy statements count as altering their table; anything else is ignored.
+fn classify_statement(sql: &str) -> Option<Step> {
+    let sql = sql.trim_start();
+    if let Some(caps) = OBJECT_STATEMENT_RE.captures(sql) {
+        let action = match caps["action"].to_uppercase().as_str() {
+            "CREATE" => Action::Create,
+            "ALTER" => Action::Alter,
+            _ => Action::Drop,
+        };
+        let kind = match caps["kind"].to_uppercase().as_str() {
+            "TABLE" | "VIEW" => "relation",
+            "FUNCTION" => "function",
+            _ => "type",
+        };
+        let (schema, name) = split_qualified(&caps["name"]);
+        return Some(Step { action, kind, schema, name });
+    }
+    let caps = ON_TABLE_STATEMENT_RE.captures(sql)?;
+    let (schema, name) = split_qualified(&caps["name"]);
+    Some(Step { action: Action::Alter, kind: "relation", schema, name })
+}
+
+/// `find_dependents` kind to look up dependents of a step's object
+fn dependents_kind(kind: &str) -> Option<&'static str> {
+    match kind {
+        "relation" => Some("table"),
+        "function" => Some("function"),
+        "type" => Some("type"),
+        _ => None,
+    }
+}
+
+/// The object whose statements stand for `dependent`: columns, constraints, triggers
+/// and rules belong to their table
+fn dependent_owner(dependent: &Dependent) -> (&'static str, String, String) {
+    let parts = split_name_parts(&dependent.name);
+    let kind = match dependent.kind.as_str() {
+        "function" => "function",
+        "type" | "domain" | "enum" => "type",
+        _ => "relation",
+    };
+    match parts.as_slice() {
+        [schema, name, ..] => (kind, schema.clone(), name.clone()),
+        [name] => (kind, "public".to_string(), name.clone()),
+        [] => (kind, "public".to_string(), String::new()),
+    }
+}
+
+/// Split a dotted name into unquoted parts, keeping dots inside double quotes
+fn split_name_parts(name: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in name.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '.' if !quoted => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+fn exists_remotely(remote: &DbSchema, kind: &str, schema: &str, name: &str) -> bool {
+    let is = |s: &str, n: &str| s == schema && n == name;
+    match kind {
+        "relation" => {
+            remote.tables.values().any(|t| is(&t.schema, &t.table_name))
+                || remote.views.values().any(|v| is(&v.schema, &v.name))
+        }
+        "function" => remote.functions.values().any(|f| is(&f.schema, &f.name)),
+        _ => {
+            remote.enums.values().any(|e| is(&e.schema, &e.name))
+                || remote.composite_types.values().any(|c| is(&c.schema, &c.name))
+                || remote.domains.values().any(|d| is(&d.schema, &d.name))
+        }
+    }
+}
+
+/// `schema.name` or `name` (resolved in public), with quotes removed
+fn split_qualified(name: &str) -> (String, String) {
+    let parts: Vec<&str> = name.split('.').map(|p| p.trim().trim_matches('"')).collect();
+    match parts.as_slice() {
+        [schema, name] => (schema.to_string(), name.to_string()),
+        _ => ("public".to_string(), parts.join(".")),
+    }
+}
+
+/// Tables and views a view definition reads from. CTE names and
+/// set-returning functions in FROM are not relations and are skipped.
+pub(super) fn view_relations(view: &ViewInfo) -> Vec<(String, String)> {
+    struct Relations {
+        found: Vec<(String, String)>,
+        ctes: HashSet<String>,
+    }
+
+    impl Visitor for Relations {
+        type Break = ();
+
+        fn pre_visit_query(&mut self, query: &sqlparser::ast::Query) -> ControlFlow<()> {
+            if let Some(with) = &query.with {
+                for cte in &with.cte_tables {
+                    self.ctes.insert(cte.alias.name.value.clone());
+                }
+            }
+            ControlFlow::Continue(())
+        }
+
+        fn pre_visit_table_factor(&mut self, factor: &TableFactor) -> ControlFlow<()> {
+            if let TableFactor::Table { name, args: None, .. } = factor {
+                let (schema, table) = split_qualified(&name.to_string());
+                let is_cte = name.0.len() == 1 && self.ctes.contains(&table);
+                if !is_cte && !self.found.contains(&(schema.clone(), table.clone())) {
+                    self.found.push((schema, table));
+                }
+            }
+            ControlFlow::Continue(())
+        }
+    }
+
+    let Ok(statements) = Parser::parse_sql(&PostgreSqlDialect {}, &view.definition) else {
+        return Vec::new();
+    };
+    let mut relations = Relations { found: Vec::new(), ctes: HashSet::new() };
+    for statement in &statements {
+        let _ = statement.visit(&mut relations);
+    }
+    relations.found
+}
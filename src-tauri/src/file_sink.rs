@@ -0,0 +1,83 @@
+//! Destination for project file writes, so commands that change files on disk can run
+//! as a dry run that only reports what they would do.
+
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A change to the project folder, as performed or (in a dry run) planned
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FileOperation {
+    CreateDir { path: String },
+    Write { path: String, bytes: usize },
+    Remove { path: String },
+}
+
+/// Records every file operation routed through it, and performs them unless it's a dry run
+#[derive(Debug, Default)]
+pub struct FileSink {
+    dry_run: bool,
+    operations: Mutex<Vec<FileOperation>>,
+}
+
+impl FileSink {
+    /// A sink that writes to disk, or with `dry_run` only records what would be written
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run, ..Default::default() }
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub async fn create_dir_all(&self, path: &Path) -> Result<(), String> {
+        if !self.dry_run {
+            tokio::fs::create_dir_all(path)
+                .await
+                .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        }
+        self.record(FileOperation::CreateDir { path: path.to_string_lossy().to_string() });
+        Ok(())
+    }
+
+    pub async fn write(&self, path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+        let contents = contents.as_ref();
+        if !self.dry_run {
+            tokio::fs::write(path, contents)
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        self.record(FileOperation::Write { path: path.to_string_lossy().to_string(), bytes: contents.len() });
+        Ok(())
+    }
+
+    pub async fn remove_file(&self, path: &Path) -> Result<(), String> {
+        if !self.dry_run {
+            tokio::fs::remove_file(path)
+                .await
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+        self.record(FileOperation::Remove { path: path.to_string_lossy().to_string() });
+        Ok(())
+    }
+
+    pub async fn remove_dir_all(&self, path: &Path) -> Result<(), String> {
+        if !self.dry_run {
+            tokio::fs::remove_dir_all(path)
+                .await
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+        self.record(FileOperation::Remove { path: path.to_string_lossy().to_string() });
+        Ok(())
+    }
+
+    /// Operations so far, in the order they were requested
+    pub fn operations(&self) -> Vec<FileOperation> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    fn record(&self, operation: FileOperation) {
+        self.operations.lock().unwrap().push(operation);
+    }
+}
@@ -1,10 +1,21 @@
-use crate::schema::{ForeignKeyInfo, IndexInfo, PolicyInfo, TriggerInfo};
+use crate::diff::CommentTarget;
+use crate::schema::{quote_ident, ForeignKeyInfo, IndexInfo, PolicyInfo, TableInfo, TriggerInfo};
 
 pub fn generate_create_index(table_name: &str, idx: &IndexInfo) -> String {
-    let mut sql = if idx.is_unique {
-        format!("CREATE UNIQUE INDEX \"{}\"", idx.index_name)
+    build_create_index(table_name, idx, false)
+}
+
+/// `CREATE INDEX CONCURRENTLY`, which avoids locking writes but cannot run in a transaction
+pub fn generate_create_index_concurrently(table_name: &str, idx: &IndexInfo) -> String {
+    build_create_index(table_name, idx, true)
+}
+
+fn build_create_index(table_name: &str, idx: &IndexInfo, concurrently: bool) -> String {
+    let create = if idx.is_unique { "CREATE UNIQUE INDEX" } else { "CREATE INDEX" };
+    let mut sql = if concurrently {
+        format!("{} CONCURRENTLY {}", create, quote_ident(&idx.index_name))
     } else {
-        format!("CREATE INDEX \"{}\"", idx.index_name)
+        format!("{} {}", create, quote_ident(&idx.index_name))
     };
 
     // table_name is already qualified/quoted
@@ -19,13 +30,23 @@ pub fn generate_create_index(table_name: &str, idx: &IndexInfo) -> String {
     // For mixed indexes (columns + expressions), include both
     let mut parts: Vec<String> = Vec::new();
     for c in &idx.columns {
-        parts.push(format!("\"{}\"", c));
+        parts.push(c.to_sql());
     }
     for e in &idx.expressions {
         parts.push(e.clone());
     }
     sql.push_str(&format!(" ({})", parts.join(", ")));
 
+    // Non-key columns of a covering index
+    if !idx.include_columns.is_empty() {
+        let included: Vec<String> = idx.include_columns.iter().map(|c| quote_ident(c)).collect();
+        sql.push_str(&format!(" INCLUDE ({})", included.join(", ")));
+    }
+
+    if idx.nulls_not_distinct {
+        sql.push_str(" NULLS NOT DISTINCT");
+    }
+
     // WHERE clause for partial indexes
     if let Some(where_clause) = &idx.where_clause {
         sql.push_str(&format!(" WHERE {}", where_clause));
@@ -35,13 +56,89 @@ pub fn generate_create_index(table_name: &str, idx: &IndexInfo) -> String {
     sql
 }
 
+/// `CONSTRAINT "name" UNIQUE (...)` for an index backing a unique constraint.
+/// Such indexes must be created and dropped through the constraint, not as indexes.
+pub fn unique_constraint_definition(idx: &IndexInfo) -> String {
+    let name = idx.owning_constraint.as_deref().unwrap_or(&idx.index_name);
+    let cols: Vec<String> = idx.columns.iter().map(|c| quote_ident(&c.name)).collect();
+    let nulls = if idx.nulls_not_distinct { " NULLS NOT DISTINCT" } else { "" };
+    let mut sql = format!("CONSTRAINT {} UNIQUE{} ({})", quote_ident(name), nulls, cols.join(", "));
+    if !idx.include_columns.is_empty() {
+        let included: Vec<String> = idx.include_columns.iter().map(|c| quote_ident(c)).collect();
+        sql.push_str(&format!(" INCLUDE ({})", included.join(", ")));
+    }
+    sql
+}
+
+/// `ALTER TABLE ... ADD CONSTRAINT` for constraint-owned indexes, `CREATE INDEX` otherwise
+pub fn generate_add_index_or_constraint(table_name: &str, idx: &IndexInfo) -> String {
+    if idx.owning_constraint.is_some() {
+        format!("ALTER TABLE {} ADD {};", table_name, unique_constraint_definition(idx))
+    } else {
+        generate_create_index(table_name, idx)
+    }
+}
+
+/// `ALTER TABLE ... DROP CONSTRAINT` for constraint-owned indexes, `DROP INDEX` otherwise.
+/// Postgres refuses to drop an index that a constraint depends on.
+pub fn generate_drop_index_or_constraint(table_name: &str, schema: &str, idx: &IndexInfo) -> String {
+    match &idx.owning_constraint {
+        Some(constraint) => format!(
+            "ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};",
+            table_name, quote_ident(constraint)
+        ),
+        None => format!("DROP INDEX IF EXISTS {}.{};", quote_ident(schema), quote_ident(&idx.index_name)),
+    }
+}
+
+/// `COMMENT ON INDEX|CONSTRAINT|POLICY`; a `None` comment clears it
+pub fn generate_object_comment(
+    table_name: &str,
+    schema: &str,
+    target: CommentTarget,
+    name: &str,
+    comment: Option<&str>,
+) -> String {
+    let value = match comment {
+        Some(c) => format!("'{}'", super::escape_string(c)),
+        None => "NULL".to_string(),
+    };
+    match target {
+        CommentTarget::Index => format!("COMMENT ON INDEX {}.{} IS {};", quote_ident(schema), quote_ident(name), value),
+        CommentTarget::Constraint => format!("COMMENT ON CONSTRAINT {} ON {} IS {};", quote_ident(name), table_name, value),
+        CommentTarget::Policy => format!("COMMENT ON POLICY {} ON {} IS {};", quote_ident(name), table_name, value),
+    }
+}
+
+/// Comments on every index, check constraint and policy of a newly created table
+pub fn generate_table_object_comments(table_name: &str, table: &TableInfo) -> Vec<String> {
+    let indexes = table
+        .indexes
+        .iter()
+        .filter_map(|i| i.comment.as_deref().map(|c| (CommentTarget::Index, &i.index_name, c)));
+    let checks = table
+        .check_constraints
+        .iter()
+        .filter_map(|c| c.comment.as_deref().map(|comment| (CommentTarget::Constraint, &c.name, comment)));
+    let policies = table
+        .policies
+        .iter()
+        .filter_map(|p| p.comment.as_deref().map(|c| (CommentTarget::Policy, &p.name, c)));
+
+    indexes
+        .chain(checks)
+        .chain(policies)
+        .map(|(target, name, comment)| generate_object_comment(table_name, &table.schema, target, name, Some(comment)))
+        .collect()
+}
+
 pub fn generate_create_trigger(table_name: &str, trigger: &TriggerInfo) -> String {
     let events = trigger.events.join(" OR ");
 
     // table_name is already qualified/quoted
     let mut sql = format!(
-        "CREATE TRIGGER \"{}\" {} {} ON {} FOR EACH {} ",
-        trigger.name, trigger.timing, events, table_name, trigger.orientation
+        "CREATE TRIGGER {} {} {} ON {} FOR EACH {} ",
+        quote_ident(&trigger.name), trigger.timing, events, table_name, trigger.orientation
     );
 
     // WHEN clause
@@ -57,8 +154,8 @@ pub fn generate_create_trigger(table_name: &str, trigger: &TriggerInfo) -> Strin
 pub fn generate_create_policy(table_name: &str, policy: &PolicyInfo) -> String {
     // table_name is already qualified/quoted
     let mut sql = format!(
-        "CREATE POLICY \"{}\" ON {} FOR {} TO {}",
-        policy.name,
+        "CREATE POLICY {} ON {} FOR {} TO {}",
+        quote_ident(&policy.name),
         table_name,
         policy.cmd,
         policy.roles.join(", ")
@@ -80,24 +177,24 @@ pub fn generate_add_foreign_key(table_name: &str, fk: &ForeignKeyInfo) -> String
     // Quote local columns
     let columns_str = fk.columns
         .iter()
-        .map(|c| format!("\"{}\"", c))
+        .map(|c| quote_ident(c))
         .collect::<Vec<_>>()
         .join(", ");
     
     // Quote foreign columns
     let foreign_columns_str = fk.foreign_columns
         .iter()
-        .map(|c| format!("\"{}\"", c))
+        .map(|c| quote_ident(c))
         .collect::<Vec<_>>()
         .join(", ");
 
     let mut sql = format!(
-        "ALTER TABLE {} ADD CONSTRAINT \"{}\" FOREIGN KEY ({}) REFERENCES \"{}\".\"{}\" ({})",
+        "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}.{} ({})",
         table_name, 
-        fk.constraint_name, 
+        quote_ident(&fk.constraint_name), 
         columns_str, 
-        fk.foreign_schema, 
-        fk.foreign_table,
+        quote_ident(&fk.foreign_schema), 
+        quote_ident(&fk.foreign_table),
         foreign_columns_str
     );
 
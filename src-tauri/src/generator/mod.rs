@@ -1,5 +1,6 @@
 mod constraints;
 pub mod objects;
+mod qualify;
 mod roles;
 mod tables;
 pub mod typescript;
@@ -7,9 +8,10 @@ mod types;
 
 use crate::defaults;
 use crate::diff::{EnumChangeType, SchemaDiff};
+pub use qualify::NameQualification;
 use crate::schema::{
-    CompositeTypeInfo, DbSchema, DomainInfo, ExtensionInfo, RoleInfo, SequenceInfo, TableInfo,
-    ViewInfo,
+    qualified_name, quote_ident, CompositeTypeInfo, DbSchema, DefaultAcl, DomainInfo, ExtensionInfo, RoleInfo,
+    SequenceInfo, TableInfo, ViewInfo,
 };
 
 /// Generate split SQL files from a `DbSchema`.
@@ -56,12 +58,17 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
                 schemas.insert(s.clone());
             }
         }
+        for (s, comment) in &schema.schema_comments {
+            if !defaults::is_builtin_schema_comment(s, comment.as_deref()) {
+                schemas.insert(s.clone());
+            }
+        }
 
         let mut sorted_schemas: Vec<String> = schemas.into_iter().collect();
         sorted_schemas.sort();
         for s in sorted_schemas {
             if !defaults::is_excluded_schema(&s) {
-                stmts.push(format!("CREATE SCHEMA IF NOT EXISTS \"{}\";", s));
+                stmts.push(format!("CREATE SCHEMA IF NOT EXISTS {};", quote_ident(&s)));
             }
         }
 
@@ -145,15 +152,14 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
             stmts.push(tables::generate_create_table(table));
 
             // Policies
-            let qualified_name = format!("\"{}\".\"{}\"\n", table.schema, table.table_name);
-            let qualified_name = qualified_name.trim().to_string();
+            let qualified = qualified_name(&table.schema, &table.table_name);
             for policy in &table.policies {
-                stmts.push(constraints::generate_create_policy(&qualified_name, policy));
+                stmts.push(constraints::generate_create_policy(&qualified, policy));
             }
 
             // Triggers
             for trigger in &table.triggers {
-                stmts.push(constraints::generate_create_trigger(&qualified_name, trigger));
+                stmts.push(constraints::generate_create_trigger(&qualified, trigger));
             }
 
             // Add a blank line between tables for readability
@@ -173,6 +179,11 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
         for view in view_list {
             stmts.push(objects::generate_create_view(view));
         }
+        let mut rule_keys: Vec<&String> = schema.rules.keys().collect();
+        rule_keys.sort();
+        for key in rule_keys {
+            stmts.push(objects::generate_create_rule(&schema.rules[key]));
+        }
         if !stmts.is_empty() {
             files.push(("05_views.sql".to_string(), stmts.join("\n")));
         }
@@ -200,10 +211,9 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
         table_list.sort_by(|a, b| a.0.cmp(b.0));
 
         for (_, table) in &table_list {
-            let qualified_name = format!("\"{}\".\"{}\"\n", table.schema, table.table_name);
-            let qualified_name = qualified_name.trim().to_string();
+            let qualified = qualified_name(&table.schema, &table.table_name);
             for fk in &table.foreign_keys {
-                stmts.push(constraints::generate_add_foreign_key(&qualified_name, fk));
+                stmts.push(constraints::generate_add_foreign_key(&qualified, fk));
             }
         }
 
@@ -216,13 +226,22 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
     {
         let mut stmts: Vec<String> = Vec::new();
 
+        // Schema comments
+        let mut schema_comments: Vec<(&String, &Option<String>)> = schema.schema_comments.iter().collect();
+        schema_comments.sort();
+        for (name, comment) in schema_comments {
+            if comment.is_some() && !defaults::is_builtin_schema_comment(name, comment.as_deref()) {
+                stmts.push(schema_comment_statement(name, comment.as_deref()));
+            }
+        }
+
         // Table and column comments
         let mut table_list: Vec<(&String, &TableInfo)> = schema.tables.iter().collect();
         table_list.sort_by(|a, b| a.0.cmp(b.0));
         for (name, table) in &table_list {
             if let Some(comment) = &table.comment {
                 stmts.push(format!(
-                    "COMMENT ON TABLE \"{}\" IS '{}';",
+                    "COMMENT ON TABLE {} IS '{}';",
                     name,
                     escape_string(comment)
                 ));
@@ -232,13 +251,17 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
             for col in columns {
                 if let Some(comment) = &col.comment {
                     stmts.push(format!(
-                        "COMMENT ON COLUMN \"{}\".\"{}\" IS '{}';",
+                        "COMMENT ON COLUMN {}.{} IS '{}';",
                         name,
-                        col.column_name,
+                        quote_ident(&col.column_name),
                         escape_string(comment)
                     ));
                 }
             }
+            stmts.extend(constraints::generate_table_object_comments(
+                &qualified_name(&table.schema, &table.table_name),
+                table,
+            ));
         }
 
         // View comments
@@ -252,9 +275,9 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
                     "VIEW"
                 };
                 stmts.push(format!(
-                    "COMMENT ON {} \"{}\" IS '{}';",
+                    "COMMENT ON {} {} IS '{}';",
                     view_type,
-                    view.name,
+                    quote_ident(&view.name),
                     escape_string(comment)
                 ));
             }
@@ -275,8 +298,8 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
         for (name, table) in &table_list {
             for grant in &table.grants {
                 stmts.push(format!(
-                    "GRANT {} ON {} TO \"{}\";",
-                    grant.privilege, name, grant.grantee
+                    "GRANT {} ON {} TO {};",
+                    grant.privilege, name, quote_ident(&grant.grantee)
                 ));
             }
         }
@@ -286,10 +309,10 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
         view_list.sort_by(|a, b| a.name.cmp(&b.name));
         for view in &view_list {
             for grant in &view.grants {
-                let qualified = format!("\"{}\".\"{}\"", view.schema, view.name);
+                let qualified = qualified_name(&view.schema, &view.name);
                 stmts.push(format!(
-                    "GRANT {} ON {} TO \"{}\";",
-                    grant.privilege, qualified, grant.grantee
+                    "GRANT {} ON {} TO {};",
+                    grant.privilege, qualified, quote_ident(&grant.grantee)
                 ));
             }
         }
@@ -300,12 +323,19 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
         for seq in &seq_list {
             for grant in &seq.grants {
                 stmts.push(format!(
-                    "GRANT {} ON SEQUENCE \"{}\".\"{}\" TO \"{}\";",
-                    grant.privilege, seq.schema, seq.name, grant.grantee
+                    "GRANT {} ON SEQUENCE {}.{} TO {};",
+                    grant.privilege, quote_ident(&seq.schema), quote_ident(&seq.name), quote_ident(&grant.grantee)
                 ));
             }
         }
 
+        // Default privileges for objects created later
+        for acl in &schema.default_acls {
+            if !defaults::is_builtin_default_acl(&acl.role, acl.schema.as_deref(), &acl.grantee) {
+                stmts.push(default_acl_statement(acl, true));
+            }
+        }
+
         if !stmts.is_empty() {
             files.push(("09_grants.sql".to_string(), stmts.join("\n")));
         }
@@ -315,8 +345,213 @@ pub fn split_sql(schema: &DbSchema) -> Vec<(String, String)> {
 }
 
 pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
+    generate_statements(diff, local_schema, &GenerateOptions::default()).join("\n")
+}
+
+/// Kinds of objects `generate_sql_filtered` can restrict a migration to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    /// Tables with their columns, constraints, indexes, triggers, policies and grants
+    Tables,
+    Views,
+    Functions,
+    Sequences,
+    /// Enums, composite types and domains
+    Types,
+    Extensions,
+    Roles,
+    Rules,
+    /// Schema comments, schema grants and default privileges
+    Schemas,
+}
+
+impl ObjectKind {
+    /// The `SchemaDiff` object kinds (see `crate::diff::OBJECT_KINDS`) this covers
+    pub fn diff_kinds(self) -> &'static [&'static str] {
+        match self {
+            ObjectKind::Tables => &["table"],
+            ObjectKind::Views => &["view"],
+            ObjectKind::Functions => &["function"],
+            ObjectKind::Sequences => &["sequence"],
+            ObjectKind::Types => &["enum", "type", "domain"],
+            ObjectKind::Extensions => &["extension"],
+            ObjectKind::Roles => &["role"],
+            ObjectKind::Rules => &["rule"],
+            ObjectKind::Schemas => &["schema"],
+        }
+    }
+}
+
+/// `generate_sql` limited to statements for the given kinds of objects, in the same
+/// order they'd have in the full migration. `CREATE SCHEMA` is still emitted for the
+/// schemas of new objects that are kept.
+pub fn generate_sql_filtered(diff: &SchemaDiff, local_schema: &DbSchema, kinds: &[ObjectKind]) -> String {
+    let filtered = retain_kinds(diff, kinds);
+    generate_statements(&filtered, local_schema, &GenerateOptions::default()).join("\n")
+}
+
+fn retain_kinds(diff: &SchemaDiff, kinds: &[ObjectKind]) -> SchemaDiff {
+    let mut diff = diff.clone();
+    let kept: Vec<&str> = kinds.iter().flat_map(|kind| kind.diff_kinds()).copied().collect();
+    for kind in crate::diff::OBJECT_KINDS.iter().filter(|k| !kept.contains(*k)) {
+        diff.clear_kind(kind);
+    }
+    diff
+}
+
+/// Options controlling how migration statements are emitted
+#[derive(Debug, Clone, Default)]
+pub struct GenerateOptions {
+    /// Build indexes on existing tables with `CREATE INDEX CONCURRENTLY`
+    pub concurrent_indexes: bool,
+    /// Whether object names always, never or only outside `public` carry their schema
+    pub qualification: NameQualification,
+}
+
+/// How a push groups a migration's statements into transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionMode {
+    /// One transaction for everything that can run in one, then the rest statement by statement
+    #[default]
+    Auto,
+    /// Everything in one transaction; refused if a statement can't run inside one
+    Single,
+    /// Every statement on its own, stopping at the first failure
+    #[serde(rename = "none")]
+    PerStatement,
+}
+
+/// One call of a migration: a `BEGIN; ... COMMIT;` block or a single statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationBatch {
+    pub sql: String,
+    pub in_transaction: bool,
+}
+
+/// Statements of a migration, split by whether they may run inside a transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationPlan {
+    /// `ALTER TYPE ... ADD VALUE`, applied one by one before the transaction. It can't
+    /// go inside it, and not after it with `non_transactional` either: the transactional
+    /// statements may use the new value (defaults, checks, policies), and Postgres only
+    /// accepts it once the statement adding it has committed. Applied after, the
+    /// transaction would fail on the value not existing yet.
+    pub before_transaction: Vec<String>,
+    /// Statements applied atomically inside `BEGIN; ... COMMIT;`
+    pub transactional: Vec<String>,
+    /// Statements Postgres refuses to run in a transaction block, applied one by one afterwards
+    pub non_transactional: Vec<String>,
+}
+
+impl MigrationPlan {
+    /// Add a statement to the part of the plan it has to run in
+    pub fn push(&mut self, statement: String) {
+        if adds_enum_value(&statement) {
+            self.before_transaction.push(statement);
+        } else if cannot_run_in_transaction(&statement) {
+            self.non_transactional.push(statement);
+        } else {
+            self.transactional.push(statement);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.before_transaction.is_empty() && self.transactional.is_empty() && self.non_transactional.is_empty()
+    }
+
+    /// Every statement in the order it's applied
+    pub fn statements(&self) -> impl Iterator<Item = &String> {
+        self.before_transaction.iter().chain(&self.transactional).chain(&self.non_transactional)
+    }
+
+    /// Render the plan as a single script, for display and logging
+    pub fn to_sql(&self) -> String {
+        let mut parts: Vec<String> = self.before_transaction.clone();
+        if !self.transactional.is_empty() {
+            parts.push(wrap_in_transaction(&self.transactional));
+        }
+        parts.extend(self.non_transactional.iter().cloned());
+        parts.join("\n")
+    }
+
+    /// The calls that apply the plan under `mode`, in order
+    pub fn batches(&self, mode: TransactionMode) -> Result<Vec<MigrationBatch>, String> {
+        let single = |sql: &String| MigrationBatch { sql: sql.clone(), in_transaction: false };
+        match mode {
+            TransactionMode::Single if !self.before_transaction.is_empty() || !self.non_transactional.is_empty() => {
+                let outside: Vec<&str> =
+                    self.before_transaction.iter().chain(&self.non_transactional).map(String::as_str).collect();
+                Err(format!(
+                    "Transaction mode 'single' can't apply statements that cannot run inside a transaction:\n{}",
+                    outside.join("\n")
+                ))
+            }
+            TransactionMode::Auto | TransactionMode::Single => {
+                let mut batches: Vec<MigrationBatch> = self.before_transaction.iter().map(single).collect();
+                if !self.transactional.is_empty() {
+                    batches.push(MigrationBatch { sql: wrap_in_transaction(&self.transactional), in_transaction: true });
+                }
+                batches.extend(self.non_transactional.iter().map(single));
+                Ok(batches)
+            }
+            TransactionMode::PerStatement => Ok(self.statements().map(single).collect()),
+        }
+    }
+}
+
+/// Wrap statements in `BEGIN; ... COMMIT;`
+pub fn wrap_in_transaction(statements: &[String]) -> String {
+    format!("BEGIN;\n{}\nCOMMIT;", statements.join("\n"))
+}
+
+/// Whether a statement cannot be executed inside a transaction block
+/// (`ALTER TYPE ... ADD VALUE` on older Postgres, `... CONCURRENTLY` index operations).
+pub fn cannot_run_in_transaction(statement: &str) -> bool {
+    let upper = statement.trim_start().to_uppercase();
+    adds_enum_value(statement)
+        || ((upper.starts_with("CREATE INDEX")
+            || upper.starts_with("CREATE UNIQUE INDEX")
+            || upper.starts_with("DROP INDEX")
+            || upper.starts_with("REINDEX"))
+            && upper.contains(" CONCURRENTLY "))
+}
+
+/// Whether a statement is `ALTER TYPE ... ADD VALUE`
+fn adds_enum_value(statement: &str) -> bool {
+    let upper = statement.trim_start().to_uppercase();
+    upper.starts_with("ALTER TYPE") && upper.contains(" ADD VALUE ")
+}
+
+/// Generate a migration for `diff`, splitting out statements that must run outside a transaction.
+pub fn generate_migration(diff: &SchemaDiff, local_schema: &DbSchema) -> MigrationPlan {
+    generate_migration_with_options(diff, local_schema, &GenerateOptions::default())
+}
+
+pub fn generate_migration_with_options(
+    diff: &SchemaDiff,
+    local_schema: &DbSchema,
+    options: &GenerateOptions,
+) -> MigrationPlan {
+    let mut plan = MigrationPlan::default();
+    for statement in generate_statements(diff, local_schema, options) {
+        plan.push(statement);
+    }
+    plan
+}
+
+fn generate_statements(
+    diff: &SchemaDiff,
+    local_schema: &DbSchema,
+    options: &GenerateOptions,
+) -> Vec<String> {
     let mut statements: Vec<String> = vec![];
 
+    // `table_changes` is a HashMap; walk it by table name so output is stable
+    let mut table_changes: Vec<(&String, &crate::diff::TableDiff)> = diff.table_changes.iter().collect();
+    table_changes.sort_by(|a, b| a.0.cmp(b.0));
+
     // Order matters! Follow dependency order:
     // 1. Extensions (needed by everything)
     // 2. Drop dependent objects first (reverse dependency order)
@@ -334,7 +569,7 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
 
     // Drop roles
     for name in &diff.roles_to_drop {
-        statements.push(format!("DROP ROLE IF EXISTS \"{}\";", name));
+        statements.push(format!("DROP ROLE IF EXISTS {};", quote_ident(name)));
     }
 
     // Create roles
@@ -362,12 +597,10 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
         schemas.insert(view.schema.clone());
     }
     for enum_change in &diff.enum_changes {
+        // The change is keyed like local_schema.enums; the EnumInfo carries the schema
         if enum_change.type_ == EnumChangeType::Create {
-            // Enum name may be schema-qualified; extract schema
-            if let Some(dot_pos) = enum_change.name.rfind('.') {
-                let schema_part = &enum_change.name[..dot_pos];
-                let cleaned = schema_part.trim_matches('"');
-                schemas.insert(cleaned.to_string());
+            if let Some(enum_info) = local_schema.enums.get(&enum_change.name) {
+                schemas.insert(enum_info.schema.clone());
             }
         }
     }
@@ -389,7 +622,7 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
 
     for schema in sorted_schemas {
         if !defaults::is_excluded_schema(&schema) {
-            statements.push(format!("CREATE SCHEMA IF NOT EXISTS \"{}\";", schema));
+            statements.push(format!("CREATE SCHEMA IF NOT EXISTS {};", quote_ident(&schema)));
         }
     }
 
@@ -404,8 +637,8 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     for ext in &diff.extensions_to_update {
         if let Some(version) = &ext.version {
             statements.push(format!(
-                "ALTER EXTENSION \"{}\" UPDATE TO '{}';",
-                ext.name, version
+                "ALTER EXTENSION {} UPDATE TO '{}';",
+                quote_ident(&ext.name), version
             ));
         }
     }
@@ -414,6 +647,15 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     // 2. DROP operations (reverse dependency order)
     // ====================
 
+    // Drop rules before the views and tables they're attached to
+    for rule in &diff.rules_to_drop {
+        statements.push(format!(
+            "DROP RULE IF EXISTS {} ON {};",
+            quote_ident(&rule.name),
+            qualified_name(&rule.schema, &rule.relation)
+        ));
+    }
+
     // Drop views first (depend on tables)
     for name in &diff.views_to_drop {
         // Check if it was a materialized view in the local schema
@@ -474,30 +716,35 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
 
     // Composite type alterations
     for (comp, type_diff) in &diff.composite_types_to_update {
-        let qualified = format!("\"{}\".\"{}\"", comp.schema, comp.name);
+        let qualified = qualified_name(&comp.schema, &comp.name);
         for attr in &type_diff.attributes_to_drop {
             statements.push(format!(
-                "ALTER TYPE {} DROP ATTRIBUTE \"{}\";",
-                qualified, attr.name
+                "ALTER TYPE {} DROP ATTRIBUTE {};",
+                qualified, quote_ident(&attr.name)
             ));
         }
         for attr in &type_diff.attributes_to_add {
+            let collation = attr
+                .collation
+                .as_ref()
+                .map(|c| format!(" COLLATE {}", quote_ident(c)))
+                .unwrap_or_default();
             statements.push(format!(
-                "ALTER TYPE {} ADD ATTRIBUTE \"{}\" {};",
-                qualified, attr.name, attr.data_type
+                "ALTER TYPE {} ADD ATTRIBUTE {} {}{};",
+                qualified, quote_ident(&attr.name), attr.data_type, collation
             ));
         }
         for (_, new_attr) in &type_diff.attributes_to_alter {
             statements.push(format!(
-                "ALTER TYPE {} ALTER ATTRIBUTE \"{}\" TYPE {};",
-                qualified, new_attr.name, new_attr.data_type
+                "ALTER TYPE {} ALTER ATTRIBUTE {} TYPE {};",
+                qualified, quote_ident(&new_attr.name), new_attr.data_type
             ));
         }
     }
 
     // Domain alterations
     for (domain, domain_diff) in &diff.domains_to_update {
-        let qualified = format!("\"{}\".\"{}\"", domain.schema, domain.name);
+        let qualified = qualified_name(&domain.schema, &domain.name);
         if let Some((_, new_default)) = &domain_diff.default_change {
             if let Some(default_val) = new_default {
                 statements.push(format!("ALTER DOMAIN {} SET DEFAULT {};", qualified, default_val));
@@ -514,14 +761,15 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
         }
         for con in &domain_diff.constraints_to_drop {
             if let Some(name) = &con.name {
-                statements.push(format!("ALTER DOMAIN {} DROP CONSTRAINT \"{}\";", qualified, name));
+                statements.push(format!("ALTER DOMAIN {} DROP CONSTRAINT {};", qualified, quote_ident(name)));
             }
         }
+        // Expressions already include the CHECK keyword
         for con in &domain_diff.constraints_to_add {
             if let Some(name) = &con.name {
-                statements.push(format!("ALTER DOMAIN {} ADD CONSTRAINT \"{}\" CHECK ({});", qualified, name, con.expression));
+                statements.push(format!("ALTER DOMAIN {} ADD CONSTRAINT {} {};", qualified, quote_ident(name), con.expression));
             } else {
-                statements.push(format!("ALTER DOMAIN {} ADD CHECK ({});", qualified, con.expression));
+                statements.push(format!("ALTER DOMAIN {} ADD {};", qualified, con.expression));
             }
         }
     }
@@ -532,8 +780,9 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
             if let Some(new_values) = &enum_change.values_to_add {
                 for value in new_values {
                     statements.push(format!(
-                        "ALTER TYPE \"{}\" ADD VALUE IF NOT EXISTS '{}';",
-                        enum_change.name, value
+                        "ALTER TYPE {} ADD VALUE IF NOT EXISTS {};",
+                        objects::ensure_quoted(&enum_change.name),
+                        types::quote_enum_label(value)
                     ));
                 }
             }
@@ -555,8 +804,8 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     for seq in &diff.sequences_to_create {
         for grant in &seq.grants {
             statements.push(format!(
-                "GRANT {} ON SEQUENCE \"{}\".\"{}\" TO \"{}\";",
-                grant.privilege, seq.schema, seq.name, grant.grantee
+                "GRANT {} ON SEQUENCE {}.{} TO {};",
+                grant.privilege, quote_ident(&seq.schema), quote_ident(&seq.name), quote_ident(&grant.grantee)
             ));
         }
     }
@@ -586,33 +835,33 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
         if let Some(table) = local_schema.tables.get(name) {
             for grant in &table.grants {
                 statements.push(format!(
-                    "GRANT {} ON {} TO \"{}\";",
-                    grant.privilege, name, grant.grantee
+                    "GRANT {} ON {} TO {};",
+                    grant.privilege, name, quote_ident(&grant.grantee)
                 ));
             }
         }
     }
 
     // Alter existing tables
-    for (table_name, table_diff) in &diff.table_changes {
+    for &(table_name, table_diff) in &table_changes {
         if let Some(table) = local_schema.tables.get(table_name) {
-            let alter_stmts = tables::generate_alter_table(table_name, table_diff, table);
+            let alter_stmts = tables::generate_alter_table(table_name, table_diff, table, options);
             statements.extend(alter_stmts);
         }
     }
 
     // Grant changes for existing tables
-    for (table_name, table_diff) in &diff.table_changes {
+    for &(table_name, table_diff) in &table_changes {
         for grant in &table_diff.grants_to_drop {
             statements.push(format!(
-                "REVOKE {} ON {} FROM \"{}\";",
-                grant.privilege, table_name, grant.grantee
+                "REVOKE {} ON {} FROM {};",
+                grant.privilege, table_name, quote_ident(&grant.grantee)
             ));
         }
         for grant in &table_diff.grants_to_create {
             statements.push(format!(
-                "GRANT {} ON {} TO \"{}\";",
-                grant.privilege, table_name, grant.grantee
+                "GRANT {} ON {} TO {};",
+                grant.privilege, table_name, quote_ident(&grant.grantee)
             ));
         }
     }
@@ -625,8 +874,8 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     for view in &diff.views_to_update {
         if view.is_materialized {
             statements.push(format!(
-                "DROP MATERIALIZED VIEW IF EXISTS \"{}\";",
-                view.name
+                "DROP MATERIALIZED VIEW IF EXISTS {};",
+                quote_ident(&view.name)
             ));
         }
         statements.push(objects::generate_create_view(view));
@@ -640,23 +889,28 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     // View grants
     for view in &diff.views_to_create {
         for grant in &view.grants {
-            let qualified = format!("\"{}\".\"{}\"", view.schema, view.name);
+            let qualified = qualified_name(&view.schema, &view.name);
             statements.push(format!(
-                "GRANT {} ON {} TO \"{}\";",
-                grant.privilege, qualified, grant.grantee
+                "GRANT {} ON {} TO {};",
+                grant.privilege, qualified, quote_ident(&grant.grantee)
             ));
         }
     }
     for view in &diff.views_to_update {
         for grant in &view.grants {
-            let qualified = format!("\"{}\".\"{}\"", view.schema, view.name);
+            let qualified = qualified_name(&view.schema, &view.name);
             statements.push(format!(
-                "GRANT {} ON {} TO \"{}\";",
-                grant.privilege, qualified, grant.grantee
+                "GRANT {} ON {} TO {};",
+                grant.privilege, qualified, quote_ident(&grant.grantee)
             ));
         }
     }
 
+    // Rules, once the tables and views they're on and act upon exist
+    for rule in &diff.rules_to_create {
+        statements.push(objects::generate_create_rule(rule));
+    }
+
     // ====================
     // 7. POST-TABLE OPERATIONS
     // ====================
@@ -685,7 +939,7 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     }
 
     // Foreign keys for modified tables
-    for (table_name, table_diff) in &diff.table_changes {
+    for &(table_name, table_diff) in &table_changes {
         for fk in &table_diff.foreign_keys_to_create {
             statements.push(constraints::generate_add_foreign_key(table_name, fk));
         }
@@ -698,7 +952,7 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
         if let Some(table) = local_schema.tables.get(name) {
             if let Some(comment) = &table.comment {
                 statements.push(format!(
-                    "COMMENT ON TABLE \"{}\" IS '{}';",
+                    "COMMENT ON TABLE {} IS '{}';",
                     name,
                     escape_string(comment)
                 ));
@@ -707,27 +961,32 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
             for col in table.columns.values() {
                 if let Some(comment) = &col.comment {
                     statements.push(format!(
-                        "COMMENT ON COLUMN \"{}\".\"{}\" IS '{}';",
+                        "COMMENT ON COLUMN {}.{} IS '{}';",
                         name,
-                        col.column_name,
+                        quote_ident(&col.column_name),
                         escape_string(comment)
                     ));
                 }
             }
+            // Index, constraint and policy comments
+            statements.extend(constraints::generate_table_object_comments(
+                &qualified_name(&table.schema, &table.table_name),
+                table,
+            ));
         }
     }
 
     // Comment changes for existing tables
-    for (table_name, table_diff) in &diff.table_changes {
+    for &(table_name, table_diff) in &table_changes {
         if let Some(new_comment) = &table_diff.comment_change {
             if let Some(comment) = new_comment {
                 statements.push(format!(
-                    "COMMENT ON TABLE \"{}\" IS '{}';",
+                    "COMMENT ON TABLE {} IS '{}';",
                     table_name,
                     escape_string(comment)
                 ));
             } else {
-                statements.push(format!("COMMENT ON TABLE \"{}\" IS NULL;", table_name));
+                statements.push(format!("COMMENT ON TABLE {} IS NULL;", table_name));
             }
         }
 
@@ -736,19 +995,37 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
             if let Some((_, new_comment)) = &mod_col.changes.comment_change {
                 if let Some(comment) = new_comment {
                     statements.push(format!(
-                        "COMMENT ON COLUMN \"{}\".\"{}\" IS '{}';",
+                        "COMMENT ON COLUMN {}.{} IS '{}';",
                         table_name,
-                        mod_col.column_name,
+                        quote_ident(&mod_col.column_name),
                         escape_string(comment)
                     ));
                 } else {
                     statements.push(format!(
-                        "COMMENT ON COLUMN \"{}\".\"{}\" IS NULL;",
-                        table_name, mod_col.column_name
+                        "COMMENT ON COLUMN {}.{} IS NULL;",
+                        table_name, quote_ident(&mod_col.column_name)
                     ));
                 }
             }
         }
+
+        // Index, constraint and policy comment changes
+        if let Some(table) = local_schema.tables.get(table_name) {
+            let qualified = qualified_name(&table.schema, &table.table_name);
+            for change in &table_diff.object_comments_to_set {
+                statements.push(constraints::generate_object_comment(
+                    &qualified,
+                    &table.schema,
+                    change.target,
+                    &change.name,
+                    change.comment.as_deref(),
+                ));
+            }
+        }
+    }
+
+    for (schema, comment) in &diff.schema_comments_to_set {
+        statements.push(schema_comment_statement(schema, comment.as_deref()));
     }
 
     // View comments
@@ -760,9 +1037,9 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
                 "VIEW"
             };
             statements.push(format!(
-                "COMMENT ON {} \"{}\" IS '{}';",
+                "COMMENT ON {} {} IS '{}';",
                 view_type,
-                view.name,
+                quote_ident(&view.name),
                 escape_string(comment)
             ));
         }
@@ -791,7 +1068,7 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
 
     // Drop extensions (last, as others may depend on them)
     for name in &diff.extensions_to_drop {
-        statements.push(format!("DROP EXTENSION IF EXISTS \"{}\" CASCADE;", name));
+        statements.push(format!("DROP EXTENSION IF EXISTS {} CASCADE;", quote_ident(name)));
     }
 
     // 16. Default Privileges and Schema Grants
@@ -799,38 +1076,78 @@ pub fn generate_sql(diff: &SchemaDiff, local_schema: &DbSchema) -> String {
     // Drops first
     for sg in &diff.schema_grants_to_drop {
         statements.push(format!(
-            "REVOKE {} ON SCHEMA \"{}\" FROM \"{}\";",
-            sg.privilege, sg.schema, sg.grantee
+            "REVOKE {} ON SCHEMA {} FROM {};",
+            sg.privilege, quote_ident(&sg.schema), quote_ident(&sg.grantee)
         ));
     }
     for dp in &diff.default_privileges_to_drop {
         statements.push(format!(
-            "REVOKE {} ON ALL {} IN SCHEMA \"{}\" FROM \"{}\";",
+            "REVOKE {} ON ALL {} IN SCHEMA {} FROM {};",
             dp.privilege,
             dp.object_type.to_uppercase(),
-            dp.schema,
-            dp.grantee
+            quote_ident(&dp.schema),
+            quote_ident(&dp.grantee)
         ));
     }
 
+    for acl in &diff.default_acls_to_drop {
+        statements.push(default_acl_statement(acl, false));
+    }
+
     // Creates
     for sg in &diff.schema_grants_to_create {
         statements.push(format!(
-            "GRANT {} ON SCHEMA \"{}\" TO \"{}\";",
-            sg.privilege, sg.schema, sg.grantee
+            "GRANT {} ON SCHEMA {} TO {};",
+            sg.privilege, quote_ident(&sg.schema), quote_ident(&sg.grantee)
         ));
     }
     for dp in &diff.default_privileges_to_create {
         statements.push(format!(
-            "GRANT {} ON ALL {} IN SCHEMA \"{}\" TO \"{}\";",
+            "GRANT {} ON ALL {} IN SCHEMA {} TO {};",
             dp.privilege,
             dp.object_type.to_uppercase(),
-            dp.schema,
-            dp.grantee
+            quote_ident(&dp.schema),
+            quote_ident(&dp.grantee)
         ));
     }
+    for acl in &diff.default_acls_to_create {
+        statements.push(default_acl_statement(acl, true));
+    }
+
+    let public_functions: std::collections::HashSet<String> = local_schema
+        .functions
+        .values()
+        .filter(|f| f.schema == "public")
+        .map(|f| f.name.clone())
+        .collect();
+    statements
+        .iter()
+        .map(|stmt| qualify::apply(stmt, options.qualification, &public_functions))
+        .collect()
+}
+
+/// `COMMENT ON SCHEMA`; a `None` comment clears it
+fn schema_comment_statement(schema: &str, comment: Option<&str>) -> String {
+    match comment {
+        Some(comment) => format!("COMMENT ON SCHEMA {} IS '{}';", quote_ident(schema), escape_string(comment)),
+        None => format!("COMMENT ON SCHEMA {} IS NULL;", quote_ident(schema)),
+    }
+}
 
-    statements.join("\n")
+/// `ALTER DEFAULT PRIVILEGES ... GRANT` (or `REVOKE`) for one default ACL entry
+fn default_acl_statement(acl: &DefaultAcl, grant: bool) -> String {
+    let mut sql = format!("ALTER DEFAULT PRIVILEGES FOR ROLE {}", quote_ident(&acl.role));
+    if let Some(schema) = &acl.schema {
+        sql.push_str(&format!(" IN SCHEMA {}", quote_ident(schema)));
+    }
+    // PUBLIC is a keyword, not a role name
+    let grantee = if acl.grantee == "public" { "PUBLIC".to_string() } else { quote_ident(&acl.grantee) };
+    let object_type = acl.object_type.to_uppercase();
+    if grant {
+        format!("{} GRANT {} ON {} TO {};", sql, acl.privilege, object_type, grantee)
+    } else {
+        format!("{} REVOKE {} ON {} FROM {};", sql, acl.privilege, object_type, grantee)
+    }
 }
 
 pub fn escape_string(s: &str) -> String {
@@ -1,10 +1,10 @@
-use crate::schema::{FunctionGrant, FunctionInfo, SequenceInfo, ViewInfo};
+use crate::schema::{quote_ident, FunctionGrant, FunctionInfo, RuleInfo, SequenceInfo, ViewInfo};
 
 pub fn ensure_quoted(name: &str) -> String {
     if name.starts_with('"') && name.ends_with('"') {
         name.to_string()
     } else {
-        format!("\"{}\"", name)
+        quote_ident(name)
     }
 }
 
@@ -13,7 +13,7 @@ pub fn ensure_quoted(name: &str) -> String {
 
 
 pub fn generate_create_sequence(seq: &SequenceInfo) -> String {
-    let mut sql = format!("CREATE SEQUENCE \"{}\".\"{}\"", seq.schema, seq.name);
+    let mut sql = format!("CREATE SEQUENCE {}.{}", quote_ident(&seq.schema), quote_ident(&seq.name));
 
     if seq.data_type != "bigint" {
         sql.push_str(&format!(" AS {}", seq.data_type));
@@ -53,7 +53,7 @@ pub fn generate_alter_sequence(seq: &SequenceInfo) -> String {
         parts.push("NO CYCLE".to_string());
     }
 
-    format!("ALTER SEQUENCE \"{}\".\"{}\" {};", seq.schema, seq.name, parts.join(" "))
+    format!("ALTER SEQUENCE {}.{} {};", quote_ident(&seq.schema), quote_ident(&seq.name), parts.join(" "))
 }
 
 pub fn generate_create_function(func: &FunctionInfo) -> String {
@@ -64,11 +64,11 @@ pub fn generate_create_function(func: &FunctionInfo) -> String {
     }
 
     let mut sql = format!(
-        "CREATE OR REPLACE FUNCTION \"{}\".\"{}\"({}) RETURNS {} LANGUAGE {} ",
-        func.schema, func.name, 
+        "CREATE OR REPLACE FUNCTION {}.{}({}) RETURNS {} LANGUAGE {} ",
+        quote_ident(&func.schema), quote_ident(&func.name), 
         func.args.iter().map(|a| {
             let arg_name = a.name.trim_matches('"');
-            let mut arg_def = format!("\"{}\" {}", arg_name, a.type_);
+            let mut arg_def = format!("{} {}", quote_ident(arg_name), a.type_);
             if let Some(mode) = &a.mode {
                 arg_def = format!("{} {}", mode, arg_def);
             }
@@ -94,21 +94,68 @@ pub fn generate_create_function(func: &FunctionInfo) -> String {
     }
 
     for (param, value) in &func.config_params {
-        sql.push_str(&format!("SET {} = '{}' ", param, value));
+        sql.push_str(&format!("SET {} = {} ", param, format_config_value(value)));
     }
 
-    sql.push_str(&format!("AS $${}$$;", func.definition));
+    let tag = dollar_quote_tag(&func.definition);
+    sql.push_str(&format!("AS {}{}{};", tag, func.definition, tag));
 
     sql
 }
 
+/// Pick a dollar-quote tag that doesn't occur in the body, so bodies containing
+/// `$$` literals of their own stay intact.
+fn dollar_quote_tag(body: &str) -> String {
+    if !body.contains("$$") {
+        return "$$".to_string();
+    }
+    let mut tag = "$function$".to_string();
+    let mut n = 1;
+    while body.contains(&tag) {
+        tag = format!("$function{}$", n);
+        n += 1;
+    }
+    tag
+}
+
+/// Render a function config value. Lists and plain words (`public, extensions`,
+/// `"$user", public`, `5000`) are emitted as-is so list settings keep their
+/// elements; anything else is a string literal.
+fn format_config_value(value: &str) -> String {
+    let value = value.trim();
+    if value.is_empty() || value == "\"\"" {
+        return "''".to_string();
+    }
+    let is_plain = value.split(',').all(|part| {
+        let part = part.trim();
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '"' | '$'))
+    });
+    if is_plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+pub fn generate_create_rule(rule: &RuleInfo) -> String {
+    format!("CREATE OR REPLACE RULE {} AS {};", quote_ident(&rule.name), rule.definition)
+}
+
 pub fn generate_create_view(view: &ViewInfo) -> String {
     let mut sql = String::new();
 
     if view.is_materialized {
-        sql.push_str(&format!("CREATE MATERIALIZED VIEW \"{}\".\"{}\"", view.schema, view.name));
+        sql.push_str(&format!("CREATE MATERIALIZED VIEW {}.{}", quote_ident(&view.schema), quote_ident(&view.name)));
     } else {
-        sql.push_str(&format!("CREATE OR REPLACE VIEW \"{}\".\"{}\"", view.schema, view.name));
+        sql.push_str(&format!("CREATE OR REPLACE VIEW {}.{}", quote_ident(&view.schema), quote_ident(&view.name)));
+    }
+
+    if !view.column_aliases.is_empty() {
+        let aliases: Vec<String> = view.column_aliases.iter().map(|c| quote_ident(c)).collect();
+        sql.push_str(&format!(" ({})", aliases.join(", ")));
     }
 
     if !view.with_options.is_empty() {
@@ -133,12 +180,12 @@ pub fn generate_function_grants(func: &FunctionInfo) -> Vec<String> {
     func.grants.iter().map(|grant| {
         let arg_types: Vec<String> = func.args.iter().map(|a| a.type_.clone()).collect();
         format!(
-            "GRANT {} ON FUNCTION \"{}\".\"{}\"{} TO \"{}\";",
+            "GRANT {} ON FUNCTION {}.{}{} TO {};",
             grant.privilege,
-            func.schema,
-            func.name,
+            quote_ident(&func.schema),
+            quote_ident(&func.name),
             format!("({})", arg_types.join(", ")),
-            grant.grantee
+            quote_ident(&grant.grantee)
         )
     }).collect()
 }
@@ -0,0 +1,148 @@
+//! Post-pass over generated statements that applies `NameQualification`.
+//! Only SQL code is rewritten; string literals, quoted identifiers' contents,
+//! dollar-quoted bodies and comments are left untouched.
+
+use std::collections::HashSet;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::quote_ident;
+
+/// How object names are qualified in generated DDL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameQualification {
+    /// Names are emitted as the generator builds them
+    #[default]
+    AsIs,
+    /// Names of the objects a statement creates, alters or drops carry their schema,
+    /// `public` included, and so do unqualified trigger functions that the local schema
+    /// defines in `public`; others, such as `moddatetime` from an extension installed
+    /// in `extensions`, are left for the search path to resolve. References
+    /// inside expressions (defaults, checks, view and function bodies) stay as written,
+    /// since an unqualified call may resolve to another schema on the search path.
+    Always,
+    /// Objects in `public` are referenced without the `public.` prefix
+    OmitPublic,
+}
+
+/// Apply `mode` to `statement`. `public_functions` holds the names of functions
+/// known to live in `public`, the only trigger functions `Always` qualifies.
+pub fn apply(statement: &str, mode: NameQualification, public_functions: &HashSet<String>) -> String {
+    match mode {
+        NameQualification::AsIs => statement.to_string(),
+        NameQualification::Always => map_code(statement, |code| qualify_trigger_functions(code, public_functions)),
+        NameQualification::OmitPublic => map_code(statement, strip_public),
+    }
+}
+
+static TRIGGER_FUNCTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(EXECUTE\s+(?:FUNCTION|PROCEDURE)\s+)("[^"]+"|[A-Za-z_][A-Za-z0-9_$]*)\s*\("#).unwrap()
+});
+
+static PUBLIC_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(^|[^A-Za-z0-9_$".])(?:"public"|public)\s*\.\s*(["A-Za-z_])"#).unwrap());
+
+/// Trigger functions are emitted as written in the schema, which may be unqualified
+fn qualify_trigger_functions(code: &str, public_functions: &HashSet<String>) -> String {
+    TRIGGER_FUNCTION_RE
+        .replace_all(code, |caps: &regex::Captures| {
+            let name = &caps[2];
+            let (bare, quoted) = match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+                Some(bare) => (bare.to_string(), name.to_string()),
+                None => (name.to_lowercase(), quote_ident(&name.to_lowercase())),
+            };
+            if public_functions.contains(&bare) {
+                format!("{}\"public\".{}(", &caps[1], quoted)
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .to_string()
+}
+
+fn strip_public(code: &str) -> String {
+    PUBLIC_PREFIX_RE.replace_all(code, "$1$2").to_string()
+}
+
+/// Apply `f` to the code between literals, quoted identifiers, dollar-quoted
+/// bodies and comments. Quoted identifiers are passed through to `f` whole,
+/// so `"public"` can be matched, but their contents are never split.
+fn map_code(sql: &str, f: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut code = String::new();
+    let mut rest = sql;
+
+    while let Some(c) = rest.chars().next() {
+        let skipped = if c == '\'' {
+            Some(rest[1..].find('\'').map(|i| i + 2).unwrap_or(rest.len()))
+        } else if rest.starts_with("--") {
+            Some(rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            Some(rest.find("*/").map(|i| i + 2).unwrap_or(rest.len()))
+        } else if c == '$' {
+            dollar_tag(rest).map(|tag| {
+                rest[tag.len()..]
+                    .find(tag)
+                    .map(|i| i + 2 * tag.len())
+                    .unwrap_or(rest.len())
+            })
+        } else {
+            None
+        };
+
+        match skipped {
+            Some(len) => {
+                out.push_str(&f(&code));
+                code.clear();
+                out.push_str(&rest[..len]);
+                rest = &rest[len..];
+            }
+            None if c == '"' => {
+                // Keep quoted identifiers whole in the code
+                let len = rest[1..].find('"').map(|i| i + 2).unwrap_or(rest.len());
+                code.push_str(&rest[..len]);
+                rest = &rest[len..];
+            }
+            None => {
+                code.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out.push_str(&f(&code));
+    out
+}
+
+/// `$tag$` or `$$` at the start of `sql`
+fn dollar_tag(sql: &str) -> Option<&str> {
+    let end = sql[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))? + 1;
+    let tag = &sql[..=end];
+    (sql[end..].starts_with('$') && !tag[1..].starts_with(|c: char| c.is_ascii_digit())).then_some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literals_and_bodies_are_untouched() {
+        let sql = "CREATE FUNCTION \"public\".\"f\"() RETURNS int AS $fn$ SELECT count(*) FROM public.t $fn$ LANGUAGE sql; -- public.x\nSELECT nextval('public.s'), $1";
+        assert_eq!(
+            apply(sql, NameQualification::OmitPublic, &HashSet::new()),
+            "CREATE FUNCTION \"f\"() RETURNS int AS $fn$ SELECT count(*) FROM public.t $fn$ LANGUAGE sql; -- public.x\nSELECT nextval('public.s'), $1"
+        );
+    }
+
+    #[test]
+    fn test_always_only_qualifies_trigger_functions_in_public() {
+        let public_functions: HashSet<String> = ["touch".to_string()].into_iter().collect();
+        let sql = "CREATE TRIGGER \"a\" BEFORE UPDATE ON \"public\".\"t\" FOR EACH ROW EXECUTE FUNCTION touch();\nCREATE TRIGGER \"b\" BEFORE UPDATE ON \"public\".\"t\" FOR EACH ROW EXECUTE FUNCTION moddatetime(updated_at);";
+        assert_eq!(
+            apply(sql, NameQualification::Always, &public_functions),
+            "CREATE TRIGGER \"a\" BEFORE UPDATE ON \"public\".\"t\" FOR EACH ROW EXECUTE FUNCTION \"public\".\"touch\"();\nCREATE TRIGGER \"b\" BEFORE UPDATE ON \"public\".\"t\" FOR EACH ROW EXECUTE FUNCTION moddatetime(updated_at);"
+        );
+    }
+}
@@ -1,9 +1,9 @@
-use crate::schema::{ExtensionInfo, RoleInfo};
+use crate::schema::{quote_ident, ExtensionInfo, RoleInfo};
 
 pub fn generate_create_extension(ext: &ExtensionInfo) -> String {
-    let mut sql = format!("CREATE EXTENSION IF NOT EXISTS \"{}\"", ext.name);
+    let mut sql = format!("CREATE EXTENSION IF NOT EXISTS {}", quote_ident(&ext.name));
     if let Some(schema) = &ext.schema {
-        sql.push_str(&format!(" WITH SCHEMA \"{}\"", schema));
+        sql.push_str(&format!(" WITH SCHEMA {}", quote_ident(schema)));
     }
     if let Some(version) = &ext.version {
         sql.push_str(&format!(" VERSION '{}'", version));
@@ -13,7 +13,7 @@ pub fn generate_create_extension(ext: &ExtensionInfo) -> String {
 }
 
 pub fn generate_create_role(role: &RoleInfo) -> String {
-    let mut sql = format!("CREATE ROLE \"{}\"", role.name);
+    let mut sql = format!("CREATE ROLE {}", quote_ident(&role.name));
 
     let mut options = Vec::new();
 
@@ -53,7 +53,7 @@ pub fn generate_create_role(role: &RoleInfo) -> String {
 }
 
 pub fn generate_alter_role(role: &RoleInfo) -> String {
-    let mut sql = format!("ALTER ROLE \"{}\"", role.name);
+    let mut sql = format!("ALTER ROLE {}", quote_ident(&role.name));
     let mut options = Vec::new();
 
     if role.superuser { options.push("SUPERUSER"); } else { options.push("NOSUPERUSER"); }
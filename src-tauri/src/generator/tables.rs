@@ -1,26 +1,31 @@
 use crate::diff::TableDiff;
-use crate::schema::TableInfo;
-use super::constraints::generate_create_index;
+use crate::schema::{qualified_name, quote_ident, PartitionInfo, TableInfo};
+use super::constraints::{generate_add_index_or_constraint, generate_drop_index_or_constraint, unique_constraint_definition};
+use super::GenerateOptions;
 
 pub fn generate_create_table(table: &TableInfo) -> String {
     let mut col_defs: Vec<String> = Vec::new();
 
-    // Sort columns for deterministic output
+    // Keep the original column order where known, by name otherwise
     let mut columns: Vec<_> = table.columns.values().collect();
-    columns.sort_by(|a, b| a.column_name.cmp(&b.column_name));
+    columns.sort_by(|a, b| {
+        let a_pos = a.ordinal_position.unwrap_or(i32::MAX);
+        let b_pos = b.ordinal_position.unwrap_or(i32::MAX);
+        a_pos.cmp(&b_pos).then_with(|| a.column_name.cmp(&b.column_name))
+    });
 
     // Primary key columns
     let pk_columns: Vec<String> = columns
         .iter()
         .filter(|c| c.is_primary_key)
-        .map(|c| format!("\"{}\"", c.column_name))
+        .map(|c| quote_ident(&c.column_name))
         .collect();
 
     for col in &columns {
-        let mut col_sql = format!("\"{}\" {}", col.column_name, col.data_type);
+        let mut col_sql = format!("{} {}", quote_ident(&col.column_name), col.data_type);
 
         if let Some(collation) = &col.collation {
-            col_sql.push_str(&format!(" COLLATE \"{}\"", collation));
+            col_sql.push_str(&format!(" COLLATE {}", quote_ident(collation)));
         }
 
         if !col.is_nullable && !col.is_primary_key {
@@ -51,25 +56,48 @@ pub fn generate_create_table(table: &TableInfo) -> String {
     // Check constraints
     for check in &table.check_constraints {
         col_defs.push(format!(
-            "CONSTRAINT \"{}\" {}",
-            check.name, check.expression
+            "CONSTRAINT {} {}",
+            quote_ident(&check.name), check.expression
         ));
     }
 
-    let qualified_name = format!("\"{}\".\"{}\"", table.schema, table.table_name);
+    // Exclusion constraints
+    for excl in &table.exclusion_constraints {
+        col_defs.push(format!("CONSTRAINT {} {}", quote_ident(&excl.name), excl.definition));
+    }
+
+    // Unique constraints (their backing indexes are created implicitly)
+    for idx in &table.indexes {
+        if !idx.is_primary && idx.owning_constraint.is_some() {
+            col_defs.push(unique_constraint_definition(idx));
+        }
+    }
+
+    let qualified = qualified_name(&table.schema, &table.table_name);
 
     let mut sql = format!(
-        "CREATE TABLE {} (\n  {}\n);",
-        qualified_name,
-        col_defs.join(",\n  ")
+        "CREATE {}TABLE {} (\n  {}\n){}{};",
+        if table.is_unlogged { "UNLOGGED " } else { "" },
+        qualified,
+        col_defs.join(",\n  "),
+        table
+            .partition_strategy
+            .as_ref()
+            .map(|strategy| format!(" PARTITION BY {}", strategy))
+            .unwrap_or_default(),
+        storage_clause(table)
     );
 
-    // Indexes (non-primary)
+    for partition in &table.partitions {
+        sql.push('\n');
+        sql.push_str(&generate_create_partition(&qualified, partition));
+    }
+
+    // Indexes (non-primary, not backing a constraint)
     for idx in &table.indexes {
-        if !idx.is_primary {
+        if !idx.is_primary && idx.owning_constraint.is_none() {
             sql.push('\n');
-            // Pass qualified name to generate_create_index
-            sql.push_str(&generate_create_index(&qualified_name, idx));
+            sql.push_str(&generate_add_index_or_constraint(&qualified, idx));
         }
     }
 
@@ -77,69 +105,116 @@ pub fn generate_create_table(table: &TableInfo) -> String {
     if table.rls_enabled {
         sql.push_str(&format!(
             "\nALTER TABLE {} ENABLE ROW LEVEL SECURITY;",
-            qualified_name
+            qualified
         ));
     }
 
     sql
 }
 
+fn generate_create_partition(parent: &str, partition: &PartitionInfo) -> String {
+    format!(
+        "CREATE TABLE {} PARTITION OF {} {};",
+        qualified_name(&partition.schema, &partition.name),
+        parent,
+        partition.bound
+    )
+}
+
+/// ` WITH (...) TABLESPACE ...` suffix for CREATE TABLE, empty for default storage
+fn storage_clause(table: &TableInfo) -> String {
+    let mut clause = String::new();
+    if !table.storage_params.is_empty() {
+        clause.push_str(&format!(" WITH ({})", format_storage_params(&table.storage_params)));
+    }
+    if let Some(tablespace) = &table.tablespace {
+        clause.push_str(&format!(" TABLESPACE {}", quote_ident(tablespace)));
+    }
+    clause
+}
+
+fn format_storage_params(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub fn generate_alter_table(
     table_name: &str,
     diff: &TableDiff,
     local_table: &TableInfo,
+    options: &GenerateOptions,
 ) -> Vec<String> {
     let mut statements = vec![];
 
+    // Rename columns first so everything below sees the new names
+    for (old_name, new_name) in &diff.columns_to_rename {
+        statements.push(format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {};",
+            table_name, quote_ident(old_name), quote_ident(new_name)
+        ));
+    }
+
     // Drop foreign keys first (before dropping columns they reference)
     for fk in &diff.foreign_keys_to_drop {
         statements.push(format!(
-            "ALTER TABLE {} DROP CONSTRAINT IF EXISTS \"{}\";",
-            table_name, fk.constraint_name
+            "ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};",
+            table_name, quote_ident(&fk.constraint_name)
         ));
     }
 
     // Drop check constraints
     for check in &diff.check_constraints_to_drop {
         statements.push(format!(
-            "ALTER TABLE {} DROP CONSTRAINT IF EXISTS \"{}\";",
-            table_name, check.name
+            "ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};",
+            table_name, quote_ident(&check.name)
+        ));
+    }
+
+    // Drop exclusion constraints
+    for excl in &diff.exclusion_constraints_to_drop {
+        statements.push(format!(
+            "ALTER TABLE {} DROP CONSTRAINT IF EXISTS {};",
+            table_name, quote_ident(&excl.name)
         ));
     }
 
     // Drop policies
     for p in &diff.policies_to_drop {
         statements.push(format!(
-            "DROP POLICY IF EXISTS \"{}\" ON {};",
-            p.name, table_name
+            "DROP POLICY IF EXISTS {} ON {};",
+            quote_ident(&p.name), table_name
         ));
     }
 
     // Drop triggers
     for t in &diff.triggers_to_drop {
         statements.push(format!(
-            "DROP TRIGGER IF EXISTS \"{}\" ON {};",
-            t.name, table_name
+            "DROP TRIGGER IF EXISTS {} ON {};",
+            quote_ident(&t.name), table_name
         ));
     }
 
     // Drop indexes
     for i in &diff.indexes_to_drop {
-        if let Some(constraint) = &i.owning_constraint {
-            statements.push(format!(
-                "ALTER TABLE {} DROP CONSTRAINT IF EXISTS \"{}\";",
-                table_name, constraint
-            ));
-        } else {
-            statements.push(format!("DROP INDEX IF EXISTS \"{}\".\"{}\";", local_table.schema, i.index_name));
-        }
+        statements.push(generate_drop_index_or_constraint(table_name, &local_table.schema, i));
+    }
+
+    // Rename indexes whose definition is unchanged
+    for (old_name, new_name) in &diff.indexes_to_rename {
+        statements.push(format!(
+            "ALTER INDEX {}.{} RENAME TO {};",
+            quote_ident(&local_table.schema), quote_ident(old_name), quote_ident(new_name)
+        ));
     }
 
     // Drop columns
     for col in &diff.columns_to_drop {
         statements.push(format!(
-            "ALTER TABLE {} DROP COLUMN IF EXISTS \"{}\";",
-            table_name, col
+            "ALTER TABLE {} DROP COLUMN IF EXISTS {};",
+            table_name, quote_ident(col)
         ));
     }
 
@@ -151,8 +226,8 @@ pub fn generate_alter_table(
             }
 
             let mut add_sql = format!(
-                "ALTER TABLE {} ADD COLUMN \"{}\" {}",
-                table_name, col.column_name, col.data_type
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table_name, quote_ident(&col.column_name), col.data_type
             );
 
             if !col.is_nullable {
@@ -175,12 +250,12 @@ pub fn generate_alter_table(
         // Type Change
         if let Some((_, new_type)) = &mod_col.changes.type_change {
             let mut alter_sql = format!(
-                "ALTER TABLE {} ALTER COLUMN \"{}\" TYPE {} USING \"{}\"::{}",
-                table_name, col_name, new_type, col_name, new_type
+                "ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{}",
+                table_name, quote_ident(col_name), new_type, quote_ident(col_name), new_type
             );
             // If collation changed, apply it with TYPE change
             if let Some((_, Some(new_collation))) = &mod_col.changes.collation_change {
-                alter_sql.push_str(&format!(" COLLATE \"{}\"", new_collation));
+                alter_sql.push_str(&format!(" COLLATE {}", quote_ident(new_collation)));
             }
             alter_sql.push(';');
             statements.push(alter_sql);
@@ -188,8 +263,8 @@ pub fn generate_alter_table(
              // Collation changed but Type didn't. Must use SET DATA TYPE ... COLLATE
              if let Some(col) = local_table.columns.get(col_name) {
                  statements.push(format!(
-                    "ALTER TABLE {} ALTER COLUMN \"{}\" SET DATA TYPE {} COLLATE \"{}\";",
-                    table_name, col_name, col.data_type, new_collation
+                    "ALTER TABLE {} ALTER COLUMN {} SET DATA TYPE {} COLLATE {};",
+                    table_name, quote_ident(col_name), col.data_type, quote_ident(new_collation)
                 ));
              }
         }
@@ -198,13 +273,13 @@ pub fn generate_alter_table(
         if let Some((_, to_nullable)) = mod_col.changes.nullable_change {
             if to_nullable {
                 statements.push(format!(
-                    "ALTER TABLE {} ALTER COLUMN \"{}\" DROP NOT NULL;",
-                    table_name, col_name
+                    "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL;",
+                    table_name, quote_ident(col_name)
                 ));
             } else {
                 statements.push(format!(
-                    "ALTER TABLE {} ALTER COLUMN \"{}\" SET NOT NULL;",
-                    table_name, col_name
+                    "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL;",
+                    table_name, quote_ident(col_name)
                 ));
             }
         }
@@ -213,13 +288,13 @@ pub fn generate_alter_table(
         if let Some((_, new_default)) = &mod_col.changes.default_change {
             if let Some(def) = new_default {
                 statements.push(format!(
-                    "ALTER TABLE {} ALTER COLUMN \"{}\" SET DEFAULT {};",
-                    table_name, col_name, def
+                    "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {};",
+                    table_name, quote_ident(col_name), def
                 ));
             } else {
                 statements.push(format!(
-                    "ALTER TABLE {} ALTER COLUMN \"{}\" DROP DEFAULT;",
-                    table_name, col_name
+                    "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT;",
+                    table_name, quote_ident(col_name)
                 ));
             }
         }
@@ -229,20 +304,20 @@ pub fn generate_alter_table(
             match (old_identity, new_identity) {
                 (Some(_), None) => {
                     statements.push(format!(
-                        "ALTER TABLE {} ALTER COLUMN \"{}\" DROP IDENTITY;",
-                        table_name, col_name
+                        "ALTER TABLE {} ALTER COLUMN {} DROP IDENTITY;",
+                        table_name, quote_ident(col_name)
                     ));
                 }
                 (None, Some(new_id)) => {
                     statements.push(format!(
-                        "ALTER TABLE {} ALTER COLUMN \"{}\" ADD GENERATED {} AS IDENTITY;",
-                        table_name, col_name, new_id
+                        "ALTER TABLE {} ALTER COLUMN {} ADD GENERATED {} AS IDENTITY;",
+                        table_name, quote_ident(col_name), new_id
                     ));
                 }
                 (Some(_), Some(new_id)) => {
                      statements.push(format!(
-                        "ALTER TABLE {} ALTER COLUMN \"{}\" SET GENERATED {};",
-                        table_name, col_name, new_id
+                        "ALTER TABLE {} ALTER COLUMN {} SET GENERATED {};",
+                        table_name, quote_ident(col_name), new_id
                     ));
                 }
                 (None, None) => {}
@@ -258,8 +333,8 @@ pub fn generate_alter_table(
             }
 
             let mut add_sql = format!(
-                "ALTER TABLE {} ADD COLUMN \"{}\" {}",
-                table_name, col.column_name, col.data_type
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table_name, quote_ident(&col.column_name), col.data_type
             );
 
             if !col.is_nullable {
@@ -290,27 +365,86 @@ pub fn generate_alter_table(
         }
     }
 
+    // Storage parameters: RESET the ones no longer set, SET new or changed values
+    if let Some((old_params, new_params)) = &diff.storage_params_change {
+        let removed: Vec<&str> = old_params
+            .iter()
+            .filter(|(name, _)| !new_params.iter().any(|(n, _)| n == name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !removed.is_empty() {
+            statements.push(format!("ALTER TABLE {} RESET ({});", table_name, removed.join(", ")));
+        }
+        let changed: Vec<(String, String)> = new_params
+            .iter()
+            .filter(|param| !old_params.contains(param))
+            .cloned()
+            .collect();
+        if !changed.is_empty() {
+            statements.push(format!(
+                "ALTER TABLE {} SET ({});",
+                table_name,
+                format_storage_params(&changed)
+            ));
+        }
+    }
+
+    if let Some(unlogged) = diff.unlogged_change {
+        statements.push(format!(
+            "ALTER TABLE {} SET {};",
+            table_name,
+            if unlogged { "UNLOGGED" } else { "LOGGED" }
+        ));
+    }
+
+    for partition in &diff.partitions_to_create {
+        statements.push(generate_create_partition(table_name, partition));
+    }
+
+    // Re-attaching checks the partition's rows against the new bound
+    for (_, partition) in &diff.partition_bounds_to_change {
+        let name = qualified_name(&partition.schema, &partition.name);
+        statements.push(format!("ALTER TABLE {} DETACH PARTITION {};", table_name, name));
+        statements.push(format!("ALTER TABLE {} ATTACH PARTITION {} {};", table_name, name, partition.bound));
+    }
+
+    if let Some(tablespace) = &diff.tablespace_change {
+        statements.push(format!(
+            "ALTER TABLE {} SET TABLESPACE {};",
+            table_name,
+            quote_ident(tablespace.as_deref().unwrap_or("pg_default"))
+        ));
+    }
+
     // Add check constraints
     for check in &diff.check_constraints_to_create {
         statements.push(format!(
-            "ALTER TABLE {} ADD CONSTRAINT \"{}\" {};",
-            table_name, check.name, check.expression
+            "ALTER TABLE {} ADD CONSTRAINT {} {};",
+            table_name, quote_ident(&check.name), check.expression
+        ));
+    }
+
+    // Add exclusion constraints
+    for excl in &diff.exclusion_constraints_to_create {
+        statements.push(format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} {};",
+            table_name, quote_ident(&excl.name), excl.definition
         ));
     }
 
     // Create indexes
     for i in &diff.indexes_to_create {
         if i.owning_constraint.is_some() {
-            // Unique constraint
-            let cols: Vec<String> = i.columns.iter().map(|c| format!("\"{}\"", c)).collect();
+            statements.push(generate_add_index_or_constraint(table_name, i));
+        } else if options.concurrent_indexes {
+            // A failed concurrent build leaves an INVALID index behind; drop it before retrying
             statements.push(format!(
-                "ALTER TABLE {} ADD CONSTRAINT \"{}\" UNIQUE ({});",
-                table_name,
-                i.index_name,
-                cols.join(", ")
+                "DROP INDEX CONCURRENTLY IF EXISTS {}.{};",
+                quote_ident(&local_table.schema), quote_ident(&i.index_name)
             ));
+            statements.push(super::constraints::generate_create_index_concurrently(table_name, i));
         } else {
-            statements.push(super::constraints::generate_create_index(table_name, i));
+            statements.push(generate_add_index_or_constraint(table_name, i));
         }
     }
 
@@ -3,10 +3,10 @@ use super::roles::generate_create_extension;
 use crate::diff::*;
 use crate::schema::*;
 use std::collections::HashMap;
-use super::constraints::{generate_create_index, generate_create_trigger, generate_add_foreign_key};
+use super::constraints::{generate_create_index, generate_create_trigger, generate_add_foreign_key, generate_add_index_or_constraint, generate_drop_index_or_constraint};
 use super::objects::{generate_create_sequence, generate_create_view};
 use super::types::{generate_create_domain, generate_create_composite_type};
-use super::tables::generate_alter_table;
+use super::tables::{generate_alter_table, generate_create_table};
 
 #[test]
 fn test_generate_sql_full() {
@@ -59,6 +59,11 @@ fn test_generate_sql_full() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     // Run generator
@@ -73,13 +78,16 @@ fn test_generate_sql_full() {
 fn test_generate_create_index_with_method_and_where() {
     let idx = IndexInfo {
         index_name: "idx_active_users".to_string(),
-        columns: vec!["email".to_string()],
+        columns: vec!["email".into()],
         is_unique: true,
         is_primary: false,
         owning_constraint: None,
         index_method: "gin".to_string(),
         where_clause: Some("active = true".to_string()),
         expressions: vec![],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     let sql = generate_create_index("\"public\".\"users\"", &idx);
@@ -151,6 +159,7 @@ fn test_generate_create_view() {
         definition: "SELECT * FROM users WHERE active = true".to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec!["security_barrier=true".to_string()],
@@ -172,6 +181,7 @@ fn test_generate_materialized_view() {
         definition: "SELECT user_id, count(*) FROM posts GROUP BY user_id".to_string(),
         is_materialized: true,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -207,6 +217,27 @@ fn test_generate_create_domain() {
     assert!(sql.contains("CONSTRAINT \"valid_email\""));
 }
 
+#[test]
+fn test_not_null_domain_parse_and_generate() {
+    let sql = r#"
+CREATE DOMAIN "public"."email_address" AS text NOT NULL CONSTRAINT "valid_email" CHECK (VALUE IS NOT NULL AND VALUE ~ '@');
+CREATE DOMAIN "public"."slug" AS text CONSTRAINT "slug_not_null" NOT NULL;
+CREATE DOMAIN "public"."nickname" AS text CHECK (VALUE IS NOT NULL);
+"#;
+    let schema = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let email = &schema.domains["\"public\".\"email_address\""];
+    assert!(email.is_not_null);
+    assert_eq!(email.check_constraints.len(), 1);
+    assert!(schema.domains["\"public\".\"slug\""].is_not_null);
+    // IS NOT NULL inside a CHECK is an ordinary check constraint
+    assert!(!schema.domains["\"public\".\"nickname\""].is_not_null);
+
+    let generated = generate_create_domain(email);
+    assert!(generated.contains("AS text NOT NULL CONSTRAINT \"valid_email\""), "{}", generated);
+    let reparsed = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), generated)]).unwrap();
+    assert_eq!(&reparsed.domains["\"public\".\"email_address\""], email);
+}
+
 #[test]
 fn test_generate_composite_type() {
     let comp_type = CompositeTypeInfo {
@@ -284,6 +315,11 @@ fn test_drop_type_quoting() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let schema = DbSchema::new();
@@ -316,6 +352,7 @@ fn test_generate_alter_table_columns() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             })
         ]),
         foreign_keys: vec![],
@@ -324,9 +361,15 @@ fn test_generate_alter_table_columns() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let mut table_diff = TableDiff {
@@ -353,13 +396,25 @@ fn test_generate_alter_table_columns() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
     // We need to mock the full column info for "email" so it can be added
@@ -380,9 +435,10 @@ fn test_generate_alter_table_columns() {
         enum_name: None,
         is_array: false,
         comment: None,
+        ordinal_position: None,
     });
 
-    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &local_table);
+    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &local_table, &GenerateOptions::default());
     
     // Add column
     assert!(statements.iter().any(|s| s.contains("ADD COLUMN \"email\" text NOT NULL")));
@@ -472,6 +528,56 @@ fn test_generate_create_enum() {
     assert!(sql.contains("'cancelled'"));
 }
 
+#[test]
+fn test_add_enum_value_in_non_public_schema() {
+    let status = |values: &[&str]| EnumInfo {
+        schema: "app".to_string(),
+        name: "status".to_string(),
+        values: values.iter().map(|v| v.to_string()).collect(),
+        extension: None,
+    };
+    let mut remote = DbSchema::new();
+    remote.enums.insert("\"app\".\"status\"".to_string(), status(&["open", "closed"]));
+    let mut local = DbSchema::new();
+    local.enums.insert("\"app\".\"status\"".to_string(), status(&["open", "closed", "won't fix"]));
+
+    let diff = crate::diff::compute_diff(&remote, &local);
+    assert_eq!(diff.enum_changes[0].name, "\"app\".\"status\"");
+
+    let sql = generate_sql(&diff, &local);
+    assert!(
+        sql.contains("ALTER TYPE \"app\".\"status\" ADD VALUE IF NOT EXISTS 'won''t fix';"),
+        "unexpected SQL: {}",
+        sql
+    );
+    assert!(!sql.contains("\"\"app"));
+}
+
+#[test]
+fn test_create_enum_in_new_schema_creates_schema_first() {
+    let mut local = DbSchema::new();
+    // A dot inside the quoted name must not be mistaken for the schema separator
+    local.enums.insert(
+        "\"app\".\"order.status\"".to_string(),
+        EnumInfo {
+            schema: "app".to_string(),
+            name: "order.status".to_string(),
+            values: vec!["open".to_string()],
+            extension: None,
+        },
+    );
+
+    let diff = crate::diff::compute_diff(&DbSchema::new(), &local);
+    let sql = generate_sql(&diff, &local);
+
+    let schema_pos = sql.find("CREATE SCHEMA IF NOT EXISTS \"app\";").expect("schema is created");
+    let type_pos = sql
+        .find("CREATE TYPE \"app\".\"order.status\" AS ENUM ('open');")
+        .expect("enum is created");
+    assert!(schema_pos < type_pos);
+    assert!(!sql.contains("CREATE SCHEMA IF NOT EXISTS \"app\".\"order"));
+}
+
 #[test]
 fn test_generate_function_with_volatility() {
     use super::objects::generate_create_function;
@@ -542,6 +648,28 @@ fn test_generate_function_with_security_definer() {
     assert!(sql.contains("SECURITY DEFINER"));
 }
 
+#[test]
+fn test_generate_function_with_all_attributes() {
+    use super::objects::generate_create_function;
+
+    let func = FunctionInfo {
+        schema: "public".to_string(),
+        name: "current_tenant".to_string(),
+        args: vec![],
+        return_type: "uuid".to_string(),
+        language: "sql".to_string(),
+        definition: "SELECT tenant_id FROM public.memberships LIMIT 1".to_string(),
+        volatility: Some("IMMUTABLE".to_string()),
+        is_strict: true,
+        security_definer: true,
+        config_params: vec![],
+        grants: vec![], extension: None,
+    };
+
+    let sql = generate_create_function(&func);
+    assert!(sql.contains("LANGUAGE sql IMMUTABLE STRICT SECURITY DEFINER AS $$"));
+}
+
 #[test]
 fn test_generate_function_with_default_args() {
     use super::objects::generate_create_function;
@@ -615,6 +743,7 @@ fn test_generate_identity_column_change() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             })
         ]),
         foreign_keys: vec![],
@@ -623,9 +752,15 @@ fn test_generate_identity_column_change() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -652,16 +787,28 @@ fn test_generate_identity_column_change() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"items\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"items\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("ADD GENERATED ALWAYS AS IDENTITY")));
 }
 
@@ -687,6 +834,7 @@ fn test_generate_collation_change() {
                 enum_name: None,
                 is_array: false,
                 comment: None,
+                ordinal_position: None,
             })
         ]),
         foreign_keys: vec![],
@@ -695,9 +843,15 @@ fn test_generate_collation_change() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -724,16 +878,28 @@ fn test_generate_collation_change() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"data\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"data\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("COLLATE")));
 }
 
@@ -753,11 +919,18 @@ fn test_generate_check_constraint_add() {
                 name: "valid_age".into(),
                 expression: "CHECK (age >= 0 AND age < 200)".into(),
                 columns: vec![],
+                comment: None,
             }
         ],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -771,22 +944,35 @@ fn test_generate_check_constraint_add() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![
             CheckConstraintInfo {
                 name: "valid_age".into(),
                 expression: "CHECK (age >= 0 AND age < 200)".into(),
                 columns: vec![],
+                comment: None,
             }
         ],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("ADD CONSTRAINT \"valid_age\"")));
 }
 
@@ -802,9 +988,15 @@ fn test_generate_rls_enable() {
         rls_enabled: true,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -818,16 +1010,28 @@ fn test_generate_rls_enable() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"posts\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"posts\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("ENABLE ROW LEVEL SECURITY")));
 }
 
@@ -843,9 +1047,15 @@ fn test_generate_rls_disable() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -859,16 +1069,28 @@ fn test_generate_rls_disable() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"posts\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"posts\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("DISABLE ROW LEVEL SECURITY")));
 }
 
@@ -882,6 +1104,7 @@ fn test_generate_policy_with_using_and_check() {
         roles: vec!["authenticated".to_string()],
         qual: Some("user_id = auth.uid()".to_string()),
         with_check: Some("user_id = auth.uid()".to_string()),
+        comment: None,
     };
 
     let sql = generate_create_policy("\"public\".\"posts\"", &policy);
@@ -920,6 +1143,9 @@ fn test_generate_index_with_expression() {
         index_method: "btree".to_string(),
         where_clause: None,
         expressions: vec!["lower(email)".to_string()],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
     };
 
     let sql = generate_create_index("\"public\".\"users\"", &idx);
@@ -959,6 +1185,11 @@ fn test_generate_drop_table() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let schema = DbSchema::new();
@@ -975,6 +1206,7 @@ fn test_generate_drop_view() {
         definition: "SELECT 1".to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -1013,6 +1245,11 @@ fn test_generate_drop_view() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let sql = generate_sql(&diff, &schema);
@@ -1028,6 +1265,7 @@ fn test_generate_drop_materialized_view() {
         definition: "SELECT 1".to_string(),
         is_materialized: true,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -1066,6 +1304,11 @@ fn test_generate_drop_materialized_view() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let sql = generate_sql(&diff, &schema);
@@ -1104,6 +1347,11 @@ fn test_generate_drop_sequence() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let schema = DbSchema::new();
@@ -1143,6 +1391,11 @@ fn test_generate_drop_extension() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let schema = DbSchema::new();
@@ -1182,6 +1435,11 @@ fn test_generate_drop_role() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let schema = DbSchema::new();
@@ -1221,6 +1479,11 @@ fn test_generate_drop_domain() {
         schema_grants_to_drop: vec![],
         default_privileges_to_create: vec![],
         default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
     };
 
     let schema = DbSchema::new();
@@ -1303,6 +1566,7 @@ fn test_generate_view_with_check_option() {
         definition: "SELECT * FROM users WHERE active = true".to_string(),
         is_materialized: false,
         columns: vec![],
+        column_aliases: vec![],
         indexes: vec![],
         comment: None,
         with_options: vec![],
@@ -1327,9 +1591,15 @@ fn test_generate_index_drop_with_constraint() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -1352,18 +1622,33 @@ fn test_generate_index_drop_with_constraint() {
                 index_method: "btree".into(),
                 where_clause: None,
                 expressions: vec![],
+                include_columns: vec![],
+                nulls_not_distinct: false,
+                comment: None,
             }
         ],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table, &GenerateOptions::default());
     // Should drop the constraint, not the index directly
     assert!(statements.iter().any(|s| s.contains("DROP CONSTRAINT IF EXISTS \"unique_email\"")));
 }
@@ -1380,9 +1665,15 @@ fn test_generate_unique_constraint_via_index() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -1404,19 +1695,34 @@ fn test_generate_unique_constraint_via_index() {
                 index_method: "btree".into(),
                 where_clause: None,
                 expressions: vec![],
+                include_columns: vec![],
+                nulls_not_distinct: false,
+                comment: None,
             }
         ],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("ADD CONSTRAINT \"unique_email\" UNIQUE")));
 }
 
@@ -1442,6 +1748,7 @@ fn test_generate_drop_default() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             })
         ]),
         foreign_keys: vec![],
@@ -1450,9 +1757,15 @@ fn test_generate_drop_default() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -1479,16 +1792,28 @@ fn test_generate_drop_default() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"users\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("DROP DEFAULT")));
 }
 
@@ -1514,6 +1839,7 @@ fn test_generate_drop_identity() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             })
         ]),
         foreign_keys: vec![],
@@ -1522,9 +1848,15 @@ fn test_generate_drop_identity() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -1551,16 +1883,28 @@ fn test_generate_drop_identity() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"items\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"items\"", &table_diff, &table, &GenerateOptions::default());
     assert!(statements.iter().any(|s| s.contains("DROP IDENTITY")));
 }
 
@@ -1591,6 +1935,7 @@ fn test_generate_add_generated_column() {
                 is_generated: true,
                 generation_expression: Some("public.calculate_progression_level(current_craft_experience)".into()),
                 comment: None,
+                ordinal_position: None,
             })
         ]),
         foreign_keys: vec![],
@@ -1599,9 +1944,15 @@ fn test_generate_add_generated_column() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = TableDiff {
@@ -1615,16 +1966,28 @@ fn test_generate_add_generated_column() {
         triggers_to_drop: vec![],
         indexes_to_create: vec![],
         indexes_to_drop: vec![],
+        indexes_to_rename: vec![],
         check_constraints_to_create: vec![],
         check_constraints_to_drop: vec![],
+        exclusion_constraints_to_create: vec![],
+        exclusion_constraints_to_drop: vec![],
         foreign_keys_to_create: vec![],
         foreign_keys_to_drop: vec![],
         grants_to_create: vec![],
         grants_to_drop: vec![],
         comment_change: None,
+        storage_params_change: None,
+        tablespace_change: None,
+        unlogged_change: None,
+        partitions_to_create: vec![],
+        partition_strategy_change: None,
+        partition_bounds_to_change: vec![],
+        object_comments_to_set: vec![],
+        columns_to_rename: vec![],
+        column_rename_candidate: None,
     };
 
-    let statements = generate_alter_table("\"public\".\"objects\"", &table_diff, &table);
+    let statements = generate_alter_table("\"public\".\"objects\"", &table_diff, &table, &GenerateOptions::default());
     
     // Should generate proper GENERATED ALWAYS AS ... STORED syntax
     assert!(statements.iter().any(|s| 
@@ -1635,8 +1998,6 @@ fn test_generate_add_generated_column() {
 
 #[test]
 fn test_generate_create_table_with_generated_column() {
-    use super::tables::generate_create_table;
-    
     let table = TableInfo {
         schema: "public".into(),
         table_name: "products".into(),
@@ -1657,6 +2018,7 @@ fn test_generate_create_table_with_generated_column() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             }),
             ("total".into(), ColumnInfo {
                 column_name: "total".into(),
@@ -1674,6 +2036,7 @@ fn test_generate_create_table_with_generated_column() {
                 is_generated: true,
                 generation_expression: Some("price * qty".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -1682,9 +2045,15 @@ fn test_generate_create_table_with_generated_column() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let sql = generate_create_table(&table);
@@ -1717,6 +2086,7 @@ fn test_enum_to_text_with_generated_dependency() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             }),
             ("permission_key".into(), ColumnInfo {
                 column_name: "permission_key".into(),
@@ -1734,6 +2104,7 @@ fn test_enum_to_text_with_generated_dependency() {
                 is_generated: true,
                 generation_expression: Some("resource_name || ':' || case action when 'create' then 'create' end".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -1742,9 +2113,15 @@ fn test_enum_to_text_with_generated_dependency() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     // Local: table has TEXT column and updated GENERATED column
@@ -1768,6 +2145,7 @@ fn test_enum_to_text_with_generated_dependency() {
                 is_generated: false,
                 generation_expression: None,
                 comment: None,
+                ordinal_position: None,
             }),
             ("permission_key".into(), ColumnInfo {
                 column_name: "permission_key".into(),
@@ -1785,6 +2163,7 @@ fn test_enum_to_text_with_generated_dependency() {
                 is_generated: true,
                 generation_expression: Some("resource_name || ':' || action".into()), // Expression changed
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -1793,9 +2172,15 @@ fn test_enum_to_text_with_generated_dependency() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let table_diff = crate::diff::tables::compute_table_diff(&remote_table, &local_table);
@@ -1804,7 +2189,7 @@ fn test_enum_to_text_with_generated_dependency() {
     // Using current logic, it probably shows as a modification
     // Our fix will change it to drop/add
     
-    let statements = generate_alter_table("\"authz\".\"permissions\"", &table_diff, &local_table);
+    let statements = generate_alter_table("\"authz\".\"permissions\"", &table_diff, &local_table, &GenerateOptions::default());
     
     println!("Statements: {:#?}", statements);
 
@@ -1883,6 +2268,7 @@ fn test_generated_column_normalization_public_prefix() {
                 is_generated: true,
                 generation_expression: Some("public.calculate_progression_level(current_combat_experience)".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -1891,9 +2277,15 @@ fn test_generated_column_normalization_public_prefix() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     // Local: no public. prefix (user definition)
@@ -1917,6 +2309,7 @@ fn test_generated_column_normalization_public_prefix() {
                 is_generated: true,
                 generation_expression: Some("calculate_progression_level(current_combat_experience)".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -1925,9 +2318,15 @@ fn test_generated_column_normalization_public_prefix() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let diff = crate::diff::tables::compute_table_diff(&remote_table, &local_table);
@@ -1965,6 +2364,7 @@ fn test_generated_column_normalization_type_casts() {
                 is_generated: true,
                 generation_expression: Some("to_tsvector('english'::regconfig, coalesce(body, ''::text))".into()),
                 comment: None,
+                ordinal_position: None,
             }),
              ("col2_concat".into(), ColumnInfo {
                 column_name: "col2_concat".into(),
@@ -1982,6 +2382,7 @@ fn test_generated_column_normalization_type_casts() {
                 is_generated: true,
                 generation_expression: Some("((resource_name)::text || ':'::text) || (action)::text".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -1990,9 +2391,15 @@ fn test_generated_column_normalization_type_casts() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     // Local: clean, user defined
@@ -2016,6 +2423,7 @@ fn test_generated_column_normalization_type_casts() {
                 is_generated: true,
                 generation_expression: Some("to_tsvector('english', coalesce(body, ''))".into()),
                 comment: None,
+                ordinal_position: None,
             }),
              ("col2_concat".into(), ColumnInfo {
                 column_name: "col2_concat".into(),
@@ -2033,6 +2441,7 @@ fn test_generated_column_normalization_type_casts() {
                 is_generated: true,
                 generation_expression: Some("resource_name || ':' || action".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -2041,9 +2450,15 @@ fn test_generated_column_normalization_type_casts() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let diff = crate::diff::tables::compute_table_diff(&remote_table, &local_table);
@@ -2077,6 +2492,7 @@ fn test_generated_column_normalization_case_sensitivity() {
                 is_generated: true,
                 generation_expression: Some("to_tsvector('english'::regconfig, COALESCE(body, ''::TEXT))".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -2085,9 +2501,15 @@ fn test_generated_column_normalization_case_sensitivity() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     // Local: Lowercase function, clean
@@ -2111,6 +2533,7 @@ fn test_generated_column_normalization_case_sensitivity() {
                 is_generated: true,
                 generation_expression: Some("to_tsvector('english', coalesce(body, ''))".into()),
                 comment: None,
+                ordinal_position: None,
             }),
         ]),
         foreign_keys: vec![],
@@ -2119,9 +2542,15 @@ fn test_generated_column_normalization_case_sensitivity() {
         rls_enabled: false,
         policies: vec![],
         check_constraints: vec![],
+        exclusion_constraints: vec![],
         grants: vec![],
         comment: None,
         extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
     };
 
     let diff = crate::diff::tables::compute_table_diff(&remote_table, &local_table);
@@ -2129,3 +2558,469 @@ fn test_generated_column_normalization_case_sensitivity() {
     // Should be empty
     assert!(diff.is_empty(), "Diff should be empty but found changes: {:#?}", diff);
 }
+
+fn plain_column(name: &str, data_type: &str, udt_name: &str, default: Option<&str>) -> ColumnInfo {
+    ColumnInfo {
+        column_name: name.into(),
+        data_type: data_type.into(),
+        is_nullable: true,
+        column_default: default.map(|d| d.to_string()),
+        udt_name: udt_name.into(),
+        is_primary_key: false,
+        is_unique: false,
+        is_identity: false,
+        identity_generation: None,
+        collation: None,
+        enum_name: None,
+        is_array: false,
+        is_generated: false,
+        generation_expression: None,
+        comment: None,
+        ordinal_position: None,
+    }
+}
+
+fn table_with_columns(columns: Vec<ColumnInfo>) -> TableInfo {
+    TableInfo {
+        schema: "public".into(),
+        table_name: "orders".into(),
+        columns: columns
+            .into_iter()
+            .map(|c| (c.column_name.clone(), c))
+            .collect(),
+        foreign_keys: vec![],
+        indexes: vec![],
+        triggers: vec![],
+        rls_enabled: false,
+        policies: vec![],
+        check_constraints: vec![],
+        exclusion_constraints: vec![],
+        grants: vec![],
+        comment: None,
+        extension: None,
+        storage_params: vec![],
+        tablespace: None,
+        is_unlogged: false,
+        partition_strategy: None,
+        partitions: vec![],
+    }
+}
+
+#[test]
+fn test_text_to_integer_type_change_uses_cast() {
+    let remote = table_with_columns(vec![plain_column("quantity", "text", "text", None)]);
+    let local = table_with_columns(vec![plain_column("quantity", "integer", "int4", None)]);
+
+    let diff = crate::diff::tables::compute_table_diff(&remote, &local);
+    let statements = generate_alter_table("\"public\".\"orders\"", &diff, &local, &GenerateOptions::default());
+
+    assert_eq!(
+        statements,
+        vec!["ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"quantity\" TYPE integer USING \"quantity\"::integer;"]
+    );
+}
+
+#[test]
+fn test_default_add_and_remove_emit_separate_alters() {
+    let remote = table_with_columns(vec![
+        plain_column("status", "text", "text", None),
+        plain_column("priority", "integer", "int4", Some("0")),
+    ]);
+    let local = table_with_columns(vec![
+        plain_column("status", "text", "text", Some("'pending'::text")),
+        plain_column("priority", "integer", "int4", None),
+    ]);
+
+    let diff = crate::diff::tables::compute_table_diff(&remote, &local);
+    let statements = generate_alter_table("\"public\".\"orders\"", &diff, &local, &GenerateOptions::default());
+
+    assert!(statements.contains(
+        &"ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"status\" SET DEFAULT 'pending'::text;".to_string()
+    ));
+    assert!(statements.contains(
+        &"ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"priority\" DROP DEFAULT;".to_string()
+    ));
+    // Default changes never piggyback on a TYPE change
+    assert!(!statements.iter().any(|s| s.contains(" TYPE ")));
+}
+
+#[test]
+fn test_add_enum_value_runs_before_transaction() {
+    let diff = SchemaDiff {
+        tables_to_create: vec![],
+        tables_to_drop: vec![],
+        table_changes: HashMap::new(),
+        enum_changes: vec![
+            EnumChange {
+                name: "priority".to_string(),
+                type_: EnumChangeType::Create,
+                values_to_add: Some(vec!["low".to_string(), "high".to_string()]),
+            },
+            EnumChange {
+                name: "status".to_string(),
+                type_: EnumChangeType::AddValue,
+                values_to_add: Some(vec!["archived".to_string()]),
+            },
+        ],
+        functions_to_create: vec![],
+        functions_to_drop: vec![],
+        functions_to_update: vec![],
+        views_to_create: vec![],
+        views_to_drop: vec![],
+        views_to_update: vec![],
+        sequences_to_create: vec![],
+        sequences_to_drop: vec![],
+        sequences_to_update: vec![],
+        extensions_to_create: vec![],
+        extensions_to_drop: vec![],
+        extensions_to_update: vec![],
+        composite_types_to_create: vec![],
+        composite_types_to_drop: vec![],
+        composite_types_to_update: vec![],
+        domains_to_create: vec![],
+        domains_to_drop: vec![],
+        domains_to_update: vec![],
+        roles_to_create: vec![],
+        roles_to_drop: vec![],
+        roles_to_update: vec![],
+        schema_grants_to_create: vec![],
+        schema_grants_to_drop: vec![],
+        default_privileges_to_create: vec![],
+        default_privileges_to_drop: vec![],
+        default_acls_to_create: vec![],
+        default_acls_to_drop: vec![],
+        rules_to_create: vec![],
+        rules_to_drop: vec![],
+        schema_comments_to_set: vec![],
+    };
+
+    let plan = generate_migration(&diff, &DbSchema::new());
+
+    assert!(plan.transactional.iter().any(|s| s.contains("CREATE TYPE")));
+    assert!(!plan.transactional.iter().any(|s| s.contains("ADD VALUE")));
+    assert!(plan.non_transactional.is_empty());
+    assert_eq!(plan.before_transaction.len(), 1);
+    assert!(plan.before_transaction[0].contains("ADD VALUE IF NOT EXISTS 'archived'"));
+
+    // Not after the transaction like CONCURRENTLY statements: the new value is
+    // committed before the transaction that may use it begins
+    let sql = plan.to_sql();
+    let add_value_pos = sql.find("ADD VALUE").unwrap();
+    let begin_pos = sql.find("BEGIN;").unwrap();
+    assert!(add_value_pos < begin_pos, "{}", sql);
+}
+
+#[test]
+fn test_concurrent_index_creation_toggle() {
+    let remote = table_with_columns(vec![plain_column("email", "text", "text", None)]);
+    let mut local = remote.clone();
+    local.indexes.push(IndexInfo {
+        index_name: "idx_orders_email".to_string(),
+        columns: vec!["email".into()],
+        is_unique: false,
+        is_primary: false,
+        owning_constraint: None,
+        index_method: "btree".to_string(),
+        where_clause: None,
+        expressions: vec![],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
+    });
+    let diff = crate::diff::tables::compute_table_diff(&remote, &local);
+
+    let plain = generate_alter_table("\"public\".\"orders\"", &diff, &local, &GenerateOptions::default());
+    assert_eq!(
+        plain,
+        vec!["CREATE INDEX \"idx_orders_email\" ON \"public\".\"orders\" (\"email\");"]
+    );
+
+    let options = GenerateOptions { concurrent_indexes: true, ..Default::default() };
+    let concurrent = generate_alter_table("\"public\".\"orders\"", &diff, &local, &options);
+    assert_eq!(
+        concurrent,
+        vec![
+            "DROP INDEX CONCURRENTLY IF EXISTS \"public\".\"idx_orders_email\";",
+            "CREATE INDEX CONCURRENTLY \"idx_orders_email\" ON \"public\".\"orders\" (\"email\");",
+        ]
+    );
+    assert!(concurrent.iter().all(|s| cannot_run_in_transaction(s)));
+    assert!(!cannot_run_in_transaction(&plain[0]));
+}
+
+#[test]
+fn test_generate_index_rename() {
+    let index = |name: &str| IndexInfo {
+        index_name: name.to_string(),
+        columns: vec!["email".into()],
+        is_unique: true,
+        is_primary: false,
+        owning_constraint: None,
+        index_method: "btree".to_string(),
+        where_clause: None,
+        expressions: vec![],
+        include_columns: vec![],
+        nulls_not_distinct: false,
+        comment: None,
+    };
+    let mut remote = table_with_columns(vec![plain_column("email", "text", "text", None)]);
+    remote.indexes.push(index("orders_email_key_idx"));
+    let mut local = remote.clone();
+    local.indexes = vec![index("idx_orders_email")];
+
+    let diff = crate::diff::tables::compute_table_diff(&remote, &local);
+    let statements = generate_alter_table("\"public\".\"orders\"", &diff, &local, &GenerateOptions::default());
+
+    assert_eq!(
+        statements,
+        vec!["ALTER INDEX \"public\".\"orders_email_key_idx\" RENAME TO \"idx_orders_email\";"]
+    );
+}
+
+#[test]
+fn test_generated_stored_column_in_create_and_add() {
+    let temp_f = ColumnInfo {
+        is_generated: true,
+        generation_expression: Some("temp_c * 9 / 5 + 32".to_string()),
+        ..plain_column("temp_f", "numeric", "numeric", None)
+    };
+    let local = table_with_columns(vec![
+        plain_column("temp_c", "numeric", "numeric", None),
+        temp_f,
+    ]);
+
+    let create_sql = super::tables::generate_create_table(&local);
+    assert!(create_sql.contains("\"temp_f\" numeric GENERATED ALWAYS AS (temp_c * 9 / 5 + 32) STORED"));
+    assert!(!create_sql.contains("\"temp_f\" numeric DEFAULT"));
+
+    let remote = table_with_columns(vec![plain_column("temp_c", "numeric", "numeric", None)]);
+    let diff = crate::diff::tables::compute_table_diff(&remote, &local);
+    let statements = generate_alter_table(
+        "\"public\".\"orders\"",
+        &diff,
+        &local,
+        &GenerateOptions::default(),
+    );
+    assert!(statements.contains(
+        &"ALTER TABLE \"public\".\"orders\" ADD COLUMN \"temp_f\" numeric GENERATED ALWAYS AS (temp_c * 9 / 5 + 32) STORED;"
+            .to_string()
+    ));
+}
+
+#[test]
+fn test_unique_constraint_and_unique_index_generate_distinct_sql() {
+    let unique_index = IndexInfo {
+        index_name: "users_email_idx".into(),
+        columns: vec!["email".into()],
+        is_unique: true,
+        index_method: "btree".into(),
+        ..Default::default()
+    };
+    let unique_constraint = IndexInfo {
+        index_name: "users_username_key".into(),
+        columns: vec!["username".into()],
+        is_unique: true,
+        owning_constraint: Some("users_username_key".into()),
+        index_method: "btree".into(),
+        ..Default::default()
+    };
+    let table_name = "\"public\".\"users\"";
+
+    assert_eq!(
+        generate_add_index_or_constraint(table_name, &unique_index),
+        "CREATE UNIQUE INDEX \"users_email_idx\" ON \"public\".\"users\" (\"email\");"
+    );
+    assert_eq!(
+        generate_add_index_or_constraint(table_name, &unique_constraint),
+        "ALTER TABLE \"public\".\"users\" ADD CONSTRAINT \"users_username_key\" UNIQUE (\"username\");"
+    );
+    assert_eq!(
+        generate_drop_index_or_constraint(table_name, "public", &unique_index),
+        "DROP INDEX IF EXISTS \"public\".\"users_email_idx\";"
+    );
+    assert_eq!(
+        generate_drop_index_or_constraint(table_name, "public", &unique_constraint),
+        "ALTER TABLE \"public\".\"users\" DROP CONSTRAINT IF EXISTS \"users_username_key\";"
+    );
+
+    // CREATE TABLE declares the constraint inline and only creates the plain index
+    let mut table = table_with_columns(vec![
+        plain_column("email", "text", "text", None),
+        plain_column("username", "text", "text", None),
+    ]);
+    table.indexes = vec![unique_index, unique_constraint];
+    let sql = generate_create_table(&table);
+    assert!(sql.contains("CONSTRAINT \"users_username_key\" UNIQUE (\"username\")"));
+    assert!(sql.contains("CREATE UNIQUE INDEX \"users_email_idx\""));
+    assert!(!sql.contains("INDEX \"users_username_key\""));
+}
+
+#[test]
+fn test_identifiers_with_double_quotes_round_trip() {
+    assert_eq!(quote_ident("plain"), "\"plain\"");
+    assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+
+    let sql = r#"CREATE TABLE public."we""ird" (id bigint PRIMARY KEY, "col""umn" text NOT NULL);"#;
+    let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let table = local.tables.get("\"public\".\"we\"\"ird\"").expect("key escapes the embedded quote");
+    assert_eq!(table.table_name, "we\"ird");
+    assert!(table.columns.contains_key("col\"umn"));
+
+    let generated = generate_sql(&crate::diff::compute_diff(&DbSchema::new(), &local), &local);
+    assert!(generated.contains("CREATE TABLE \"public\".\"we\"\"ird\""), "{}", generated);
+    assert!(generated.contains("\"col\"\"umn\" text NOT NULL"), "{}", generated);
+
+    // The generated SQL parses back to the same names
+    let reparsed = crate::parsing::parse_schema_sql(&[("generated.sql".to_string(), generated)]).unwrap();
+    let reparsed_table = &reparsed.tables["\"public\".\"we\"\"ird\""];
+    assert_eq!(reparsed_table.table_name, "we\"ird");
+    assert!(reparsed_table.columns.contains_key("col\"umn"));
+}
+
+#[test]
+fn test_name_qualification_modes() {
+    let sql = r#"
+CREATE TYPE "public"."mood" AS ENUM ('happy', 'sad');
+CREATE TABLE "public"."people" (
+    "id" bigint PRIMARY KEY,
+    "mood" "public"."mood" DEFAULT 'happy'
+);
+CREATE TABLE "analytics"."visits" (
+    "id" bigint PRIMARY KEY,
+    "person_id" bigint,
+    CONSTRAINT "visits_person_fk" FOREIGN KEY ("person_id") REFERENCES "public"."people"("id")
+);
+CREATE FUNCTION "public"."touch"() RETURNS trigger LANGUAGE plpgsql AS $$ BEGIN PERFORM 1 FROM public.people; RETURN NEW; END; $$;
+CREATE TRIGGER "people_touch" BEFORE UPDATE ON "public"."people" FOR EACH ROW EXECUTE FUNCTION touch();
+"#;
+    let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let diff = crate::diff::compute_diff(&DbSchema::new(), &local);
+    let generate = |qualification: NameQualification| {
+        generate_migration_with_options(&diff, &local, &GenerateOptions { qualification, ..Default::default() }).to_sql()
+    };
+
+    let as_is = generate(NameQualification::AsIs);
+    assert!(as_is.contains("EXECUTE FUNCTION touch();"), "{}", as_is);
+
+    // Always: identical apart from the trigger function, which gains its schema
+    let always = generate(NameQualification::Always);
+    assert_eq!(
+        always,
+        as_is.replace("EXECUTE FUNCTION touch();", "EXECUTE FUNCTION \"public\".\"touch\"();")
+    );
+
+    // OmitPublic: public objects lose the prefix, other schemas and function bodies keep theirs
+    let omit = generate(NameQualification::OmitPublic);
+    assert!(!omit.contains("\"public\"."), "{}", omit);
+    assert!(omit.contains("CREATE TABLE \"people\" ("), "{}", omit);
+    assert!(omit.contains("REFERENCES \"people\" (\"id\")"), "{}", omit);
+    assert!(omit.contains("CREATE TABLE \"analytics\".\"visits\""), "{}", omit);
+    assert!(omit.contains("PERFORM 1 FROM public.people"), "{}", omit);
+}
+
+
+#[test]
+fn test_generate_sql_is_deterministic() {
+    let remote_sql = r#"
+        CREATE TABLE public.a (id bigint PRIMARY KEY, old_1 text, old_2 text);
+        CREATE TABLE public.b (id bigint PRIMARY KEY, kept int);
+        CREATE TABLE public.c (id bigint PRIMARY KEY);
+        CREATE VIEW public.v_old AS SELECT id FROM public.c;
+    "#;
+    let local_sql = r#"
+        CREATE TABLE public.a (id bigint PRIMARY KEY, new_1 int, new_2 int, new_3 int);
+        CREATE TABLE public.b (id bigint PRIMARY KEY, kept bigint NOT NULL, extra text);
+        CREATE TABLE public.c (id bigint PRIMARY KEY, note text);
+        CREATE TABLE public.d (id bigint PRIMARY KEY);
+        CREATE TABLE public.e (id bigint PRIMARY KEY);
+        CREATE VIEW public.v1 AS SELECT id FROM public.d;
+        CREATE VIEW public.v2 AS SELECT id FROM public.e;
+        CREATE INDEX idx_c_note ON public.c (note);
+        CREATE INDEX idx_b_extra ON public.b (extra);
+    "#;
+
+    // Fresh parses get fresh HashMaps, so iteration order differs between runs
+    let generate = || {
+        let remote = crate::parsing::parse_schema_sql(&[("remote.sql".to_string(), remote_sql.to_string())]).unwrap();
+        let local = crate::parsing::parse_schema_sql(&[("local.sql".to_string(), local_sql.to_string())]).unwrap();
+        let diff = compute_diff(&remote, &local);
+        generate_sql(&diff, &local)
+    };
+
+    let first = generate();
+    assert!(!first.is_empty());
+    for _ in 0..10 {
+        assert_eq!(generate(), first);
+    }
+}
+
+#[test]
+fn test_migration_batches_per_transaction_mode() {
+    let plan = MigrationPlan {
+        before_transaction: vec!["ALTER TYPE \"public\".\"status\" ADD VALUE 'archived';".to_string()],
+        transactional: vec![
+            "CREATE TABLE \"public\".\"tags\" (\"id\" bigint);".to_string(),
+            "ALTER TABLE \"public\".\"posts\" ADD COLUMN \"tag_id\" bigint;".to_string(),
+        ],
+        non_transactional: vec!["CREATE INDEX CONCURRENTLY \"tags_id_idx\" ON \"public\".\"tags\" (\"id\");".to_string()],
+    };
+
+    let auto = plan.batches(TransactionMode::Auto).unwrap();
+    assert_eq!(auto.len(), 3);
+    assert_eq!(auto[0], MigrationBatch { sql: plan.before_transaction[0].clone(), in_transaction: false });
+    assert_eq!(auto[1], MigrationBatch { sql: wrap_in_transaction(&plan.transactional), in_transaction: true });
+    assert_eq!(auto[2], MigrationBatch { sql: plan.non_transactional[0].clone(), in_transaction: false });
+
+    let err = plan.batches(TransactionMode::Single).unwrap_err();
+    assert!(err.contains("ADD VALUE 'archived'"), "{}", err);
+    assert!(err.contains("CONCURRENTLY"), "{}", err);
+    let transactional_only = MigrationPlan { transactional: plan.transactional.clone(), ..Default::default() };
+    assert_eq!(
+        transactional_only.batches(TransactionMode::Single).unwrap(),
+        vec![MigrationBatch { sql: wrap_in_transaction(&plan.transactional), in_transaction: true }]
+    );
+
+    let per_statement = plan.batches(TransactionMode::PerStatement).unwrap();
+    let sqls: Vec<&str> = per_statement.iter().map(|b| b.sql.as_str()).collect();
+    assert_eq!(
+        sqls,
+        vec![
+            plan.before_transaction[0].as_str(),
+            plan.transactional[0].as_str(),
+            plan.transactional[1].as_str(),
+            plan.non_transactional[0].as_str(),
+        ]
+    );
+    assert!(per_statement.iter().all(|b| !b.in_transaction));
+}
+
+#[test]
+fn test_generate_sql_filtered_to_tables() {
+    let sql = r#"
+        CREATE SCHEMA app;
+        CREATE TABLE app.posts (id bigint PRIMARY KEY, title text NOT NULL);
+        CREATE FUNCTION app.post_count() RETURNS bigint LANGUAGE sql AS $$ SELECT count(*) FROM app.posts $$;
+        CREATE VIEW app.recent_posts AS SELECT id, title FROM app.posts;
+    "#;
+    let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+    let diff = compute_diff(&DbSchema::new(), &local);
+
+    let full = generate_sql(&diff, &local);
+    assert!(full.contains("post_count"), "{}", full);
+    assert!(full.contains("recent_posts"), "{}", full);
+
+    let tables_only = generate_sql_filtered(&diff, &local, &[ObjectKind::Tables]);
+    assert!(tables_only.contains("CREATE SCHEMA IF NOT EXISTS \"app\";"), "{}", tables_only);
+    assert!(tables_only.contains("CREATE TABLE \"app\".\"posts\""), "{}", tables_only);
+    assert!(!tables_only.contains("FUNCTION"), "{}", tables_only);
+    assert!(!tables_only.contains("VIEW"), "{}", tables_only);
+    assert!(
+        tables_only.find("CREATE SCHEMA").unwrap() < tables_only.find("CREATE TABLE").unwrap(),
+        "{}",
+        tables_only
+    );
+
+    let functions_only = generate_sql_filtered(&diff, &local, &[ObjectKind::Functions]);
+    assert!(functions_only.contains("post_count"), "{}", functions_only);
+    assert!(!functions_only.contains("CREATE TABLE"), "{}", functions_only);
+}
@@ -1,10 +1,10 @@
-use crate::schema::{CompositeTypeInfo, DomainInfo};
+use crate::schema::{quote_ident, CompositeTypeInfo, DomainInfo};
 
 pub fn generate_create_domain(domain: &DomainInfo) -> String {
-    let mut sql = format!("CREATE DOMAIN \"{}\".\"{}\" AS {}", domain.schema, domain.name, domain.base_type);
+    let mut sql = format!("CREATE DOMAIN {}.{} AS {}", quote_ident(&domain.schema), quote_ident(&domain.name), domain.base_type);
 
     if let Some(collation) = &domain.collation {
-        sql.push_str(&format!(" COLLATE \"{}\"", collation));
+        sql.push_str(&format!(" COLLATE {}", quote_ident(collation)));
     }
 
     if let Some(default) = &domain.default_value {
@@ -17,7 +17,7 @@ pub fn generate_create_domain(domain: &DomainInfo) -> String {
 
     for check in &domain.check_constraints {
         if let Some(name) = &check.name {
-            sql.push_str(&format!(" CONSTRAINT \"{}\"", name));
+            sql.push_str(&format!(" CONSTRAINT {}", quote_ident(name)));
         }
         sql.push_str(&format!(" {}", check.expression));
     }
@@ -31,28 +31,33 @@ pub fn generate_create_composite_type(comp_type: &CompositeTypeInfo) -> String {
         .attributes
         .iter()
         .map(|a| {
-            let mut attr_sql = format!("\"{}\" {}", a.name, a.data_type);
+            let mut attr_sql = format!("{} {}", quote_ident(&a.name), a.data_type);
             if let Some(collation) = &a.collation {
-                attr_sql.push_str(&format!(" COLLATE \"{}\"", collation));
+                attr_sql.push_str(&format!(" COLLATE {}", quote_ident(collation)));
             }
             attr_sql
         })
         .collect();
 
     format!(
-        "CREATE TYPE \"{}\".\"{}\" AS (\n  {}\n);",
-        comp_type.schema,
-        comp_type.name,
+        "CREATE TYPE {}.{} AS (\n  {}\n);",
+        quote_ident(&comp_type.schema),
+        quote_ident(&comp_type.name),
         attrs.join(",\n  ")
     )
 }
 
+/// Enum labels are string literals; embedded quotes are doubled
+pub fn quote_enum_label(label: &str) -> String {
+    format!("'{}'", label.replace('\'', "''"))
+}
+
 pub fn generate_create_enum(name: &str, values: &[String]) -> String {
-    let quoted_values: Vec<String> = values.iter().map(|v| format!("'{}'", v)).collect();
-    // Name is already qualified
+    let quoted_values: Vec<String> = values.iter().map(|v| quote_enum_label(v)).collect();
+    // Same key as ALTER TYPE ... ADD VALUE and DROP TYPE use
     format!(
         "CREATE TYPE {} AS ENUM ({});",
-        name,
+        super::objects::ensure_quoted(name),
         quoted_values.join(", ")
     )
 }
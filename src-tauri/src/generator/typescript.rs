@@ -637,6 +637,7 @@ mod tests {
                 enum_name: None,
                 is_array: false,
                 comment: None,
+                ordinal_position: None,
             },
         );
         columns.insert(
@@ -657,6 +658,7 @@ mod tests {
                 enum_name: None,
                 is_array: false,
                 comment: None,
+                ordinal_position: None,
             },
         );
 
@@ -672,9 +674,15 @@ mod tests {
                 rls_enabled: false,
                 policies: vec![],
                 check_constraints: vec![],
+                exclusion_constraints: vec![],
                 grants: vec![],
                 comment: None,
                 extension: None,
+                storage_params: vec![],
+                tablespace: None,
+                is_unlogged: false,
+                partition_strategy: None,
+                partitions: vec![],
             },
         );
 
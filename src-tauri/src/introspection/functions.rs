@@ -1,11 +1,11 @@
 //! Function introspection.
 
 use crate::schema::{FunctionGrant, FunctionInfo};
-use crate::supabase_api::SupabaseApi;
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use super::helpers::parse_function_args;
+use super::helpers::{parse_function_args, scope_query_to_schemas};
 
 /// Parse config params from PostgreSQL proconfig array format
 /// e.g., ["search_path=''", "statement_timeout=5000"]
@@ -44,8 +44,9 @@ fn parse_grants(grants_json: Option<serde_json::Value>) -> Vec<FunctionGrant> {
 
 /// Fetch all functions from the database.
 pub async fn get_functions(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, FunctionInfo>, String> {
     // Main query for function metadata including grants via aclexplode
     let query = r#"
@@ -102,7 +103,7 @@ pub async fn get_functions(
     }
 
     let result = api
-        .run_query(project_ref, query, true)
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -1,8 +1,10 @@
-use crate::schema::{DefaultPrivilege, ObjectGrant, SchemaGrant};
-use crate::supabase_api::SupabaseApi;
+use crate::schema::{qualified_name, DefaultAcl, DefaultPrivilege, ObjectGrant, SchemaGrant};
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
+use std::collections::HashMap;
 
-use super::queries::{DEFAULT_PRIVILEGES_QUERY, OBJECT_GRANTS_QUERY, SCHEMA_GRANTS_QUERY};
+use super::helpers::scope_query_to_schemas;
+use super::queries::{DEFAULT_ACLS_QUERY, DEFAULT_PRIVILEGES_QUERY, OBJECT_GRANTS_QUERY, SCHEMA_COMMENTS_QUERY, SCHEMA_GRANTS_QUERY};
 
 #[derive(Deserialize)]
 struct SchemaGrantRow {
@@ -11,6 +13,12 @@ struct SchemaGrantRow {
     privilege: String,
 }
 
+#[derive(Deserialize)]
+struct SchemaCommentRow {
+    schema: String,
+    comment: String,
+}
+
 #[derive(Deserialize)]
 struct DefaultPrivilegeRow {
     schema: String,
@@ -20,11 +28,12 @@ struct DefaultPrivilegeRow {
 }
 
 pub async fn get_schema_grants(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<Vec<SchemaGrant>, String> {
     let result = api
-        .run_query(project_ref, SCHEMA_GRANTS_QUERY, true)
+        .run_query(project_ref, &scope_query_to_schemas(SCHEMA_GRANTS_QUERY, schemas), true)
         .await
         .map_err(|e| format!("Failed to fetch schema grants: {}", e))?;
 
@@ -44,12 +53,34 @@ pub async fn get_schema_grants(
     Ok(grants)
 }
 
+pub async fn get_schema_comments(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    schemas: Option<&[String]>,
+) -> Result<HashMap<String, Option<String>>, String> {
+    let result = api
+        .run_query(project_ref, &scope_query_to_schemas(SCHEMA_COMMENTS_QUERY, schemas), true)
+        .await
+        .map_err(|e| format!("Failed to fetch schema comments: {}", e))?;
+
+    let rows: Vec<SchemaCommentRow> =
+        serde_json::from_value(result.result.unwrap_or(serde_json::Value::Array(vec![])))
+            .map_err(|e| format!("Failed to parse schema comments: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .filter(|row| !crate::defaults::is_builtin_schema_comment(&row.schema, Some(&row.comment)))
+        .map(|row| (row.schema, Some(row.comment)))
+        .collect())
+}
+
 pub async fn get_default_privileges(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<Vec<DefaultPrivilege>, String> {
     let result = api
-        .run_query(project_ref, DEFAULT_PRIVILEGES_QUERY, true)
+        .run_query(project_ref, &scope_query_to_schemas(DEFAULT_PRIVILEGES_QUERY, schemas), true)
         .await
         .map_err(|e| format!("Failed to fetch default privileges: {}", e))?;
 
@@ -70,6 +101,41 @@ pub async fn get_default_privileges(
     Ok(privs)
 }
 
+#[derive(Deserialize)]
+struct DefaultAclRow {
+    role: String,
+    schema: Option<String>,
+    object_type: String,
+    grantee: String,
+    privilege: String,
+}
+
+pub async fn get_default_acls(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    schemas: Option<&[String]>,
+) -> Result<Vec<DefaultAcl>, String> {
+    let result = api
+        .run_query(project_ref, &scope_query_to_schemas(DEFAULT_ACLS_QUERY, schemas), true)
+        .await
+        .map_err(|e| format!("Failed to fetch default ACLs: {}", e))?;
+
+    let rows: Vec<DefaultAclRow> =
+        serde_json::from_value(result.result.unwrap_or(serde_json::Value::Array(vec![])))
+            .map_err(|e| format!("Failed to parse default ACLs: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| DefaultAcl {
+            role: row.role,
+            schema: row.schema,
+            object_type: row.object_type,
+            grantee: row.grantee,
+            privilege: row.privilege,
+        })
+        .collect())
+}
+
 #[derive(Deserialize)]
 struct ObjectGrantRow {
     schema: String,
@@ -82,11 +148,12 @@ struct ObjectGrantRow {
 /// Fetch grants on tables, views, and sequences.
 /// Returns a vec of (object_type, qualified_key, ObjectGrant).
 pub async fn get_object_grants(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<Vec<(String, String, ObjectGrant)>, String> {
     let result = api
-        .run_query(project_ref, OBJECT_GRANTS_QUERY, true)
+        .run_query(project_ref, &scope_query_to_schemas(OBJECT_GRANTS_QUERY, schemas), true)
         .await
         .map_err(|e| format!("Failed to fetch object grants: {}", e))?;
 
@@ -101,7 +168,7 @@ pub async fn get_object_grants(
             continue;
         }
 
-        let key = format!("\"{}\".\"{}\"", row.schema, row.object_name);
+        let key = qualified_name(&row.schema, &row.object_name);
         grants.push((
             row.object_type,
             key,
@@ -1,6 +1,6 @@
 //! Helper functions for parsing PostgreSQL introspection results.
 
-use crate::schema::FunctionArg;
+use crate::schema::{FunctionArg, IndexColumn};
 use serde::Deserialize;
 
 /// Parse a PostgreSQL array value from JSON.
@@ -161,44 +161,138 @@ pub fn extract_update_of_columns(trigger_def: &str) -> Option<Vec<String>> {
 pub fn extract_index_expressions(index_def: &str) -> Vec<String> {
     let mut expressions = vec![];
 
-    if let Some(on_idx) = index_def.to_uppercase().find(" ON ") {
-        let after_on = &index_def[on_idx + 4..];
-        if let Some(paren_start) = after_on.find('(') {
-            // Find the matching closing paren by tracking depth
-            // This avoids capturing content from WHERE clauses
-            let in_parens = &after_on[paren_start + 1..];
-            let mut depth = 1;
-            let mut paren_end = None;
-            for (i, c) in in_parens.char_indices() {
-                match c {
-                    '(' => depth += 1,
-                    ')' => {
-                        depth -= 1;
-                        if depth == 0 {
-                            paren_end = Some(i);
-                            break;
-                        }
-                    }
-                    _ => {}
+    if let Some(cols_str) = index_key_list(index_def) {
+        // Split by commas, but only at depth 0 (not inside parentheses)
+        // This correctly handles expressions like COALESCE(a, b) which contain commas
+        let parts = split_respecting_parens(cols_str);
+        for part in &parts {
+            let trimmed = part.trim();
+            // Check if the part contains parentheses, indicating it's likely an expression
+            if trimmed.contains('(') {
+                expressions.push(trimmed.to_string());
+            }
+        }
+    }
+
+    expressions
+}
+
+/// Extract the column names of an `INCLUDE (...)` clause from `pg_get_indexdef` output.
+pub fn extract_index_include_columns(index_def: &str) -> Vec<String> {
+    let Some(include_idx) = index_def.to_uppercase().find(" INCLUDE (") else {
+        return vec![];
+    };
+    let after = &index_def[include_idx + " INCLUDE (".len()..];
+    let Some(end) = after.find(')') else {
+        return vec![];
+    };
+    after[..end]
+        .split(',')
+        .map(|c| c.trim().trim_matches('"').to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/// Whether `pg_get_indexdef` output declares `NULLS NOT DISTINCT`
+pub fn index_nulls_not_distinct(index_def: &str) -> bool {
+    index_def.to_uppercase().contains(" NULLS NOT DISTINCT")
+}
+
+/// Key columns of `pg_get_indexdef` output with their collation, operator class,
+/// sort order and NULLS ordering, e.g. `(name text_pattern_ops, created_at DESC)`.
+/// Expressions are skipped; see `extract_index_expressions`.
+pub fn extract_index_columns(index_def: &str) -> Vec<IndexColumn> {
+    let Some(cols_str) = index_key_list(index_def) else {
+        return vec![];
+    };
+
+    let mut columns = vec![];
+    for part in split_respecting_parens(cols_str) {
+        let Some((tokens, is_expression)) = index_key_tokens(&part) else {
+            continue;
+        };
+        if is_expression {
+            continue;
+        }
+
+        let mut column = IndexColumn::new(tokens[0].clone());
+        let mut rest = tokens[1..].iter();
+        while let Some(token) = rest.next() {
+            match token.to_uppercase().as_str() {
+                "COLLATE" => column.collation = rest.next().cloned(),
+                "ASC" => {}
+                "DESC" => column.descending = true,
+                "NULLS" => column.nulls_first = rest.next().map(|n| n.eq_ignore_ascii_case("FIRST")),
+                _ => column.opclass = Some(token.clone()),
+            }
+        }
+        columns.push(column.normalized());
+    }
+    columns
+}
+
+/// Split one index key into whitespace-separated tokens, keeping quoted identifiers
+/// whole (`"my col" DESC`) and unquoting them (`""` is a literal quote). Also reports
+/// whether the key is an expression, i.e. has a parenthesis outside quotes.
+fn index_key_tokens(part: &str) -> Option<(Vec<String>, bool)> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quoted = false;
+    let mut is_expression = false;
+    let mut chars = part.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                current.push('"');
+            }
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
                 }
             }
-            if let Some(end) = paren_end {
-                let cols_str = &in_parens[..end];
-                // Split by commas, but only at depth 0 (not inside parentheses)
-                // This correctly handles expressions like COALESCE(a, b) which contain commas
-                let parts = split_respecting_parens(cols_str);
-                for part in &parts {
-                    let trimmed = part.trim();
-                    // Check if the part contains parentheses, indicating it's likely an expression
-                    if trimmed.contains('(') {
-                        expressions.push(trimmed.to_string());
-                    }
+            c => {
+                if c == '(' && !quoted {
+                    is_expression = true;
                 }
+                current.push(c);
+                in_token = true;
             }
         }
     }
+    if in_token {
+        tokens.push(current);
+    }
+    (!tokens.is_empty()).then_some((tokens, is_expression))
+}
 
-    expressions
+/// The parenthesized key list after `ON table [USING method]`, matching parentheses
+/// by depth so the INCLUDE list and WHERE clause aren't captured
+fn index_key_list(index_def: &str) -> Option<&str> {
+    let on_idx = index_def.to_uppercase().find(" ON ")?;
+    let after_on = &index_def[on_idx + 4..];
+    let paren_start = after_on.find('(')?;
+    let in_parens = &after_on[paren_start + 1..];
+    let mut depth = 1;
+    for (i, c) in in_parens.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&in_parens[..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
 }
 
 /// Split a string by commas, but only at parenthesis depth 0.
@@ -259,3 +353,26 @@ where
         StrOrInt::Int(v) => Ok(v),
     }
 }
+
+/// Narrow an introspection query to a set of schemas.
+/// Every `n.nspname NOT LIKE 'pg_temp%'` guard gets an extra `AND n.nspname IN (...)`
+/// clause, so the filter is applied inside each CTE rather than after the fact.
+/// Returns the query unchanged when no schemas are given.
+///
+/// Panics if the query has no such guard: it would silently stay unscoped.
+pub fn scope_query_to_schemas(query: &str, schemas: Option<&[String]>) -> String {
+    const ANCHOR: &str = "n.nspname NOT LIKE 'pg_temp%'";
+    assert!(query.contains(ANCHOR), "query can't be scoped to schemas, it has no `{}` guard", ANCHOR);
+
+    match schemas {
+        Some(list) if !list.is_empty() => {
+            let in_list = list
+                .iter()
+                .map(|s| format!("'{}'", s.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            query.replace(ANCHOR, &format!("{} AND n.nspname IN ({})", ANCHOR, in_list))
+        }
+        _ => query.to_string(),
+    }
+}
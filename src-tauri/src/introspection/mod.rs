@@ -1,6 +1,7 @@
 mod functions;
 mod grants;
 mod helpers;
+pub mod progress;
 mod queries;
 mod roles;
 mod sequences;
@@ -9,23 +10,60 @@ mod types;
 mod views;
 
 use helpers::*;
+use progress::{ProgressSink, ProgressTracker};
 
 use crate::schema::{
-    CompositeTypeInfo, DbSchema, DefaultPrivilege, DomainInfo, EnumInfo, ExtensionInfo,
-    FunctionInfo, ObjectGrant, RoleInfo, SchemaGrant, SequenceInfo, TableInfo, ViewInfo,
+    CompositeTypeInfo, DbSchema, DefaultAcl, DefaultPrivilege, DomainInfo, EnumInfo, ExtensionInfo,
+    FunctionInfo, ObjectGrant, RoleInfo, RuleInfo, SchemaGrant, SequenceInfo, TableInfo, ViewInfo,
 };
-use crate::supabase_api::SupabaseApi;
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
 pub struct Introspector<'a> {
-    api: &'a SupabaseApi,
+    api: &'a dyn ManagementApi,
     project_ref: String,
+    watched_schemas: Option<Vec<String>>,
+    progress: Option<ProgressSink>,
+    query_timeout: std::time::Duration,
 }
 
+/// Number of bulk queries reported through the progress sink during `introspect`
+pub const INTROSPECTION_STAGES: usize = 15;
+
+/// How long a single bulk query may run by default, kept under the limit on the whole
+/// introspection so a stuck query is reported by name. Projects can raise it with
+/// `introspection_query_timeout_secs`.
+pub const DEFAULT_QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(8);
+
 impl<'a> Introspector<'a> {
-    pub fn new(api: &'a SupabaseApi, project_ref: String) -> Self {
-        Self { api, project_ref }
+    pub fn new(api: &'a dyn ManagementApi, project_ref: String) -> Self {
+        Self {
+            api,
+            project_ref,
+            watched_schemas: None,
+            progress: None,
+            query_timeout: api.query_timeout(),
+        }
+    }
+
+    /// Fail any single bulk query that runs longer than `timeout`, instead of the
+    /// api client's `query_timeout`
+    pub fn with_query_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.query_timeout = timeout;
+        self
+    }
+
+    /// Restrict introspection to the given schemas (all non-system schemas when `None`)
+    pub fn with_watched_schemas(mut self, watched_schemas: Option<Vec<String>>) -> Self {
+        self.watched_schemas = watched_schemas;
+        self
+    }
+
+    /// Report each completed bulk query to `progress`
+    pub fn with_progress(mut self, progress: Option<ProgressSink>) -> Self {
+        self.progress = progress;
+        self
     }
 
     pub async fn introspect(&self) -> Result<DbSchema, String> {
@@ -36,23 +74,29 @@ impl<'a> Introspector<'a> {
 
         // Run all bulk queries in parallel for maximum efficiency
         println!("[DEBUG introspect] Running bulk queries...");
+        let tracker = ProgressTracker::new(INTROSPECTION_STAGES, self.progress.clone());
+        // Leave room for the slowest query to be reported by name before giving up on the whole
+        let overall_timeout = self.query_timeout.max(DEFAULT_QUERY_TIMEOUT) + std::time::Duration::from_secs(2);
 
-        let (enums, functions, roles, mut tables_data, mut views, mut sequences, extensions, composite_types, domains, schema_grants, default_privileges) =
+        let (enums, functions, roles, mut tables_data, mut views, mut sequences, extensions, composite_types, domains, schema_grants, default_privileges, default_acls, rules, schema_comments) =
             match tokio::time::timeout(
-                std::time::Duration::from_secs(10),
+                overall_timeout,
                 async {
                     tokio::try_join!(
-                        self.get_enums(),
-                        self.get_functions(),
-                        self.get_roles(),
-                        self.get_all_tables_bulk(),
-                        self.get_views(),
-                        self.get_sequences(),
-                        self.get_extensions(),
-                        self.get_composite_types(),
-                        self.get_domains(),
-                        self.get_schema_grants(),
-                        self.get_default_privileges()
+                        tracker.track_within("enums", self.query_timeout, self.get_enums()),
+                        tracker.track_within("functions", self.query_timeout, self.get_functions()),
+                        tracker.track_within("roles", self.query_timeout, self.get_roles()),
+                        tracker.track_within("tables", self.query_timeout, self.get_all_tables_bulk()),
+                        tracker.track_within("views", self.query_timeout, self.get_views()),
+                        tracker.track_within("sequences", self.query_timeout, self.get_sequences()),
+                        tracker.track_within("extensions", self.query_timeout, self.get_extensions()),
+                        tracker.track_within("composite_types", self.query_timeout, self.get_composite_types()),
+                        tracker.track_within("domains", self.query_timeout, self.get_domains()),
+                        tracker.track_within("schema_grants", self.query_timeout, self.get_schema_grants()),
+                        tracker.track_within("default_privileges", self.query_timeout, self.get_default_privileges()),
+                        tracker.track_within("default_acls", self.query_timeout, self.get_default_acls()),
+                        tracker.track_within("rules", self.query_timeout, self.get_rules()),
+                        tracker.track_within("schema_comments", self.query_timeout, self.get_schema_comments())
                     )
                 },
             )
@@ -60,15 +104,15 @@ impl<'a> Introspector<'a> {
             {
                 Ok(result) => result?,
                 Err(_) => {
-                    return Err(
-                        "Introspection timed out after 10 seconds. Check your database connection."
-                            .to_string(),
-                    )
+                    return Err(format!(
+                        "Introspection timed out after {} seconds. Check your database connection.",
+                        overall_timeout.as_secs()
+                    ))
                 }
             };
 
         // Fetch object grants separately (needs mutable access to results)
-        let object_grants = self.get_object_grants().await?;
+        let object_grants = tracker.track_within("object_grants", self.query_timeout, self.get_object_grants()).await?;
         for (object_type, key, grant) in object_grants {
             match object_type.as_str() {
                 "table" => {
@@ -128,23 +172,62 @@ impl<'a> Introspector<'a> {
             domains,
             schema_grants,
             default_privileges,
+            default_acls,
+            rules,
+            schema_comments,
+        })
+    }
+
+    /// Cheap hash of the remote schema, used to tell whether a cached introspection
+    /// result is still current. The watched schemas are part of the fingerprint.
+    pub async fn fingerprint(&self) -> Result<String, String> {
+        #[derive(Deserialize)]
+        struct Row {
+            fingerprint: String,
+        }
+
+        let query = scope_query_to_schemas(
+            queries::SCHEMA_FINGERPRINT_QUERY,
+            self.watched_schemas.as_deref(),
+        );
+        let result = self
+            .api
+            .run_query(&self.project_ref, &query, true)
+            .await
+            .map_err(|e| e.to_string())?;
+        let rows: Vec<Row> =
+            serde_json::from_value(result.result.unwrap_or(serde_json::Value::Array(vec![])))
+                .map_err(|e| e.to_string())?;
+        let hash = rows
+            .into_iter()
+            .next()
+            .map(|r| r.fingerprint)
+            .ok_or("Fingerprint query returned no rows")?;
+
+        Ok(match &self.watched_schemas {
+            Some(schemas) => format!("{}:{}", hash, schemas.join(",")),
+            None => hash,
         })
     }
 
     async fn get_enums(&self) -> Result<HashMap<String, EnumInfo>, String> {
-        types::get_enums(self.api, &self.project_ref).await
+        types::get_enums(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_functions(&self) -> Result<HashMap<String, FunctionInfo>, String> {
-        functions::get_functions(self.api, &self.project_ref).await
+        functions::get_functions(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_views(&self) -> Result<HashMap<String, ViewInfo>, String> {
-        views::get_views(self.api, &self.project_ref).await
+        views::get_views(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
+    }
+
+    async fn get_rules(&self) -> Result<HashMap<String, RuleInfo>, String> {
+        views::get_rules(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_sequences(&self) -> Result<HashMap<String, SequenceInfo>, String> {
-        sequences::get_sequences(self.api, &self.project_ref).await
+        sequences::get_sequences(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_extensions(&self) -> Result<HashMap<String, ExtensionInfo>, String> {
@@ -152,28 +235,36 @@ impl<'a> Introspector<'a> {
     }
 
     async fn get_composite_types(&self) -> Result<HashMap<String, CompositeTypeInfo>, String> {
-        types::get_composite_types(self.api, &self.project_ref).await
+        types::get_composite_types(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_domains(&self) -> Result<HashMap<String, DomainInfo>, String> {
-        types::get_domains(self.api, &self.project_ref).await
+        types::get_domains(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_schema_grants(&self) -> Result<Vec<SchemaGrant>, String> {
-        grants::get_schema_grants(self.api, &self.project_ref).await
+        grants::get_schema_grants(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_default_privileges(&self) -> Result<Vec<DefaultPrivilege>, String> {
-        grants::get_default_privileges(self.api, &self.project_ref).await
+        grants::get_default_privileges(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
+    }
+
+    async fn get_default_acls(&self) -> Result<Vec<DefaultAcl>, String> {
+        grants::get_default_acls(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
+    }
+
+    async fn get_schema_comments(&self) -> Result<HashMap<String, Option<String>>, String> {
+        grants::get_schema_comments(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_object_grants(&self) -> Result<Vec<(String, String, ObjectGrant)>, String> {
-        grants::get_object_grants(self.api, &self.project_ref).await
+        grants::get_object_grants(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     /// Fetch all table information using bulk queries (minimal API calls)
     async fn get_all_tables_bulk(&self) -> Result<HashMap<String, TableInfo>, String> {
-        tables::get_all_tables_bulk(self.api, &self.project_ref).await
+        tables::get_all_tables_bulk(self.api, &self.project_ref, self.watched_schemas.as_deref()).await
     }
 
     async fn get_roles(&self) -> Result<HashMap<String, RoleInfo>, String> {
@@ -183,6 +274,7 @@ impl<'a> Introspector<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::supabase_api::SupabaseApi;
     use serde_json::json;
 
     #[tokio::test]
@@ -1030,4 +1122,595 @@ mod tests {
         let trigger = &table.triggers[0];
         assert_eq!(trigger.timing, "BEFORE");
     }
+
+    #[test]
+    fn test_scope_query_to_schemas() {
+        let schemas = vec!["public".to_string(), "app".to_string()];
+        let scoped = scope_query_to_schemas(tables::TABLES_BULK_QUERY, Some(&schemas));
+        assert!(scoped.contains("AND n.nspname IN ('public', 'app')"));
+        assert_eq!(
+            scoped.matches("n.nspname IN ('public', 'app')").count(),
+            tables::TABLES_BULK_QUERY.matches("n.nspname NOT LIKE 'pg_temp%'").count()
+        );
+
+        let unscoped = scope_query_to_schemas(tables::TABLES_BULK_QUERY, None);
+        assert_eq!(unscoped, tables::TABLES_BULK_QUERY);
+
+        // User namespaces and default ACLs both follow the watched schemas
+        let fingerprint = scope_query_to_schemas(queries::SCHEMA_FINGERPRINT_QUERY, Some(&schemas));
+        assert_eq!(fingerprint.matches("n.nspname IN ('public', 'app')").count(), 2, "{}", fingerprint);
+        let default_acls = fingerprint.lines().find(|l| l.contains("da.defaclnamespace = 0")).unwrap();
+        assert!(default_acls.contains("AND n.nspname IN ('public', 'app')"), "{}", default_acls);
+    }
+
+    #[test]
+    #[should_panic(expected = "has no `n.nspname NOT LIKE 'pg_temp%'` guard")]
+    fn test_scope_query_to_schemas_requires_the_guard() {
+        scope_query_to_schemas(queries::EXTENSIONS_QUERY, None);
+    }
+
+    #[tokio::test]
+    async fn test_progress_tracker_reports_stages() {
+        use progress::ProgressUpdate;
+        use std::sync::{Arc, Mutex};
+
+        let updates: Arc<Mutex<Vec<ProgressUpdate>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink_updates = updates.clone();
+        let sink: ProgressSink = Arc::new(move |update| sink_updates.lock().unwrap().push(update));
+
+        let tracker = ProgressTracker::new(4, Some(sink));
+        tracker.track("enums", async { Ok::<_, String>(()) }).await.unwrap();
+        tracker.track("tables", async { Ok::<_, String>(()) }).await.unwrap();
+        // A failed stage is not reported
+        assert!(tracker.track("views", async { Err::<(), _>("boom".to_string()) }).await.is_err());
+        tracker.complete("functions");
+        tracker.complete("roles");
+
+        let updates = updates.lock().unwrap();
+        let stages: Vec<(&str, usize, u8)> = updates
+            .iter()
+            .map(|u| (u.stage.as_str(), u.completed, u.percent))
+            .collect();
+        assert_eq!(
+            stages,
+            vec![("enums", 1, 25), ("tables", 2, 50), ("functions", 3, 75), ("roles", 4, 100)]
+        );
+        assert!(updates.iter().all(|u| u.total == 4));
+    }
+
+    #[test]
+    fn test_covering_index_include_round_trip() {
+        let local_sql = r#"
+CREATE TABLE "public"."orders" ("id" bigint NOT NULL, "customer_id" bigint, "total" numeric);
+CREATE INDEX "idx_orders_customer" ON "public"."orders" ("customer_id") INCLUDE ("total");
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        let local_idx = &local.tables.get("\"public\".\"orders\"").unwrap().indexes[0];
+        assert_eq!(local_idx.column_names(), vec!["customer_id"]);
+        assert_eq!(local_idx.include_columns, vec!["total"]);
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(generated.contains("ON \"public\".\"orders\" (\"customer_id\") INCLUDE (\"total\");"));
+
+        // pg_index.indkey lists the included column as well
+        let data = json!({
+            "tables": [{"schema": "public", "name": "orders"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [
+                {
+                    "schema": "public",
+                    "table_name": "orders",
+                    "index_name": "idx_orders_customer",
+                    "index_method": "btree",
+                    "is_unique": false,
+                    "is_primary": false,
+                    "columns": ["customer_id", "total"],
+                    "owning_constraint": null,
+                    "index_def": "CREATE INDEX idx_orders_customer ON public.orders USING btree (customer_id) INCLUDE (total)",
+                    "where_clause": null
+                }
+            ],
+            "triggers": [],
+            "policies": [],
+            "rls": [],
+            "check_constraints": [],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_idx = &remote.get("\"public\".\"orders\"").unwrap().indexes[0];
+        assert_eq!(remote_idx.column_names(), vec!["customer_id"]);
+        assert_eq!(remote_idx.include_columns, vec!["total"]);
+
+        assert!(!crate::diff::tables::indexes_differ(local_idx, remote_idx));
+    }
+
+    #[test]
+    fn test_quoted_index_column_with_spaces_keeps_its_name() {
+        let columns = helpers::extract_index_columns(
+            r#"CREATE INDEX i ON public.t USING btree ("my col" DESC, "say ""hi""" COLLATE "C", name)"#,
+        );
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0].name, "my col");
+        assert!(columns[0].descending);
+        assert_eq!(columns[1].name, "say \"hi\"");
+        assert_eq!(columns[1].collation.as_deref(), Some("C"));
+        assert_eq!(columns[2].name, "name");
+        assert!(!columns[2].descending);
+    }
+
+    #[test]
+    fn test_index_column_options_round_trip() {
+        let local_sql = r#"
+CREATE TABLE "public"."posts" ("id" bigint NOT NULL, "title" text, "created_at" timestamptz);
+CREATE INDEX "idx_posts_title" ON "public"."posts" ("title" text_pattern_ops, "created_at" DESC);
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        let local_idx = &local.tables.get("\"public\".\"posts\"").unwrap().indexes[0];
+        assert_eq!(local_idx.column_names(), vec!["title", "created_at"]);
+        assert_eq!(local_idx.columns[0].opclass.as_deref(), Some("text_pattern_ops"));
+        assert!(local_idx.columns[1].descending);
+        assert_eq!(local_idx.columns[1].nulls_first, None, "NULLS FIRST is the default for DESC");
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(
+            generated.contains("ON \"public\".\"posts\" (\"title\" text_pattern_ops, \"created_at\" DESC);"),
+            "{}",
+            generated
+        );
+
+        let data = json!({
+            "tables": [{"schema": "public", "name": "posts"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [
+                {
+                    "schema": "public",
+                    "table_name": "posts",
+                    "index_name": "idx_posts_title",
+                    "index_method": "btree",
+                    "is_unique": false,
+                    "is_primary": false,
+                    "columns": ["title", "created_at"],
+                    "owning_constraint": null,
+                    "index_def": "CREATE INDEX idx_posts_title ON public.posts USING btree (title text_pattern_ops, created_at DESC)",
+                    "where_clause": null
+                }
+            ],
+            "triggers": [],
+            "policies": [],
+            "rls": [],
+            "check_constraints": [],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_idx = &remote.get("\"public\".\"posts\"").unwrap().indexes[0];
+        assert_eq!(remote_idx.columns, local_idx.columns);
+        assert!(!crate::diff::tables::indexes_differ(local_idx, remote_idx));
+
+        // Flipping the sort order is a change
+        let mut ascending = remote_idx.clone();
+        ascending.columns[1].descending = false;
+        assert!(crate::diff::tables::indexes_differ(local_idx, &ascending));
+    }
+
+    #[test]
+    fn test_unique_nulls_not_distinct_round_trip() {
+        let local_sql = r#"
+CREATE TABLE "public"."profiles" ("id" bigint NOT NULL, "email" text, CONSTRAINT "profiles_email_key" UNIQUE NULLS NOT DISTINCT ("email"));
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        let local_idx = &local.tables.get("\"public\".\"profiles\"").unwrap().indexes[0];
+        assert!(local_idx.nulls_not_distinct);
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(
+            generated.contains("CONSTRAINT \"profiles_email_key\" UNIQUE NULLS NOT DISTINCT (\"email\")"),
+            "{}",
+            generated
+        );
+        let reparsed = crate::parsing::parse_schema_sql(&[("generated.sql".to_string(), generated)]).unwrap();
+        assert!(reparsed.tables.get("\"public\".\"profiles\"").unwrap().indexes[0].nulls_not_distinct);
+
+        let data = json!({
+            "tables": [{"schema": "public", "name": "profiles"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [
+                {
+                    "schema": "public",
+                    "table_name": "profiles",
+                    "index_name": "profiles_email_key",
+                    "index_method": "btree",
+                    "is_unique": true,
+                    "is_primary": false,
+                    "columns": ["email"],
+                    "owning_constraint": "profiles_email_key",
+                    "index_def": "CREATE UNIQUE INDEX profiles_email_key ON public.profiles USING btree (email) NULLS NOT DISTINCT",
+                    "where_clause": null
+                }
+            ],
+            "triggers": [],
+            "policies": [],
+            "rls": [],
+            "check_constraints": [],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_idx = &remote.get("\"public\".\"profiles\"").unwrap().indexes[0];
+        assert!(remote_idx.nulls_not_distinct);
+        assert!(!crate::diff::tables::indexes_differ(local_idx, remote_idx));
+
+        // Dropping the flag is a change
+        let mut plain = remote_idx.clone();
+        plain.nulls_not_distinct = false;
+        assert!(crate::diff::tables::indexes_differ(local_idx, &plain));
+    }
+
+    #[test]
+    fn test_exclusion_constraint_round_trip() {
+        let local_sql = r#"
+CREATE TABLE "public"."bookings" (
+    "id" bigint NOT NULL,
+    "room_id" bigint NOT NULL,
+    "during" tstzrange NOT NULL,
+    CONSTRAINT "no_overlap" EXCLUDE USING gist (room_id WITH =, during WITH &&)
+);
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        let local_table = local.tables.get("\"public\".\"bookings\"").unwrap();
+        assert_eq!(local_table.columns.len(), 3);
+        assert_eq!(local_table.exclusion_constraints.len(), 1);
+        assert_eq!(local_table.exclusion_constraints[0].name, "no_overlap");
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(generated.contains("CONSTRAINT \"no_overlap\" EXCLUDE USING gist (room_id WITH =, during WITH &&)"));
+
+        let data = json!({
+            "tables": [{"schema": "public", "name": "bookings"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [],
+            "triggers": [],
+            "policies": [],
+            "rls": [],
+            "check_constraints": [],
+            "exclusion_constraints": [
+                {
+                    "schema": "public",
+                    "table_name": "bookings",
+                    "name": "no_overlap",
+                    "definition": "EXCLUDE USING gist (room_id WITH =, during WITH &&)"
+                }
+            ],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_table = remote.get("\"public\".\"bookings\"").unwrap();
+        assert_eq!(remote_table.exclusion_constraints, local_table.exclusion_constraints);
+
+        let diff = crate::diff::tables::compute_table_diff(remote_table, local_table);
+        assert!(diff.exclusion_constraints_to_create.is_empty());
+        assert!(diff.exclusion_constraints_to_drop.is_empty());
+    }
+
+    #[test]
+    fn test_storage_params_round_trip() {
+        let local_sql = r#"
+CREATE TABLE "public"."events" (
+    "id" bigint NOT NULL
+) WITH (fillfactor=70, autovacuum_enabled=false);
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        let local_table = local.tables.get("\"public\".\"events\"").unwrap();
+        assert_eq!(
+            local_table.storage_params,
+            vec![
+                ("autovacuum_enabled".to_string(), "false".to_string()),
+                ("fillfactor".to_string(), "70".to_string()),
+            ]
+        );
+        assert_eq!(local_table.tablespace, None);
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(generated.contains(") WITH (autovacuum_enabled=false, fillfactor=70);"));
+        let reparsed = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), generated)]).unwrap();
+        assert_eq!(reparsed.tables["\"public\".\"events\""].storage_params, local_table.storage_params);
+
+        let data = json!({
+            "tables": [{"schema": "public", "name": "events"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [],
+            "triggers": [],
+            "policies": [],
+            "rls": [{
+                "schema": "public",
+                "table_name": "events",
+                "rls_enabled": false,
+                "storage_params": ["fillfactor=70", "autovacuum_enabled=false"],
+                "tablespace": null
+            }],
+            "check_constraints": [],
+            "exclusion_constraints": [],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_table = remote.get("\"public\".\"events\"").unwrap();
+        assert_eq!(remote_table.storage_params, local_table.storage_params);
+
+        let diff = crate::diff::tables::compute_table_diff(remote_table, local_table);
+        assert!(diff.storage_params_change.is_none());
+        assert!(diff.tablespace_change.is_none());
+
+        // Lowering the fillfactor is a SET, dropping autovacuum_enabled a RESET
+        let mut remote_schema = DbSchema::new();
+        remote_schema.tables = remote;
+        let mut changed = local.clone();
+        changed.tables.get_mut("\"public\".\"events\"").unwrap().storage_params =
+            vec![("fillfactor".to_string(), "50".to_string())];
+        let migration = crate::generator::generate_sql(&crate::diff::compute_diff(&remote_schema, &changed), &changed);
+        assert!(migration.contains("ALTER TABLE \"public\".\"events\" RESET (autovacuum_enabled);"));
+        assert!(migration.contains("ALTER TABLE \"public\".\"events\" SET (fillfactor=50);"));
+    }
+
+    #[test]
+    fn test_unlogged_table_round_trip() {
+        let local_sql = r#"
+CREATE UNLOGGED TABLE "public"."page_views" (
+    "id" bigint NOT NULL
+);
+CREATE TEMPORARY TABLE "scratch" ("id" bigint);
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        assert_eq!(local.tables.len(), 1, "temporary tables are not part of the schema");
+        let local_table = local.tables.get("\"public\".\"page_views\"").unwrap();
+        assert!(local_table.is_unlogged);
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(generated.contains("CREATE UNLOGGED TABLE \"public\".\"page_views\""));
+        let reparsed = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), generated)]).unwrap();
+        assert!(reparsed.tables["\"public\".\"page_views\""].is_unlogged);
+
+        let data = json!({
+            "tables": [{"schema": "public", "name": "page_views"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [],
+            "triggers": [],
+            "policies": [],
+            "rls": [{
+                "schema": "public",
+                "table_name": "page_views",
+                "rls_enabled": false,
+                "storage_params": null,
+                "tablespace": null,
+                "is_unlogged": true
+            }],
+            "check_constraints": [],
+            "exclusion_constraints": [],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_table = remote.get("\"public\".\"page_views\"").unwrap();
+        assert!(remote_table.is_unlogged);
+        let diff = crate::diff::tables::compute_table_diff(remote_table, local_table);
+        assert!(diff.unlogged_change.is_none());
+
+        // Switching back to a logged table is an ALTER, not a recreate
+        let mut logged = local.clone();
+        logged.tables.get_mut("\"public\".\"page_views\"").unwrap().is_unlogged = false;
+        let migration = crate::generator::generate_sql(&crate::diff::compute_diff(&local, &logged), &logged);
+        assert!(migration.contains("ALTER TABLE \"public\".\"page_views\" SET LOGGED;"));
+    }
+
+    #[test]
+    fn test_range_partitioned_table_round_trip() {
+        let local_sql = r#"
+CREATE TABLE "public"."events" (
+    "id" bigint NOT NULL,
+    "created_at" date NOT NULL
+) PARTITION BY RANGE (created_at);
+CREATE TABLE "public"."events_2024" PARTITION OF "public"."events"
+    FOR VALUES FROM ('2024-01-01') TO ('2025-01-01');
+CREATE TABLE "public"."events_2025" PARTITION OF "public"."events"
+    FOR VALUES FROM ('2025-01-01') TO ('2026-01-01');
+"#;
+        let local = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), local_sql.to_string())]).unwrap();
+        assert_eq!(local.tables.len(), 1, "partitions belong to their parent");
+        let local_table = local.tables.get("\"public\".\"events\"").unwrap();
+        assert_eq!(local_table.partition_strategy.as_deref(), Some("RANGE (created_at)"));
+        let names: Vec<&str> = local_table.partitions.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["events_2024", "events_2025"]);
+        assert_eq!(
+            local_table.partitions[0].bound,
+            "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')"
+        );
+
+        let generated = crate::generator::generate_sql(
+            &crate::diff::compute_diff(&DbSchema::new(), &local),
+            &local,
+        );
+        assert!(generated.contains(") PARTITION BY RANGE (created_at);"));
+        assert!(generated.contains(
+            "CREATE TABLE \"public\".\"events_2025\" PARTITION OF \"public\".\"events\" FOR VALUES FROM ('2025-01-01') TO ('2026-01-01');"
+        ));
+        let reparsed = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), generated)]).unwrap();
+        assert_eq!(reparsed.tables["\"public\".\"events\""].partition_strategy, local_table.partition_strategy);
+        assert_eq!(reparsed.tables["\"public\".\"events\""].partitions, local_table.partitions);
+
+        let partition = |name: &str, from: &str, to: &str| {
+            json!({
+                "schema": "public",
+                "table_name": "events",
+                "partition_schema": "public",
+                "partition_name": name,
+                "bound": format!("FOR VALUES FROM ('{}') TO ('{}')", from, to)
+            })
+        };
+        let data = json!({
+            "tables": [{"schema": "public", "name": "events"}],
+            "columns": [],
+            "foreign_keys": [],
+            "indexes": [],
+            "triggers": [],
+            "policies": [],
+            "rls": [{
+                "schema": "public",
+                "table_name": "events",
+                "rls_enabled": false,
+                "storage_params": null,
+                "tablespace": null,
+                "is_unlogged": false,
+                "partition_strategy": "RANGE (created_at)"
+            }],
+            "partitions": [
+                partition("events_2024", "2024-01-01", "2025-01-01"),
+                partition("events_2025", "2025-01-01", "2026-01-01")
+            ],
+            "check_constraints": [],
+            "exclusion_constraints": [],
+            "table_comments": []
+        });
+        let remote = tables::parse_bulk_response(&data).unwrap();
+        let remote_table = remote.get("\"public\".\"events\"").unwrap();
+        assert_eq!(remote_table.partition_strategy, local_table.partition_strategy);
+        assert_eq!(remote_table.partitions, local_table.partitions);
+        assert!(crate::diff::tables::compute_table_diff(remote_table, local_table).partitions_to_create.is_empty());
+
+        // A partition missing locally is left alone rather than dropped
+        let mut fewer = local_table.clone();
+        fewer.partitions.pop();
+        let diff = crate::diff::tables::compute_table_diff(local_table, &fewer);
+        assert!(diff.is_empty());
+        let diff = crate::diff::tables::compute_table_diff(&fewer, local_table);
+        assert_eq!(diff.partitions_to_create.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_bulk_response_records_ordinal_positions() {
+        let column = |name: &str, ordinal: i32| {
+            json!({
+                "schema": "public",
+                "table_name": "orders",
+                "column_name": name,
+                "data_type": "text",
+                "is_nullable": "YES",
+                "column_default": null,
+                "udt_name": "text",
+                "is_identity": "NO",
+                "is_primary_key": false,
+                "is_unique": false,
+                "comment": null,
+                "ordinal_position": ordinal
+            })
+        };
+        // attnum skips dropped columns, so ordinals can have gaps
+        let data = json!({
+            "tables": [{"schema": "public", "name": "orders"}],
+            "columns": [column("id", 1), column("status", 2), column("customer", 4), column("amount", 5)],
+            "foreign_keys": [],
+            "indexes": [],
+            "triggers": [],
+            "policies": [],
+            "rls": [],
+            "check_constraints": [],
+            "table_comments": []
+        });
+
+        let result = tables::parse_bulk_response(&data).unwrap();
+        let table = result.get("\"public\".\"orders\"").unwrap();
+        assert_eq!(table.columns["id"].ordinal_position, Some(1));
+        assert_eq!(table.columns["status"].ordinal_position, Some(2));
+        assert_eq!(table.columns["customer"].ordinal_position, Some(4));
+        assert_eq!(table.columns["amount"].ordinal_position, Some(5));
+
+        // CREATE TABLE follows the database order rather than the alphabetical one
+        let schema = DbSchema { tables: result.clone(), ..DbSchema::new() };
+        let sql = crate::generator::generate_sql(&crate::diff::compute_diff(&DbSchema::new(), &schema), &schema);
+        let position = |name: &str| sql.find(&format!("\"{}\" text", name)).unwrap();
+        assert!(position("id") < position("status"));
+        assert!(position("status") < position("customer"));
+        assert!(position("customer") < position("amount"));
+
+        // Local files get their ordinals from the CREATE TABLE statement
+        let local = crate::parsing::parse_schema_sql(&[(
+            "schema.sql".to_string(),
+            "CREATE TABLE public.orders (id bigint, status text, amount numeric);".to_string(),
+        )])
+        .unwrap();
+        let local_table = &local.tables["\"public\".\"orders\""];
+        assert_eq!(local_table.columns["amount"].ordinal_position, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_introspect_against_mock_api() {
+        use crate::supabase_api::mock::MockApi;
+
+        let api = MockApi::new()
+            .respond("as fingerprint FROM parts", json!([{"fingerprint": "f00d"}]))
+            .respond(
+                "e.enumlabel ORDER BY e.enumsortorder",
+                json!([{"schema": "app", "name": "status", "values": ["draft", "published"], "extension": null}]),
+            )
+            .respond(
+                "aclexplode(d.defaclacl)",
+                json!([{"role": "postgres", "schema": "app", "object_type": "tables", "grantee": "reporting", "privilege": "SELECT"}]),
+            )
+            .respond(
+                "row_to_json(table_comments)",
+                json!([{"data": {"tables": [{"schema": "app", "name": "posts"}]}}]),
+            );
+        let introspector =
+            Introspector::new(&api, "ref".to_string()).with_watched_schemas(Some(vec!["app".to_string()]));
+
+        let schema = introspector.introspect().await.unwrap();
+        assert_eq!(schema.enums["\"app\".\"status\""].values, vec!["draft", "published"]);
+        assert!(schema.tables.contains_key("\"app\".\"posts\""));
+        assert_eq!(schema.default_acls.len(), 1);
+        assert_eq!(schema.default_acls[0].grantee, "reporting");
+        assert!(schema.functions.is_empty());
+
+        // Every bulk query is sent read-only, and schema-filtered ones are scoped to `app`
+        let queries = api.queries();
+        assert_eq!(queries.len(), INTROSPECTION_STAGES);
+        assert!(api.writes().is_empty());
+        let enum_query = queries.iter().find(|q| q.contains("pg_enum")).unwrap();
+        assert!(enum_query.contains("AND n.nspname IN ('app')"), "{}", enum_query);
+
+        assert_eq!(introspector.fingerprint().await.unwrap(), "f00d:app");
+    }
+
+    #[tokio::test]
+    async fn test_stuck_query_times_out_by_name() {
+        use crate::supabase_api::mock::MockApi;
+
+        // Would only be caught by the 10 second limit on the whole introspection
+        let api = MockApi::new().delay("aclexplode(d.defaclacl)", std::time::Duration::from_secs(30));
+        let introspector = Introspector::new(&api, "ref".to_string())
+            .with_query_timeout(std::time::Duration::from_millis(100));
+
+        let started = std::time::Instant::now();
+        let err = introspector.introspect().await.unwrap_err();
+        assert_eq!(err, "Introspection query 'default_acls' timed out after 0.1s");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5), "{:?}", started.elapsed());
+    }
 }
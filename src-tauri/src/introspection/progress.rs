@@ -0,0 +1,82 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A single progress update for a multi-stage operation (introspection, push, ...)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProgressUpdate {
+    pub stage: String,
+    pub completed: usize,
+    pub total: usize,
+    pub percent: u8,
+}
+
+/// Receiver for progress updates, e.g. a closure emitting a Tauri event
+pub type ProgressSink = Arc<dyn Fn(ProgressUpdate) + Send + Sync>;
+
+/// Counts completed stages and forwards each completion to an optional sink.
+/// Stages may complete concurrently; `completed` is always increasing.
+pub struct ProgressTracker {
+    sink: Option<ProgressSink>,
+    total: usize,
+    completed: AtomicUsize,
+}
+
+impl ProgressTracker {
+    pub fn new(total: usize, sink: Option<ProgressSink>) -> Self {
+        Self {
+            sink,
+            total,
+            completed: AtomicUsize::new(0),
+        }
+    }
+
+    /// Mark a stage as completed and report it
+    pub fn complete(&self, stage: &str) {
+        let completed = self.completed.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(sink) = &self.sink {
+            let percent = if self.total == 0 {
+                100
+            } else {
+                ((completed.min(self.total) * 100) / self.total) as u8
+            };
+            sink(ProgressUpdate {
+                stage: stage.to_string(),
+                completed,
+                total: self.total,
+                percent,
+            });
+        }
+    }
+
+    /// Await a fallible stage, reporting it as completed when it succeeds
+    pub async fn track<T, F>(&self, stage: &str, fut: F) -> Result<T, String>
+    where
+        F: std::future::Future<Output = Result<T, String>>,
+    {
+        let result = fut.await?;
+        self.complete(stage);
+        Ok(result)
+    }
+
+    /// `track` with a deadline; a stage still running after `timeout` fails with an
+    /// error naming it
+    pub async fn track_within<T, F>(&self, stage: &str, timeout: Duration, fut: F) -> Result<T, String>
+    where
+        F: std::future::Future<Output = Result<T, String>>,
+    {
+        match tokio::time::timeout(timeout, fut).await {
+            Ok(result) => {
+                let result = result?;
+                self.complete(stage);
+                Ok(result)
+            }
+            Err(_) => Err(format!(
+                "Introspection query '{}' timed out after {:.1}s",
+                stage,
+                timeout.as_secs_f64()
+            )),
+        }
+    }
+}
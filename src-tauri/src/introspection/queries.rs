@@ -87,6 +87,15 @@ pub const SCHEMA_GRANTS_QUERY: &str = r#"
       AND n.nspname NOT LIKE 'pg_temp%'
 "#;
 
+pub const SCHEMA_COMMENTS_QUERY: &str = r#"
+    SELECT n.nspname AS schema, obj_description(n.oid, 'pg_namespace') AS comment
+    FROM pg_namespace n
+    WHERE obj_description(n.oid, 'pg_namespace') IS NOT NULL
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+      AND n.nspname NOT LIKE 'pg_toast%'
+      AND n.nspname NOT LIKE 'pg_temp%'
+"#;
+
 pub const OBJECT_GRANTS_QUERY: &str = r#"
     SELECT n.nspname AS schema, c.relname AS object_name,
            CASE c.relkind WHEN 'r' THEN 'table' WHEN 'v' THEN 'view' WHEN 'm' THEN 'view' WHEN 'S' THEN 'sequence' END AS object_type,
@@ -139,3 +148,81 @@ pub const DEFAULT_PRIVILEGES_QUERY: &str = r#"
     WHERE gc.granted_tables = sc.total_tables
       AND sc.total_tables > 0
 "#;
+
+/// `ALTER DEFAULT PRIVILEGES` entries from `pg_default_acl`, one row per privilege.
+/// Global entries (no schema) store the full ACL, so the built-in defaults and the
+/// role's own privileges are left out to keep only what was granted explicitly.
+pub const DEFAULT_ACLS_QUERY: &str = r#"
+    SELECT
+        pg_get_userbyid(d.defaclrole) AS role,
+        n.nspname AS schema,
+        CASE d.defaclobjtype
+            WHEN 'r' THEN 'tables'
+            WHEN 'S' THEN 'sequences'
+            WHEN 'f' THEN 'functions'
+            WHEN 'T' THEN 'types'
+            WHEN 'n' THEN 'schemas'
+        END AS object_type,
+        COALESCE(r.rolname, 'public') AS grantee,
+        acl.privilege_type AS privilege
+    FROM pg_default_acl d
+    LEFT JOIN pg_namespace n ON n.oid = d.defaclnamespace
+    CROSS JOIN LATERAL aclexplode(d.defaclacl) acl
+    LEFT JOIN pg_roles r ON r.oid = acl.grantee
+    WHERE acl.grantee <> d.defaclrole
+      AND (
+        d.defaclnamespace = 0
+        OR (n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+          AND n.nspname NOT LIKE 'pg_temp%')
+      )
+      AND NOT (
+        d.defaclnamespace = 0
+        AND (acl.grantee, acl.privilege_type) IN (
+            SELECT builtin.grantee, builtin.privilege_type
+            FROM aclexplode(acldefault(
+                (CASE d.defaclobjtype WHEN 'S' THEN 's' ELSE d.defaclobjtype END)::"char",
+                d.defaclrole
+            )) builtin
+        )
+      )
+"#;
+
+/// Query to compute a cheap fingerprint of the user schemas.
+/// Any DDL rewrites the affected catalog rows, which changes their `xmin`, so the
+/// hash changes whenever something introspection would see has changed.
+/// Extensions and roles are introspected database-wide, so they are fingerprinted
+/// that way too; default ACLs are scoped like `DEFAULT_ACLS_QUERY`.
+pub const SCHEMA_FINGERPRINT_QUERY: &str = r#"
+    WITH user_namespaces AS (
+        SELECT n.oid
+        FROM pg_namespace n
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+          AND n.nspname NOT LIKE 'pg_temp%'
+          AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
+    ),
+    user_classes AS (
+        SELECT c.oid, c.xmin FROM pg_class c WHERE c.relnamespace IN (SELECT oid FROM user_namespaces)
+    ),
+    parts(v) AS (
+        SELECT 'n' || n.oid || ':' || n.xmin FROM pg_namespace n WHERE n.oid IN (SELECT oid FROM user_namespaces)
+        UNION ALL SELECT 'c' || c.oid || ':' || c.xmin FROM user_classes c
+        UNION ALL SELECT 'a' || a.attrelid || '.' || a.attnum || ':' || a.xmin FROM pg_attribute a WHERE a.attrelid IN (SELECT oid FROM user_classes) AND a.attnum > 0
+        UNION ALL SELECT 'd' || d.oid || ':' || d.xmin FROM pg_attrdef d WHERE d.adrelid IN (SELECT oid FROM user_classes)
+        UNION ALL SELECT 'r' || r.oid || ':' || r.xmin FROM pg_rewrite r WHERE r.ev_class IN (SELECT oid FROM user_classes)
+        UNION ALL SELECT 's' || s.seqrelid || ':' || s.xmin FROM pg_sequence s WHERE s.seqrelid IN (SELECT oid FROM user_classes)
+        UNION ALL SELECT 'k' || con.oid || ':' || con.xmin FROM pg_constraint con WHERE con.connamespace IN (SELECT oid FROM user_namespaces)
+        UNION ALL SELECT 'p' || pol.oid || ':' || pol.xmin FROM pg_policy pol WHERE pol.polrelid IN (SELECT oid FROM user_classes)
+        UNION ALL SELECT 't' || tg.oid || ':' || tg.xmin FROM pg_trigger tg WHERE tg.tgrelid IN (SELECT oid FROM user_classes) AND NOT tg.tgisinternal
+        UNION ALL SELECT 'f' || p.oid || ':' || p.xmin FROM pg_proc p WHERE p.pronamespace IN (SELECT oid FROM user_namespaces)
+        UNION ALL SELECT 'y' || t.oid || ':' || t.xmin FROM pg_type t WHERE t.typnamespace IN (SELECT oid FROM user_namespaces)
+        UNION ALL SELECT 'e' || e.oid || ':' || e.xmin FROM pg_enum e JOIN pg_type t ON t.oid = e.enumtypid WHERE t.typnamespace IN (SELECT oid FROM user_namespaces)
+        UNION ALL SELECT 'x' || x.oid || ':' || x.xmin FROM pg_extension x
+        UNION ALL SELECT 'g' || da.oid || ':' || da.xmin FROM pg_default_acl da LEFT JOIN pg_namespace n ON n.oid = da.defaclnamespace
+            WHERE da.defaclnamespace = 0 OR (n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg_toast%' AND n.nspname NOT LIKE 'pg_temp%')
+        UNION ALL SELECT 'm' || ds.objoid || '.' || ds.classoid || '.' || ds.objsubid || ':' || ds.xmin FROM pg_description ds WHERE ds.objoid >= 16384
+        UNION ALL SELECT 'o' || ro.rolname || ':' || md5(ro::text) FROM pg_roles ro
+    )
+    SELECT md5(coalesce(string_agg(v, ',' ORDER BY v), '')) as fingerprint FROM parts
+"#;
@@ -1,13 +1,13 @@
 //! Roles and extensions introspection.
 
 use crate::schema::{ExtensionInfo, RoleInfo};
-use crate::supabase_api::SupabaseApi;
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
 /// Fetch database roles.
 pub async fn get_roles(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
 ) -> Result<HashMap<String, RoleInfo>, String> {
     let query = r#"
@@ -76,7 +76,7 @@ pub async fn get_roles(
 
 /// Fetch database extensions.
 pub async fn get_extensions(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
 ) -> Result<HashMap<String, ExtensionInfo>, String> {
     let query = r#"
@@ -1,16 +1,17 @@
 //! Sequence introspection.
 
-use crate::schema::SequenceInfo;
-use crate::supabase_api::SupabaseApi;
+use crate::schema::{qualified_name, SequenceInfo};
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use super::helpers::deserialize_i64_or_string;
+use super::helpers::{deserialize_i64_or_string, scope_query_to_schemas};
 
 /// Fetch all sequences from the database.
 pub async fn get_sequences(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, SequenceInfo>, String> {
     let query = r#"
         SELECT
@@ -74,7 +75,7 @@ pub async fn get_sequences(
     }
 
     let result = api
-        .run_query(project_ref, query, true)
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -84,7 +85,7 @@ pub async fn get_sequences(
 
     let mut sequences = HashMap::new();
     for row in rows {
-        let key = format!("\"{}\".\"{}\"", row.schema, row.name);
+        let key = qualified_name(&row.schema, &row.name);
         sequences.insert(
             key,
             SequenceInfo {
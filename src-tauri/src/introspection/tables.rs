@@ -1,13 +1,14 @@
 //! Table introspection: get_all_tables_bulk and parse_bulk_response.
 
 use crate::schema::{
-    CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexInfo, PolicyInfo, TableInfo, TriggerInfo,
+    qualified_name, CheckConstraintInfo, ColumnInfo, ExclusionConstraintInfo, ForeignKeyInfo,
+    IndexColumn, IndexInfo, PartitionInfo, PolicyInfo, TableInfo, TriggerInfo,
 };
-use crate::supabase_api::SupabaseApi;
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use super::helpers::{extract_index_expressions, extract_trigger_when_clause, extract_update_of_columns, parse_pg_array, parse_policy_cmd};
+use super::helpers::{extract_index_columns, extract_index_expressions, extract_index_include_columns, index_nulls_not_distinct, extract_trigger_when_clause, extract_update_of_columns, parse_pg_array, parse_policy_cmd, scope_query_to_schemas};
 
 /// The bulk SQL query to fetch all table information in a single call.
 pub const TABLES_BULK_QUERY: &str = r#"
@@ -24,7 +25,8 @@ pub const TABLES_BULK_QUERY: &str = r#"
           AND n.nspname NOT LIKE 'pg_toast%'
           AND n.nspname NOT LIKE 'pg_temp%'
           AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
-        AND c.relkind = 'r'
+        AND c.relkind IN ('r', 'p')
+        AND NOT c.relispartition
     ),
     columns_data AS (
         SELECT
@@ -47,7 +49,8 @@ pub const TABLES_BULK_QUERY: &str = r#"
             false as is_unique,
             a.attgenerated as generated_status,
             CASE WHEN a.attgenerated = 's' THEN pg_get_expr(d.adbin, d.adrelid) ELSE NULL END as generation_expression,
-            col_description(t.oid, a.attnum) as comment
+            col_description(t.oid, a.attnum) as comment,
+            a.attnum as ordinal_position
         FROM pg_attribute a
         JOIN pg_class t ON a.attrelid = t.oid
         JOIN pg_namespace n ON t.relnamespace = n.oid
@@ -63,7 +66,7 @@ pub const TABLES_BULK_QUERY: &str = r#"
           AND n.nspname NOT LIKE 'pg_toast%'
           AND n.nspname NOT LIKE 'pg_temp%'
           AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
-        AND t.relkind = 'r'
+        AND t.relkind IN ('r', 'p')
         AND a.attnum > 0
         AND NOT a.attisdropped
     ),
@@ -117,7 +120,8 @@ pub const TABLES_BULK_QUERY: &str = r#"
             MAX(con.conname) as owning_constraint,
             am.amname as index_method,
             pg_get_expr(ix.indpred, ix.indrelid) as where_clause,
-            pg_get_indexdef(i.oid) as index_def
+            pg_get_indexdef(i.oid) as index_def,
+            obj_description(i.oid, 'pg_class') as comment
         FROM pg_class t
         JOIN pg_index ix ON t.oid = ix.indrelid
         JOIN pg_class i ON i.oid = ix.indexrelid
@@ -130,6 +134,8 @@ pub const TABLES_BULK_QUERY: &str = r#"
           AND n.nspname NOT LIKE 'pg_temp%'
           AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
         AND NOT ix.indisprimary
+        -- Indexes backing EXCLUDE constraints are reported as exclusion constraints
+        AND NOT EXISTS (SELECT 1 FROM pg_constraint xc WHERE xc.conindid = i.oid AND xc.contype = 'x')
         GROUP BY n.nspname, t.relname, i.relname, ix.indisunique, ix.indisprimary, am.amname, ix.indpred, ix.indrelid, i.oid
     ),
     trigger_data AS (
@@ -163,7 +169,8 @@ pub const TABLES_BULK_QUERY: &str = r#"
                 ELSE ARRAY(SELECT rolname::text FROM pg_authid WHERE oid = ANY(p.polroles))
             END as roles,
             pg_get_expr(p.polqual, p.polrelid) as qual,
-            pg_get_expr(p.polwithcheck, p.polrelid) as with_check
+            pg_get_expr(p.polwithcheck, p.polrelid) as with_check,
+            obj_description(p.oid, 'pg_policy') as comment
         FROM pg_policy p
         JOIN pg_class c ON c.oid = p.polrelid
         JOIN pg_namespace n ON n.oid = c.relnamespace
@@ -173,14 +180,41 @@ pub const TABLES_BULK_QUERY: &str = r#"
           AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
     ),
     rls_data AS (
-        SELECT n.nspname as schema, c.relname as table_name, c.relrowsecurity as rls_enabled
+        SELECT
+            n.nspname as schema,
+            c.relname as table_name,
+            c.relrowsecurity as rls_enabled,
+            c.reloptions as storage_params,
+            ts.spcname as tablespace,
+            c.relpersistence = 'u' as is_unlogged,
+            CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) END as partition_strategy
         FROM pg_class c
         JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN pg_tablespace ts ON ts.oid = c.reltablespace
         WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
           AND n.nspname NOT LIKE 'pg_toast%'
           AND n.nspname NOT LIKE 'pg_temp%'
           AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
-        AND c.relkind = 'r'
+        AND c.relkind IN ('r', 'p')
+    ),
+    partition_data AS (
+        SELECT
+            pn.nspname as schema,
+            parent.relname as table_name,
+            n.nspname as partition_schema,
+            c.relname as partition_name,
+            pg_get_expr(c.relpartbound, c.oid) as bound
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_inherits inh ON inh.inhrelid = c.oid
+        JOIN pg_class parent ON parent.oid = inh.inhparent
+        JOIN pg_namespace pn ON pn.oid = parent.relnamespace
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+          AND n.nspname NOT LIKE 'pg_temp%'
+          AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
+        AND c.relispartition
+        AND c.relkind IN ('r', 'p')
     ),
     check_data AS (
         SELECT
@@ -188,7 +222,8 @@ pub const TABLES_BULK_QUERY: &str = r#"
             c.relname as table_name,
             con.conname as name,
             pg_get_constraintdef(con.oid) as expression,
-            array_agg(a.attname ORDER BY a.attnum) as columns
+            array_agg(a.attname ORDER BY a.attnum) as columns,
+            obj_description(con.oid, 'pg_constraint') as comment
         FROM pg_constraint con
         JOIN pg_class c ON con.conrelid = c.oid
         JOIN pg_namespace n ON c.relnamespace = n.oid
@@ -200,6 +235,21 @@ pub const TABLES_BULK_QUERY: &str = r#"
         AND con.contype = 'c'
         GROUP BY n.nspname, c.relname, con.conname, con.oid
     ),
+    exclusion_data AS (
+        SELECT
+            n.nspname as schema,
+            c.relname as table_name,
+            con.conname as name,
+            pg_get_constraintdef(con.oid) as definition
+        FROM pg_constraint con
+        JOIN pg_class c ON con.conrelid = c.oid
+        JOIN pg_namespace n ON c.relnamespace = n.oid
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+          AND n.nspname NOT LIKE 'pg_temp%'
+          AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
+        AND con.contype = 'x'
+    ),
     table_comments AS (
         SELECT
             n.nspname as schema,
@@ -211,7 +261,7 @@ pub const TABLES_BULK_QUERY: &str = r#"
           AND n.nspname NOT LIKE 'pg_toast%'
           AND n.nspname NOT LIKE 'pg_temp%'
           AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
-        AND c.relkind = 'r'
+        AND c.relkind IN ('r', 'p')
     )
     SELECT json_build_object(
         'tables', (SELECT json_agg(row_to_json(table_list)) FROM table_list),
@@ -221,18 +271,21 @@ pub const TABLES_BULK_QUERY: &str = r#"
         'triggers', (SELECT json_agg(row_to_json(trigger_data)) FROM trigger_data),
         'policies', (SELECT json_agg(row_to_json(policy_data)) FROM policy_data),
         'rls', (SELECT json_agg(row_to_json(rls_data)) FROM rls_data),
+        'partitions', (SELECT json_agg(row_to_json(partition_data)) FROM partition_data),
         'check_constraints', (SELECT json_agg(row_to_json(check_data)) FROM check_data),
+        'exclusion_constraints', (SELECT json_agg(row_to_json(exclusion_data)) FROM exclusion_data),
         'table_comments', (SELECT json_agg(row_to_json(table_comments)) FROM table_comments)
     ) as data
 "#;
 
 /// Fetch all table information using a bulk query (minimal API calls).
 pub async fn get_all_tables_bulk(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, TableInfo>, String> {
     let result = api
-        .run_query(project_ref, TABLES_BULK_QUERY, true)
+        .run_query(project_ref, &scope_query_to_schemas(TABLES_BULK_QUERY, schemas), true)
         .await
         .map_err(|e| format!("Bulk query failed: {}", e))?;
 
@@ -280,6 +333,8 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         generated_status: Option<String>,
         generation_expression: Option<String>,
         comment: Option<String>,
+        #[serde(default)]
+        ordinal_position: Option<i32>,
     }
     let columns: Vec<ColumnRow> = data
         .get("columns")
@@ -317,6 +372,8 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         index_method: String,
         where_clause: Option<String>,
         index_def: Option<String>,
+        #[serde(default)]
+        comment: Option<String>,
     }
     let indexes: Vec<IndexRow> = data
         .get("indexes")
@@ -349,6 +406,8 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         roles: Vec<String>,
         qual: Option<String>,
         with_check: Option<String>,
+        #[serde(default)]
+        comment: Option<String>,
     }
     let policies: Vec<PolicyRow> = data
         .get("policies")
@@ -361,6 +420,14 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         schema: String,
         table_name: String,
         rls_enabled: bool,
+        #[serde(default)]
+        storage_params: Option<Vec<String>>,
+        #[serde(default)]
+        tablespace: Option<String>,
+        #[serde(default)]
+        is_unlogged: bool,
+        #[serde(default)]
+        partition_strategy: Option<String>,
     }
     let rls_data: Vec<RlsRow> = data
         .get("rls")
@@ -368,6 +435,20 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         .and_then(|v| serde_json::from_value(v).ok())
         .unwrap_or_default();
 
+    #[derive(Deserialize)]
+    struct PartitionRow {
+        schema: String,
+        table_name: String,
+        partition_schema: String,
+        partition_name: String,
+        bound: String,
+    }
+    let partition_data: Vec<PartitionRow> = data
+        .get("partitions")
+        .cloned()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
     #[derive(Deserialize)]
     struct CheckRow {
         schema: String,
@@ -375,6 +456,8 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         name: String,
         expression: String,
         columns: serde_json::Value,
+        #[serde(default)]
+        comment: Option<String>,
     }
     let check_data: Vec<CheckRow> = data
         .get("check_constraints")
@@ -382,6 +465,19 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
         .and_then(|v| serde_json::from_value(v).ok())
         .unwrap_or_default();
 
+    #[derive(Deserialize)]
+    struct ExclusionRow {
+        schema: String,
+        table_name: String,
+        name: String,
+        definition: String,
+    }
+    let exclusion_data: Vec<ExclusionRow> = data
+        .get("exclusion_constraints")
+        .cloned()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
     #[derive(Deserialize)]
     struct CommentRow {
         schema: String,
@@ -399,7 +495,7 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
 
     // Initialize all tables
     for row in table_rows {
-        let key = format!("\"{}\".\"{}\"", row.schema, row.name);
+        let key = qualified_name(&row.schema, &row.name);
         tables.insert(
             key,
             TableInfo {
@@ -412,16 +508,22 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
                 rls_enabled: false,
                 policies: vec![],
                 check_constraints: vec![],
+                exclusion_constraints: vec![],
                 grants: vec![],
                 comment: None,
                 extension: row.extension,
+                storage_params: vec![],
+                tablespace: None,
+                is_unlogged: false,
+                partition_strategy: None,
+                partitions: vec![],
             },
         );
     }
 
     // Populate columns
     for col in columns {
-        let key = format!("\"{}\".\"{}\"", col.schema, col.table_name);
+        let key = qualified_name(&col.schema, &col.table_name);
         if let Some(table) = tables.get_mut(&key) {
             let mut final_data_type = col.data_type.clone();
             if final_data_type == "ARRAY" {
@@ -448,6 +550,7 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
                     is_generated: col.generated_status.as_deref() == Some("s"),
                     generation_expression: col.generation_expression,
                     comment: col.comment,
+                    ordinal_position: col.ordinal_position,
                 },
             );
         }
@@ -461,7 +564,7 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
     let mut table_fk_map: HashMap<String, HashMap<String, ForeignKeyInfo>> = HashMap::new();
 
     for fk in fks {
-        let table_key = format!("\"{}\".\"{}\"", fk.schema, fk.table_name);
+        let table_key = qualified_name(&fk.schema, &fk.table_name);
         
         table_fk_map
             .entry(table_key)
@@ -498,23 +601,46 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
 
     // Populate indexes
     for idx in indexes {
-        let key = format!("\"{}\".\"{}\"", idx.schema, idx.table_name);
+        let key = qualified_name(&idx.schema, &idx.table_name);
         if let Some(table) = tables.get_mut(&key) {
             let expressions = idx
                 .index_def
                 .as_ref()
                 .map(|d| extract_index_expressions(d))
                 .unwrap_or_default();
+            let include_columns = idx
+                .index_def
+                .as_ref()
+                .map(|d| extract_index_include_columns(d))
+                .unwrap_or_default();
+            // Per-column options only appear in the definition
+            let mut defined_columns = idx
+                .index_def
+                .as_ref()
+                .map(|d| extract_index_columns(d))
+                .unwrap_or_default();
+            // indkey lists INCLUDE columns too; keep only the key columns
+            let columns: Vec<IndexColumn> = parse_pg_array(&idx.columns)
+                .into_iter()
+                .filter(|c| !include_columns.contains(c))
+                .map(|name| match defined_columns.iter().position(|c| c.name == name) {
+                    Some(i) => defined_columns.remove(i),
+                    None => IndexColumn::new(name),
+                })
+                .collect();
 
             table.indexes.push(IndexInfo {
                 index_name: idx.index_name,
-                columns: parse_pg_array(&idx.columns),
+                columns,
                 is_unique: idx.is_unique,
                 is_primary: idx.is_primary,
                 owning_constraint: idx.owning_constraint,
                 index_method: idx.index_method,
                 where_clause: idx.where_clause,
                 expressions,
+                include_columns,
+                nulls_not_distinct: idx.index_def.as_deref().is_some_and(index_nulls_not_distinct),
+                comment: idx.comment,
             });
         }
     }
@@ -526,7 +652,7 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
             "\"{}\".\"{}\".{}",
             tr.schema, tr.table_name, tr.trigger_name
         );
-        let table_key = format!("\"{}\".\"{}\"", tr.schema, tr.table_name);
+        let table_key = qualified_name(&tr.schema, &tr.table_name);
 
         let tgtype = tr.tgtype;
         let is_row = (tgtype & 1) != 0;
@@ -610,15 +736,33 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
 
     // Populate RLS
     for rls in rls_data {
-        let key = format!("\"{}\".\"{}\"", rls.schema, rls.table_name);
+        let key = qualified_name(&rls.schema, &rls.table_name);
         if let Some(table) = tables.get_mut(&key) {
             table.rls_enabled = rls.rls_enabled;
+            // reloptions are `name=value` strings
+            let params = rls.storage_params.unwrap_or_default().join(",");
+            table.storage_params = crate::parsing::parse_storage_params(&params);
+            table.tablespace = rls.tablespace;
+            table.is_unlogged = rls.is_unlogged;
+            table.partition_strategy = rls.partition_strategy;
+        }
+    }
+
+    // Populate partitions
+    for row in partition_data {
+        let key = qualified_name(&row.schema, &row.table_name);
+        if let Some(table) = tables.get_mut(&key) {
+            table.partitions.push(PartitionInfo {
+                schema: row.partition_schema,
+                name: row.partition_name,
+                bound: row.bound,
+            });
         }
     }
 
     // Populate policies
     for pol in policies {
-        let key = format!("\"{}\".\"{}\"", pol.schema, pol.table_name);
+        let key = qualified_name(&pol.schema, &pol.table_name);
         if let Some(table) = tables.get_mut(&key) {
             table.policies.push(PolicyInfo {
                 name: pol.name,
@@ -626,25 +770,38 @@ pub fn parse_bulk_response(data: &serde_json::Value) -> Result<HashMap<String, T
                 roles: pol.roles,
                 qual: pol.qual,
                 with_check: pol.with_check,
+                comment: pol.comment,
             });
         }
     }
 
     // Populate check constraints
     for check in check_data {
-        let key = format!("\"{}\".\"{}\"", check.schema, check.table_name);
+        let key = qualified_name(&check.schema, &check.table_name);
         if let Some(table) = tables.get_mut(&key) {
             table.check_constraints.push(CheckConstraintInfo {
                 name: check.name,
                 expression: check.expression,
                 columns: parse_pg_array(&check.columns),
+                comment: check.comment,
+            });
+        }
+    }
+
+    // Populate exclusion constraints
+    for excl in exclusion_data {
+        let key = qualified_name(&excl.schema, &excl.table_name);
+        if let Some(table) = tables.get_mut(&key) {
+            table.exclusion_constraints.push(ExclusionConstraintInfo {
+                name: excl.name,
+                definition: excl.definition,
             });
         }
     }
 
     // Populate table comments
     for comment in comment_data {
-        let key = format!("\"{}\".\"{}\"", comment.schema, comment.table_name);
+        let key = qualified_name(&comment.schema, &comment.table_name);
         if let Some(table) = tables.get_mut(&key) {
             table.comment = comment.comment;
         }
@@ -1,18 +1,20 @@
 //! Database type introspection: enums, composite types, and domains.
 
 use crate::schema::{
-    CompositeTypeAttribute, CompositeTypeInfo, DomainCheckConstraint, DomainInfo, EnumInfo,
+    qualified_name, CompositeTypeAttribute, CompositeTypeInfo, DomainCheckConstraint, DomainInfo,
+    EnumInfo,
 };
-use crate::supabase_api::SupabaseApi;
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use super::helpers::parse_pg_array;
+use super::helpers::{parse_pg_array, scope_query_to_schemas};
 
 /// Fetch enum types from the database.
 pub async fn get_enums(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, EnumInfo>, String> {
     let query = r#"
         SELECT
@@ -41,7 +43,7 @@ pub async fn get_enums(
     }
 
     let result = api
-        .run_query(project_ref, query, true)
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -52,7 +54,7 @@ pub async fn get_enums(
     let mut enums = HashMap::new();
     for row in rows {
         let values = parse_pg_array(&row.values);
-        let key = format!("\"{}\".\"{}\"", row.schema, row.name);
+        let key = qualified_name(&row.schema, &row.name);
         enums.insert(
             key,
             EnumInfo {
@@ -69,8 +71,9 @@ pub async fn get_enums(
 
 /// Fetch composite types from the database.
 pub async fn get_composite_types(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, CompositeTypeInfo>, String> {
     let query = r#"
         SELECT
@@ -111,7 +114,7 @@ pub async fn get_composite_types(
     }
 
     let result = api
-        .run_query(project_ref, query, true)
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -138,7 +141,7 @@ pub async fn get_composite_types(
             vec![]
         };
 
-        let key = format!("\"{}\".\"{}\"", row.schema, row.name);
+        let key = qualified_name(&row.schema, &row.name);
         types.insert(
             key,
             CompositeTypeInfo {
@@ -156,8 +159,9 @@ pub async fn get_composite_types(
 
 /// Fetch domain types from the database.
 pub async fn get_domains(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, DomainInfo>, String> {
     let query = r#"
         SELECT
@@ -203,7 +207,7 @@ pub async fn get_domains(
     }
 
     let result = api
-        .run_query(project_ref, query, true)
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -226,7 +230,7 @@ pub async fn get_domains(
             })
             .collect();
 
-        let key = format!("\"{}\".\"{}\"", row.schema, row.name);
+        let key = qualified_name(&row.schema, &row.name);
         domains.insert(
             key,
             DomainInfo {
@@ -1,16 +1,17 @@
 //! View introspection (including materialized views).
 
-use crate::schema::{IndexInfo, ViewColumnInfo, ViewInfo};
-use crate::supabase_api::SupabaseApi;
+use crate::schema::{qualified_name, IndexColumn, IndexInfo, RuleInfo, ViewColumnInfo, ViewInfo};
+use crate::supabase_api::ManagementApi;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use super::helpers::parse_pg_array;
+use super::helpers::{parse_pg_array, scope_query_to_schemas};
 
 /// Fetch all views (regular and materialized) from the database.
 pub async fn get_views(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
+    schemas: Option<&[String]>,
 ) -> Result<HashMap<String, ViewInfo>, String> {
     let query = r#"
         WITH view_data AS (
@@ -104,7 +105,7 @@ pub async fn get_views(
     "#;
 
     let result = api
-        .run_query(project_ref, query, true)
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
         .await
         .map_err(|e| format!("Views query failed: {}", e))?;
 
@@ -175,8 +176,18 @@ fn parse_views_response(data: &serde_json::Value) -> Result<HashMap<String, View
     let mut views: HashMap<String, ViewInfo> = HashMap::new();
 
     for row in view_rows {
-        let options = row.options.map(|v| parse_pg_array(&v)).unwrap_or_default();
-        let key = format!("\"{}\".\"{}\"", row.schema, row.name);
+        // `WITH ... CHECK OPTION` is stored as the `check_option` reloption
+        let (check_options, options): (Vec<String>, Vec<String>) = row
+            .options
+            .map(|v| parse_pg_array(&v))
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|o| o.to_lowercase().starts_with("check_option="));
+        let check_option = check_options
+            .first()
+            .and_then(|o| o.split_once('='))
+            .map(|(_, level)| level.trim_matches('\'').to_uppercase());
+        let key = qualified_name(&row.schema, &row.name);
 
         views.insert(
             key,
@@ -186,10 +197,11 @@ fn parse_views_response(data: &serde_json::Value) -> Result<HashMap<String, View
                 definition: row.definition.unwrap_or_default(),
                 is_materialized: row.is_materialized,
                 columns: vec![],
+                column_aliases: vec![],
                 indexes: vec![],
                 comment: row.comment,
                 with_options: options,
-                check_option: None,
+                check_option,
                 grants: vec![],
                 extension: row.extension,
             },
@@ -198,7 +210,7 @@ fn parse_views_response(data: &serde_json::Value) -> Result<HashMap<String, View
 
     // Add columns to views
     for col in column_rows {
-        let key = format!("\"{}\".\"{}\"", col.schema, col.view_name);
+        let key = qualified_name(&col.schema, &col.view_name);
         if let Some(view) = views.get_mut(&key) {
             view.columns.push(ViewColumnInfo {
                 name: col.column_name,
@@ -210,20 +222,86 @@ fn parse_views_response(data: &serde_json::Value) -> Result<HashMap<String, View
 
     // Add indexes to materialized views
     for idx in index_rows {
-        let key = format!("\"{}\".\"{}\"", idx.schema, idx.view_name);
+        let key = qualified_name(&idx.schema, &idx.view_name);
         if let Some(view) = views.get_mut(&key) {
             view.indexes.push(IndexInfo {
                 index_name: idx.index_name,
-                columns: parse_pg_array(&idx.columns),
+                columns: parse_pg_array(&idx.columns).into_iter().map(IndexColumn::new).collect(),
                 is_unique: idx.is_unique,
                 is_primary: false,
                 owning_constraint: None,
                 index_method: idx.index_method,
                 where_clause: idx.where_clause,
                 expressions: vec![],
+                include_columns: vec![],
+                nulls_not_distinct: false,
+                comment: None,
             });
         }
     }
 
     Ok(views)
 }
+
+/// Fetch rules on tables and views, skipping the `_RETURN` rule that implements every view.
+pub async fn get_rules(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    schemas: Option<&[String]>,
+) -> Result<HashMap<String, RuleInfo>, String> {
+    let query = r#"
+        SELECT
+            n.nspname as schema,
+            c.relname as relation,
+            r.rulename as name,
+            pg_get_ruledef(r.oid) as definition
+        FROM pg_rewrite r
+        JOIN pg_class c ON c.oid = r.ev_class
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE r.rulename <> '_RETURN'
+          AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+          AND n.nspname NOT LIKE 'pg_temp%'
+          AND n.nspname NOT IN ('auth', 'storage', 'extensions', 'realtime', 'graphql', 'graphql_public', 'vault', 'pgsodium', 'pgsodium_masks', 'supa_audit', 'net', 'pgtle', 'repack', 'tiger', 'topology', 'supabase_migrations', 'supabase_functions', 'cron', 'pgbouncer')
+    "#;
+
+    #[derive(Deserialize)]
+    struct RuleRow {
+        schema: String,
+        relation: String,
+        name: String,
+        definition: String,
+    }
+
+    let result = api
+        .run_query(project_ref, &scope_query_to_schemas(query, schemas), true)
+        .await
+        .map_err(|e| format!("Rules query failed: {}", e))?;
+
+    let rows: Vec<RuleRow> =
+        serde_json::from_value(result.result.unwrap_or(serde_json::Value::Array(vec![])))
+            .map_err(|e| format!("Failed to parse rules: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let rule = RuleInfo {
+                schema: row.schema,
+                relation: row.relation,
+                name: row.name,
+                definition: rule_body(&row.definition),
+            };
+            (rule.key(), rule)
+        })
+        .collect())
+}
+
+/// `CREATE RULE name AS ON ... ;` from `pg_get_ruledef` -> `ON ...`
+fn rule_body(ruledef: &str) -> String {
+    let collapsed = ruledef.split_whitespace().collect::<Vec<_>>().join(" ");
+    let body = match collapsed.find(" AS ON ") {
+        Some(pos) => &collapsed[pos + 4..],
+        None => collapsed.as_str(),
+    };
+    body.trim_end_matches(';').trim().to_string()
+}
@@ -0,0 +1,177 @@
+//! On-disk log history: `LogEntry`s appended as JSON lines to a file that is
+//! rotated once it grows past a size limit, so history survives restarts.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::models::LogEntry;
+
+const FILE_PREFIX: &str = "harbor";
+const FILE_EXTENSION: &str = "jsonl";
+const DEFAULT_MAX_BYTES: u64 = 2 * 1024 * 1024;
+/// Rotated files kept besides the active one
+const DEFAULT_MAX_ROTATED: usize = 3;
+
+pub struct LogFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_rotated: usize,
+}
+
+impl LogFile {
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_limits(dir, DEFAULT_MAX_BYTES, DEFAULT_MAX_ROTATED)
+    }
+
+    pub fn with_limits(dir: PathBuf, max_bytes: u64, max_rotated: usize) -> Self {
+        Self { dir, max_bytes, max_rotated }
+    }
+
+    /// `harbor.jsonl` is the active file, `harbor.1.jsonl` the most recently rotated one
+    fn path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(format!("{}.{}", FILE_PREFIX, FILE_EXTENSION))
+        } else {
+            self.dir.join(format!("{}.{}.{}", FILE_PREFIX, index, FILE_EXTENSION))
+        }
+    }
+
+    pub fn append(&self, entry: &LogEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let current_len = fs::metadata(self.path(0)).map(|m| m.len()).unwrap_or(0);
+        if current_len > 0 && current_len + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(0))
+            .map_err(|e| e.to_string())?;
+        file.write_all(line.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    /// Shift every file up one index, dropping the oldest
+    fn rotate(&self) -> Result<(), String> {
+        let oldest = self.path(self.max_rotated);
+        if oldest.exists() {
+            fs::remove_file(&oldest).map_err(|e| e.to_string())?;
+        }
+        for index in (0..self.max_rotated).rev() {
+            let from = self.path(index);
+            if from.exists() {
+                fs::rename(&from, self.path(index + 1)).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// All persisted entries, oldest first. Unreadable lines are skipped.
+    pub fn load_all(&self) -> Vec<LogEntry> {
+        (0..=self.max_rotated)
+            .rev()
+            .flat_map(|index| read_entries(&self.path(index)))
+            .collect()
+    }
+
+    /// The newest `limit` entries, oldest first
+    pub fn load_recent(&self, limit: usize) -> Vec<LogEntry> {
+        let mut entries = self.load_all();
+        let skip = entries.len().saturating_sub(limit);
+        entries.drain(..skip);
+        entries
+    }
+
+    /// Rewrite the history keeping only entries matching `keep`
+    pub fn retain(&self, keep: impl Fn(&LogEntry) -> bool) -> Result<(), String> {
+        let entries: Vec<LogEntry> = self.load_all().into_iter().filter(|e| keep(e)).collect();
+        for index in 0..=self.max_rotated {
+            let path = self.path(index);
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| e.to_string())?;
+            }
+        }
+        entries.iter().try_for_each(|entry| self.append(entry))
+    }
+}
+
+fn read_entries(path: &Path) -> Vec<LogEntry> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Write entries to `path` as JSON lines
+pub fn export_entries(entries: &[LogEntry], path: &Path) -> Result<(), String> {
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    fs::write(path, out).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LogSource;
+    use uuid::Uuid;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("harbor_test_logs_{}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_append_rotates_past_size_threshold() {
+        let dir = temp_dir();
+        let log_file = LogFile::with_limits(dir.clone(), 300, 2);
+
+        for i in 0..10 {
+            log_file
+                .append(&LogEntry::info(None, LogSource::System, format!("message {}", i)))
+                .unwrap();
+        }
+
+        assert!(log_file.path(1).exists(), "writing past the limit creates a rotated file");
+        assert!(log_file.path(2).exists());
+        assert!(!log_file.path(3).exists(), "only max_rotated files are kept");
+        assert!(fs::metadata(log_file.path(0)).unwrap().len() <= 300);
+
+        // Entries are read back oldest first and end with the newest
+        let loaded = log_file.load_all();
+        assert!(loaded.len() < 10);
+        assert_eq!(loaded.last().unwrap().message, "message 9");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_recent_restores_history_in_order() {
+        let dir = temp_dir();
+        let project_id = Uuid::new_v4();
+        let log_file = LogFile::new(dir.clone());
+        for i in 0..5 {
+            log_file
+                .append(&LogEntry::info(Some(project_id), LogSource::Schema, format!("step {}", i)))
+                .unwrap();
+        }
+
+        // A new instance over the same directory sees the previous session's logs
+        let reopened = LogFile::new(dir.clone());
+        let recent: Vec<String> = reopened.load_recent(3).into_iter().map(|e| e.message).collect();
+        assert_eq!(recent, vec!["step 2", "step 3", "step 4"]);
+
+        reopened.retain(|e| e.message != "step 3").unwrap();
+        assert_eq!(reopened.load_all().len(), 4);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,193 @@
+//! Live tailing of Supabase logs. The analytics endpoint has no push API, so
+//! each stream polls a short trailing window and emits only entries that
+//! weren't in the previous window as `log-stream` events.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use crate::supabase_api::{ApiError, LogQueryFilter, ManagementApi, SupabaseApi};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// Each poll looks this far back; consecutive windows overlap, hence the dedup
+const WINDOW_MINUTES: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteLogSource {
+    Edge,
+    Postgres,
+    Auth,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogFilters {
+    /// Only used for edge function logs
+    pub function_name: Option<String>,
+    #[serde(flatten)]
+    pub query: LogQueryFilter,
+}
+
+/// Payload of `log-stream` events
+#[derive(Clone, Serialize)]
+struct LogStreamEvent {
+    project_id: Uuid,
+    source: RemoteLogSource,
+    entries: Vec<serde_json::Value>,
+}
+
+/// Drops entries already returned by the previous poll window
+#[derive(Default)]
+pub struct LogDedup {
+    previous: HashSet<String>,
+}
+
+impl LogDedup {
+    pub fn new_entries(&mut self, window: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+        let keys: HashSet<String> = window.iter().map(entry_key).collect();
+        let fresh = window
+            .into_iter()
+            .filter(|entry| !self.previous.contains(&entry_key(entry)))
+            .collect();
+        self.previous = keys;
+        fresh
+    }
+}
+
+/// Log rows carry an `id`; fall back to timestamp + message for sources that don't
+fn entry_key(entry: &serde_json::Value) -> String {
+    match entry.get("id") {
+        Some(serde_json::Value::String(id)) => id.clone(),
+        Some(id) if !id.is_null() => id.to_string(),
+        _ => format!(
+            "{}|{}",
+            entry.get("timestamp").map(|t| t.to_string()).unwrap_or_default(),
+            entry.get("event_message").map(|m| m.to_string()).unwrap_or_default()
+        ),
+    }
+}
+
+/// Running streams, at most one per project and source
+#[derive(Default)]
+pub struct LogStreams {
+    streams: RwLock<HashMap<(Uuid, RemoteLogSource), JoinHandle<()>>>,
+}
+
+impl LogStreams {
+    /// Start streaming, replacing any stream already running for the same source
+    pub async fn start(
+        &self,
+        app_handle: AppHandle,
+        project_id: Uuid,
+        project_ref: String,
+        api: SupabaseApi,
+        source: RemoteLogSource,
+        filters: LogFilters,
+    ) {
+        let handle = tauri::async_runtime::spawn(poll(app_handle, project_id, project_ref, api, source, filters));
+        if let Some(previous) = self.streams.write().await.insert((project_id, source), handle) {
+            previous.abort();
+        }
+    }
+
+    /// Stop one source, or every stream of the project when `source` is None
+    pub async fn stop(&self, project_id: Uuid, source: Option<RemoteLogSource>) {
+        self.streams.write().await.retain(|(id, s), handle| {
+            let matches = *id == project_id && (source.is_none() || source == Some(*s));
+            if matches {
+                handle.abort();
+            }
+            !matches
+        });
+    }
+}
+
+async fn fetch_window(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    source: RemoteLogSource,
+    filters: &LogFilters,
+) -> Result<Vec<serde_json::Value>, ApiError> {
+    let result = match source {
+        RemoteLogSource::Edge => {
+            api.get_edge_function_logs(project_ref, filters.function_name.as_deref(), WINDOW_MINUTES, &filters.query)
+                .await?
+        }
+        RemoteLogSource::Postgres => api.get_postgres_logs(project_ref, WINDOW_MINUTES, &filters.query).await?,
+        RemoteLogSource::Auth => api.get_auth_logs(project_ref, WINDOW_MINUTES, &filters.query).await?,
+    };
+    Ok(result.as_array().cloned().unwrap_or_default())
+}
+
+async fn poll(
+    app_handle: AppHandle,
+    project_id: Uuid,
+    project_ref: String,
+    api: SupabaseApi,
+    source: RemoteLogSource,
+    filters: LogFilters,
+) {
+    let mut dedup = LogDedup::default();
+
+    loop {
+        match fetch_window(&api, &project_ref, source, &filters).await {
+            Ok(window) => {
+                let entries = dedup.new_entries(window);
+                if !entries.is_empty() {
+                    app_handle
+                        .emit("log-stream", LogStreamEvent { project_id, source, entries })
+                        .ok();
+                }
+            }
+            Err(e) if e.is_auth_expired() => {
+                // Polling can't recover until the user signs in again
+                let state = app_handle.state::<Arc<AppState>>();
+                state.mark_token_expired().await;
+                break;
+            }
+            Err(ApiError::InvalidLogFilter(message)) => {
+                eprintln!("[LOGS] Stopping log stream: invalid filter: {}", message);
+                break;
+            }
+            Err(e) => eprintln!("[LOGS] Log stream poll failed: {}", e),
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_drops_entries_from_previous_window() {
+        let mut dedup = LogDedup::default();
+
+        let first = dedup.new_entries(vec![
+            serde_json::json!({"id": "a", "timestamp": 1, "event_message": "one"}),
+            serde_json::json!({"id": "b", "timestamp": 2, "event_message": "two"}),
+        ]);
+        assert_eq!(first.len(), 2);
+
+        // The next window still contains "b"
+        let second = dedup.new_entries(vec![
+            serde_json::json!({"id": "b", "timestamp": 2, "event_message": "two"}),
+            serde_json::json!({"id": "c", "timestamp": 3, "event_message": "three"}),
+        ]);
+        assert_eq!(second, vec![serde_json::json!({"id": "c", "timestamp": 3, "event_message": "three"})]);
+
+        // Entries without an id are keyed by timestamp and message
+        let third = dedup.new_entries(vec![serde_json::json!({"timestamp": 4, "event_message": "four"})]);
+        assert_eq!(third.len(), 1);
+        let fourth = dedup.new_entries(vec![serde_json::json!({"timestamp": 4, "event_message": "four"})]);
+        assert!(fourth.is_empty());
+    }
+}
@@ -1,13 +1,18 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod ai;
 mod commands;
 mod defaults;
 mod diff;
+mod file_sink;
 mod fns;
 mod generator;
 mod introspection;
+mod log_file;
+mod log_stream;
 mod models;
+mod query;
 mod parsing;
 mod schema;
 mod state;
@@ -60,26 +65,42 @@ fn main() {
             commands::set_openai_key,
             commands::has_openai_key,
             commands::clear_openai_key,
+            commands::validate_openai_key,
+            // Account commands
+            commands::add_account,
+            commands::list_accounts,
+            commands::set_active_account,
+            commands::remove_account,
+            commands::set_project_account,
             // Remote project commands
             commands::list_remote_projects,
             commands::list_organizations,
             commands::pull_project,
             commands::get_pull_diff,
             commands::push_project,
+            commands::push_all_projects,
+            commands::pull_all_projects,
             commands::get_project_diff,
             // Project commands
             commands::create_project,
+            commands::check_project_folder,
+            commands::resume_pull,
+            commands::project_status,
             commands::get_projects,
             commands::get_project,
             commands::update_project,
             commands::delete_project,
             commands::link_supabase_project,
+            commands::unlink_supabase_project,
+            commands::list_regions,
+            commands::get_project_db_password,
             commands::get_project_keys,
             commands::reveal_in_finder,
             // Template commands
             commands::templates::is_folder_empty,
             commands::templates::get_templates,
             commands::templates::copy_template,
+            commands::templates::create_edge_function,
             // Watcher commands
             commands::start_watching,
             commands::stop_watching,
@@ -87,27 +108,53 @@ fn main() {
             // Log commands
             commands::get_logs,
             commands::clear_logs,
+            commands::export_logs,
             // Admin config commands
             commands::has_admin_config,
             commands::get_sidebar_spec,
             commands::write_sidebar_spec,
             commands::add_sidebar_item,
             commands::add_sidebar_group,
+            commands::validate_sidebar_spec,
+            commands::remove_sidebar_item,
+            commands::remove_sidebar_group,
+            commands::reorder_sidebar,
             // Supabase API commands
             commands::run_query,
+            commands::explain_query,
+            commands::export_query_result,
+            commands::save_query,
+            commands::list_saved_queries,
+            commands::delete_saved_query,
+            commands::run_saved_query,
+            commands::apply_sql,
             commands::deploy_edge_function,
+            commands::function_status,
             commands::get_remote_schema,
+            commands::export_schema_json,
+            commands::introspect_remote,
+            commands::find_dependents,
+            commands::diff_projects,
+            commands::diff_schema_files,
             commands::run_seeds,
             commands::get_seed_content,
+            commands::list_migrations,
+            commands::get_migration_content,
+            commands::apply_migration,
             // Supabase Logs API commands
             commands::query_supabase_logs,
             commands::get_edge_function_logs,
             commands::get_postgres_logs,
             commands::get_auth_logs,
+            commands::stream_logs,
+            commands::stop_log_stream,
             // SQL validation and AI commands
             commands::validate_sql,
             commands::convert_with_ai,
+            commands::cancel_ai_convert,
             commands::split_schema,
+            commands::analyze_schema_file,
+            commands::preview_object_sql,
         ])
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
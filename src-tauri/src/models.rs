@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,12 +20,103 @@ pub struct Project {
     /// Whether to generate TypeScript types on schema changes
     #[serde(default = "default_generate_typescript")]
     pub generate_typescript: bool,
+    /// Schemas to introspect and diff. When unset, every non-system schema is included.
+    #[serde(default)]
+    pub watched_schemas: Option<Vec<String>>,
+    /// Glob patterns of tables managed by Supawatch. Empty means all tables.
+    #[serde(default)]
+    pub table_allowlist: Vec<String>,
+    /// Glob patterns of tables owned by another tool; never created, altered or dropped.
+    #[serde(default)]
+    pub table_denylist: Vec<String>,
+    /// Build indexes on existing tables with CREATE INDEX CONCURRENTLY (outside the migration transaction)
+    #[serde(default)]
+    pub concurrent_indexes: bool,
+    /// How push treats objects that exist remotely but not locally
+    #[serde(default)]
+    pub push_mode: PushMode,
+    /// Named account whose token is used for this project; the active account when unset
+    #[serde(default)]
+    pub account: Option<String>,
+    /// Outcome of the auto-pull run when the project was linked; `resume_pull` retries failed parts
+    #[serde(default)]
+    pub last_pull_status: Option<PullStatus>,
+    #[serde(default)]
+    pub last_pull_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_push_at: Option<DateTime<Utc>>,
+    /// Whether pull/push also sync edge functions; callers can override it per run
+    #[serde(default = "default_sync_functions")]
+    pub sync_functions: bool,
+    /// How generated migrations qualify object names
+    #[serde(default)]
+    pub name_qualification: crate::generator::NameQualification,
+    /// Push additively and refuse destructive changes, even when forced. On for new and
+    /// newly linked projects until the user opts in to drops.
+    #[serde(default)]
+    pub safe_mode: bool,
+    /// Roles whose remote grants are treated as implicit: they only count in grant diffs
+    /// when the local schema grants to the role too
+    #[serde(default = "default_ignored_grant_roles")]
+    pub ignored_grant_roles: Vec<String>,
+    /// Seconds a single introspection query may run before it's reported as stuck;
+    /// `introspection::DEFAULT_QUERY_TIMEOUT` when unset
+    #[serde(default)]
+    pub introspection_query_timeout_secs: Option<u64>,
+}
+
+/// At-a-glance sync state of a project, returned by `project_status`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectStatus {
+    pub linked: bool,
+    pub watching: bool,
+    /// Whether the remote schema changed since the last pull/push; None when unknown
+    pub drifted: Option<bool>,
+    pub last_pull: Option<DateTime<Utc>>,
+    pub last_push: Option<DateTime<Utc>>,
+    /// Local edge functions changed since they were last deployed
+    pub pending_function_changes: usize,
+}
+
+/// Which parts of a pull succeeded
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PullStatus {
+    pub schema_pulled: bool,
+    pub functions_pulled: bool,
+    /// Errors of the parts that failed on the last attempt
+    #[serde(default)]
+    pub errors: Vec<String>,
+    pub attempted_at: Option<DateTime<Utc>>,
+}
+
+impl PullStatus {
+    pub fn is_complete(&self) -> bool {
+        self.schema_pulled && self.functions_pulled
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PushMode {
+    /// Make the remote match local exactly, dropping remote-only objects
+    #[default]
+    Replace,
+    /// Only apply local additions and modifications; never drop remote-only objects
+    Merge,
 }
 
 fn default_generate_typescript() -> bool {
     true
 }
 
+fn default_sync_functions() -> bool {
+    true
+}
+
+fn default_ignored_grant_roles() -> Vec<String> {
+    crate::diff::default_ignored_grant_roles()
+}
+
 impl Project {
     pub fn new(name: String, local_path: String) -> Self {
         let now = Utc::now();
@@ -39,6 +131,20 @@ impl Project {
             is_watching: false,
             typescript_output_path: None,
             generate_typescript: true,
+            watched_schemas: None,
+            table_allowlist: Vec::new(),
+            table_denylist: Vec::new(),
+            concurrent_indexes: false,
+            push_mode: PushMode::Replace,
+            account: None,
+            last_pull_status: None,
+            last_pull_at: None,
+            last_push_at: None,
+            sync_functions: true,
+            name_qualification: crate::generator::NameQualification::AsIs,
+            safe_mode: true,
+            ignored_grant_roles: default_ignored_grant_roles(),
+            introspection_query_timeout_secs: None,
         }
     }
 
@@ -53,6 +159,37 @@ impl Project {
         project.supabase_project_ref = Some(project_ref);
         project
     }
+
+    pub fn introspection_query_timeout(&self) -> std::time::Duration {
+        self.introspection_query_timeout_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(crate::introspection::DEFAULT_QUERY_TIMEOUT)
+    }
+
+    /// Diff options derived from the project's sync settings
+    pub fn diff_options(&self) -> crate::diff::DiffOptions {
+        crate::diff::DiffOptions {
+            watched_schemas: self.watched_schemas.clone(),
+            table_allowlist: self.table_allowlist.clone(),
+            table_denylist: self.table_denylist.clone(),
+            merge: self.push_mode == PushMode::Merge || self.safe_mode,
+            accepted_renames: vec![],
+            ignored_grant_roles: Some(self.ignored_grant_roles.clone()),
+        }
+    }
+
+    /// Whether a pull/push syncs edge functions, `requested` overriding the project default
+    pub fn sync_functions_enabled(&self, requested: Option<bool>) -> bool {
+        requested.unwrap_or(self.sync_functions)
+    }
+
+    /// Migration generation options derived from the project's settings
+    pub fn generate_options(&self) -> crate::generator::GenerateOptions {
+        crate::generator::GenerateOptions {
+            concurrent_indexes: self.concurrent_indexes,
+            qualification: self.name_qualification,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -82,6 +219,22 @@ pub struct LogEntry {
     pub message: String,
     pub details: Option<String>,
     pub timestamp: DateTime<Utc>,
+    /// Step of a push/pull this entry belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<LogPhase>,
+    /// Structured diff for the UI to render instead of raw SQL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<crate::sync::DiffReport>,
+}
+
+/// Steps of a schema sync, in the order they run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogPhase {
+    Introspect,
+    Diff,
+    Generate,
+    Execute,
 }
 
 impl LogEntry {
@@ -99,6 +252,8 @@ impl LogEntry {
             message,
             details: None,
             timestamp: Utc::now(),
+            phase: None,
+            report: None,
         }
     }
 
@@ -107,6 +262,16 @@ impl LogEntry {
         self
     }
 
+    pub fn with_phase(mut self, phase: LogPhase) -> Self {
+        self.phase = Some(phase);
+        self
+    }
+
+    pub fn with_report(mut self, report: crate::sync::DiffReport) -> Self {
+        self.report = Some(report);
+        self
+    }
+
     pub fn info(project_id: Option<Uuid>, source: LogSource, message: String) -> Self {
         Self::new(project_id, LogLevel::Info, source, message)
     }
@@ -160,6 +325,29 @@ pub struct AppData {
     #[serde(skip)]
     pub access_token: Option<String>,
     pub version: String,
+    /// Saved SQL editor queries, keyed by project
+    #[serde(default)]
+    pub saved_queries: HashMap<Uuid, Vec<SavedQuery>>,
+    /// Write app logs to a rotating file under the data dir so they survive restarts
+    #[serde(default = "default_persist_logs")]
+    pub persist_logs: bool,
+    /// Names of the stored Supabase accounts; tokens live in the system keyring
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Account used by default; the single legacy access token when unset
+    #[serde(default)]
+    pub active_account: Option<String>,
+}
+
+/// A stored Supabase account as shown in the UI (never includes the token)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccountInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+fn default_persist_logs() -> bool {
+    true
 }
 
 impl Default for AppData {
@@ -168,6 +356,30 @@ impl Default for AppData {
             projects: Vec::new(),
             access_token: None,
             version: "1.0.0".to_string(),
+            saved_queries: HashMap::new(),
+            persist_logs: true,
+            accounts: Vec::new(),
+            active_account: None,
+        }
+    }
+}
+
+/// A named query kept with a project for re-running later
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedQuery {
+    pub id: Uuid,
+    pub name: String,
+    pub sql: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SavedQuery {
+    pub fn new(name: String, sql: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            sql,
+            created_at: Utc::now(),
         }
     }
 }
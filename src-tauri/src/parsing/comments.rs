@@ -0,0 +1,105 @@
+use crate::diff::CommentTarget;
+use crate::schema::TableInfo;
+use regex::Regex;
+use std::collections::HashMap;
+
+use super::exclusions::{strip_leading_comments, table_key, unquote};
+use super::statements::split_statements;
+
+const OBJECT_NAME: &str = r#"(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?"#;
+
+/// `COMMENT ON INDEX|CONSTRAINT|POLICY` statement, resolved to its table after parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectComment {
+    pub target: CommentTarget,
+    /// Index name (possibly schema-qualified) or constraint/policy name
+    pub name: String,
+    /// `"schema"."table"` for constraints and policies
+    pub table_key: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// sqlparser doesn't understand comments on indexes, constraints and policies, so they
+/// are extracted before parsing and removed from the SQL. Domain constraints are left alone.
+pub fn extract_object_comments(sql: &str) -> (String, Vec<ObjectComment>) {
+    let re = Regex::new(&format!(
+        r#"(?is)^COMMENT\s+ON\s+(?P<kind>INDEX|CONSTRAINT|POLICY)\s+(?P<name>{0})(?:\s+ON\s+(?P<table>{0}))?\s+IS\s+(?P<value>NULL|'(?:[^']|'')*')$"#,
+        OBJECT_NAME
+    ))
+    .unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+        let Some(caps) = re.captures(body) else {
+            continue;
+        };
+        let target = match caps["kind"].to_uppercase().as_str() {
+            "INDEX" => CommentTarget::Index,
+            "CONSTRAINT" => CommentTarget::Constraint,
+            _ => CommentTarget::Policy,
+        };
+        // Constraints and policies must name their table
+        let table = caps.name("table").map(|t| table_key(t.as_str()));
+        if target != CommentTarget::Index && table.is_none() {
+            continue;
+        }
+
+        let value = &caps["value"];
+        let comment = if value.eq_ignore_ascii_case("NULL") {
+            None
+        } else {
+            Some(value[1..value.len() - 1].replace("''", "'"))
+        };
+
+        found.push(ObjectComment {
+            target,
+            name: caps["name"].to_string(),
+            table_key: table,
+            comment,
+        });
+        cleaned = cleaned.replacen(body, "", 1);
+    }
+
+    (cleaned, found)
+}
+
+/// Attach extracted comments to the indexes, check constraints and policies they name
+pub fn apply_object_comments(tables: &mut HashMap<String, TableInfo>, comments: Vec<ObjectComment>) {
+    for c in comments {
+        match c.target {
+            CommentTarget::Index => {
+                // Indexes live in their table's schema; unqualified names default to public
+                let parts: Vec<String> = c.name.split('.').map(unquote).collect();
+                let (schema, name) = match parts.as_slice() {
+                    [schema, name] => (schema.clone(), name.clone()),
+                    _ => ("public".to_string(), parts.join(".")),
+                };
+                let index = tables
+                    .values_mut()
+                    .filter(|t| t.schema == schema)
+                    .flat_map(|t| t.indexes.iter_mut())
+                    .find(|i| i.index_name == name);
+                if let Some(index) = index {
+                    index.comment = c.comment;
+                }
+            }
+            CommentTarget::Constraint => {
+                let name = unquote(&c.name);
+                let table = c.table_key.as_ref().and_then(|key| tables.get_mut(key));
+                if let Some(check) = table.and_then(|t| t.check_constraints.iter_mut().find(|k| k.name == name)) {
+                    check.comment = c.comment;
+                }
+            }
+            CommentTarget::Policy => {
+                let name = unquote(&c.name);
+                let table = c.table_key.as_ref().and_then(|key| tables.get_mut(key));
+                if let Some(policy) = table.and_then(|t| t.policies.iter_mut().find(|p| p.name == name)) {
+                    policy.comment = c.comment;
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::schema::{PolicyInfo, TableInfo, TriggerInfo};
+use crate::schema::{qualified_name, PolicyInfo, TableInfo, TriggerInfo};
 use sqlparser::ast::{CreatePolicyCommand, CreateTrigger, TriggerExecBody};
 use std::collections::HashMap;
 use super::helpers::{parse_object_name, strip_quotes};
@@ -20,7 +20,7 @@ pub fn handle_create_trigger(
 
     let t_name = strip_quotes(&name.to_string());
     let (t_schema, t_table) = parse_object_name(&table_name);
-    let table_key = format!("\"{}\".\"{}\"", t_schema, t_table);
+    let table_key = qualified_name(&t_schema, &t_table);
 
     let ev_strs: Vec<String> = events.iter().map(|e| {
         match e {
@@ -82,7 +82,7 @@ pub fn handle_create_policy(
 ) {
     let p_name = name.value;
     let (t_schema, t_table) = parse_object_name(&table_name);
-    let table_key = format!("\"{}\".\"{}\"", t_schema, t_table);
+    let table_key = qualified_name(&t_schema, &t_table);
 
     let cmd = match command {
         Some(CreatePolicyCommand::All) | None => "ALL",
@@ -109,6 +109,7 @@ pub fn handle_create_policy(
             roles: roles_vec,
             qual: q,
             with_check: wc,
+            comment: None,
         });
     }
 }
@@ -0,0 +1,108 @@
+use regex::Regex;
+
+use crate::schema::DefaultAcl;
+
+use super::exclusions::{strip_leading_comments, unquote};
+use super::statements::split_statements;
+
+/// Role that owns objects created by migrations when `FOR ROLE` is omitted
+const DEFAULT_ROLE: &str = "postgres";
+
+/// An `ALTER DEFAULT PRIVILEGES` statement, expanded to one entry per role, schema,
+/// grantee and privilege
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultAclStatement {
+    pub is_grant: bool,
+    pub acls: Vec<DefaultAcl>,
+}
+
+/// Extract `ALTER DEFAULT PRIVILEGES` statements, which sqlparser can't parse, and
+/// remove them from the SQL, mirroring `extract_table_storage`. Statements are returned
+/// in file order so a later REVOKE can cancel an earlier GRANT.
+pub fn extract_default_acls(sql: &str) -> (String, Vec<DefaultAclStatement>) {
+    let re = Regex::new(
+        r"(?is)^ALTER\s+DEFAULT\s+PRIVILEGES\s+(?:FOR\s+(?:ROLE|USER)\s+(?P<roles>.+?)\s+)?(?:IN\s+SCHEMA\s+(?P<schemas>.+?)\s+)?(?P<action>GRANT|REVOKE)\s+(?:GRANT\s+OPTION\s+FOR\s+)?(?P<privileges>.+?)\s+ON\s+(?P<object_type>TABLES|SEQUENCES|FUNCTIONS|ROUTINES|TYPES|SCHEMAS)\s+(?:TO|FROM)\s+(?P<grantees>.+?)(?:\s+WITH\s+GRANT\s+OPTION)?(?:\s+(?:CASCADE|RESTRICT))?\s*;?\s*$",
+    )
+    .unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+        let Some(caps) = re.captures(body) else {
+            continue;
+        };
+
+        let object_type = match caps["object_type"].to_lowercase().as_str() {
+            "routines" => "functions".to_string(),
+            other => other.to_string(),
+        };
+        let roles = caps
+            .name("roles")
+            .map(|m| split_names(m.as_str()))
+            .unwrap_or_else(|| vec![DEFAULT_ROLE.to_string()]);
+        let schemas: Vec<Option<String>> = match caps.name("schemas") {
+            Some(m) => split_names(m.as_str()).into_iter().map(Some).collect(),
+            None => vec![None],
+        };
+        let privileges = expand_privileges(&caps["privileges"], &object_type);
+        let grantees: Vec<String> = split_names(&caps["grantees"])
+            .into_iter()
+            .map(|g| g.trim_start_matches("GROUP ").trim().to_string())
+            .map(|g| if g.eq_ignore_ascii_case("public") { "public".to_string() } else { g })
+            .collect();
+
+        let mut acls = Vec::new();
+        for role in &roles {
+            for schema in &schemas {
+                for grantee in &grantees {
+                    for privilege in &privileges {
+                        acls.push(DefaultAcl {
+                            role: role.clone(),
+                            schema: schema.clone(),
+                            object_type: object_type.clone(),
+                            grantee: grantee.clone(),
+                            privilege: privilege.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        found.push(DefaultAclStatement {
+            is_grant: caps["action"].eq_ignore_ascii_case("GRANT"),
+            acls,
+        });
+        cleaned = cleaned.replacen(body, "", 1);
+    }
+
+    (cleaned, found)
+}
+
+/// Privileges `ALL` stands for on each kind of object
+fn all_privileges(object_type: &str) -> &'static [&'static str] {
+    match object_type {
+        "tables" => &["SELECT", "INSERT", "UPDATE", "DELETE", "TRUNCATE", "REFERENCES", "TRIGGER"],
+        "sequences" => &["USAGE", "SELECT", "UPDATE"],
+        "functions" => &["EXECUTE"],
+        "types" => &["USAGE"],
+        "schemas" => &["USAGE", "CREATE"],
+        _ => &[],
+    }
+}
+
+fn expand_privileges(list: &str, object_type: &str) -> Vec<String> {
+    let privileges: Vec<String> = list
+        .split(',')
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase())
+        .collect();
+    if privileges.iter().any(|p| p == "ALL" || p == "ALL PRIVILEGES") {
+        return all_privileges(object_type).iter().map(|p| p.to_string()).collect();
+    }
+    privileges
+}
+
+fn split_names(list: &str) -> Vec<String> {
+    list.split(',').map(unquote).filter(|name| !name.is_empty()).collect()
+}
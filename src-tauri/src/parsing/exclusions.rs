@@ -0,0 +1,239 @@
+use crate::schema::{qualified_name, ExclusionConstraintInfo};
+use regex::Regex;
+
+use super::statements::split_statements;
+
+const TABLE_NAME: &str = r#"(?P<table>(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?)"#;
+
+/// sqlparser doesn't understand `EXCLUDE` table constraints, so they are extracted
+/// before parsing and removed from the SQL.
+///
+/// Handles both inline `CONSTRAINT name EXCLUDE USING ...` elements of `CREATE TABLE`
+/// and `ALTER TABLE ... ADD CONSTRAINT name EXCLUDE USING ...` statements.
+/// Returns the cleaned SQL and `(table_key, constraint)` pairs.
+pub fn extract_exclusion_constraints(sql: &str) -> (String, Vec<(String, ExclusionConstraintInfo)>) {
+    let alter_re = Regex::new(&format!(
+        r#"(?is)^ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:ONLY\s+)?{}\s+ADD\s+CONSTRAINT\s+(?P<name>"[^"]+"|\w+)\s+(?P<def>EXCLUDE\b.*)$"#,
+        TABLE_NAME
+    ))
+    .unwrap();
+    let create_re = Regex::new(&format!(
+        r#"(?is)^CREATE\s+(?:UNLOGGED\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?{}\s*\("#,
+        TABLE_NAME
+    ))
+    .unwrap();
+    let element_re =
+        Regex::new(r#"(?is)^(?:CONSTRAINT\s+(?P<name>"[^"]+"|\w+)\s+)?(?P<def>EXCLUDE\b.*)$"#).unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        if !stmt.sql.to_uppercase().contains("EXCLUDE") {
+            continue;
+        }
+        let body = strip_leading_comments(&stmt.sql);
+
+        if let Some(caps) = alter_re.captures(body) {
+            found.push((
+                table_key(&caps["table"]),
+                ExclusionConstraintInfo {
+                    name: unquote(&caps["name"]),
+                    definition: caps["def"].trim().to_string(),
+                },
+            ));
+            cleaned = cleaned.replacen(body, "", 1);
+            continue;
+        }
+
+        let Some(caps) = create_re.captures(body) else {
+            continue;
+        };
+        let key = table_key(&caps["table"]);
+        let table_name = key.rsplit('.').next().unwrap_or_default().trim_matches('"').to_string();
+        let open = caps.get(0).unwrap().end();
+        let Some(close) = matching_paren(body, open) else {
+            continue;
+        };
+
+        let mut kept = Vec::new();
+        let mut removed = false;
+        for element in split_top_level(&body[open..close]) {
+            match element_re.captures(element.trim()) {
+                Some(el) => {
+                    let name = el
+                        .name("name")
+                        .map(|n| unquote(n.as_str()))
+                        .unwrap_or_else(|| default_exclusion_name(&table_name, &el["def"]));
+                    found.push((
+                        key.clone(),
+                        ExclusionConstraintInfo {
+                            name,
+                            definition: el["def"].trim().to_string(),
+                        },
+                    ));
+                    removed = true;
+                }
+                None => kept.push(element),
+            }
+        }
+
+        if removed {
+            let rewritten = format!("{}{}{}", &body[..open], kept.join(","), &body[close..]);
+            cleaned = cleaned.replacen(body, &rewritten, 1);
+        }
+    }
+
+    (cleaned, found)
+}
+
+/// Name Postgres gives an unnamed EXCLUDE constraint: `{table}_{columns}_excl`, where
+/// expression elements are called `expr`, repeated names get a number and the result
+/// is clipped to 63 bytes like `makeObjectName` does.
+fn default_exclusion_name(table: &str, definition: &str) -> String {
+    let mut columns: Vec<String> = Vec::new();
+    if let Some(open) = definition.find('(') {
+        let close = matching_paren(definition, open + 1).unwrap_or(definition.len());
+        for element in split_top_level(&definition[open + 1..close]) {
+            let base = exclusion_element_name(element.trim());
+            let mut name = base.clone();
+            let mut n = 1;
+            while columns.contains(&name) {
+                name = format!("{}{}", base, n);
+                n += 1;
+            }
+            columns.push(name);
+        }
+    }
+
+    let mut joined = String::new();
+    for column in &columns {
+        if !joined.is_empty() {
+            joined.push('_');
+        }
+        joined.push_str(column);
+        if joined.len() >= MAX_IDENTIFIER_LEN + 1 {
+            break;
+        }
+    }
+
+    let available = MAX_IDENTIFIER_LEN - "excl".len() - 1 - if joined.is_empty() { 0 } else { 1 };
+    let (mut table_len, mut joined_len) = (table.len(), joined.len());
+    while table_len + joined_len > available {
+        if table_len > joined_len {
+            table_len -= 1;
+        } else {
+            joined_len -= 1;
+        }
+    }
+    let table = clip(table, table_len);
+    let joined = clip(&joined, joined_len);
+    if joined.is_empty() {
+        format!("{}_excl", table)
+    } else {
+        format!("{}_{}_excl", table, joined)
+    }
+}
+
+/// Postgres' NAMEDATALEN - 1
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// Column name of an EXCLUDE element (`col [opclass] WITH op`), or `expr` for an expression
+fn exclusion_element_name(element: &str) -> String {
+    if let Some(rest) = element.strip_prefix('"') {
+        return rest.split('"').next().unwrap_or_default().to_string();
+    }
+    let end = element
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(element.len());
+    let ident = &element[..end];
+    if ident.is_empty() || element[end..].trim_start().starts_with('(') {
+        return "expr".to_string();
+    }
+    ident.to_lowercase()
+}
+
+/// The longest prefix of `s` that is at most `len` bytes and ends on a character boundary
+fn clip(s: &str, mut len: usize) -> &str {
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    &s[..len]
+}
+
+/// `sql` without the whitespace and `--`/`/* */` comments in front of it
+pub fn strip_leading_comments(sql: &str) -> &str {
+    let mut rest = sql.trim_start();
+    loop {
+        if rest.starts_with("--") {
+            rest = rest.find('\n').map(|i| rest[i + 1..].trim_start()).unwrap_or("");
+        } else if rest.starts_with("/*") {
+            rest = rest.find("*/").map(|i| rest[i + 2..].trim_start()).unwrap_or("");
+        } else {
+            return rest;
+        }
+    }
+}
+
+pub(super) fn unquote(ident: &str) -> String {
+    ident.trim().trim_matches('"').to_string()
+}
+
+/// `"schema"."table"` key for a possibly unqualified, possibly quoted table name
+pub(super) fn table_key(name: &str) -> String {
+    let parts: Vec<String> = name.split('.').map(unquote).collect();
+    match parts.as_slice() {
+        [schema, table] => qualified_name(schema, table),
+        _ => format!("\"public\".\"{}\"", parts.join(".")),
+    }
+}
+
+/// Index of the `)` closing the parenthesis opened just before `start`
+pub(super) fn matching_paren(sql: &str, start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut quote: Option<char> = None;
+    for (i, c) in sql[start..].char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(start + i);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Split a CREATE TABLE body on commas that are not nested in parentheses or quotes
+pub(super) fn split_top_level(body: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut last = 0;
+    for (i, c) in body.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(&body[last..i]);
+                    last = i + 1;
+                }
+                _ => {}
+            },
+        }
+    }
+    parts.push(&body[last..]);
+    parts
+}
@@ -51,7 +51,10 @@ pub fn handle_create_function(
     let lang = language.map(|l| l.value).unwrap_or("sql".to_string());
     let volatility = behavior.map(|b| b.to_string());
     let is_strict = called_on_null
-        .map(|c| c.to_string().contains("STRICT"))
+        .map(|c| {
+            let c = c.to_string();
+            c.contains("STRICT") || c.contains("RETURNS NULL ON NULL INPUT")
+        })
         .unwrap_or(false);
 
     let def = if let Some(CreateFunctionBody::AsBeforeOptions { body, .. }) = function_body {
@@ -1,4 +1,4 @@
-use sqlparser::ast::ObjectName;
+use sqlparser::ast::{ObjectName, ObjectNamePart};
 
 /// Strip surrounding double-quotes from an identifier.
 /// PostgreSQL identifiers can be quoted like "my_column" or bare like my_column.
@@ -7,19 +7,25 @@ pub fn strip_quotes(s: &str) -> String {
     s.trim_matches('"').to_string()
 }
 
+/// `(schema, name)` of a possibly qualified name, defaulting to `public`.
+/// Names are unescaped (`"we""ird"` becomes `we"ird`), matching introspection.
 pub fn parse_object_name(name: &ObjectName) -> (String, String) {
     if name.0.len() >= 2 {
-        (
-            name.0[0].to_string().trim_matches('"').to_string(),
-            name.0[1].to_string().trim_matches('"').to_string(),
-        )
-    } else if let Some(ident) = name.0.first() {
-        ("public".to_string(), ident.to_string().trim_matches('"').to_string())
+        (name_part_value(&name.0[0]), name_part_value(&name.0[1]))
+    } else if let Some(part) = name.0.first() {
+        ("public".to_string(), name_part_value(part))
     } else {
         ("public".to_string(), "unknown".to_string())
     }
 }
 
+fn name_part_value(part: &ObjectNamePart) -> String {
+    match part.as_ident() {
+        Some(ident) => ident.value.clone(),
+        None => strip_quotes(&part.to_string()),
+    }
+}
+
 pub fn format_check_expression(expr_str: String) -> String {
     let trimmed = expr_str.trim();
     let upper = trimmed.to_uppercase();
@@ -1,22 +1,50 @@
-use crate::schema::{DbSchema, FunctionGrant, FunctionInfo, ObjectGrant, SchemaGrant, DefaultPrivilege, TableInfo, ViewInfo, SequenceInfo};
+use crate::schema::{qualified_name, DbSchema, FunctionGrant, FunctionInfo, ObjectGrant, SchemaGrant, DefaultPrivilege, TableInfo, ViewInfo, SequenceInfo};
 use sqlparser::dialect::PostgreSqlDialect;
 use sqlparser::parser::Parser;
-use sqlparser::ast::{Statement, Privileges, GrantObjects, Grantee, ObjectName};
+use sqlparser::ast::{Statement, Privileges, GrantObjects, Grantee, ObjectName, CommentObject};
 use std::collections::HashMap;
 
+mod comments;
 mod constraints;
+mod default_acls;
+mod exclusions;
 mod functions;
 mod helpers;
+mod partitions;
 mod roles;
+mod rules;
 mod sequences;
+mod statements;
+mod storage;
 mod tables;
 mod types;
+mod unsupported;
 mod views;
 
+pub use exclusions::strip_leading_comments;
+pub use helpers::parse_object_name;
+pub use statements::{split_statements, SqlStatement};
+pub use storage::parse_storage_params;
+
 #[cfg(test)]
 mod tests_composite;
 
+/// A statement `parse_schema_sql` skipped, so callers can tell users what isn't tracked
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct UnparsedStatement {
+    pub file: String,
+    pub text: String,
+    pub reason: String,
+}
+
 pub fn parse_schema_sql(files: &[(String, String)]) -> Result<DbSchema, String> {
+    parse_schema_sql_with_report(files).map(|(schema, _)| schema)
+}
+
+/// Parse like `parse_schema_sql`, also returning the statements that were skipped:
+/// objects sqlparser can't parse and statements that don't map to anything in `DbSchema`.
+pub fn parse_schema_sql_with_report(files: &[(String, String)]) -> Result<(DbSchema, Vec<UnparsedStatement>), String> {
+    let mut unparsed = Vec::new();
     let mut tables = HashMap::new();
     let mut enums = HashMap::new();
     let mut functions = HashMap::new();
@@ -28,6 +56,15 @@ pub fn parse_schema_sql(files: &[(String, String)]) -> Result<DbSchema, String>
     let mut domains = HashMap::new();
     let mut schema_grants = Vec::new();
     let mut default_privileges = Vec::new();
+    let mut default_acls: Vec<crate::schema::DefaultAcl> = Vec::new();
+    let mut exclusion_constraints = Vec::new();
+    let mut table_storage = Vec::new();
+    let mut not_null_domains = Vec::new();
+    let mut table_partitions = partitions::TablePartitions::default();
+    let mut object_comments = Vec::new();
+    let mut rules = HashMap::new();
+    let mut schema_comments = HashMap::new();
+    let mut view_check_options = Vec::new();
 
     let dialect = PostgreSqlDialect {};
 
@@ -36,6 +73,40 @@ pub fn parse_schema_sql(files: &[(String, String)]) -> Result<DbSchema, String>
         // sqlparser-rs doesn't support SECURITY DEFINER or SET clauses yet, so we manually extract them
         // and remove them from the SQL before parsing.
         let (cleaned_sql, func_options) = preprocess_function_options(sql_content);
+        // Same for EXCLUDE constraints; they're attached to their tables once all files are parsed
+        let (cleaned_sql, file_partitions) = partitions::extract_partitions(&cleaned_sql);
+        table_partitions.strategies.extend(file_partitions.strategies);
+        table_partitions.partitions.extend(file_partitions.partitions);
+        let (cleaned_sql, file_exclusions) = exclusions::extract_exclusion_constraints(&cleaned_sql);
+        exclusion_constraints.extend(file_exclusions);
+        let (cleaned_sql, file_storage) = storage::extract_table_storage(&cleaned_sql);
+        table_storage.extend(file_storage);
+        let (cleaned_sql, file_not_null_domains) = types::extract_domain_not_null(&cleaned_sql);
+        not_null_domains.extend(file_not_null_domains);
+        let (cleaned_sql, file_comments) = comments::extract_object_comments(&cleaned_sql);
+        object_comments.extend(file_comments);
+        let (cleaned_sql, file_default_acls) = default_acls::extract_default_acls(&cleaned_sql);
+        for stmt in file_default_acls {
+            if stmt.is_grant {
+                for acl in stmt.acls {
+                    if !default_acls.contains(&acl) {
+                        default_acls.push(acl);
+                    }
+                }
+            } else {
+                default_acls.retain(|acl| !stmt.acls.contains(acl));
+            }
+        }
+
+        let (cleaned_sql, file_check_options) = views::extract_check_options(&cleaned_sql);
+        view_check_options.extend(file_check_options);
+
+        let (cleaned_sql, file_rules) = rules::extract_rules(&cleaned_sql);
+        for rule in file_rules {
+            rules.insert(rule.key(), rule);
+        }
+        let (cleaned_sql, file_unsupported) = unsupported::extract_unsupported(&cleaned_sql, filename);
+        unparsed.extend(file_unsupported);
 
         let ast = Parser::parse_sql(&dialect, &cleaned_sql).map_err(|e| {
             // e is typically "Expected ..., found ... at line X, col Y"
@@ -132,6 +203,15 @@ pub fn parse_schema_sql(files: &[(String, String)]) -> Result<DbSchema, String>
                 Statement::CreateDomain(stmt) => {
                     types::handle_create_domain(&mut domains, stmt);
                 }
+                Statement::Comment {
+                    object_type: CommentObject::Schema,
+                    object_name,
+                    comment,
+                    ..
+                } => {
+                    let name = object_name.to_string().trim_matches('"').to_string();
+                    schema_comments.insert(name, comment);
+                }
                 Statement::Comment {
                     object_type,
                     object_name,
@@ -167,12 +247,59 @@ pub fn parse_schema_sql(files: &[(String, String)]) -> Result<DbSchema, String>
                         }
                     }
                 }
-                _ => {}
+                other if unsupported::is_harmless(&other.to_string()) => {}
+                other => {
+                    let text = other.to_string();
+                    unparsed.push(UnparsedStatement {
+                        file: filename.clone(),
+                        reason: format!("{} statements are not tracked", unsupported::statement_kind(&text)),
+                        text,
+                    });
+                }
             }
         }
     }
 
-    Ok(DbSchema {
+    for (table_key, constraint) in exclusion_constraints {
+        if let Some(table) = tables.get_mut(&table_key) {
+            table.exclusion_constraints.push(constraint);
+        }
+    }
+
+    for (table_key, storage) in table_storage {
+        if let Some(table) = tables.get_mut(&table_key) {
+            table.storage_params = storage.storage_params;
+            table.tablespace = storage.tablespace;
+            table.is_unlogged = storage.is_unlogged;
+        }
+    }
+
+    for (table_key, strategy) in table_partitions.strategies {
+        if let Some(table) = tables.get_mut(&table_key) {
+            table.partition_strategy = Some(strategy);
+        }
+    }
+    for (parent_key, partition) in table_partitions.partitions {
+        if let Some(table) = tables.get_mut(&parent_key) {
+            table.partitions.push(partition);
+        }
+    }
+
+    for domain_key in not_null_domains {
+        if let Some(domain) = domains.get_mut(&domain_key) {
+            domain.is_not_null = true;
+        }
+    }
+
+    for (view_key, level) in view_check_options {
+        if let Some(view) = views.get_mut(&view_key) {
+            view.check_option = Some(level);
+        }
+    }
+
+    comments::apply_object_comments(&mut tables, object_comments);
+
+    let schema = DbSchema {
         tables,
         enums,
         functions,
@@ -184,7 +311,11 @@ pub fn parse_schema_sql(files: &[(String, String)]) -> Result<DbSchema, String>
         domains,
         schema_grants,
         default_privileges,
-    })
+        default_acls,
+        rules,
+        schema_comments,
+    };
+    Ok((schema, unparsed))
 }
 
 /// Extract privileges from Privileges enum
@@ -300,7 +431,7 @@ fn handle_grant_on_tables(
 
     for obj_name in table_names {
         let (schema, name) = helpers::parse_object_name(obj_name);
-        let key = format!("\"{}\".\"{}\"", schema, name);
+        let key = qualified_name(&schema, &name);
 
         for grantee in &grantee_names {
             for priv_str in &priv_strings {
@@ -335,7 +466,7 @@ fn handle_grant_on_sequences(
 
     for obj_name in seq_names {
         let (schema, name) = helpers::parse_object_name(obj_name);
-        let key = format!("\"{}\".\"{}\"", schema, name);
+        let key = qualified_name(&schema, &name);
 
         for grantee in &grantee_names {
             for priv_str in &priv_strings {
@@ -497,11 +628,19 @@ fn preprocess_function_options(sql: &str) -> (String, std::collections::HashMap<
         }
         
         // Look for SET clauses in header
-        // Pattern: SET param_name = 'value' or SET param_name TO value
-        let set_regex = regex::Regex::new(r"(?i)\bSET\s+(\w+)\s*=\s*'([^']*)'").unwrap();
+        // Pattern: SET param_name = 'value' or SET param_name TO value, where an unquoted
+        // value may be a list (e.g. SET search_path TO public, extensions)
+        let set_regex = regex::Regex::new(
+            r"(?i)\bSET\s+(\w+)\s*(?:=|\bTO\b)\s*('(?:[^']|'')*'|[^\s,;']+(?:\s*,\s*[^\s,;']+)*)",
+        )
+        .unwrap();
         for cap in set_regex.captures_iter(header_slice) {
             let param_name = cap.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-            let param_value = cap.get(2).map(|m| m.as_str().to_string()).unwrap_or_default();
+            let raw_value = cap.get(2).map(|m| m.as_str()).unwrap_or_default();
+            let param_value = match raw_value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+                Some(quoted) => quoted.replace("''", "'"),
+                None => raw_value.to_string(),
+            };
             options.config_params.push((param_name, param_value));
             
             // Schedule removal of the entire match
@@ -725,6 +864,59 @@ CREATE INDEX active_users_idx ON users (id) WHERE active = true;
         assert!(idx.where_clause.is_some());
     }
 
+    #[test]
+    fn test_parse_index_constraint_and_policy_comments() {
+        let sql = r#"
+CREATE TABLE users (id uuid NOT NULL, email text, CONSTRAINT email_format CHECK (email LIKE '%@%'));
+CREATE TABLE posts (id uuid NOT NULL, title text);
+CREATE INDEX idx_x ON users (email);
+CREATE INDEX idx_y ON posts (title);
+CREATE POLICY "read own" ON users FOR SELECT USING (true);
+COMMENT ON INDEX idx_x IS 'note';
+COMMENT ON CONSTRAINT email_format ON public.users IS 'it''s an email';
+COMMENT ON POLICY "read own" ON users IS 'owners only';
+        "#;
+
+        let files = vec![("test.sql".to_string(), sql.to_string())];
+        let schema = parse_schema_sql(&files).expect("Failed to parse SQL");
+        let users = schema.tables.get("\"public\".\"users\"").unwrap();
+        let posts = schema.tables.get("\"public\".\"posts\"").unwrap();
+
+        let idx_x = users.indexes.iter().find(|i| i.index_name == "idx_x").unwrap();
+        assert_eq!(idx_x.comment.as_deref(), Some("note"));
+        assert!(posts.indexes.iter().all(|i| i.comment.is_none()));
+        assert_eq!(users.check_constraints[0].comment.as_deref(), Some("it's an email"));
+        assert_eq!(users.policies[0].comment.as_deref(), Some("owners only"));
+    }
+
+    #[test]
+    fn test_parse_alter_column_updates_column() {
+        let sql = r#"
+CREATE TABLE public.orders (id integer PRIMARY KEY, total integer NOT NULL, note text DEFAULT 'none');
+ALTER TABLE public.orders ALTER COLUMN id TYPE bigint;
+ALTER TABLE public.orders ALTER COLUMN total TYPE numeric(10,2), ALTER COLUMN total DROP NOT NULL;
+ALTER TABLE public.orders ALTER COLUMN note SET NOT NULL, ALTER COLUMN note DROP DEFAULT;
+ALTER TABLE public.orders ALTER COLUMN id DROP NOT NULL;
+        "#;
+
+        let files = vec![("test.sql".to_string(), sql.to_string())];
+        let schema = parse_schema_sql(&files).expect("Failed to parse SQL");
+        let orders = schema.tables.get("\"public\".\"orders\"").unwrap();
+
+        let id = &orders.columns["id"];
+        assert_eq!(id.data_type.to_lowercase(), "bigint");
+        assert_eq!(id.udt_name, id.data_type);
+        assert!(!id.is_nullable, "primary key columns stay NOT NULL");
+
+        let total = &orders.columns["total"];
+        assert_eq!(total.data_type.to_lowercase(), "numeric(10,2)");
+        assert!(total.is_nullable);
+
+        let note = &orders.columns["note"];
+        assert!(!note.is_nullable);
+        assert_eq!(note.column_default, None);
+    }
+
     #[test]
     fn test_parse_indexes_and_constraints() {
         let sql = r#"
@@ -1015,6 +1207,90 @@ $$;
         assert_eq!(func.config_params[0].1, "", "Config param value should be empty string");
     }
 
+    #[test]
+    fn test_parse_function_custom_dollar_tag_with_inner_dollar_quotes() {
+        let sql = r#"
+CREATE FUNCTION public.run_dynamic() RETURNS void
+    LANGUAGE plpgsql
+    AS $func$
+BEGIN
+    EXECUTE $$SELECT 'a;b'$$;
+END;
+$func$;
+"#;
+        let files = vec![("test.sql".to_string(), sql.to_string())];
+        let schema = parse_schema_sql(&files).expect("Failed to parse SQL");
+
+        let func = schema.functions.get("\"public\".\"run_dynamic\"()").unwrap();
+        assert!(func.definition.starts_with("BEGIN"));
+        assert!(func.definition.contains("EXECUTE $$SELECT 'a;b'$$;"));
+        assert!(func.definition.ends_with("END;"));
+
+        // The generated function must not close the body at the inner $$
+        let generated = crate::generator::objects::generate_create_function(func);
+        assert!(generated.contains("AS $function$BEGIN"));
+        assert!(generated.ends_with("END;$function$;"));
+
+        // prosrc returns the body verbatim; a different outer tag compares equal
+        assert_eq!(
+            crate::diff::utils::normalize_function_definition(&func.definition),
+            crate::diff::utils::normalize_function_definition(
+                "\nBEGIN\n    EXECUTE $q$SELECT 'a;b'$q$;\nEND;\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_function_config_params_round_trip() {
+        let sql = r#"
+CREATE FUNCTION public.is_admin() RETURNS boolean
+    LANGUAGE sql
+    SECURITY DEFINER
+    SET search_path = ''
+    SET statement_timeout TO 5000
+    AS $$ SELECT true $$;
+
+CREATE FUNCTION public.lookup() RETURNS boolean
+    LANGUAGE sql
+    SET search_path TO public, extensions
+    AS $$ SELECT true $$;
+"#;
+        let files = vec![("test.sql".to_string(), sql.to_string())];
+        let local = parse_schema_sql(&files).expect("Failed to parse SQL");
+
+        let is_admin = local.functions.get("\"public\".\"is_admin\"()").unwrap();
+        assert_eq!(
+            is_admin.config_params,
+            vec![
+                ("search_path".to_string(), "".to_string()),
+                ("statement_timeout".to_string(), "5000".to_string()),
+            ]
+        );
+        let lookup = local.functions.get("\"public\".\"lookup\"()").unwrap();
+        assert_eq!(
+            lookup.config_params,
+            vec![("search_path".to_string(), "public, extensions".to_string())]
+        );
+
+        let generated = crate::generator::objects::generate_create_function(is_admin);
+        assert!(generated.contains("SET search_path = '' SET statement_timeout = 5000 "));
+        let generated = crate::generator::objects::generate_create_function(lookup);
+        assert!(generated.contains("SET search_path = public, extensions "));
+
+        // proconfig stores the empty search_path as a quoted empty identifier
+        let mut remote = local.clone();
+        remote
+            .functions
+            .get_mut("\"public\".\"is_admin\"()")
+            .unwrap()
+            .config_params = vec![
+            ("search_path".to_string(), "\"\"".to_string()),
+            ("statement_timeout".to_string(), "5000".to_string()),
+        ];
+        let diff = crate::diff::compute_diff(&remote, &local);
+        assert!(diff.functions_to_update.is_empty());
+    }
+
     #[test]
     fn test_parse_function_volatility_stable() {
         let sql = r#"
@@ -1610,6 +1886,50 @@ GRANT ALL PRIVILEGES ON ALL TABLES IN SCHEMA cron TO postgres;
         assert_eq!(dp.privilege, "INSERT"); // Note: "SELECT" is stripped out for cron
     }
 
+    #[test]
+    fn test_parse_alter_default_privileges() {
+        let sql = r#"
+ALTER DEFAULT PRIVILEGES IN SCHEMA public GRANT SELECT ON TABLES TO reporting;
+ALTER DEFAULT PRIVILEGES FOR ROLE "app_owner" GRANT ALL ON SEQUENCES TO app_user, PUBLIC;
+ALTER DEFAULT PRIVILEGES FOR ROLE "app_owner" REVOKE UPDATE ON SEQUENCES FROM app_user;
+CREATE TABLE t (id int);
+        "#;
+        let files = vec![("test.sql".to_string(), sql.to_string())];
+        let schema = parse_schema_sql(&files).expect("Failed to parse SQL");
+
+        assert!(schema.tables.contains_key("\"public\".\"t\""));
+        assert_eq!(
+            schema.default_acls[0],
+            crate::schema::DefaultAcl {
+                role: "postgres".to_string(),
+                schema: Some("public".to_string()),
+                object_type: "tables".to_string(),
+                grantee: "reporting".to_string(),
+                privilege: "SELECT".to_string(),
+            }
+        );
+
+        // ALL expands per object type, and the later REVOKE removes UPDATE for app_user
+        let sequence_acls: Vec<(&str, &str)> = schema.default_acls[1..]
+            .iter()
+            .map(|a| {
+                assert_eq!(a.role, "app_owner");
+                assert_eq!(a.schema, None);
+                (a.grantee.as_str(), a.privilege.as_str())
+            })
+            .collect();
+        assert_eq!(
+            sequence_acls,
+            vec![
+                ("app_user", "USAGE"),
+                ("app_user", "SELECT"),
+                ("public", "USAGE"),
+                ("public", "SELECT"),
+                ("public", "UPDATE"),
+            ]
+        );
+    }
+
     #[test]
     fn test_issue_security_definer_edge_cases() {
         use crate::generator::objects::generate_create_function;
@@ -1630,5 +1950,91 @@ GRANT ALL PRIVILEGES ON ALL TABLES IN SCHEMA cron TO postgres;
         let gen_sql = generate_create_function(func);
         assert!(gen_sql.to_uppercase().contains("SECURITY DEFINER"), "Generated SQL must contain SECURITY DEFINER");
     }
+
+    #[test]
+    fn test_unsupported_statements_reported() {
+        let sql = r#"
+        CREATE TABLE public.scores (id bigint PRIMARY KEY, value int);
+
+        CREATE AGGREGATE public.product(int) (
+            SFUNC = int4mul,
+            STYPE = int
+        );
+
+        CREATE SCHEMA IF NOT EXISTS app;
+        SET statement_timeout = 0;
+        SELECT pg_catalog.set_config('search_path', '', false);
+        "#;
+        let files = vec![("schema.sql".to_string(), sql.to_string())];
+        let (schema, unparsed) = parse_schema_sql_with_report(&files).unwrap();
+
+        assert!(schema.tables.contains_key("\"public\".\"scores\""));
+        // Schema creation and session settings have nothing to track and aren't reported
+        assert_eq!(unparsed.len(), 1, "{:?}", unparsed);
+        assert_eq!(unparsed[0].reason, "CREATE AGGREGATE is not supported");
+        assert!(unparsed[0].text.starts_with("CREATE AGGREGATE public.product(int)"));
+        assert_eq!(unparsed[0].file, "schema.sql");
+    }
+
+    #[test]
+    fn test_alter_rule_reported_as_unhandled() {
+        let sql = r#"
+        CREATE VIEW public.active_items AS SELECT id FROM public.items;
+        ALTER RULE protect_delete ON public.active_items RENAME TO keep_rows;
+        "#;
+        let files = vec![("schema.sql".to_string(), sql.to_string())];
+        let (schema, unparsed) = parse_schema_sql_with_report(&files).unwrap();
+
+        assert!(schema.views.contains_key("\"public\".\"active_items\""));
+        assert_eq!(unparsed.len(), 1, "{:?}", unparsed);
+        assert_eq!(unparsed[0].reason, "ALTER RULE is not supported");
+        assert!(unparsed[0].text.contains("RENAME TO keep_rows"));
+    }
+
+    #[test]
+    fn test_parse_create_rule() {
+        let sql = r#"
+        CREATE VIEW public.active_items AS SELECT id FROM public.items;
+        CREATE RULE protect_delete AS
+            ON DELETE TO public.active_items
+            DO INSTEAD NOTHING;
+        "#;
+        let files = vec![("schema.sql".to_string(), sql.to_string())];
+        let (schema, unparsed) = parse_schema_sql_with_report(&files).unwrap();
+
+        assert!(unparsed.is_empty(), "{:?}", unparsed);
+        let rule = &schema.rules["\"public\".\"active_items\".\"protect_delete\""];
+        assert_eq!(rule.relation, "active_items");
+        assert_eq!(rule.definition, "ON DELETE TO public.active_items DO INSTEAD NOTHING");
+    }
+
+    #[test]
+    fn test_unnamed_exclusion_constraints_get_postgres_names() {
+        let sql = r#"
+        CREATE TABLE public.bookings (
+            room_id bigint,
+            during tstzrange,
+            EXCLUDE USING gist (room_id WITH =, during WITH &&)
+        );
+        CREATE TABLE public.slots (
+            a int,
+            b int,
+            EXCLUDE USING gist (int4range(a, b) WITH &&, (a + b) WITH =, "A" WITH =)
+        );
+        CREATE TABLE public.a_very_long_table_name_for_exclusion_constraints (
+            a_very_long_column_name_number_one int,
+            a_very_long_column_name_number_two int,
+            EXCLUDE USING gist (a_very_long_column_name_number_one WITH =, a_very_long_column_name_number_two WITH =)
+        );
+        "#;
+        let schema = parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        let name = |table: &str| schema.tables[table].exclusion_constraints[0].name.clone();
+
+        assert_eq!(name("\"public\".\"bookings\""), "bookings_room_id_during_excl");
+        assert_eq!(name("\"public\".\"slots\""), "slots_expr_expr1_A_excl");
+        let long = name("\"public\".\"a_very_long_table_name_for_exclusion_constraints\"");
+        assert_eq!(long, "a_very_long_table_name_for_ex_a_very_long_column_name_numb_excl");
+        assert_eq!(long.len(), 63);
+    }
 }
 mod tests_snippet;
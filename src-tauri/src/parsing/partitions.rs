@@ -0,0 +1,105 @@
+use regex::Regex;
+
+use crate::schema::PartitionInfo;
+
+use super::exclusions::{matching_paren, strip_leading_comments, table_key, unquote};
+use super::statements::split_statements;
+
+const TABLE_NAME: &str = r#"(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?"#;
+
+/// Partitioning clauses sqlparser can't parse, keyed by parent table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TablePartitions {
+    /// `(table_key, strategy)` for `CREATE TABLE ... PARTITION BY`
+    pub strategies: Vec<(String, String)>,
+    /// `(parent_key, partition)` for `CREATE TABLE ... PARTITION OF`
+    pub partitions: Vec<(String, PartitionInfo)>,
+}
+
+/// Extract `PARTITION BY` from `CREATE TABLE` statements and remove
+/// `CREATE TABLE ... PARTITION OF` statements entirely, mirroring `extract_table_storage`.
+/// Partitions are attached to their parent rather than parsed as tables of their own.
+pub fn extract_partitions(sql: &str) -> (String, TablePartitions) {
+    let create_re = Regex::new(&format!(
+        r#"(?is)^CREATE\s+(?:UNLOGGED\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?(?P<table>{})\s*\("#,
+        TABLE_NAME
+    ))
+    .unwrap();
+    let partition_of_re = Regex::new(&format!(
+        r#"(?is)^CREATE\s+(?:UNLOGGED\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?(?P<table>{})\s+PARTITION\s+OF\s+(?P<parent>{})\s*"#,
+        TABLE_NAME, TABLE_NAME
+    ))
+    .unwrap();
+    let partition_by_re = Regex::new(r"(?i)\bPARTITION\s+BY\s+(RANGE|LIST|HASH)\s*\(").unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = TablePartitions::default();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+
+        if let Some(caps) = partition_of_re.captures(body) {
+            let mut rest = &body[caps.get(0).unwrap().end()..];
+            // Column constraints of the partition itself are not tracked
+            if rest.starts_with('(') {
+                if let Some(close) = matching_paren(rest, 1) {
+                    rest = &rest[close + 1..];
+                }
+            }
+            let (schema, name) = split_name(&caps["table"]);
+            found.partitions.push((
+                table_key(&caps["parent"]),
+                PartitionInfo {
+                    schema,
+                    name,
+                    bound: collapse_whitespace(rest.trim().trim_end_matches(';')),
+                },
+            ));
+            cleaned = cleaned.replacen(body, "", 1);
+            continue;
+        }
+
+        let Some(caps) = create_re.captures(body) else {
+            continue;
+        };
+        let open = caps.get(0).unwrap().end();
+        let Some(close) = matching_paren(body, open) else {
+            continue;
+        };
+        let tail = &body[close + 1..];
+        let Some(m) = partition_by_re.captures(tail) else {
+            continue;
+        };
+        let whole = m.get(0).unwrap();
+        let Some(end) = matching_paren(tail, whole.end()) else {
+            continue;
+        };
+
+        found.strategies.push((
+            table_key(&caps["table"]),
+            format!("{} ({})", m[1].to_uppercase(), collapse_whitespace(&tail[whole.end()..end])),
+        ));
+        let rewritten = format!(
+            "{}{}{}",
+            &body[..=close],
+            &tail[..whole.start()],
+            &tail[end + 1..]
+        );
+        cleaned = cleaned.replacen(body, rewritten.trim_end(), 1);
+    }
+
+    (cleaned, found)
+}
+
+/// `"schema"."name"` or `name` -> unquoted `(schema, name)`, defaulting to public
+pub(super) fn split_name(name: &str) -> (String, String) {
+    let parts: Vec<String> = name.split('.').map(unquote).collect();
+    match parts.as_slice() {
+        [schema, table] => (schema.clone(), table.clone()),
+        _ => ("public".to_string(), parts.join(".")),
+    }
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
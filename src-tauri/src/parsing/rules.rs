@@ -0,0 +1,37 @@
+use regex::Regex;
+
+use crate::schema::RuleInfo;
+
+use super::exclusions::{strip_leading_comments, unquote};
+use super::partitions::split_name;
+use super::statements::split_statements;
+
+/// Extract `CREATE RULE` statements, which sqlparser can't parse, and remove them from
+/// the SQL, mirroring `extract_table_storage`. The rule body is kept verbatim.
+pub fn extract_rules(sql: &str) -> (String, Vec<RuleInfo>) {
+    let re = Regex::new(
+        r#"(?is)^CREATE\s+(?:OR\s+REPLACE\s+)?RULE\s+(?P<name>"[^"]+"|\w+)\s+AS\s+(?P<definition>ON\s+\w+\s+TO\s+(?P<relation>(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?)\s.*?)\s*;?\s*$"#,
+    )
+    .unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+        let Some(caps) = re.captures(body) else {
+            continue;
+        };
+
+        let (schema, relation) = split_name(&caps["relation"]);
+        found.push(RuleInfo {
+            schema,
+            relation,
+            name: unquote(&caps["name"]),
+            definition: caps["definition"].split_whitespace().collect::<Vec<_>>().join(" "),
+        });
+        cleaned = cleaned.replacen(body, "", 1);
+    }
+
+    (cleaned, found)
+}
@@ -1,4 +1,4 @@
-use crate::schema::SequenceInfo;
+use crate::schema::{qualified_name, SequenceInfo};
 use sqlparser::ast::SequenceOptions;
 use std::collections::HashMap;
 use super::helpers::parse_object_name;
@@ -44,7 +44,7 @@ pub fn handle_create_sequence(
         }
     }
 
-    let key = format!("\"{}\".\"{}\"", schema, seq_name);
+    let key = qualified_name(&schema, &seq_name);
     sequences.insert(
         key,
         SequenceInfo {
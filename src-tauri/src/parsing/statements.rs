@@ -0,0 +1,195 @@
+/// A single top-level SQL statement and the 1-based line it starts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlStatement {
+    pub sql: String,
+    pub line: usize,
+}
+
+/// Split a SQL script into top-level statements on `;`.
+///
+/// Semicolons inside string literals, quoted identifiers, comments and
+/// dollar-quoted bodies (`$$ ... $$`, `$fn$ ... $fn$`) are ignored.
+/// Statements consisting only of comments are dropped. The returned SQL
+/// does not include the terminating semicolon.
+pub fn split_statements(sql: &str) -> Vec<SqlStatement> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = Vec::new();
+
+    let mut current = String::new();
+    let mut has_code = false;
+    let mut start_line = 1;
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Line comment
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                current.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+
+        // Block comment (Postgres allows nesting)
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    current.push_str("/*");
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    current.push_str("*/");
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    current.push(chars[i]);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        if c == ';' {
+            if has_code {
+                statements.push(SqlStatement {
+                    sql: current.trim().to_string(),
+                    line: start_line,
+                });
+            }
+            current.clear();
+            has_code = false;
+            i += 1;
+            continue;
+        }
+
+        if !c.is_whitespace() && !has_code {
+            has_code = true;
+            start_line = line;
+        }
+
+        // Quoted string or identifier
+        if c == '\'' || c == '"' {
+            current.push(c);
+            i += 1;
+            while i < chars.len() {
+                let q = chars[i];
+                if q == '\n' {
+                    line += 1;
+                }
+                current.push(q);
+                i += 1;
+                if q == c {
+                    // Doubled quote is an escaped quote
+                    if chars.get(i) == Some(&c) {
+                        current.push(c);
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+
+        // Dollar-quoted body
+        if c == '$' {
+            if let Some(tag) = dollar_tag_at(&chars, i) {
+                let tag_chars: Vec<char> = tag.chars().collect();
+                current.push_str(&tag);
+                i += tag_chars.len();
+                while i < chars.len() {
+                    if chars[i] == '$' && chars[i..].starts_with(&tag_chars) {
+                        current.push_str(&tag);
+                        i += tag_chars.len();
+                        break;
+                    }
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    current.push(chars[i]);
+                    i += 1;
+                }
+                continue;
+            }
+        }
+
+        if c == '\n' {
+            line += 1;
+        }
+        current.push(c);
+        i += 1;
+    }
+
+    if has_code {
+        statements.push(SqlStatement {
+            sql: current.trim().to_string(),
+            line: start_line,
+        });
+    }
+
+    statements
+}
+
+/// Return the dollar-quote tag (e.g. `$$` or `$body$`) starting at `start`, if any.
+fn dollar_tag_at(chars: &[char], start: usize) -> Option<String> {
+    // `$` directly after an identifier character is part of that identifier
+    if start > 0 && (chars[start - 1].is_alphanumeric() || chars[start - 1] == '_') {
+        return None;
+    }
+
+    let mut j = start + 1;
+    while j < chars.len() {
+        let ch = chars[j];
+        if ch == '$' {
+            return Some(chars[start..=j].iter().collect());
+        }
+        let valid = if j == start + 1 {
+            ch.is_alphabetic() || ch == '_'
+        } else {
+            ch.is_alphanumeric() || ch == '_'
+        };
+        if !valid {
+            return None;
+        }
+        j += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements_ignores_quoted_semicolons() {
+        let sql = r#"-- seed users
+INSERT INTO users (name) VALUES ('a;b');
+
+CREATE FUNCTION f() RETURNS void AS $body$
+BEGIN
+  PERFORM 1;
+END;
+$body$ LANGUAGE plpgsql;
+/* trailing; comment */
+SELECT "weird;name" FROM t"#;
+
+        let statements = split_statements(sql);
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0].line, 2);
+        assert!(statements[0].sql.ends_with("('a;b')"));
+        assert_eq!(statements[1].line, 4);
+        assert!(statements[1].sql.contains("PERFORM 1;"));
+        assert_eq!(statements[2].line, 10);
+        assert!(statements[2].sql.ends_with(r#"SELECT "weird;name" FROM t"#));
+    }
+}
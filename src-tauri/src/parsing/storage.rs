@@ -0,0 +1,89 @@
+use regex::Regex;
+
+use super::exclusions::{matching_paren, split_top_level, strip_leading_comments, table_key, unquote};
+use super::statements::split_statements;
+
+const TABLE_NAME: &str = r#"(?P<table>(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?)"#;
+
+/// Storage options of a `CREATE TABLE` that sqlparser drops
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableStorage {
+    pub storage_params: Vec<(String, String)>,
+    pub tablespace: Option<String>,
+    pub is_unlogged: bool,
+}
+
+/// Extract `UNLOGGED`, `WITH (...)` storage parameters and `TABLESPACE` from `CREATE TABLE`
+/// statements and remove them from the SQL, mirroring `extract_exclusion_constraints`.
+/// Returns the cleaned SQL and `(table_key, storage)` pairs for tables that set any of them.
+pub fn extract_table_storage(sql: &str) -> (String, Vec<(String, TableStorage)>) {
+    let create_re = Regex::new(&format!(
+        r#"(?is)^CREATE\s+(?P<unlogged>UNLOGGED\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?{}\s*\("#,
+        TABLE_NAME
+    ))
+    .unwrap();
+    let with_re = Regex::new(r"(?i)\bWITH\s*\(").unwrap();
+    let tablespace_re = Regex::new(r#"(?i)\bTABLESPACE\s+("[^"]+"|\w+)"#).unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+        let Some(caps) = create_re.captures(body) else {
+            continue;
+        };
+        let open = caps.get(0).unwrap().end();
+        let Some(close) = matching_paren(body, open) else {
+            continue;
+        };
+
+        let mut tail = body[close + 1..].to_string();
+        let mut storage = TableStorage {
+            is_unlogged: caps.name("unlogged").is_some(),
+            ..Default::default()
+        };
+
+        if let Some((start, list_start)) = with_re.find(&tail).map(|m| (m.start(), m.end())) {
+            if let Some(end) = matching_paren(&tail, list_start) {
+                storage.storage_params = parse_storage_params(&tail[list_start..end]);
+                tail.replace_range(start..=end, "");
+            }
+        }
+        if let Some((range, name)) = tablespace_re
+            .captures(&tail)
+            .map(|c| (c.get(0).unwrap().range(), unquote(&c[1])))
+        {
+            storage.tablespace = Some(name);
+            tail.replace_range(range, "");
+        }
+
+        if storage != TableStorage::default() {
+            let head = match caps.name("unlogged") {
+                Some(m) => format!("{}{}", &body[..m.start()], &body[m.end()..=close]),
+                None => body[..=close].to_string(),
+            };
+            let rewritten = format!("{}{}", head, tail.trim_end());
+            cleaned = cleaned.replacen(body, &rewritten, 1);
+            found.push((table_key(&caps["table"]), storage));
+        }
+    }
+
+    (cleaned, found)
+}
+
+/// `fillfactor=70, autovacuum_enabled = 'false'` -> sorted `(name, value)` pairs
+pub fn parse_storage_params(list: &str) -> Vec<(String, String)> {
+    let mut params: Vec<(String, String)> = split_top_level(list)
+        .into_iter()
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            Some((
+                name.trim().to_lowercase(),
+                value.trim().trim_matches('\'').to_string(),
+            ))
+        })
+        .collect();
+    params.sort();
+    params
+}
@@ -1,9 +1,9 @@
 use crate::schema::{
-    CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexInfo, TableInfo,
+    qualified_name, CheckConstraintInfo, ColumnInfo, ForeignKeyInfo, IndexColumn, IndexInfo, TableInfo,
 };
 use sqlparser::ast::{
-    AlterTable, AlterTableOperation, ColumnDef, ColumnOption, CreateIndex, CreateTable,
-    TableConstraint, Expr,
+    AlterColumnOperation, AlterTable, AlterTableOperation, ColumnDef, ColumnOption, CreateIndex,
+    CreateTable, TableConstraint, Expr, NullsDistinctOption, RenameTableNameKind,
 };
 use std::collections::HashMap;
 use super::helpers::{parse_object_name, strip_quotes};
@@ -16,11 +16,17 @@ pub fn handle_create_table(
         name,
         columns,
         constraints,
+        temporary,
         ..
     } = stmt;
 
+    // Temporary tables only live for a session and aren't part of the schema
+    if temporary {
+        return;
+    }
+
     let (schema, table_name) = parse_object_name(&name);
-    let (parsed_columns, mut foreign_keys, indexes, mut check_constraints) =
+    let (parsed_columns, mut foreign_keys, mut indexes, mut check_constraints) =
         parse_columns(&table_name, columns, &constraints);
 
     // Extract table-level constraints like Foreign Keys and Checks
@@ -57,6 +63,28 @@ pub fn handle_create_table(
                     });
                 }
             }
+            TableConstraint::Unique(uq) => {
+                let columns: Vec<String> = uq.columns.iter().map(|c| strip_quotes(&c.to_string())).collect();
+                let constraint_name = uq
+                    .name
+                    .as_ref()
+                    .map(|n| strip_quotes(&n.value))
+                    .unwrap_or_else(|| format!("{}_{}_key", table_name, columns.join("_")));
+
+                indexes.push(IndexInfo {
+                    index_name: constraint_name.clone(),
+                    columns: columns.into_iter().map(IndexColumn::new).collect(),
+                    is_unique: true,
+                    is_primary: false,
+                    owning_constraint: Some(constraint_name),
+                    index_method: "btree".to_string(),
+                    where_clause: None,
+                    expressions: vec![],
+                    include_columns: vec![],
+                    nulls_not_distinct: matches!(uq.nulls_distinct, NullsDistinctOption::NotDistinct),
+                    comment: None,
+                });
+            }
             TableConstraint::Check(chk) => {
                 let constraint_name = chk
                     .name
@@ -68,13 +96,14 @@ pub fn handle_create_table(
                     name: constraint_name,
                     expression: super::helpers::format_check_expression(chk.expr.to_string()),
                     columns: vec![],
+                    comment: None,
                 });
             }
             _ => {}
         }
     }
 
-    let key = format!("\"{}\".\"{}\"", schema, table_name);
+    let key = qualified_name(&schema, &table_name);
     tables.insert(
         key,
         TableInfo {
@@ -87,9 +116,15 @@ pub fn handle_create_table(
             rls_enabled: false,
             policies: vec![],
             check_constraints,
+            exclusion_constraints: vec![],
             grants: vec![],
             comment: None,
             extension: None,
+            storage_params: vec![],
+            tablespace: None,
+            is_unlogged: false,
+            partition_strategy: None,
+            partitions: vec![],
         },
     );
 }
@@ -100,11 +135,49 @@ pub fn handle_alter_table(
 ) {
     let AlterTable { name, operations, .. } = stmt;
     let (schema, table_name) = parse_object_name(&name);
-    let table_key = format!("\"{}\".\"{}\"", schema, table_name);
+    let table_key = qualified_name(&schema, &table_name);
+    let mut renamed_to = None;
 
     if let Some(t_info) = tables.get_mut(&table_key) {
         for op in operations {
             match op {
+                AlterTableOperation::AddColumn { column_def, .. } => {
+                    let next_position = t_info.columns.values().filter_map(|c| c.ordinal_position).max().unwrap_or(0) + 1;
+                    let (columns, foreign_keys, _, check_constraints) =
+                        parse_columns(&table_name, vec![column_def], &[]);
+                    for (name, mut column) in columns {
+                        column.ordinal_position = Some(next_position);
+                        t_info.columns.entry(name).or_insert(column);
+                    }
+                    t_info.foreign_keys.extend(foreign_keys);
+                    t_info.check_constraints.extend(check_constraints);
+                }
+                AlterTableOperation::DropColumn { column_names, .. } => {
+                    for column in column_names {
+                        let column = strip_quotes(&column.value);
+                        // Postgres drops indexes and constraints on the column along with it
+                        t_info.columns.remove(&column);
+                        t_info.indexes.retain(|i| !i.column_names().contains(&column.as_str()));
+                        t_info.foreign_keys.retain(|fk| !fk.columns.contains(&column));
+                    }
+                }
+                AlterTableOperation::RenameColumn { old_column_name, new_column_name } => {
+                    let (old_name, new_name) = (strip_quotes(&old_column_name.value), strip_quotes(&new_column_name.value));
+                    if let Some(mut column) = t_info.columns.remove(&old_name) {
+                        column.column_name = new_name.clone();
+                        t_info.columns.insert(new_name.clone(), column);
+                    }
+                    for index in &mut t_info.indexes {
+                        index.columns.iter_mut().filter(|c| c.name == old_name).for_each(|c| c.name = new_name.clone());
+                    }
+                    for fk in &mut t_info.foreign_keys {
+                        fk.columns.iter_mut().filter(|c| **c == old_name).for_each(|c| *c = new_name.clone());
+                    }
+                }
+                AlterTableOperation::RenameTable { table_name: RenameTableNameKind::As(new_name) | RenameTableNameKind::To(new_name) } => {
+                    // The renamed table stays in its schema
+                    renamed_to = Some(parse_object_name(&new_name).1);
+                }
                 AlterTableOperation::EnableRowLevelSecurity => t_info.rls_enabled = true,
                 AlterTableOperation::DisableRowLevelSecurity => t_info.rls_enabled = false,
                 AlterTableOperation::AddConstraint { constraint, .. } => {
@@ -152,13 +225,16 @@ pub fn handle_alter_table(
 
                             t_info.indexes.push(IndexInfo {
                                 index_name: constraint_name.clone(),
-                                columns,
+                                columns: columns.into_iter().map(IndexColumn::new).collect(),
                                 is_unique: true,
                                 is_primary: false,
                                 owning_constraint: Some(constraint_name),
                                 index_method: "btree".to_string(),
                                 where_clause: None,
                                 expressions: vec![],
+                                include_columns: vec![],
+                                nulls_not_distinct: matches!(uq.nulls_distinct, NullsDistinctOption::NotDistinct),
+                                comment: None,
                             });
                         }
                         TableConstraint::Check(chk) => {
@@ -172,15 +248,45 @@ pub fn handle_alter_table(
                                 name: constraint_name,
                                 expression: super::helpers::format_check_expression(chk.expr.to_string()),
                                 columns: vec![],
+                                comment: None,
                             });
                         }
                         _ => {}
                     }
                 }
+                AlterTableOperation::AlterColumn { column_name, op } => {
+                    if let Some(col) = t_info.columns.get_mut(&strip_quotes(&column_name.value)) {
+                        apply_alter_column(col, op);
+                    }
+                }
                 _ => {}
             }
         }
     }
+
+    if let Some(new_name) = renamed_to {
+        if let Some(mut t_info) = tables.remove(&table_key) {
+            t_info.table_name = new_name.clone();
+            tables.insert(qualified_name(&schema, &new_name), t_info);
+        }
+    }
+}
+
+/// Update a parsed column for `ALTER TABLE ... ALTER COLUMN`, so a schema built
+/// from migration-style statements ends up with the final definition
+fn apply_alter_column(col: &mut ColumnInfo, op: AlterColumnOperation) {
+    match op {
+        AlterColumnOperation::SetDataType { data_type, .. } => {
+            col.data_type = data_type.to_string();
+            col.udt_name = col.data_type.clone();
+        }
+        AlterColumnOperation::SetNotNull => col.is_nullable = false,
+        // Primary key columns stay NOT NULL
+        AlterColumnOperation::DropNotNull => col.is_nullable = col.is_primary_key,
+        AlterColumnOperation::SetDefault { value } => col.column_default = Some(value.to_string()),
+        AlterColumnOperation::DropDefault => col.column_default = None,
+        _ => {}
+    }
 }
 
 pub fn handle_create_index(
@@ -193,39 +299,51 @@ pub fn handle_create_index(
         columns,
         unique,
         using,
+        include,
+        nulls_distinct,
         predicate,
         ..
     } = stmt;
 
     let index_name = name.map(|n| strip_quotes(&n.to_string())).unwrap_or_default();
     let (schema, t_name) = parse_object_name(&table_name);
-    let table_key = format!("\"{}\".\"{}\"", schema, t_name);
-
-    let (index_columns, expressions): (Vec<String>, Vec<String>) = columns
-        .iter()
-        .map(|c| {
-            let col_str = match &c.column.expr {
-                Expr::Identifier(ident) => strip_quotes(&ident.value),
-                _ => strip_quotes(&c.column.to_string()),
-            };
-            if col_str.contains('(') {
-                (String::new(), col_str)
-            } else {
-                (col_str, String::new())
-            }
-        })
-        .unzip();
+    let table_key = qualified_name(&schema, &t_name);
+
+    let mut index_columns: Vec<IndexColumn> = Vec::new();
+    let mut expressions: Vec<String> = Vec::new();
+    for c in &columns {
+        // `name COLLATE "C"` parses as a collate expression around the column
+        let (column, collation) = match &c.column.expr {
+            Expr::Collate { expr, collation } => (expr.as_ref(), Some(strip_quotes(&collation.to_string()))),
+            expr => (expr, None),
+        };
+        if let Expr::Identifier(ident) = column {
+            index_columns.push(
+                IndexColumn {
+                    name: strip_quotes(&ident.value),
+                    descending: c.column.options.asc == Some(false),
+                    nulls_first: c.column.options.nulls_first,
+                    collation,
+                    opclass: c.operator_class.as_ref().map(|o| o.to_string()),
+                }
+                .normalized(),
+            );
+            continue;
+        }
 
-    let index_columns: Vec<String> = index_columns
-        .into_iter()
-        .filter(|c| !c.is_empty())
-        .collect();
-    let expressions: Vec<String> = expressions.into_iter().filter(|e| !e.is_empty()).collect();
+        let col_str = strip_quotes(&c.column.to_string());
+        if col_str.contains('(') {
+            expressions.push(col_str);
+        } else if !col_str.is_empty() {
+            index_columns.push(IndexColumn::new(col_str));
+        }
+    }
 
     let index_method = using
         .map(|u| u.to_string().to_lowercase())
         .unwrap_or("btree".to_string());
     let where_clause = predicate.map(|p| p.to_string());
+    let include_columns: Vec<String> = include.iter().map(|i| strip_quotes(&i.value)).collect();
 
     if let Some(t_info) = tables.get_mut(&table_key) {
         t_info.indexes.push(IndexInfo {
@@ -237,6 +355,9 @@ pub fn handle_create_index(
             index_method,
             where_clause,
             expressions,
+            include_columns,
+            nulls_not_distinct: nulls_distinct == Some(false),
+            comment: None,
         });
     }
 }
@@ -251,7 +372,7 @@ pub fn handle_comment(
     match object_type {
         sqlparser::ast::CommentObject::Table => {
             let (schema, table_name) = parse_object_name(&object_name);
-            let key = format!("\"{}\".\"{}\"", schema, table_name);
+            let key = qualified_name(&schema, &table_name);
             if let Some(table) = tables.get_mut(&key) {
                 table.comment = comment;
             }
@@ -262,7 +383,7 @@ pub fn handle_comment(
                 let schema = idents[0].to_string().trim_matches('"').to_string();
                 let table = idents[1].to_string().trim_matches('"').to_string();
                 let col = idents[2].to_string().trim_matches('"').to_string();
-                let table_key = format!("\"{}\".\"{}\"", schema, table);
+                let table_key = qualified_name(&schema, &table);
                 if let Some(t_info) = tables.get_mut(&table_key) {
                     if let Some(c_info) = t_info.columns.get_mut(&col) {
                         c_info.comment = comment;
@@ -272,7 +393,7 @@ pub fn handle_comment(
                 let schema = "public".to_string();
                 let table = idents[0].to_string().trim_matches('"').to_string();
                 let col = idents[1].to_string().trim_matches('"').to_string();
-                let table_key = format!("\"{}\".\"{}\"", schema, table);
+                let table_key = qualified_name(&schema, &table);
                 if let Some(t_info) = tables.get_mut(&table_key) {
                     if let Some(c_info) = t_info.columns.get_mut(&col) {
                         c_info.comment = comment;
@@ -299,8 +420,8 @@ pub fn parse_columns(
     let _option_indexes: Vec<IndexInfo> = Vec::new(); // Note: unused for now to match mod.rs logic
     let mut check_constraints = Vec::new();
 
-    for col in columns {
-        let name = col.name.to_string().trim_matches('"').to_string();
+    for (index, col) in columns.into_iter().enumerate() {
+        let name = col.name.value.clone();
         let data_type = col.data_type.to_string();
         let mut is_nullable = true;
         let mut is_primary_key = false;
@@ -347,6 +468,7 @@ pub fn parse_columns(
                         name: constraint_name,
                         expression: super::helpers::format_check_expression(check_expr.to_string()),
                         columns: vec![name.clone()],
+                        comment: None,
                     });
                 }
                 ColumnOption::ForeignKey(fk_constraint) => {
@@ -438,6 +560,7 @@ pub fn parse_columns(
                 is_generated,
                 generation_expression,
                 comment: None,
+                ordinal_position: Some(index as i32 + 1),
             },
         );
     }
@@ -1,7 +1,10 @@
-use crate::schema::{CompositeTypeAttribute, CompositeTypeInfo, DomainCheckConstraint, DomainInfo, EnumInfo};
+use crate::schema::{qualified_name, CompositeTypeAttribute, CompositeTypeInfo, DomainCheckConstraint, DomainInfo, EnumInfo};
 use sqlparser::ast::{CreateDomain, UserDefinedTypeRepresentation, TableConstraint};
+use regex::Regex;
 use std::collections::HashMap;
+use super::exclusions::{strip_leading_comments, table_key};
 use super::helpers::parse_object_name;
+use super::statements::split_statements;
 
 pub fn handle_create_type(
     enums: &mut HashMap<String, EnumInfo>,
@@ -13,7 +16,7 @@ pub fn handle_create_type(
         match rep {
             UserDefinedTypeRepresentation::Enum { labels, .. } => {
                 let (schema, enum_name) = parse_object_name(&name);
-                let key = format!("\"{}\".\"{}\"", schema, enum_name);
+                let key = qualified_name(&schema, &enum_name);
                 enums.insert(
                     key,
                     EnumInfo {
@@ -35,7 +38,7 @@ pub fn handle_create_type(
                     })
                     .collect();
 
-                let key = format!("\"{}\".\"{}\"", schema, type_name);
+                let key = qualified_name(&schema, &type_name);
                 composite_types.insert(
                     key,
                     CompositeTypeInfo {
@@ -68,7 +71,6 @@ pub fn handle_create_domain(
     let base_type = data_type.to_string().to_lowercase();
     let default_value = default.map(|d| d.to_string());
 
-    let is_not_null = false; // Default to false
     let mut check_constraints = vec![];
 
     for constraint in constraints {
@@ -83,7 +85,7 @@ pub fn handle_create_domain(
         }
     }
 
-    let key = format!("\"{}\".\"{}\"", schema, domain_name);
+    let key = qualified_name(&schema, &domain_name);
     domains.insert(
         key,
         DomainInfo {
@@ -91,7 +93,8 @@ pub fn handle_create_domain(
             name: domain_name,
             base_type,
             default_value,
-            is_not_null,
+            // sqlparser rejects NOT NULL on domains; it's extracted before parsing
+            is_not_null: false,
             check_constraints,
             collation: collation.map(|c| c.to_string()),
             comment: None,
@@ -99,3 +102,58 @@ pub fn handle_create_domain(
         },
     );
 }
+
+/// Remove top-level `NOT NULL` from `CREATE DOMAIN` statements, which sqlparser
+/// can't parse, mirroring `extract_table_storage`. Returns the cleaned SQL and
+/// the keys of the domains that declared it.
+pub fn extract_domain_not_null(sql: &str) -> (String, Vec<String>) {
+    let create_re = Regex::new(
+        r#"(?is)^CREATE\s+DOMAIN\s+(?P<name>(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?)\s"#,
+    )
+    .unwrap();
+    // Also matches the named form, `CONSTRAINT name NOT NULL`; the name isn't tracked
+    let not_null_re = Regex::new(r"(?i)(?:\bCONSTRAINT\s+(?:\w+\s+)?)?\bNOT\s+NULL\b").unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+        let Some(caps) = create_re.captures(body) else {
+            continue;
+        };
+        // `CHECK (VALUE IS NOT NULL)` is a check constraint, so only match outside parentheses
+        let masked = mask_nested(body);
+        let Some(m) = not_null_re.find(&masked) else {
+            continue;
+        };
+        let rewritten = format!("{}{}", &body[..m.start()], &body[m.end()..]);
+        cleaned = cleaned.replacen(body, &rewritten, 1);
+        found.push(table_key(&caps["name"]));
+    }
+
+    (cleaned, found)
+}
+
+/// Blank out everything inside parentheses and quotes, keeping byte offsets
+fn mask_nested(sql: &str) -> String {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    sql.chars()
+        .map(|c| {
+            let masked = depth > 0 || quote.is_some() || matches!(c, '(' | '\'' | '"');
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None => match c {
+                    '\'' | '"' => quote = Some(c),
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                },
+            }
+            // Multi-byte characters are kept so offsets stay valid
+            if masked && c.is_ascii() { ' ' } else { c }
+        })
+        .collect()
+}
@@ -0,0 +1,54 @@
+use regex::Regex;
+
+use super::exclusions::strip_leading_comments;
+use super::statements::split_statements;
+use super::UnparsedStatement;
+
+/// Remove statements for objects Supawatch doesn't model and sqlparser can't parse,
+/// so they are reported instead of failing the whole file.
+pub fn extract_unsupported(sql: &str, filename: &str) -> (String, Vec<UnparsedStatement>) {
+    let re = Regex::new(
+        r"(?is)^(?P<kind>(?:CREATE|ALTER|DROP)\s+(?:OR\s+REPLACE\s+)?(?:AGGREGATE|RULE|OPERATOR(?:\s+CLASS|\s+FAMILY)?|CAST|COLLATION|CONVERSION|EVENT\s+TRIGGER|PUBLICATION|SUBSCRIPTION|TEXT\s+SEARCH|FOREIGN\s+DATA\s+WRAPPER|SERVER|USER\s+MAPPING|LANGUAGE|TRANSFORM|STATISTICS|ACCESS\s+METHOD))\b",
+    )
+    .unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql);
+        let Some(caps) = re.captures(body) else {
+            continue;
+        };
+
+        let kind = caps["kind"].split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+        found.push(UnparsedStatement {
+            file: filename.to_string(),
+            text: body.trim().to_string(),
+            reason: format!("{} is not supported", kind),
+        });
+        cleaned = cleaned.replacen(body, "", 1);
+    }
+
+    (cleaned, found)
+}
+
+/// Statements with nothing to track that are safe to skip silently: schema creation
+/// (`split_sql` writes `CREATE SCHEMA IF NOT EXISTS` itself) and session settings
+/// like pg_dump's `SET` and `SELECT pg_catalog.set_config(...)`
+pub fn is_harmless(text: &str) -> bool {
+    let kind = statement_kind(text);
+    kind == "CREATE SCHEMA"
+        || kind.starts_with("SET")
+        || kind.starts_with("RESET")
+        || (kind == "SELECT" && text.to_lowercase().contains("set_config("))
+}
+
+/// Leading keywords of a statement rendered by sqlparser, e.g. `CREATE SCHEMA`
+pub fn statement_kind(text: &str) -> String {
+    text.split_whitespace()
+        .take_while(|word| word.chars().all(|c| c.is_ascii_uppercase()))
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
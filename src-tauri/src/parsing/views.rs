@@ -1,7 +1,43 @@
-use crate::schema::ViewInfo;
+use crate::schema::{qualified_name, ViewInfo};
+use regex::Regex;
 use sqlparser::ast::{CreateTableOptions, CreateView};
 use std::collections::HashMap;
+use super::exclusions::{strip_leading_comments, table_key};
 use super::helpers::parse_object_name;
+use super::statements::split_statements;
+
+/// Extract `WITH [CASCADED | LOCAL] CHECK OPTION` from `CREATE VIEW` statements, which
+/// sqlparser can't parse, and remove it from the SQL. Returns `(view_key, level)` pairs;
+/// a bare `WITH CHECK OPTION` is `CASCADED`, as in Postgres.
+pub fn extract_check_options(sql: &str) -> (String, Vec<(String, String)>) {
+    let create_re = Regex::new(
+        r#"(?is)^CREATE\s+(?:OR\s+REPLACE\s+)?(?:TEMP(?:ORARY)?\s+)?(?:RECURSIVE\s+)?VIEW\s+(?P<view>(?:"[^"]+"|\w+)(?:\s*\.\s*(?:"[^"]+"|\w+))?)"#,
+    )
+    .unwrap();
+    let check_re = Regex::new(r"(?is)\s+WITH\s+(?:(?P<level>CASCADED|LOCAL)\s+)?CHECK\s+OPTION\s*$").unwrap();
+
+    let mut cleaned = sql.to_string();
+    let mut found = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let body = strip_leading_comments(&stmt.sql).trim_end();
+        let Some(caps) = create_re.captures(body) else {
+            continue;
+        };
+        let Some(check) = check_re.captures(body) else {
+            continue;
+        };
+
+        let level = check
+            .name("level")
+            .map(|m| m.as_str().to_uppercase())
+            .unwrap_or_else(|| "CASCADED".to_string());
+        found.push((table_key(&caps["view"]), level));
+        cleaned = cleaned.replacen(body, &body[..check.get(0).unwrap().start()], 1);
+    }
+
+    (cleaned, found)
+}
 
 pub fn handle_create_view(
     views: &mut HashMap<String, ViewInfo>,
@@ -9,6 +45,7 @@ pub fn handle_create_view(
 ) {
     let CreateView {
         name,
+        columns,
         query,
         materialized,
         options,
@@ -17,13 +54,28 @@ pub fn handle_create_view(
 
     let (schema, view_name) = parse_object_name(&name);
     let definition = query.to_string();
+    let column_aliases: Vec<String> = columns.iter().map(|c| c.name.value.clone()).collect();
 
-    let with_options: Vec<String> = match options {
-        CreateTableOptions::Options(opts) => opts.iter().map(|o| o.to_string()).collect(),
+    // `security_invoker = true` -> `security_invoker=true`, the form Postgres keeps in reloptions
+    let options: Vec<String> = match options {
+        CreateTableOptions::With(opts) | CreateTableOptions::Options(opts) => opts
+            .iter()
+            .map(|o| match o.to_string().split_once('=') {
+                Some((name, value)) => format!("{}={}", name.trim().to_lowercase(), value.trim()),
+                None => o.to_string(),
+            })
+            .collect(),
         _ => vec![],
     };
+    // `WITH (check_option=...)` is the same as a trailing `WITH ... CHECK OPTION`
+    let (check_options, with_options): (Vec<String>, Vec<String>) =
+        options.into_iter().partition(|o| o.starts_with("check_option="));
+    let check_option = check_options
+        .first()
+        .and_then(|o| o.split_once('='))
+        .map(|(_, level)| level.trim_matches('\'').to_uppercase());
 
-    let key = format!("\"{}\".\"{}\"", schema, view_name);
+    let key = qualified_name(&schema, &view_name);
     views.insert(
         key,
         ViewInfo {
@@ -32,10 +84,11 @@ pub fn handle_create_view(
             definition,
             is_materialized: materialized,
             columns: vec![],
+            column_aliases,
             indexes: vec![],
             comment: None,
             with_options,
-            check_option: None,
+            check_option,
             grants: vec![],
             extension: None,
         },
@@ -0,0 +1,462 @@
+//! Helpers for running ad-hoc queries from the SQL editor: pagination of
+//! SELECT results, a cap on how much data is sent back to the UI, a guard
+//! for read-only runs, EXPLAIN plans, and exporting results as CSV or JSON.
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::parser::Parser;
+
+/// Largest serialized result `run_query` sends to the frontend
+pub const MAX_RESULT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Result of `run_query`. `total_count` is only known for paginated SELECTs.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryPage {
+    pub rows: serde_json::Value,
+    pub total_count: Option<u64>,
+    /// Rows were dropped to stay under `MAX_RESULT_BYTES`
+    pub truncated: bool,
+}
+
+/// SQL for one page of a query and for its total row count
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaginatedQuery {
+    pub page_sql: String,
+    pub count_sql: String,
+}
+
+/// Wrap `query` with LIMIT/OFFSET when it is a single read-only SELECT.
+/// Anything else (DDL, DML, multiple statements, data-modifying CTEs) is
+/// returned as None and should run untouched.
+pub fn paginate_select(query: &str, limit: u64, offset: u64) -> Option<PaginatedQuery> {
+    let statements = crate::parsing::split_statements(query);
+    let [statement] = statements.as_slice() else {
+        return None;
+    };
+
+    let parsed = Parser::parse_sql(&PostgreSqlDialect {}, &statement.sql).ok()?;
+    match parsed.as_slice() {
+        [Statement::Query(q)] if is_read_only_query(q) => {}
+        _ => return None,
+    }
+
+    // Newlines keep a trailing line comment from swallowing the closing paren
+    let inner = statement.sql.trim();
+    Some(PaginatedQuery {
+        page_sql: format!(
+            "SELECT * FROM (\n{}\n) AS _page LIMIT {} OFFSET {}",
+            inner, limit, offset
+        ),
+        count_sql: format!("SELECT count(*) AS total FROM (\n{}\n) AS _count", inner),
+    })
+}
+
+/// Reject a query sent with `read_only` unless every statement is a plain
+/// SELECT, EXPLAIN or SHOW, so a mistaken write never reaches the server.
+/// EXPLAIN ANALYZE executes its statement, so that statement must be a read too.
+pub fn check_read_only(query: &str) -> Result<(), String> {
+    for statement in crate::parsing::split_statements(query) {
+        let parsed = Parser::parse_sql(&PostgreSqlDialect {}, &statement.sql)
+            .map_err(|e| format!("Read-only query could not be checked: {}", e))?;
+        if let Some(rejected) = parsed.iter().find(|s| !is_read_only_statement(s)) {
+            return Err(format!(
+                "Read-only mode only allows SELECT, EXPLAIN and SHOW statements, got: {}",
+                statement_keyword(&rejected.to_string())
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn is_read_only_statement(statement: &Statement) -> bool {
+    match statement {
+        Statement::Query(q) => is_read_only_query(q),
+        Statement::Explain { analyze, statement, .. } => !analyze || is_read_only_statement(statement),
+        Statement::ShowVariable { .. } => true,
+        _ => false,
+    }
+}
+
+/// Leading keywords of a formatted statement for error messages, e.g. "DROP TABLE"
+fn statement_keyword(sql: &str) -> String {
+    sql.split_whitespace()
+        .take_while(|word| word.chars().all(|c| c.is_ascii_uppercase()))
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_read_only_query(query: &Query) -> bool {
+    let ctes_read_only = query
+        .with
+        .as_ref()
+        .map(|with| with.cte_tables.iter().all(|cte| is_read_only_query(&cte.query)))
+        .unwrap_or(true);
+    ctes_read_only && is_read_only_body(&query.body)
+}
+
+fn is_read_only_body(body: &SetExpr) -> bool {
+    match body {
+        // `SELECT ... INTO new_table` creates a table
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Values(_) | SetExpr::Table(_) => true,
+        SetExpr::Query(q) => is_read_only_query(q),
+        SetExpr::SetOperation { left, right, .. } => is_read_only_body(left) && is_read_only_body(right),
+        _ => false,
+    }
+}
+
+/// `EXPLAIN (FORMAT JSON)` for a single statement, with `ANALYZE, BUFFERS` when `analyze`
+/// is set. ANALYZE executes the statement, so anything but a read is refused unless `force`.
+pub fn explain_sql(query: &str, analyze: bool, force: bool) -> Result<String, String> {
+    let statements = crate::parsing::split_statements(query);
+    let [statement] = statements.as_slice() else {
+        return Err("EXPLAIN takes exactly one statement".to_string());
+    };
+    let sql = statement.sql.trim().trim_end_matches(';').trim_end();
+    if sql.split_whitespace().next().is_some_and(|word| word.eq_ignore_ascii_case("EXPLAIN")) {
+        return Err("The query is already an EXPLAIN".to_string());
+    }
+
+    if analyze && !force {
+        check_read_only(sql).map_err(|e| {
+            format!("EXPLAIN ANALYZE executes the statement, so only reads can be analyzed without force. {}", e)
+        })?;
+    }
+
+    let options = if analyze { "ANALYZE, BUFFERS, FORMAT JSON" } else { "FORMAT JSON" };
+    // A newline keeps a trailing line comment in `sql` from hiding anything after it
+    Ok(format!("EXPLAIN ({})\n{}", options, sql))
+}
+
+/// The JSON plan from the result of an `explain_sql` query: the single `QUERY PLAN` value,
+/// which the API returns either as JSON or as its text
+pub fn parse_explain_plan(result: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let plan = result
+        .as_array()
+        .and_then(|rows| rows.first())
+        .and_then(|row| row.get("QUERY PLAN"))
+        .ok_or("EXPLAIN returned no plan")?;
+    match plan {
+        serde_json::Value::String(text) => {
+            serde_json::from_str(text).map_err(|e| format!("Failed to parse plan: {}", e))
+        }
+        other => Ok(other.clone()),
+    }
+}
+
+/// Read `total` from the result of a `count_sql` query
+pub fn parse_total_count(result: &serde_json::Value) -> Option<u64> {
+    let total = result.as_array()?.first()?.get("total")?;
+    // count(*) is a bigint, which may come back as a string
+    total
+        .as_u64()
+        .or_else(|| total.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Drop trailing rows until the serialized result fits in `max_bytes`.
+/// Returns the (possibly shortened) rows and whether anything was dropped.
+pub fn cap_result_size(rows: serde_json::Value, max_bytes: usize) -> (serde_json::Value, bool) {
+    let serde_json::Value::Array(items) = rows else {
+        return (rows, false);
+    };
+
+    // Account for the brackets and separating commas
+    let mut size = 2;
+    let mut kept = Vec::new();
+    let total = items.len();
+    for item in items {
+        let item_size = serde_json::to_string(&item).map(|s| s.len()).unwrap_or(0) + 1;
+        if size + item_size > max_bytes {
+            break;
+        }
+        size += item_size;
+        kept.push(item);
+    }
+
+    let truncated = kept.len() < total;
+    (serde_json::Value::Array(kept), truncated)
+}
+
+/// File format for `export_query_result`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Column names of the first row of a query response body (the rows, or an object
+/// with them under `result`) in the order the query returned them. Parsed values
+/// lose that order, their keys are sorted. Empty when there are no rows.
+pub fn first_row_columns(body: &str) -> Vec<String> {
+    use serde::de::{Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+    struct Columns(Vec<String>);
+    struct RowKeys(Vec<String>);
+
+    impl<'de> Deserialize<'de> for Columns {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct ColumnsVisitor;
+            impl<'de> Visitor<'de> for ColumnsVisitor {
+                type Value = Columns;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("an array of rows or an object with a `result` array")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Columns, A::Error> {
+                    let first = seq.next_element::<RowKeys>()?;
+                    while seq.next_element::<IgnoredAny>()?.is_some() {}
+                    Ok(Columns(first.map(|row| row.0).unwrap_or_default()))
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Columns, A::Error> {
+                    let mut columns = Vec::new();
+                    while let Some(key) = map.next_key::<String>()? {
+                        if key == "result" {
+                            columns = map.next_value::<Option<Columns>>()?.map(|c| c.0).unwrap_or_default();
+                        } else {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                    Ok(Columns(columns))
+                }
+            }
+            deserializer.deserialize_any(ColumnsVisitor)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for RowKeys {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct RowKeysVisitor;
+            impl<'de> Visitor<'de> for RowKeysVisitor {
+                type Value = RowKeys;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a row object")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RowKeys, A::Error> {
+                    let mut keys = Vec::new();
+                    while let Some(key) = map.next_key::<String>()? {
+                        map.next_value::<IgnoredAny>()?;
+                        keys.push(key);
+                    }
+                    Ok(RowKeys(keys))
+                }
+            }
+            deserializer.deserialize_map(RowKeysVisitor)
+        }
+    }
+
+    serde_json::from_str::<Columns>(body).map(|c| c.0).unwrap_or_default()
+}
+
+/// Serialize query result rows (an array of objects) in the given format. CSV columns
+/// follow `columns` (see `first_row_columns`), then any other keys the rows have.
+pub fn export_rows(rows: &serde_json::Value, columns: &[String], format: ExportFormat) -> Result<String, String> {
+    let rows: &[serde_json::Value] = match rows {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Null => &[],
+        _ => return Err("Query did not return rows".to_string()),
+    };
+
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(rows).map_err(|e| e.to_string()),
+        ExportFormat::Csv => Ok(rows_to_csv(rows, columns)),
+    }
+}
+
+fn rows_to_csv(rows: &[serde_json::Value], known_columns: &[String]) -> String {
+    // Then the other columns in order of first appearance; rows may omit keys
+    let mut columns: Vec<&str> = known_columns.iter().map(String::as_str).collect();
+    for row in rows {
+        if let Some(obj) = row.as_object() {
+            for key in obj.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let header: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+
+    for row in rows {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|col| csv_value(row.get(*col).unwrap_or(&serde_json::Value::Null)))
+            .collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// NULL becomes an empty field; nested JSON is written as its JSON text
+fn csv_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => csv_field(s),
+        serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => csv_field(&value.to_string()),
+    }
+}
+
+/// Quote fields containing separators, quotes or line breaks (RFC 4180)
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_is_wrapped_with_limit_and_offset() {
+        let page = paginate_select("SELECT * FROM users WHERE id > 10; ", 50, 100).unwrap();
+        assert_eq!(
+            page.page_sql,
+            "SELECT * FROM (\nSELECT * FROM users WHERE id > 10\n) AS _page LIMIT 50 OFFSET 100"
+        );
+        assert_eq!(
+            page.count_sql,
+            "SELECT count(*) AS total FROM (\nSELECT * FROM users WHERE id > 10\n) AS _count"
+        );
+
+        // CTEs and set operations are still plain reads
+        assert!(paginate_select("WITH u AS (SELECT 1) SELECT * FROM u", 10, 0).is_some());
+        assert!(paginate_select("SELECT 1 UNION SELECT 2", 10, 0).is_some());
+    }
+
+    #[test]
+    fn test_non_select_statements_pass_through() {
+        assert_eq!(paginate_select("CREATE TABLE t (id int)", 50, 0), None);
+        assert_eq!(paginate_select("ALTER TABLE t ADD COLUMN name text", 50, 0), None);
+        assert_eq!(paginate_select("DELETE FROM t", 50, 0), None);
+        assert_eq!(paginate_select("SELECT 1; SELECT 2", 50, 0), None);
+        assert_eq!(
+            paginate_select("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d", 50, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cap_result_size_truncates_rows() {
+        let rows = serde_json::json!([{"v": "aaaa"}, {"v": "bbbb"}, {"v": "cccc"}]);
+        let (capped, truncated) = cap_result_size(rows.clone(), 20);
+        assert!(truncated);
+        assert_eq!(capped, serde_json::json!([{"v": "aaaa"}]));
+
+        let (capped, truncated) = cap_result_size(rows.clone(), MAX_RESULT_BYTES);
+        assert!(!truncated);
+        assert_eq!(capped, rows);
+
+        assert_eq!(parse_total_count(&serde_json::json!([{"total": "42"}])), Some(42));
+    }
+
+    #[test]
+    fn test_csv_export_escapes_special_characters() {
+        let rows = serde_json::json!([
+            {"a": null, "b": 1, "c": {"k": [1, 2]}, "d": "x, \"quoted\"\nvalue"},
+        ]);
+        let csv = export_rows(&rows, &[], ExportFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            "a,b,c,d\r\n,1,\"{\"\"k\"\":[1,2]}\",\"x, \"\"quoted\"\"\nvalue\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_export_keeps_query_column_order() {
+        // Parsed from text like the API response, not built with json!
+        let body = r#"[{"name": "a", "id": 1, "created_at": null}, {"zeta": true, "name": "b"}]"#;
+        let rows: serde_json::Value = serde_json::from_str(body).unwrap();
+        let columns = first_row_columns(body);
+        assert_eq!(columns, vec!["name", "id", "created_at"]);
+        let csv = export_rows(&rows, &columns, ExportFormat::Csv).unwrap();
+        assert_eq!(csv, "name,id,created_at,zeta\r\na,1,,\r\nb,,,true\r\n");
+
+        // Wrapped responses and empty results
+        assert_eq!(first_row_columns(r#"{"result": [{"b": 1, "a": [1, {"z": 2}]}], "error": null}"#), vec!["b", "a"]);
+        assert!(first_row_columns(r#"{"result": null}"#).is_empty());
+        assert!(first_row_columns("[]").is_empty());
+    }
+
+    #[test]
+    fn test_json_export_is_array_of_rows() {
+        let rows = serde_json::json!([{"id": 1, "name": null}, {"id": 2, "name": "b"}]);
+        let json = export_rows(&rows, &[], ExportFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, rows);
+
+        let empty = export_rows(&serde_json::Value::Null, &[], ExportFormat::Json).unwrap();
+        assert_eq!(empty, "[]");
+    }
+
+    #[test]
+    fn test_read_only_guard_allows_reads() {
+        assert_eq!(check_read_only("SELECT * FROM users"), Ok(()));
+        assert_eq!(check_read_only("WITH u AS (SELECT 1) SELECT * FROM u; SELECT 2;"), Ok(()));
+        assert_eq!(check_read_only("EXPLAIN SELECT * FROM users"), Ok(()));
+        assert_eq!(check_read_only("EXPLAIN ANALYZE SELECT * FROM users"), Ok(()));
+        assert_eq!(check_read_only("SHOW search_path"), Ok(()));
+    }
+
+    #[test]
+    fn test_read_only_guard_rejects_writes_locally() {
+        let err = check_read_only("UPDATE users SET name = 'x'").unwrap_err();
+        assert!(err.ends_with("got: UPDATE"), "{}", err);
+
+        let err = check_read_only("SELECT 1; DROP TABLE users;").unwrap_err();
+        assert!(err.contains("got: DROP TABLE"), "{}", err);
+
+        // EXPLAIN ANALYZE runs the statement
+        assert!(check_read_only("EXPLAIN ANALYZE DELETE FROM users").is_err());
+        assert!(check_read_only("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d").is_err());
+
+        // SELECT INTO creates a table
+        assert!(check_read_only("SELECT * INTO users_copy FROM users").is_err());
+        assert!(explain_sql("SELECT id INTO TEMP t FROM users", true, false).is_err());
+    }
+
+    #[test]
+    fn test_explain_sql_uses_json_format() {
+        assert_eq!(
+            explain_sql("SELECT * FROM users WHERE id = 1;", false, false).unwrap(),
+            "EXPLAIN (FORMAT JSON)\nSELECT * FROM users WHERE id = 1"
+        );
+        assert_eq!(
+            explain_sql("SELECT * FROM users", true, false).unwrap(),
+            "EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON)\nSELECT * FROM users"
+        );
+        assert!(explain_sql("SELECT 1; SELECT 2;", false, false).is_err());
+        assert!(explain_sql("EXPLAIN SELECT 1", false, false).is_err());
+
+        let rows = serde_json::json!([{"QUERY PLAN": "[{\"Plan\": {\"Node Type\": \"Seq Scan\"}}]"}]);
+        assert_eq!(parse_explain_plan(&rows).unwrap()[0]["Plan"]["Node Type"], "Seq Scan");
+    }
+
+    #[test]
+    fn test_explain_analyze_rejects_writes_without_force() {
+        let insert = "INSERT INTO users (name) VALUES ('x')";
+        let err = explain_sql(insert, true, false).unwrap_err();
+        assert!(err.starts_with("EXPLAIN ANALYZE executes the statement"), "{}", err);
+
+        // Planning alone doesn't run it, and forcing is an explicit opt-in
+        assert!(explain_sql(insert, false, false).is_ok());
+        assert_eq!(
+            explain_sql(insert, true, true).unwrap(),
+            format!("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON)\n{}", insert)
+        );
+    }
+}
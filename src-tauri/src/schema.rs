@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RoleInfo {
@@ -31,6 +31,14 @@ pub struct DbSchema {
     // Grants & Default Privileges
     pub schema_grants: Vec<SchemaGrant>,
     pub default_privileges: Vec<DefaultPrivilege>,
+    #[serde(default)]
+    pub default_acls: Vec<DefaultAcl>,
+    /// Keyed by `RuleInfo::key`
+    #[serde(default)]
+    pub rules: HashMap<String, RuleInfo>,
+    /// `COMMENT ON SCHEMA`, keyed by schema name. `None` is an explicit `IS NULL`.
+    #[serde(default)]
+    pub schema_comments: HashMap<String, Option<String>>,
 }
 
 impl Default for DbSchema {
@@ -47,14 +55,95 @@ impl Default for DbSchema {
             domains: HashMap::new(),
             schema_grants: Vec::new(),
             default_privileges: Vec::new(),
+            default_acls: Vec::new(),
+            rules: HashMap::new(),
+            schema_comments: HashMap::new(),
         }
     }
 }
 
+/// Quote an identifier for SQL, doubling embedded double quotes (`we"ird` -> `"we""ird"`)
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `"schema"."name"`, the key format of tables, views, types and sequences in `DbSchema`
+pub fn qualified_name(schema: &str, name: &str) -> String {
+    format!("{}.{}", quote_ident(schema), quote_ident(name))
+}
+
 impl DbSchema {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Names of every object, grouped by kind, for inspecting what was parsed or introspected.
+    /// Table-level objects are named `table_key.object`.
+    pub fn summary(&self) -> SchemaSummary {
+        fn sorted(names: impl Iterator<Item = String>) -> Vec<String> {
+            let mut names: Vec<String> = names.collect();
+            names.sort();
+            names
+        }
+        fn table_objects(
+            tables: &HashMap<String, TableInfo>,
+            names: impl Fn(&TableInfo) -> Vec<&str>,
+        ) -> Vec<String> {
+            sorted(tables.iter().flat_map(|(key, table)| {
+                names(table).into_iter().map(move |name| format!("{}.{}", key, name))
+            }))
+        }
+
+        let mut objects = BTreeMap::new();
+        objects.insert("tables", sorted(self.tables.keys().cloned()));
+        objects.insert("enums", sorted(self.enums.keys().cloned()));
+        objects.insert("functions", sorted(self.functions.keys().cloned()));
+        objects.insert("roles", sorted(self.roles.keys().cloned()));
+        objects.insert("views", sorted(self.views.keys().cloned()));
+        objects.insert("sequences", sorted(self.sequences.keys().cloned()));
+        objects.insert("extensions", sorted(self.extensions.keys().cloned()));
+        objects.insert("composite_types", sorted(self.composite_types.keys().cloned()));
+        objects.insert("domains", sorted(self.domains.keys().cloned()));
+        objects.insert("indexes", table_objects(&self.tables, |t| t.indexes.iter().map(|i| i.index_name.as_str()).collect()));
+        objects.insert("triggers", table_objects(&self.tables, |t| t.triggers.iter().map(|tr| tr.name.as_str()).collect()));
+        objects.insert("policies", table_objects(&self.tables, |t| t.policies.iter().map(|p| p.name.as_str()).collect()));
+        objects.insert("rules", sorted(self.rules.keys().cloned()));
+        objects.insert(
+            "schema_grants",
+            sorted(self.schema_grants.iter().map(|g| format!("{} {} TO {}", g.schema, g.privilege, g.grantee))),
+        );
+        objects.insert(
+            "default_privileges",
+            sorted(self.default_privileges.iter().map(|p| {
+                format!("{} {} {} TO {}", p.schema, p.object_type, p.privilege, p.grantee)
+            })),
+        );
+        objects.insert(
+            "default_acls",
+            sorted(self.default_acls.iter().map(|a| {
+                format!(
+                    "{} {} {} {} TO {}",
+                    a.role,
+                    a.schema.as_deref().unwrap_or("*"),
+                    a.object_type,
+                    a.privilege,
+                    a.grantee
+                )
+            })),
+        );
+
+        let objects: BTreeMap<String, Vec<String>> =
+            objects.into_iter().map(|(kind, names)| (kind.to_string(), names)).collect();
+        let counts = objects.iter().map(|(kind, names)| (kind.clone(), names.len())).collect();
+        SchemaSummary { counts, objects }
+    }
+}
+
+/// Per-kind object counts and sorted names of a `DbSchema`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaSummary {
+    pub counts: BTreeMap<String, usize>,
+    pub objects: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -68,9 +157,35 @@ pub struct TableInfo {
     pub rls_enabled: bool,
     pub policies: Vec<PolicyInfo>,
     pub check_constraints: Vec<CheckConstraintInfo>,
+    #[serde(default)]
+    pub exclusion_constraints: Vec<ExclusionConstraintInfo>,
     pub grants: Vec<ObjectGrant>,
     pub comment: Option<String>,
     pub extension: Option<String>,
+    /// `WITH (...)` storage parameters such as `fillfactor`, sorted by name
+    #[serde(default)]
+    pub storage_params: Vec<(String, String)>,
+    /// Non-default tablespace
+    #[serde(default)]
+    pub tablespace: Option<String>,
+    /// `CREATE UNLOGGED TABLE`: not written to WAL, truncated after a crash
+    #[serde(default)]
+    pub is_unlogged: bool,
+    /// Partition key of a partitioned table, e.g. `RANGE (created_at)`
+    #[serde(default)]
+    pub partition_strategy: Option<String>,
+    /// Partitions attached to this table
+    #[serde(default)]
+    pub partitions: Vec<PartitionInfo>,
+}
+
+/// A `CREATE TABLE ... PARTITION OF` child of a partitioned table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PartitionInfo {
+    pub schema: String,
+    pub name: String,
+    /// Bound spec, e.g. `FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')` or `DEFAULT`
+    pub bound: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -90,6 +205,9 @@ pub struct ColumnInfo {
     pub enum_name: Option<String>,
     pub is_array: bool,
     pub comment: Option<String>,
+    /// 1-based position in the table; columns are keyed by name, so this keeps the CREATE TABLE order
+    #[serde(default)]
+    pub ordinal_position: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -180,6 +298,35 @@ pub struct DefaultPrivilege {
     pub privilege: String,
 }
 
+/// A `CREATE RULE` on a table or view, kept as opaque SQL so it round-trips
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuleInfo {
+    pub schema: String,
+    /// Table or view the rule is attached to
+    pub relation: String,
+    pub name: String,
+    /// Everything after `AS`, e.g. `ON DELETE TO public.items DO INSTEAD NOTHING`
+    pub definition: String,
+}
+
+impl RuleInfo {
+    /// `"schema"."relation"."name"`; rule names are only unique per relation
+    pub fn key(&self) -> String {
+        format!("{}.{}", qualified_name(&self.schema, &self.relation), quote_ident(&self.name))
+    }
+}
+
+/// One grant from `ALTER DEFAULT PRIVILEGES`, applied to objects `role` creates later
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DefaultAcl {
+    pub role: String,
+    /// None for defaults that apply in every schema
+    pub schema: Option<String>,
+    pub object_type: String, // "tables", "sequences", "functions", "types" or "schemas"
+    pub grantee: String,
+    pub privilege: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ViewInfo {
     pub schema: String,
@@ -187,6 +334,9 @@ pub struct ViewInfo {
     pub definition: String,
     pub is_materialized: bool,
     pub columns: Vec<ViewColumnInfo>,
+    /// Column names from `CREATE VIEW v (a, b)`; empty when the query's names are used
+    #[serde(default)]
+    pub column_aliases: Vec<String>,
     pub indexes: Vec<IndexInfo>,
     pub comment: Option<String>,
     pub with_options: Vec<String>,
@@ -264,13 +414,108 @@ pub struct DomainCheckConstraint {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct IndexInfo {
     pub index_name: String,
-    pub columns: Vec<String>,
+    #[serde(deserialize_with = "deserialize_index_columns")]
+    pub columns: Vec<IndexColumn>,
     pub is_unique: bool,
     pub is_primary: bool,
     pub owning_constraint: Option<String>,
     pub index_method: String,
     pub where_clause: Option<String>,
     pub expressions: Vec<String>,
+    /// Non-key columns of a covering index (`INCLUDE (...)`)
+    #[serde(default)]
+    pub include_columns: Vec<String>,
+    /// Unique index treating NULLs as equal (`NULLS NOT DISTINCT`, Postgres 15+)
+    #[serde(default)]
+    pub nulls_not_distinct: bool,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl IndexInfo {
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.name.as_str()).collect()
+    }
+}
+
+/// A key column of an index. Options at their defaults (ascending, the sort order's
+/// default NULLS ordering, the column's collation and default operator class) are unset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct IndexColumn {
+    pub name: String,
+    #[serde(default)]
+    pub descending: bool,
+    /// Explicit `NULLS FIRST`/`NULLS LAST`; `None` when it matches the sort order's default
+    #[serde(default)]
+    pub nulls_first: Option<bool>,
+    #[serde(default)]
+    pub collation: Option<String>,
+    #[serde(default)]
+    pub opclass: Option<String>,
+}
+
+impl IndexColumn {
+    pub fn new(name: impl Into<String>) -> Self {
+        IndexColumn { name: name.into(), ..Default::default() }
+    }
+
+    /// Drop a NULLS ordering that Postgres would pick anyway (`NULLS LAST` for
+    /// ascending, `NULLS FIRST` for descending)
+    pub fn normalized(mut self) -> Self {
+        if self.nulls_first == Some(self.descending) {
+            self.nulls_first = None;
+        }
+        self
+    }
+
+    /// The column as written in an index key list, e.g. `"name" text_pattern_ops DESC`
+    pub fn to_sql(&self) -> String {
+        let mut sql = quote_ident(&self.name);
+        if let Some(collation) = &self.collation {
+            sql.push_str(&format!(" COLLATE {}", quote_ident(collation)));
+        }
+        if let Some(opclass) = &self.opclass {
+            sql.push_str(&format!(" {}", opclass));
+        }
+        if self.descending {
+            sql.push_str(" DESC");
+        }
+        match self.nulls_first {
+            Some(true) => sql.push_str(" NULLS FIRST"),
+            Some(false) => sql.push_str(" NULLS LAST"),
+            None => {}
+        }
+        sql
+    }
+}
+
+/// Index key columns, also accepting the plain column names that schema caches
+/// written before `IndexColumn` kept sort options in
+fn deserialize_index_columns<'de, D>(deserializer: D) -> Result<Vec<IndexColumn>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredColumn {
+        Name(String),
+        Column(IndexColumn),
+    }
+
+    let stored = Vec::<StoredColumn>::deserialize(deserializer)?;
+    Ok(stored
+        .into_iter()
+        .map(|column| match column {
+            StoredColumn::Name(name) => IndexColumn::new(name),
+            StoredColumn::Column(column) => column,
+        })
+        .collect())
+}
+
+impl From<&str> for IndexColumn {
+    fn from(name: &str) -> Self {
+        IndexColumn::new(name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -290,6 +535,15 @@ pub struct PolicyInfo {
     pub roles: Vec<String>,
     pub qual: Option<String>,
     pub with_check: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ExclusionConstraintInfo {
+    pub name: String,
+    /// Definition starting at EXCLUDE, e.g. `EXCLUDE USING gist (room_id WITH =, during WITH &&)`
+    pub definition: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -297,4 +551,67 @@ pub struct CheckConstraintInfo {
     pub name: String,
     pub expression: String,
     pub columns: Vec<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_db_schema_serde_round_trip() {
+        let sql = r#"
+CREATE TYPE "public"."status" AS ENUM ('active', 'archived');
+CREATE TYPE "public"."address" AS (street text, city text);
+CREATE DOMAIN "public"."email" AS text CHECK (VALUE ~ '@');
+CREATE SEQUENCE "public"."order_seq" START WITH 100;
+CREATE TABLE "public"."orders" (
+    "id" bigint PRIMARY KEY,
+    "email" "public"."email" NOT NULL,
+    "status" "public"."status" DEFAULT 'active',
+    "total" numeric(10,2) CHECK (total >= 0)
+);
+CREATE INDEX "idx_orders_email" ON "public"."orders" ("email");
+ALTER TABLE "public"."orders" ENABLE ROW LEVEL SECURITY;
+CREATE POLICY "own orders" ON "public"."orders" FOR SELECT USING (true);
+CREATE VIEW "public"."active_orders" AS SELECT id FROM public.orders WHERE status = 'active';
+CREATE FUNCTION "public"."order_count"() RETURNS bigint LANGUAGE sql STABLE SET search_path = '' AS $$ SELECT count(*) FROM public.orders $$;
+GRANT SELECT ON "public"."orders" TO authenticated;
+"#;
+        let schema = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        assert!(!schema.tables.is_empty());
+        assert!(!schema.functions.is_empty());
+
+        let json = serde_json::to_string(&schema).unwrap();
+        let restored: DbSchema = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, schema);
+    }
+
+    #[test]
+    fn test_summary_counts_match_fixture() {
+        let sql = r#"
+CREATE TYPE "public"."status" AS ENUM ('active', 'archived');
+CREATE TABLE "public"."orders" ("id" bigint PRIMARY KEY, "status" "public"."status");
+CREATE TABLE "app"."audit" ("id" bigint PRIMARY KEY);
+CREATE INDEX "idx_orders_status" ON "public"."orders" ("status");
+ALTER TABLE "public"."orders" ENABLE ROW LEVEL SECURITY;
+CREATE POLICY "own orders" ON "public"."orders" FOR SELECT USING (true);
+CREATE VIEW "public"."active_orders" AS SELECT id FROM public.orders WHERE status = 'active';
+"#;
+        let schema = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        let summary = schema.summary();
+
+        assert_eq!(summary.counts["tables"], 2);
+        assert_eq!(summary.counts["enums"], 1);
+        assert_eq!(summary.counts["views"], 1);
+        assert_eq!(summary.counts["policies"], 1);
+        assert_eq!(summary.counts["functions"], 0);
+        assert_eq!(summary.objects["tables"], vec!["\"app\".\"audit\"", "\"public\".\"orders\""]);
+        assert!(summary.objects["indexes"].contains(&"\"public\".\"orders\".idx_orders_status".to_string()));
+
+        // Every kind is present, even when empty
+        assert_eq!(summary.counts.len(), summary.objects.len());
+        assert!(summary.counts.values().sum::<usize>() >= 6);
+    }
 }
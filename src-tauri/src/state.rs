@@ -1,25 +1,47 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
 use uuid::Uuid;
 use keyring::Entry;
 
-use crate::models::{AppData, LogEntry, Project};
+use crate::log_file::LogFile;
+use crate::models::{AccountInfo, AppData, LogEntry, Project, ProjectStatus, SavedQuery};
 use crate::schema::DbSchema;
 use crate::supabase_api::SupabaseApi;
 
 const SERVICE_NAME: &str = "harbor";
 const ACCESS_TOKEN_KEY: &str = "access_token";
 const OPENAI_KEY_KEY: &str = "openai_key";
+const MAX_LOGS_IN_MEMORY: usize = 1000;
+const HTTP_TIMEOUT_SECS: u64 = 120;
+const HTTP_CONNECT_TIMEOUT_SECS: u64 = 15;
+/// Introspection runs about fifteen queries at once; keep that many connections warm
+const HTTP_POOL_MAX_IDLE_PER_HOST: usize = 16;
+const HTTP_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+/// Keyring entry holding a named account's token
+fn account_key(name: &str) -> String {
+    format!("account:{}", name)
+}
+
+/// Keyring entry holding the database password generated for a remote project
+fn db_password_key(project_ref: &str) -> String {
+    format!("db_password:{}", project_ref)
+}
 
 #[derive(Error, Debug)]
 pub enum StateError {
     #[error("Project not found: {0}")]
     ProjectNotFound(Uuid),
+    #[error("Saved query not found: {0}")]
+    SavedQueryNotFound(Uuid),
+    #[error("Account not found: {0}")]
+    AccountNotFound(String),
     #[error("Failed to read data file: {0}")]
     ReadError(String),
     #[error("Failed to write data file: {0}")]
@@ -28,6 +50,8 @@ pub enum StateError {
     ParseError(String),
     #[error("Access token not configured")]
     NoAccessToken,
+    #[error("AUTH_EXPIRED: Access token is expired or invalid")]
+    AuthExpired,
 }
 
 pub type WatcherHandle = notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>;
@@ -37,21 +61,29 @@ pub struct AppState {
     pub logs: RwLock<Vec<LogEntry>>,
     pub watchers: RwLock<HashMap<Uuid, WatcherHandle>>,
     pub openai_key: RwLock<Option<String>>,
+    /// Tokens of the named accounts in `AppData::accounts`, loaded from the keyring
+    account_tokens: RwLock<HashMap<String, String>>,
     pub schema_cache: RwLock<HashMap<Uuid, DbSchema>>,
+    /// Set when the Management API rejected the token (401/403); cleared when a new token is set
+    pub token_expired: RwLock<bool>,
+    /// Shared by every API client, see `build_http_client`
     pub http_client: reqwest::Client,
+    /// In-flight AI conversions, cancellable per project
+    pub ai_requests: crate::ai::AiRequests,
+    /// Live Supabase log tails started with `stream_logs`
+    pub log_streams: crate::log_stream::LogStreams,
+    /// Held for the duration of a pull or push so only one runs per project
+    project_locks: RwLock<HashMap<Uuid, Arc<Mutex<()>>>>,
+    /// Rolling on-disk log history; None when `persist_logs` is off. Its own lock keeps
+    /// appends and rotation serialized without holding `logs` during file I/O.
+    log_file: Option<Mutex<LogFile>>,
     data_path: PathBuf,
+    /// Directory holding the per-project remote schema cache files
+    schema_cache_dir: PathBuf,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        const HTTP_TIMEOUT_SECS: u64 = 120;
-        const HTTP_CONNECT_TIMEOUT_SECS: u64 = 15;
-
-        let data_dir = Self::get_data_dir();
-        let data_path = data_dir.join("data.json");
-        
-        let (mut data, _) = Self::load_data(&data_path).unwrap_or_default();
-
         println!("[TOKEN] Loading token from keychain...");
         let access_token = match Entry::new(SERVICE_NAME, ACCESS_TOKEN_KEY) {
             Ok(entry) => match entry.get_password() {
@@ -69,7 +101,6 @@ impl AppState {
                 None
             }
         };
-        data.access_token = access_token.clone();
 
         println!("[OPENAI] Loading OpenAI key from keychain...");
         let openai_key_val = match Entry::new(SERVICE_NAME, OPENAI_KEY_KEY) {
@@ -83,29 +114,69 @@ impl AppState {
             Err(_) => None
         };
 
-        // Initialize state with loaded values
-        let openai_key = RwLock::new(openai_key_val);
+        Self::with_data_dir(Self::get_data_dir(), access_token, openai_key_val)
+    }
 
-        let http_client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
-            .connect_timeout(Duration::from_secs(HTTP_CONNECT_TIMEOUT_SECS))
-            .build()
-            .unwrap_or_else(|e| {
-                eprintln!("[HTTP] Failed to build client with timeouts: {}", e);
-                reqwest::Client::new()
-            });
+    /// Build state backed by `data_dir`, with credentials already loaded
+    fn with_data_dir(data_dir: PathBuf, access_token: Option<String>, openai_key: Option<String>) -> Self {
+        let data_path = data_dir.join("data.json");
+        let schema_cache_dir = data_dir.join("schema_cache");
+
+        let (mut data, _) = Self::load_data(&data_path).unwrap_or_default();
+        data.access_token = access_token;
+
+        let account_tokens: HashMap<String, String> = data
+            .accounts
+            .iter()
+            .filter_map(|name| {
+                let token = Entry::new(SERVICE_NAME, &account_key(name)).ok()?.get_password().ok()?;
+                Some((name.clone(), token))
+            })
+            .collect();
+
+        let log_file = data.persist_logs.then(|| LogFile::new(data_dir.join("logs")));
+        let logs = log_file
+            .as_ref()
+            .map(|f| f.load_recent(MAX_LOGS_IN_MEMORY))
+            .unwrap_or_default();
+
+        // Initialize state with loaded values
+        let openai_key = RwLock::new(openai_key);
 
         Self {
             data: RwLock::new(data),
-            logs: RwLock::new(Vec::new()),
+            logs: RwLock::new(logs),
             watchers: RwLock::new(HashMap::new()),
             openai_key,
+            account_tokens: RwLock::new(account_tokens),
             schema_cache: RwLock::new(HashMap::new()),
-            http_client,
+            token_expired: RwLock::new(false),
+            http_client: Self::build_http_client(),
+            ai_requests: crate::ai::AiRequests::default(),
+            log_streams: crate::log_stream::LogStreams::default(),
+            project_locks: RwLock::new(HashMap::new()),
+            log_file: log_file.map(Mutex::new),
             data_path,
+            schema_cache_dir,
         }
     }
     
+    /// The one HTTP client of the app. `SupabaseApi` and the AI client clone it, and
+    /// clones share its connection pool, so parallel queries reuse warm TLS connections.
+    fn build_http_client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+            .connect_timeout(Duration::from_secs(HTTP_CONNECT_TIMEOUT_SECS))
+            .pool_max_idle_per_host(HTTP_POOL_MAX_IDLE_PER_HOST)
+            .pool_idle_timeout(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .tcp_keepalive(Duration::from_secs(HTTP_POOL_IDLE_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|e| {
+                eprintln!("[HTTP] Failed to build client with timeouts: {}", e);
+                reqwest::Client::new()
+            })
+    }
+
     fn get_data_dir() -> PathBuf {
         let data_dir = dirs::data_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -198,6 +269,7 @@ impl AppState {
             .ok_or(StateError::ProjectNotFound(id))?;
 
         data.projects.remove(idx);
+        data.saved_queries.remove(&id);
         drop(data);
         self.save().await
     }
@@ -216,6 +288,118 @@ impl AppState {
         self.save().await
     }
 
+    /// Record a successful pull (`pushed == false`) or push
+    pub async fn record_sync(&self, id: Uuid, pushed: bool) -> Result<(), StateError> {
+        let mut data = self.data.write().await;
+        let project = data
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or(StateError::ProjectNotFound(id))?;
+
+        let now = chrono::Utc::now();
+        if pushed {
+            project.last_push_at = Some(now);
+        } else {
+            project.last_pull_at = Some(now);
+        }
+        drop(data);
+        self.save().await
+    }
+
+    /// Summarize a project's sync state. `drifted` comes from comparing the
+    /// remote fingerprint with the baseline, which needs the API.
+    pub async fn project_status(&self, id: Uuid, drifted: Option<bool>) -> Result<ProjectStatus, StateError> {
+        let project = self.get_project(id).await?;
+        let pending_function_changes = crate::sync::compute_edge_functions_diff(Path::new(&project.local_path))
+            .await
+            .map(|changed| changed.len())
+            .unwrap_or(0);
+
+        Ok(ProjectStatus {
+            linked: project.supabase_project_ref.is_some(),
+            watching: project.is_watching,
+            drifted,
+            last_pull: project.last_pull_at,
+            last_push: project.last_push_at,
+            pending_function_changes,
+        })
+    }
+
+    /// Detach a project from its Supabase project: stop watching, clear the
+    /// remote reference and drop schema state that belonged to the old remote
+    pub async fn unlink_project(&self, id: Uuid) -> Result<Project, StateError> {
+        self.stop_watcher(id).await;
+        self.log_streams.stop(id, None).await;
+
+        let mut data = self.data.write().await;
+        let project = data
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or(StateError::ProjectNotFound(id))?;
+
+        project.supabase_project_ref = None;
+        project.supabase_project_id = None;
+        project.is_watching = false;
+        project.updated_at = chrono::Utc::now();
+        let project = project.clone();
+        drop(data);
+        self.save().await?;
+
+        self.clear_cached_schema(id).await;
+        tokio::fs::remove_file(crate::sync::schema_baseline_path(&self.schema_cache_dir, id))
+            .await
+            .ok();
+
+        Ok(project)
+    }
+
+    // Saved query operations
+    pub async fn save_query(&self, project_id: Uuid, name: String, sql: String) -> Result<SavedQuery, StateError> {
+        self.get_project(project_id).await?;
+
+        let query = SavedQuery::new(name, sql);
+        let mut data = self.data.write().await;
+        data.saved_queries.entry(project_id).or_default().push(query.clone());
+        drop(data);
+        self.save().await?;
+        Ok(query)
+    }
+
+    pub async fn list_saved_queries(&self, project_id: Uuid) -> Vec<SavedQuery> {
+        let data = self.data.read().await;
+        data.saved_queries.get(&project_id).cloned().unwrap_or_default()
+    }
+
+    pub async fn get_saved_query(&self, project_id: Uuid, query_id: Uuid) -> Result<SavedQuery, StateError> {
+        let data = self.data.read().await;
+        data.saved_queries
+            .get(&project_id)
+            .and_then(|queries| queries.iter().find(|q| q.id == query_id))
+            .cloned()
+            .ok_or(StateError::SavedQueryNotFound(query_id))
+    }
+
+    pub async fn delete_saved_query(&self, project_id: Uuid, query_id: Uuid) -> Result<(), StateError> {
+        let mut data = self.data.write().await;
+        let queries = data
+            .saved_queries
+            .get_mut(&project_id)
+            .ok_or(StateError::SavedQueryNotFound(query_id))?;
+        let idx = queries
+            .iter()
+            .position(|q| q.id == query_id)
+            .ok_or(StateError::SavedQueryNotFound(query_id))?;
+
+        queries.remove(idx);
+        if queries.is_empty() {
+            data.saved_queries.remove(&project_id);
+        }
+        drop(data);
+        self.save().await
+    }
+
     // Watcher operations
     pub async fn add_watcher(&self, project_id: Uuid, watcher: WatcherHandle) {
         let mut watchers = self.watchers.write().await;
@@ -234,13 +418,21 @@ impl AppState {
 
     // Log operations
     pub async fn add_log(&self, log: LogEntry) {
-        let mut logs = self.logs.write().await;
-        logs.push(log);
+        {
+            let mut logs = self.logs.write().await;
+            logs.push(log.clone());
 
-        // Keep only last 1000 logs
-        if logs.len() > 1000 {
-            let drain_count = logs.len() - 1000;
-            logs.drain(0..drain_count);
+            // Keep only the most recent logs in memory
+            if logs.len() > MAX_LOGS_IN_MEMORY {
+                let drain_count = logs.len() - MAX_LOGS_IN_MEMORY;
+                logs.drain(0..drain_count);
+            }
+        }
+
+        if let Some(log_file) = &self.log_file {
+            if let Err(e) = log_file.lock().await.append(&log) {
+                eprintln!("[LOGS] Failed to persist log entry: {}", e);
+            }
         }
     }
 
@@ -258,14 +450,43 @@ impl AppState {
     }
 
     pub async fn clear_logs(&self, project_id: Option<Uuid>) {
-        let mut logs = self.logs.write().await;
-        if let Some(pid) = project_id {
-            logs.retain(|log| log.project_id != Some(pid));
-        } else {
-            logs.clear();
+        {
+            let mut logs = self.logs.write().await;
+            if let Some(pid) = project_id {
+                logs.retain(|log| log.project_id != Some(pid));
+            } else {
+                logs.clear();
+            }
+        }
+
+        if let Some(log_file) = &self.log_file {
+            let log_file = log_file.lock().await;
+            let result = match project_id {
+                Some(pid) => log_file.retain(|log| log.project_id != Some(pid)),
+                None => log_file.retain(|_| false),
+            };
+            if let Err(e) = result {
+                eprintln!("[LOGS] Failed to clear persisted logs: {}", e);
+            }
         }
     }
 
+    /// Write a project's (or all) log history to `path` as JSON lines, oldest first.
+    /// Uses the on-disk history when persistence is on, which reaches further back.
+    pub async fn export_logs(&self, project_id: Option<Uuid>, path: &std::path::Path) -> Result<usize, String> {
+        let history = match &self.log_file {
+            Some(log_file) => log_file.lock().await.load_all(),
+            None => self.logs.read().await.clone(),
+        };
+
+        let entries: Vec<LogEntry> = history
+            .into_iter()
+            .filter(|log| project_id.is_none() || log.project_id == project_id)
+            .collect();
+        crate::log_file::export_entries(&entries, path)?;
+        Ok(entries.len())
+    }
+
     // Access token operations
     pub async fn set_access_token(&self, token: String) -> Result<(), StateError> {
         println!("[TOKEN] set_access_token called");
@@ -276,12 +497,19 @@ impl AppState {
         let mut data = self.data.write().await;
         data.access_token = Some(token);
         drop(data);
+        *self.token_expired.write().await = false;
+        // Cached schemas may belong to another account
+        self.clear_remote_schema_caches().await;
         Ok(())
     }
 
+    /// Token of the active account, or the legacy single token when no account is active
     pub async fn get_access_token(&self) -> Option<String> {
         let data = self.data.read().await;
-        data.access_token.clone()
+        match &data.active_account {
+            Some(name) => self.account_tokens.read().await.get(name).cloned(),
+            None => data.access_token.clone(),
+        }
     }
 
     pub async fn clear_access_token(&self) -> Result<(), StateError> {
@@ -291,20 +519,148 @@ impl AppState {
         let mut data = self.data.write().await;
         data.access_token = None;
         drop(data);
+        *self.token_expired.write().await = false;
+        self.clear_remote_schema_caches().await;
         self.save().await
     }
 
     pub async fn has_access_token(&self) -> bool {
-        let data = self.data.read().await;
-        data.access_token.is_some()
+        self.get_access_token().await.is_some()
     }
 
-    /// Get a Supabase API client using the stored access token
+    /// Get a Supabase API client using the stored access token.
+    /// Fails with `AuthExpired` once the API has rejected the token, so callers
+    /// can prompt for re-auth instead of retrying.
     pub async fn get_api_client(&self) -> Result<SupabaseApi, StateError> {
         let token = self.get_access_token().await.ok_or(StateError::NoAccessToken)?;
+        if *self.token_expired.read().await {
+            return Err(StateError::AuthExpired);
+        }
         Ok(SupabaseApi::new(token, self.http_client.clone()))
     }
 
+    /// API client for a project, using the account it is bound to (if any)
+    pub async fn get_project_api_client(&self, project_id: Uuid) -> Result<SupabaseApi, StateError> {
+        let project = self.get_project(project_id).await?;
+        let query_timeout = project.introspection_query_timeout();
+        let Some(name) = project.account else {
+            return Ok(self.get_api_client().await?.with_query_timeout(query_timeout));
+        };
+        let token = self
+            .account_tokens
+            .read()
+            .await
+            .get(&name)
+            .cloned()
+            .ok_or(StateError::AccountNotFound(name))?;
+        Ok(SupabaseApi::new(token, self.http_client.clone()).with_query_timeout(query_timeout))
+    }
+
+    // Account operations
+    /// Store a named account's token in the keyring, replacing an existing one with the same name
+    pub async fn add_account(&self, name: String, token: String) -> Result<(), StateError> {
+        let entry = Entry::new(SERVICE_NAME, &account_key(&name)).map_err(|e| StateError::WriteError(e.to_string()))?;
+        entry.set_password(&token).map_err(|e| StateError::WriteError(e.to_string()))?;
+        self.insert_account(name, token).await
+    }
+
+    async fn insert_account(&self, name: String, token: String) -> Result<(), StateError> {
+        self.account_tokens.write().await.insert(name.clone(), token);
+
+        let mut data = self.data.write().await;
+        if !data.accounts.contains(&name) {
+            data.accounts.push(name);
+        }
+        drop(data);
+        self.save().await
+    }
+
+    pub async fn list_accounts(&self) -> Vec<AccountInfo> {
+        let data = self.data.read().await;
+        data.accounts
+            .iter()
+            .map(|name| AccountInfo {
+                name: name.clone(),
+                active: data.active_account.as_ref() == Some(name),
+            })
+            .collect()
+    }
+
+    pub async fn active_account(&self) -> Option<String> {
+        self.data.read().await.active_account.clone()
+    }
+
+    /// Switch the default account; None falls back to the legacy access token
+    pub async fn set_active_account(&self, name: Option<String>) -> Result<(), StateError> {
+        let mut data = self.data.write().await;
+        if let Some(name) = &name {
+            if !data.accounts.contains(name) {
+                return Err(StateError::AccountNotFound(name.clone()));
+            }
+        }
+        data.active_account = name;
+        drop(data);
+
+        *self.token_expired.write().await = false;
+        // Cached schemas may belong to another account
+        self.clear_remote_schema_caches().await;
+        self.save().await
+    }
+
+    pub async fn remove_account(&self, name: &str) -> Result<(), StateError> {
+        let mut data = self.data.write().await;
+        let idx = data
+            .accounts
+            .iter()
+            .position(|a| a == name)
+            .ok_or_else(|| StateError::AccountNotFound(name.to_string()))?;
+        data.accounts.remove(idx);
+        if data.active_account.as_deref() == Some(name) {
+            data.active_account = None;
+        }
+        for project in data.projects.iter_mut().filter(|p| p.account.as_deref() == Some(name)) {
+            project.account = None;
+        }
+        drop(data);
+        self.account_tokens.write().await.remove(name);
+
+        if let Ok(entry) = Entry::new(SERVICE_NAME, &account_key(name)) {
+            entry.delete_credential().ok();
+        }
+        self.save().await
+    }
+
+    /// Record that the API rejected the current token
+    pub async fn mark_token_expired(&self) {
+        *self.token_expired.write().await = true;
+    }
+
+    /// Shared error conversion for commands that call the API: a rejected token
+    /// surfaces as an `AUTH_EXPIRED:` error, whatever context it was wrapped in,
+    /// and is remembered so later calls fail fast until the user signs in again.
+    pub async fn surface_auth_error<T>(&self, result: Result<T, String>) -> Result<T, String> {
+        match result {
+            Err(err) => match crate::supabase_api::auth_expired_message(&err) {
+                Some(message) => {
+                    self.mark_token_expired().await;
+                    Err(message)
+                }
+                None => Err(err),
+            },
+            ok => ok,
+        }
+    }
+
+    // Database password operations
+    pub fn set_db_password(&self, project_ref: &str, password: &str) -> Result<(), StateError> {
+        let entry = Entry::new(SERVICE_NAME, &db_password_key(project_ref)).map_err(|e| StateError::WriteError(e.to_string()))?;
+        entry.set_password(password).map_err(|e| StateError::WriteError(e.to_string()))
+    }
+
+    pub fn get_db_password(&self, project_ref: &str) -> Option<String> {
+        Entry::new(SERVICE_NAME, &db_password_key(project_ref)).ok()?.get_password().ok()
+    }
+
     // OpenAI key operations
     pub async fn set_openai_key(&self, key: String) -> Result<(), StateError> {
         println!("[OPENAI] set_openai_key called");
@@ -349,6 +705,55 @@ impl AppState {
     pub async fn clear_cached_schema(&self, project_id: Uuid) {
         let mut cache = self.schema_cache.write().await;
         cache.remove(&project_id);
+        drop(cache);
+        tokio::fs::remove_file(crate::sync::schema_cache_path(&self.schema_cache_dir, project_id))
+            .await
+            .ok();
+    }
+
+    /// Last introspected remote schema persisted for the project, if any
+    pub async fn load_remote_schema_cache(&self, project_id: Uuid) -> Option<crate::sync::CachedSchema> {
+        crate::sync::read_schema_cache(&crate::sync::schema_cache_path(&self.schema_cache_dir, project_id)).await
+    }
+
+    pub async fn save_remote_schema_cache(&self, project_id: Uuid, cached: &crate::sync::CachedSchema) {
+        let path = crate::sync::schema_cache_path(&self.schema_cache_dir, project_id);
+        if let Err(e) = crate::sync::write_schema_cache(&path, cached).await {
+            eprintln!("[CACHE] Failed to write schema cache for {}: {}", project_id, e);
+        }
+    }
+
+    /// Remote schema as of the last pull/push, for divergence checks
+    pub async fn load_schema_baseline(&self, project_id: Uuid) -> Option<crate::sync::CachedSchema> {
+        crate::sync::read_schema_cache(&crate::sync::schema_baseline_path(&self.schema_cache_dir, project_id)).await
+    }
+
+    pub async fn save_schema_baseline(&self, project_id: Uuid, baseline: &crate::sync::CachedSchema) {
+        let path = crate::sync::schema_baseline_path(&self.schema_cache_dir, project_id);
+        if let Err(e) = crate::sync::write_schema_cache(&path, baseline).await {
+            eprintln!("[CACHE] Failed to write schema baseline for {}: {}", project_id, e);
+        }
+    }
+
+    /// Claim the project for a pull, push, migration or seed run. Fails instead of waiting when one is
+    /// already running; the claim is released when the guard is dropped.
+    pub async fn try_lock_project(&self, project_id: Uuid) -> Result<OwnedMutexGuard<()>, String> {
+        let lock = self.project_locks.write().await.entry(project_id).or_default().clone();
+        lock.try_lock_owned()
+            .map_err(|_| "Operation already in progress for this project".to_string())
+    }
+
+    /// Drop every persisted remote schema, e.g. when the access token changes.
+    /// Push baselines stay: they record what the remote looked like, whoever reads it.
+    pub async fn clear_remote_schema_caches(&self) {
+        let Ok(mut entries) = tokio::fs::read_dir(&self.schema_cache_dir).await else {
+            return;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if !entry.file_name().to_string_lossy().ends_with(".baseline.json") {
+                tokio::fs::remove_file(entry.path()).await.ok();
+            }
+        }
     }
 }
 
@@ -357,3 +762,342 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LogSource;
+
+    #[tokio::test]
+    async fn test_api_clients_share_connection_pool() {
+        use std::io::{Read, Write};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Keep-alive server answering `[]` to every request, counting TCP connections
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                counter.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = stream.read(&mut buf).unwrap_or(0);
+                        if n == 0 {
+                            break;
+                        }
+                        let requests = String::from_utf8_lossy(&buf[..n]).matches("\r\n\r\n").count();
+                        for _ in 0..requests {
+                            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n[]";
+                            if stream.write_all(response.as_bytes()).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let state = AppState::with_data_dir(dir.clone(), Some("token".to_string()), None);
+
+        for _ in 0..3 {
+            let api = state.get_api_client().await.unwrap().with_base_url(format!("http://{}", addr));
+            assert!(api.list_projects().await.unwrap().is_empty());
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1, "each client opened its own connection");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_saved_query_round_trip() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        let project = state
+            .add_project(Project::new("demo".to_string(), "/tmp/demo".to_string()))
+            .await
+            .unwrap();
+
+        let first = state
+            .save_query(project.id, "Slow queries".to_string(), "SELECT * FROM pg_stat_statements".to_string())
+            .await
+            .unwrap();
+        let second = state
+            .save_query(project.id, "Locks".to_string(), "SELECT * FROM pg_locks".to_string())
+            .await
+            .unwrap();
+        assert!(state.save_query(Uuid::new_v4(), "x".to_string(), "SELECT 1".to_string()).await.is_err());
+
+        // Saved queries survive a restart
+        let reloaded = AppState::with_data_dir(dir.clone(), None, None);
+        assert_eq!(reloaded.list_saved_queries(project.id).await, vec![first.clone(), second.clone()]);
+        assert_eq!(reloaded.get_saved_query(project.id, second.id).await.unwrap(), second);
+
+        reloaded.delete_saved_query(project.id, first.id).await.unwrap();
+        assert!(reloaded.delete_saved_query(project.id, first.id).await.is_err());
+        assert_eq!(reloaded.list_saved_queries(project.id).await, vec![second]);
+
+        let reloaded = AppState::with_data_dir(dir.clone(), None, None);
+        assert_eq!(reloaded.list_saved_queries(project.id).await.len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_logs_are_loaded_on_startup() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let project_id = Uuid::new_v4();
+
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        state.add_log(LogEntry::info(Some(project_id), LogSource::Schema, "first".to_string())).await;
+        state.add_log(LogEntry::info(None, LogSource::System, "second".to_string())).await;
+
+        let restarted = AppState::with_data_dir(dir.clone(), None, None);
+        let messages: Vec<String> = restarted.get_logs(None, 10).await.into_iter().map(|l| l.message).collect();
+        assert_eq!(messages, vec!["second", "first"]);
+
+        let out = dir.join("export.jsonl");
+        assert_eq!(restarted.export_logs(Some(project_id), &out).await.unwrap(), 1);
+        assert_eq!(fs::read_to_string(&out).unwrap().lines().count(), 1);
+
+        restarted.clear_logs(None).await;
+        let restarted = AppState::with_data_dir(dir.clone(), None, None);
+        assert!(restarted.get_logs(None, 10).await.is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_unlink_project_clears_ref_and_watching() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        let mut project = Project::new("demo".to_string(), "/tmp/demo".to_string());
+        project.supabase_project_ref = Some("abcdefghijklmnopqrst".to_string());
+        project.supabase_project_id = Some("abcdefghijklmnopqrst".to_string());
+        let project = state.add_project(project).await.unwrap();
+        state.set_project_watching(project.id, true).await.unwrap();
+
+        state.unlink_project(project.id).await.unwrap();
+
+        let unlinked = state.get_project(project.id).await.unwrap();
+        assert!(unlinked.supabase_project_ref.is_none());
+        assert!(unlinked.supabase_project_id.is_none());
+        assert!(!unlinked.is_watching);
+        assert!(!state.is_watching(project.id).await);
+        assert!(state.unlink_project(Uuid::new_v4()).await.is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_switching_accounts_changes_client_token() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let state = AppState::with_data_dir(dir.clone(), Some("legacy-token".to_string()), None);
+        state.insert_account("personal".to_string(), "personal-token".to_string()).await.unwrap();
+        state.insert_account("work".to_string(), "work-token".to_string()).await.unwrap();
+
+        // No active account: the legacy token is used
+        assert_eq!(state.get_api_client().await.unwrap().access_token(), "legacy-token");
+
+        state.set_active_account(Some("work".to_string())).await.unwrap();
+        assert_eq!(state.get_api_client().await.unwrap().access_token(), "work-token");
+        assert_eq!(
+            state.list_accounts().await,
+            vec![
+                AccountInfo { name: "personal".to_string(), active: false },
+                AccountInfo { name: "work".to_string(), active: true },
+            ]
+        );
+
+        state.set_active_account(Some("personal".to_string())).await.unwrap();
+        assert_eq!(state.get_api_client().await.unwrap().access_token(), "personal-token");
+        assert!(state.set_active_account(Some("unknown".to_string())).await.is_err());
+
+        // A project bound to an account keeps using it regardless of the active one
+        let mut project = Project::new("demo".to_string(), "/tmp/demo".to_string());
+        project.account = Some("work".to_string());
+        let project = state.add_project(project).await.unwrap();
+        assert_eq!(state.get_project_api_client(project.id).await.unwrap().access_token(), "work-token");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_project_api_client_uses_project_query_timeout() {
+        use crate::supabase_api::ManagementApi;
+
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let state = AppState::with_data_dir(dir.clone(), Some("token".to_string()), None);
+
+        let project = state.add_project(Project::new("demo".to_string(), "/tmp/demo".to_string())).await.unwrap();
+        let client = state.get_project_api_client(project.id).await.unwrap();
+        assert_eq!(client.query_timeout(), crate::introspection::DEFAULT_QUERY_TIMEOUT);
+
+        let mut slow = Project::new("slow".to_string(), "/tmp/slow".to_string());
+        slow.introspection_query_timeout_secs = Some(30);
+        let slow = state.add_project(slow).await.unwrap();
+        let client = state.get_project_api_client(slow.id).await.unwrap();
+        assert_eq!(client.query_timeout(), Duration::from_secs(30));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_switching_accounts_keeps_push_baselines() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        state.insert_account("work".to_string(), "work-token".to_string()).await.unwrap();
+        let project_id = Uuid::new_v4();
+        let cached = crate::sync::CachedSchema {
+            fingerprint: "abc".to_string(),
+            cached_at: chrono::Utc::now(),
+            schema: crate::schema::DbSchema::new(),
+        };
+        state.save_remote_schema_cache(project_id, &cached).await;
+        state.save_schema_baseline(project_id, &cached).await;
+
+        state.set_active_account(Some("work".to_string())).await.unwrap();
+        assert!(state.load_remote_schema_cache(project_id).await.is_none());
+        assert_eq!(state.load_schema_baseline(project_id).await.unwrap().fingerprint, "abc");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_partial_auto_pull_is_recorded_and_resumed() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Introspection succeeds but listing edge functions fails
+        let status = crate::sync::run_pull_steps(
+            None,
+            async { Ok(()) },
+            async { Err("Failed to list functions: 500".to_string()) },
+        )
+        .await;
+        assert!(status.schema_pulled);
+        assert!(!status.functions_pulled);
+        assert_eq!(status.errors, vec!["Edge functions: Failed to list functions: 500"]);
+
+        // The project is still created, with the partial status
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        let mut project = Project::with_remote(
+            "demo".to_string(),
+            "/tmp/demo".to_string(),
+            "abcdefghijklmnopqrst".to_string(),
+            "abcdefghijklmnopqrst".to_string(),
+        );
+        project.last_pull_status = Some(status);
+        let project = state.add_project(project).await.unwrap();
+
+        let reloaded = AppState::with_data_dir(dir.clone(), None, None);
+        let previous = reloaded.get_project(project.id).await.unwrap().last_pull_status.unwrap();
+        assert!(!previous.is_complete());
+
+        // Resuming only retries the failed part
+        let schema_retried = std::cell::Cell::new(false);
+        let resumed = crate::sync::run_pull_steps(
+            Some(&previous),
+            async {
+                schema_retried.set(true);
+                Ok(())
+            },
+            async { Ok(()) },
+        )
+        .await;
+        assert!(!schema_retried.get());
+        assert!(resumed.is_complete());
+        assert!(resumed.errors.is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_project_status_aggregates_project_state() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        let project_dir = dir.join("project");
+        let functions_dir = project_dir.join("supabase/functions");
+
+        // One function deployed and unchanged, one never deployed
+        fs::create_dir_all(functions_dir.join("clean")).unwrap();
+        fs::create_dir_all(functions_dir.join("dirty")).unwrap();
+        fs::write(functions_dir.join("clean/index.ts"), "Deno.serve(() => new Response('a'));").unwrap();
+        fs::write(functions_dir.join("dirty/index.ts"), "Deno.serve(() => new Response('b'));").unwrap();
+        let files = crate::sync::function_deploy_files(&functions_dir.join("clean")).await.unwrap();
+        fs::write(functions_dir.join("clean/.harbor_hash"), crate::sync::hash_deploy_files(&files).await.unwrap()).unwrap();
+
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        let local = state
+            .add_project(Project::new("local".to_string(), project_dir.to_string_lossy().to_string()))
+            .await
+            .unwrap();
+
+        let status = state.project_status(local.id, None).await.unwrap();
+        assert_eq!(
+            status,
+            ProjectStatus {
+                linked: false,
+                watching: false,
+                drifted: None,
+                last_pull: None,
+                last_push: None,
+                pending_function_changes: 1,
+            }
+        );
+
+        let mut linked = Project::new("linked".to_string(), dir.join("missing").to_string_lossy().to_string());
+        linked.supabase_project_ref = Some("abcdefghijklmnopqrst".to_string());
+        let linked = state.add_project(linked).await.unwrap();
+        state.set_project_watching(linked.id, true).await.unwrap();
+        state.record_sync(linked.id, false).await.unwrap();
+        state.record_sync(linked.id, true).await.unwrap();
+
+        // Timestamps are persisted with the project
+        let reloaded = AppState::with_data_dir(dir.clone(), None, None);
+        let status = reloaded.project_status(linked.id, Some(true)).await.unwrap();
+        assert!(status.linked);
+        assert!(status.watching);
+        assert_eq!(status.drifted, Some(true));
+        assert!(status.last_pull.is_some());
+        assert!(status.last_push >= status.last_pull);
+        assert_eq!(status.pending_function_changes, 0);
+
+        assert!(reloaded.project_status(Uuid::new_v4(), None).await.is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_push_on_same_project_is_rejected() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_state_{}", Uuid::new_v4()));
+        let state = AppState::with_data_dir(dir.clone(), None, None);
+        let project_id = Uuid::new_v4();
+
+        let first_push = state.try_lock_project(project_id).await.unwrap();
+        let second_push = state.try_lock_project(project_id).await;
+        assert_eq!(second_push.unwrap_err(), "Operation already in progress for this project");
+
+        // Other projects aren't blocked
+        assert!(state.try_lock_project(Uuid::new_v4()).await.is_ok());
+
+        drop(first_push);
+        assert!(state.try_lock_project(project_id).await.is_ok());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,9 +1,19 @@
+use futures::future::BoxFuture;
+use futures::Stream;
 use reqwest::multipart::{Form, Part};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use thiserror::Error;
+use tokio::io::AsyncReadExt;
 
 const SUPABASE_API_BASE: &str = "https://api.supabase.com";
 
+/// Largest total size of the files in one function deploy (the deploy API's 20 MB limit)
+pub const MAX_FUNCTION_UPLOAD_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Size of the chunks files on disk are read in while a deploy is uploaded
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("HTTP request failed: {0}")]
@@ -16,6 +26,56 @@ pub enum ApiError {
     MissingProjectRef,
     #[error("File read error: {0}")]
     FileReadError(String),
+    #[error("Invalid log filter: {0}")]
+    InvalidLogFilter(String),
+    /// 401: the access token is expired or revoked and the user needs to sign in again.
+    /// A 403 is a permission problem on one project and stays an `ApiError`.
+    #[error("AUTH_EXPIRED: {message}")]
+    AuthExpired { status: u16, message: String },
+    /// 5xx, 408 or 429: worth retrying later, the token is fine
+    #[error("Supabase API temporarily unavailable: {status} - {message}")]
+    Transient { status: u16, message: String },
+    /// Rejected before uploading anything
+    #[error("Function files total {size} bytes, over the deploy limit of {limit} bytes")]
+    UploadTooLarge { size: u64, limit: u64 },
+}
+
+impl ApiError {
+    /// Classify an unsuccessful HTTP response
+    pub fn from_status(status: u16, message: String) -> Self {
+        match status {
+            401 => ApiError::AuthExpired { status, message },
+            408 | 429 | 500..=599 => ApiError::Transient { status, message },
+            _ => ApiError::ApiError { status, message },
+        }
+    }
+
+    pub fn is_auth_expired(&self) -> bool {
+        matches!(self, ApiError::AuthExpired { .. })
+    }
+
+    /// Server-side or network failures that say nothing about the token
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Transient { .. } => true,
+            ApiError::RequestFailed(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+const AUTH_EXPIRED_PREFIX: &str = "AUTH_EXPIRED: ";
+
+/// If `error` came from an `AuthExpired` error, possibly behind context like
+/// "Failed to fetch ...: ", the same message with `AUTH_EXPIRED:` moved to the front
+pub fn auth_expired_message(error: &str) -> Option<String> {
+    let pos = error.find(AUTH_EXPIRED_PREFIX)?;
+    Some(format!(
+        "{}{}{}",
+        AUTH_EXPIRED_PREFIX,
+        &error[..pos],
+        &error[pos + AUTH_EXPIRED_PREFIX.len()..]
+    ))
 }
 
 #[derive(Debug, Serialize)]
@@ -31,6 +91,10 @@ pub struct QueryResponse {
     pub result: Option<serde_json::Value>,
     #[serde(default)]
     pub error: Option<String>,
+    /// Columns of the first row in the order the query returned them (`result` rows
+    /// are maps sorted by key); see `query::first_row_columns`
+    #[serde(skip)]
+    pub columns: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,6 +112,27 @@ pub struct Organization {
     pub name: String,
 }
 
+/// Region codes accepted by project creation, used when the regions endpoint is unavailable
+pub const DEFAULT_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
+    "ca-central-1",
+    "sa-east-1",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "eu-central-1",
+    "eu-central-2",
+    "eu-north-1",
+    "ap-south-1",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-northeast-1",
+    "ap-northeast-2",
+];
+
 #[derive(Debug, Serialize)]
 struct CreateProjectBody {
     name: String,
@@ -94,6 +179,75 @@ pub struct DeployResponse {
     pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
 
+/// A file uploaded by `deploy_function`, under its path in the function bundle
+#[derive(Debug, Clone)]
+pub enum DeployFile {
+    /// Contents already in memory
+    Bytes { path: String, content: Vec<u8> },
+    /// Read from disk in chunks while the request body is sent
+    Disk { path: String, source: PathBuf, size: u64 },
+}
+
+impl DeployFile {
+    pub fn path(&self) -> &str {
+        match self {
+            DeployFile::Bytes { path, .. } | DeployFile::Disk { path, .. } => path,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match self {
+            DeployFile::Bytes { content, .. } => content.len() as u64,
+            DeployFile::Disk { size, .. } => *size,
+        }
+    }
+
+    fn into_part(self) -> Part {
+        match self {
+            DeployFile::Bytes { path, content } => Part::bytes(content).file_name(path),
+            DeployFile::Disk { path, source, size } => {
+                let body = reqwest::Body::wrap_stream(file_chunks(source, size));
+                Part::stream_with_length(body, size).file_name(path)
+            }
+        }
+    }
+}
+
+impl From<(String, Vec<u8>)> for DeployFile {
+    fn from((path, content): (String, Vec<u8>)) -> Self {
+        DeployFile::Bytes { path, content }
+    }
+}
+
+/// The contents of `source`, opened when the first chunk is polled. The part was
+/// sized from an earlier stat, so a file that has grown or shrunk since fails the
+/// upload rather than sending a body that disagrees with its Content-Length.
+fn file_chunks(source: PathBuf, size: u64) -> impl Stream<Item = Result<Vec<u8>, std::io::Error>> + Send + 'static {
+    futures::stream::try_unfold((None, 0u64), move |(file, sent): (Option<tokio::fs::File>, u64)| {
+        let source = source.clone();
+        async move {
+            let mut file = match file {
+                Some(file) => file,
+                None => tokio::fs::File::open(&source).await?,
+            };
+            let mut chunk = vec![0u8; UPLOAD_CHUNK_SIZE];
+            let read = file.read(&mut chunk).await?;
+            let sent = sent + read as u64;
+            if sent > size || (read == 0 && sent < size) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} changed while uploading: expected {} bytes", source.display(), size),
+                ));
+            }
+            if read == 0 {
+                return Ok(None);
+            }
+            chunk.truncate(read);
+            Ok(Some((chunk, (Some(file), sent))))
+        }
+    })
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ApiKey {
     pub api_key: String,
@@ -141,6 +295,8 @@ pub struct FunctionBody {
 pub struct SupabaseApi {
     client: reqwest::Client,
     access_token: String,
+    base_url: String,
+    query_timeout: std::time::Duration,
 }
 
 impl SupabaseApi {
@@ -148,16 +304,34 @@ impl SupabaseApi {
         Self {
             client,
             access_token,
+            base_url: SUPABASE_API_BASE.to_string(),
+            query_timeout: crate::introspection::DEFAULT_QUERY_TIMEOUT,
         }
     }
 
+    /// How long each introspection query through this client may run
+    pub fn with_query_timeout(mut self, query_timeout: std::time::Duration) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    /// Point the client at a different Management API host (used by tests)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
     fn auth_header(&self) -> String {
         format!("Bearer {}", self.access_token)
     }
 
     /// List all projects accessible by the access token
     pub async fn list_projects(&self) -> Result<Vec<Project>, ApiError> {
-        let url = format!("{}/v1/projects", SUPABASE_API_BASE);
+        let url = format!("{}/v1/projects", self.base_url);
 
         let response = self
             .client
@@ -169,7 +343,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
@@ -177,7 +351,7 @@ impl SupabaseApi {
 
     /// List all organizations
     pub async fn list_organizations(&self) -> Result<Vec<Organization>, ApiError> {
-        let url = format!("{}/v1/organizations", SUPABASE_API_BASE);
+        let url = format!("{}/v1/organizations", self.base_url);
 
         let response = self
             .client
@@ -189,12 +363,37 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
     }
 
+    /// Region codes new projects in the organization can be created in
+    pub async fn list_available_regions(&self, organization_id: &str) -> Result<Vec<String>, ApiError> {
+        let url = format!(
+            "{}/v1/projects/available-regions?organization_slug={}",
+            self.base_url,
+            urlencoding::encode(organization_id)
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response.text().await.unwrap_or_default();
+            return Err(ApiError::from_status(status, message));
+        }
+
+        let val: serde_json::Value = response.json().await?;
+        Ok(parse_region_codes(&val))
+    }
+
     /// Create a new project
     pub async fn create_project(
         &self,
@@ -203,7 +402,7 @@ impl SupabaseApi {
         db_pass: &str,
         region: &str,
     ) -> Result<Project, ApiError> {
-        let url = format!("{}/v1/projects", SUPABASE_API_BASE);
+        let url = format!("{}/v1/projects", self.base_url);
 
         let body = CreateProjectBody {
             name: name.to_string(),
@@ -225,7 +424,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
@@ -233,7 +432,7 @@ impl SupabaseApi {
 
     /// Get a specific project by reference
     pub async fn get_project(&self, project_ref: &str) -> Result<Project, ApiError> {
-        let url = format!("{}/v1/projects/{}", SUPABASE_API_BASE, project_ref);
+        let url = format!("{}/v1/projects/{}", self.base_url, project_ref);
 
         let response = self
             .client
@@ -245,7 +444,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
@@ -260,7 +459,7 @@ impl SupabaseApi {
     ) -> Result<QueryResponse, ApiError> {
         let url = format!(
             "{}/v1/projects/{}/database/query",
-            SUPABASE_API_BASE, project_ref
+            self.base_url, project_ref
         );
 
         let body = QueryRequest {
@@ -281,7 +480,7 @@ impl SupabaseApi {
         if !status.is_success() {
             let status_code = status.as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status: status_code, message });
+            return Err(ApiError::from_status(status_code, message));
         }
 
         let body_text = response.text().await?;
@@ -299,13 +498,15 @@ impl SupabaseApi {
             return Ok(QueryResponse {
                 result: Some(val),
                 error: None,
+                columns: crate::query::first_row_columns(&body_text),
             });
         }
 
         if let serde_json::Value::Object(ref map) = val {
             if map.contains_key("result") || map.contains_key("error") {
                  // Try standard deserialization
-                 if let Ok(resp) = serde_json::from_value::<QueryResponse>(val.clone()) {
+                 if let Ok(mut resp) = serde_json::from_value::<QueryResponse>(val.clone()) {
+                      resp.columns = crate::query::first_row_columns(&body_text);
                       // Validate inner result is array if present
                       if let Some(res) = &resp.result {
                           if !res.is_array() {
@@ -330,7 +531,7 @@ impl SupabaseApi {
 
     /// List all edge functions for a project
     pub async fn list_functions(&self, project_ref: &str) -> Result<Vec<EdgeFunction>, ApiError> {
-        let url = format!("{}/v1/projects/{}/functions", SUPABASE_API_BASE, project_ref);
+        let url = format!("{}/v1/projects/{}/functions", self.base_url, project_ref);
 
         let response = self
             .client
@@ -342,7 +543,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         let body_text = response.text().await?;
@@ -360,7 +561,8 @@ impl SupabaseApi {
 
     /// Deploy an edge function
     ///
-    /// files is a vector of (relative_path, content) pairs for all files in the function
+    /// files are all files in the function; ones on disk are streamed rather than buffered,
+    /// and the total may not exceed `MAX_FUNCTION_UPLOAD_BYTES`
     /// entrypoint is the main file name (e.g., "index.ts")
     /// bundle_only: if true, bundles the function without persisting (for use with bulk_update_functions)
     pub async fn deploy_function(
@@ -369,30 +571,36 @@ impl SupabaseApi {
         slug: &str,
         name: &str,
         entrypoint: &str,
-        files: Vec<(String, Vec<u8>)>,
+        files: Vec<DeployFile>,
         bundle_only: bool,
     ) -> Result<DeployResponse, ApiError> {
+        let size: u64 = files.iter().map(DeployFile::size).sum();
+        if size > MAX_FUNCTION_UPLOAD_BYTES {
+            return Err(ApiError::UploadTooLarge { size, limit: MAX_FUNCTION_UPLOAD_BYTES });
+        }
+
         let url = if bundle_only {
             format!(
                 "{}/v1/projects/{}/functions/deploy?slug={}&bundleOnly=true",
-                SUPABASE_API_BASE, project_ref, slug
+                self.base_url, project_ref, slug
             )
         } else {
             format!(
                 "{}/v1/projects/{}/functions/deploy?slug={}",
-                SUPABASE_API_BASE, project_ref, slug
+                self.base_url, project_ref, slug
             )
         };
 
         // Detect import map file (deno.json or import_map.json)
         let import_map_path = files.iter()
-            .find(|(path, _)| {
+            .map(DeployFile::path)
+            .find(|path| {
                 let lower = path.to_lowercase();
                 lower == "deno.json" || lower == "deno.jsonc" || 
                 lower == "import_map.json" || lower.ends_with("/deno.json") ||
                 lower.ends_with("/deno.jsonc") || lower.ends_with("/import_map.json")
             })
-            .map(|(path, _)| path.clone());
+            .map(|path| path.to_string());
 
         let metadata = FunctionMetadata {
             entrypoint_path: entrypoint.to_string(),
@@ -408,7 +616,8 @@ impl SupabaseApi {
             .text("metadata", metadata_json);
 
         // Add all files to the form
-        for (path, content) in files {
+        for file in files {
+            let path = file.path();
             let mime_type = if path.ends_with(".ts") {
                 "application/typescript"
             } else if path.ends_with(".js") {
@@ -421,8 +630,7 @@ impl SupabaseApi {
             
             form = form.part(
                 "file",
-                Part::bytes(content)
-                    .file_name(path)
+                file.into_part()
                     .mime_str(mime_type)
                     .map_err(|e| ApiError::FileReadError(e.to_string()))?,
             );
@@ -439,7 +647,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
@@ -456,7 +664,7 @@ impl SupabaseApi {
     ) -> Result<(), ApiError> {
         let url = format!(
             "{}/v1/projects/{}/functions",
-            SUPABASE_API_BASE, project_ref
+            self.base_url, project_ref
         );
 
         let mut payload = functions.to_vec();
@@ -477,7 +685,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(())
@@ -491,7 +699,7 @@ impl SupabaseApi {
     ) -> Result<(), ApiError> {
         let url = format!(
             "{}/v1/projects/{}/functions/{}",
-            SUPABASE_API_BASE, project_ref, function_slug
+            self.base_url, project_ref, function_slug
         );
 
         let response = self
@@ -504,7 +712,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(())
@@ -520,7 +728,7 @@ impl SupabaseApi {
     ) -> Result<FunctionBody, ApiError> {
         let url = format!(
             "{}/v1/projects/{}/functions/{}/body",
-            SUPABASE_API_BASE, project_ref, function_slug
+            self.base_url, project_ref, function_slug
         );
 
         let response = self
@@ -534,7 +742,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         let content_type = response
@@ -678,7 +886,7 @@ impl SupabaseApi {
     ) -> Result<serde_json::Value, ApiError> {
         let mut url = format!(
             "{}/v1/projects/{}/analytics/endpoints/logs.all",
-            SUPABASE_API_BASE, project_ref
+            self.base_url, project_ref
         );
 
         let mut query_params = Vec::new();
@@ -706,7 +914,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         let val: serde_json::Value = response.json().await?;
@@ -739,60 +947,10 @@ impl SupabaseApi {
         project_ref: &str,
         function_name: Option<&str>,
         minutes: u32,
+        filter: &LogQueryFilter,
     ) -> Result<serde_json::Value, ApiError> {
-        let now = chrono::Utc::now();
-        let start = now - chrono::Duration::minutes(minutes as i64);
-
-        let sql = if let Some(name) = function_name {
-            format!(
-                r#"select 
-                    id, 
-                    datetime(t.timestamp) as timestamp, 
-                    event_message, 
-                    m.function_id, 
-                    m.execution_time_ms, 
-                    m.deployment_id, 
-                    m.version, 
-                    r.method, 
-                    r.url, 
-                    resp.status_code 
-                   from function_edge_logs as t
-                   cross join unnest(metadata) as m
-                   cross join unnest(m.request) as r
-                   cross join unnest(m.response) as resp
-                   where m.function_id = '{}'
-                   order by timestamp desc
-                   limit 100"#,
-                name
-            )
-        } else {
-            r#"select 
-                id, 
-                datetime(t.timestamp) as timestamp, 
-                event_message, 
-                m.function_id, 
-                m.execution_time_ms, 
-                m.deployment_id, 
-                m.version, 
-                r.method, 
-                r.url, 
-                resp.status_code 
-               from function_edge_logs as t
-               left join unnest(metadata) as m
-               left join unnest(m.request) as r
-               left join unnest(m.response) as resp
-               order by timestamp desc
-               limit 100"#
-                .to_string()
-        };
-
-        self.query_logs(
-            project_ref,
-            Some(&sql),
-            Some(&start.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
-            Some(&now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
-        )
-        .await
+        let sql = edge_function_logs_sql(function_name, filter)?;
+        self.query_recent_logs(project_ref, &sql, minutes).await
     }
 
     /// Get postgres logs for the last N minutes
@@ -800,33 +958,10 @@ impl SupabaseApi {
         &self,
         project_ref: &str,
         minutes: u32,
+        filter: &LogQueryFilter,
     ) -> Result<serde_json::Value, ApiError> {
-        let now = chrono::Utc::now();
-        let start = now - chrono::Duration::minutes(minutes as i64);
-
-        // Select metadata to get error_severity, user_name, query etc.
-        // Filter to only show errors to reduce noise
-        let sql = r#"select 
-                    identifier, 
-                    postgres_logs.timestamp, 
-                    id, 
-                    event_message, 
-                    parsed.error_severity, 
-                    parsed.detail, 
-                    parsed.hint 
-                    from postgres_logs
-                    cross join unnest(metadata) as m
-                    cross join unnest(m.parsed) as parsed
-                    order by timestamp desc
-                    limit 100"#;
-
-        self.query_logs(
-            project_ref,
-            Some(sql),
-            Some(&start.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
-            Some(&now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
-        )
-        .await
+        let sql = postgres_logs_sql(filter)?;
+        self.query_recent_logs(project_ref, &sql, minutes).await
     }
 
     /// Get auth logs for the last N minutes
@@ -834,16 +969,21 @@ impl SupabaseApi {
         &self,
         project_ref: &str,
         minutes: u32,
+        filter: &LogQueryFilter,
+    ) -> Result<serde_json::Value, ApiError> {
+        let sql = auth_logs_sql(filter)?;
+        self.query_recent_logs(project_ref, &sql, minutes).await
+    }
+
+    async fn query_recent_logs(
+        &self,
+        project_ref: &str,
+        sql: &str,
+        minutes: u32,
     ) -> Result<serde_json::Value, ApiError> {
         let now = chrono::Utc::now();
         let start = now - chrono::Duration::minutes(minutes as i64);
 
-        // Select metadata to get detail fields
-        let sql = r#"select id, datetime(timestamp) as timestamp, event_message, metadata
-                     from auth_logs
-                     order by timestamp desc
-                     limit 100"#;
-
         self.query_logs(
             project_ref,
             Some(sql),
@@ -857,7 +997,7 @@ impl SupabaseApi {
     pub async fn get_api_keys(&self, project_ref: &str) -> Result<Vec<ApiKey>, ApiError> {
         let url = format!(
             "{}/v1/projects/{}/api-keys?reveal=true",
-            SUPABASE_API_BASE, project_ref
+            self.base_url, project_ref
         );
 
         let response = self
@@ -870,7 +1010,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
@@ -886,7 +1026,7 @@ impl SupabaseApi {
     ) -> Result<ApiKey, ApiError> {
         let url = format!(
             "{}/v1/projects/{}/api-keys?reveal=true",
-            SUPABASE_API_BASE, project_ref
+            self.base_url, project_ref
         );
 
         let body = CreateApiKeyBody {
@@ -911,7 +1051,7 @@ impl SupabaseApi {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(ApiError::ApiError { status, message });
+            return Err(ApiError::from_status(status, message));
         }
 
         Ok(response.json().await?)
@@ -945,3 +1085,782 @@ impl SupabaseApi {
         Ok(final_publishable_key)
     }
 }
+
+/// The Management API calls Harbor depends on. Introspection, sync and the command
+/// helpers take `&dyn ManagementApi` so they can run against `mock::MockApi` in tests.
+pub trait ManagementApi: Send + Sync {
+    /// How long a single introspection query may run before `Introspector` reports it stuck
+    fn query_timeout(&self) -> std::time::Duration {
+        crate::introspection::DEFAULT_QUERY_TIMEOUT
+    }
+
+    fn list_projects(&self) -> BoxFuture<'_, Result<Vec<Project>, ApiError>>;
+
+    fn list_available_regions<'a>(&'a self, organization_id: &'a str) -> BoxFuture<'a, Result<Vec<String>, ApiError>>;
+
+    fn run_query<'a>(
+        &'a self,
+        project_ref: &'a str,
+        query: &'a str,
+        read_only: bool,
+    ) -> BoxFuture<'a, Result<QueryResponse, ApiError>>;
+
+    fn list_functions<'a>(&'a self, project_ref: &'a str) -> BoxFuture<'a, Result<Vec<EdgeFunction>, ApiError>>;
+
+    fn get_function_body<'a>(
+        &'a self,
+        project_ref: &'a str,
+        function_slug: &'a str,
+    ) -> BoxFuture<'a, Result<FunctionBody, ApiError>>;
+
+    fn deploy_function<'a>(
+        &'a self,
+        project_ref: &'a str,
+        slug: &'a str,
+        name: &'a str,
+        entrypoint: &'a str,
+        files: Vec<DeployFile>,
+        bundle_only: bool,
+    ) -> BoxFuture<'a, Result<DeployResponse, ApiError>>;
+
+    fn bulk_update_functions<'a>(
+        &'a self,
+        project_ref: &'a str,
+        functions: &'a [DeployResponse],
+    ) -> BoxFuture<'a, Result<(), ApiError>>;
+
+    fn get_schema<'a>(&'a self, project_ref: &'a str) -> BoxFuture<'a, Result<String, ApiError>>;
+
+    fn query_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        sql: Option<&'a str>,
+        iso_timestamp_start: Option<&'a str>,
+        iso_timestamp_end: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>>;
+
+    fn get_edge_function_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        function_name: Option<&'a str>,
+        minutes: u32,
+        filter: &'a LogQueryFilter,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>>;
+
+    fn get_postgres_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        minutes: u32,
+        filter: &'a LogQueryFilter,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>>;
+
+    fn get_auth_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        minutes: u32,
+        filter: &'a LogQueryFilter,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>>;
+}
+
+impl ManagementApi for SupabaseApi {
+    fn query_timeout(&self) -> std::time::Duration {
+        self.query_timeout
+    }
+
+    fn list_projects(&self) -> BoxFuture<'_, Result<Vec<Project>, ApiError>> {
+        Box::pin(SupabaseApi::list_projects(self))
+    }
+
+    fn list_available_regions<'a>(&'a self, organization_id: &'a str) -> BoxFuture<'a, Result<Vec<String>, ApiError>> {
+        Box::pin(SupabaseApi::list_available_regions(self, organization_id))
+    }
+
+    fn run_query<'a>(
+        &'a self,
+        project_ref: &'a str,
+        query: &'a str,
+        read_only: bool,
+    ) -> BoxFuture<'a, Result<QueryResponse, ApiError>> {
+        Box::pin(SupabaseApi::run_query(self, project_ref, query, read_only))
+    }
+
+    fn list_functions<'a>(&'a self, project_ref: &'a str) -> BoxFuture<'a, Result<Vec<EdgeFunction>, ApiError>> {
+        Box::pin(SupabaseApi::list_functions(self, project_ref))
+    }
+
+    fn get_function_body<'a>(
+        &'a self,
+        project_ref: &'a str,
+        function_slug: &'a str,
+    ) -> BoxFuture<'a, Result<FunctionBody, ApiError>> {
+        Box::pin(SupabaseApi::get_function_body(self, project_ref, function_slug))
+    }
+
+    fn deploy_function<'a>(
+        &'a self,
+        project_ref: &'a str,
+        slug: &'a str,
+        name: &'a str,
+        entrypoint: &'a str,
+        files: Vec<DeployFile>,
+        bundle_only: bool,
+    ) -> BoxFuture<'a, Result<DeployResponse, ApiError>> {
+        Box::pin(SupabaseApi::deploy_function(self, project_ref, slug, name, entrypoint, files, bundle_only))
+    }
+
+    fn bulk_update_functions<'a>(
+        &'a self,
+        project_ref: &'a str,
+        functions: &'a [DeployResponse],
+    ) -> BoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(SupabaseApi::bulk_update_functions(self, project_ref, functions))
+    }
+
+    fn get_schema<'a>(&'a self, project_ref: &'a str) -> BoxFuture<'a, Result<String, ApiError>> {
+        Box::pin(SupabaseApi::get_schema(self, project_ref))
+    }
+
+    fn query_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        sql: Option<&'a str>,
+        iso_timestamp_start: Option<&'a str>,
+        iso_timestamp_end: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+        Box::pin(SupabaseApi::query_logs(self, project_ref, sql, iso_timestamp_start, iso_timestamp_end))
+    }
+
+    fn get_edge_function_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        function_name: Option<&'a str>,
+        minutes: u32,
+        filter: &'a LogQueryFilter,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+        Box::pin(SupabaseApi::get_edge_function_logs(self, project_ref, function_name, minutes, filter))
+    }
+
+    fn get_postgres_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        minutes: u32,
+        filter: &'a LogQueryFilter,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+        Box::pin(SupabaseApi::get_postgres_logs(self, project_ref, minutes, filter))
+    }
+
+    fn get_auth_logs<'a>(
+        &'a self,
+        project_ref: &'a str,
+        minutes: u32,
+        filter: &'a LogQueryFilter,
+    ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+        Box::pin(SupabaseApi::get_auth_logs(self, project_ref, minutes, filter))
+    }
+}
+
+/// Region codes from an `available-regions` response (`all.specific[].code`)
+fn parse_region_codes(val: &serde_json::Value) -> Vec<String> {
+    val.pointer("/all/specific")
+        .and_then(|v| v.as_array())
+        .map(|regions| {
+            regions
+                .iter()
+                .filter_map(|r| r.get("code").and_then(|c| c.as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Optional filters applied to the generated log queries
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogQueryFilter {
+    pub severity: Option<LogSeverity>,
+    /// Case-insensitive substring of the event message
+    pub search: Option<String>,
+}
+
+const MAX_LOG_SEARCH_LEN: usize = 200;
+
+/// Quote a value as a string literal for the (BigQuery-flavoured) log query SQL
+fn log_string_literal(value: &str) -> Result<String, ApiError> {
+    if value.chars().any(|c| c.is_control()) {
+        return Err(ApiError::InvalidLogFilter("control characters are not allowed".to_string()));
+    }
+    Ok(format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")))
+}
+
+impl LogQueryFilter {
+    /// `strpos` predicate for the search term, if any
+    fn search_predicate(&self, column: &str) -> Result<Option<String>, ApiError> {
+        let Some(search) = self.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        if search.chars().count() > MAX_LOG_SEARCH_LEN {
+            return Err(ApiError::InvalidLogFilter(format!(
+                "search term is longer than {} characters",
+                MAX_LOG_SEARCH_LEN
+            )));
+        }
+        Ok(Some(format!(
+            "strpos(lower({}), {}) > 0",
+            column,
+            log_string_literal(&search.to_lowercase())?
+        )))
+    }
+}
+
+fn where_clause(predicates: &[String]) -> String {
+    if predicates.is_empty() {
+        String::new()
+    } else {
+        format!("where {}", predicates.join(" and "))
+    }
+}
+
+/// Edge logs are requests, so severity maps onto the response status
+fn edge_function_logs_sql(function_name: Option<&str>, filter: &LogQueryFilter) -> Result<String, ApiError> {
+    let mut predicates = Vec::new();
+    if let Some(name) = function_name {
+        predicates.push(format!("m.function_id = {}", log_string_literal(name)?));
+    }
+    if let Some(severity) = filter.severity {
+        predicates.push(
+            match severity {
+                LogSeverity::Error => "resp.status_code >= 500",
+                LogSeverity::Warning => "resp.status_code between 400 and 499",
+                LogSeverity::Info => "resp.status_code < 400",
+            }
+            .to_string(),
+        );
+    }
+    predicates.extend(filter.search_predicate("event_message")?);
+
+    // Without a function filter, keep log rows that lack request metadata
+    let join = if function_name.is_some() { "cross join" } else { "left join" };
+    Ok(format!(
+        r#"select 
+                id, 
+                datetime(t.timestamp) as timestamp, 
+                event_message, 
+                m.function_id, 
+                m.execution_time_ms, 
+                m.deployment_id, 
+                m.version, 
+                r.method, 
+                r.url, 
+                resp.status_code 
+               from function_edge_logs as t
+               {join} unnest(metadata) as m
+               {join} unnest(m.request) as r
+               {join} unnest(m.response) as resp
+               {}
+               order by timestamp desc
+               limit 100"#,
+        where_clause(&predicates),
+        join = join
+    ))
+}
+
+fn postgres_logs_sql(filter: &LogQueryFilter) -> Result<String, ApiError> {
+    let mut predicates = Vec::new();
+    if let Some(severity) = filter.severity {
+        predicates.push(
+            match severity {
+                LogSeverity::Error => "parsed.error_severity in ('ERROR', 'FATAL', 'PANIC')",
+                LogSeverity::Warning => "parsed.error_severity = 'WARNING'",
+                LogSeverity::Info => "parsed.error_severity in ('LOG', 'INFO', 'NOTICE', 'DEBUG')",
+            }
+            .to_string(),
+        );
+    }
+    predicates.extend(filter.search_predicate("event_message")?);
+
+    // Select metadata to get error_severity, user_name, query etc.
+    Ok(format!(
+        r#"select 
+                    identifier, 
+                    postgres_logs.timestamp, 
+                    id, 
+                    event_message, 
+                    parsed.error_severity, 
+                    parsed.detail, 
+                    parsed.hint 
+                    from postgres_logs
+                    cross join unnest(metadata) as m
+                    cross join unnest(m.parsed) as parsed
+                    {}
+                    order by timestamp desc
+                    limit 100"#,
+        where_clause(&predicates)
+    ))
+}
+
+fn auth_logs_sql(filter: &LogQueryFilter) -> Result<String, ApiError> {
+    let mut predicates = Vec::new();
+    if let Some(severity) = filter.severity {
+        predicates.push(
+            match severity {
+                LogSeverity::Error => "m.level in ('error', 'fatal', 'panic')",
+                LogSeverity::Warning => "m.level in ('warning', 'warn')",
+                LogSeverity::Info => "m.level in ('info', 'debug')",
+            }
+            .to_string(),
+        );
+    }
+    predicates.extend(filter.search_predicate("event_message")?);
+
+    // The level lives in metadata, which is only unnested when filtering on it
+    let level_join = if filter.severity.is_some() {
+        "cross join unnest(metadata) as m"
+    } else {
+        ""
+    };
+    Ok(format!(
+        r#"select id, datetime(timestamp) as timestamp, event_message, metadata
+                     from auth_logs
+                     {}
+                     {}
+                     order by timestamp desc
+                     limit 100"#,
+        level_join,
+        where_clause(&predicates)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve a single request with the given status and return the server's base URL
+    fn one_shot_server(status: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let body = r#"{"message":"error"}"#;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Serve a single request with `status` and `body`; the raw request is sent back on the channel
+    fn capturing_server(status: &'static str, body: &'static str) -> (String, std::sync::mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read until the headers and Content-Length bytes of body have arrived
+                loop {
+                    let n = stream.read(&mut buf).unwrap_or(0);
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    let complete = text.split_once("\r\n\r\n").is_some_and(|(headers, rest)| {
+                        let length = headers
+                            .lines()
+                            .find_map(|l| {
+                                let l = l.to_lowercase();
+                                l.strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        rest.len() >= length
+                    });
+                    if n == 0 || complete {
+                        break;
+                    }
+                }
+                tx.send(String::from_utf8_lossy(&request).to_string()).ok();
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}", addr), rx)
+    }
+
+    #[tokio::test]
+    async fn test_deploy_function_streams_files_from_disk() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_deploy_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("index.ts"), "import { greet } from './lib/greet.ts';").unwrap();
+        std::fs::write(dir.join("lib/greet.ts"), "export const greet = () => 'hi';").unwrap();
+        // Larger than one read chunk, so the body is assembled from several
+        let big = "x".repeat(UPLOAD_CHUNK_SIZE * 2 + 17);
+        std::fs::write(dir.join("data.json"), format!("\"{}\"", big)).unwrap();
+
+        let files: Vec<DeployFile> = ["index.ts", "lib/greet.ts", "data.json"]
+            .iter()
+            .map(|path| DeployFile::Disk {
+                path: path.to_string(),
+                source: dir.join(path),
+                size: std::fs::metadata(dir.join(path)).unwrap().len(),
+            })
+            .collect();
+
+        let (url, requests) = capturing_server(
+            "201 Created",
+            r#"{"id":"fn-id","slug":"hello","name":"hello","version":1}"#,
+        );
+        let api = SupabaseApi::new("token".to_string(), reqwest::Client::new()).with_base_url(url);
+        let deployed = api.deploy_function("ref", "hello", "hello", "index.ts", files, false).await.unwrap();
+        assert_eq!(deployed.version, 1);
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /v1/projects/ref/functions/deploy?slug=hello HTTP/1.1"));
+        assert!(request.to_lowercase().contains("content-type: multipart/form-data"));
+        assert!(request.contains(r#"filename="lib/greet.ts""#), "{}", request);
+        assert!(request.contains("export const greet = () => 'hi';"));
+        assert!(request.contains(&big));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Oversized uploads fail before anything is sent
+        let huge = vec![DeployFile::Disk {
+            path: "index.ts".to_string(),
+            source: dir.join("index.ts"),
+            size: MAX_FUNCTION_UPLOAD_BYTES + 1,
+        }];
+        let api = SupabaseApi::new("token".to_string(), reqwest::Client::new()).with_base_url("http://127.0.0.1:9".to_string());
+        let err = api.deploy_function("ref", "hello", "hello", "index.ts", huge, false).await.unwrap_err();
+        assert!(matches!(err, ApiError::UploadTooLarge { .. }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_file_chunks_fail_when_the_file_changed_size() {
+        use futures::StreamExt;
+
+        let path = std::env::temp_dir().join(format!("harbor_test_chunks_{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "export default 1;").unwrap();
+
+        let chunks: Vec<_> = file_chunks(path.clone(), 17).collect().await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_ref().unwrap(), b"export default 1;");
+
+        for stale_size in [10, 40] {
+            let chunks: Vec<_> = file_chunks(path.clone(), stale_size).collect().await;
+            let err = chunks.into_iter().find_map(|c| c.err()).expect("size mismatch should fail");
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_region_codes() {
+        let val = serde_json::json!({
+            "recommendations": {"specific": [{"code": "us-east-1"}]},
+            "all": {"specific": [{"code": "us-east-1", "name": "East US"}, {"code": "eu-west-2", "name": "London"}]}
+        });
+        assert_eq!(parse_region_codes(&val), vec!["us-east-1", "eu-west-2"]);
+        assert!(parse_region_codes(&serde_json::json!({})).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_maps_to_auth_expired() {
+        let api = SupabaseApi::new("token".to_string(), reqwest::Client::new())
+            .with_base_url(one_shot_server("401 Unauthorized"));
+
+        let err = api.list_projects().await.unwrap_err();
+        assert!(err.is_auth_expired());
+        assert!(!err.is_transient());
+        assert!(err.to_string().starts_with("AUTH_EXPIRED:"));
+    }
+
+    #[tokio::test]
+    async fn test_service_unavailable_is_transient() {
+        let api = SupabaseApi::new("token".to_string(), reqwest::Client::new())
+            .with_base_url(one_shot_server("503 Service Unavailable"));
+
+        let err = api.list_projects().await.unwrap_err();
+        assert!(err.is_transient());
+        assert!(!err.is_auth_expired());
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_is_not_auth_expired() {
+        let api = SupabaseApi::new("token".to_string(), reqwest::Client::new())
+            .with_base_url(one_shot_server("403 Forbidden"));
+
+        let err = api.list_projects().await.unwrap_err();
+        assert!(!err.is_auth_expired());
+        assert!(!err.to_string().starts_with("AUTH_EXPIRED:"));
+    }
+
+    #[test]
+    fn test_auth_expired_message_moves_marker_to_front() {
+        let err = ApiError::from_status(401, "JWT expired".to_string());
+        let wrapped = format!("Failed to fetch schema grants: {}", err);
+        assert_eq!(
+            auth_expired_message(&wrapped).as_deref(),
+            Some("AUTH_EXPIRED: Failed to fetch schema grants: JWT expired")
+        );
+        assert_eq!(auth_expired_message(&err.to_string()).as_deref(), Some("AUTH_EXPIRED: JWT expired"));
+        let forbidden = ApiError::from_status(403, "permission denied".to_string());
+        assert_eq!(auth_expired_message(&forbidden.to_string()), None);
+    }
+
+    #[test]
+    fn test_log_sql_includes_severity_predicate() {
+        let filter = LogQueryFilter { severity: Some(LogSeverity::Error), search: None };
+        let sql = postgres_logs_sql(&filter).unwrap();
+        assert!(sql.contains("where parsed.error_severity in ('ERROR', 'FATAL', 'PANIC')"), "{}", sql);
+
+        let sql = auth_logs_sql(&filter).unwrap();
+        assert!(sql.contains("cross join unnest(metadata) as m"));
+        assert!(sql.contains("where m.level in ('error', 'fatal', 'panic')"));
+
+        let sql = edge_function_logs_sql(None, &filter).unwrap();
+        assert!(sql.contains("where resp.status_code >= 500"));
+
+        let unfiltered = postgres_logs_sql(&LogQueryFilter::default()).unwrap();
+        assert!(!unfiltered.contains("where"));
+    }
+
+    #[test]
+    fn test_log_sql_escapes_search_term() {
+        let filter = LogQueryFilter {
+            severity: Some(LogSeverity::Warning),
+            search: Some(r"O'Brien\' or 1=1 --".to_string()),
+        };
+        let sql = postgres_logs_sql(&filter).unwrap();
+        assert!(
+            sql.contains(r"where parsed.error_severity = 'WARNING' and strpos(lower(event_message), 'o\'brien\\\' or 1=1 --') > 0"),
+            "{}",
+            sql
+        );
+
+        let sql = edge_function_logs_sql(Some("fn'x"), &LogQueryFilter::default()).unwrap();
+        assert!(sql.contains(r"m.function_id = 'fn\'x'"));
+
+        let bad = LogQueryFilter { severity: None, search: Some("line\nbreak".to_string()) };
+        assert!(auth_logs_sql(&bad).is_err());
+        let long = LogQueryFilter { severity: None, search: Some("x".repeat(MAX_LOG_SEARCH_LEN + 1)) };
+        assert!(postgres_logs_sql(&long).is_err());
+    }
+}
+
+/// In-memory `ManagementApi` for tests
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Answers `run_query` with the rows registered for the first fragment the query
+    /// contains, or an empty result, and records every query it receives.
+    #[derive(Default)]
+    pub struct MockApi {
+        responses: Vec<(String, serde_json::Value)>,
+        delays: Vec<(String, std::time::Duration)>,
+        queries: Mutex<Vec<(String, bool)>>,
+        projects: Vec<String>,
+        regions: Vec<String>,
+    }
+
+    impl MockApi {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Return `rows` for queries containing `fragment`
+        pub fn respond(mut self, fragment: &str, rows: serde_json::Value) -> Self {
+            self.responses.push((fragment.to_string(), rows));
+            self
+        }
+
+        /// Answer queries containing `fragment` only after `delay`
+        pub fn delay(mut self, fragment: &str, delay: std::time::Duration) -> Self {
+            self.delays.push((fragment.to_string(), delay));
+            self
+        }
+
+        /// List a project with the given ref in `list_projects`
+        pub fn with_project(mut self, project_ref: &str) -> Self {
+            self.projects.push(project_ref.to_string());
+            self
+        }
+
+        /// Answer `list_available_regions` with `regions`
+        pub fn with_regions(mut self, regions: &[&str]) -> Self {
+            self.regions = regions.iter().map(|r| r.to_string()).collect();
+            self
+        }
+
+        /// Every query run so far, in order
+        pub fn queries(&self) -> Vec<String> {
+            self.queries.lock().unwrap().iter().map(|(q, _)| q.clone()).collect()
+        }
+
+        /// Queries run with `read_only` off, i.e. what a push executed
+        pub fn writes(&self) -> Vec<String> {
+            self.queries
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, read_only)| !read_only)
+                .map(|(q, _)| q.clone())
+                .collect()
+        }
+    }
+
+    impl ManagementApi for MockApi {
+        fn list_projects(&self) -> BoxFuture<'_, Result<Vec<Project>, ApiError>> {
+            let projects = self
+                .projects
+                .iter()
+                .map(|project_ref| Project {
+                    id: project_ref.clone(),
+                    name: project_ref.clone(),
+                    organization_id: "org".to_string(),
+                    region: "us-east-1".to_string(),
+                    created_at: "2024-01-01T00:00:00Z".to_string(),
+                })
+                .collect();
+            Box::pin(async move { Ok(projects) })
+        }
+
+        fn list_available_regions<'a>(&'a self, _organization_id: &'a str) -> BoxFuture<'a, Result<Vec<String>, ApiError>> {
+            Box::pin(async move { Ok(self.regions.clone()) })
+        }
+
+        fn run_query<'a>(
+            &'a self,
+            _project_ref: &'a str,
+            query: &'a str,
+            read_only: bool,
+        ) -> BoxFuture<'a, Result<QueryResponse, ApiError>> {
+            self.queries.lock().unwrap().push((query.to_string(), read_only));
+            let rows = self
+                .responses
+                .iter()
+                .find(|(fragment, _)| query.contains(fragment.as_str()))
+                .map(|(_, rows)| rows.clone())
+                .unwrap_or_else(|| serde_json::json!([]));
+            let delay = self
+                .delays
+                .iter()
+                .find(|(fragment, _)| query.contains(fragment.as_str()))
+                .map(|(_, delay)| *delay);
+            Box::pin(async move {
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                Ok(QueryResponse { result: Some(rows), error: None, columns: Vec::new() })
+            })
+        }
+
+        fn list_functions<'a>(&'a self, _project_ref: &'a str) -> BoxFuture<'a, Result<Vec<EdgeFunction>, ApiError>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn get_function_body<'a>(
+            &'a self,
+            _project_ref: &'a str,
+            function_slug: &'a str,
+        ) -> BoxFuture<'a, Result<FunctionBody, ApiError>> {
+            Box::pin(async move {
+                Err(ApiError::ApiError { status: 404, message: format!("Function {} not found", function_slug) })
+            })
+        }
+
+        fn deploy_function<'a>(
+            &'a self,
+            _project_ref: &'a str,
+            slug: &'a str,
+            _name: &'a str,
+            _entrypoint: &'a str,
+            _files: Vec<DeployFile>,
+            _bundle_only: bool,
+        ) -> BoxFuture<'a, Result<DeployResponse, ApiError>> {
+            Box::pin(async move {
+                Err(ApiError::ApiError { status: 501, message: format!("Mock cannot deploy {}", slug) })
+            })
+        }
+
+        fn bulk_update_functions<'a>(
+            &'a self,
+            _project_ref: &'a str,
+            _functions: &'a [DeployResponse],
+        ) -> BoxFuture<'a, Result<(), ApiError>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn get_schema<'a>(&'a self, _project_ref: &'a str) -> BoxFuture<'a, Result<String, ApiError>> {
+            Box::pin(async { Ok(String::new()) })
+        }
+
+        /// Log queries go through `run_query` so tests can answer and inspect them the same way
+        fn query_logs<'a>(
+            &'a self,
+            project_ref: &'a str,
+            sql: Option<&'a str>,
+            _iso_timestamp_start: Option<&'a str>,
+            _iso_timestamp_end: Option<&'a str>,
+        ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+            let response = self.run_query(project_ref, sql.unwrap_or_default(), true);
+            Box::pin(async move { Ok(response.await?.result.unwrap_or_default()) })
+        }
+
+        fn get_edge_function_logs<'a>(
+            &'a self,
+            project_ref: &'a str,
+            function_name: Option<&'a str>,
+            _minutes: u32,
+            filter: &'a LogQueryFilter,
+        ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+            Box::pin(async move {
+                let sql = edge_function_logs_sql(function_name, filter)?;
+                self.query_logs(project_ref, Some(&sql), None, None).await
+            })
+        }
+
+        fn get_postgres_logs<'a>(
+            &'a self,
+            project_ref: &'a str,
+            _minutes: u32,
+            filter: &'a LogQueryFilter,
+        ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+            Box::pin(async move {
+                let sql = postgres_logs_sql(filter)?;
+                self.query_logs(project_ref, Some(&sql), None, None).await
+            })
+        }
+
+        fn get_auth_logs<'a>(
+            &'a self,
+            project_ref: &'a str,
+            _minutes: u32,
+            filter: &'a LogQueryFilter,
+        ) -> BoxFuture<'a, Result<serde_json::Value, ApiError>> {
+            Box::pin(async move {
+                let sql = auth_logs_sql(filter)?;
+                self.query_logs(project_ref, Some(&sql), None, None).await
+            })
+        }
+    }
+}
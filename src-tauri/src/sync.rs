@@ -8,9 +8,11 @@ use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter};
 use uuid::Uuid;
 
-use crate::models::{LogEntry, LogSource};
+use crate::file_sink::FileSink;
+use crate::introspection::progress::ProgressSink;
+use crate::models::{LogEntry, LogPhase, LogSource, PullStatus};
 use crate::state::AppState;
-use crate::supabase_api::SupabaseApi;
+use crate::supabase_api::{DeployFile, ManagementApi};
 
 // ============================================================================
 // Edge Function File Operations
@@ -24,14 +26,69 @@ pub struct EdgeFunctionDiff {
     pub path: String, // Relative path from project root
 }
 
-/// Collect all source files in a function directory recursively.
-/// Returns a list of (relative_path, content) pairs.
-/// Also includes files from `../_shared` if it exists, prefixed with `_shared/`.
-pub async fn collect_function_files(dir: &Path) -> Result<Vec<(String, Vec<u8>)>, String> {
+/// Check a function slug against the charset Supabase accepts:
+/// ASCII letters, digits, `-` and `_`, starting with a letter.
+pub fn validate_function_slug(slug: &str) -> Result<(), String> {
+    let mut chars = slug.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid function slug '{}': use letters, digits, '-' and '_', starting with a letter",
+            slug
+        ))
+    }
+}
+
+/// A valid slug close to `name`: lowercased, with runs of other characters
+/// collapsed to `-`. None when nothing usable is left (e.g. only unicode).
+pub fn suggest_function_slug(name: &str) -> Option<String> {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug
+        .trim_start_matches(|c: char| !c.is_ascii_alphabetic())
+        .trim_end_matches(['-', '_']);
+    (!slug.is_empty()).then(|| slug.to_string())
+}
+
+/// Validate the slug taken from a function's directory name, naming the
+/// directory and a sanitized suggestion in the error.
+pub fn validate_function_dir_slug(slug: &str, function_dir: &Path) -> Result<(), String> {
+    validate_function_slug(slug).map_err(|e| {
+        let hint = match suggest_function_slug(slug) {
+            Some(suggestion) => format!(" Rename the directory to '{}'.", suggestion),
+            None => String::new(),
+        };
+        format!("{} (directory {}).{}", e, function_dir.display(), hint)
+    })
+}
+
+/// All source files of a function directory, recursively, plus `../_shared` under
+/// `../_shared/`. Files are streamed from disk by `deploy_function` and
+/// `hash_deploy_files` instead of read into memory up front.
+pub async fn function_deploy_files(dir: &Path) -> Result<Vec<DeployFile>, String> {
+    let mut files = Vec::new();
+    for (key, path) in function_source_paths(dir).await? {
+        let size = tokio::fs::metadata(&path).await.map_err(|e| e.to_string())?.len();
+        files.push(DeployFile::Disk { path: key, source: path, size });
+    }
+    Ok(files)
+}
+
+/// (relative_path, path on disk) of every source file of a function, including `../_shared`
+async fn function_source_paths(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
     let mut files = Vec::new();
     
     // 1. Collect function-specific files
-    collect_files_recursive(dir, dir, &mut files).await?;
+    collect_files_recursive_with_prefix(dir, dir, &mut files, "").await?;
 
     // 2. Collect shared files if they exist
     // Check for _shared directory at ../_shared relative to the function dir
@@ -52,7 +109,7 @@ pub async fn collect_function_files(dir: &Path) -> Result<Vec<(String, Vec<u8>)>
 async fn collect_files_recursive_with_prefix(
     base: &Path,
     current: &Path,
-    files: &mut Vec<(String, Vec<u8>)>,
+    files: &mut Vec<(String, PathBuf)>,
     prefix: &str,
 ) -> Result<(), String> {
     let mut entries = tokio::fs::read_dir(current)
@@ -76,8 +133,7 @@ async fn collect_files_recursive_with_prefix(
                     .map_err(|e| e.to_string())?
                     .to_string_lossy();
                 let full_key = format!("{}{}", prefix, relative);
-                let content = tokio::fs::read(&path).await.map_err(|e| e.to_string())?;
-                files.push((full_key, content));
+                files.push((full_key, path.clone()));
             }
         }
     }
@@ -97,7 +153,40 @@ pub async fn compute_edge_functions_diff(
     }
 
     let mut changed_functions = Vec::new();
-    let mut entries = tokio::fs::read_dir(&functions_dir)
+    for local in local_function_hashes(&functions_dir).await? {
+        if local.is_changed() {
+            changed_functions.push(EdgeFunctionDiff {
+                slug: local.slug.clone(),
+                name: local.slug.clone(), // Name is usually slug
+                path: format!("supabase/functions/{}", local.slug),
+            });
+        }
+    }
+
+    // Sort by name for deterministic output
+    changed_functions.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+    Ok(changed_functions)
+}
+
+/// A local function's current hash and the hash recorded when it was last deployed
+struct LocalFunctionHash {
+    slug: String,
+    local_hash: String,
+    deployed_hash: Option<String>,
+}
+
+impl LocalFunctionHash {
+    fn is_changed(&self) -> bool {
+        self.deployed_hash.as_deref() != Some(self.local_hash.as_str())
+    }
+}
+
+/// Hash every function directory under `functions_dir`, skipping shared
+/// folders (`_shared`), unreadable and empty ones.
+async fn local_function_hashes(functions_dir: &Path) -> Result<Vec<LocalFunctionHash>, String> {
+    let mut functions = Vec::new();
+    let mut entries = tokio::fs::read_dir(functions_dir)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -117,8 +206,8 @@ pub async fn compute_edge_functions_diff(
             continue;
         }
 
-        // Collect all files
-        let files = match collect_function_files(&path).await {
+        // Hash the files from disk without holding them in memory
+        let files = match function_deploy_files(&path).await {
             Ok(f) => f,
             Err(_) => continue, // Skip unreadable
         };
@@ -126,99 +215,129 @@ pub async fn compute_edge_functions_diff(
         if files.is_empty() {
             continue;
         }
-
-        // Compute local hash
-        let local_hash = compute_files_hash(&files);
-
-        // Check stored hash
-        let hash_file = path.join(".harbor_hash");
-        let is_changed = match tokio::fs::read_to_string(&hash_file).await {
-            Ok(stored_hash) => stored_hash.trim() != local_hash,
-            Err(_) => true, // No hash = new or not deployed
+        let Ok(local_hash) = hash_deploy_files(&files).await else {
+            continue;
         };
 
-        if is_changed {
-            changed_functions.push(EdgeFunctionDiff {
-                slug: function_slug.clone(),
-                name: function_slug.clone(), // Name is usually slug
-                path: format!("supabase/functions/{}", function_slug),
-            });
-        }
+        // No hash file = new or not deployed
+        let deployed_hash = tokio::fs::read_to_string(path.join(".harbor_hash"))
+            .await
+            .ok()
+            .map(|stored| stored.trim().to_string());
+
+        functions.push(LocalFunctionHash {
+            slug: function_slug,
+            local_hash,
+            deployed_hash,
+        });
     }
 
-    // Sort by name for deterministic output
-    changed_functions.sort_by(|a, b| a.slug.cmp(&b.slug));
-
-    Ok(changed_functions)
+    Ok(functions)
 }
 
-#[async_recursion::async_recursion]
-async fn collect_files_recursive(
-    base: &Path,
-    current: &Path,
-    files: &mut Vec<(String, Vec<u8>)>,
-) -> Result<(), String> {
-    let mut entries = tokio::fs::read_dir(current)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    while let Ok(Some(entry)) = entries.next_entry().await {
-        let path = entry.path();
+/// Deploy state of one edge function, returned by `function_status`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FunctionStatus {
+    pub slug: String,
+    /// None when the function only exists remotely
+    pub local_hash: Option<String>,
+    /// None when the function isn't deployed
+    pub deployed_version: Option<i32>,
+    /// Local files differ from what was last deployed, or the function exists on one side only
+    pub dirty: bool,
+}
 
-        if path.is_dir() {
-            // Skip node_modules and hidden directories
-            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            if name == "node_modules" || name.starts_with('.') {
-                continue;
-            }
-            collect_files_recursive(base, &path, files).await?;
-        } else {
-            // Only include source files
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if matches!(ext, "ts" | "js" | "json" | "tsx" | "jsx" | "mts" | "mjs") {
-                let relative = path
-                    .strip_prefix(base)
-                    .map_err(|e| e.to_string())?
-                    .to_string_lossy()
-                    .to_string();
-                let content = tokio::fs::read(&path).await.map_err(|e| e.to_string())?;
-                files.push((relative, content));
+/// Combine local function hashes with the remote function list, sorted by slug
+pub async fn function_statuses(
+    project_local_path: &Path,
+    remote: &[crate::supabase_api::EdgeFunction],
+) -> Result<Vec<FunctionStatus>, String> {
+    let functions_dir = project_local_path.join("supabase").join("functions");
+    let local = if functions_dir.exists() {
+        local_function_hashes(&functions_dir).await?
+    } else {
+        Vec::new()
+    };
+
+    let mut statuses: Vec<FunctionStatus> = local
+        .iter()
+        .map(|function| {
+            let deployed_version = remote.iter().find(|r| r.slug == function.slug).map(|r| r.version);
+            FunctionStatus {
+                slug: function.slug.clone(),
+                local_hash: Some(function.local_hash.clone()),
+                deployed_version,
+                dirty: deployed_version.is_none() || function.is_changed(),
             }
+        })
+        .collect();
+
+    for function in remote {
+        if !local.iter().any(|l| l.slug == function.slug) {
+            statuses.push(FunctionStatus {
+                slug: function.slug.clone(),
+                local_hash: None,
+                deployed_version: Some(function.version),
+                dirty: true,
+            });
         }
     }
 
-    Ok(())
+    statuses.sort_by(|a, b| a.slug.cmp(&b.slug));
+    Ok(statuses)
 }
 
-/// Compute a hash of all files for change detection.
-pub fn compute_files_hash(files: &[(String, Vec<u8>)]) -> String {
+/// Hash a function's files for change detection, reading files on disk in chunks so
+/// its contents are never held in memory as a whole. Files are hashed in path order.
+pub async fn hash_deploy_files(files: &[DeployFile]) -> Result<String, String> {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
+    use tokio::io::AsyncReadExt;
 
     let mut hasher = DefaultHasher::new();
 
-    // Sort files by path for deterministic ordering
-    let mut sorted_files: Vec<_> = files.iter().collect();
-    sorted_files.sort_by(|a, b| a.0.cmp(&b.0));
-
-    for (path, content) in sorted_files {
-        path.hash(&mut hasher);
-        content.hash(&mut hasher);
+    let mut sorted_files: Vec<&DeployFile> = files.iter().collect();
+    sorted_files.sort_by(|a, b| a.path().cmp(b.path()));
+
+    let mut buf = vec![0u8; 64 * 1024];
+    for file in sorted_files {
+        file.path().hash(&mut hasher);
+        match file {
+            DeployFile::Bytes { content, .. } => content.hash(&mut hasher),
+            DeployFile::Disk { source, .. } => {
+                // Same as hashing the contents as one `Vec<u8>`: the length, then the bytes
+                let mut reader = tokio::fs::File::open(source).await.map_err(|e| e.to_string())?;
+                let len = reader.metadata().await.map_err(|e| e.to_string())?.len() as usize;
+                hasher.write_usize(len);
+                let mut read = 0;
+                loop {
+                    let n = reader.read(&mut buf).await.map_err(|e| e.to_string())?;
+                    if n == 0 {
+                        break;
+                    }
+                    hasher.write(&buf[..n]);
+                    read += n;
+                }
+                if read != len {
+                    return Err(format!("{} changed while it was being read", source.display()));
+                }
+            }
+        }
     }
 
-    format!("{:x}", hasher.finish())
+    Ok(format!("{:x}", hasher.finish()))
 }
 
-/// Determine the entrypoint file for an edge function.
-pub fn determine_entrypoint(files: &[(String, Vec<u8>)]) -> String {
-    if files.iter().any(|(p, _)| p == "index.ts") {
+/// Determine the entrypoint file for an edge function from its file paths.
+pub fn entrypoint_from_paths(paths: &[&str]) -> String {
+    if paths.contains(&"index.ts") {
         "index.ts".to_string()
-    } else if files.iter().any(|(p, _)| p == "index.js") {
+    } else if paths.contains(&"index.js") {
         "index.js".to_string()
     } else {
-        files
+        paths
             .first()
-            .map(|(p, _)| p.clone())
+            .map(|p| p.to_string())
             .unwrap_or_else(|| "index.ts".to_string())
     }
 }
@@ -230,12 +349,32 @@ pub fn determine_entrypoint(files: &[(String, Vec<u8>)]) -> String {
 /// Download and save edge function files from Supabase.
 /// This handles the different formats (multipart, text, eszip) that Supabase returns.
 pub async fn download_edge_function(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
     func_slug: &str,
     func_dir: &Path,
+    sink: &FileSink,
 ) -> Result<bool, String> {
     let body = api.get_function_body(project_ref, func_slug).await.map_err(|e| e.to_string())?;
+    save_function_body(&body, func_slug, func_dir, sink).await
+}
+
+/// Write a downloaded function body to `func_dir` through `sink`, preferring multipart
+/// files, then plain text source, then unpacked eszip modules.
+pub async fn save_function_body(
+    body: &crate::supabase_api::FunctionBody,
+    func_slug: &str,
+    func_dir: &Path,
+    sink: &FileSink,
+) -> Result<bool, String> {
+    // Stale files are removed only when present, so a dry run doesn't list them
+    let remove_stale = |name: &'static str| async move {
+        let path = func_dir.join(name);
+        if path.exists() {
+            let _ = sink.remove_file(&path).await;
+        }
+    };
+
     let mut saved_files = false;
 
     // First: try to use multipart files if available (best option)
@@ -256,23 +395,25 @@ pub async fn download_edge_function(
             // Create any subdirectories if needed (for nested files)
             if let Some(parent) = file_path.parent() {
                 if parent != func_dir {
-                    let _ = tokio::fs::create_dir_all(parent).await;
+                    let _ = sink.create_dir_all(parent).await;
                 }
             }
-            let _ = tokio::fs::write(&file_path, &file.content).await;
+            let _ = sink.write(&file_path, &file.content).await;
             println!("[DEBUG] Wrote {} for {}", file_name, func_slug);
         }
         // Clean up old eszip if we got real files
-        let _ = tokio::fs::remove_file(func_dir.join("function.eszip")).await;
+        remove_stale("function.eszip").await;
         // Clean up old source folder if it exists
-        let _ = tokio::fs::remove_dir_all(func_dir.join("source")).await;
+        if func_dir.join("source").exists() {
+            let _ = sink.remove_dir_all(&func_dir.join("source")).await;
+        }
         saved_files = true;
     }
 
     // Second: if no multipart files, check if it's plain text TypeScript
     if !saved_files && (body.content_type.contains("text/") || body.content_type.contains("typescript")) {
-        let _ = tokio::fs::write(func_dir.join("index.ts"), &body.data).await;
-        let _ = tokio::fs::remove_file(func_dir.join("function.eszip")).await;
+        let _ = sink.write(&func_dir.join("index.ts"), &body.data).await;
+        remove_stale("function.eszip").await;
         saved_files = true;
     }
 
@@ -304,7 +445,7 @@ pub async fn download_edge_function(
                                     .file_name()
                                     .and_then(|n| n.to_str())
                                     .unwrap_or("index.ts");
-                                let _ = tokio::fs::write(func_dir.join(out_name), source).await;
+                                let _ = sink.write(&func_dir.join(out_name), source).await;
                                 saved_files = true;
                             }
                         }
@@ -315,62 +456,93 @@ pub async fn download_edge_function(
             loader_handle.abort();
 
             if saved_files {
-                let _ = tokio::fs::remove_file(func_dir.join("function.eszip")).await;
+                remove_stale("function.eszip").await;
             }
         }
     }
 
     // Fallback: save the raw data
     if !saved_files {
-        let _ = tokio::fs::remove_file(func_dir.join("index.ts")).await;
-        let _ = tokio::fs::write(func_dir.join("function.eszip"), &body.data).await;
+        remove_stale("index.ts").await;
+        let _ = sink.write(&func_dir.join("function.eszip"), &body.data).await;
         let notice = format!(
             "// The source code for function '{}' could not be unpacked.\n// The deployed bundle has been downloaded as 'function.eszip'.",
             func_slug
         );
-        let _ = tokio::fs::write(func_dir.join("index.ts"), notice).await;
+        let _ = sink.write(&func_dir.join("index.ts"), notice).await;
     }
 
     Ok(true)
 }
 
-/// Pull all edge functions from a Supabase project.
+/// Where sync operations send frontend events (logs, progress). The default sends
+/// nothing, for running them without the app, e.g. in tests.
+#[derive(Clone, Default)]
+pub struct EventSink(Option<AppHandle>);
+
+impl EventSink {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self(Some(app_handle.clone()))
+    }
+
+    pub fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        match &self.0 {
+            Some(app_handle) => app_handle.emit(event, payload),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Run an edge function pull/push unless function sync is disabled.
+/// Returns None without calling `sync` when it is.
+pub async fn sync_functions_if_enabled<T, F, Fut>(enabled: bool, sync: F) -> Result<Option<T>, String>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<T, String>>,
+{
+    if !enabled {
+        return Ok(None);
+    }
+    sync().await.map(Some)
+}
+
+/// Pull all edge functions from a Supabase project, writing them through `sink`.
+/// A dry run only logs failures.
 pub async fn pull_edge_functions(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
     project_id: Option<Uuid>,
     project_local_path: &Path,
     state: &AppState,
-    app_handle: &AppHandle,
+    events: &EventSink,
+    sink: &FileSink,
 ) -> Result<(), String> {
-    let log = LogEntry::info(
-        project_id,
-        LogSource::System,
-        "Syncing edge functions...".to_string(),
-    );
-    state.add_log(log.clone()).await;
-    app_handle.emit("log", &log).ok();
+    if !sink.is_dry_run() {
+        let log = LogEntry::info(
+            project_id,
+            LogSource::System,
+            "Syncing edge functions...".to_string(),
+        );
+        state.add_log(log.clone()).await;
+        events.emit("log", &log).ok();
+    }
 
     match api.list_functions(project_ref).await {
         Ok(funcs) => {
             let supabase_dir = project_local_path.join("supabase");
             let functions_dir = supabase_dir.join("functions");
             if !functions_dir.exists() {
-                tokio::fs::create_dir_all(&functions_dir)
-                    .await
-                    .map_err(|e| e.to_string())?;
+                sink.create_dir_all(&functions_dir).await?;
             }
 
             let mut func_count = 0;
             for func in funcs {
                 let func_dir = functions_dir.join(&func.slug);
                 if !func_dir.exists() {
-                    tokio::fs::create_dir_all(&func_dir)
-                        .await
-                        .map_err(|e| e.to_string())?;
+                    sink.create_dir_all(&func_dir).await?;
                 }
 
-                match download_edge_function(api, project_ref, &func.slug, &func_dir).await {
+                match download_edge_function(api, project_ref, &func.slug, &func_dir, sink).await {
                     Ok(_) => {
                         func_count += 1;
                     }
@@ -385,28 +557,133 @@ pub async fn pull_edge_functions(
                 }
             }
 
-            if func_count > 0 {
+            if func_count > 0 && !sink.is_dry_run() {
                 let log = LogEntry::success(
                     project_id,
                     LogSource::System,
                     format!("Synced {} edge functions", func_count),
                 );
                 state.add_log(log.clone()).await;
-                app_handle.emit("log", &log).ok();
+                events.emit("log", &log).ok();
             }
         }
         Err(e) => {
-            let log = LogEntry::error(
-                project_id,
-                LogSource::System,
-                format!("Failed to list functions: {}", e),
-            );
+            let message = format!("Failed to list functions: {}", e);
+            let log = LogEntry::error(project_id, LogSource::System, message.clone());
             state.add_log(log).await;
+            return Err(message);
         }
     }
     Ok(())
 }
 
+/// Run the parts of a pull that haven't succeeded yet (all of them when there is
+/// no previous status). A failing part doesn't stop the other one; its error is
+/// recorded so it can be retried later.
+pub async fn run_pull_steps<S, F>(previous: Option<&PullStatus>, schema: S, functions: F) -> PullStatus
+where
+    S: std::future::Future<Output = Result<(), String>>,
+    F: std::future::Future<Output = Result<(), String>>,
+{
+    let mut status = previous.cloned().unwrap_or_default();
+    status.errors.clear();
+    status.attempted_at = Some(chrono::Utc::now());
+
+    if !status.schema_pulled {
+        match schema.await {
+            Ok(()) => status.schema_pulled = true,
+            Err(e) => status.errors.push(format!("Schema: {}", e)),
+        }
+    }
+    if !status.functions_pulled {
+        match functions.await {
+            Ok(()) => status.functions_pulled = true,
+            Err(e) => status.errors.push(format!("Edge functions: {}", e)),
+        }
+    }
+    status
+}
+
+// ============================================================================
+// Bulk Operations
+// ============================================================================
+
+/// Projects pushed or pulled at the same time by bulk operations
+pub const BULK_CONCURRENCY: usize = 2;
+
+/// Outcome for one project of a bulk push/pull
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BulkOperationResult {
+    pub project_id: Uuid,
+    pub name: String,
+    /// "success", "skipped" (not linked) or "error"
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// Run `operation` for every linked project, at most `concurrency` at a time.
+/// A failing project doesn't stop the others; results keep the input order.
+pub async fn run_for_linked_projects<F, Fut>(
+    projects: Vec<crate::models::Project>,
+    concurrency: usize,
+    operation: F,
+) -> Vec<BulkOperationResult>
+where
+    F: Fn(crate::models::Project) -> Fut,
+    Fut: std::future::Future<Output = Result<(), String>>,
+{
+    use futures::stream::{self, StreamExt};
+
+    let mut results: Vec<(usize, BulkOperationResult)> = stream::iter(projects.into_iter().enumerate())
+        .map(|(index, project)| {
+            let project_id = project.id;
+            let name = project.name.clone();
+            let linked = project.supabase_project_ref.is_some();
+            let run = linked.then(|| operation(project));
+            async move {
+                let (status, error) = match run {
+                    None => ("skipped", None),
+                    Some(run) => match run.await {
+                        Ok(()) => ("success", None),
+                        Err(e) => ("error", Some(e)),
+                    },
+                };
+                let result = BulkOperationResult {
+                    project_id,
+                    name,
+                    status: status.to_string(),
+                    error,
+                };
+                (index, result)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// One log line for the whole bulk operation, e.g. "Push all: 2 succeeded, 1 failed, 1 skipped"
+pub fn bulk_summary_log(operation: &str, results: &[BulkOperationResult]) -> LogEntry {
+    let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+    let (succeeded, failed, skipped) = (count("success"), count("error"), count("skipped"));
+    let message = format!(
+        "{}: {} succeeded, {} failed, {} skipped",
+        operation, succeeded, failed, skipped
+    );
+    if failed > 0 {
+        let failures: Vec<String> = results
+            .iter()
+            .filter_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.name, e)))
+            .collect();
+        LogEntry::warning(None, LogSource::System, message).with_details(failures.join("\n"))
+    } else {
+        LogEntry::success(None, LogSource::System, message)
+    }
+}
+
 // ============================================================================
 // Schema Path Resolution
 // ============================================================================
@@ -440,6 +717,75 @@ pub fn find_schema_source(project_local_path: &Path) -> Option<SchemaSource> {
     find_schema_path(project_local_path).map(SchemaSource::SingleFile)
 }
 
+/// How an existing project folder keeps its schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectLayout {
+    /// Nothing to follow yet; pulls use the `supabase/schemas/` layout
+    Empty,
+    /// Declarative schema in `supabase/schemas/` or `supabase/schema.sql`
+    Schemas,
+    /// Only versioned files in `supabase/migrations/`
+    Migrations,
+}
+
+/// Detect the layout of `project_local_path`. A declarative schema wins when both exist,
+/// since that's what push diffs against.
+pub fn detect_project_layout(project_local_path: &Path) -> ProjectLayout {
+    if find_schema_source(project_local_path).is_some() {
+        return ProjectLayout::Schemas;
+    }
+    let has_migrations = std::fs::read_dir(migrations_dir(project_local_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.path().extension().is_some_and(|ext| ext == "sql"))
+        })
+        .unwrap_or(false);
+    if has_migrations {
+        ProjectLayout::Migrations
+    } else {
+        ProjectLayout::Empty
+    }
+}
+
+/// Something about a project folder the user should confirm before create/link
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PreflightWarning {
+    /// `existing_supabase_dir` (create) or `existing_schema` (link)
+    pub code: String,
+    pub message: String,
+    pub path: String,
+}
+
+/// Check a folder before creating a new project in it (`link_mode == false`)
+/// or linking it to an existing remote, whose auto-pull writes the schema files.
+pub fn preflight_project_folder(local_path: &Path, link_mode: bool) -> Vec<PreflightWarning> {
+    let mut warnings = Vec::new();
+    let supabase_dir = local_path.join("supabase");
+
+    if link_mode {
+        if let Some(source) = find_schema_source(local_path) {
+            let path = match source {
+                SchemaSource::SingleFile(path) | SchemaSource::Directory(path) => path,
+            };
+            warnings.push(PreflightWarning {
+                code: "existing_schema".to_string(),
+                message: "A local schema already exists and will be overwritten by the remote schema".to_string(),
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+    } else if supabase_dir.is_dir() {
+        warnings.push(PreflightWarning {
+            code: "existing_supabase_dir".to_string(),
+            message: "The folder already contains a supabase/ directory; link the existing project instead".to_string(),
+            path: supabase_dir.to_string_lossy().to_string(),
+        });
+    }
+
+    warnings
+}
+
 fn has_sql_files_recursive(dir: &Path) -> bool {
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
@@ -534,6 +880,123 @@ pub async fn read_schema_source(source: &SchemaSource) -> Result<Vec<(String, St
     }
 }
 
+/// Write a pulled schema into `supabase/schemas/`, replacing any `.sql` files already there.
+/// When `split` is true the schema is written as the numbered `split_sql` files,
+/// otherwise as a single `schema.sql`. Returns the written filenames.
+pub async fn write_pulled_schema(
+    schemas_dir: &Path,
+    schema: &crate::schema::DbSchema,
+    sql: &str,
+    split: bool,
+    sink: &FileSink,
+) -> Result<Vec<String>, String> {
+    sink.create_dir_all(schemas_dir).await?;
+
+    let files = if split {
+        crate::generator::split_sql(schema)
+    } else {
+        vec![("schema.sql".to_string(), sql.to_string())]
+    };
+
+    // Clear existing schema files so the previous layout doesn't shadow the new one.
+    // In a dry run the directory may not exist yet.
+    if schemas_dir.exists() {
+        let mut existing_entries = tokio::fs::read_dir(schemas_dir)
+            .await
+            .map_err(|e| e.to_string())?;
+        while let Some(entry) = existing_entries.next_entry().await.map_err(|e| e.to_string())? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("sql") {
+                sink.remove_file(&path).await?;
+            }
+        }
+    }
+
+    let mut written_files: Vec<String> = Vec::new();
+    for (filename, content) in &files {
+        sink.write(&schemas_dir.join(filename), content).await?;
+        written_files.push(filename.clone());
+    }
+
+    Ok(written_files)
+}
+
+/// Introspect the remote and render it as a full SQL dump
+pub async fn fetch_remote_schema_sql(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    watched_schemas: Option<Vec<String>>,
+    progress: Option<ProgressSink>,
+) -> Result<(String, crate::schema::DbSchema), String> {
+    let remote_schema = crate::introspection::Introspector::new(api, project_ref.to_string())
+        .with_watched_schemas(watched_schemas)
+        .with_progress(progress)
+        .introspect()
+        .await?;
+
+    let diff = crate::diff::compute_diff(&crate::schema::DbSchema::new(), &remote_schema);
+    let sql = crate::generator::generate_sql(&diff, &remote_schema);
+
+    Ok((sql, remote_schema))
+}
+
+/// Remote schema written to a project's schema files by `pull_schema`
+pub struct PulledSchema {
+    /// Full SQL dump of what was written: the remote schema, or the merge result
+    pub sql: String,
+    /// The pulled schema and its fingerprint, to be saved as the push baseline
+    pub remote: CachedSchema,
+    pub written_files: Vec<String>,
+}
+
+/// Introspect the remote schema and write it to `schemas_dir` through `sink` (see `write_pulled_schema`).
+/// With `merge_base`, the schema of the previous pull, the local schema in `local` is
+/// merged with the remote instead of being overwritten (see `merge_schemas`); if both
+/// sides changed the same object nothing is written and a `MERGE_CONFLICT:` error lists them.
+#[allow(clippy::too_many_arguments)]
+pub async fn pull_schema(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    watched_schemas: Option<Vec<String>>,
+    schemas_dir: &Path,
+    split: bool,
+    progress: Option<ProgressSink>,
+    merge_base: Option<(&crate::schema::DbSchema, &SchemaSource)>,
+    sink: &FileSink,
+) -> Result<PulledSchema, String> {
+    // Fingerprint first: a change racing the introspection then shows up as divergence on push
+    let fingerprint = crate::introspection::Introspector::new(api, project_ref.to_string())
+        .with_watched_schemas(watched_schemas.clone())
+        .fingerprint()
+        .await?;
+    let (mut sql, schema) = fetch_remote_schema_sql(api, project_ref, watched_schemas, progress).await?;
+
+    let written_files = match merge_base {
+        Some((base, local)) => {
+            let local_schema = crate::parsing::parse_schema_sql(&read_schema_source(local).await?)?;
+            let merged = crate::diff::merge::merge_schemas(base, &local_schema, &schema);
+            if !merged.conflicts.is_empty() {
+                let list: Vec<String> = merged.conflicts.iter().map(|c| c.to_string()).collect();
+                return Err(format!("MERGE_CONFLICT:{}", list.join("\n")));
+            }
+            let diff = crate::diff::compute_diff(&crate::schema::DbSchema::new(), &merged.schema);
+            sql = crate::generator::generate_sql(&diff, &merged.schema);
+            write_pulled_schema(schemas_dir, &merged.schema, &sql, split, sink).await?
+        }
+        None => write_pulled_schema(schemas_dir, &schema, &sql, split, sink).await?,
+    };
+
+    Ok(PulledSchema {
+        sql,
+        remote: CachedSchema {
+            fingerprint,
+            cached_at: chrono::Utc::now(),
+            schema,
+        },
+        written_files,
+    })
+}
+
 /// Find the admin config file path, checking multiple standard locations.
 /// Returns the path to admin.json if it exists.
 pub fn find_admin_config_path(project_local_path: &Path) -> Option<std::path::PathBuf> {
@@ -560,105 +1023,865 @@ pub fn get_admin_config_write_path(project_local_path: &Path) -> std::path::Path
 // Schema Operations
 // ============================================================================
 
+/// Last introspected remote schema, persisted per project so a restart doesn't
+/// force a full re-introspection.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CachedSchema {
+    /// `Introspector::fingerprint` of the remote when `schema` was introspected
+    pub fingerprint: String,
+    pub cached_at: chrono::DateTime<chrono::Utc>,
+    pub schema: crate::schema::DbSchema,
+}
+
+/// Cache file for a project's remote schema inside `cache_dir`
+pub fn schema_cache_path(cache_dir: &Path, project_id: Uuid) -> PathBuf {
+    cache_dir.join(format!("{}.json", project_id))
+}
+
+/// Remote schema as of the last pull or push, used to detect out-of-band remote changes
+pub fn schema_baseline_path(cache_dir: &Path, project_id: Uuid) -> PathBuf {
+    cache_dir.join(format!("{}.baseline.json", project_id))
+}
+
+/// Fail with `REMOTE_DIVERGED:<changes>` when the remote changed since `baseline`
+/// (e.g. someone edited it in the dashboard), so a push doesn't silently revert it.
+/// Without a baseline (never pulled) there is nothing to compare against.
+pub fn check_divergence(
+    baseline: Option<&CachedSchema>,
+    current: &CachedSchema,
+    options: &crate::diff::DiffOptions,
+) -> Result<(), String> {
+    let Some(baseline) = baseline else {
+        return Ok(());
+    };
+    if baseline.fingerprint == current.fingerprint {
+        return Ok(());
+    }
+
+    let remote_changes = crate::diff::compute_diff_with_options(&baseline.schema, &current.schema, options);
+    if remote_changes.is_empty() {
+        return Ok(());
+    }
+    Err(format!("REMOTE_DIVERGED:{}", remote_changes.summarize()))
+}
+
+/// After a push the remote matches `local_schema`; record it under the remote's new
+/// fingerprint as the baseline for the next divergence check
+pub async fn save_pushed_baseline(
+    state: &AppState,
+    api: &dyn ManagementApi,
+    project: &crate::models::Project,
+    project_ref: &str,
+    local_schema: &crate::schema::DbSchema,
+) {
+    match crate::introspection::Introspector::new(api, project_ref.to_string())
+        .with_watched_schemas(project.watched_schemas.clone())
+        .fingerprint()
+        .await
+    {
+        Ok(fingerprint) => {
+            let baseline = CachedSchema {
+                fingerprint,
+                cached_at: chrono::Utc::now(),
+                schema: local_schema.clone(),
+            };
+            state.save_schema_baseline(project.id, &baseline).await;
+        }
+        Err(e) => eprintln!("[CACHE] Failed to fingerprint remote after push: {}", e),
+    }
+}
+
+/// Record a finished pull (`pushed == false`) or push. Only the timestamp is lost
+/// if saving fails, so that is logged rather than failing a sync that already happened.
+pub async fn record_sync_time(state: &AppState, project_id: Uuid, pushed: bool) {
+    if let Err(e) = state.record_sync(project_id, pushed).await {
+        eprintln!("[STATE] Failed to record {} time for {}: {}", if pushed { "push" } else { "pull" }, project_id, e);
+    }
+}
+
+/// Fail when safe mode is on and the diff would lose data. Unlike the destructive-change
+/// confirmation, `force` doesn't get past this; safe mode has to be turned off first.
+pub fn check_safe_mode(safe_mode: bool, diff: &crate::diff::SchemaDiff) -> Result<(), String> {
+    if safe_mode && diff.is_destructive() {
+        return Err(format!(
+            "Safe mode blocks destructive changes. Turn off safe mode in the project settings to apply them.\n{}",
+            diff.summarize()
+        ));
+    }
+    Ok(())
+}
+
+/// `check_safe_mode` for hand-written SQL such as a migration file
+pub fn check_safe_mode_sql(safe_mode: bool, sql: &str) -> Result<(), String> {
+    let destructive = destructive_statements(sql);
+    if safe_mode && !destructive.is_empty() {
+        return Err(format!(
+            "Safe mode blocks destructive changes. Turn off safe mode in the project settings to apply them.\n{}",
+            destructive.join(";\n")
+        ));
+    }
+    Ok(())
+}
+
+/// Fail when the diff changes how a table is partitioned. Postgres can't alter that in
+/// place, so the table has to be rebuilt (and its data moved) by hand first.
+pub fn check_rebuild_required(diff: &crate::diff::SchemaDiff) -> Result<(), String> {
+    let mut tables: Vec<&String> = diff
+        .table_changes
+        .iter()
+        .filter(|(_, changes)| changes.partition_strategy_change.is_some())
+        .map(|(name, _)| name)
+        .collect();
+    if tables.is_empty() {
+        return Ok(());
+    }
+    tables.sort();
+    Err(format!(
+        "Changing how a table is partitioned requires rebuilding it. Recreate {} by hand, then pull.\n{}",
+        tables.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(", "),
+        diff.summarize()
+    ))
+}
+
+/// Read a cached schema, treating a missing or unreadable file as a cache miss
+pub async fn read_schema_cache(path: &Path) -> Option<CachedSchema> {
+    let content = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub async fn write_schema_cache(path: &Path, cached: &CachedSchema) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(cached).map_err(|e| e.to_string())?;
+    tokio::fs::write(path, content).await.map_err(|e| e.to_string())
+}
+
+/// Introspect the remote schema, reusing `cached` when the remote fingerprint still matches.
+pub async fn introspect_remote(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    watched_schemas: Option<Vec<String>>,
+    cached: Option<CachedSchema>,
+) -> Result<CachedSchema, String> {
+    let introspector = crate::introspection::Introspector::new(api, project_ref.to_string())
+        .with_watched_schemas(watched_schemas);
+    let fingerprint = introspector.fingerprint().await?;
+
+    if let Some(cached) = cached {
+        if cached.fingerprint == fingerprint {
+            println!("[INFO] Remote schema unchanged, using cached introspection");
+            return Ok(cached);
+        }
+    }
+
+    let schema = introspector.introspect().await?;
+    Ok(CachedSchema {
+        fingerprint,
+        cached_at: chrono::Utc::now(),
+        schema,
+    })
+}
+
 /// Result of computing a schema diff.
 pub struct SchemaDiffResult {
     pub diff: crate::diff::SchemaDiff,
     pub local_schema: crate::schema::DbSchema,
+    /// Remote schema the diff was computed against, to be written back to the cache
+    pub remote: CachedSchema,
+    pub migration: crate::generator::MigrationPlan,
     pub migration_sql: String,
+    /// References in the migration that won't resolve when it's applied, including
+    /// ones its statement order breaks
+    pub reference_warnings: Vec<String>,
+    /// Local statements the parser skipped, so the migration doesn't include them
+    pub unparsed: Vec<crate::parsing::UnparsedStatement>,
 }
 
 /// Compute the diff between remote and local schemas.
 /// Accepts a `SchemaSource` to support both single file and split directory layouts.
-/// Compute the diff between remote and local schemas.
-/// Accepts a `SchemaSource` to support both single file and split directory layouts.
+/// `options` restricts which objects are introspected and diffed (see `Project::diff_options`).
+/// `cached_remote` is used instead of introspecting when the remote hasn't changed since.
 pub async fn compute_schema_diff(
-    api: &SupabaseApi,
+    api: &dyn ManagementApi,
     project_ref: &str,
     source: &SchemaSource,
+    options: &crate::diff::DiffOptions,
+    generate_options: &crate::generator::GenerateOptions,
+    cached_remote: Option<CachedSchema>,
 ) -> Result<SchemaDiffResult, String> {
-    // 1. Introspect Remote
-    let introspector = crate::introspection::Introspector::new(api, project_ref.to_string());
-    let remote_schema = introspector.introspect().await?;
+    // 1. Introspect Remote (or reuse the cached result)
+    let remote = introspect_remote(api, project_ref, options.watched_schemas.clone(), cached_remote).await?;
+    let remote_schema = &remote.schema;
 
     // 2. Parse Local (read from single file or stitch from directory)
     // Now returns Vec<(filename, content)>
     let local_files = read_schema_source(source).await?;
-    let local_schema = crate::parsing::parse_schema_sql(&local_files)?;
+    let (local_schema, unparsed) = crate::parsing::parse_schema_sql_with_report(&local_files)?;
 
     // 3. Diff (Remote -> Local)
-    let diff = crate::diff::compute_diff(&remote_schema, &local_schema);
+    let diff = crate::diff::compute_diff_with_options(remote_schema, &local_schema, options);
 
     // 4. Generate Migration SQL
-    let migration_sql = crate::generator::generate_sql(&diff, &local_schema);
+    let migration =
+        crate::generator::generate_migration_with_options(&diff, &local_schema, generate_options);
+    let migration_sql = migration.to_sql();
+    let mut reference_warnings = crate::diff::validate::validate_references(&diff, remote_schema, &local_schema);
+    let statements: Vec<String> = migration.statements().cloned().collect();
+    reference_warnings.extend(crate::diff::validate::validate_statement_order(
+        &statements,
+        remote_schema,
+        &local_schema,
+    ));
 
     Ok(SchemaDiffResult {
         diff,
         local_schema,
+        remote,
+        migration,
         migration_sql,
+        reference_warnings,
+        unparsed,
     })
 }
 
-// ============================================================================
-// TypeScript Generation
-// ============================================================================
+/// What `target` would need to change to match `source`, e.g. staging vs prod.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DiffReport {
+    pub summary: String,
+    pub migration_sql: String,
+    pub is_destructive: bool,
+    pub tables_to_create: Vec<String>,
+    pub tables_to_drop: Vec<String>,
+    pub tables_to_alter: Vec<String>,
+    /// Dropped/added column pairs that look like renames, to confirm before pushing
+    #[serde(default)]
+    pub column_rename_candidates: Vec<ColumnRenameCandidate>,
+}
 
-/// Generate TypeScript types from a schema file and save to the output path.
-pub async fn generate_typescript_types(
-    schema_path: &Path,
-    output_path: &Path,
-) -> Result<(), String> {
-    // 1. Read and parse the schema
-    let local_sql = tokio::fs::read_to_string(schema_path)
-        .await
-        .map_err(|e| format!("Failed to read schema file: {}", e))?;
-    
-    let filename = schema_path.file_name().unwrap_or_default().to_string_lossy().to_string();
-    let files = vec![(filename, local_sql)];
-    
-    let schema = crate::parsing::parse_schema_sql(&files)?;
+pub use crate::diff::ColumnRenameCandidate;
+
+/// Diff two schemas, treating `target` as the current state and `source` as the desired one
+pub fn build_diff_report(
+    source: &crate::schema::DbSchema,
+    target: &crate::schema::DbSchema,
+    options: &crate::diff::DiffOptions,
+) -> DiffReport {
+    let diff = crate::diff::compute_diff_with_options(target, source, options);
+    let migration_sql = crate::generator::generate_sql(&diff, source);
+    DiffReport::from_diff(&diff, migration_sql)
+}
 
-    // 2. Generate TypeScript
-    let config = crate::generator::typescript::TypeScriptConfig::default();
-    let typescript_content = crate::generator::typescript::generate_typescript(&schema, &config);
+/// Diff two schema files offline: what `base_path` would need to change to match
+/// `target_path`. Either path may also be a split schema directory.
+pub async fn diff_schema_files(base_path: &Path, target_path: &Path) -> Result<DiffReport, String> {
+    let read = |path: &Path| {
+        let source = if path.is_dir() {
+            SchemaSource::Directory(path.to_path_buf())
+        } else {
+            SchemaSource::SingleFile(path.to_path_buf())
+        };
+        async move { crate::parsing::parse_schema_sql(&read_schema_source(&source).await?) }
+    };
+    let base = read(base_path).await?;
+    let target = read(target_path).await?;
+    Ok(build_diff_report(&target, &base, &crate::diff::DiffOptions::default()))
+}
 
-    // 3. Ensure output directory exists
-    if let Some(parent) = output_path.parent() {
-        tokio::fs::create_dir_all(parent)
-            .await
-            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+impl DiffReport {
+    pub fn from_diff(diff: &crate::diff::SchemaDiff, migration_sql: String) -> Self {
+        let mut tables_to_alter: Vec<String> = diff.table_changes.keys().cloned().collect();
+        tables_to_alter.sort();
+
+        let mut column_rename_candidates: Vec<ColumnRenameCandidate> = diff
+            .table_changes
+            .iter()
+            .filter_map(|(table, changes)| {
+                let (from, to) = changes.column_rename_candidate.clone()?;
+                Some(ColumnRenameCandidate { table: table.clone(), from, to })
+            })
+            .collect();
+        column_rename_candidates.sort_by(|a, b| a.table.cmp(&b.table));
+
+        DiffReport {
+            summary: diff.summarize(),
+            migration_sql,
+            is_destructive: diff.is_destructive(),
+            tables_to_create: diff.tables_to_create.clone(),
+            tables_to_drop: diff.tables_to_drop.clone(),
+            tables_to_alter,
+            column_rename_candidates,
+        }
     }
+}
 
-    // 4. Write the TypeScript file
-    tokio::fs::write(output_path, typescript_content)
-        .await
-        .map_err(|e| format!("Failed to write TypeScript file: {}", e))?;
+/// Log entries for a push with changes, one per phase in the order they run
+pub struct PushPhaseLogs {
+    /// The introspected remote and the diff (with a structured report), logged
+    /// before the push is checked for divergence and destructive changes
+    pub before_checks: Vec<LogEntry>,
+    /// The generated migration (SQL in `details` rather than the message) and its
+    /// execution, logged once the push goes ahead
+    pub after_checks: Vec<LogEntry>,
+}
 
-    Ok(())
+pub fn push_phase_logs(project_id: Uuid, result: &SchemaDiffResult) -> PushPhaseLogs {
+    let report = DiffReport::from_diff(&result.diff, result.migration_sql.clone());
+    let statement_count = result.migration.statements().count();
+
+    let before_checks = vec![
+        LogEntry::info(
+            Some(project_id),
+            LogSource::System,
+            format!("Introspected remote schema ({} tables)", result.remote.schema.tables.len()),
+        )
+        .with_phase(LogPhase::Introspect),
+        LogEntry::info(Some(project_id), LogSource::System, "Computed schema diff".to_string())
+            .with_details(report.summary.clone())
+            .with_phase(LogPhase::Diff)
+            .with_report(report),
+    ];
+    let after_checks = vec![
+        LogEntry::info(
+            Some(project_id),
+            LogSource::System,
+            format!("Generated migration ({} statements)", statement_count),
+        )
+        .with_details(result.migration_sql.clone())
+        .with_phase(LogPhase::Generate),
+        LogEntry::info(Some(project_id), LogSource::System, "Applying changes...".to_string())
+            .with_phase(LogPhase::Execute),
+    ];
+    PushPhaseLogs { before_checks, after_checks }
 }
 
-/// Generate TypeScript types from a list of schema files.
-pub async fn generate_typescript_types_from_sql(
-    files: &[(String, String)],
-    output_path: &Path,
+/// Apply a migration plan: new enum values first, then the transactional part in a single
+/// `BEGIN; ... COMMIT;` call, so a failure leaves the schema untouched, then each
+/// non-transactional statement on its own.
+pub async fn execute_migration(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    migration: &crate::generator::MigrationPlan,
 ) -> Result<(), String> {
-    let schema = crate::parsing::parse_schema_sql(files)?;
-
-    let config = crate::generator::typescript::TypeScriptConfig::default();
-    let typescript_content = crate::generator::typescript::generate_typescript(&schema, &config);
-
-    if let Some(parent) = output_path.parent() {
-        tokio::fs::create_dir_all(parent)
-            .await
-            .map_err(|e| format!("Failed to create output directory: {}", e))?;
-    }
+    execute_migration_with_mode(api, project_ref, migration, crate::generator::TransactionMode::Auto).await
+}
 
-    tokio::fs::write(output_path, typescript_content)
-        .await
-        .map_err(|e| format!("Failed to write TypeScript file: {}", e))?;
+/// Apply a migration plan grouped by `mode` (see `MigrationPlan::batches`). Statement by
+/// statement, the error names the failed statement's position, since earlier ones stay applied.
+pub async fn execute_migration_with_mode(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    migration: &crate::generator::MigrationPlan,
+    mode: crate::generator::TransactionMode,
+) -> Result<(), String> {
+    let batches = migration.batches(mode)?;
+    let total = batches.len();
+    for (i, batch) in batches.iter().enumerate() {
+        let result = api.run_query(project_ref, &batch.sql, false).await.map_err(|e| e.to_string())?;
+        let Some(err) = result.error else {
+            continue;
+        };
+        return Err(if batch.in_transaction {
+            err
+        } else if mode == crate::generator::TransactionMode::PerStatement {
+            format!("Statement {} of {} failed: {} (statement: {})", i + 1, total, err, batch.sql)
+        } else {
+            format!("{} (statement: {})", err, batch.sql)
+        });
+    }
 
     Ok(())
 }
 
+// ============================================================================
+// Seed Execution
+// ============================================================================
+
+/// Number of seed statements sent per `run_query` call, keeping each request
+/// under the API's statement size and timeout limits
+pub const SEED_BATCH_SIZE: usize = 50;
+
+/// Wrap seed statements in a single transaction.
+/// `search_path` is set so functions/tables in public/extensions are found.
+/// With `rollback` the transaction is discarded, which is used to probe for failures.
+pub fn seed_batch_sql(statements: &[crate::parsing::SqlStatement], rollback: bool) -> String {
+    let mut sql = String::from("BEGIN;\nSET LOCAL search_path = \"$user\", public, extensions;\n");
+    for stmt in statements {
+        sql.push_str(&stmt.sql);
+        sql.push_str(";\n");
+    }
+    sql.push_str(if rollback { "ROLLBACK;" } else { "COMMIT;" });
+    sql
+}
+
+/// Make a seed statement safe to re-run: a plain `INSERT ... VALUES` into a
+/// table with a primary key or unique index gets `ON CONFLICT DO NOTHING`.
+/// Returns None for anything else (no detectable key, existing ON CONFLICT,
+/// RETURNING, INSERT ... SELECT, other statements), which runs unchanged.
+pub fn idempotent_seed_insert(sql: &str, schema: &crate::schema::DbSchema) -> Option<String> {
+    use sqlparser::ast::{SetExpr, Statement, TableObject};
+
+    let parsed = sqlparser::parser::Parser::parse_sql(&sqlparser::dialect::PostgreSqlDialect {}, sql).ok()?;
+    let [Statement::Insert(insert)] = parsed.as_slice() else {
+        return None;
+    };
+    if insert.on.is_some() || insert.returning.is_some() {
+        return None;
+    }
+    if !matches!(insert.source.as_deref().map(|q| q.body.as_ref()), Some(SetExpr::Values(_))) {
+        return None;
+    }
+
+    let TableObject::TableName(name) = &insert.table else {
+        return None;
+    };
+    let (table_schema, table_name) = crate::parsing::parse_object_name(name);
+    let table = schema.tables.get(&crate::schema::qualified_name(&table_schema, &table_name))?;
+    let has_key = table.columns.values().any(|c| c.is_primary_key) || table.indexes.iter().any(|i| i.is_unique);
+    if !has_key {
+        return None;
+    }
+
+    Some(format!("{} ON CONFLICT DO NOTHING", trim_trailing_comments(sql)))
+}
+
+/// `sql` without trailing whitespace and comments, so text appended to it isn't
+/// swallowed by a `-- comment` on its last line. Quoted strings, quoted identifiers
+/// and dollar-quoted bodies are skipped over.
+fn trim_trailing_comments(sql: &str) -> &str {
+    let bytes = sql.as_bytes();
+    let mut end = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let mut depth = 0;
+                while i < bytes.len() {
+                    if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'*') {
+                        depth += 1;
+                        i += 2;
+                    } else if bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+                continue;
+            }
+            quote @ (b'\'' | b'"') => {
+                // A doubled quote inside is just the next quoted section; E'...' strings
+                // may also escape it with a backslash
+                let backslash_escapes = quote == b'\'' && i > 0 && bytes[i - 1].eq_ignore_ascii_case(&b'e');
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if backslash_escapes && bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                end = i;
+                continue;
+            }
+            b'$' => {
+                let tag_len = sql[i + 1..].find(|c: char| !(c.is_alphanumeric() || c == '_'));
+                if let Some(n) = tag_len.filter(|n| bytes.get(i + 1 + n) == Some(&b'$')) {
+                    let tag = &sql[i..i + n + 2];
+                    i = sql[i + tag.len()..].find(tag).map_or(bytes.len(), |m| i + tag.len() + m + tag.len());
+                    end = i;
+                    continue;
+                }
+            }
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+        end = i;
+    }
+    &sql[..end]
+}
+
+/// Apply `idempotent_seed_insert` to every statement, returning how many were rewritten
+pub fn make_seed_idempotent(
+    statements: Vec<crate::parsing::SqlStatement>,
+    schema: &crate::schema::DbSchema,
+) -> (Vec<crate::parsing::SqlStatement>, usize) {
+    let mut rewritten = 0;
+    let statements = statements
+        .into_iter()
+        .map(|stmt| match idempotent_seed_insert(&stmt.sql, schema) {
+            Some(sql) => {
+                rewritten += 1;
+                crate::parsing::SqlStatement { sql, line: stmt.line }
+            }
+            None => stmt,
+        })
+        .collect();
+    (statements, rewritten)
+}
+
+/// Run one batch of seed statements in its own transaction. On failure, returns the
+/// index within the batch of the offending statement (located by probing prefixes
+/// in rolled-back transactions, if it could be found) and the error.
+pub async fn run_seed_batch(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    statements: &[crate::parsing::SqlStatement],
+) -> Result<(), (Option<usize>, String)> {
+    let error = match api.run_query(project_ref, &seed_batch_sql(statements, false), false).await {
+        Ok(result) => result.error,
+        Err(e) => Some(e.to_string()),
+    };
+    let Some(err) = error else {
+        return Ok(());
+    };
+
+    let failing = locate_failing_statement(statements.len(), |k| {
+        let probe_sql = seed_batch_sql(&statements[..=k], true);
+        async move {
+            match api.run_query(project_ref, &probe_sql, false).await {
+                Ok(result) => result.error.is_some(),
+                Err(_) => true,
+            }
+        }
+    })
+    .await;
+    Err((failing, err))
+}
+
+/// Find the first statement of a failed batch that causes the failure.
+///
+/// `prefix_fails(k)` must report whether running statements `0..=k` fails.
+/// Bisects over the prefixes, so a batch of n statements needs about log2(n) probes.
+/// Returns `None` if no prefix fails (e.g. the original error was transient).
+pub async fn locate_failing_statement<F, Fut>(count: usize, mut prefix_fails: F) -> Option<usize>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    if count == 0 || !prefix_fails(count - 1).await {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, count - 1);
+    while low < high {
+        let mid = (low + high) / 2;
+        if prefix_fails(mid).await {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(low)
+}
+
+// ============================================================================
+// Migration History
+// ============================================================================
+
+/// A migration file in `supabase/migrations/`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct MigrationFile {
+    pub filename: String,
+    /// Leading `YYYYMMDDHHMMSS` version of the filename, if present
+    pub timestamp: Option<String>,
+    /// Filename without the version prefix and `.sql` extension
+    pub name: String,
+    pub statement_count: usize,
+    /// Statement kinds with counts, e.g. "2 CREATE TABLE, 1 CREATE INDEX"
+    pub summary: String,
+}
+
+pub fn migrations_dir(project_local_path: &Path) -> PathBuf {
+    project_local_path.join("supabase").join("migrations")
+}
+
+/// Split `20240101120000_add_todos.sql` into its version and name
+fn parse_migration_filename(filename: &str) -> (Option<String>, String) {
+    let stem = filename.strip_suffix(".sql").unwrap_or(filename);
+    match stem.split_once('_') {
+        Some((version, name)) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) => {
+            (Some(version.to_string()), name.to_string())
+        }
+        _ => (None, stem.to_string()),
+    }
+}
+
+/// Summarize statements by kind (first two keywords), most frequent first
+pub fn summarize_statements(statements: &[crate::parsing::SqlStatement]) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for stmt in statements {
+        let words: Vec<String> = stmt
+            .sql
+            .lines()
+            .filter(|l| !l.trim_start().starts_with("--"))
+            .flat_map(|l| l.split_whitespace())
+            .filter(|w| !w.eq_ignore_ascii_case("OR") && !w.eq_ignore_ascii_case("REPLACE"))
+            .take(2)
+            .map(|w| w.to_uppercase())
+            .collect();
+        let kind = words.join(" ");
+        match counts.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((kind, 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    counts
+        .iter()
+        .map(|(kind, count)| format!("{} {}", count, kind))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// List the `.sql` migrations in `dir`, newest first
+pub async fn list_migration_files(dir: &Path) -> Result<Vec<MigrationFile>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut migrations = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await.map_err(|e| e.to_string())?;
+    while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        let path = entry.path();
+        if !path.is_file() || path.extension().map_or(true, |ext| ext != "sql") {
+            continue;
+        }
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let content = tokio::fs::read_to_string(&path).await.map_err(|e| e.to_string())?;
+        let statements = crate::parsing::split_statements(&content);
+        let (timestamp, name) = parse_migration_filename(&filename);
+
+        migrations.push(MigrationFile {
+            filename,
+            timestamp,
+            name,
+            statement_count: statements.len(),
+            summary: summarize_statements(&statements),
+        });
+    }
+
+    // Versioned filenames sort chronologically
+    migrations.sort_by(|a, b| b.filename.cmp(&a.filename));
+    Ok(migrations)
+}
+
+/// Migration that brings the schema built by the migrations in `dir` up to `remote`, or
+/// `None` when they already match. The migrations are parsed oldest first as one schema;
+/// objects they create that the remote lacks are kept rather than dropped.
+pub async fn migration_to_remote(dir: &Path, remote: &crate::schema::DbSchema) -> Result<Option<String>, String> {
+    let mut files = Vec::new();
+    for migration in list_migration_files(dir).await?.into_iter().rev() {
+        let sql = tokio::fs::read_to_string(dir.join(&migration.filename))
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", migration.filename, e))?;
+        files.push((migration.filename, sql));
+    }
+    let migrated = crate::parsing::parse_schema_sql(&files)?;
+
+    let mut diff = crate::diff::compute_diff(&migrated, remote);
+    diff.retain_additive(remote);
+    let sql = crate::generator::generate_sql(&diff, remote);
+    Ok((!sql.trim().is_empty()).then_some(sql))
+}
+
+/// Read a migration by filename, refusing anything outside `dir`
+pub async fn read_migration_file(dir: &Path, filename: &str) -> Result<String, String> {
+    // A single plain `.sql` file name; backslashes are refused too so Windows
+    // separators can't sneak through on other platforms
+    let path = Path::new(filename);
+    let mut components = path.components();
+    let single_file = matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    );
+    if !single_file || filename.contains('\\') || path.extension().and_then(|e| e.to_str()) != Some("sql") {
+        return Err(format!("Invalid migration filename: {}", filename));
+    }
+    tokio::fs::read_to_string(dir.join(filename))
+        .await
+        .map_err(|e| format!("Failed to read migration {}: {}", filename, e))
+}
+
+/// Split a migration file into a plan. The file's own `BEGIN`/`COMMIT` are dropped
+/// since `execute_migration` wraps the transactional part itself.
+pub fn migration_plan_from_sql(sql: &str) -> crate::generator::MigrationPlan {
+    let transaction_control =
+        regex::Regex::new(r"(?i)^(?:\s*--[^\n]*\n)*\s*(?:BEGIN|COMMIT|END|START\s+TRANSACTION)(?:\s+(?:WORK|TRANSACTION))?\s*$")
+            .unwrap();
+    let mut plan = crate::generator::MigrationPlan::default();
+    for stmt in crate::parsing::split_statements(sql) {
+        let body = stmt.sql.trim();
+        if transaction_control.is_match(body) {
+            continue;
+        }
+        plan.push(format!("{};", body));
+    }
+    plan
+}
+
+/// Apply a migration file from `dir`. Destructive statements are refused outright in
+/// safe mode, and otherwise with `CONFIRMATION_NEEDED:` unless `force` is set, as
+/// `apply_sql` does.
+pub async fn apply_migration_file(
+    api: &dyn ManagementApi,
+    project_ref: &str,
+    dir: &Path,
+    filename: &str,
+    safe_mode: bool,
+    force: bool,
+) -> Result<crate::generator::MigrationPlan, String> {
+    let sql = read_migration_file(dir, filename).await?;
+
+    check_safe_mode_sql(safe_mode, &sql)?;
+    let destructive = destructive_statements(&sql);
+    if !force && !destructive.is_empty() {
+        return Err(format!("CONFIRMATION_NEEDED:{}", destructive.join(";\n")));
+    }
+
+    let plan = migration_plan_from_sql(&sql);
+    execute_migration(api, project_ref, &plan).await?;
+    Ok(plan)
+}
+
+// ============================================================================
+// Ad-hoc SQL
+// ============================================================================
+
+/// Error from validating or applying a SQL statement
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SqlError {
+    pub message: String,
+    /// 1-based line in the submitted SQL, when the parser or Postgres reports one
+    pub line: Option<usize>,
+}
+
+impl SqlError {
+    /// Build from a sqlparser (`at Line: 2, Column: 5`) or Postgres (`LINE 2:`) error message
+    pub fn from_message(message: &str) -> Self {
+        let line_re = regex::Regex::new(r"(?i)\bline:?\s*(\d+)").unwrap();
+        let line = line_re
+            .captures(message)
+            .and_then(|c| c[1].parse().ok());
+        Self {
+            message: message.to_string(),
+            line,
+        }
+    }
+}
+
+/// Result of `apply_sql`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApplySqlResult {
+    pub success: bool,
+    /// Rows the statement returned (0 for DDL, and for DML without `RETURNING`).
+    /// The Management API doesn't report how many rows a write touched.
+    pub rows_returned: Option<usize>,
+    pub error: Option<SqlError>,
+}
+
+impl ApplySqlResult {
+    /// Build from a `run_query` result: the returned rows, or the error message
+    pub fn from_query_result(result: Result<serde_json::Value, String>) -> Self {
+        match result {
+            Ok(rows) => Self {
+                success: true,
+                rows_returned: Some(rows.as_array().map(|r| r.len()).unwrap_or(0)),
+                error: None,
+            },
+            Err(message) => Self {
+                success: false,
+                rows_returned: None,
+                error: Some(SqlError::from_message(&message)),
+            },
+        }
+    }
+}
+
+/// Check that SQL parses. sqlparser doesn't cover every Postgres statement,
+/// so this is a pre-check for the UI rather than a gate on `apply_sql`.
+pub fn validate_sql(sql: &str) -> Option<SqlError> {
+    let dialect = sqlparser::dialect::PostgreSqlDialect {};
+    sqlparser::parser::Parser::parse_sql(&dialect, sql)
+        .err()
+        .map(|e| SqlError::from_message(&e.to_string()))
+}
+
+/// Statements that can lose data or break dependents (dropping tables, columns,
+/// types, functions, views, indexes and sequences, type changes, TRUNCATE and
+/// DELETE). Broader than `SchemaDiff::is_destructive`, since hand-written SQL
+/// has no diff to say whether a dropped object comes back.
+pub fn destructive_statements(sql: &str) -> Vec<String> {
+    let destructive_re = regex::Regex::new(
+        r"(?is)^(?:DROP\s+(?:TABLE|SCHEMA|TYPE|FUNCTION|PROCEDURE|(?:MATERIALIZED\s+)?VIEW|INDEX|SEQUENCE)\b|TRUNCATE\b|DELETE\s+FROM\b|ALTER\s+TABLE\b.*\b(?:DROP\s+COLUMN\b|ALTER\s+COLUMN\b.*\bTYPE\b))",
+    )
+    .unwrap();
+    crate::parsing::split_statements(sql)
+        .into_iter()
+        .map(|stmt| stmt.sql)
+        .filter(|stmt| destructive_re.is_match(crate::parsing::strip_leading_comments(stmt)))
+        .collect()
+}
+
+// ============================================================================
+// TypeScript Generation
+// ============================================================================
+
+/// Generate TypeScript types from a schema file and save to the output path through `sink`.
+pub async fn generate_typescript_types(
+    schema_path: &Path,
+    output_path: &Path,
+    sink: &FileSink,
+) -> Result<(), String> {
+    // 1. Read and parse the schema
+    let local_sql = tokio::fs::read_to_string(schema_path)
+        .await
+        .map_err(|e| format!("Failed to read schema file: {}", e))?;
+    
+    let filename = schema_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let files = vec![(filename, local_sql)];
+    
+    let schema = crate::parsing::parse_schema_sql(&files)?;
+
+    // 2. Generate TypeScript
+    let config = crate::generator::typescript::TypeScriptConfig::default();
+    let typescript_content = crate::generator::typescript::generate_typescript(&schema, &config);
+
+    // 3. Ensure output directory exists and write the TypeScript file
+    write_typescript_file(output_path, &typescript_content, sink).await
+}
+
+/// Generate TypeScript types from a list of schema files and save them through `sink`.
+pub async fn generate_typescript_types_from_sql(
+    files: &[(String, String)],
+    output_path: &Path,
+    sink: &FileSink,
+) -> Result<(), String> {
+    let schema = crate::parsing::parse_schema_sql(files)?;
+
+    let config = crate::generator::typescript::TypeScriptConfig::default();
+    let typescript_content = crate::generator::typescript::generate_typescript(&schema, &config);
+
+    write_typescript_file(output_path, &typescript_content, sink).await
+}
+
+async fn write_typescript_file(output_path: &Path, content: &str, sink: &FileSink) -> Result<(), String> {
+    if let Some(parent) = output_path.parent() {
+        if !parent.exists() {
+            sink.create_dir_all(parent).await?;
+        }
+    }
+    sink.write(output_path, content).await
+}
+
 /// Find the TypeScript output path based on project settings.
 /// Uses custom path if provided, otherwise defaults to `<project_path>/src/types/database.ts`
 pub fn get_typescript_output_path(
@@ -758,4 +1981,901 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    #[tokio::test]
+    async fn test_write_pulled_schema_split() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_pull_{}", Uuid::new_v4()));
+        tokio::fs::create_dir(&dir).await.unwrap();
+        std::fs::write(dir.join("schema.sql"), "CREATE TABLE stale (id int);").unwrap();
+
+        let mut schema = crate::schema::DbSchema::new();
+        schema.tables.insert(
+            "\"public\".\"todos\"".to_string(),
+            crate::schema::TableInfo {
+                schema: "public".to_string(),
+                table_name: "todos".to_string(),
+                columns: std::collections::HashMap::from([(
+                    "id".to_string(),
+                    crate::schema::ColumnInfo {
+                        column_name: "id".to_string(),
+                        data_type: "bigint".to_string(),
+                        udt_name: "int8".to_string(),
+                        is_primary_key: true,
+                        ..Default::default()
+                    },
+                )]),
+                ..Default::default()
+            },
+        );
+
+        let result = write_pulled_schema(&dir, &schema, "", true, &FileSink::default()).await;
+        let tables_sql = std::fs::read_to_string(dir.join("04_tables.sql"));
+        let monolith_exists = dir.join("schema.sql").exists();
+
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let written = result.unwrap();
+        assert!(written.contains(&"04_tables.sql".to_string()));
+        assert!(tables_sql.unwrap().contains("CREATE TABLE \"public\".\"todos\""));
+        assert!(!monolith_exists, "old schema.sql should be removed");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_pull_reports_files_without_writing() {
+        use crate::supabase_api::mock::MockApi;
+        use serde_json::json;
+
+        let api = MockApi::new()
+            .respond("as fingerprint FROM parts", json!([{"fingerprint": "abc"}]))
+            .respond(
+                "row_to_json(table_comments)",
+                json!([{"data": {
+                    "tables": [{"schema": "public", "name": "todos"}],
+                    "columns": [{
+                        "schema": "public", "table_name": "todos", "column_name": "id",
+                        "data_type": "bigint", "udt_name": "int8", "is_nullable": "NO",
+                        "column_default": null, "is_identity": "NO", "is_primary_key": true,
+                        "is_unique": false, "ordinal_position": 1
+                    }],
+                }}]),
+            );
+
+        let dir = std::env::temp_dir().join(format!("harbor_test_dry_pull_{}", Uuid::new_v4()));
+        let schemas_dir = dir.join("supabase").join("schemas");
+        std::fs::create_dir_all(&schemas_dir).unwrap();
+        std::fs::write(schemas_dir.join("schema.sql"), "CREATE TABLE stale (id int);").unwrap();
+
+        let sink = FileSink::new(true);
+        let result = pull_schema(&api, "ref", None, &schemas_dir, true, None, None, &sink).await;
+        let remaining: Vec<String> = std::fs::read_dir(&schemas_dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        let stale = std::fs::read_to_string(schemas_dir.join("schema.sql")).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let pulled = result.unwrap();
+        assert!(pulled.written_files.contains(&"04_tables.sql".to_string()), "{:?}", pulled.written_files);
+        assert_eq!(remaining, vec!["schema.sql"]);
+        assert_eq!(stale, "CREATE TABLE stale (id int);");
+
+        let operations = sink.operations();
+        let path = |name: &str| schemas_dir.join(name).to_string_lossy().to_string();
+        assert!(operations.contains(&crate::file_sink::FileOperation::Remove { path: path("schema.sql") }), "{:?}", operations);
+        assert!(
+            operations.iter().any(|op| matches!(op, crate::file_sink::FileOperation::Write { path: p, bytes } if *p == path("04_tables.sql") && *bytes > 0)),
+            "{:?}",
+            operations
+        );
+    }
+
+    #[tokio::test]
+    async fn test_locate_failing_seed_statement() {
+        let seed = "INSERT INTO users (id) VALUES (1);\nINSERT INTO users (id) VALUES (2);\n\nINSERT INTO missing_table (id) VALUES (3);\nINSERT INTO users (id) VALUES (4);\n";
+        let statements = crate::parsing::split_statements(seed);
+        assert_eq!(statements.len(), 4);
+
+        let batch_sql = seed_batch_sql(&statements, true);
+        assert!(batch_sql.starts_with("BEGIN;"));
+        assert!(batch_sql.ends_with("ROLLBACK;"));
+
+        // Simulate the database: any prefix containing the bad insert fails
+        let failing = locate_failing_statement(statements.len(), |k| {
+            let fails = statements[..=k].iter().any(|s| s.sql.contains("missing_table"));
+            async move { fails }
+        })
+        .await;
+
+        assert_eq!(failing, Some(2));
+        assert_eq!(statements[2].line, 4);
+
+        let none_failing = locate_failing_statement(statements.len(), |_| async { false }).await;
+        assert_eq!(none_failing, None);
+    }
+
+    #[tokio::test]
+    async fn test_seed_batch_runs_in_one_committed_transaction() {
+        let first = crate::parsing::split_statements("INSERT INTO users (id) VALUES (1);\nINSERT INTO users (id) VALUES (2);\n");
+        let second = crate::parsing::split_statements("INSERT INTO posts (id) VALUES (1);\n");
+        let statements: Vec<_> = first.into_iter().chain(second).collect();
+
+        let api = crate::supabase_api::mock::MockApi::new();
+        assert!(run_seed_batch(&api, "ref", &statements).await.is_ok());
+
+        let writes = api.writes();
+        assert_eq!(writes.len(), 1, "{:?}", writes);
+        assert!(writes[0].starts_with("BEGIN;"));
+        assert!(writes[0].ends_with("COMMIT;"));
+        assert_eq!(writes[0].matches("COMMIT").count(), 1);
+        assert!(writes[0].contains("INSERT INTO users (id) VALUES (2)"));
+        assert!(writes[0].contains("INSERT INTO posts (id) VALUES (1)"));
+    }
+
+    #[test]
+    fn test_seed_insert_gets_on_conflict_for_keyed_tables() {
+        let schema = crate::parsing::parse_schema_sql(&[(
+            "schema.sql".to_string(),
+            "CREATE TABLE public.users (id bigint PRIMARY KEY, email text);
+CREATE TABLE app.tags (name text);
+CREATE UNIQUE INDEX tags_name_key ON app.tags (name);
+CREATE TABLE public.events (payload jsonb);"
+                .to_string(),
+        )])
+        .unwrap();
+
+        assert_eq!(
+            idempotent_seed_insert("INSERT INTO users (id, email) VALUES (1, 'a@example.com')", &schema).as_deref(),
+            Some("INSERT INTO users (id, email) VALUES (1, 'a@example.com') ON CONFLICT DO NOTHING")
+        );
+        assert!(idempotent_seed_insert("INSERT INTO \"app\".\"tags\" (name) VALUES ('x')", &schema).is_some());
+
+        // No key to conflict on, or not a simple VALUES insert
+        assert_eq!(idempotent_seed_insert("INSERT INTO events (payload) VALUES ('{}')", &schema), None);
+        assert_eq!(idempotent_seed_insert("INSERT INTO unknown (id) VALUES (1)", &schema), None);
+        assert_eq!(
+            idempotent_seed_insert("INSERT INTO users (id) VALUES (1) ON CONFLICT (id) DO UPDATE SET email = NULL", &schema),
+            None
+        );
+        assert_eq!(idempotent_seed_insert("INSERT INTO users (id) VALUES (1) RETURNING id", &schema), None);
+        assert_eq!(idempotent_seed_insert("INSERT INTO users (id) SELECT 1", &schema), None);
+        assert_eq!(idempotent_seed_insert("UPDATE users SET email = NULL", &schema), None);
+
+        // A trailing comment stays after the clause instead of swallowing it
+        assert_eq!(
+            idempotent_seed_insert("INSERT INTO users (id, email) VALUES (1, 'a -- b') -- admin\n/* seed */ ", &schema)
+                .as_deref(),
+            Some("INSERT INTO users (id, email) VALUES (1, 'a -- b') ON CONFLICT DO NOTHING")
+        );
+
+        let statements = crate::parsing::split_statements(
+            "INSERT INTO users (id) VALUES (1);\nSELECT setval('users_id_seq', 1);\nINSERT INTO users (id) VALUES (2);",
+        );
+        let (statements, rewritten) = make_seed_idempotent(statements, &schema);
+        assert_eq!(rewritten, 2);
+        assert_eq!(statements[2].line, 3);
+        assert!(statements[1].sql.starts_with("SELECT setval"));
+    }
+
+    #[test]
+    fn test_apply_sql_result_for_ddl() {
+        let sql = "CREATE TABLE public.notes (id bigint PRIMARY KEY, body text);";
+        assert_eq!(validate_sql(sql), None);
+        assert!(destructive_statements(sql).is_empty());
+
+        let result = ApplySqlResult::from_query_result(Ok(serde_json::json!([])));
+        assert!(result.success);
+        assert_eq!(result.rows_returned, Some(0));
+        assert!(result.error.is_none());
+    }
+
+    #[test]
+    fn test_apply_sql_error_reports_line() {
+        let sql = "CREATE TABLE public.notes (id bigint);\nCREAT TABLE public.broken (id bigint);";
+        let error = validate_sql(sql).expect("should fail to parse");
+        assert_eq!(error.line, Some(2));
+
+        let result = ApplySqlResult::from_query_result(Err(
+            "ERROR:  42601: syntax error at or near \"CREAT\"\nLINE 2: CREAT TABLE public.broken (id bigint);\n        ^".to_string(),
+        ));
+        assert!(!result.success);
+        assert_eq!(result.rows_returned, None);
+        assert_eq!(result.error.unwrap().line, Some(2));
+
+        assert_eq!(
+            destructive_statements("ALTER TABLE public.notes DROP COLUMN body;\nSELECT 1;"),
+            vec!["ALTER TABLE public.notes DROP COLUMN body".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_destructive_statements_after_leading_comment() {
+        let sql = "-- cleanup\nDROP TABLE users;\n/* old column */ ALTER TABLE public.notes DROP COLUMN body;\n-- keep\nSELECT 1;";
+        let destructive = destructive_statements(sql);
+        assert_eq!(destructive.len(), 2, "{:?}", destructive);
+        assert!(destructive[0].ends_with("DROP TABLE users"));
+        assert!(destructive[1].ends_with("DROP COLUMN body"));
+    }
+
+    #[test]
+    fn test_destructive_statements_cover_dropped_objects() {
+        for sql in [
+            "DROP FUNCTION public.touch()",
+            "DROP VIEW IF EXISTS public.active_users",
+            "DROP MATERIALIZED VIEW public.stats",
+            "DROP INDEX CONCURRENTLY public.users_email_idx",
+            "DROP SEQUENCE public.order_seq",
+        ] {
+            assert_eq!(destructive_statements(sql), vec![sql.to_string()]);
+        }
+        assert!(destructive_statements("DROP POLICY p ON public.users").is_empty());
+    }
+
+    #[test]
+    fn test_schema_cache_path_per_project() {
+        let dir = Path::new("/tmp/harbor/schema_cache");
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+
+        assert_eq!(schema_cache_path(dir, a), dir.join(format!("{}.json", a)));
+        assert_ne!(schema_cache_path(dir, a), schema_cache_path(dir, b));
+    }
+
+    #[tokio::test]
+    async fn test_schema_cache_round_trip() {
+        let temp_dir = std::env::temp_dir().join(format!("harbor_test_{}", Uuid::new_v4()));
+        let path = schema_cache_path(&temp_dir, Uuid::new_v4());
+
+        let sql = r#"
+CREATE TYPE "public"."status" AS ENUM ('active', 'archived');
+CREATE TABLE "public"."todos" ("id" bigint PRIMARY KEY, "title" text NOT NULL, "status" "public"."status");
+CREATE INDEX "idx_todos_title" ON "public"."todos" ("title");
+"#;
+        let schema = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        let cached = CachedSchema {
+            fingerprint: "abc123".to_string(),
+            cached_at: chrono::Utc::now(),
+            schema,
+        };
+
+        assert!(read_schema_cache(&path).await.is_none());
+        write_schema_cache(&path, &cached).await.unwrap();
+
+        let loaded = read_schema_cache(&path).await.expect("cache should load");
+        assert_eq!(loaded.fingerprint, "abc123");
+        assert_eq!(loaded.schema, cached.schema);
+
+        // A corrupt file is a cache miss, not an error
+        tokio::fs::write(&path, "{not json").await.unwrap();
+        assert!(read_schema_cache(&path).await.is_none());
+
+        tokio::fs::remove_dir_all(&temp_dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_schema_cache_with_plain_index_column_names_loads() {
+        let temp_dir = std::env::temp_dir().join(format!("harbor_test_{}", Uuid::new_v4()));
+        let path = schema_cache_path(&temp_dir, Uuid::new_v4());
+
+        let sql = r#"
+CREATE TABLE "public"."todos" ("id" bigint PRIMARY KEY, "title" text NOT NULL, "done" boolean);
+CREATE INDEX "idx_todos_title_done" ON "public"."todos" ("title", "done");
+"#;
+        let schema = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        let cached = CachedSchema { fingerprint: "old".to_string(), cached_at: chrono::Utc::now(), schema };
+
+        // Caches written before index columns kept sort options stored bare names
+        let mut json = serde_json::to_value(&cached).unwrap();
+        for table in json["schema"]["tables"].as_object_mut().unwrap().values_mut() {
+            for index in table["indexes"].as_array_mut().unwrap() {
+                let names: Vec<serde_json::Value> =
+                    index["columns"].as_array().unwrap().iter().map(|c| c["name"].clone()).collect();
+                index["columns"] = serde_json::Value::Array(names);
+            }
+        }
+        tokio::fs::create_dir_all(&temp_dir).await.unwrap();
+        tokio::fs::write(&path, json.to_string()).await.unwrap();
+
+        let loaded = read_schema_cache(&path).await.expect("old cache should load");
+        assert_eq!(loaded.schema, cached.schema);
+        let index = loaded.schema.tables["\"public\".\"todos\""]
+            .indexes
+            .iter()
+            .find(|i| i.index_name == "idx_todos_title_done")
+            .unwrap();
+        assert_eq!(index.column_names(), vec!["title", "done"]);
+
+        tokio::fs::remove_dir_all(&temp_dir).await.ok();
+    }
+
+    #[test]
+    fn test_diff_report_marks_missing_table_for_creation() {
+        let target_sql = r#"CREATE TABLE "public"."todos" ("id" bigint PRIMARY KEY, "title" text);"#;
+        let source_sql = format!(
+            "{}\n{}",
+            target_sql, r#"CREATE TABLE "public"."projects" ("id" bigint PRIMARY KEY, "name" text);"#
+        );
+        let source = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), source_sql)]).unwrap();
+        let target = crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), target_sql.to_string())]).unwrap();
+
+        let report = build_diff_report(&source, &target, &crate::diff::DiffOptions::default());
+        assert_eq!(report.tables_to_create, vec!["\"public\".\"projects\"".to_string()]);
+        assert!(report.tables_to_drop.is_empty());
+        assert!(report.tables_to_alter.is_empty());
+        assert!(!report.is_destructive);
+        assert!(report.migration_sql.contains("CREATE TABLE \"public\".\"projects\""));
+
+        // The other direction drops it
+        let reverse = build_diff_report(&target, &source, &crate::diff::DiffOptions::default());
+        assert_eq!(reverse.tables_to_drop, vec!["\"public\".\"projects\"".to_string()]);
+        assert!(reverse.is_destructive);
+    }
+
+    #[tokio::test]
+    async fn test_list_migration_files_newest_first() {
+        let temp_dir = std::env::temp_dir().join(format!("harbor_test_{}", Uuid::new_v4()));
+        let dir = migrations_dir(&temp_dir);
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+
+        tokio::fs::write(
+            dir.join("20240101120000_create_todos.sql"),
+            "CREATE TABLE public.todos (id bigint);\nCREATE INDEX idx_todos ON public.todos (id);\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            dir.join("20240315090000_add_projects.sql"),
+            "-- projects\nCREATE TABLE public.projects (id bigint);\nCREATE TABLE public.members (id bigint);\nCREATE OR REPLACE FUNCTION public.f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;\n",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(dir.join("README.md"), "not a migration").await.unwrap();
+
+        let migrations = list_migration_files(&dir).await.unwrap();
+        assert_eq!(migrations.len(), 2);
+
+        assert_eq!(migrations[0].filename, "20240315090000_add_projects.sql");
+        assert_eq!(migrations[0].timestamp.as_deref(), Some("20240315090000"));
+        assert_eq!(migrations[0].name, "add_projects");
+        assert_eq!(migrations[0].statement_count, 3);
+        assert_eq!(migrations[0].summary, "2 CREATE TABLE, 1 CREATE FUNCTION");
+
+        assert_eq!(migrations[1].name, "create_todos");
+        assert_eq!(migrations[1].statement_count, 2);
+        assert_eq!(migrations[1].summary, "1 CREATE TABLE, 1 CREATE INDEX");
+
+        let content = read_migration_file(&dir, "20240101120000_create_todos.sql").await.unwrap();
+        assert!(content.starts_with("CREATE TABLE public.todos"));
+        assert!(read_migration_file(&dir, "../data.json").await.is_err());
+
+        tokio::fs::remove_dir_all(&temp_dir).await.ok();
+    }
+
+    #[test]
+    fn test_remote_only_table_reports_divergence() {
+        let baseline_sql = r#"CREATE TABLE "public"."todos" ("id" bigint PRIMARY KEY);"#;
+        let remote_sql = format!(
+            "{}\n{}",
+            baseline_sql, r#"CREATE TABLE "public"."audit_log" ("id" bigint PRIMARY KEY);"#
+        );
+        let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        let baseline = CachedSchema {
+            fingerprint: "before".to_string(),
+            cached_at: chrono::Utc::now(),
+            schema: parse(baseline_sql),
+        };
+        let current = CachedSchema {
+            fingerprint: "after".to_string(),
+            cached_at: chrono::Utc::now(),
+            schema: parse(&remote_sql),
+        };
+        let options = crate::diff::DiffOptions::default();
+
+        let err = check_divergence(Some(&baseline), &current, &options).unwrap_err();
+        assert!(err.starts_with("REMOTE_DIVERGED:"));
+        assert!(err.contains("audit_log"));
+
+        // Unchanged fingerprint or no baseline never blocks
+        assert!(check_divergence(Some(&baseline), &baseline, &options).is_ok());
+        assert!(check_divergence(None, &current, &options).is_ok());
+    }
+
+    #[test]
+    fn test_push_logs_are_tagged_with_each_phase_in_order() {
+        let parse = |sql: &str| crate::parsing::parse_schema_sql(&[("schema.sql".to_string(), sql.to_string())]).unwrap();
+        let remote = parse(r#"CREATE TABLE "public"."todos" ("id" bigint PRIMARY KEY);"#);
+        let local = parse(
+            r#"CREATE TABLE "public"."todos" ("id" bigint PRIMARY KEY, "title" text);
+CREATE TABLE "public"."tags" ("id" bigint PRIMARY KEY);"#,
+        );
+
+        let diff = crate::diff::compute_diff_with_options(&remote, &local, &crate::diff::DiffOptions::default());
+        let migration = crate::generator::generate_migration_with_options(
+            &diff,
+            &local,
+            &crate::generator::GenerateOptions::default(),
+        );
+        let migration_sql = migration.to_sql();
+        let result = SchemaDiffResult {
+            diff,
+            local_schema: local,
+            remote: CachedSchema {
+                fingerprint: "fp".to_string(),
+                cached_at: chrono::Utc::now(),
+                schema: remote,
+            },
+            migration,
+            migration_sql,
+            reference_warnings: vec![],
+            unparsed: vec![],
+        };
+
+        let project_id = Uuid::new_v4();
+        let phase_logs = push_phase_logs(project_id, &result);
+        assert_eq!(phase_logs.before_checks.len(), 2);
+        let logs: Vec<LogEntry> = phase_logs.before_checks.into_iter().chain(phase_logs.after_checks).collect();
+        let phases: Vec<Option<LogPhase>> = logs.iter().map(|l| l.phase).collect();
+        assert_eq!(
+            phases,
+            vec![
+                Some(LogPhase::Introspect),
+                Some(LogPhase::Diff),
+                Some(LogPhase::Generate),
+                Some(LogPhase::Execute)
+            ]
+        );
+
+        // The report rides on the diff entry; SQL stays out of the messages
+        let report = logs[1].report.as_ref().unwrap();
+        assert_eq!(report.tables_to_create, vec![r#""public"."tags""#.to_string()]);
+        assert_eq!(report.tables_to_alter, vec![r#""public"."todos""#.to_string()]);
+        assert!(logs.iter().all(|l| !l.message.contains("CREATE TABLE")));
+        assert!(logs[2].details.as_ref().unwrap().contains("CREATE TABLE"));
+
+        // Entries without a phase still deserialize, and serialize without the new keys
+        let legacy: LogEntry = serde_json::from_value(serde_json::json!({
+            "id": project_id, "project_id": null, "level": "info", "source": "system",
+            "message": "old", "details": null, "timestamp": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert!(legacy.phase.is_none());
+        assert!(!serde_json::to_string(&legacy).unwrap().contains("phase"));
+    }
+
+    #[test]
+    fn test_preflight_create_mode_requires_empty_or_no_supabase_dir() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_preflight_{}", Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+
+        // Empty folders and folders without supabase/ are fine
+        assert!(preflight_project_folder(&dir, false).is_empty());
+        std::fs::write(dir.join("package.json"), "{}").unwrap();
+        assert!(preflight_project_folder(&dir, false).is_empty());
+
+        std::fs::create_dir(dir.join("supabase")).unwrap();
+        let warnings = preflight_project_folder(&dir, false);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "existing_supabase_dir");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_preflight_link_mode_warns_about_existing_schema() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_preflight_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("supabase/schemas")).unwrap();
+        assert!(preflight_project_folder(&dir, true).is_empty());
+
+        std::fs::write(dir.join("supabase/schemas/schema.sql"), "CREATE TABLE t (id int);").unwrap();
+        let warnings = preflight_project_folder(&dir, true);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "existing_schema");
+        assert!(warnings[0].path.ends_with("schemas"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_detect_project_layout() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_layout_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(detect_project_layout(&dir), ProjectLayout::Empty);
+
+        // An empty migrations folder is still empty
+        std::fs::create_dir_all(dir.join("supabase/migrations")).unwrap();
+        assert_eq!(detect_project_layout(&dir), ProjectLayout::Empty);
+
+        std::fs::write(dir.join("supabase/migrations/20240101000000_init.sql"), "CREATE TABLE t (id int);").unwrap();
+        assert_eq!(detect_project_layout(&dir), ProjectLayout::Migrations);
+
+        let schemas_only = std::env::temp_dir().join(format!("harbor_test_layout_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(schemas_only.join("supabase/schemas")).unwrap();
+        std::fs::write(schemas_only.join("supabase/schemas/01_tables.sql"), "CREATE TABLE t (id int);").unwrap();
+        assert_eq!(detect_project_layout(&schemas_only), ProjectLayout::Schemas);
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&schemas_only);
+    }
+
+    #[tokio::test]
+    async fn test_typescript_content_type_does_not_overwrite_multipart_files() {
+        let func_dir = std::env::temp_dir().join(format!("harbor_test_func_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&func_dir).unwrap();
+
+        let body = crate::supabase_api::FunctionBody {
+            content_type: "multipart/form-data; boundary=x; typescript".to_string(),
+            data: b"--x raw multipart payload".to_vec(),
+            files: vec![crate::supabase_api::FunctionFile {
+                name: "source/index.ts".to_string(),
+                content: b"Deno.serve(() => new Response('ok'));".to_vec(),
+            }],
+            metadata: Default::default(),
+        };
+
+        assert!(save_function_body(&body, "hello", &func_dir, &FileSink::default()).await.unwrap());
+        assert_eq!(
+            std::fs::read_to_string(func_dir.join("index.ts")).unwrap(),
+            "Deno.serve(() => new Response('ok'));"
+        );
+
+        let _ = std::fs::remove_dir_all(&func_dir);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_function_save_records_files_without_writing() {
+        let func_dir = std::env::temp_dir().join(format!("harbor_test_func_{}", Uuid::new_v4()));
+        let body = crate::supabase_api::FunctionBody {
+            content_type: "application/typescript".to_string(),
+            data: b"Deno.serve(() => new Response('ok'));".to_vec(),
+            files: vec![],
+            metadata: Default::default(),
+        };
+
+        let sink = FileSink::new(true);
+        assert!(save_function_body(&body, "hello", &func_dir, &sink).await.unwrap());
+        assert_eq!(
+            sink.operations(),
+            vec![crate::file_sink::FileOperation::Write {
+                path: func_dir.join("index.ts").to_string_lossy().to_string(),
+                bytes: body.data.len(),
+            }]
+        );
+        assert!(!func_dir.exists());
+    }
+
+    #[test]
+    fn test_function_slug_validation() {
+        for slug in ["hello", "hello-world", "stripe_webhook", "Resize2", "a"] {
+            assert!(validate_function_slug(slug).is_ok(), "'{}' should be valid", slug);
+        }
+        for slug in ["", "My Function", "1-function", "-leading", "_shared-ish", "héllo", "emoji-🚀", "a/b", "a.b"] {
+            assert!(validate_function_slug(slug).is_err(), "'{}' should be invalid", slug);
+        }
+    }
+
+    #[test]
+    fn test_invalid_function_dir_suggests_sanitized_slug() {
+        assert_eq!(suggest_function_slug("My Function").as_deref(), Some("my-function"));
+        assert_eq!(suggest_function_slug("  send  email!! ").as_deref(), Some("send-email"));
+        assert_eq!(suggest_function_slug("2fa-check").as_deref(), Some("fa-check"));
+        assert_eq!(suggest_function_slug("café").as_deref(), Some("caf"));
+        assert_eq!(suggest_function_slug("🚀🚀"), None);
+
+        let dir = Path::new("/app/supabase/functions/My Function");
+        let err = validate_function_dir_slug("My Function", dir).unwrap_err();
+        assert!(err.contains("/app/supabase/functions/My Function"));
+        assert!(err.contains("Rename the directory to 'my-function'"));
+        assert!(validate_function_dir_slug("my-function", dir).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_hash_deploy_files_streams_like_in_memory() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_hash_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Larger than one read chunk
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(dir.join("index.ts"), &big).unwrap();
+        std::fs::write(dir.join("deno.json"), "{}").unwrap();
+
+        let on_disk = function_deploy_files(&dir).await.unwrap();
+        let in_memory = vec![
+            DeployFile::Bytes { path: "index.ts".to_string(), content: big },
+            DeployFile::Bytes { path: "deno.json".to_string(), content: b"{}".to_vec() },
+        ];
+        let hash = hash_deploy_files(&on_disk).await.unwrap();
+        assert_eq!(hash, hash_deploy_files(&in_memory).await.unwrap());
+
+        std::fs::write(dir.join("deno.json"), "{ }").unwrap();
+        let edited = function_deploy_files(&dir).await.unwrap();
+        assert_ne!(hash, hash_deploy_files(&edited).await.unwrap());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_function_status_reports_dirty_and_clean_functions() {
+        let project_dir = std::env::temp_dir().join(format!("harbor_test_funcs_{}", Uuid::new_v4()));
+        let functions_dir = project_dir.join("supabase/functions");
+        for (slug, source) in [("clean", "export const a = 1;"), ("dirty", "export const b = 1;")] {
+            std::fs::create_dir_all(functions_dir.join(slug)).unwrap();
+            std::fs::write(functions_dir.join(slug).join("index.ts"), source).unwrap();
+            let files = function_deploy_files(&functions_dir.join(slug)).await.unwrap();
+            std::fs::write(functions_dir.join(slug).join(".harbor_hash"), hash_deploy_files(&files).await.unwrap()).unwrap();
+        }
+        // Edited after its last deploy
+        std::fs::write(functions_dir.join("dirty/index.ts"), "export const b = 2;").unwrap();
+
+        let remote_function = |slug: &str, version: i32| crate::supabase_api::EdgeFunction {
+            id: slug.to_string(),
+            slug: slug.to_string(),
+            name: slug.to_string(),
+            status: "ACTIVE".to_string(),
+            version,
+            created_at: serde_json::Value::Null,
+            updated_at: serde_json::Value::Null,
+            entrypoint_path: None,
+        };
+        let remote = vec![remote_function("dirty", 7), remote_function("clean", 3), remote_function("remote-only", 1)];
+
+        let statuses = function_statuses(&project_dir, &remote).await.unwrap();
+        let summary: Vec<(&str, Option<i32>, bool)> = statuses
+            .iter()
+            .map(|s| (s.slug.as_str(), s.deployed_version, s.dirty))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("clean", Some(3), false), ("dirty", Some(7), true), ("remote-only", Some(1), true)]
+        );
+        assert!(statuses[0].local_hash.is_some());
+        assert!(statuses[2].local_hash.is_none());
+
+        // compute_edge_functions_diff agrees on what needs deploying
+        let changed = compute_edge_functions_diff(&project_dir).await.unwrap();
+        assert_eq!(changed.iter().map(|c| c.slug.as_str()).collect::<Vec<_>>(), vec!["dirty"]);
+
+        let _ = std::fs::remove_dir_all(&project_dir);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_operation_continues_after_a_failure() {
+        let linked = |name: &str| {
+            crate::models::Project::with_remote(
+                name.to_string(),
+                format!("/tmp/{}", name),
+                format!("{}-ref", name),
+                format!("{}-ref", name),
+            )
+        };
+        let projects = vec![
+            linked("broken"),
+            linked("healthy"),
+            crate::models::Project::new("local-only".to_string(), "/tmp/local-only".to_string()),
+        ];
+        let attempted = std::sync::Mutex::new(Vec::new());
+
+        let results = run_for_linked_projects(projects, 2, |project| {
+            attempted.lock().unwrap().push(project.name.clone());
+            async move {
+                if project.name == "broken" {
+                    Err("Migration failed: syntax error".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+        let summary: Vec<(&str, &str)> = results.iter().map(|r| (r.name.as_str(), r.status.as_str())).collect();
+        assert_eq!(summary, vec![("broken", "error"), ("healthy", "success"), ("local-only", "skipped")]);
+        assert_eq!(results[0].error.as_deref(), Some("Migration failed: syntax error"));
+        assert_eq!(attempted.into_inner().unwrap().len(), 2, "unlinked projects are not attempted");
+
+        let log = bulk_summary_log("Push all", &results);
+        assert_eq!(log.message, "Push all: 1 succeeded, 1 failed, 1 skipped");
+        assert_eq!(log.details.as_deref(), Some("broken: Migration failed: syntax error"));
+    }
+
+    #[tokio::test]
+    async fn test_function_sync_skipped_when_disabled() {
+        let called = &std::sync::atomic::AtomicBool::new(false);
+        let sync = move || async move {
+            called.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok::<_, String>(vec!["hello-world".to_string()])
+        };
+
+        let skipped = sync_functions_if_enabled(false, sync).await.unwrap();
+        assert_eq!(skipped, None);
+        assert!(!called.load(std::sync::atomic::Ordering::SeqCst), "sync must not run when disabled");
+
+        let synced = sync_functions_if_enabled(true, sync).await.unwrap();
+        assert_eq!(synced, Some(vec!["hello-world".to_string()]));
+        assert!(called.load(std::sync::atomic::Ordering::SeqCst));
+
+        // The per-project default applies unless the call overrides it
+        let mut project = crate::models::Project::new("p".to_string(), "/tmp/p".to_string());
+        assert!(project.sync_functions_enabled(None));
+        project.sync_functions = false;
+        assert!(!project.sync_functions_enabled(None));
+        assert!(project.sync_functions_enabled(Some(true)));
+    }
+
+    #[test]
+    fn test_safe_mode_push_never_drops() {
+        let remote = crate::parsing::parse_schema_sql(&[(
+            "remote.sql".to_string(),
+            r#"CREATE TABLE public.todos (id bigint PRIMARY KEY, title text, legacy text);
+CREATE TABLE public.audit_log (id bigint PRIMARY KEY);
+CREATE VIEW public.open_todos AS SELECT id FROM public.todos;
+CREATE FUNCTION public.cleanup() RETURNS void LANGUAGE sql AS $$ SELECT 1 $$;"#
+                .to_string(),
+        )])
+        .unwrap();
+        let local = crate::parsing::parse_schema_sql(&[(
+            "schema.sql".to_string(),
+            "CREATE TABLE public.todos (id bigint PRIMARY KEY, title text, done boolean);".to_string(),
+        )])
+        .unwrap();
+
+        let mut project = crate::models::Project::new("p".to_string(), "/tmp/p".to_string());
+        assert!(project.safe_mode, "new projects start in safe mode");
+
+        let diff = crate::diff::compute_diff_with_options(&remote, &local, &project.diff_options());
+        let sql = crate::generator::generate_sql(&diff, &local);
+        assert!(sql.contains("ADD COLUMN \"done\""), "{}", sql);
+        assert!(!sql.contains("DROP "), "{}", sql);
+        assert!(check_safe_mode(project.safe_mode, &diff).is_ok());
+
+        // Changes that lose data are refused outright, not just confirmed
+        let retyped = crate::parsing::parse_schema_sql(&[(
+            "schema.sql".to_string(),
+            "CREATE TABLE public.todos (id bigint PRIMARY KEY, title integer, legacy text);".to_string(),
+        )])
+        .unwrap();
+        let diff = crate::diff::compute_diff_with_options(&remote, &retyped, &project.diff_options());
+        let err = check_safe_mode(project.safe_mode, &diff).unwrap_err();
+        assert!(err.starts_with("Safe mode blocks destructive changes"), "{}", err);
+
+        // Opting out restores replace-mode drops
+        project.safe_mode = false;
+        let diff = crate::diff::compute_diff_with_options(&remote, &local, &project.diff_options());
+        assert!(crate::generator::generate_sql(&diff, &local).contains("DROP TABLE"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_migration_file() {
+        use crate::supabase_api::mock::MockApi;
+
+        let dir = std::env::temp_dir().join(format!("harbor_test_migration_{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(
+            dir.join("20240101000000_tags.sql"),
+            "BEGIN;\nCREATE TABLE public.tags (id bigint PRIMARY KEY);\nCREATE INDEX CONCURRENTLY tags_id_idx ON public.tags (id);\nCOMMIT;\n",
+        )
+        .await
+        .unwrap();
+
+        let api = MockApi::new();
+        let plan = apply_migration_file(&api, "ref", &dir, "20240101000000_tags.sql", false, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.transactional, vec!["CREATE TABLE public.tags (id bigint PRIMARY KEY);".to_string()]);
+        assert_eq!(
+            api.writes(),
+            vec![
+                "BEGIN;\nCREATE TABLE public.tags (id bigint PRIMARY KEY);\nCOMMIT;".to_string(),
+                "CREATE INDEX CONCURRENTLY tags_id_idx ON public.tags (id);".to_string(),
+            ]
+        );
+
+        // Destructive migrations need confirmation first
+        tokio::fs::write(dir.join("20240102000000_drop.sql"), "DROP TABLE public.tags;").await.unwrap();
+        let err = apply_migration_file(&api, "ref", &dir, "20240102000000_drop.sql", false, false)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("CONFIRMATION_NEEDED:"), "{}", err);
+        assert_eq!(api.writes().len(), 2);
+
+        // Safe mode refuses them even when confirmed
+        let err = apply_migration_file(&api, "ref", &dir, "20240102000000_drop.sql", true, true)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("Safe mode blocks destructive changes"), "{}", err);
+        assert_eq!(api.writes().len(), 2);
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_apply_migration_rejects_path_traversal() {
+        use crate::supabase_api::mock::MockApi;
+
+        let dir = std::env::temp_dir().join(format!("harbor_test_migration_{}", Uuid::new_v4()));
+        let api = MockApi::new();
+        for filename in ["../secret.sql", "nested/20240101000000_x.sql", "..\\secret.sql", "/etc/x.sql", "..", "notes.txt", ""] {
+            let err = apply_migration_file(&api, "ref", &dir, filename, false, true).await.unwrap_err();
+            assert!(err.starts_with("Invalid migration filename"), "{}", err);
+        }
+        assert!(api.queries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_migration_to_remote_skips_migrated_objects() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_migrations_{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(
+            dir.join("20240101000000_init.sql"),
+            "CREATE TABLE public.todos (id bigint PRIMARY KEY, title text);\nCREATE TABLE public.drafts (id bigint PRIMARY KEY);",
+        )
+        .await
+        .unwrap();
+
+        let remote = |sql: &str| crate::parsing::parse_schema_sql(&[("remote.sql".to_string(), sql.to_string())]).unwrap();
+        let migrated = remote("CREATE TABLE public.todos (id bigint PRIMARY KEY, title text);");
+        assert_eq!(migration_to_remote(&dir, &migrated).await.unwrap(), None);
+
+        let ahead = remote(
+            "CREATE TABLE public.todos (id bigint PRIMARY KEY, title text);\nCREATE TABLE public.tags (id bigint PRIMARY KEY);",
+        );
+        let sql = migration_to_remote(&dir, &ahead).await.unwrap().unwrap();
+        assert!(sql.contains("CREATE TABLE \"public\".\"tags\""), "{}", sql);
+        assert!(!sql.contains("\"todos\""), "{}", sql);
+        assert!(!sql.contains("DROP"), "{}", sql);
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_migration_to_remote_applies_later_alter_table() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_migrations_{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(
+            dir.join("20240101000000_init.sql"),
+            "CREATE TABLE public.todos (id bigint PRIMARY KEY, title text, legacy text);",
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            dir.join("20240201000000_todos_done.sql"),
+            "ALTER TABLE public.todos ADD COLUMN done boolean NOT NULL DEFAULT false;\n\
+             ALTER TABLE public.todos DROP COLUMN legacy;\n\
+             ALTER TABLE public.todos RENAME COLUMN title TO name;",
+        )
+        .await
+        .unwrap();
+
+        let remote = crate::parsing::parse_schema_sql(&[(
+            "remote.sql".to_string(),
+            "CREATE TABLE public.todos (id bigint PRIMARY KEY, name text, done boolean NOT NULL DEFAULT false);".to_string(),
+        )])
+        .unwrap();
+        assert_eq!(migration_to_remote(&dir, &remote).await.unwrap(), None);
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_diff_schema_files_added_column() {
+        let dir = std::env::temp_dir().join(format!("harbor_test_diff_files_{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let base = dir.join("base.sql");
+        let target = dir.join("target.sql");
+        tokio::fs::write(&base, "CREATE TABLE public.todos (id bigint PRIMARY KEY);").await.unwrap();
+        tokio::fs::write(&target, "CREATE TABLE public.todos (id bigint PRIMARY KEY, title text);")
+            .await
+            .unwrap();
+
+        let report = diff_schema_files(&base, &target).await.unwrap();
+        assert_eq!(report.tables_to_alter, vec!["\"public\".\"todos\"".to_string()]);
+        assert!(report.tables_to_create.is_empty() && report.tables_to_drop.is_empty());
+        assert!(!report.is_destructive);
+        assert!(report.migration_sql.contains("ADD COLUMN \"title\" text"), "{}", report.migration_sql);
+
+        // The other way round drops it
+        let report = diff_schema_files(&target, &base).await.unwrap();
+        assert!(report.is_destructive);
+        assert!(report.migration_sql.contains("DROP COLUMN IF EXISTS \"title\""), "{}", report.migration_sql);
+
+        assert!(diff_schema_files(&dir.join("missing.sql"), &target).await.is_err());
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
 }
@@ -12,6 +12,7 @@ use uuid::Uuid;
 
 use crate::models::{FileChange, FileChangeType, LogEntry, LogSource};
 use crate::state::AppState;
+use crate::file_sink::FileSink;
 use crate::sync;
 use crate::tray::update_icon;
 
@@ -223,6 +224,18 @@ async fn handle_schema_push(
         debounce.insert(project_id, now);
     }
 
+    // A manual pull or push is already running; it covers this change
+    let Ok(_lock) = state.try_lock_project(project_id).await else {
+        let log = LogEntry::info(
+            Some(project_id),
+            LogSource::Schema,
+            "Auto-push skipped: another operation is in progress for this project".to_string(),
+        );
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+        return Ok(());
+    };
+
     // Get project details
     let project = state.get_project(project_id).await.map_err(|e| e.to_string())?;
     
@@ -241,7 +254,7 @@ async fn handle_schema_push(
     };
 
     // Get API client
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(project_id).await.map_err(|e| e.to_string())?;
 
     update_icon(&app_handle, true);
 
@@ -270,8 +283,12 @@ async fn handle_schema_push(
     };
 
     // Compute diff using shared sync module (introspect remote, parse local, compute diff)
-    let diff_result = match sync::compute_schema_diff(&api, &project_ref, &schema_source).await {
-        Ok(r) => r,
+    let cached_remote = state.load_remote_schema_cache(project_id).await;
+    let diff_result = match sync::compute_schema_diff(&api, &project_ref, &schema_source, &project.diff_options(), &project.generate_options(), cached_remote).await {
+        Ok(r) => {
+            state.save_remote_schema_cache(project_id, &r.remote).await;
+            r
+        }
         Err(e) => {
             let log = LogEntry::error(
                 Some(project_id),
@@ -285,7 +302,56 @@ async fn handle_schema_push(
         }
     };
 
-    let diff = diff_result.diff;
+    for statement in &diff_result.unparsed {
+        let log = LogEntry::warning(
+            Some(project_id),
+            LogSource::Schema,
+            format!("Skipped statement in {}: {}", statement.file, statement.reason),
+        )
+        .with_details(statement.text.clone());
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+    }
+
+    let diff = &diff_result.diff;
+
+    // Don't silently revert remote changes made since the last pull/push; ask instead
+    if !diff.is_empty() {
+        let baseline = state.load_schema_baseline(project_id).await;
+        if let Err(err) = sync::check_divergence(baseline.as_ref(), &diff_result.remote, &project.diff_options()) {
+            let log = LogEntry::warning(
+                Some(project_id),
+                LogSource::Schema,
+                "Remote schema changed since last pull. Waiting for user confirmation...".to_string(),
+            );
+            state.add_log(log.clone()).await;
+            app_handle.emit("log", &log).ok();
+
+            #[derive(serde::Serialize, Clone)]
+            struct DivergedPayload {
+                project_id: Uuid,
+                summary: String,
+            }
+
+            app_handle.emit("schema-push-remote-diverged", DivergedPayload {
+                project_id,
+                summary: err.trim_start_matches("REMOTE_DIVERGED:").to_string(),
+            }).ok();
+
+            let _ = app_handle.get_webview_window("main").map(|w| w.request_user_attention(Some(tauri::UserAttentionType::Critical)));
+
+            update_icon(&app_handle, false);
+            return Ok(());
+        }
+    }
+
+    if let Err(err) = sync::check_rebuild_required(diff) {
+        let log = LogEntry::error(Some(project_id), LogSource::Schema, err.clone());
+        state.add_log(log.clone()).await;
+        app_handle.emit("log", &log).ok();
+        update_icon(&app_handle, false);
+        return Err(err);
+    }
 
     if diff.is_destructive() {
         let summary = diff.summarize();
@@ -319,6 +385,9 @@ async fn handle_schema_push(
     let migration_sql = &diff_result.migration_sql;
 
     if migration_sql.trim().is_empty() {
+        // Local and remote agree, so the current remote is the new baseline
+        state.save_schema_baseline(project_id, &diff_result.remote).await;
+
         let log = LogEntry::success(
             Some(project_id),
             LogSource::Schema,
@@ -339,9 +408,7 @@ async fn handle_schema_push(
     app_handle.emit("log", &log).ok();
 
     // 5. Execute
-    let result = api.run_query(&project_ref, &migration_sql, false).await.map_err(|e| e.to_string())?;
-
-    if let Some(err) = result.error {
+    if let Err(err) = sync::execute_migration(&api, &project_ref, &diff_result.migration).await {
         let log = LogEntry::error(
             Some(project_id),
             LogSource::Schema,
@@ -361,6 +428,10 @@ async fn handle_schema_push(
     state.add_log(log.clone()).await;
     app_handle.emit("log", &log).ok();
 
+    state.clear_cached_schema(project_id).await;
+    sync::save_pushed_baseline(&state, &api, &project, &project_ref, &diff_result.local_schema).await;
+    sync::record_sync_time(&state, project_id, true).await;
+
     update_icon(&app_handle, false);
     Ok(())
 }
@@ -414,7 +485,7 @@ async fn handle_edge_function_push(
     }
 
     // Get API client
-    let api = state.get_api_client().await.map_err(|e| e.to_string())?;
+    let api = state.get_project_api_client(project_id).await.map_err(|e| e.to_string())?;
 
     update_icon(&app_handle, true);
 
@@ -476,7 +547,15 @@ async fn handle_edge_function_push(
                     .join("functions")
                     .join(&slug);
 
-                let files = match sync::collect_function_files(&function_dir).await {
+                if let Err(e) = sync::validate_function_dir_slug(&slug, &function_dir) {
+                    let log = LogEntry::error(Some(project_id), LogSource::EdgeFunction, e.clone());
+                    state.add_log(log.clone()).await;
+                    app_handle.emit("log", &log).ok();
+                    return (slug, Err(e), None);
+                }
+
+                // Only paths and sizes are read up front; contents are hashed and uploaded in chunks
+                let files = match sync::function_deploy_files(&function_dir).await {
                     Ok(f) => f,
                     Err(e) => {
                         let log = LogEntry::error(
@@ -494,9 +573,14 @@ async fn handle_edge_function_push(
                     return (slug, Ok(None), None);
                 }
 
-                let entrypoint = sync::determine_entrypoint(&files);
-                let local_hash = sync::compute_files_hash(&files);
+                let paths: Vec<&str> = files.iter().map(|f| f.path()).collect();
+                let entrypoint = sync::entrypoint_from_paths(&paths);
+                let local_hash = match sync::hash_deploy_files(&files).await {
+                    Ok(hash) => hash,
+                    Err(e) => return (slug, Err(format!("Failed to read files: {}", e)), None),
+                };
                 let hash_file = function_dir.join(".harbor_hash");
+                let deploy_files = files;
 
                 // Deploy with retry (up to 3 attempts for transient failures)
                 let mut last_err = String::new();
@@ -507,7 +591,7 @@ async fn handle_edge_function_push(
                     }
 
                     match api.deploy_function(
-                        &project_ref, &slug, &slug, &entrypoint, files.clone(), use_bundle_only,
+                        &project_ref, &slug, &slug, &entrypoint, deploy_files.clone(), use_bundle_only,
                     ).await {
                         Ok(result) => {
                             let log_msg = if use_bundle_only {
@@ -692,7 +776,7 @@ async fn handle_typescript_generation(
     app_handle.emit("log", &log).ok();
 
     // Generate TypeScript types from the already-read schema SQL
-    match sync::generate_typescript_types_from_sql(&schema_sql, &ts_output_path).await {
+    match sync::generate_typescript_types_from_sql(&schema_sql, &ts_output_path, &FileSink::default()).await {
         Ok(()) => {
             let relative_output = ts_output_path
                 .strip_prefix(project_path)
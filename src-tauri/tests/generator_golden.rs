@@ -0,0 +1,201 @@
+//! Golden-file tests for migration SQL: each scenario diffs a pair of fixture schemas
+//! and compares the generated SQL byte-for-byte with `tests/golden/<scenario>.sql`.
+//!
+//! After an intended change to the generator, rewrite the goldens with
+//! `UPDATE_GOLDENS=1 cargo test --test generator_golden` and review the diff.
+
+use std::path::PathBuf;
+
+use harbor_lib::diff::compute_diff;
+use harbor_lib::generator::{generate_migration_with_options, generate_sql, GenerateOptions, NameQualification};
+use harbor_lib::schema::{qualified_name, ColumnInfo, DbSchema, EnumInfo, FunctionInfo, TableInfo, TriggerInfo};
+
+fn golden_path(scenario: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.sql", scenario))
+}
+
+fn assert_golden(scenario: &str, remote: &DbSchema, local: &DbSchema) {
+    let diff = compute_diff(remote, local);
+    assert_golden_sql(scenario, &generate_sql(&diff, local));
+}
+
+fn assert_golden_sql(scenario: &str, sql: &str) {
+    let actual = format!("{}\n", sql);
+    let path = golden_path(scenario);
+
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!("Missing golden file {} ({}); run with UPDATE_GOLDENS=1 to create it", path.display(), e)
+    });
+    assert!(
+        actual == expected,
+        "Generated SQL for '{}' differs from {}\n--- expected\n{}--- actual\n{}\nRun with UPDATE_GOLDENS=1 if the change is intended",
+        scenario,
+        path.display(),
+        expected,
+        actual
+    );
+}
+
+fn column(name: &str, data_type: &str, position: i32) -> ColumnInfo {
+    ColumnInfo {
+        column_name: name.to_string(),
+        data_type: data_type.to_string(),
+        udt_name: data_type.to_string(),
+        is_nullable: true,
+        ordinal_position: Some(position),
+        ..Default::default()
+    }
+}
+
+fn table(name: &str, columns: Vec<ColumnInfo>) -> TableInfo {
+    TableInfo {
+        schema: "public".to_string(),
+        table_name: name.to_string(),
+        columns: columns.into_iter().map(|c| (c.column_name.clone(), c)).collect(),
+        ..Default::default()
+    }
+}
+
+fn todos(extra: ColumnInfo) -> TableInfo {
+    let mut id = column("id", "bigint", 1);
+    id.is_nullable = false;
+    id.is_primary_key = true;
+    table("todos", vec![id, extra])
+}
+
+fn with_table(table: TableInfo) -> DbSchema {
+    let mut schema = DbSchema::new();
+    schema.tables.insert(qualified_name(&table.schema, &table.table_name), table);
+    schema
+}
+
+fn with_enum(name: &str, values: &[&str]) -> DbSchema {
+    let mut schema = DbSchema::new();
+    schema.enums.insert(
+        qualified_name("public", name),
+        EnumInfo {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+            extension: None,
+        },
+    );
+    schema
+}
+
+fn with_function(name: &str, definition: &str) -> DbSchema {
+    let mut schema = DbSchema::new();
+    schema.functions.insert(
+        format!("{}()", qualified_name("public", name)),
+        FunctionInfo {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            args: vec![],
+            return_type: "integer".to_string(),
+            language: "sql".to_string(),
+            definition: definition.to_string(),
+            volatility: Some("IMMUTABLE".to_string()),
+            is_strict: false,
+            security_definer: false,
+            config_params: vec![],
+            grants: vec![],
+            extension: None,
+        },
+    );
+    schema
+}
+
+#[test]
+fn golden_new_table() {
+    let mut title = column("title", "text", 2);
+    title.is_nullable = false;
+    let mut local = todos(title);
+    let mut done = column("done", "boolean", 3);
+    done.column_default = Some("false".to_string());
+    local.columns.insert("done".to_string(), done);
+
+    assert_golden("new_table", &DbSchema::new(), &with_table(local));
+}
+
+#[test]
+fn golden_drop_table() {
+    let remote = with_table(todos(column("title", "text", 2)));
+
+    assert_golden("drop_table", &remote, &DbSchema::new());
+}
+
+#[test]
+fn golden_column_modify() {
+    let remote = with_table(todos(column("priority", "integer", 2)));
+    let mut priority = column("priority", "bigint", 2);
+    priority.is_nullable = false;
+    priority.column_default = Some("0".to_string());
+    let local = with_table(todos(priority));
+
+    assert_golden("column_modify", &remote, &local);
+}
+
+#[test]
+fn golden_enum_add_value() {
+    let remote = with_enum("status", &["todo", "done"]);
+    let local = with_enum("status", &["todo", "doing", "done"]);
+
+    assert_golden("enum_add_value", &remote, &local);
+}
+
+#[test]
+fn golden_function_update() {
+    let remote = with_function("answer", "select 41");
+    let local = with_function("answer", "select 42");
+
+    assert_golden("function_update", &remote, &local);
+}
+
+/// A function update plus a new table whose trigger function is written without a schema
+fn generate_qualification_sample(qualification: NameQualification) -> String {
+    // `touch` is unchanged, so it only tells `Always` that the trigger function lives in public
+    let mut remote = with_function("answer", "select 41");
+    let mut local = with_function("answer", "select 42");
+    remote.functions.extend(with_function("touch", "select 1").functions);
+    local.functions.extend(with_function("touch", "select 1").functions);
+
+    let mut title = column("title", "text", 2);
+    title.is_nullable = false;
+    let mut table = todos(title);
+    let mut done = column("done", "boolean", 3);
+    done.column_default = Some("false".to_string());
+    table.columns.insert("done".to_string(), done);
+    table.triggers.push(TriggerInfo {
+        name: "todos_touch".to_string(),
+        events: vec!["UPDATE".to_string()],
+        timing: "BEFORE".to_string(),
+        orientation: "ROW".to_string(),
+        function_name: "touch".to_string(),
+        when_clause: None,
+    });
+    local.tables.insert(qualified_name("public", "todos"), table);
+
+    let options = GenerateOptions { qualification, ..Default::default() };
+    let plan = generate_migration_with_options(&compute_diff(&remote, &local), &local, &options);
+    assert!(plan.non_transactional.is_empty());
+    plan.transactional.join("\n")
+}
+
+#[test]
+fn golden_qualification_always() {
+    assert_golden_sql("qualification_always", &generate_qualification_sample(NameQualification::Always));
+}
+
+#[test]
+fn golden_qualification_omit_public() {
+    assert_golden_sql("qualification_omit_public", &generate_qualification_sample(NameQualification::OmitPublic));
+}
@@ -0,0 +1,3 @@
+ALTER TABLE "public"."todos" ALTER COLUMN "priority" TYPE bigint USING "priority"::bigint;
+ALTER TABLE "public"."todos" ALTER COLUMN "priority" SET NOT NULL;
+ALTER TABLE "public"."todos" ALTER COLUMN "priority" SET DEFAULT 0;
@@ -0,0 +1 @@
+CREATE OR REPLACE FUNCTION "public"."answer"() RETURNS integer LANGUAGE sql IMMUTABLE AS $$select 42$$;
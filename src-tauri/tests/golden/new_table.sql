@@ -0,0 +1,6 @@
+CREATE TABLE "public"."todos" (
+  "id" bigint,
+  "title" text NOT NULL,
+  "done" boolean DEFAULT false,
+  PRIMARY KEY ("id")
+);
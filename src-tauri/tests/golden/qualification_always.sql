@@ -0,0 +1,8 @@
+CREATE OR REPLACE FUNCTION "public"."answer"() RETURNS integer LANGUAGE sql IMMUTABLE AS $$select 42$$;
+CREATE TABLE "public"."todos" (
+  "id" bigint,
+  "title" text NOT NULL,
+  "done" boolean DEFAULT false,
+  PRIMARY KEY ("id")
+);
+CREATE TRIGGER "todos_touch" BEFORE UPDATE ON "public"."todos" FOR EACH ROW EXECUTE FUNCTION "public"."touch"();
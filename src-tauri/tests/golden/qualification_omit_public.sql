@@ -0,0 +1,8 @@
+CREATE OR REPLACE FUNCTION "answer"() RETURNS integer LANGUAGE sql IMMUTABLE AS $$select 42$$;
+CREATE TABLE "todos" (
+  "id" bigint,
+  "title" text NOT NULL,
+  "done" boolean DEFAULT false,
+  PRIMARY KEY ("id")
+);
+CREATE TRIGGER "todos_touch" BEFORE UPDATE ON "todos" FOR EACH ROW EXECUTE FUNCTION touch();
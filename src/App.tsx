@@ -148,6 +148,51 @@ function App() {
       }
     });
 
+    const unlistenDiverged = listen<{
+      project_id: string;
+      summary: string;
+    }>("schema-push-remote-diverged", async (event) => {
+      const confirmed = await ask(
+        `The remote schema changed since your last pull, so auto-push was paused:\n\n${event.payload.summary}\n\nPushing will overwrite these changes. Pull first to merge them, or push anyway?`,
+        {
+          title: "Remote Schema Changed",
+          kind: "warning",
+          okLabel: "Push Anyway",
+          cancelLabel: "Cancel Push",
+        },
+      );
+      if (!confirmed) return;
+
+      const projectId = event.payload.project_id;
+      try {
+        await api.pushProject(projectId, false, undefined, undefined, undefined, true);
+      } catch (err) {
+        const errorMsg = String(err);
+        if (!errorMsg.startsWith("CONFIRMATION_NEEDED:")) {
+          console.error("Failed to push project:", err);
+          notify("Push Failed", `Failed to push project: ${err}`);
+          return;
+        }
+        const force = await ask(
+          `Destructive changes detected!\n\n${errorMsg.replace("CONFIRMATION_NEEDED:", "")}\n\nDo you want to force push these changes?`,
+          {
+            title: "Destructive Changes Detected",
+            kind: "warning",
+            okLabel: "Force Push Changes",
+            cancelLabel: "Cancel Push",
+          },
+        );
+        if (force) {
+          try {
+            await api.pushProject(projectId, true, undefined, undefined, undefined, true);
+          } catch (retryErr) {
+            console.error("Failed to push project (forced):", retryErr);
+            notify("Push Failed", `Failed to push project: ${retryErr}`);
+          }
+        }
+      }
+    });
+
     // Listen for deep link events from Rust backend
     // This is triggered when the app is opened via a harbor:// URL
     const unlistenDeeplink = listen<string>("deep-link-received", (event) => {
@@ -164,6 +209,7 @@ function App() {
     return () => {
       unlistenFileChange.then((fn) => fn());
       unlistenConfirmation.then((fn) => fn());
+      unlistenDiverged.then((fn) => fn());
       unlistenDeeplink.then((fn) => fn());
       unlistenMenuImport.then((fn) => fn());
     };
@@ -1,5 +1,22 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { LogEntry, Project, ProjectKeys, RemoteProject } from "./types";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type {
+  CreatedProject,
+  Dependent,
+  DiffReport,
+  FileOperation,
+  LogEntry,
+  PreflightWarning,
+  Project,
+  ProjectKeys,
+  ProjectStatus,
+  PullStatus,
+  RemoteProject,
+  SchemaAnalysis,
+  SchemaSummary,
+} from "./types";
+
+export type { DiffReport };
 
 const DEFAULT_RUN_QUERY_TIMEOUT_MS = 60_000;
 
@@ -49,6 +66,42 @@ export async function validateAccessToken(): Promise<boolean> {
   return invoke("validate_access_token");
 }
 
+// Commands fail with an "AUTH_EXPIRED:" message when the token was rejected
+export function isAuthExpiredError(error: unknown): boolean {
+  return String(error).startsWith("AUTH_EXPIRED:");
+}
+
+// Accounts
+export interface AccountInfo {
+  name: string;
+  active: boolean;
+}
+
+export async function addAccount(name: string, token: string): Promise<void> {
+  return invoke("add_account", { name, token });
+}
+
+export async function listAccounts(): Promise<AccountInfo[]> {
+  return invoke("list_accounts");
+}
+
+// Pass null to go back to the single access token
+export async function setActiveAccount(name: string | null): Promise<void> {
+  return invoke("set_active_account", { name });
+}
+
+export async function removeAccount(name: string): Promise<void> {
+  return invoke("remove_account", { name });
+}
+
+// Bind a project to an account; null follows the active account
+export async function setProjectAccount(
+  projectId: string,
+  account: string | null,
+): Promise<void> {
+  return invoke("set_project_account", { projectId, account });
+}
+
 // OpenAI API Key
 export async function setOpenAiKey(key: string): Promise<void> {
   return invoke("set_openai_key", { key });
@@ -62,6 +115,11 @@ export async function clearOpenAiKey(): Promise<void> {
   return invoke("clear_openai_key");
 }
 
+// Checks the key against the configured endpoint; omit `key` to check the stored one
+export async function validateOpenAiKey(key?: string): Promise<boolean> {
+  return invoke("validate_openai_key", { key });
+}
+
 // Remote Supabase Projects API
 export async function listRemoteProjects(): Promise<RemoteProject[]> {
   return invoke("list_remote_projects");
@@ -82,7 +140,10 @@ export async function createProject(
   organizationId?: string,
   generateTypescript: boolean = true,
   typescriptOutputPath?: string,
-): Promise<Project> {
+  region?: string,
+  confirmed?: boolean,
+  dryRun?: boolean,
+): Promise<CreatedProject> {
   return invoke("create_project", {
     name,
     localPath,
@@ -91,9 +152,40 @@ export async function createProject(
     organizationId,
     generateTypescript,
     typescriptOutputPath,
+    region,
+    confirmed,
+    dryRun,
   });
 }
 
+// Warnings to confirm before creating or linking a project in the folder.
+// create_project fails with PREFLIGHT_WARNING: unless called with confirmed.
+export async function checkProjectFolder(
+  localPath: string,
+  linkMode: boolean,
+): Promise<PreflightWarning[]> {
+  return invoke("check_project_folder", { localPath, linkMode });
+}
+
+// Region codes available for new projects in the organization
+export async function listRegions(organizationId: string): Promise<string[]> {
+  return invoke("list_regions", { organizationId });
+}
+
+export async function projectStatus(projectId: string): Promise<ProjectStatus> {
+  return invoke("project_status", { projectId });
+}
+
+// Retry the parts of a linked project's auto-pull that failed
+export async function resumePull(projectId: string): Promise<PullStatus> {
+  return invoke("resume_pull", { projectId });
+}
+
+// Only available for projects created from Harbor
+export async function getProjectDbPassword(projectId: string): Promise<string> {
+  return invoke("get_project_db_password", { projectId });
+}
+
 export async function getProjects(): Promise<Project[]> {
   return invoke("get_projects");
 }
@@ -129,6 +221,11 @@ export async function linkSupabaseProject(
   return invoke("link_supabase_project", { projectId, supabaseProjectRef });
 }
 
+// Also stops watching; push/pull fail until the project is linked again
+export async function unlinkSupabaseProject(projectId: string): Promise<Project> {
+  return invoke("unlink_supabase_project", { projectId });
+}
+
 // Watcher API
 export async function startWatching(projectId: string): Promise<void> {
   return invoke("start_watching", { projectId });
@@ -154,30 +251,152 @@ export async function clearLogs(projectId?: string): Promise<void> {
   return invoke("clear_logs", { projectId });
 }
 
+// Writes the log history as JSON lines; returns the number of entries
+export async function exportLogs(
+  projectId: string | undefined,
+  outPath: string,
+): Promise<number> {
+  return invoke("export_logs", { projectId, outPath });
+}
+
 // Supabase API
-export async function runQuery(
+export interface QueryPage {
+  rows: unknown;
+  // Only set for paginated SELECT queries
+  total_count: number | null;
+  // Rows were dropped because the result was too large
+  truncated: boolean;
+}
+
+export async function runQueryPage(
   projectId: string,
   query: string,
   readOnly?: boolean,
+  page?: { limit: number; offset?: number },
   timeoutMs: number = DEFAULT_RUN_QUERY_TIMEOUT_MS,
-): Promise<unknown> {
+): Promise<QueryPage> {
   return invokeWithTimeout(
     "run_query",
-    { projectId, query, readOnly },
+    { projectId, query, readOnly, limit: page?.limit, offset: page?.offset },
     timeoutMs,
     `Query timed out after ${Math.ceil(timeoutMs / 1000)} seconds`,
   );
 }
 
+export async function runQuery(
+  projectId: string,
+  query: string,
+  readOnly?: boolean,
+  timeoutMs: number = DEFAULT_RUN_QUERY_TIMEOUT_MS,
+): Promise<unknown> {
+  const page = await runQueryPage(projectId, query, readOnly, undefined, timeoutMs);
+  return page.rows;
+}
+
+// EXPLAIN (FORMAT JSON) plan of a single statement. analyze runs the statement,
+// which the backend refuses for writes unless force is set.
+export async function explainQuery(
+  projectId: string,
+  query: string,
+  analyze?: boolean,
+  force?: boolean,
+): Promise<unknown> {
+  return invoke("explain_query", { projectId, query, analyze, force });
+}
+
+// Runs the query read-only and writes every row to outPath; returns the row count
+export async function exportQueryResult(
+  projectId: string,
+  query: string,
+  format: "csv" | "json",
+  outPath: string,
+): Promise<number> {
+  return invoke("export_query_result", { projectId, query, format, outPath });
+}
+
+// Saved queries
+export interface SavedQuery {
+  id: string;
+  name: string;
+  sql: string;
+  created_at: string;
+}
+
+export async function saveQuery(
+  projectId: string,
+  name: string,
+  sql: string,
+): Promise<SavedQuery> {
+  return invoke("save_query", { projectId, name, sql });
+}
+
+export async function listSavedQueries(projectId: string): Promise<SavedQuery[]> {
+  return invoke("list_saved_queries", { projectId });
+}
+
+export async function deleteSavedQuery(
+  projectId: string,
+  queryId: string,
+): Promise<void> {
+  return invoke("delete_saved_query", { projectId, queryId });
+}
+
+export async function runSavedQuery(
+  projectId: string,
+  queryId: string,
+  readOnly?: boolean,
+  page?: { limit: number; offset?: number },
+): Promise<QueryPage> {
+  return invoke("run_saved_query", {
+    projectId,
+    queryId,
+    readOnly,
+    limit: page?.limit,
+    offset: page?.offset,
+  });
+}
+
+export interface SqlError {
+  message: string;
+  line: number | null;
+}
+
+export interface ApplySqlResult {
+  success: boolean;
+  rows_returned: number | null;
+  error: SqlError | null;
+}
+
+export async function applySql(
+  projectId: string,
+  sql: string,
+  readOnly?: boolean,
+  force?: boolean,
+): Promise<ApplySqlResult> {
+  return invoke("apply_sql", { projectId, sql, readOnly, force });
+}
+
 export async function validateSql(sql: string): Promise<void> {
   return invoke("validate_sql", { sql });
 }
 
+export interface FlaggedStatement {
+  sql: string;
+  line: number;
+  reason: string;
+}
+
+export interface AiConvertResult {
+  sql: string;
+  /** Statements that should be reviewed before running (DML, DROPs, ...) */
+  flagged: FlaggedStatement[];
+}
+
 export async function convertWithAi(
   projectId: string,
   input: string,
   errorMessage?: string,
-): Promise<string> {
+): Promise<AiConvertResult> {
   return invoke("convert_with_ai", {
     projectId,
     input,
@@ -185,6 +404,29 @@ export async function convertWithAi(
   });
 }
 
+/** Abort the project's in-flight AI conversion; returns false if none was running. */
+export async function cancelAiConvert(projectId: string): Promise<boolean> {
+  return invoke("cancel_ai_convert", { projectId });
+}
+
+export function isCancelledError(error: unknown): boolean {
+  return String(error).startsWith("CANCELLED:");
+}
+
+export interface AiConvertChunk {
+  project_id: string;
+  delta: string;
+}
+
+/** Receive streamed pieces of an AI conversion as they arrive. */
+export function onAiConvertChunk(
+  handler: (chunk: AiConvertChunk) => void,
+): Promise<UnlistenFn> {
+  return listen<AiConvertChunk>("ai-convert-chunk", (event) =>
+    handler(event.payload),
+  );
+}
+
 export async function runEdgeFunction(
   projectId: string,
   functionName: string,
@@ -230,6 +472,19 @@ export async function runEdgeFunction(
   return results;
 }
 
+export interface FunctionStatus {
+  slug: string;
+  // null when the function only exists remotely
+  local_hash: string | null;
+  // null when the function isn't deployed
+  deployed_version: number | null;
+  dirty: boolean;
+}
+
+export async function functionStatus(projectId: string): Promise<FunctionStatus[]> {
+  return invoke("function_status", { projectId });
+}
+
 export async function deployEdgeFunction(
   projectId: string,
   functionSlug: string,
@@ -255,24 +510,118 @@ export async function getRemoteSchema(projectId: string): Promise<string> {
   return invoke("get_remote_schema", { projectId });
 }
 
-export async function pullProject(projectId: string): Promise<void> {
-  return invoke("pull_project", { projectId });
+// What the target project would need to change to match the source project
+export async function diffProjects(
+  sourceProjectId: string,
+  targetProjectId: string,
+): Promise<DiffReport> {
+  return invoke("diff_projects", { sourceProjectId, targetProjectId });
+}
+
+// What the base schema file would need to change to match the target, offline
+export async function diffSchemaFiles(
+  basePath: string,
+  targetPath: string,
+): Promise<DiffReport> {
+  return invoke("diff_schema_files", { basePath, targetPath });
+}
+
+export interface MigrationFile {
+  filename: string;
+  timestamp: string | null;
+  name: string;
+  statement_count: number;
+  summary: string;
+}
+
+// Migrations in supabase/migrations/, newest first
+export async function listMigrations(projectId: string): Promise<MigrationFile[]> {
+  return invoke("list_migrations", { projectId });
+}
+
+export async function getMigrationContent(
+  projectId: string,
+  filename: string,
+): Promise<string> {
+  return invoke("get_migration_content", { projectId, filename });
+}
+
+// Run a migration file against the linked project. Destructive migrations
+// fail with CONFIRMATION_NEEDED: unless force is set.
+export async function applyMigration(
+  projectId: string,
+  filename: string,
+  force?: boolean,
+): Promise<void> {
+  return invoke("apply_migration", { projectId, filename, force });
+}
+
+// Full introspected schema (DbSchema) as JSON
+export async function exportSchemaJson(projectId: string): Promise<unknown> {
+  return invoke("export_schema_json", { projectId });
+}
+
+export async function introspectRemote(projectId: string): Promise<SchemaSummary> {
+  return invoke("introspect_remote", { projectId });
+}
+
+// Remote objects referencing a table, view, function or type
+export async function findDependents(
+  projectId: string,
+  objectKind: string,
+  objectName: string,
+): Promise<Dependent[]> {
+  return invoke("find_dependents", { projectId, objectKind, objectName });
+}
+
+export async function pullProject(
+  projectId: string,
+  split?: boolean,
+  syncFunctions?: boolean,
+  merge?: boolean,
+  dryRun?: boolean,
+): Promise<FileOperation[]> {
+  return invoke("pull_project", { projectId, split, syncFunctions, merge, dryRun });
 }
 
 export async function pushProject(
   projectId: string,
   force?: boolean,
+  syncFunctions?: boolean,
+  // Rename candidates from the diff report to apply as renames instead of drop + add
+  acceptRenames?: import("./types").ColumnRenameCandidate[],
+  // "auto" splits out statements that can't run in a transaction, "single" runs
+  // everything in one, "none" runs each statement on its own
+  transactionMode?: "auto" | "single" | "none",
+  // Push even though the remote changed since the last pull (REMOTE_DIVERGED:)
+  ignoreDivergence?: boolean,
 ): Promise<import("./types").PushResponse> {
   // 3 minute timeout (backend http client has 2m timeout)
   const TIMEOUT = 180_000;
   return invokeWithTimeout(
     "push_project",
-    { projectId, force },
+    { projectId, force, syncFunctions, acceptRenames, transactionMode, ignoreDivergence },
     TIMEOUT,
     "Push timed out after 3 minutes. Please check your internet connection or try again.",
   );
 }
 
+export interface BulkOperationResult {
+  project_id: string;
+  name: string;
+  status: "success" | "skipped" | "error";
+  error: string | null;
+}
+
+// Unlinked projects are reported as skipped; failures don't stop the rest
+export async function pushAllProjects(force?: boolean): Promise<BulkOperationResult[]> {
+  return invoke("push_all_projects", { force });
+}
+
+export async function pullAllProjects(): Promise<BulkOperationResult[]> {
+  return invoke("pull_all_projects");
+}
+
 export async function getProjectDiff(
   projectId: string,
 ): Promise<import("./types").DiffResponse> {
@@ -285,8 +634,20 @@ export async function getPullDiff(
   return invoke("get_pull_diff", { projectId });
 }
 
-export async function splitSchema(projectId: string): Promise<string[]> {
-  return invoke("split_schema", { projectId });
+export async function splitSchema(projectId: string, dryRun?: boolean): Promise<string[]> {
+  return invoke("split_schema", { projectId, dryRun });
+}
+
+export async function previewObjectSql(
+  projectId: string,
+  objectKind: string,
+  objectName: string,
+): Promise<string> {
+  return invoke("preview_object_sql", { projectId, objectKind, objectName });
+}
+
+export async function analyzeSchemaFile(path: string): Promise<SchemaAnalysis> {
+  return invoke("analyze_schema_file", { path });
 }
 
 // Supabase Logs API
@@ -304,30 +665,78 @@ export async function querySupabaseLogs(
   });
 }
 
+export type LogSeverity = "error" | "warning" | "info";
+
+export interface LogQueryFilter {
+  severity?: LogSeverity;
+  // Case-insensitive substring of the event message
+  search?: string;
+}
+
 export async function getEdgeFunctionLogs(
   projectId: string,
   functionName?: string,
   minutes?: number,
+  filter: LogQueryFilter = {},
 ): Promise<unknown> {
   return invoke("get_edge_function_logs", {
     projectId,
     functionName,
     minutes,
+    ...filter,
   });
 }
 
 export async function getPostgresLogs(
   projectId: string,
   minutes?: number,
+  filter: LogQueryFilter = {},
 ): Promise<unknown> {
-  return invoke("get_postgres_logs", { projectId, minutes });
+  return invoke("get_postgres_logs", { projectId, minutes, ...filter });
 }
 
 export async function getAuthLogs(
   projectId: string,
   minutes?: number,
+  filter: LogQueryFilter = {},
 ): Promise<unknown> {
-  return invoke("get_auth_logs", { projectId, minutes });
+  return invoke("get_auth_logs", { projectId, minutes, ...filter });
+}
+
+export type RemoteLogSource = "edge" | "postgres" | "auth";
+
+export interface LogFilters extends LogQueryFilter {
+  function_name?: string;
+}
+
+export interface LogStreamEvent {
+  project_id: string;
+  source: RemoteLogSource;
+  entries: unknown[];
+}
+
+// Polls the source in the background; only unseen entries are emitted
+export async function streamLogs(
+  projectId: string,
+  source: RemoteLogSource,
+  filters?: LogFilters,
+): Promise<void> {
+  return invoke("stream_logs", { projectId, source, filters });
+}
+
+export async function stopLogStream(
+  projectId: string,
+  source?: RemoteLogSource,
+): Promise<void> {
+  return invoke("stop_log_stream", { projectId, source });
+}
+
+export function onLogStream(
+  callback: (event: LogStreamEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<LogStreamEvent>("log-stream", (event) =>
+    callback(event.payload),
+  );
 }
 
 // Templates API
@@ -346,9 +755,24 @@ export async function copyTemplate(
   return invoke("copy_template", { templateName, targetPath });
 }
 
+export type EdgeFunctionTemplate = "hello-world" | "webhook" | "cron";
+
+// Scaffold supabase/functions/<slug>/index.ts; returns the function directory
+export async function createEdgeFunction(
+  projectId: string,
+  slug: string,
+  template: EdgeFunctionTemplate,
+  denoJson?: boolean,
+): Promise<string> {
+  return invoke("create_edge_function", { projectId, slug, template, denoJson });
+}
+
 // Seed API
-export async function runSeeds(projectId: string): Promise<string> {
-  return invoke("run_seeds", { projectId });
+export async function runSeeds(
+  projectId: string,
+  idempotent?: boolean,
+): Promise<string> {
+  return invoke("run_seeds", { projectId, idempotent });
 }
 
 export async function getSeedContent(projectId: string): Promise<string> {
@@ -387,3 +811,43 @@ export async function addSidebarGroup(
 ): Promise<void> {
   return invoke("add_sidebar_group", { projectId, group });
 }
+
+export interface SpecIssue {
+  code: "invalid_structure" | "duplicate_id" | "dangling_reference" | "invalid_icon";
+  path: string;
+  message: string;
+}
+
+// Empty when the spec is valid. Writes fail with INVALID_SPEC: plus these issues.
+export async function validateSidebarSpec(
+  spec: import("./specs/types").SidebarSpec,
+): Promise<SpecIssue[]> {
+  return invoke("validate_sidebar_spec", { spec });
+}
+
+// groupId is only needed when several groups have an item with this id
+export async function removeSidebarItem(
+  projectId: string,
+  itemId: string,
+  groupId?: string,
+): Promise<void> {
+  return invoke("remove_sidebar_item", { projectId, itemId, groupId });
+}
+
+// Items of the removed group move to moveItemsTo, or are removed with it
+export async function removeSidebarGroup(
+  projectId: string,
+  groupId: string,
+  moveItemsTo?: string,
+): Promise<void> {
+  return invoke("remove_sidebar_group", { projectId, groupId, moveItemsTo });
+}
+
+// Reorders groups, or the items of groupId when given
+export async function reorderSidebar(
+  projectId: string,
+  orderedIds: string[],
+  groupId?: string,
+): Promise<void> {
+  return invoke("reorder_sidebar", { projectId, orderedIds, groupId });
+}
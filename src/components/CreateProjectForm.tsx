@@ -1,4 +1,5 @@
 import { useForm } from "@tanstack/react-form";
+import { ask } from "@tauri-apps/plugin-dialog";
 import {
   isPermissionGranted,
   requestPermission,
@@ -163,6 +164,23 @@ export function CreateProjectForm({
     },
     onSubmit: async ({ value }) => {
       try {
+        const warnings = await api.checkProjectFolder(
+          value.localPath.trim(),
+          mode !== "create",
+        );
+        if (warnings.length > 0) {
+          const confirmed = await ask(
+            `${warnings.map((w) => `${w.message}\n${w.path}`).join("\n\n")}\n\nContinue anyway?`,
+            {
+              title: mode === "create" ? "Folder Not Empty" : "Existing Schema",
+              kind: "warning",
+            },
+          );
+          if (!confirmed) return;
+        }
+        // Only lets the backend overwrite what the user was just warned about
+        const confirmed = warnings.length > 0;
+
         if (mode === "create") {
           if (isEmptyFolder && value.template !== "none") {
             await api.copyTemplate(value.template, value.localPath.trim());
@@ -176,6 +194,8 @@ export function CreateProjectForm({
             value.orgId,
             value.generateTypescript,
             value.generateTypescript ? value.typescriptOutputPath : undefined,
+            undefined,
+            confirmed,
           );
         } else {
           const project = remoteProjects.find((p) => p.id === value.projectId);
@@ -189,6 +209,8 @@ export function CreateProjectForm({
             undefined,
             value.generateTypescript,
             value.generateTypescript ? value.typescriptOutputPath : undefined,
+            undefined,
+            confirmed,
           );
         }
 
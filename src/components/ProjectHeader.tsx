@@ -15,14 +15,20 @@ import {
 } from "lucide-react";
 import { useEffect, useState } from "react";
 import * as api from "../api";
-import type { Project } from "../types";
+import type { NameQualification, Project } from "../types";
 import { notify } from "../utils/notification";
 import { Button } from "./ui/button";
 import {
   DropdownMenu,
+  DropdownMenuCheckboxItem,
   DropdownMenuContent,
   DropdownMenuItem,
+  DropdownMenuRadioGroup,
+  DropdownMenuRadioItem,
   DropdownMenuSeparator,
+  DropdownMenuSub,
+  DropdownMenuSubContent,
+  DropdownMenuSubTrigger,
   DropdownMenuTrigger,
 } from "./ui/dropdown-menu";
 
@@ -119,6 +125,32 @@ export function ProjectHeader({
     }
   };
 
+  const updateSettings = async (changes: Partial<Project>) => {
+    try {
+      await api.updateProject({ ...project, ...changes });
+      onUpdate();
+    } catch (err) {
+      console.error("Failed to update project settings:", err);
+      notify("Error", "Failed to update project settings: " + String(err));
+    }
+  };
+
+  const toggleSafeMode = async (enabled: boolean) => {
+    if (!enabled) {
+      const confirmed = await ask(
+        "Turn off safe mode? Pushes will then be able to drop tables, columns and other objects that only exist remotely.",
+        {
+          title: "Safe Mode",
+          kind: "warning",
+          okLabel: "Turn Off",
+          cancelLabel: "Cancel",
+        },
+      );
+      if (!confirmed) return;
+    }
+    await updateSettings({ safe_mode: enabled });
+  };
+
   const handleOpenFolder = async () => {
     try {
       await api.revealInFinder(project.local_path);
@@ -276,6 +308,46 @@ export function ProjectHeader({
               Split Schema
             </DropdownMenuItem>
             <DropdownMenuSeparator />
+            <DropdownMenuCheckboxItem
+              checked={project.safe_mode}
+              onCheckedChange={(checked) => toggleSafeMode(checked === true)}
+              title="Push additively and refuse destructive changes, even when forced"
+            >
+              Safe Mode
+            </DropdownMenuCheckboxItem>
+            <DropdownMenuCheckboxItem
+              checked={project.sync_functions}
+              onCheckedChange={(checked) =>
+                updateSettings({ sync_functions: checked === true })
+              }
+              title="Pull and push edge functions along with the schema"
+            >
+              Sync Edge Functions
+            </DropdownMenuCheckboxItem>
+            <DropdownMenuSub>
+              <DropdownMenuSubTrigger>Name Qualification</DropdownMenuSubTrigger>
+              <DropdownMenuSubContent>
+                <DropdownMenuRadioGroup
+                  value={project.name_qualification}
+                  onValueChange={(value) =>
+                    updateSettings({
+                      name_qualification: value as NameQualification,
+                    })
+                  }
+                >
+                  <DropdownMenuRadioItem value="as_is">
+                    As generated
+                  </DropdownMenuRadioItem>
+                  <DropdownMenuRadioItem value="always">
+                    Always include schema
+                  </DropdownMenuRadioItem>
+                  <DropdownMenuRadioItem value="omit_public">
+                    Omit public schema
+                  </DropdownMenuRadioItem>
+                </DropdownMenuRadioGroup>
+              </DropdownMenuSubContent>
+            </DropdownMenuSub>
+            <DropdownMenuSeparator />
             <DropdownMenuItem variant="destructive" onClick={handleDelete}>
               <Trash2 size={16} strokeWidth={1} />
               Delete Project
@@ -68,10 +68,17 @@ export function ProjectItem({ project, onUpdate, onDelete }: ProjectItemProps) {
     }
   };
 
-  const handlePush = async () => {
-    setIsLoading(true);
+  // Confirming a divergence or destructive change retries with just that check skipped
+  const push = async (force: boolean, ignoreDivergence: boolean) => {
     try {
-      const result = await api.pushProject(project.id);
+      const result = await api.pushProject(
+        project.id,
+        force,
+        undefined,
+        undefined,
+        undefined,
+        ignoreDivergence,
+      );
       const hasChanges =
         result.migration_sql.trim() !== "" ||
         result.edge_function_results.length > 0;
@@ -106,18 +113,34 @@ export function ProjectItem({ project, onUpdate, onDelete }: ProjectItemProps) {
         );
 
         if (confirmed) {
-          try {
-            await api.pushProject(project.id, true);
-            notify("Success", "Schema changes pushed successfully");
-          } catch (retryErr) {
-            console.error("Failed to push project (forced):", retryErr);
-            notify("Error", "Failed to push project: " + String(retryErr));
-          }
+          await push(true, ignoreDivergence);
+        }
+      } else if (errorMsg.startsWith("REMOTE_DIVERGED:")) {
+        const summary = errorMsg.replace("REMOTE_DIVERGED:", "");
+        const confirmed = await ask(
+          `The remote schema changed since your last pull:\n\n${summary}\n\nPushing will overwrite these changes. Pull first to merge them, or push anyway?`,
+          {
+            title: "Remote Schema Changed",
+            kind: "warning",
+            okLabel: "Push Anyway",
+            cancelLabel: "Cancel Push",
+          },
+        );
+
+        if (confirmed) {
+          await push(force, true);
         }
       } else {
         console.error("Failed to push project:", err);
         notify("Error", "Failed to push project: " + String(err));
       }
+    }
+  };
+
+  const handlePush = async () => {
+    setIsLoading(true);
+    try {
+      await push(false, false);
     } finally {
       setIsLoading(false);
     }
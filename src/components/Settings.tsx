@@ -3,6 +3,13 @@ import { zodValidator } from "@tanstack/zod-form-adapter";
 import { useEffect, useState } from "react";
 import { z } from "zod";
 import * as api from "../api";
+import {
+  AI_SETTINGS_KEY,
+  DEFAULT_AI_SETTINGS,
+  load,
+  save,
+  type AiSettings,
+} from "../utils/store";
 import { Button } from "./ui/button";
 import { Field, FieldDescription, FieldGroup, FieldLabel } from "./ui/field";
 import { Input } from "./ui/input";
@@ -20,6 +27,7 @@ export function Settings() {
 
       <AccessTokenForm />
       <OpenAIKeyForm />
+      <AiModelForm />
     </div>
   );
 }
@@ -299,3 +307,75 @@ function OpenAIKeyForm() {
     </div>
   );
 }
+
+function AiModelForm() {
+  const [settings, setSettings] = useState<AiSettings>(DEFAULT_AI_SETTINGS);
+  const [status, setStatus] = useState<string | null>(null);
+  const [isTesting, setIsTesting] = useState(false);
+
+  useEffect(() => {
+    load<AiSettings>(AI_SETTINGS_KEY).then((stored) => {
+      if (stored) setSettings({ ...DEFAULT_AI_SETTINGS, ...stored });
+    });
+  }, []);
+
+  const handleSave = async () => {
+    await save(AI_SETTINGS_KEY, {
+      model: settings.model.trim(),
+      base_url: settings.base_url.trim(),
+    });
+    setStatus("Saved");
+  };
+
+  const handleTest = async () => {
+    setIsTesting(true);
+    setStatus(null);
+    try {
+      await handleSave();
+      const valid = await api.validateOpenAiKey();
+      setStatus(valid ? "Connection OK" : "The endpoint rejected the API key");
+    } catch (err) {
+      setStatus(String(err));
+    } finally {
+      setIsTesting(false);
+    }
+  };
+
+  return (
+    <div className="space-y-4">
+      <FieldGroup>
+        <Field>
+          <FieldLabel>Model</FieldLabel>
+          <Input
+            placeholder={DEFAULT_AI_SETTINGS.model}
+            value={settings.model}
+            onChange={(e) => setSettings({ ...settings, model: e.target.value })}
+          />
+        </Field>
+        <Field>
+          <FieldLabel>API Base URL</FieldLabel>
+          <Input
+            placeholder={DEFAULT_AI_SETTINGS.base_url}
+            value={settings.base_url}
+            onChange={(e) =>
+              setSettings({ ...settings, base_url: e.target.value })
+            }
+          />
+          <FieldDescription>
+            Any OpenAI-compatible endpoint, e.g. Azure OpenAI or a local
+            Ollama / LM Studio server (http://localhost:11434/v1).
+          </FieldDescription>
+        </Field>
+        <div className="flex gap-2 items-center">
+          <Button onClick={handleSave}>Save</Button>
+          <Button variant="outline" onClick={handleTest} disabled={isTesting}>
+            {isTesting ? "Testing..." : "Test connection"}
+          </Button>
+          {status && (
+            <span className="text-sm text-muted-foreground">{status}</span>
+          )}
+        </div>
+      </FieldGroup>
+    </div>
+  );
+}
@@ -537,15 +537,29 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
 
             try {
               // Use full schema introspection for AI context
-              const convertedSql = await api.convertWithAi(
+              const converted = await api.convertWithAi(
                 projectId,
                 sourceToRun.value,
               );
 
               // Update the Source in the editor with the converted version
-              sourceToRun = { ...sourceToRun, value: convertedSql };
+              sourceToRun = { ...sourceToRun, value: converted.sql };
               updateState({ source: sourceToRun });
               setIsProcessingWithAI(false);
+
+              // Never auto-run flagged statements; leave them for the user to review
+              if (converted.flagged.length > 0) {
+                updateState({
+                  error: `Review AI-generated SQL before running:\n${converted.flagged
+                    .map((f) => `Line ${f.line}: ${f.reason}`)
+                    .join("\n")}`,
+                  results: [],
+                  originalResults: [],
+                  displayColumns: [],
+                  queryMetadata: null,
+                });
+                return false;
+              }
               setLoadingQueries((prev) => ({ ...prev, [loadingKey]: true }));
             } catch (aiError) {
               // AI conversion failed
@@ -1098,7 +1112,7 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
                             queryState.source.value,
                             errorMsg,
                           )
-                          .then((convertedSql) => {
+                          .then(({ sql: convertedSql, flagged }) => {
                             setTabs((prev) =>
                               prev.map((t) => {
                                 if (t.id !== activeTabId || !t.queryStates)
@@ -1110,13 +1124,20 @@ export function SqlEditor({ projectId }: SqlEditorProps) {
                                     ...newStates[queryIndex].source,
                                     value: convertedSql,
                                   },
-                                  error: null,
+                                  error:
+                                    flagged.length > 0
+                                      ? `Review AI-generated SQL before running:\n${flagged
+                                          .map((f) => `Line ${f.line}: ${f.reason}`)
+                                          .join("\n")}`
+                                      : null,
                                 };
                                 return { ...t, queryStates: newStates };
                               }),
                             );
-                            // Re-run the query with fixed SQL
-                            runQuery(convertedSql, queryIndex);
+                            // Re-run the query with fixed SQL unless it needs review
+                            if (flagged.length === 0) {
+                              runQuery(convertedSql, queryIndex);
+                            }
                           })
                           .catch((err) => {
                             setTabs((prev) =>
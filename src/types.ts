@@ -7,6 +7,59 @@ export interface Project {
   created_at: string;
   updated_at: string;
   is_watching: boolean;
+  // Outcome of the auto-pull when the project was linked
+  last_pull_status: PullStatus | null;
+  last_pull_at: string | null;
+  last_push_at: string | null;
+  // Whether pull/push also sync edge functions unless a run says otherwise
+  sync_functions: boolean;
+  // Pushes never drop anything until the user turns this off
+  safe_mode: boolean;
+  // Roles whose remote grants only count when the local schema grants to them too
+  ignored_grant_roles: string[];
+  // How generated migrations qualify object names
+  name_qualification: NameQualification;
+  // Seconds one introspection query may run before it's reported as stuck (8 when null)
+  introspection_query_timeout_secs: number | null;
+}
+
+// "as_is" keeps names as generated, "always" adds the schema (public included),
+// "omit_public" drops the `public.` prefix
+export type NameQualification = "as_is" | "always" | "omit_public";
+
+export interface ProjectStatus {
+  linked: boolean;
+  watching: boolean;
+  // null when the remote couldn't be compared with the last pull/push
+  drifted: boolean | null;
+  last_pull: string | null;
+  last_push: string | null;
+  pending_function_changes: number;
+}
+
+// Object counts and sorted names per kind (tables, enums, indexes, ...)
+export interface SchemaSummary {
+  counts: Record<string, number>;
+  objects: Record<string, string[]>;
+}
+
+// A statement the schema parser skipped
+export interface UnparsedStatement {
+  file: string;
+  text: string;
+  reason: string;
+}
+
+export interface SchemaAnalysis {
+  summary: SchemaSummary;
+  unrecognized: UnparsedStatement[];
+}
+
+export interface PullStatus {
+  schema_pulled: boolean;
+  functions_pulled: boolean;
+  errors: string[];
+  attempted_at: string | null;
 }
 
 export type LogLevel = "info" | "warning" | "error" | "success";
@@ -21,6 +74,41 @@ export interface LogEntry {
   message: string;
   details: string | null;
   timestamp: string;
+  // Step of a push/pull, when the entry belongs to one
+  phase?: LogPhase;
+  // Structured diff attached to the diff step of a push
+  report?: DiffReport;
+}
+
+export interface PreflightWarning {
+  code: "existing_supabase_dir" | "existing_schema";
+  message: string;
+  path: string;
+}
+
+export type LogPhase = "introspect" | "diff" | "generate" | "execute";
+
+export interface DiffReport {
+  summary: string;
+  migration_sql: string;
+  is_destructive: boolean;
+  tables_to_create: string[];
+  tables_to_drop: string[];
+  tables_to_alter: string[];
+  column_rename_candidates: ColumnRenameCandidate[];
+}
+
+export interface ColumnRenameCandidate {
+  table: string;
+  from: string;
+  to: string;
+}
+
+// An object that references another one, e.g. a view selecting from a table
+export interface Dependent {
+  kind: string;
+  name: string;
+  reason: string;
 }
 
 export type FileChangeType = "schema" | "edge_function" | "migration" | "other";
@@ -100,3 +188,14 @@ export interface PushResponse {
   migration_sql: string;
   edge_function_results: EdgeFunctionDeploymentResult[];
 }
+
+export type FileOperation =
+  | { op: "create_dir"; path: string }
+  | { op: "write"; path: string; bytes: number }
+  | { op: "remove"; path: string };
+
+// Returned by create_project: the project and the file operations it performed
+// (or, in a dry run, would perform)
+export interface CreatedProject extends Project {
+  operations: FileOperation[];
+}
@@ -37,6 +37,19 @@ export async function load<T>(key: string): Promise<T | null> {
   return value ?? null;
 }
 
+// Model and OpenAI-compatible endpoint used for AI conversion (read by the backend too)
+export const AI_SETTINGS_KEY = "ai_settings";
+
+export interface AiSettings {
+  model: string;
+  base_url: string;
+}
+
+export const DEFAULT_AI_SETTINGS: AiSettings = {
+  model: "gpt-4o-mini",
+  base_url: "https://api.openai.com/v1",
+};
+
 // Project-specific helpers
 export const PROJECT_KEYS = {
   activeTab: (projectId: string) => `project:${projectId}:activeTab`,